@@ -0,0 +1,2 @@
+-- Add free-form profile metadata to users
+ALTER TABLE users ADD COLUMN IF NOT EXISTS metadata JSONB NOT NULL DEFAULT '{}'::jsonb;
@@ -0,0 +1,2 @@
+-- Track when a user last made an authenticated request
+ALTER TABLE users ADD COLUMN IF NOT EXISTS last_active_at TIMESTAMP WITH TIME ZONE;
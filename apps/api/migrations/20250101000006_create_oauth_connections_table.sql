@@ -0,0 +1,17 @@
+-- Accounts created through social login have no password
+ALTER TABLE users ALTER COLUMN password_hash DROP NOT NULL;
+
+-- Create oauth_connections table
+CREATE TABLE IF NOT EXISTS oauth_connections (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider VARCHAR(50) NOT NULL,
+    provider_user_id VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CONSTRAINT oauth_connections_user_provider UNIQUE (user_id, provider),
+    CONSTRAINT oauth_connections_provider_account UNIQUE (provider, provider_user_id)
+);
+
+-- Create index on user_id for per-user listings
+CREATE INDEX IF NOT EXISTS idx_oauth_connections_user_id ON oauth_connections(user_id);
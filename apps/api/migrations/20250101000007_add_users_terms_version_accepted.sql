@@ -0,0 +1,2 @@
+-- Record which terms-of-service version each user last accepted
+ALTER TABLE users ADD COLUMN IF NOT EXISTS terms_version_accepted VARCHAR(50);
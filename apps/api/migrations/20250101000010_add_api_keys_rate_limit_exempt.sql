@@ -0,0 +1,2 @@
+-- Keys flagged by an admin skip the global rate limiter (trusted backend services)
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS rate_limit_exempt BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+-- A user's active API keys must have distinct names; revoked keys free their name
+CREATE UNIQUE INDEX IF NOT EXISTS api_keys_user_id_name_key
+    ON api_keys (user_id, name)
+    WHERE revoked_at IS NULL;
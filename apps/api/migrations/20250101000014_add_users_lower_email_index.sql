@@ -0,0 +1,3 @@
+-- Case-insensitive email search for the admin user list; text_pattern_ops
+-- lets prefix matches such as lower(email) LIKE 'ann%' use the index
+CREATE INDEX IF NOT EXISTS idx_users_email_lower ON users (lower(email) text_pattern_ops);
@@ -0,0 +1,5 @@
+-- Presigned uploads are recorded before the client sends the object and stay
+-- pending until confirmed; their bytes never pass through the API, so they
+-- carry no content hash
+ALTER TABLE files ADD COLUMN IF NOT EXISTS pending BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE files ALTER COLUMN content_hash DROP NOT NULL;
@@ -0,0 +1,3 @@
+-- Key of the generated thumbnail for image uploads; NULL for other files and
+-- for images whose thumbnail could not be generated
+ALTER TABLE files ADD COLUMN IF NOT EXISTS thumbnail_key TEXT;
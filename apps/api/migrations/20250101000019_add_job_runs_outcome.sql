@@ -0,0 +1,3 @@
+-- Rows a job run deleted or counted, and why a failed run failed
+ALTER TABLE job_runs ADD COLUMN IF NOT EXISTS rows_affected BIGINT;
+ALTER TABLE job_runs ADD COLUMN IF NOT EXISTS error_message TEXT;
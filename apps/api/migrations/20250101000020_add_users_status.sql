@@ -0,0 +1,3 @@
+-- Suspended accounts keep their data but can neither sign in nor use issued tokens
+ALTER TABLE users ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'active';
+ALTER TABLE users ADD CONSTRAINT status_values CHECK (status IN ('active', 'suspended'));
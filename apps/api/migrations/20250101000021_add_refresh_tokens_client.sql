@@ -0,0 +1,4 @@
+-- Coarse description of the client a session was started from, shown in the
+-- user's session list; copied forward to each rotated token
+ALTER TABLE refresh_tokens ADD COLUMN IF NOT EXISTS user_agent VARCHAR(255);
+ALTER TABLE refresh_tokens ADD COLUMN IF NOT EXISTS client_network VARCHAR(64);
@@ -0,0 +1,5 @@
+-- A soft-deleted account keeps its row until the purge, so only live
+-- accounts hold their email; same name, so violations still map to the email
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_key ON users(email) WHERE deleted_at IS NULL;
@@ -1,15 +1,42 @@
+use ipnet::IpNet;
 use serde::Deserialize;
-use std::env;
+use std::net::IpAddr;
+
+mod source;
+
+pub use source::ConfigSource;
+use source::Settings;
+
+/// Shortest `JWT_SECRET` accepted in production, in bytes
+pub const MIN_PRODUCTION_JWT_SECRET_BYTES: usize = 32;
+
+/// Example secrets from the env templates and deployment docs
+const PLACEHOLDER_JWT_SECRETS: [&str; 6] = [
+    "your-secret-key",
+    "your-secret-key-here",
+    "your-super-secret-key-minimum-32-characters-long",
+    "your-super-secret-key-minimum-32-characters-long-replace-this",
+    "your-super-secret-jwt-key-change-this-in-production",
+    "test_secret_key_for_integration_tests_do_not_use_in_production",
+];
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub jwt: JwtConfig,
+    /// Admin created on first boot when no admin exists yet
+    pub bootstrap_admin: Option<BootstrapAdminConfig>,
+    /// Sign in with Google; enabled when `GOOGLE_CLIENT_ID` is set
+    pub google_oauth: Option<GoogleOAuthConfig>,
     #[cfg(feature = "ai")]
     pub ai: AiConfig,
     #[cfg(feature = "storage")]
     pub storage: StorageConfig,
+    #[cfg(feature = "jobs")]
+    pub jobs: JobsConfig,
+    #[cfg(feature = "websocket")]
+    pub websocket: WebSocketConfig,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -18,15 +45,101 @@ pub struct ServerConfig {
     pub host: String,
     pub cors_origins: Vec<String>,
     pub environment: Environment,
+    /// Concurrent requests allowed on DB-heavy routes before queuing
+    pub heavy_route_concurrency: usize,
+    /// How long a queued request on a DB-heavy route waits before a 503
+    pub heavy_route_queue_timeout_ms: u64,
+    /// Serve the GraphiQL playground on GET /graphql (off by default in production)
+    pub graphiql_enabled: bool,
+    /// GraphQL queries nested deeper than this are rejected before execution
+    pub graphql_max_depth: usize,
+    /// GraphQL queries whose complexity (one per field) exceeds this are rejected
+    pub graphql_max_complexity: usize,
+    /// Honour `?pretty=1` by indenting JSON responses (off by default in production)
+    pub pretty_json_enabled: bool,
+    /// Render every error as RFC 7807 problem+json, not only for requests
+    /// that send `Accept: application/problem+json`
+    pub problem_json_default: bool,
+    /// Strict-Transport-Security max-age, sent only in production; 0 disables
+    pub hsts_max_age_secs: u64,
+    /// Content-Security-Policy for API responses; unset disables
+    pub content_security_policy: Option<String>,
+    /// Path prefixes served without a Content-Security-Policy because their
+    /// pages need inline scripts (Swagger UI, GraphiQL)
+    pub csp_exempt_paths: Vec<String>,
+    /// Largest request body accepted on routes without their own limit; bigger
+    /// bodies get a 413
+    pub max_request_body_bytes: usize,
+    /// Responses smaller than this many bytes are never compressed
+    pub compression_min_bytes: u16,
+    /// Requests per second allowed through the global rate limiter
+    pub rate_limit_per_second: u32,
+    /// Path prefixes that bypass rate limiting (health checks, metrics scraping)
+    pub rate_limit_exempt_paths: Vec<String>,
+    /// Peer addresses/CIDRs that bypass rate limiting (internal monitors)
+    pub rate_limit_trusted_networks: Vec<IpNet>,
+    /// Reverse proxies whose `X-Forwarded-For` identifies the client for rate
+    /// limiting, the signup cap and session labels
+    pub rate_limit_trusted_proxies: Vec<IpNet>,
+    /// Per-client requests per minute on path prefixes that need a stricter
+    /// limit than the default (login and signup)
+    pub rate_limit_routes: Vec<(String, u32)>,
+    /// How long shutdown waits for in-flight requests before abandoning them
+    pub shutdown_grace_secs: u64,
+    /// Terms version users must have accepted to use protected routes; unset disables the gate
+    pub current_terms_version: Option<String>,
+    /// Fail requests that run more database queries than this (N+1 guard); unset disables
+    pub max_queries_per_request: Option<usize>,
+    /// Failed logins for one email from one client IP before that client is
+    /// locked out of it; 0 (the default) disables the lockout
+    pub login_max_failed_attempts: u32,
+    /// How long a locked-out client is refused; also the window failures are counted in
+    pub login_lockout_secs: u64,
+    /// 401/403 responses from one address within the window that raise a security event
+    pub auth_failure_alert_threshold: u32,
+    pub auth_failure_window_secs: u64,
+    /// Accounts one peer address may create within the signup window
+    pub signup_max_per_ip: u32,
+    pub signup_window_secs: u64,
+    /// Email domains refused at signup, loaded from DISPOSABLE_EMAIL_DOMAINS_FILE
+    pub disposable_email_domains: Vec<String>,
+    /// Length bounds for user display names (signup and profile updates)
+    pub name_min_length: usize,
+    pub name_max_length: usize,
+    /// Start with writes blocked (maintenance); admins can toggle it at runtime
+    pub read_only_mode: bool,
+    /// Require an admin token for `GET /api/v1/status`
+    pub status_admin_only: bool,
+    /// How long a POST's `Idempotency-Key` and stored response are kept
+    pub idempotency_ttl_hours: u64,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
     pub min_connections: u32,
     pub acquire_timeout_secs: u64,
     pub idle_timeout_secs: u64,
+    /// Startup connection attempts before giving up (see `connect_with_retry`)
+    pub connect_max_attempts: u32,
+    /// Longest wait between two startup connection attempts
+    pub connect_backoff_cap_secs: u64,
+}
+
+// Manual impl so the connection string password never ends up in debug logs
+impl std::fmt::Debug for DatabaseConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatabaseConfig")
+            .field("url", &crate::database::redact_database_url(&self.url))
+            .field("max_connections", &self.max_connections)
+            .field("min_connections", &self.min_connections)
+            .field("acquire_timeout_secs", &self.acquire_timeout_secs)
+            .field("idle_timeout_secs", &self.idle_timeout_secs)
+            .field("connect_max_attempts", &self.connect_max_attempts)
+            .field("connect_backoff_cap_secs", &self.connect_backoff_cap_secs)
+            .finish()
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -35,6 +148,49 @@ pub struct JwtConfig {
     pub access_token_expiry_hours: i64,
     pub refresh_token_expiry_days: i64,
     pub issuer: String,
+    /// Absolute session lifetime from login; refresh is refused after this regardless of token expiry
+    pub max_session_days: i64,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct BootstrapAdminConfig {
+    pub email: String,
+    pub password: String,
+}
+
+impl std::fmt::Debug for BootstrapAdminConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BootstrapAdminConfig")
+            .field("email", &self.email)
+            .field("password", &"****")
+            .finish()
+    }
+}
+
+/// OAuth 2.0 client registered in the Google Cloud console
+#[derive(Clone, Deserialize)]
+pub struct GoogleOAuthConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    /// Must match an authorized redirect URI, e.g.
+    /// `https://api.example.com/auth/oauth/google/callback`
+    pub redirect_uri: String,
+    pub auth_url: String,
+    pub token_url: String,
+    pub userinfo_url: String,
+}
+
+impl std::fmt::Debug for GoogleOAuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GoogleOAuthConfig")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"****")
+            .field("redirect_uri", &self.redirect_uri)
+            .field("auth_url", &self.auth_url)
+            .field("token_url", &self.token_url)
+            .field("userinfo_url", &self.userinfo_url)
+            .finish()
+    }
 }
 
 #[cfg(feature = "ai")]
@@ -45,6 +201,31 @@ pub struct AiConfig {
     pub default_model: String,
     pub max_tokens: u32,
     pub temperature: f32,
+    /// Most messages (history + new message) accepted in one chat request
+    pub max_messages: usize,
+    /// Most characters accepted across a whole chat request
+    pub max_conversation_chars: usize,
+    /// Most texts accepted in one batch embeddings request
+    pub max_embedding_batch_size: usize,
+    /// Attempts per provider call, including the first
+    pub max_attempts: u32,
+    /// First retry delay; doubles on every further attempt, plus jitter
+    pub retry_base_ms: u64,
+    /// Models `user` accounts may request; `None` leaves them unrestricted
+    pub allowed_models_user: Option<Vec<String>>,
+    /// Models moderators may request; `None` leaves them unrestricted
+    pub allowed_models_moderator: Option<Vec<String>>,
+    /// Models each provider accepts; `None` leaves that provider unrestricted
+    pub allowed_models_openai: Option<Vec<String>>,
+    pub allowed_models_anthropic: Option<Vec<String>>,
+    pub allowed_models_local: Option<Vec<String>>,
+    /// Lowercase requested model names (they are always trimmed)
+    pub normalize_model_names: bool,
+    /// Tokens one user may use per alert period before each alert fires
+    pub usage_alert_user_tokens: Vec<u64>,
+    /// Tokens all users together may use per alert period before each alert fires
+    pub usage_alert_global_tokens: Vec<u64>,
+    pub usage_alert_period_secs: u64,
 }
 
 #[cfg(feature = "storage")]
@@ -56,6 +237,55 @@ pub struct StorageConfig {
     pub s3_access_key: String,
     pub s3_secret_key: String,
     pub max_file_size_mb: u64,
+    /// Uploads processed at once; further uploads are rejected with 503
+    pub max_concurrent_uploads: usize,
+    /// Longest pause allowed between multipart chunks before a 408
+    pub upload_read_timeout_secs: u64,
+    /// Timeout for a single S3 call attempt
+    pub s3_timeout_secs: u64,
+    /// Attempts per S3 call, including the first
+    pub s3_max_attempts: u32,
+    /// First retry delay; doubles on every further attempt
+    pub s3_retry_base_ms: u64,
+    /// Reuse an owner's existing file when they upload identical content
+    pub dedupe_uploads: bool,
+    /// How long presigned upload and download URLs stay valid
+    pub presigned_url_expiry_secs: u64,
+}
+
+#[cfg(feature = "jobs")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobsConfig {
+    /// Days a soft-deleted row is kept before the purge job removes it
+    pub soft_delete_retention_days: i64,
+    pub scheduler: SchedulerConfig,
+}
+
+/// When each background job runs, as six-field cron expressions
+/// (`sec min hour day month weekday`, UTC)
+#[cfg(feature = "jobs")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct SchedulerConfig {
+    pub cleanup_cron: String,
+    pub metrics_cron: String,
+    pub purge_cron: String,
+}
+
+#[cfg(feature = "websocket")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebSocketConfig {
+    /// Rooms one connection may be in at once; further joins get an error message
+    pub max_rooms_per_connection: usize,
+    /// Outbound messages queued per connection; beyond this broadcasts are
+    /// dropped and a client that cannot take a direct reply is disconnected
+    pub outbound_queue_capacity: usize,
+    /// Accept upgrades without a token; such connections have no user id
+    pub allow_anonymous: bool,
+    /// Seconds between server pings
+    pub ping_interval_secs: u64,
+    /// Seconds without any frame from the client before it is disconnected;
+    /// should be a few ping intervals
+    pub idle_timeout_secs: u64,
 }
 
 #[derive(Debug, Clone, Deserialize, PartialEq)]
@@ -67,104 +297,226 @@ pub enum Environment {
 }
 
 impl Config {
+    /// Environment variables (including `.env`) take precedence over the
+    /// optional config file; see [`ConfigSource`]
     pub fn load() -> Result<Self, config::ConfigError> {
         // Load .env file
         dotenvy::dotenv().ok();
+        let source = ConfigSource::load()?;
+        let settings = Settings::new(&source);
+
+        let environment = settings.string_or("ENVIRONMENT", "development");
 
-        let environment = env::var("ENVIRONMENT")
-            .unwrap_or_else(|_| "development".to_string());
+        let environment = Self::parse_environment(&environment);
 
         let server = ServerConfig {
-            port: env::var("PORT")
-                .unwrap_or_else(|_| "3000".to_string())
-                .parse()
-                .expect("PORT must be a valid number"),
-            host: env::var("HOST")
-                .unwrap_or_else(|_| "0.0.0.0".to_string()),
+            port: settings.number("PORT", 3000),
+            host: settings.string_or("HOST", "0.0.0.0"),
             cors_origins: Self::parse_cors_origins(
-                &env::var("CORS_ORIGINS")
-                    .unwrap_or_else(|_| "http://localhost:3000".to_string())
+                &settings.string_or("CORS_ORIGINS", "http://localhost:3000"),
+            ),
+            environment: environment.clone(),
+            heavy_route_concurrency: settings.number("HEAVY_ROUTE_CONCURRENCY", 4),
+            heavy_route_queue_timeout_ms: settings.number("HEAVY_ROUTE_QUEUE_TIMEOUT_MS", 2000),
+            graphiql_enabled: settings.flag("GRAPHIQL_ENABLED", environment != Environment::Production),
+            graphql_max_depth: settings.number("GRAPHQL_MAX_DEPTH", 10),
+            graphql_max_complexity: settings.number("GRAPHQL_MAX_COMPLEXITY", 200),
+            pretty_json_enabled: settings.flag("PRETTY_JSON_ENABLED", environment == Environment::Development),
+            problem_json_default: settings.flag("PROBLEM_JSON_DEFAULT", false),
+            hsts_max_age_secs: settings.number("HSTS_MAX_AGE_SECS", 31536000),
+            content_security_policy: match settings.optional("CONTENT_SECURITY_POLICY") {
+                Some(policy) if policy.trim().is_empty() => None,
+                Some(policy) => Some(policy),
+                None => Some("default-src 'none'; frame-ancestors 'none'".to_string()),
+            },
+            csp_exempt_paths: Self::parse_list(
+                &settings.string_or("CSP_EXEMPT_PATHS", "/swagger-ui,/graphql"),
             ),
-            environment: Self::parse_environment(&environment),
+            max_request_body_bytes: settings.number("MAX_REQUEST_BODY_BYTES", 1048576),
+            compression_min_bytes: settings.number("COMPRESSION_MIN_BYTES", 1024),
+            rate_limit_per_second: settings.number("RATE_LIMIT_PER_SECOND", 100),
+            rate_limit_exempt_paths: Self::parse_list(
+                &settings.string_or("RATE_LIMIT_EXEMPT_PATHS", "/health,/ready,/metrics"),
+            ),
+            rate_limit_trusted_networks: settings.check(Self::parse_networks(
+                &settings.optional("RATE_LIMIT_TRUSTED_IPS").unwrap_or_default(),
+                "RATE_LIMIT_TRUSTED_IPS",
+            )),
+            rate_limit_trusted_proxies: settings.check(Self::parse_networks(
+                &settings.optional("RATE_LIMIT_TRUSTED_PROXIES").unwrap_or_default(),
+                "RATE_LIMIT_TRUSTED_PROXIES",
+            )),
+            rate_limit_routes: settings.check(Self::parse_route_limits(&settings.string_or(
+                "RATE_LIMIT_ROUTES",
+                "/auth/login=10,/auth/register=5",
+            ))),
+            shutdown_grace_secs: settings.number("SHUTDOWN_GRACE_SECS", 30),
+            current_terms_version: settings.optional("CURRENT_TERMS_VERSION")
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty()),
+            max_queries_per_request: settings.optional_number("MAX_QUERIES_PER_REQUEST"),
+            login_max_failed_attempts: settings.number("LOGIN_MAX_FAILED_ATTEMPTS", 0),
+            login_lockout_secs: settings.number("LOGIN_LOCKOUT_SECS", 900),
+            auth_failure_alert_threshold: settings.number("AUTH_FAILURE_ALERT_THRESHOLD", 20),
+            auth_failure_window_secs: settings.number("AUTH_FAILURE_WINDOW_SECS", 60),
+            signup_max_per_ip: settings.number("SIGNUP_MAX_PER_IP", 5),
+            signup_window_secs: settings.number("SIGNUP_WINDOW_SECS", 3600),
+            disposable_email_domains: settings
+                .optional("DISPOSABLE_EMAIL_DOMAINS_FILE")
+                .map(|path| match std::fs::read_to_string(&path) {
+                    Ok(contents) => Self::parse_domain_list(&contents),
+                    Err(e) => {
+                        settings.error(format!(
+                            "DISPOSABLE_EMAIL_DOMAINS_FILE must point to a readable file, got '{}' ({})",
+                            path, e
+                        ));
+                        Vec::new()
+                    }
+                })
+                .unwrap_or_default(),
+            name_min_length: settings.number("NAME_MIN_LENGTH", 2),
+            name_max_length: settings.number("NAME_MAX_LENGTH", 100),
+            read_only_mode: settings.flag("READ_ONLY_MODE", false),
+            status_admin_only: settings.flag("STATUS_ADMIN_ONLY", true),
+            idempotency_ttl_hours: settings.number("IDEMPOTENCY_TTL_HOURS", 24),
         };
 
         let database = DatabaseConfig {
-            url: env::var("DATABASE_URL")
-                .expect("DATABASE_URL must be set"),
-            max_connections: env::var("DB_MAX_CONNECTIONS")
-                .unwrap_or_else(|_| "10".to_string())
-                .parse()
-                .expect("DB_MAX_CONNECTIONS must be a valid number"),
-            min_connections: env::var("DB_MIN_CONNECTIONS")
-                .unwrap_or_else(|_| "2".to_string())
-                .parse()
-                .expect("DB_MIN_CONNECTIONS must be a valid number"),
-            acquire_timeout_secs: env::var("DB_ACQUIRE_TIMEOUT_SECS")
-                .unwrap_or_else(|_| "30".to_string())
-                .parse()
-                .expect("DB_ACQUIRE_TIMEOUT_SECS must be a valid number"),
-            idle_timeout_secs: env::var("DB_IDLE_TIMEOUT_SECS")
-                .unwrap_or_else(|_| "600".to_string())
-                .parse()
-                .expect("DB_IDLE_TIMEOUT_SECS must be a valid number"),
+            url: settings.required("DATABASE_URL"),
+            max_connections: settings.number("DB_MAX_CONNECTIONS", 10),
+            min_connections: settings.number("DB_MIN_CONNECTIONS", 2),
+            acquire_timeout_secs: settings.number("DB_ACQUIRE_TIMEOUT_SECS", 30),
+            idle_timeout_secs: settings.number("DB_IDLE_TIMEOUT_SECS", 600),
+            connect_max_attempts: settings.number("DB_CONNECT_MAX_ATTEMPTS", 10),
+            connect_backoff_cap_secs: settings.number("DB_CONNECT_BACKOFF_CAP_SECS", 30),
         };
 
         let jwt = JwtConfig {
-            secret: env::var("JWT_SECRET")
-                .expect("JWT_SECRET must be set"),
-            access_token_expiry_hours: env::var("JWT_ACCESS_TOKEN_EXPIRY_HOURS")
-                .unwrap_or_else(|_| "24".to_string())
-                .parse()
-                .expect("JWT_ACCESS_TOKEN_EXPIRY_HOURS must be a valid number"),
-            refresh_token_expiry_days: env::var("JWT_REFRESH_TOKEN_EXPIRY_DAYS")
-                .unwrap_or_else(|_| "30".to_string())
-                .parse()
-                .expect("JWT_REFRESH_TOKEN_EXPIRY_DAYS must be a valid number"),
-            issuer: env::var("JWT_ISSUER")
-                .unwrap_or_else(|_| "vibe-api".to_string()),
+            secret: settings.required("JWT_SECRET"),
+            access_token_expiry_hours: settings.number("JWT_ACCESS_TOKEN_EXPIRY_HOURS", 24),
+            refresh_token_expiry_days: settings.number("JWT_REFRESH_TOKEN_EXPIRY_DAYS", 30),
+            issuer: settings.string_or("JWT_ISSUER", "vibe-api"),
+            max_session_days: settings.number("JWT_MAX_SESSION_DAYS", 30),
         };
 
+        if environment == Environment::Production {
+            for message in Self::production_errors(&server, &jwt) {
+                settings.error(message);
+            }
+        }
+
+        let bootstrap_admin =
+            settings
+                .optional("BOOTSTRAP_ADMIN_EMAIL")
+                .map(|email| BootstrapAdminConfig {
+                    email: crate::utils::validation::normalize_email(&email),
+                    password: settings.required("BOOTSTRAP_ADMIN_PASSWORD"),
+                });
+
+        let google_oauth =
+            settings
+                .optional("GOOGLE_CLIENT_ID")
+                .map(|client_id| GoogleOAuthConfig {
+                    client_id,
+                    client_secret: settings.required("GOOGLE_CLIENT_SECRET"),
+                    redirect_uri: settings.required("GOOGLE_REDIRECT_URI"),
+                    auth_url: settings.string_or(
+                        "GOOGLE_AUTH_URL",
+                        "https://accounts.google.com/o/oauth2/v2/auth",
+                    ),
+                    token_url: settings
+                        .string_or("GOOGLE_TOKEN_URL", "https://oauth2.googleapis.com/token"),
+                    userinfo_url: settings.string_or(
+                        "GOOGLE_USERINFO_URL",
+                        "https://openidconnect.googleapis.com/v1/userinfo",
+                    ),
+                });
+
         #[cfg(feature = "ai")]
         let ai = AiConfig {
-            openai_api_key: env::var("OPENAI_API_KEY").ok(),
-            anthropic_api_key: env::var("ANTHROPIC_API_KEY").ok(),
-            default_model: env::var("AI_DEFAULT_MODEL")
-                .unwrap_or_else(|_| "gpt-4".to_string()),
-            max_tokens: env::var("AI_MAX_TOKENS")
-                .unwrap_or_else(|_| "2000".to_string())
-                .parse()
-                .expect("AI_MAX_TOKENS must be a valid number"),
-            temperature: env::var("AI_TEMPERATURE")
-                .unwrap_or_else(|_| "0.7".to_string())
-                .parse()
-                .expect("AI_TEMPERATURE must be a valid float"),
+            openai_api_key: settings.optional("OPENAI_API_KEY"),
+            anthropic_api_key: settings.optional("ANTHROPIC_API_KEY"),
+            default_model: settings.string_or("AI_DEFAULT_MODEL", "gpt-4"),
+            max_tokens: settings.number("AI_MAX_TOKENS", 2000),
+            temperature: settings.number("AI_TEMPERATURE", 0.7),
+            max_messages: settings.number("AI_MAX_MESSAGES", 50),
+            max_conversation_chars: settings.number("AI_MAX_CONVERSATION_CHARS", 32000),
+            max_embedding_batch_size: settings.number("AI_MAX_EMBEDDING_BATCH_SIZE", 100),
+            max_attempts: settings.number("AI_MAX_ATTEMPTS", 3),
+            retry_base_ms: settings.number("AI_RETRY_BASE_MS", 500),
+            allowed_models_user: settings
+                .optional("AI_ALLOWED_MODELS_USER")
+                .map(|models| Self::parse_list(&models)),
+            allowed_models_moderator: settings
+                .optional("AI_ALLOWED_MODELS_MODERATOR")
+                .map(|models| Self::parse_list(&models)),
+            allowed_models_openai: settings
+                .optional("AI_ALLOWED_MODELS_OPENAI")
+                .map(|models| Self::parse_list(&models)),
+            allowed_models_anthropic: settings
+                .optional("AI_ALLOWED_MODELS_ANTHROPIC")
+                .map(|models| Self::parse_list(&models)),
+            allowed_models_local: settings
+                .optional("AI_ALLOWED_MODELS_LOCAL")
+                .map(|models| Self::parse_list(&models)),
+            normalize_model_names: settings.flag("AI_NORMALIZE_MODEL_NAMES", true),
+            usage_alert_user_tokens: settings.number_list("AI_USAGE_ALERT_USER_TOKENS"),
+            usage_alert_global_tokens: settings.number_list("AI_USAGE_ALERT_GLOBAL_TOKENS"),
+            usage_alert_period_secs: settings.number("AI_USAGE_ALERT_PERIOD_SECS", 86400),
         };
 
         #[cfg(feature = "storage")]
         let storage = StorageConfig {
-            s3_bucket: env::var("S3_BUCKET")
-                .expect("S3_BUCKET must be set when storage feature is enabled"),
-            s3_region: env::var("S3_REGION")
-                .unwrap_or_else(|_| "us-east-1".to_string()),
-            s3_endpoint: env::var("S3_ENDPOINT").ok(),
-            s3_access_key: env::var("S3_ACCESS_KEY")
-                .expect("S3_ACCESS_KEY must be set when storage feature is enabled"),
-            s3_secret_key: env::var("S3_SECRET_KEY")
-                .expect("S3_SECRET_KEY must be set when storage feature is enabled"),
-            max_file_size_mb: env::var("MAX_FILE_SIZE_MB")
-                .unwrap_or_else(|_| "10".to_string())
-                .parse()
-                .expect("MAX_FILE_SIZE_MB must be a valid number"),
+            s3_bucket: settings.required("S3_BUCKET"),
+            s3_region: settings.string_or("S3_REGION", "us-east-1"),
+            s3_endpoint: settings.optional("S3_ENDPOINT"),
+            s3_access_key: settings.required("S3_ACCESS_KEY"),
+            s3_secret_key: settings.required("S3_SECRET_KEY"),
+            max_file_size_mb: settings.number("MAX_FILE_SIZE_MB", 10),
+            max_concurrent_uploads: settings.number("MAX_CONCURRENT_UPLOADS", 8),
+            upload_read_timeout_secs: settings.number("UPLOAD_READ_TIMEOUT_SECS", 30),
+            s3_timeout_secs: settings.number("S3_TIMEOUT_SECS", 10),
+            s3_max_attempts: settings.number("S3_MAX_ATTEMPTS", 3),
+            s3_retry_base_ms: settings.number("S3_RETRY_BASE_MS", 200),
+            dedupe_uploads: settings.flag("STORAGE_DEDUPE_UPLOADS", true),
+            presigned_url_expiry_secs: settings.number("S3_PRESIGNED_URL_EXPIRY_SECS", 3600),
+        };
+
+        #[cfg(feature = "jobs")]
+        let jobs = JobsConfig {
+            soft_delete_retention_days: settings.number("SOFT_DELETE_RETENTION_DAYS", 30),
+            scheduler: SchedulerConfig {
+                cleanup_cron: settings.string_or("JOB_CLEANUP_CRON", "0 0 0 * * *"),
+                metrics_cron: settings.string_or("JOB_METRICS_CRON", "0 0 * * * *"),
+                purge_cron: settings.string_or("JOB_PURGE_CRON", "0 0 3 * * *"),
+            },
         };
 
+        #[cfg(feature = "websocket")]
+        let websocket = WebSocketConfig {
+            max_rooms_per_connection: settings.number("WS_MAX_ROOMS_PER_CONNECTION", 50),
+            outbound_queue_capacity: settings.number("WS_OUTBOUND_QUEUE_CAPACITY", 256),
+            allow_anonymous: settings.flag("WS_ALLOW_ANONYMOUS", false),
+            ping_interval_secs: settings.number("WS_PING_INTERVAL_SECS", 30),
+            idle_timeout_secs: settings.number("WS_IDLE_TIMEOUT_SECS", 90),
+        };
+
+        settings.finish()?;
+
         Ok(Config {
             server,
             database,
             jwt,
+            bootstrap_admin,
+            google_oauth,
             #[cfg(feature = "ai")]
             ai,
             #[cfg(feature = "storage")]
             storage,
+            #[cfg(feature = "jobs")]
+            jobs,
+            #[cfg(feature = "websocket")]
+            websocket,
         })
     }
 
@@ -176,14 +528,103 @@ impl Config {
         }
     }
 
+    /// Settings that are fine for local work but unsafe in production
+    fn production_errors(server: &ServerConfig, jwt: &JwtConfig) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        // An unset secret is already reported as required
+        if !jwt.secret.is_empty() {
+            let secret = jwt.secret.trim();
+            if PLACEHOLDER_JWT_SECRETS
+                .iter()
+                .any(|placeholder| secret.eq_ignore_ascii_case(placeholder))
+            {
+                errors.push(
+                    "JWT_SECRET is a placeholder value and must be replaced in production"
+                        .to_string(),
+                );
+            } else if jwt.secret.len() < MIN_PRODUCTION_JWT_SECRET_BYTES {
+                errors.push(format!(
+                    "JWT_SECRET must be at least {} bytes in production, got {}",
+                    MIN_PRODUCTION_JWT_SECRET_BYTES,
+                    jwt.secret.len()
+                ));
+            }
+        }
+
+        if server
+            .cors_origins
+            .iter()
+            .any(|origin| origin.trim() == "*")
+        {
+            errors.push("CORS_ORIGINS must not contain '*' in production".to_string());
+        }
+
+        errors
+    }
+
     fn parse_cors_origins(origins: &str) -> Vec<String> {
-        origins
+        origins.split(',').map(|s| s.trim().to_string()).collect()
+    }
+
+    fn parse_list(values: &str) -> Vec<String> {
+        values
             .split(',')
-            .map(|s| s.trim().to_string())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// One domain per line; blank lines and `#` comments are skipped
+    fn parse_domain_list(contents: &str) -> Vec<String> {
+        contents
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|line| !line.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+
+    /// Accepts CIDRs (`10.0.0.0/8`) and bare addresses (`127.0.0.1`)
+    fn parse_networks(values: &str, var: &str) -> Result<Vec<IpNet>, String> {
+        Self::parse_list(values)
+            .iter()
+            .map(|value| {
+                value
+                    .parse::<IpNet>()
+                    .or_else(|_| value.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| {
+                        format!(
+                            "{} must contain IP addresses or CIDRs, got '{}'",
+                            var, value
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// `prefix=requests_per_minute` pairs, e.g. `/auth/login=10,/auth/register=5`
+    fn parse_route_limits(values: &str) -> Result<Vec<(String, u32)>, String> {
+        Self::parse_list(values)
+            .iter()
+            .map(|value| {
+                value
+                    .split_once('=')
+                    .and_then(|(prefix, limit)| {
+                        let limit = limit.trim().parse().ok().filter(|limit| *limit > 0)?;
+                        Some((prefix.trim().to_string(), limit))
+                    })
+                    .ok_or_else(|| {
+                        format!(
+                            "RATE_LIMIT_ROUTES must contain prefix=requests_per_minute pairs, got '{}'",
+                            value
+                        )
+                    })
+            })
             .collect()
     }
 }
 
 #[cfg(test)]
 mod tests;
-
@@ -0,0 +1,176 @@
+use config::{ConfigError, File, Value, ValueKind};
+use std::{cell::RefCell, collections::HashMap, env, path::Path, str::FromStr};
+
+#[cfg(feature = "ai")]
+use super::Config;
+
+/// Files picked up from the working directory when `CONFIG_FILE` is unset
+const DEFAULT_CONFIG_FILES: [&str; 3] = ["config.toml", "config.yaml", "config.yml"];
+
+/// Where `Config::load` reads its settings: environment variables, falling
+/// back to an optional TOML/YAML file.
+///
+/// File keys use the environment variable names, either flat
+/// (`db_max_connections = 20`) or nested, with tables joined by `_`
+/// (`[db] max_connections = 20`). Arrays become comma-separated lists, so
+/// `cors_origins = ["a", "b"]` reads like `CORS_ORIGINS=a,b`.
+#[derive(Debug, Default)]
+pub struct ConfigSource {
+    file: HashMap<String, String>,
+}
+
+impl ConfigSource {
+    /// Environment only
+    pub fn env() -> Self {
+        Self::default()
+    }
+
+    /// Environment over the file named by `CONFIG_FILE`, or over the first
+    /// default config file present; a missing `CONFIG_FILE` is an error
+    pub fn load() -> Result<Self, ConfigError> {
+        match env::var("CONFIG_FILE") {
+            Ok(path) => Self::from_file(&path),
+            Err(_) => match DEFAULT_CONFIG_FILES
+                .iter()
+                .find(|path| Path::new(path).is_file())
+            {
+                Some(path) => Self::from_file(path),
+                None => Ok(Self::env()),
+            },
+        }
+    }
+
+    /// Format follows the extension (`.toml`, `.yaml`/`.yml`, ...)
+    pub fn from_file(path: &str) -> Result<Self, ConfigError> {
+        let table = config::Config::builder()
+            .add_source(File::with_name(path).required(true))
+            .build()?
+            .try_deserialize::<HashMap<String, Value>>()?;
+
+        let mut file = HashMap::new();
+        for (key, value) in table {
+            flatten(&key, value, &mut file)?;
+        }
+        Ok(Self { file })
+    }
+
+    /// Same contract as `env::var`, so unset keys keep their defaults
+    pub fn var(&self, name: &str) -> Result<String, env::VarError> {
+        env::var(name).or_else(|err| self.file.get(name).cloned().ok_or(err))
+    }
+}
+
+/// Typed reads from a [`ConfigSource`] that record problems instead of
+/// panicking, so one failed startup reports every bad key at once
+pub(super) struct Settings<'a> {
+    source: &'a ConfigSource,
+    errors: RefCell<Vec<String>>,
+}
+
+impl<'a> Settings<'a> {
+    pub(super) fn new(source: &'a ConfigSource) -> Self {
+        Self {
+            source,
+            errors: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub(super) fn optional(&self, name: &str) -> Option<String> {
+        self.source.var(name).ok()
+    }
+
+    pub(super) fn string_or(&self, name: &str, default: &str) -> String {
+        self.optional(name).unwrap_or_else(|| default.to_string())
+    }
+
+    pub(super) fn required(&self, name: &str) -> String {
+        self.optional(name).unwrap_or_else(|| {
+            self.error(format!("{} is required", name));
+            String::new()
+        })
+    }
+
+    pub(super) fn number<T: FromStr>(&self, name: &str, default: T) -> T {
+        self.optional_number(name).unwrap_or(default)
+    }
+
+    pub(super) fn optional_number<T: FromStr>(&self, name: &str) -> Option<T> {
+        let value = self.optional(name)?;
+        let parsed = value.trim().parse().ok();
+        if parsed.is_none() {
+            self.error(format!("{} must be a number, got '{}'", name, value));
+        }
+        parsed
+    }
+
+    /// Comma-separated numbers; empty when unset
+    #[cfg(feature = "ai")]
+    pub(super) fn number_list<T: FromStr>(&self, name: &str) -> Vec<T> {
+        let value = self.optional(name).unwrap_or_default();
+        let parsed = Config::parse_list(&value)
+            .iter()
+            .map(|item| item.parse())
+            .collect::<Result<Vec<_>, _>>();
+        parsed.unwrap_or_else(|_| {
+            self.error(format!(
+                "{} must be a comma-separated list of numbers, got '{}'",
+                name, value
+            ));
+            Vec::new()
+        })
+    }
+
+    pub(super) fn flag(&self, name: &str, default: bool) -> bool {
+        match self.optional(name) {
+            Some(value) => value.trim().parse().unwrap_or_else(|_| {
+                self.error(format!("{} must be true or false, got '{}'", name, value));
+                default
+            }),
+            None => default,
+        }
+    }
+
+    /// Unwrap a parser's result, recording its message on failure
+    pub(super) fn check<T: Default>(&self, result: Result<T, String>) -> T {
+        result.unwrap_or_else(|message| {
+            self.error(message);
+            T::default()
+        })
+    }
+
+    pub(super) fn error(&self, message: String) {
+        self.errors.borrow_mut().push(message);
+    }
+
+    /// All recorded problems as one error, in the order they were read
+    pub(super) fn finish(self) -> Result<(), ConfigError> {
+        let errors = self.errors.into_inner();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Message(errors.join("; ")))
+        }
+    }
+}
+
+fn flatten(key: &str, value: Value, out: &mut HashMap<String, String>) -> Result<(), ConfigError> {
+    match value.kind {
+        ValueKind::Table(table) => {
+            for (child, value) in table {
+                flatten(&format!("{}_{}", key, child), value, out)?;
+            }
+        }
+        ValueKind::Array(values) => {
+            let values = values
+                .into_iter()
+                .map(Value::into_string)
+                .collect::<Result<Vec<_>, _>>()?;
+            out.insert(key.to_uppercase(), values.join(","));
+        }
+        ValueKind::Nil => {}
+        kind => {
+            out.insert(key.to_uppercase(), Value::new(None, kind).into_string()?);
+        }
+    }
+    Ok(())
+}
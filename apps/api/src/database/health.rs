@@ -1,16 +1,18 @@
-use sqlx::PgPool;
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 
 use crate::utils::error::AppResult;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct DatabaseHealth {
     pub status: HealthStatus,
     pub connections: ConnectionStats,
     pub latency_ms: Option<u64>,
+    /// Latest applied migration; unknown when the probe fails
+    pub migration_version: Option<i64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum HealthStatus {
     Healthy,
@@ -18,23 +20,24 @@ pub enum HealthStatus {
     Unhealthy,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Pool occupancy; `idle == 0` with `size == max` means every connection is
+/// checked out and new queries wait for the acquire timeout
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct ConnectionStats {
-    pub total: u32,
+    pub size: u32,
     pub idle: u32,
+    pub max: u32,
 }
 
 pub async fn check_health(pool: &PgPool) -> AppResult<DatabaseHealth> {
     let start = std::time::Instant::now();
 
     // Test database connectivity with a simple query
-    let result = sqlx::query("SELECT 1")
-        .fetch_one(pool)
-        .await;
+    let result = sqlx::query("SELECT 1").fetch_one(pool).await;
 
     let latency_ms = start.elapsed().as_millis() as u64;
 
-    let status = match result {
+    let status = match &result {
         Ok(_) => {
             if latency_ms > 1000 {
                 HealthStatus::Degraded
@@ -42,17 +45,37 @@ pub async fn check_health(pool: &PgPool) -> AppResult<DatabaseHealth> {
                 HealthStatus::Healthy
             }
         }
-        Err(_) => HealthStatus::Unhealthy,
+        Err(e) => {
+            tracing::warn!("Database health probe failed: {}", e);
+            HealthStatus::Unhealthy
+        }
+    };
+
+    let migration_version = match result {
+        Ok(_) => migration_version(pool).await,
+        Err(_) => None,
     };
 
     let connections = ConnectionStats {
-        total: pool.size(),
+        size: pool.size(),
         idle: pool.num_idle() as u32,
+        max: pool.options().get_max_connections(),
     };
 
     Ok(DatabaseHealth {
         status,
         connections,
         latency_ms: Some(latency_ms),
+        migration_version,
     })
 }
+
+/// Highest successfully applied version in sqlx's migration table
+async fn migration_version(pool: &PgPool) -> Option<i64> {
+    sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| tracing::warn!("Could not read migration version: {}", e))
+        .ok()
+        .flatten()
+}
@@ -0,0 +1,62 @@
+use std::future::Future;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use crate::utils::error::{AppError, AppResult};
+
+/// Queries executed by the current request, with an optional sanity cap
+#[derive(Debug, Default)]
+pub struct QueryCounter {
+    count: AtomicUsize,
+    max_queries: Option<usize>,
+}
+
+impl QueryCounter {
+    pub fn new(max_queries: Option<usize>) -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            max_queries,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+tokio::task_local! {
+    static REQUEST_QUERIES: Arc<QueryCounter>;
+}
+
+/// Run `fut` with `counter` receiving the queries made through [`tracked`]
+pub async fn scope<F: Future>(counter: Arc<QueryCounter>, fut: F) -> F::Output {
+    REQUEST_QUERIES.scope(counter, fut).await
+}
+
+/// Count a query against the current request before running it
+///
+/// Once the request's cap is exceeded the query is not executed and the
+/// request fails with a 500. Outside a request scope queries run uncounted.
+pub async fn tracked<T, F>(query: F) -> AppResult<T>
+where
+    F: Future<Output = Result<T, sqlx::Error>>,
+{
+    let over_cap = REQUEST_QUERIES
+        .try_with(|counter| {
+            let count = counter.count.fetch_add(1, Ordering::Relaxed) + 1;
+            counter.max_queries.filter(|max| count > *max)
+        })
+        .ok()
+        .flatten();
+
+    if let Some(max) = over_cap {
+        return Err(AppError::InternalServer(format!(
+            "Request exceeded the cap of {} database queries",
+            max
+        )));
+    }
+
+    Ok(query.await?)
+}
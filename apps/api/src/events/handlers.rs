@@ -0,0 +1,71 @@
+use crate::metrics;
+use crate::security::redact_email;
+
+use super::{DomainEvent, EventBus};
+
+/// Subscribe the built-in audit and metrics handlers
+pub fn spawn_default_handlers(bus: &EventBus) {
+    bus.spawn_handler("audit", |event| async move { audit(&event) });
+    bus.spawn_handler("metrics", |event| async move {
+        metrics::record_domain_event(event.name())
+    });
+}
+
+/// Log the event under the `audit` target; emails are redacted
+pub fn audit(event: &DomainEvent) {
+    let name = event.name();
+    match event {
+        DomainEvent::UserCreated { user_id, email } => tracing::info!(
+            target: "audit",
+            event = name,
+            user_id = %user_id,
+            email = %redact_email(email),
+            "User created"
+        ),
+        DomainEvent::UserDeleted { user_id } => tracing::info!(
+            target: "audit",
+            event = name,
+            user_id = %user_id,
+            "User deleted"
+        ),
+        DomainEvent::UserRoleChanged { user_id, role } => tracing::info!(
+            target: "audit",
+            event = name,
+            user_id = %user_id,
+            role = %role,
+            "User role changed"
+        ),
+        DomainEvent::LoginFailed { email } => tracing::info!(
+            target: "audit",
+            event = name,
+            email = %redact_email(email),
+            "Login failed"
+        ),
+        DomainEvent::PasswordResetRequested { user_id, email, .. } => tracing::info!(
+            target: "audit",
+            event = name,
+            user_id = %user_id,
+            email = %redact_email(email),
+            "Password reset requested"
+        ),
+        DomainEvent::PasswordReset { user_id } => tracing::info!(
+            target: "audit",
+            event = name,
+            user_id = %user_id,
+            "Password reset"
+        ),
+        DomainEvent::EmailVerificationRequested { user_id, email, .. } => tracing::info!(
+            target: "audit",
+            event = name,
+            user_id = %user_id,
+            email = %redact_email(email),
+            "Email verification requested"
+        ),
+        DomainEvent::EmailVerified { user_id } => tracing::info!(
+            target: "audit",
+            event = name,
+            user_id = %user_id,
+            "Email verified"
+        ),
+    }
+}
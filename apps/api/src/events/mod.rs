@@ -0,0 +1,119 @@
+// In-process domain events
+// Services publish what happened; audit logging, metrics and other listeners
+// subscribe without the services knowing about them. Delivery is best effort:
+// durable side effects (webhooks) keep using their own persisted queue.
+
+pub mod handlers;
+
+use std::future::Future;
+use tokio::{sync::broadcast, task::JoinHandle};
+use uuid::Uuid;
+
+use crate::modules::users::model::UserRole;
+
+/// Events buffered per subscriber before a slow one starts missing events
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomainEvent {
+    UserCreated {
+        user_id: Uuid,
+        email: String,
+    },
+    UserDeleted {
+        user_id: Uuid,
+    },
+    UserRoleChanged {
+        user_id: Uuid,
+        role: UserRole,
+    },
+    LoginFailed {
+        email: String,
+    },
+    /// Carries the plaintext reset token for whoever delivers it (e.g. a
+    /// mailer); handlers must never log `token`
+    PasswordResetRequested {
+        user_id: Uuid,
+        email: String,
+        token: String,
+    },
+    PasswordReset {
+        user_id: Uuid,
+    },
+    /// Carries the plaintext verification token for whoever delivers it;
+    /// handlers must never log `token`
+    EmailVerificationRequested {
+        user_id: Uuid,
+        email: String,
+        token: String,
+    },
+    EmailVerified {
+        user_id: Uuid,
+    },
+}
+
+impl DomainEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DomainEvent::UserCreated { .. } => "user.created",
+            DomainEvent::UserDeleted { .. } => "user.deleted",
+            DomainEvent::UserRoleChanged { .. } => "user.role_changed",
+            DomainEvent::LoginFailed { .. } => "login.failed",
+            DomainEvent::PasswordResetRequested { .. } => "password_reset.requested",
+            DomainEvent::PasswordReset { .. } => "password_reset.completed",
+            DomainEvent::EmailVerificationRequested { .. } => "email_verification.requested",
+            DomainEvent::EmailVerified { .. } => "email_verification.completed",
+        }
+    }
+}
+
+/// Broadcast channel for `DomainEvent`s; cloning shares the channel
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<DomainEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Publish without waiting on subscribers; dropped when nobody listens
+    pub fn publish(&self, event: DomainEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Run `handler` for every published event on a background task
+    ///
+    /// A handler that falls more than the channel capacity behind skips the
+    /// events it missed (logged) instead of slowing publishers down.
+    pub fn spawn_handler<F, Fut>(&self, name: &'static str, mut handler: F) -> JoinHandle<()>
+    where
+        F: FnMut(DomainEvent) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let mut receiver = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => handler(event).await,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!("Event handler {} lagged, skipped {} events", name, missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
@@ -3,9 +3,13 @@
 
 pub mod config;
 pub mod database;
+pub mod events;
 pub mod metrics;
 pub mod middleware;
 pub mod modules;
+pub mod openapi;
+pub mod security;
+pub mod shutdown;
 pub mod utils;
 
 // Re-export commonly used types
@@ -1,25 +1,37 @@
-use axum::{routing::get, Router, Json};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
 use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
 use serde::Serialize;
-use std::sync::Arc;
+use sqlx::PgPool;
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
+use tokio::task::JoinHandle;
+use utoipa::ToSchema;
 
 use crate::utils::error::AppResult;
 use crate::utils::response::ApiResponse;
+use crate::utils::uptime;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
+#[schema(as = metrics::HealthResponse)]
 struct HealthResponse {
     status: String,
     version: String,
     uptime_seconds: u64,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct ReadinessResponse {
     ready: bool,
     checks: Vec<CheckResult>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct CheckResult {
     name: String,
     healthy: bool,
@@ -27,70 +39,213 @@ struct CheckResult {
     message: Option<String>,
 }
 
-static START_TIME: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+/// How long one readiness check may take before it counts as failed
+pub const READINESS_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
 
-pub fn init_metrics() -> PrometheusHandle {
-    START_TIME.set(std::time::Instant::now()).ok();
+type ReadinessCheck =
+    Arc<dyn Fn() -> Pin<Box<dyn Future<Output = Result<(), String>> + Send>> + Send + Sync>;
+
+/// Dependencies probed by `GET /ready`; the API is ready only when every
+/// check passes
+#[derive(Clone)]
+pub struct Readiness {
+    checks: Vec<(String, ReadinessCheck)>,
+    timeout: Duration,
+}
+
+impl Readiness {
+    pub fn new() -> Self {
+        Self {
+            checks: Vec::new(),
+            timeout: READINESS_CHECK_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Register (or replace) the check reported under `name`; an `Err`
+    /// message is shown in the check's `message`
+    pub fn with_check<F, Fut>(mut self, name: &str, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.checks.retain(|(existing, _)| existing != name);
+        self.checks
+            .push((name.to_string(), Arc::new(move || Box::pin(check()))));
+        self
+    }
+
+    /// Register the `database` check, a `SELECT 1` against `pool`
+    pub fn with_database(self, pool: PgPool) -> Self {
+        self.with_check("database", move || {
+            let pool = pool.clone();
+            async move {
+                sqlx::query("SELECT 1")
+                    .fetch_one(&pool)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| {
+                        tracing::warn!("Readiness check: database unavailable: {}", e);
+                        "Database unavailable".to_string()
+                    })
+            }
+        })
+    }
+
+    /// Run every check concurrently, each bounded by the timeout so one hung
+    /// dependency can't stall the probe
+    async fn run(&self) -> ReadinessResponse {
+        let pending: Vec<_> = self
+            .checks
+            .iter()
+            .map(|(name, check)| {
+                let name = name.clone();
+                let check = tokio::time::timeout(self.timeout, check());
+                tokio::spawn(async move {
+                    let outcome = check
+                        .await
+                        .unwrap_or_else(|_| Err("Check timed out".to_string()));
+                    CheckResult {
+                        name,
+                        healthy: outcome.is_ok(),
+                        message: outcome.err(),
+                    }
+                })
+            })
+            .collect();
+
+        let mut checks = Vec::with_capacity(pending.len());
+        for (task, (name, _)) in pending.into_iter().zip(&self.checks) {
+            checks.push(task.await.unwrap_or_else(|_| CheckResult {
+                name: name.clone(),
+                healthy: false,
+                message: Some("Check panicked".to_string()),
+            }));
+        }
+
+        let ready = checks.iter().all(|check| check.healthy);
+        ReadinessResponse { ready, checks }
+    }
+}
+
+impl Default for Readiness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
+pub fn init_metrics() -> PrometheusHandle {
     PrometheusBuilder::new()
         .set_buckets_for_metric(
             Matcher::Full("http_requests_duration_seconds".to_string()),
-            &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0],
+            &[
+                0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+            ],
         )
         .unwrap()
         .install_recorder()
         .expect("Failed to install Prometheus recorder")
 }
 
-pub fn routes() -> Router {
+/// Content type of the Prometheus text exposition format
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// `/metrics` renders the recorder behind `handle` (from `init_metrics`);
+/// `/ready` runs the `readiness` checks
+pub fn routes(handle: PrometheusHandle, readiness: Readiness) -> Router {
     Router::new()
         .route("/health", get(health_handler))
-        .route("/ready", get(readiness_handler))
         .route("/metrics", get(metrics_handler))
+        .with_state(handle)
+        .merge(
+            Router::new()
+                .route("/ready", get(readiness_handler))
+                .with_state(Arc::new(readiness)),
+        )
 }
 
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "Server is up", body = ApiResponse<HealthResponse>)
+    )
+)]
 async fn health_handler() -> impl axum::response::IntoResponse {
-    let uptime = START_TIME
-        .get()
-        .map(|start| start.elapsed().as_secs())
-        .unwrap_or(0);
-
     let response = HealthResponse {
         status: "healthy".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
-        uptime_seconds: uptime,
+        uptime_seconds: uptime::uptime_seconds(),
     };
 
     ApiResponse::success(response)
 }
 
-async fn readiness_handler() -> impl axum::response::IntoResponse {
-    // In production, you would check:
-    // - Database connectivity
-    // - External service availability
-    // - Cache connectivity
-    // etc.
+/// 503 when any dependency check fails, so load balancers stop routing here
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Every dependency check passed", body = ApiResponse<ReadinessResponse>),
+        (status = 503, description = "A dependency check failed", body = ApiResponse<ReadinessResponse>)
+    )
+)]
+async fn readiness_handler(State(readiness): State<Arc<Readiness>>) -> impl IntoResponse {
+    let response = readiness.run().await;
+    let status = if response.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, ApiResponse::success(response))
+}
 
-    let checks = vec![
-        CheckResult {
-            name: "api".to_string(),
-            healthy: true,
-            message: None,
-        },
-        // Add more checks as needed
-    ];
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "metrics",
+    responses(
+        (status = 200, description = "Prometheus text exposition", body = String, content_type = "text/plain; version=0.0.4")
+    )
+)]
+async fn metrics_handler(State(handle): State<PrometheusHandle>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+        handle.render(),
+    )
+}
 
-    let ready = checks.iter().all(|check| check.healthy);
+/// `path` label for requests that matched no route, so probes for random
+/// URLs cannot create new series
+pub const UNMATCHED_PATH: &str = "unmatched";
 
-    let response = ReadinessResponse { ready, checks };
+/// Record every request's count and latency via `record_request`
+///
+/// The `path` label is the route template (`/users/{id}`), never the raw URI.
+pub async fn track_metrics(request: Request, next: Next) -> Response {
+    let started = std::time::Instant::now();
+    let method = request.method().clone();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_PATH.to_string(), |path| path.as_str().to_string());
 
-    ApiResponse::success(response)
-}
+    let response = next.run(request).await;
 
-async fn metrics_handler() -> String {
-    // This would return Prometheus-formatted metrics
-    // In production, you'd use the PrometheusHandle to export metrics
-    "# Metrics endpoint\n".to_string()
+    record_request(
+        method.as_str(),
+        &path,
+        response.status().as_u16(),
+        started.elapsed().as_secs_f64(),
+    );
+    response
 }
 
 // Utility functions for recording metrics
@@ -99,7 +254,8 @@ pub fn record_request(method: &str, path: &str, status: u16, duration: f64) {
     let path = path.to_string();
     let status = status.to_string();
     metrics::counter!("http_requests_total", "method" => method.clone(), "path" => path.clone(), "status" => status).increment(1);
-    metrics::histogram!("http_requests_duration_seconds", "method" => method, "path" => path).record(duration);
+    metrics::histogram!("http_requests_duration_seconds", "method" => method, "path" => path)
+        .record(duration);
 }
 
 pub fn record_database_query(query_type: &str, duration: f64) {
@@ -113,3 +269,76 @@ pub fn record_external_api_call(provider: &str, success: bool, duration: f64) {
     metrics::counter!("external_api_calls_total", "provider" => provider.clone(), "status" => status).increment(1);
     metrics::histogram!("external_api_duration_seconds", "provider" => provider).record(duration);
 }
+
+/// Requests finished vs cut off while draining at shutdown
+pub fn record_shutdown_drain(drained: u64, abandoned: u64) {
+    metrics::counter!("shutdown_drained_requests_total").increment(drained);
+    metrics::counter!("shutdown_timeout_abandoned_total").increment(abandoned);
+}
+
+// Auth outcome counters; `result` is `success`, `failure` or `locked`
+pub fn record_auth_login(result: &str) {
+    metrics::counter!("auth_logins_total", "result" => result.to_string()).increment(1);
+}
+
+pub fn record_auth_signup(result: &str) {
+    metrics::counter!("auth_signups_total", "result" => result.to_string()).increment(1);
+}
+
+pub fn record_auth_token_refresh(result: &str) {
+    metrics::counter!("auth_token_refresh_total", "result" => result.to_string()).increment(1);
+}
+
+/// Domain events seen on the in-process event bus, by event name
+pub fn record_domain_event(event: &str) {
+    metrics::counter!("domain_events_total", "event" => event.to_string()).increment(1);
+}
+
+/// Outbound WebSocket messages dropped because a client's queue was full
+pub fn record_ws_message_dropped() {
+    metrics::counter!("ws_messages_dropped_total").increment(1);
+}
+
+/// WebSocket connections closed for being too slow to take a direct reply
+pub fn record_ws_connection_shed() {
+    metrics::counter!("ws_connections_shed_total").increment(1);
+}
+
+/// WebSocket connections closed for sending nothing within the idle timeout
+pub fn record_ws_idle_timeout() {
+    metrics::counter!("ws_connections_idle_timeout_total").increment(1);
+}
+
+/// Currently open WebSocket connections
+pub fn set_ws_active_connections(count: usize) {
+    metrics::gauge!("ws_active_connections").set(count as f64);
+}
+
+/// How often `spawn_pool_sampler` records the database pool gauges
+pub const POOL_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Connections checked out of `pool` and idle in it, plus its ceiling, so an
+/// alert can fire as `active` nears `max` before acquires start timing out
+pub fn record_db_pool(pool: &PgPool) {
+    let size = pool.size() as usize;
+    let idle = pool.num_idle();
+    metrics::gauge!("db_pool_connections_active").set(size.saturating_sub(idle) as f64);
+    metrics::gauge!("db_pool_connections_idle").set(idle as f64);
+    metrics::gauge!("db_pool_connections_max").set(pool.options().get_max_connections() as f64);
+}
+
+/// Queries that gave up waiting `DB_ACQUIRE_TIMEOUT_SECS` for a connection
+pub fn record_db_pool_acquire_timeout() {
+    metrics::counter!("db_pool_acquire_timeouts_total").increment(1);
+}
+
+/// Record the pool gauges every `interval` until the task is aborted
+pub fn spawn_pool_sampler(pool: PgPool, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            record_db_pool(&pool);
+        }
+    })
+}
@@ -0,0 +1,74 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::security::{self, SecurityEvent};
+
+/// Emits a security event when one peer address collects `threshold`
+/// 401/403 responses within `window`; reported once per window
+#[derive(Clone)]
+pub struct AuthFailureMonitor {
+    threshold: u32,
+    window: Duration,
+    failures: Arc<Mutex<HashMap<IpAddr, (Instant, u32)>>>,
+}
+
+impl AuthFailureMonitor {
+    pub fn new(threshold: u32, window: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            window,
+            failures: Arc::default(),
+        }
+    }
+
+    fn record(&self, ip: IpAddr) {
+        let now = Instant::now();
+        let mut failures = self.failures.lock().unwrap();
+        failures.retain(|_, (started, _)| now.duration_since(*started) < self.window);
+
+        let (_, count) = failures.entry(ip).or_insert((now, 0));
+        *count += 1;
+        if *count == self.threshold {
+            security::emit(SecurityEvent::RepeatedAuthFailures {
+                client_ip: ip,
+                failures: *count,
+                window_secs: self.window.as_secs(),
+            });
+        }
+    }
+}
+
+/// Matched on the TCP peer address only, like the rate limiter's trusted IPs
+pub async fn monitor_auth_failures(
+    State(monitor): State<AuthFailureMonitor>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(peer)| peer.ip().to_canonical());
+
+    let response = next.run(request).await;
+
+    if let Some(ip) = peer {
+        if matches!(
+            response.status(),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+        ) {
+            monitor.record(ip);
+        }
+    }
+
+    response
+}
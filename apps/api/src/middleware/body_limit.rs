@@ -0,0 +1,79 @@
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http_body_util::Limited;
+use std::sync::Arc;
+
+use super::rate_limit::matches_prefix;
+use crate::utils::error::{AppError, ErrorInfo};
+
+/// Request body size caps: one default plus larger (or smaller) limits for
+/// specific path prefixes, such as the upload route
+#[derive(Clone)]
+pub struct BodyLimit {
+    default: usize,
+    routes: Arc<Vec<(String, usize)>>,
+}
+
+impl BodyLimit {
+    pub fn new(default: usize) -> Self {
+        Self {
+            default,
+            routes: Arc::new(Vec::new()),
+        }
+    }
+
+    /// Apply `limit_bytes` to paths under `prefix` instead of the default;
+    /// the first matching prefix wins
+    pub fn with_route_limit(mut self, prefix: impl Into<String>, limit_bytes: usize) -> Self {
+        Arc::make_mut(&mut self.routes).push((prefix.into(), limit_bytes));
+        self
+    }
+
+    pub fn limit_for(&self, path: &str) -> usize {
+        self.routes
+            .iter()
+            .find(|(prefix, _)| matches_prefix(prefix, path))
+            .map_or(self.default, |(_, limit)| *limit)
+    }
+}
+
+/// Body limit middleware, applied to the whole router.
+///
+/// Works like tower-http's `RequestBodyLimitLayer`: a declared
+/// `Content-Length` over the limit is refused before the body is read, and
+/// chunked bodies are cut off once they pass it. Either way the client gets
+/// the structured `PAYLOAD_TOO_LARGE` error instead of axum's plain-text 413.
+pub async fn limit_request_body(
+    State(limit): State<BodyLimit>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limit_bytes = limit.limit_for(request.uri().path());
+
+    let declared_length = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if declared_length.is_some_and(|length| length > limit_bytes as u64) {
+        return AppError::PayloadTooLarge { limit_bytes }.into_response();
+    }
+
+    let request = request.map(|body| Body::new(Limited::new(body, limit_bytes)));
+    let response = next.run(request).await;
+
+    // Extractors report an overrun as a bare 413; handlers that raise their
+    // own 413 (e.g. FILE_TOO_LARGE) already carry error info and are kept
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE
+        && response.extensions().get::<ErrorInfo>().is_none()
+    {
+        return AppError::PayloadTooLarge { limit_bytes }.into_response();
+    }
+
+    response
+}
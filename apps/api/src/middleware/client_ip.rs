@@ -0,0 +1,92 @@
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{request::Parts, HeaderMap},
+};
+use ipnet::IpNet;
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+/// Reverse proxies whose `X-Forwarded-For` is believed; for any other peer
+/// the header is ignored, so clients can't pick their own address
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(Arc<Vec<IpNet>>);
+
+impl TrustedProxies {
+    pub fn new(proxies: Vec<IpNet>) -> Self {
+        Self(Arc::new(proxies))
+    }
+
+    /// The TCP peer, or behind a trusted proxy the last address it appended
+    /// to `X-Forwarded-For`
+    pub fn client_ip(&self, headers: &HeaderMap, peer: IpAddr) -> IpAddr {
+        let peer = peer.to_canonical();
+        if !self.0.iter().any(|proxy| proxy.contains(&peer)) {
+            return peer;
+        }
+
+        headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|address| address.trim().parse::<IpAddr>().ok())
+            .next_back()
+            .map_or(peer, |ip| ip.to_canonical())
+    }
+}
+
+/// Extracts the client address as the rate limiter sees it
+///
+/// Uses the `TrustedProxies` request extension when the router has one, and
+/// the bare TCP peer otherwise. `None` when the peer is unknown.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(ConnectInfo(peer)) = parts.extensions.get::<ConnectInfo<SocketAddr>>() else {
+            return Ok(Self(None));
+        };
+        let ip = match parts.extensions.get::<TrustedProxies>() {
+            Some(proxies) => proxies.client_ip(&parts.headers, peer.ip()),
+            None => peer.ip().to_canonical(),
+        };
+
+        Ok(Self(Some(ip)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn forwarded(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_forwarded_for_is_only_believed_from_trusted_proxies() {
+        let proxies = TrustedProxies::new(vec!["10.0.0.0/8".parse().unwrap()]);
+        let headers = forwarded("203.0.113.9, 198.51.100.7");
+
+        assert_eq!(
+            proxies.client_ip(&headers, "10.0.0.1".parse().unwrap()),
+            "198.51.100.7".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(
+            proxies.client_ip(&headers, "192.0.2.1".parse().unwrap()),
+            "192.0.2.1".parse::<IpAddr>().unwrap()
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer,
+};
+
+/// Response compression negotiated from `Accept-Encoding`
+///
+/// Brotli and gzip are both offered; when the client weights them equally
+/// Brotli wins. Bodies smaller than `min_size_bytes` and content that is
+/// already compressed (images, archives, audio/video) are sent as-is.
+pub fn compression(min_size_bytes: u16) -> CompressionLayer<impl Predicate> {
+    let predicate = SizeAbove::new(min_size_bytes)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::SSE)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::const_new("audio/"))
+        .and(NotForContentType::const_new("video/"))
+        .and(NotForContentType::const_new("application/zip"))
+        .and(NotForContentType::const_new("application/gzip"))
+        .and(NotForContentType::const_new("application/x-gzip"))
+        .and(NotForContentType::const_new("application/x-7z-compressed"));
+
+    CompressionLayer::new()
+        .br(true)
+        .gzip(true)
+        .compress_when(predicate)
+}
@@ -0,0 +1,51 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::Semaphore;
+
+/// Bounded concurrency for expensive, DB-heavy handlers
+///
+/// Requests beyond the permit count wait up to `queue_timeout` for a slot and
+/// are shed with `503 Service Unavailable` + `Retry-After` once it elapses.
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    semaphore: Arc<Semaphore>,
+    queue_timeout: Duration,
+}
+
+impl ConcurrencyLimit {
+    pub fn new(permits: usize, queue_timeout: Duration) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(permits.max(1))),
+            queue_timeout,
+        }
+    }
+
+    fn retry_after_secs(&self) -> u64 {
+        self.queue_timeout.as_secs().max(1)
+    }
+}
+
+/// Concurrency limiting middleware, applied per route with `route_layer`
+pub async fn concurrency_limit_middleware(
+    State(limit): State<ConcurrencyLimit>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let permit =
+        tokio::time::timeout(limit.queue_timeout, limit.semaphore.clone().acquire_owned()).await;
+
+    match permit {
+        Ok(Ok(_permit)) => next.run(request).await,
+        _ => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, limit.retry_after_secs().to_string())],
+            "Server is busy. Please try again later.",
+        )
+            .into_response(),
+    }
+}
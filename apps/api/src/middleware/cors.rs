@@ -0,0 +1,70 @@
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use crate::config::{Environment, ServerConfig};
+
+/// CORS policy for public, read-only endpoints (config, version)
+///
+/// Any origin may read these; credentials are never allowed.
+pub fn permissive_cors() -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods([Method::GET, Method::HEAD, Method::OPTIONS])
+        .allow_headers(Any)
+}
+
+/// CORS policy for authenticated endpoints, from `CORS_ORIGINS`
+///
+/// A `*` entry echoes back whatever origin calls, for local development.
+/// `Config::load` refuses it in production; should a config built some other
+/// way still carry one, it is ignored rather than opening the API up.
+pub fn cors_from_config(config: &ServerConfig) -> CorsLayer {
+    let wildcard = config
+        .cors_origins
+        .iter()
+        .any(|origin| origin.trim() == "*");
+    if !wildcard {
+        return strict_cors(&config.cors_origins);
+    }
+
+    if config.environment == Environment::Production {
+        tracing::warn!("Ignoring '*' in CORS_ORIGINS in production");
+        return strict_cors(&config.cors_origins);
+    }
+
+    credentialed_cors(AllowOrigin::mirror_request())
+}
+
+/// CORS policy for authenticated endpoints (auth, users)
+///
+/// Only the listed origins are echoed back; requests from any other origin
+/// receive no `Access-Control-Allow-Origin` header.
+pub fn strict_cors(allowed_origins: &[String]) -> CorsLayer {
+    let origins: Vec<HeaderValue> = allowed_origins
+        .iter()
+        .filter(|origin| origin.trim() != "*")
+        .filter_map(|origin| HeaderValue::from_str(origin.trim()).ok())
+        .collect();
+
+    credentialed_cors(AllowOrigin::list(origins))
+}
+
+fn credentialed_cors(allow_origin: AllowOrigin) -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE, header::ACCEPT])
+        .expose_headers([
+            header::RETRY_AFTER,
+            HeaderName::from_static("x-ratelimit-remaining"),
+            HeaderName::from_static("x-request-id"),
+        ])
+        .allow_credentials(true)
+}
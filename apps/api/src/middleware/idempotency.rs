@@ -0,0 +1,328 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use http_body_util::LengthLimitError;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use super::rate_limit::matches_prefix;
+use crate::config::JwtConfig;
+use crate::modules::auth::{jwt::validate_access_token, middleware::extract_token};
+use crate::utils::error::{AppError, AppResult};
+
+/// Header naming a client-chosen key for one logical POST
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Set on responses served from a stored first response
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Routes whose responses carry access or refresh tokens; they are never
+/// stored, so a retry runs them again
+const CREDENTIAL_PATHS: &[&str] = &["/auth/login", "/auth/register", "/auth/refresh"];
+
+/// Minimum interval between purges of expired keys
+const PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Stores the first response to each keyed POST and replays it for retries
+#[derive(Clone)]
+pub struct Idempotency {
+    db_pool: PgPool,
+    jwt_config: Arc<JwtConfig>,
+    ttl: Duration,
+    last_purge: Arc<Mutex<Option<Instant>>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct StoredResponse {
+    request_hash: String,
+    status_code: Option<i16>,
+    content_type: Option<String>,
+    response_body: Option<Vec<u8>>,
+}
+
+impl Idempotency {
+    /// Keys are remembered for `ttl`; after that a key may be used again
+    pub fn new(db_pool: PgPool, jwt_config: JwtConfig, ttl: Duration) -> Self {
+        Self {
+            db_pool,
+            jwt_config: Arc::new(jwt_config),
+            ttl,
+            last_purge: Arc::default(),
+        }
+    }
+
+    /// Delete keys past their TTL, returning how many went
+    pub async fn purge_expired(&self) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM idempotency_keys WHERE expires_at <= NOW()")
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Purge in the background if the last purge was over an interval ago
+    fn purge_if_due(&self) {
+        {
+            let mut last_purge = self.last_purge.lock().unwrap();
+            if last_purge.is_some_and(|at| at.elapsed() < PURGE_INTERVAL) {
+                return;
+            }
+            *last_purge = Some(Instant::now());
+        }
+
+        let idempotency = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = idempotency.purge_expired().await {
+                tracing::warn!("Failed to purge expired idempotency keys: {}", e);
+            }
+        });
+    }
+
+    /// Keys belong to the signed-in user, or else to the TCP peer address
+    fn scope(&self, request: &Request) -> Option<String> {
+        let user = extract_token(request.headers())
+            .and_then(|token| validate_access_token(&token, &self.jwt_config))
+            .ok();
+        if let Some(claims) = user {
+            return Some(format!("user:{}", claims.sub));
+        }
+
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(peer)| format!("ip:{}", peer.ip().to_canonical()))
+    }
+
+    /// Take the key, or an expired claim on it; `false` when it is live
+    async fn claim(&self, scope: &str, key: &str, request_hash: &str) -> AppResult<bool> {
+        let expires_at =
+            Utc::now() + chrono::Duration::from_std(self.ttl).unwrap_or(chrono::Duration::days(1));
+        let claimed: Option<DateTime<Utc>> = sqlx::query_scalar(
+            r#"
+            INSERT INTO idempotency_keys (scope, idempotency_key, request_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (scope, idempotency_key) DO UPDATE
+            SET request_hash = EXCLUDED.request_hash,
+                status_code = NULL,
+                content_type = NULL,
+                response_body = NULL,
+                created_at = NOW(),
+                expires_at = EXCLUDED.expires_at
+            WHERE idempotency_keys.expires_at <= NOW()
+            RETURNING created_at
+            "#,
+        )
+        .bind(scope)
+        .bind(key)
+        .bind(request_hash)
+        .bind(expires_at)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(claimed.is_some())
+    }
+
+    async fn stored(&self, scope: &str, key: &str) -> AppResult<Option<StoredResponse>> {
+        let stored = sqlx::query_as::<_, StoredResponse>(
+            r#"
+            SELECT request_hash, status_code, content_type, response_body
+            FROM idempotency_keys
+            WHERE scope = $1 AND idempotency_key = $2
+            "#,
+        )
+        .bind(scope)
+        .bind(key)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(stored)
+    }
+
+    async fn store(
+        &self,
+        scope: &str,
+        key: &str,
+        status: StatusCode,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE idempotency_keys
+            SET status_code = $3, content_type = $4, response_body = $5
+            WHERE scope = $1 AND idempotency_key = $2
+            "#,
+        )
+        .bind(scope)
+        .bind(key)
+        .bind(status.as_u16() as i16)
+        .bind(content_type)
+        .bind(body)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Give the key back so a retry runs the request again
+    async fn release(&self, scope: &str, key: &str) -> AppResult<()> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE scope = $1 AND idempotency_key = $2")
+            .bind(scope)
+            .bind(key)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// SHA-256 over the path, query and body, so a key reused for another request is caught
+fn request_hash(uri: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(uri.as_bytes());
+    hasher.update([0]);
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+/// Idempotency middleware for POST requests carrying `Idempotency-Key`
+///
+/// The first request with a key runs normally and its response is stored
+/// for the TTL. Retries with the same key, URI and body get that response
+/// back with `Idempotent-Replayed: true` instead of running again; a
+/// different URI or body is rejected with 422 `IDEMPOTENCY_KEY_REUSED`, and
+/// a retry while the first request is still running gets 409. Server errors,
+/// 429s and streamed responses are not stored, so a retry runs the request
+/// again. Routes issuing tokens are never keyed, so no credential is stored.
+pub async fn idempotent_posts(
+    State(idempotency): State<Idempotency>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    if request.method() != Method::POST
+        || CREDENTIAL_PATHS
+            .iter()
+            .any(|prefix| matches_prefix(prefix, path))
+    {
+        return next.run(request).await;
+    }
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(request).await;
+    };
+    let key = match key.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => key.to_string(),
+        _ => {
+            return AppError::BadRequest(format!(
+                "Idempotency-Key must be 1 to {} visible ASCII characters",
+                MAX_KEY_LENGTH
+            ))
+            .into_response()
+        }
+    };
+    let Some(scope) = idempotency.scope(&request) else {
+        return next.run(request).await;
+    };
+    idempotency.purge_if_due();
+
+    let (parts, body) = request.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        // The body limit layer turns a bare 413 into its structured error
+        Err(e) if std::error::Error::source(&e).is_some_and(|e| e.is::<LengthLimitError>()) => {
+            return StatusCode::PAYLOAD_TOO_LARGE.into_response()
+        }
+        Err(e) => return AppError::BadRequest(e.to_string()).into_response(),
+    };
+    let hash = request_hash(&parts.uri.to_string(), &body);
+    let request = Request::from_parts(parts, Body::from(body));
+
+    match run_once(&idempotency, &scope, &key, &hash, request, next).await {
+        Ok(response) => response,
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn run_once(
+    idempotency: &Idempotency,
+    scope: &str,
+    key: &str,
+    hash: &str,
+    request: Request,
+    next: Next,
+) -> AppResult<Response> {
+    if !idempotency.claim(scope, key, hash).await? {
+        let stored = idempotency.stored(scope, key).await?.ok_or_else(|| {
+            AppError::Conflict("Idempotency-Key was just released; retry the request".to_string())
+        })?;
+        if stored.request_hash != hash {
+            return Err(AppError::IdempotencyKeyReused);
+        }
+        return replay(stored);
+    }
+
+    let response = next.run(request).await;
+    let streamed = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"text/event-stream"));
+    // A retry should run again after these rather than repeat the failure
+    let transient =
+        response.status().is_server_error() || response.status() == StatusCode::TOO_MANY_REQUESTS;
+    if transient || streamed {
+        idempotency.release(scope, key).await?;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body: Bytes = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .map_err(|e| AppError::InternalServer(e.to_string()))?;
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    if let Err(e) = idempotency
+        .store(scope, key, parts.status, content_type, &body)
+        .await
+    {
+        // The request already ran; answer it, and let a retry run it again
+        tracing::warn!("Failed to store idempotent response: {}", e);
+        let _ = idempotency.release(scope, key).await;
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+fn replay(stored: StoredResponse) -> AppResult<Response> {
+    let Some(status) = stored.status_code else {
+        return Err(AppError::Conflict(
+            "A request with this Idempotency-Key is still being processed".to_string(),
+        ));
+    };
+
+    let mut response = Response::new(Body::from(stored.response_body.unwrap_or_default()));
+    *response.status_mut() =
+        StatusCode::from_u16(status as u16).map_err(|e| AppError::InternalServer(e.to_string()))?;
+    let headers = response.headers_mut();
+    if let Some(content_type) = stored
+        .content_type
+        .and_then(|value| HeaderValue::from_str(&value).ok())
+    {
+        headers.insert(header::CONTENT_TYPE, content_type);
+    }
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    Ok(response)
+}
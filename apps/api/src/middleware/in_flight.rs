@@ -0,0 +1,65 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::sync::Notify;
+
+/// Counts requests currently being handled so shutdown can wait for them
+#[derive(Clone, Default)]
+pub struct InFlightTracker {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    count: AtomicUsize,
+    idle: Notify,
+}
+
+impl InFlightTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.inner.count.load(Ordering::SeqCst)
+    }
+
+    /// Resolve once no requests are in flight
+    pub async fn wait_idle(&self) {
+        loop {
+            let idle = self.inner.idle.notified();
+            if self.in_flight() == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+}
+
+/// Decrements on drop, so cancelled or panicking handlers are still counted out
+struct InFlightGuard(InFlightTracker);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.0.inner.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.inner.idle.notify_waiters();
+        }
+    }
+}
+
+/// In-flight tracking middleware, applied to the whole router
+pub async fn track_in_flight(
+    State(tracker): State<InFlightTracker>,
+    request: Request,
+    next: Next,
+) -> Response {
+    tracker.inner.count.fetch_add(1, Ordering::SeqCst);
+    let _guard = InFlightGuard(tracker);
+    next.run(request).await
+}
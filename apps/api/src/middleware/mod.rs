@@ -1,3 +1,29 @@
+pub mod auth_failures;
+pub mod body_limit;
+pub mod client_ip;
+pub mod compression;
+pub mod concurrency;
+pub mod cors;
+pub mod idempotency;
+pub mod in_flight;
+pub mod pretty_json;
+pub mod problem_json;
+pub mod query_count;
 pub mod rate_limit;
+pub mod read_only;
+pub mod request_log;
+pub mod security_headers;
 
-pub use rate_limit::RateLimitLayer;
+pub use auth_failures::AuthFailureMonitor;
+pub use body_limit::BodyLimit;
+pub use client_ip::{ClientIp, TrustedProxies};
+pub use compression::compression;
+pub use concurrency::ConcurrencyLimit;
+pub use cors::{cors_from_config, permissive_cors, strict_cors};
+pub use idempotency::Idempotency;
+pub use in_flight::InFlightTracker;
+pub use query_count::QueryBudget;
+pub use rate_limit::{RateLimit, RateLimitExemptions, RateLimitLayer};
+pub use read_only::ReadOnlyMode;
+pub use request_log::log_requests;
+pub use security_headers::SecurityHeaders;
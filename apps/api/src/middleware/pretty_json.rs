@@ -0,0 +1,173 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::utils::error::AppError;
+
+/// Query parameter that asks for an indented JSON body
+pub const PRETTY_PARAM: &str = "pretty";
+
+/// Pretty-printing middleware, applied to the whole router
+///
+/// When `enabled` (development by default) a request with `?pretty=1` gets its
+/// JSON response re-indented; status, headers and key order are unchanged. The
+/// parameter is removed before routing so strict query extractors never see it.
+pub async fn pretty_print_json(
+    State(enabled): State<bool>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if !enabled {
+        return next.run(request).await;
+    }
+
+    let Some((uri, pretty)) = strip_pretty_param(request.uri()) else {
+        return next.run(request).await;
+    };
+    *request.uri_mut() = uri;
+
+    let response = next.run(request).await;
+    if !pretty || !is_json(&response) {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return AppError::InternalServer(format!(
+                "Could not buffer response for pretty-printing: {}",
+                e
+            ))
+            .into_response()
+        }
+    };
+
+    // Leave bodies that are not valid JSON exactly as the handler wrote them
+    if serde_json::from_slice::<serde::de::IgnoredAny>(&bytes).is_err() {
+        return Response::from_parts(parts, Body::from(bytes));
+    }
+
+    let mut response = Response::from_parts(parts, Body::from(indent(&bytes)));
+    response.headers_mut().remove(header::CONTENT_LENGTH);
+    response
+}
+
+/// The URI without `pretty`, and whether it asked for pretty output;
+/// `None` when the parameter is absent
+fn strip_pretty_param(uri: &Uri) -> Option<(Uri, bool)> {
+    let query = uri.query()?;
+    let mut pretty = None;
+    let kept: Vec<&str> = query
+        .split('&')
+        .filter(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            if key != PRETTY_PARAM {
+                return true;
+            }
+            pretty = Some(matches!(value, "" | "1" | "true"));
+            false
+        })
+        .collect();
+    let pretty = pretty?;
+
+    let path_and_query = if kept.is_empty() {
+        uri.path().to_string()
+    } else {
+        format!("{}?{}", uri.path(), kept.join("&"))
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(path_and_query.parse().ok()?);
+
+    Some((Uri::from_parts(parts).ok()?, pretty))
+}
+
+fn is_json(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+/// Re-indent compact JSON with two spaces, matching `serde_json::to_string_pretty`;
+/// works on the text so object key order is preserved
+fn indent(json: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(json.len() * 2);
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut bytes = json.iter().copied().peekable();
+
+    let newline = |out: &mut Vec<u8>, depth: usize| {
+        out.push(b'\n');
+        out.extend(std::iter::repeat_n(b' ', depth * 2));
+    };
+
+    while let Some(byte) = bytes.next() {
+        if in_string {
+            out.push(byte);
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => {
+                in_string = true;
+                out.push(byte);
+            }
+            b'{' | b'[' => {
+                out.push(byte);
+                while bytes.next_if(u8::is_ascii_whitespace).is_some() {}
+                if matches!(bytes.peek(), Some(b'}' | b']')) {
+                    out.push(bytes.next().unwrap());
+                } else {
+                    depth += 1;
+                    newline(&mut out, depth);
+                }
+            }
+            b'}' | b']' => {
+                depth = depth.saturating_sub(1);
+                newline(&mut out, depth);
+                out.push(byte);
+            }
+            b',' => {
+                out.push(byte);
+                newline(&mut out, depth);
+            }
+            b':' => out.extend_from_slice(b": "),
+            _ if byte.is_ascii_whitespace() => {}
+            _ => out.push(byte),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_indent_matches_serde_pretty_output() {
+        let value = json!({
+            "success": true,
+            "data": { "items": [1, 2], "empty": [], "none": {}, "text": "a, \"b\": {c}" }
+        });
+        let compact = serde_json::to_vec(&value).unwrap();
+
+        let pretty = String::from_utf8(indent(&compact)).unwrap();
+
+        assert_eq!(pretty, serde_json::to_string_pretty(&value).unwrap());
+    }
+}
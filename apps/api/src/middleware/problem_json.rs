@@ -0,0 +1,75 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::utils::error::{ErrorInfo, PROBLEM_JSON};
+
+/// Problem-details middleware, applied to the whole router
+///
+/// Error responses built by `AppError` are re-rendered as RFC 7807
+/// `application/problem+json` when the request's `Accept` header lists that
+/// type, or for every request when `by_default` is set. Successful responses
+/// and errors that did not come from `AppError` are passed through unchanged.
+pub async fn negotiate_problem_json(
+    State(by_default): State<bool>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !by_default && !accepts_problem_json(request.headers()) {
+        return next.run(request).await;
+    }
+
+    let instance = request.uri().path().to_string();
+    let response = next.run(request).await;
+    let Some(info) = response.extensions().get::<ErrorInfo>().cloned() else {
+        return response;
+    };
+
+    // Keep headers set along the way (Retry-After, request id, CORS)
+    let (parts, _) = response.into_parts();
+    let mut problem = info.problem_response(&instance);
+    for (name, value) in &parts.headers {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            problem.headers_mut().append(name, value.clone());
+        }
+    }
+    problem
+}
+
+fn accepts_problem_json(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|range| {
+            let media_type = range.split(';').next().unwrap_or_default().trim();
+            media_type.eq_ignore_ascii_case(PROBLEM_JSON)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_accepts_problem_json_among_other_types() {
+        assert!(accepts_problem_json(&accept(
+            "application/json, application/problem+json;q=0.9"
+        )));
+        assert!(accepts_problem_json(&accept("Application/Problem+JSON")));
+        assert!(!accepts_problem_json(&accept("application/json")));
+        assert!(!accepts_problem_json(&accept("*/*")));
+        assert!(!accepts_problem_json(&HeaderMap::new()));
+    }
+}
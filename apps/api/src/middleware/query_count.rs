@@ -0,0 +1,48 @@
+use axum::{
+    extract::{Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::database::query_count::{scope, QueryCounter};
+
+/// Response header carrying the number of queries a request executed
+pub const QUERY_COUNT_HEADER: &str = "x-db-query-count";
+
+/// Settings for per-request query counting
+#[derive(Clone, Copy, Debug, Default)]
+pub struct QueryBudget {
+    /// Report the count in `x-db-query-count` and `Server-Timing` (development only)
+    pub expose_header: bool,
+    /// Fail requests that attempt more queries than this
+    pub max_queries: Option<usize>,
+}
+
+/// Query counting middleware, applied to the whole router
+pub async fn track_query_count(
+    State(budget): State<QueryBudget>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let counter = Arc::new(QueryCounter::new(budget.max_queries));
+    let mut response = scope(counter.clone(), next.run(request)).await;
+
+    let count = counter.count();
+    if let Some(max) = budget.max_queries {
+        if count > max {
+            tracing::warn!("Request attempted {} database queries (cap {})", count, max);
+        }
+    }
+
+    if budget.expose_header {
+        let headers = response.headers_mut();
+        headers.insert(QUERY_COUNT_HEADER, HeaderValue::from(count));
+        if let Ok(timing) = HeaderValue::from_str(&format!("db;desc=\"{} queries\"", count)) {
+            headers.append("server-timing", timing);
+        }
+    }
+
+    response
+}
@@ -1,50 +1,281 @@
 use axum::{
-    extract::Request,
-    http::StatusCode,
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue},
     middleware::Next,
     response::{IntoResponse, Response},
 };
 use governor::{
-    clock::DefaultClock,
-    state::{InMemoryState, NotKeyed},
+    clock::{Clock, DefaultClock},
+    middleware::StateInformationMiddleware,
+    state::keyed::DefaultKeyedStateStore,
     Quota, RateLimiter,
 };
-use std::{num::NonZeroU32, sync::Arc, time::Duration};
+use ipnet::IpNet;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    num::NonZeroU32,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use super::client_ip::TrustedProxies;
+use crate::modules::api_keys::{ApiKeyService, API_KEY_HEADER};
+use crate::security::{self, SecurityEvent};
+use crate::utils::error::AppError;
+
+/// Requests the client may still make before it is limited
+pub const RATE_LIMIT_REMAINING_HEADER: HeaderName =
+    HeaderName::from_static("x-ratelimit-remaining");
+
+/// Token buckets per client IP; requests without a known peer share one
+pub type RateLimitLayer = Arc<
+    RateLimiter<IpAddr, DefaultKeyedStateStore<IpAddr>, DefaultClock, StateInformationMiddleware>,
+>;
+
+/// Buckets kept before idle (full) ones are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
 
-pub type RateLimitLayer = Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock>>;
+/// How long a verified exempt API key is trusted before it is checked again,
+/// which is also how long a revoked or unflagged key keeps its exemption
+const EXEMPT_KEY_TTL: Duration = Duration::from_secs(60);
 
-/// Create a rate limiter with specified requests per second
+/// Create a rate limiter allowing each client the given requests per second
 pub fn create_rate_limiter(requests_per_second: u32) -> RateLimitLayer {
-    let quota = Quota::per_second(
-        NonZeroU32::new(requests_per_second).expect("Invalid rate limit value"),
-    );
-    Arc::new(RateLimiter::direct(quota))
+    let quota =
+        Quota::per_second(NonZeroU32::new(requests_per_second).expect("Invalid rate limit value"));
+    Arc::new(RateLimiter::keyed(quota).with_middleware::<StateInformationMiddleware>())
+}
+
+/// Create a rate limiter allowing each client the given requests per minute,
+/// all of which may be spent at once
+pub fn create_rate_limiter_per_minute(requests_per_minute: u32) -> RateLimitLayer {
+    let quota =
+        Quota::per_minute(NonZeroU32::new(requests_per_minute).expect("Invalid rate limit value"));
+    Arc::new(RateLimiter::keyed(quota).with_middleware::<StateInformationMiddleware>())
+}
+
+/// Whether `prefix` covers `path` on whole segments
+pub(crate) fn matches_prefix(prefix: &str, path: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    path == prefix
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Traffic that never consumes rate-limit budget
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitExemptions {
+    path_prefixes: Vec<String>,
+    trusted_networks: Vec<IpNet>,
+}
+
+impl RateLimitExemptions {
+    pub fn new(path_prefixes: Vec<String>, trusted_networks: Vec<IpNet>) -> Self {
+        Self {
+            path_prefixes,
+            trusted_networks,
+        }
+    }
+
+    /// Prefixes match whole segments: `/health` covers `/health/db` but not `/healthz`
+    pub fn is_exempt_path(&self, path: &str) -> bool {
+        self.path_prefixes
+            .iter()
+            .any(|prefix| matches_prefix(prefix, path))
+    }
+
+    pub fn is_trusted_ip(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.trusted_networks
+            .iter()
+            .any(|network| network.contains(&ip))
+    }
+}
+
+/// Per-client limiters plus the exemptions checked before them
+#[derive(Clone)]
+pub struct RateLimit {
+    limiter: RateLimitLayer,
+    routes: Arc<Vec<(String, RateLimitLayer)>>,
+    trusted_proxies: TrustedProxies,
+    exemptions: Arc<RateLimitExemptions>,
+    api_keys: Option<Arc<ApiKeyService>>,
+    /// SHA-256 of recently verified exempt keys, with when they were verified
+    exempt_keys: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl RateLimit {
+    /// `limiter` applies to every path without a route override
+    pub fn new(limiter: RateLimitLayer) -> Self {
+        Self {
+            limiter,
+            routes: Arc::default(),
+            trusted_proxies: TrustedProxies::default(),
+            exemptions: Arc::default(),
+            api_keys: None,
+            exempt_keys: Arc::default(),
+        }
+    }
+
+    /// Use `limiter` instead of the default for paths under `prefix`; the
+    /// first matching override wins
+    pub fn with_route_limit(mut self, prefix: &str, limiter: RateLimitLayer) -> Self {
+        Arc::make_mut(&mut self.routes).push((prefix.to_string(), limiter));
+        self
+    }
+
+    /// Proxies whose `X-Forwarded-For` is believed; for any other peer the
+    /// header is ignored, so clients can't pick their own bucket
+    pub fn with_trusted_proxies(mut self, proxies: Vec<IpNet>) -> Self {
+        self.trusted_proxies = TrustedProxies::new(proxies);
+        self
+    }
+
+    pub fn with_exemptions(mut self, exemptions: RateLimitExemptions) -> Self {
+        self.exemptions = Arc::new(exemptions);
+        self
+    }
+
+    /// Let requests carrying a valid API key flagged `rate_limit_exempt` through
+    /// once they would otherwise be limited
+    pub fn with_exempt_api_keys(mut self, api_keys: ApiKeyService) -> Self {
+        self.api_keys = Some(Arc::new(api_keys));
+        self
+    }
+
+    /// Trusted IPs are matched on the TCP peer address only, never on
+    /// client-supplied headers such as `X-Forwarded-For`.
+    fn is_exempt(&self, request: &Request) -> bool {
+        if self.exemptions.is_exempt_path(request.uri().path()) {
+            return true;
+        }
+
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .is_some_and(|ConnectInfo(peer)| self.exemptions.is_trusted_ip(peer.ip()))
+    }
+
+    fn limiter_for(&self, path: &str) -> &RateLimitLayer {
+        self.routes
+            .iter()
+            .find(|(prefix, _)| matches_prefix(prefix, path))
+            .map_or(&self.limiter, |(_, limiter)| limiter)
+    }
+
+    fn client_ip(&self, request: &Request) -> Option<IpAddr> {
+        let ConnectInfo(peer) = request.extensions().get::<ConnectInfo<SocketAddr>>()?;
+        Some(self.trusted_proxies.client_ip(request.headers(), peer.ip()))
+    }
+
+    /// The key must verify fully; unknown, revoked or unflagged keys (and
+    /// JWTs) are limited like any other client. Only called for requests the
+    /// limiter rejected, so unthrottled clients never cost an Argon2 verify.
+    async fn is_exempt_api_key(&self, presented: Option<String>) -> bool {
+        let (Some(api_keys), Some(presented)) = (&self.api_keys, presented) else {
+            return false;
+        };
+
+        let digest = hex::encode(Sha256::digest(presented.as_bytes()));
+        {
+            let exempt_keys = self.exempt_keys.lock().unwrap();
+            if exempt_keys
+                .get(&digest)
+                .is_some_and(|at| at.elapsed() < EXEMPT_KEY_TTL)
+            {
+                return true;
+            }
+        }
+
+        let exempt = api_keys
+            .verify(&presented)
+            .await
+            .is_ok_and(|key| key.rate_limit_exempt);
+        if exempt {
+            let mut exempt_keys = self.exempt_keys.lock().unwrap();
+            exempt_keys.retain(|_, at| at.elapsed() < EXEMPT_KEY_TTL);
+            exempt_keys.insert(digest, Instant::now());
+        }
+        exempt
+    }
 }
 
 /// Rate limiting middleware
+///
+/// Each client IP gets its own token bucket per limiter. Limited responses
+/// carry `X-RateLimit-Remaining`; a rejected request gets 429 with
+/// `Retry-After` (whole seconds until a token is available), unless it carries
+/// an exempt API key.
 pub async fn rate_limit_middleware(
-    limiter: axum::extract::State<RateLimitLayer>,
+    State(limit): State<RateLimit>,
     request: Request,
     next: Next,
 ) -> Response {
-    match limiter.check() {
-        Ok(_) => next.run(request).await,
-        Err(_) => (
-            StatusCode::TOO_MANY_REQUESTS,
-            "Rate limit exceeded. Please try again later.",
-        )
-            .into_response(),
+    if limit.is_exempt(&request) {
+        return next.run(request).await;
+    }
+
+    let client_ip = limit.client_ip(&request);
+    let limiter = limit.limiter_for(request.uri().path());
+    if limiter.len() > MAX_TRACKED_CLIENTS {
+        limiter.retain_recent();
+    }
+
+    match limiter.check_key(&client_ip.unwrap_or(Ipv4Addr::UNSPECIFIED.into())) {
+        Ok(state) => {
+            let mut response = next.run(request).await;
+            set_remaining(response.headers_mut(), state.remaining_burst_capacity());
+            response
+        }
+        Err(not_until) => {
+            let presented_key = request
+                .headers()
+                .get(API_KEY_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            if limit.is_exempt_api_key(presented_key).await {
+                return next.run(request).await;
+            }
+
+            security::emit(SecurityEvent::RateLimitExceeded {
+                client_ip,
+                method: request.method().as_str(),
+                path: request.uri().path(),
+            });
+
+            let wait = not_until.wait_time_from(DefaultClock::default().now());
+            let retry_after = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+
+            let mut response = AppError::RateLimitExceeded.into_response();
+            set_remaining(response.headers_mut(), 0);
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after.max(1)));
+            response
+        }
     }
 }
 
+fn set_remaining(headers: &mut HeaderMap, remaining: u32) {
+    headers.insert(RATE_LIMIT_REMAINING_HEADER, HeaderValue::from(remaining));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
 
     #[test]
     fn test_create_rate_limiter() {
         let limiter = create_rate_limiter(100);
-        assert!(limiter.check().is_ok());
+        assert!(limiter.check_key(&ip("192.0.2.1")).is_ok());
     }
 
     #[tokio::test]
@@ -52,14 +283,42 @@ mod tests {
         let limiter = create_rate_limiter(2);
 
         // First two requests should succeed
-        assert!(limiter.check().is_ok());
-        assert!(limiter.check().is_ok());
+        assert!(limiter.check_key(&ip("192.0.2.1")).is_ok());
+        assert!(limiter.check_key(&ip("192.0.2.1")).is_ok());
 
         // Third request should fail
-        assert!(limiter.check().is_err());
+        assert!(limiter.check_key(&ip("192.0.2.1")).is_err());
 
         // Wait a bit and try again
         tokio::time::sleep(Duration::from_secs(1)).await;
-        assert!(limiter.check().is_ok());
+        assert!(limiter.check_key(&ip("192.0.2.1")).is_ok());
+    }
+
+    #[test]
+    fn test_clients_have_separate_buckets() {
+        let limiter = create_rate_limiter(1);
+
+        assert!(limiter.check_key(&ip("192.0.2.1")).is_ok());
+        assert!(limiter.check_key(&ip("192.0.2.1")).is_err());
+        assert!(limiter.check_key(&ip("192.0.2.2")).is_ok());
+    }
+
+    #[test]
+    fn test_exempt_paths_match_whole_segments() {
+        let exemptions = RateLimitExemptions::new(vec!["/health".to_string()], vec![]);
+
+        assert!(exemptions.is_exempt_path("/health"));
+        assert!(exemptions.is_exempt_path("/health/db"));
+        assert!(!exemptions.is_exempt_path("/healthz"));
+        assert!(!exemptions.is_exempt_path("/users"));
+    }
+
+    #[test]
+    fn test_trusted_networks_match_cidr_and_mapped_v4() {
+        let exemptions = RateLimitExemptions::new(vec![], vec!["10.0.0.0/8".parse().unwrap()]);
+
+        assert!(exemptions.is_trusted_ip("10.1.2.3".parse().unwrap()));
+        assert!(exemptions.is_trusted_ip("::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!exemptions.is_trusted_ip("192.168.1.1".parse().unwrap()));
     }
 }
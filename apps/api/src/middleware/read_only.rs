@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::utils::error::AppError;
+
+/// Admin endpoint that toggles read-only mode; stays writable so it can be turned off
+pub const READ_ONLY_ADMIN_PATH: &str = "/api/v1/admin/read-only";
+
+/// Shared maintenance switch that rejects mutating requests while enabled
+#[derive(Clone, Default)]
+pub struct ReadOnlyMode {
+    enabled: Arc<AtomicBool>,
+}
+
+impl ReadOnlyMode {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(enabled)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+}
+
+fn is_write(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    )
+}
+
+/// Read-only middleware, applied to the whole router; reads and health checks pass through
+pub async fn reject_writes_when_read_only(
+    State(mode): State<ReadOnlyMode>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if mode.is_enabled()
+        && is_write(request.method())
+        && request.uri().path() != READ_ONLY_ADMIN_PATH
+    {
+        return AppError::ReadOnlyMode.into_response();
+    }
+
+    next.run(request).await
+}
@@ -0,0 +1,62 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::time::Instant;
+use tracing::{field, Instrument};
+
+/// Tracing target of the per-request span and its completion event
+pub const REQUEST_LOG_TARGET: &str = "http";
+
+/// Header carrying the request id, set by `SetRequestIdLayer` further out
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Log one line per request under a `request` span carrying the method,
+/// route, and `x-request-id`, so everything logged while handling the
+/// request (security events, service warnings) names the same request id.
+///
+/// The route is the template (`/users/{id}`) when one matched. Completed
+/// requests log at INFO, 4xx at WARN and 5xx at ERROR.
+pub async fn log_requests(request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("-")
+        .to_string();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| request.uri().path(), MatchedPath::as_str)
+        .to_string();
+
+    let span = tracing::info_span!(
+        target: REQUEST_LOG_TARGET,
+        "request",
+        method = %request.method(),
+        path = %path,
+        request_id = %request_id,
+        status = field::Empty,
+        latency_ms = field::Empty,
+    );
+
+    let response = next.run(request).instrument(span.clone()).await;
+
+    let status = response.status();
+    let latency_ms = started.elapsed().as_millis() as u64;
+    span.record("status", status.as_u16());
+    span.record("latency_ms", latency_ms);
+
+    let _entered = span.enter();
+    if status.is_server_error() {
+        tracing::error!(target: REQUEST_LOG_TARGET, event = "request_completed", "Request failed");
+    } else if status.is_client_error() {
+        tracing::warn!(target: REQUEST_LOG_TARGET, event = "request_completed", "Request rejected");
+    } else {
+        tracing::info!(target: REQUEST_LOG_TARGET, event = "request_completed", "Request completed");
+    }
+
+    response
+}
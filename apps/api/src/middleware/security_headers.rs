@@ -0,0 +1,119 @@
+use axum::{
+    extract::{Request, State},
+    http::{
+        header::{
+            CONTENT_SECURITY_POLICY, REFERRER_POLICY, STRICT_TRANSPORT_SECURITY,
+            X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+        },
+        HeaderValue,
+    },
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use super::rate_limit::matches_prefix;
+use crate::config::{Environment, ServerConfig};
+
+/// Hardening headers added to every response
+#[derive(Clone)]
+pub struct SecurityHeaders {
+    hsts: Option<HeaderValue>,
+    csp: Option<HeaderValue>,
+    csp_exempt_paths: Arc<Vec<String>>,
+}
+
+impl SecurityHeaders {
+    /// Only the always-on headers: nosniff, frame denial and no referrer
+    pub fn new() -> Self {
+        Self {
+            hsts: None,
+            csp: None,
+            csp_exempt_paths: Arc::new(Vec::new()),
+        }
+    }
+
+    /// HSTS is only sent in production, where TLS is terminated in front of
+    /// the API; a zero max-age or empty policy turns that header off
+    pub fn from_config(config: &ServerConfig) -> Self {
+        let headers = Self::new().with_csp_exempt_paths(config.csp_exempt_paths.clone());
+        let headers = match config.content_security_policy.as_deref() {
+            Some(policy) => headers.with_content_security_policy(policy),
+            None => headers,
+        };
+
+        if config.environment == Environment::Production && config.hsts_max_age_secs > 0 {
+            headers.with_hsts(config.hsts_max_age_secs)
+        } else {
+            headers
+        }
+    }
+
+    pub fn with_hsts(mut self, max_age_secs: u64) -> Self {
+        self.hsts = Some(
+            HeaderValue::from_str(&format!("max-age={}; includeSubDomains", max_age_secs))
+                .expect("HSTS max-age is a valid header value"),
+        );
+        self
+    }
+
+    pub fn with_content_security_policy(mut self, policy: &str) -> Self {
+        self.csp = Some(HeaderValue::from_str(policy).expect("Invalid Content-Security-Policy"));
+        self
+    }
+
+    /// Path prefixes served without a Content-Security-Policy, for pages
+    /// that rely on inline scripts (Swagger UI, GraphiQL)
+    pub fn with_csp_exempt_paths(mut self, paths: Vec<String>) -> Self {
+        self.csp_exempt_paths = Arc::new(paths);
+        self
+    }
+
+    fn csp_for(&self, path: &str) -> Option<&HeaderValue> {
+        if self
+            .csp_exempt_paths
+            .iter()
+            .any(|prefix| matches_prefix(prefix, path))
+        {
+            return None;
+        }
+        self.csp.as_ref()
+    }
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Security headers middleware, applied to the whole router; headers a
+/// handler already set are left alone
+pub async fn add_security_headers(
+    State(security): State<SecurityHeaders>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let csp = security.csp_for(request.uri().path()).cloned();
+
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    headers
+        .entry(X_CONTENT_TYPE_OPTIONS)
+        .or_insert(HeaderValue::from_static("nosniff"));
+    headers
+        .entry(X_FRAME_OPTIONS)
+        .or_insert(HeaderValue::from_static("DENY"));
+    headers
+        .entry(REFERRER_POLICY)
+        .or_insert(HeaderValue::from_static("no-referrer"));
+    if let Some(hsts) = security.hsts {
+        headers.entry(STRICT_TRANSPORT_SECURITY).or_insert(hsts);
+    }
+    if let Some(csp) = csp {
+        headers.entry(CONTENT_SECURITY_POLICY).or_insert(csp);
+    }
+
+    response
+}
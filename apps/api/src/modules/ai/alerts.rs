@@ -0,0 +1,140 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use uuid::Uuid;
+
+use crate::modules::webhooks::{WebhookEvent, WebhookService};
+
+/// A token threshold crossed within the current period
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UsageAlert {
+    /// `None` for a global (all users) threshold
+    pub user_id: Option<Uuid>,
+    pub threshold_tokens: u64,
+    pub tokens_used: u64,
+    pub period_started_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct PeriodUsage {
+    period: i64,
+    global: u64,
+    users: HashMap<Uuid, u64>,
+}
+
+/// Raises an alert the first time per-user or global token usage crosses a
+/// configured threshold within a fixed period (e.g. a UTC day)
+///
+/// Totals only grow within a period and reset when the next one starts, so
+/// every threshold fires at most once per period.
+#[derive(Clone)]
+pub struct UsageAlerts {
+    per_user_tokens: Arc<Vec<u64>>,
+    global_tokens: Arc<Vec<u64>>,
+    period_secs: i64,
+    webhooks: Option<WebhookService>,
+    usage: Arc<Mutex<PeriodUsage>>,
+}
+
+impl UsageAlerts {
+    pub fn new(per_user_tokens: Vec<u64>, global_tokens: Vec<u64>, period: Duration) -> Self {
+        Self {
+            per_user_tokens: Arc::new(per_user_tokens),
+            global_tokens: Arc::new(global_tokens),
+            period_secs: period.as_secs().max(1) as i64,
+            webhooks: None,
+            usage: Arc::default(),
+        }
+    }
+
+    /// Also queue an `ai.usage_threshold_crossed` webhook for every alert
+    pub fn with_webhooks(mut self, webhooks: WebhookService) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    /// Add `tokens` to the user's and the global total, returning the
+    /// thresholds this usage crossed
+    pub fn record(&self, user_id: Uuid, tokens: u64) -> Vec<UsageAlert> {
+        let now = Utc::now();
+        let period = now.timestamp().div_euclid(self.period_secs);
+        let period_started_at = Utc
+            .timestamp_opt(period * self.period_secs, 0)
+            .single()
+            .unwrap_or(now);
+
+        let mut usage = self.usage.lock().unwrap();
+        if usage.period != period {
+            *usage = PeriodUsage {
+                period,
+                ..Default::default()
+            };
+        }
+
+        let user_total = usage.users.entry(user_id).or_default();
+        let user_before = *user_total;
+        *user_total += tokens;
+        let user_after = *user_total;
+
+        let global_before = usage.global;
+        usage.global += tokens;
+        let global_after = usage.global;
+
+        let mut alerts = Vec::new();
+        let mut crossed = |thresholds: &[u64], before: u64, after: u64, user_id: Option<Uuid>| {
+            for &threshold in thresholds {
+                if before < threshold && threshold <= after {
+                    alerts.push(UsageAlert {
+                        user_id,
+                        threshold_tokens: threshold,
+                        tokens_used: after,
+                        period_started_at,
+                    });
+                }
+            }
+        };
+        crossed(
+            &self.per_user_tokens,
+            user_before,
+            user_after,
+            Some(user_id),
+        );
+        crossed(&self.global_tokens, global_before, global_after, None);
+
+        alerts
+    }
+
+    /// Record usage and emit every crossed threshold as a log event and,
+    /// when configured, a webhook
+    pub async fn track(&self, user_id: Uuid, tokens: u64) {
+        for alert in self.record(user_id, tokens) {
+            tracing::warn!(
+                user_id = ?alert.user_id,
+                threshold_tokens = alert.threshold_tokens,
+                tokens_used = alert.tokens_used,
+                period_started_at = %alert.period_started_at,
+                "AI usage threshold crossed"
+            );
+
+            if let Some(webhooks) = &self.webhooks {
+                webhooks
+                    .notify(
+                        WebhookEvent::AiUsageThresholdCrossed,
+                        serde_json::to_value(&alert).unwrap_or_default(),
+                    )
+                    .await;
+            }
+        }
+    }
+}
+
+impl Default for UsageAlerts {
+    /// No thresholds, daily periods
+    fn default() -> Self {
+        Self::new(Vec::new(), Vec::new(), Duration::from_secs(86_400))
+    }
+}
@@ -1,7 +1,11 @@
+pub mod alerts;
 pub mod model;
 pub mod providers;
-pub mod service;
 pub mod routes;
+pub mod service;
 pub mod streaming;
+pub mod usage;
 
-pub use routes::routes;
+pub use alerts::UsageAlerts;
+pub use routes::{routes, service_routes};
+pub use usage::AiUsageLog;
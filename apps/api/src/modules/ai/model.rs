@@ -1,20 +1,36 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use validator::Validate;
 
-#[derive(Debug, Deserialize, Validate)]
+use crate::modules::users::model::UserRole;
+use crate::utils::error::{AppError, AppResult};
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct ChatRequest {
+    /// Shorthand for a final user turn, appended to `messages` (`prompt` is
+    /// accepted as an alias)
     #[validate(length(min = 1, message = "Message cannot be empty"))]
-    pub message: String,
+    #[serde(default, alias = "prompt")]
+    pub message: Option<String>,
+
+    /// The conversation, oldest first
+    #[serde(default)]
+    pub messages: Vec<Message>,
 
+    /// Provider name as sent; resolved with `AiProvider::parse`, or implied by
+    /// the configured default model when absent
     #[serde(default)]
-    pub provider: AiProvider,
+    pub provider: Option<String>,
 
     #[serde(default)]
     pub model: Option<String>,
 
+    #[validate(range(min = 0.0, max = 2.0, message = "temperature must be between 0 and 2"))]
     #[serde(default)]
     pub temperature: Option<f32>,
 
+    /// At most `AI_MAX_TOKENS`
+    #[validate(range(min = 1, message = "max_tokens must be at least 1"))]
     #[serde(default)]
     pub max_tokens: Option<u32>,
 
@@ -23,9 +39,351 @@ pub struct ChatRequest {
 
     #[serde(default)]
     pub system_prompt: Option<String>,
+
+    /// Functions the model may ask the client to call (`functions` is accepted as an alias)
+    #[serde(default, alias = "functions")]
+    pub tools: Vec<ToolDefinition>,
+}
+
+/// A function offered to the model, described by a JSON Schema
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ToolDefinition {
+    pub name: String,
+
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// JSON Schema for the arguments object
+    #[serde(default = "empty_parameters")]
+    #[schema(value_type = Object)]
+    pub parameters: serde_json::Value,
+}
+
+fn empty_parameters() -> serde_json::Value {
+    serde_json::json!({ "type": "object", "properties": {} })
+}
+
+/// A tool call requested by the model; running it is left to the client
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    #[schema(value_type = Object)]
+    pub arguments: serde_json::Value,
+}
+
+impl ToolCall {
+    /// Build a tool call from arguments the provider returned as a JSON string
+    pub fn from_raw_arguments(id: String, name: String, arguments: &str) -> AppResult<Self> {
+        let arguments = if arguments.trim().is_empty() {
+            serde_json::json!({})
+        } else {
+            serde_json::from_str(arguments).map_err(|e| {
+                AppError::ExternalService(format!(
+                    "Model returned invalid arguments for tool '{}': {}",
+                    name, e
+                ))
+            })?
+        };
+
+        Ok(Self {
+            id,
+            name,
+            arguments,
+        })
+    }
+}
+
+/// Size bounds applied to a conversation before it reaches a provider
+#[derive(Debug, Clone, Copy)]
+pub struct ConversationLimits {
+    pub max_messages: usize,
+    pub max_total_chars: usize,
+    /// Largest `max_tokens` a request may ask for
+    pub max_tokens: u32,
+}
+
+impl ChatRequest {
+    /// Resolve the requested provider, rejecting unknown names; `default`
+    /// applies when none was requested
+    pub fn provider(&self, default: AiProvider) -> AppResult<AiProvider> {
+        self.provider
+            .as_deref()
+            .map_or(Ok(default), AiProvider::parse)
+    }
+
+    /// Messages sent to the provider: `messages`, then `message` as a user turn
+    pub fn conversation(&self) -> Vec<Message> {
+        let mut conversation = self.messages.clone();
+        if let Some(message) = &self.message {
+            conversation.push(Message {
+                role: Role::User,
+                content: message.clone(),
+            });
+        }
+        conversation
+    }
+
+    pub fn message_count(&self) -> usize {
+        self.messages.len() + usize::from(self.message.is_some())
+    }
+
+    /// Characters across the system prompt and the conversation
+    pub fn total_chars(&self) -> usize {
+        let messages: usize = self
+            .messages
+            .iter()
+            .map(|message| message.content.chars().count())
+            .sum();
+        let message = self
+            .message
+            .as_deref()
+            .map_or(0, |message| message.chars().count());
+        let system = self
+            .system_prompt
+            .as_deref()
+            .map_or(0, |prompt| prompt.chars().count());
+
+        messages + message + system
+    }
+
+    /// Reject empty conversations and ones that exceed the configured bounds
+    pub fn check_limits(&self, limits: &ConversationLimits) -> AppResult<()> {
+        let invalid = |field: &str, rule: &'static str, message: String| AppError::InvalidField {
+            field: field.to_string(),
+            rule,
+            message,
+        };
+
+        if self.message_count() == 0 {
+            return Err(invalid(
+                "messages",
+                "required",
+                "Provide message (or prompt) or messages".to_string(),
+            ));
+        }
+        if self.messages.iter().any(|m| m.content.is_empty()) {
+            return Err(invalid(
+                "messages",
+                "length",
+                "messages cannot contain empty content".to_string(),
+            ));
+        }
+        if let Some(max_tokens) = self.max_tokens.filter(|&n| n > limits.max_tokens) {
+            return Err(invalid(
+                "max_tokens",
+                "range",
+                format!(
+                    "max_tokens {} exceeds the limit of {}",
+                    max_tokens, limits.max_tokens
+                ),
+            ));
+        }
+
+        let count = self.message_count();
+        if count > limits.max_messages {
+            return Err(AppError::ConversationTooLarge(format!(
+                "{} messages exceeds the limit of {}",
+                count, limits.max_messages
+            )));
+        }
+
+        let chars = self.total_chars();
+        if chars > limits.max_total_chars {
+            return Err(AppError::ConversationTooLarge(format!(
+                "{} characters exceeds the limit of {}",
+                chars, limits.max_total_chars
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Models each role may request; admins are never restricted
+#[derive(Debug, Clone, Default)]
+pub struct ModelAllowlist {
+    pub user: Option<Vec<String>>,
+    pub moderator: Option<Vec<String>>,
+}
+
+impl ModelAllowlist {
+    pub fn check(&self, role: UserRole, model: &str) -> AppResult<()> {
+        let allowed = match role {
+            UserRole::Admin => None,
+            UserRole::User => self.user.as_ref(),
+            UserRole::Moderator => self.moderator.as_ref(),
+        };
+
+        match allowed {
+            Some(models)
+                if !models
+                    .iter()
+                    .any(|m| m.trim().eq_ignore_ascii_case(model.trim())) =>
+            {
+                Err(AppError::ModelNotAllowed(format!(
+                    "'{}' is not available for the {} role",
+                    model, role
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Models each provider accepts; `None` leaves a provider unrestricted
+#[derive(Debug, Clone, Default)]
+pub struct ProviderModels {
+    pub openai: Option<Vec<String>>,
+    pub anthropic: Option<Vec<String>>,
+    pub local: Option<Vec<String>>,
+}
+
+impl ProviderModels {
+    /// Provider serving `model`: the first whose allowlist names it, else
+    /// the one `AiProvider::for_model` infers from the name
+    pub fn implied_provider(&self, model: &str) -> AiProvider {
+        AiProvider::ALL
+            .into_iter()
+            .find(|&provider| {
+                self.models(provider).is_some_and(|models| {
+                    models
+                        .iter()
+                        .any(|m| m.trim().eq_ignore_ascii_case(model.trim()))
+                })
+            })
+            .unwrap_or_else(|| AiProvider::for_model(model))
+    }
+
+    /// Models `provider` offers: its allowlist when set, else every model
+    /// in `KNOWN_MODELS` for it
+    pub fn catalog(&self, provider: AiProvider) -> Vec<ModelInfo> {
+        match self.models(provider) {
+            Some(models) => models
+                .iter()
+                .map(|model| ModelInfo::describe(provider, model))
+                .collect(),
+            None => ModelInfo::known(provider),
+        }
+    }
+
+    fn models(&self, provider: AiProvider) -> Option<&Vec<String>> {
+        match provider {
+            AiProvider::Openai => self.openai.as_ref(),
+            AiProvider::Anthropic => self.anthropic.as_ref(),
+            AiProvider::Local => self.local.as_ref(),
+        }
+    }
+
+    /// Reject a model the provider does not offer with `INVALID_MODEL`
+    /// listing the ones it does
+    pub fn check(&self, provider: AiProvider, model: &str) -> AppResult<()> {
+        match self.models(provider) {
+            Some(models)
+                if !models
+                    .iter()
+                    .any(|m| m.trim().eq_ignore_ascii_case(model.trim())) =>
+            {
+                Err(AppError::InvalidModel {
+                    model: model.to_string(),
+                    provider: provider.to_string(),
+                    valid_models: models.clone(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A model clients may pick, as listed by `GET /ai/models`
+#[derive(Debug, Clone, Serialize, PartialEq, ToSchema)]
+pub struct ModelInfo {
+    pub id: String,
+    pub provider: String,
+    /// Tokens of prompt and completion together; `None` when not known
+    pub context_window: Option<u32>,
+    pub supports_embeddings: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Models the API knows the details of: provider, id, context window and
+/// whether it embeds
+const KNOWN_MODELS: &[(AiProvider, &str, Option<u32>, bool)] = &[
+    (AiProvider::Openai, "gpt-4o", Some(128_000), false),
+    (AiProvider::Openai, "gpt-4o-mini", Some(128_000), false),
+    (AiProvider::Openai, "gpt-4-turbo", Some(128_000), false),
+    (AiProvider::Openai, "gpt-4", Some(8_192), false),
+    (AiProvider::Openai, "gpt-3.5-turbo", Some(16_385), false),
+    (
+        AiProvider::Openai,
+        "text-embedding-3-small",
+        Some(8_191),
+        true,
+    ),
+    (
+        AiProvider::Openai,
+        "text-embedding-3-large",
+        Some(8_191),
+        true,
+    ),
+    (
+        AiProvider::Anthropic,
+        "claude-3-5-sonnet-20241022",
+        Some(200_000),
+        false,
+    ),
+    (
+        AiProvider::Anthropic,
+        "claude-3-5-haiku-20241022",
+        Some(200_000),
+        false,
+    ),
+    (
+        AiProvider::Anthropic,
+        "claude-3-opus-20240229",
+        Some(200_000),
+        false,
+    ),
+    (AiProvider::Local, "local-model", None, false),
+];
+
+impl ModelInfo {
+    /// Details of `model` on `provider`; a model missing from `KNOWN_MODELS`
+    /// gets no context window and is assumed not to embed
+    pub fn describe(provider: AiProvider, model: &str) -> Self {
+        let known = KNOWN_MODELS
+            .iter()
+            .find(|(p, id, _, _)| *p == provider && id.eq_ignore_ascii_case(model.trim()));
+
+        Self {
+            id: known.map_or_else(|| model.trim().to_string(), |(_, id, _, _)| id.to_string()),
+            provider: provider.to_string(),
+            context_window: known.and_then(|(_, _, window, _)| *window),
+            supports_embeddings: known.is_some_and(|(_, _, _, embeds)| *embeds),
+        }
+    }
+
+    /// Every known model of `provider`
+    pub fn known(provider: AiProvider) -> Vec<Self> {
+        KNOWN_MODELS
+            .iter()
+            .filter(|(p, _, _, _)| *p == provider)
+            .map(|(_, id, _, _)| Self::describe(provider, id))
+            .collect()
+    }
+}
+
+/// Trim a model name and, when `lowercase`, fold its case so `GPT-4` and
+/// ` gpt-4 ` name the same model
+pub fn normalize_model_name(model: &str, lowercase: bool) -> String {
+    let model = model.trim();
+    if lowercase {
+        model.to_lowercase()
+    } else {
+        model.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum AiProvider {
     #[default]
@@ -34,43 +392,183 @@ pub enum AiProvider {
     Local,
 }
 
-#[derive(Debug, Serialize)]
+impl AiProvider {
+    pub const ALL: [AiProvider; 3] = [AiProvider::Openai, AiProvider::Anthropic, AiProvider::Local];
+
+    /// Parse a provider from request input, ignoring case and surrounding
+    /// whitespace; unknown names fail with `INVALID_PROVIDER` listing the
+    /// accepted values
+    pub fn parse(provider: &str) -> AppResult<Self> {
+        let normalized = provider.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.to_string() == normalized)
+            .ok_or_else(|| AppError::InvalidProvider {
+                provider: provider.to_string(),
+                valid_providers: Self::ALL.iter().map(ToString::to_string).collect(),
+            })
+    }
+
+    /// Guess the provider from a model's name: `claude-*` is Anthropic,
+    /// `local-*` the local runtime, anything else OpenAI
+    pub fn for_model(model: &str) -> Self {
+        let model = model.trim().to_lowercase();
+        if model.starts_with("claude") {
+            AiProvider::Anthropic
+        } else if model.starts_with("local") {
+            AiProvider::Local
+        } else {
+            AiProvider::Openai
+        }
+    }
+}
+
+impl std::fmt::Display for AiProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AiProvider::Openai => write!(f, "openai"),
+            AiProvider::Anthropic => write!(f, "anthropic"),
+            AiProvider::Local => write!(f, "local"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ChatResponse {
     pub response: String,
     pub provider: String,
     pub model: String,
     pub tokens_used: Option<u32>,
+    /// Prompt/completion split of `tokens_used`, when the provider reports it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    /// Tool calls the model requested instead of (or alongside) a text reply
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct StreamChunk {
-    pub content: String,
-    pub done: bool,
+/// Tokens a single completion consumed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TokenUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+}
+
+impl TokenUsage {
+    /// Rough characters-per-token ratio for English text
+    const CHARS_PER_TOKEN: usize = 4;
+
+    pub fn new(prompt_tokens: u32, completion_tokens: u32) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+
+    /// Approximate usage from text lengths, for replies the provider did not
+    /// (or, when a stream was cut short, could not yet) report usage for
+    pub fn estimate(prompt_chars: usize, completion_chars: usize) -> Self {
+        let tokens = |chars: usize| chars.div_ceil(Self::CHARS_PER_TOKEN) as u32;
+        Self::new(tokens(prompt_chars), tokens(completion_chars))
+    }
 }
 
-#[derive(Debug, Deserialize, Validate)]
+/// One incremental piece of a streamed chat completion
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChatStreamChunk {
+    pub delta: String,
+    /// Set on the provider's last chunk, e.g. `stop` or `length`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<String>,
+    /// Usage so far as reported by the provider; later chunks supersede
+    /// earlier ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+}
+
+/// Either `text` (one embedding) or `texts` (a batch), never both
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct EmbeddingRequest {
-    #[validate(length(min = 1))]
-    pub text: String,
+    #[serde(default)]
+    pub text: Option<String>,
+
+    #[serde(default)]
+    pub texts: Option<Vec<String>>,
 
     #[serde(default)]
     pub model: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+/// What an `EmbeddingRequest` asks to embed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmbeddingInput {
+    Single(String),
+    Batch(Vec<String>),
+}
+
+impl EmbeddingRequest {
+    /// Check the request shape: exactly one of `text`/`texts`, no empty
+    /// texts, and at most `max_batch_size` texts in a batch
+    pub fn input(&self, max_batch_size: usize) -> AppResult<EmbeddingInput> {
+        let invalid = |field: &str, rule: &'static str, message: &str| AppError::InvalidField {
+            field: field.to_string(),
+            rule,
+            message: message.to_string(),
+        };
+
+        match (&self.text, &self.texts) {
+            (Some(_), Some(_)) | (None, None) => Err(invalid(
+                "texts",
+                "exactly_one",
+                "Provide either text or texts",
+            )),
+            (Some(text), None) if text.is_empty() => {
+                Err(invalid("text", "length", "text cannot be empty"))
+            }
+            (Some(text), None) => Ok(EmbeddingInput::Single(text.clone())),
+            (None, Some(texts)) if texts.is_empty() => {
+                Err(invalid("texts", "length", "texts cannot be empty"))
+            }
+            (None, Some(texts)) if texts.len() > max_batch_size => Err(invalid(
+                "texts",
+                "max_items",
+                &format!("At most {} texts are allowed per request", max_batch_size),
+            )),
+            (None, Some(texts)) if texts.iter().any(String::is_empty) => Err(invalid(
+                "texts",
+                "length",
+                "texts cannot contain empty strings",
+            )),
+            (None, Some(texts)) => Ok(EmbeddingInput::Batch(texts.clone())),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct EmbeddingResponse {
     pub embedding: Vec<f32>,
     pub model: String,
     pub dimensions: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Vectors in the order of the request's `texts`, all `dimensions` long
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchEmbeddingResponse {
+    pub embeddings: Vec<Vec<f32>>,
+    pub model: String,
+    pub dimensions: usize,
+}
+
+/// One turn of a conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 pub struct Message {
     pub role: Role,
     pub content: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Role {
     System,
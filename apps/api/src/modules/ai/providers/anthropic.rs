@@ -1,89 +1,201 @@
-use anthropic_sdk::{Client, MessagesRequest, ContentBlock, Role};
+use async_anthropic::{
+    types::{
+        ContentBlockDelta, CreateMessagesRequest, Message, MessageContent, MessageRole as Role,
+        MessagesStreamEvent, Usage,
+    },
+    Client,
+};
 use async_trait::async_trait;
-
+use backoff::ExponentialBackoffBuilder;
+use futures::{future, stream, StreamExt};
+use std::time::Duration;
+
+use super::super::model::{
+    ChatRequest, ChatResponse, ChatStreamChunk, Role as MessageRole, TokenUsage,
+};
+use super::retry::RetryPolicy;
+use super::ChatStream;
 use crate::utils::error::{AppError, AppResult};
-use super::super::model::{ChatRequest, ChatResponse};
 
 pub struct AnthropicProvider {
     client: Client,
     default_model: String,
+    retry: RetryPolicy,
 }
 
 impl AnthropicProvider {
     pub fn new(api_key: String, default_model: String) -> Self {
-        let client = Client::new(api_key);
+        Self::with_client(Client::from_api_key(api_key), default_model)
+    }
+
+    /// Provider using `client`, e.g. one built with a different `base_url`
+    pub fn with_client(client: Client, default_model: String) -> Self {
+        // Retries are ours (`RetryPolicy`), as for OpenAI
+        let no_backoff = ExponentialBackoffBuilder::new()
+            .with_max_elapsed_time(Some(Duration::ZERO))
+            .build();
 
         Self {
-            client,
+            client: client.with_backoff(no_backoff),
             default_model,
+            retry: RetryPolicy::default(),
         }
     }
-}
 
-#[async_trait]
-impl super::AiProvider for AnthropicProvider {
-    async fn chat(&self, request: &ChatRequest) -> AppResult<ChatResponse> {
-        let model = request.model.as_ref()
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Messages request for `request`, along with the model it targets
+    fn messages_request(
+        &self,
+        request: &ChatRequest,
+    ) -> AppResult<(CreateMessagesRequest, String)> {
+        let model = request
+            .model
+            .as_ref()
             .unwrap_or(&self.default_model)
             .clone();
 
-        let mut messages_request = MessagesRequest::new(
-            model.clone(),
-            vec![ContentBlock::Text {
-                text: request.message.clone(),
-            }],
-        );
-
-        // Set system prompt if provided
-        if let Some(system_prompt) = &request.system_prompt {
-            messages_request = messages_request.with_system(system_prompt.clone());
+        if !request.tools.is_empty() {
+            return Err(AppError::BadRequest(
+                "Tool calling is not supported by the Anthropic provider yet".to_string(),
+            ));
         }
 
-        // Set temperature if provided
-        if let Some(temp) = request.temperature {
-            messages_request = messages_request.with_temperature(temp);
+        // Anthropic takes system instructions apart from the turns, so
+        // system messages join the system prompt in order
+        let mut system: Vec<String> = request.system_prompt.iter().cloned().collect();
+        let mut messages = Vec::new();
+        for message in request.conversation() {
+            let role = match message.role {
+                MessageRole::System => {
+                    system.push(message.content);
+                    continue;
+                }
+                MessageRole::User => Role::User,
+                MessageRole::Assistant => Role::Assistant,
+            };
+            messages.push(Message {
+                role,
+                content: message.content.into(),
+            });
         }
 
-        // Set max tokens if provided
-        if let Some(max_tokens) = request.max_tokens {
-            messages_request = messages_request.with_max_tokens(max_tokens as usize);
-        } else {
+        let messages_request = CreateMessagesRequest {
+            messages,
+            model: model.clone(),
             // Anthropic requires max_tokens, so set a default
-            messages_request = messages_request.with_max_tokens(2048);
-        }
+            max_tokens: request
+                .max_tokens
+                .map_or(2048, |n| n.min(i32::MAX as u32) as i32),
+            metadata: None,
+            stop_sequences: None,
+            stream: false,
+            temperature: request.temperature,
+            tool_choice: None,
+            tools: None,
+            top_k: None,
+            top_p: None,
+            system: (!system.is_empty()).then(|| system.join("\n\n")),
+        };
+
+        Ok((messages_request, model))
+    }
+}
+
+#[async_trait]
+impl super::AiProvider for AnthropicProvider {
+    async fn chat(&self, request: &ChatRequest) -> AppResult<ChatResponse> {
+        let (messages_request, model) = self.messages_request(request)?;
 
         let response = self
-            .client
-            .messages(messages_request)
-            .await
-            .map_err(|e| AppError::ExternalService(format!("Anthropic API error: {}", e)))?;
+            .retry
+            .run("anthropic", || async {
+                self.client
+                    .messages()
+                    .create(messages_request.clone())
+                    .await
+            })
+            .await?;
 
         // Extract text from response
         let content = response
             .content
             .iter()
-            .filter_map(|block| match block {
-                ContentBlock::Text { text } => Some(text.clone()),
-                _ => None,
-            })
+            .flatten()
+            .filter_map(MessageContent::as_text)
+            .map(|text| text.text.as_str())
             .collect::<Vec<_>>()
             .join("\n");
 
         if content.is_empty() {
-            return Err(AppError::ExternalService("No response from Anthropic".to_string()));
+            return Err(AppError::ExternalService(
+                "No response from Anthropic".to_string(),
+            ));
         }
 
-        // Calculate total tokens used
-        let tokens_used = response.usage.map(|u| (u.input_tokens + u.output_tokens) as u32);
+        let usage = response.usage.map(|u| {
+            TokenUsage::new(
+                u.input_tokens.unwrap_or_default(),
+                u.output_tokens.unwrap_or_default(),
+            )
+        });
 
         Ok(ChatResponse {
             response: content,
             provider: "anthropic".to_string(),
             model,
-            tokens_used,
+            tokens_used: usage.map(|u| u.total_tokens),
+            usage,
+            tool_calls: vec![],
         })
     }
 
+    async fn chat_stream(&self, request: &ChatRequest) -> AppResult<ChatStream> {
+        let (messages_request, _) = self.messages_request(request)?;
+
+        // Only opening the stream is retried, as for OpenAI. The SDK reports
+        // a failed request as the stream's first item, so opening waits for it
+        let (first, rest) = self
+            .retry
+            .run("anthropic", || async {
+                let mut events = self
+                    .client
+                    .messages()
+                    .create_stream(messages_request.clone())
+                    .await;
+                match events.next().await {
+                    Some(Err(e)) => Err(e),
+                    first => Ok((first, events)),
+                }
+            })
+            .await?;
+
+        // `message_start` reports the input tokens, each `message_delta` the
+        // output tokens so far
+        let mut prompt_tokens = 0;
+        Ok(stream::iter(first)
+            .chain(rest)
+            .map(move |event| match event {
+                Ok(MessagesStreamEvent::MessageStart { message, usage }) => {
+                    prompt_tokens = usage
+                        .or(message.usage)
+                        .and_then(|usage| usage.input_tokens)
+                        .unwrap_or_default();
+                    None
+                }
+                Ok(event) => stream_chunk_from(event, prompt_tokens).map(Ok),
+                Err(e) => Some(Err(AppError::ExternalService(format!(
+                    "Anthropic API error: {}",
+                    e
+                )))),
+            })
+            .filter_map(future::ready)
+            .boxed())
+    }
+
     async fn generate_embedding(&self, _text: &str, _model: Option<String>) -> AppResult<Vec<f32>> {
         // Anthropic doesn't provide embedding API as of now
         Err(AppError::ExternalService(
@@ -95,3 +207,25 @@ impl super::AiProvider for AnthropicProvider {
         "anthropic"
     }
 }
+
+/// Map one Anthropic stream event to a chunk; only text deltas and
+/// `message_delta` (which carries usage and the stop reason) yield one
+fn stream_chunk_from(event: MessagesStreamEvent, prompt_tokens: u32) -> Option<ChatStreamChunk> {
+    match event {
+        MessagesStreamEvent::ContentBlockDelta {
+            delta: ContentBlockDelta::TextDelta { text },
+            ..
+        } => Some(ChatStreamChunk {
+            delta: text,
+            ..Default::default()
+        }),
+        MessagesStreamEvent::MessageDelta { delta, usage } => Some(ChatStreamChunk {
+            delta: String::new(),
+            finish_reason: delta.stop_reason,
+            usage: usage.and_then(|Usage { output_tokens, .. }| {
+                output_tokens.map(|output| TokenUsage::new(prompt_tokens, output))
+            }),
+        }),
+        _ => None,
+    }
+}
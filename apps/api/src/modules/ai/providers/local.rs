@@ -1,7 +1,10 @@
 use async_trait::async_trait;
+use futures::{stream, StreamExt};
 
-use crate::utils::error::{AppError, AppResult};
-use super::super::model::{ChatRequest, ChatResponse};
+use super::super::model::{ChatRequest, ChatResponse, ChatStreamChunk};
+use super::super::streaming::chunk_response;
+use super::ChatStream;
+use crate::utils::error::AppResult;
 
 pub struct LocalProvider {
     model_path: String,
@@ -20,21 +23,51 @@ impl super::AiProvider for LocalProvider {
         // In production, you would use llama.cpp, Candle, or ONNX Runtime here
         // For now, we'll return a mock response
 
-        tracing::warn!("Local AI provider is not fully implemented yet");
+        tracing::warn!(
+            model_path = %self.model_path,
+            "Local AI provider is not fully implemented yet"
+        );
+
+        let last = request
+            .conversation()
+            .pop()
+            .map(|message| message.content)
+            .unwrap_or_default();
 
         Ok(ChatResponse {
-            response: format!(
-                "Local model response (mock): Received message: {}",
-                request.message
-            ),
+            response: format!("Local model response (mock): Received message: {}", last),
             provider: "local".to_string(),
-            model: request.model.clone()
+            model: request
+                .model
+                .clone()
                 .unwrap_or_else(|| "local-model".to_string()),
             tokens_used: None,
+            usage: None,
+            tool_calls: vec![],
         })
     }
 
-    async fn generate_embedding(&self, text: &str, _model: Option<String>) -> AppResult<Vec<f32>> {
+    async fn chat_stream(&self, request: &ChatRequest) -> AppResult<ChatStream> {
+        // Replays the mock reply in small pieces until local inference exists
+        let response = self.chat(request).await?;
+        let mut chunks: Vec<AppResult<ChatStreamChunk>> = chunk_response(response.response, 20)
+            .into_iter()
+            .map(|delta| {
+                Ok(ChatStreamChunk {
+                    delta,
+                    ..Default::default()
+                })
+            })
+            .collect();
+        chunks.push(Ok(ChatStreamChunk {
+            finish_reason: Some("stop".to_string()),
+            ..Default::default()
+        }));
+
+        Ok(stream::iter(chunks).boxed())
+    }
+
+    async fn generate_embedding(&self, _text: &str, _model: Option<String>) -> AppResult<Vec<f32>> {
         // Placeholder for local embedding generation
         tracing::warn!("Local embedding generation is not fully implemented yet");
 
@@ -1,15 +1,35 @@
-pub mod openai;
 pub mod anthropic;
 pub mod local;
+pub mod openai;
+pub mod retry;
 
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 
+use super::model::{ChatRequest, ChatResponse, ChatStreamChunk};
 use crate::utils::error::AppResult;
-use super::model::{ChatRequest, ChatResponse};
+
+/// Deltas of a streamed completion; dropping the stream aborts the upstream request
+pub type ChatStream = BoxStream<'static, AppResult<ChatStreamChunk>>;
 
 #[async_trait]
 pub trait AiProvider: Send + Sync {
     async fn chat(&self, request: &ChatRequest) -> AppResult<ChatResponse>;
+    /// Errors starting the request are returned here, errors mid-stream as items
+    async fn chat_stream(&self, request: &ChatRequest) -> AppResult<ChatStream>;
     async fn generate_embedding(&self, text: &str, model: Option<String>) -> AppResult<Vec<f32>>;
+    /// One vector per text, in order; embeds the texts one at a time unless
+    /// the provider can batch them in a single upstream call
+    async fn generate_embeddings_batch(
+        &self,
+        texts: &[String],
+        model: Option<String>,
+    ) -> AppResult<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for text in texts {
+            embeddings.push(self.generate_embedding(text, model.clone()).await?);
+        }
+        Ok(embeddings)
+    }
     fn provider_name(&self) -> &str;
 }
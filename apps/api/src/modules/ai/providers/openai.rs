@@ -1,38 +1,66 @@
 use async_openai::{
     config::OpenAIConfig,
     types::{
-        ChatCompletionRequestMessage, ChatCompletionRequestSystemMessageArgs,
-        ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequestArgs,
-        CreateEmbeddingRequestArgs,
+        ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestMessage,
+        ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
+        ChatCompletionStreamOptions, ChatCompletionToolArgs, ChatCompletionToolType,
+        CompletionUsage, CreateChatCompletionRequest, CreateChatCompletionRequestArgs,
+        CreateChatCompletionResponse, CreateChatCompletionStreamResponse,
+        CreateEmbeddingRequestArgs, FunctionObjectArgs,
     },
     Client,
 };
 use async_trait::async_trait;
+use backoff::ExponentialBackoffBuilder;
+use futures::StreamExt;
+use std::time::Duration;
 
+use super::super::model::{ChatRequest, ChatResponse, ChatStreamChunk, Role, TokenUsage, ToolCall};
+use super::retry::RetryPolicy;
+use super::ChatStream;
 use crate::utils::error::{AppError, AppResult};
-use super::super::model::{ChatRequest, ChatResponse};
 
 pub struct OpenAIProvider {
     client: Client<OpenAIConfig>,
     default_model: String,
+    retry: RetryPolicy,
 }
 
 impl OpenAIProvider {
     pub fn new(api_key: String, default_model: String) -> Self {
-        let config = OpenAIConfig::new().with_api_key(api_key);
-        let client = Client::with_config(config);
+        Self::with_config(OpenAIConfig::new().with_api_key(api_key), default_model)
+    }
+
+    /// Client for `config`, e.g. pointed at a proxy or a compatible API
+    /// through `with_api_base`
+    pub fn with_config(config: OpenAIConfig, default_model: String) -> Self {
+        // Retries are ours (`RetryPolicy`); the SDK's own backoff would keep
+        // repeating rate-limited calls without a bound on attempts
+        let no_backoff = ExponentialBackoffBuilder::new()
+            .with_max_elapsed_time(Some(Duration::ZERO))
+            .build();
+        let client = Client::with_config(config).with_backoff(no_backoff);
 
         Self {
             client,
             default_model,
+            retry: RetryPolicy::default(),
         }
     }
-}
 
-#[async_trait]
-impl super::AiProvider for OpenAIProvider {
-    async fn chat(&self, request: &ChatRequest) -> AppResult<ChatResponse> {
-        let model = request.model.as_ref()
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Completion request for `request`, along with the model it targets
+    fn completion_request(
+        &self,
+        request: &ChatRequest,
+    ) -> AppResult<(CreateChatCompletionRequest, String)> {
+        let model = request
+            .model
+            .as_ref()
             .unwrap_or(&self.default_model)
             .clone();
 
@@ -42,21 +70,34 @@ impl super::AiProvider for OpenAIProvider {
         if let Some(system_prompt) = &request.system_prompt {
             messages.push(
                 ChatCompletionRequestSystemMessageArgs::default()
-                    .content(system_prompt)
+                    .content(system_prompt.as_str())
                     .build()
                     .map_err(|e| AppError::ExternalService(e.to_string()))?
                     .into(),
             );
         }
 
-        // Add user message
-        messages.push(
-            ChatCompletionRequestUserMessageArgs::default()
-                .content(&request.message)
-                .build()
-                .map_err(|e| AppError::ExternalService(e.to_string()))?
-                .into(),
-        );
+        // The conversation, each turn under its own role
+        for message in request.conversation() {
+            let message: ChatCompletionRequestMessage = match message.role {
+                Role::System => ChatCompletionRequestSystemMessageArgs::default()
+                    .content(message.content)
+                    .build()
+                    .map_err(|e| AppError::ExternalService(e.to_string()))?
+                    .into(),
+                Role::User => ChatCompletionRequestUserMessageArgs::default()
+                    .content(message.content)
+                    .build()
+                    .map_err(|e| AppError::ExternalService(e.to_string()))?
+                    .into(),
+                Role::Assistant => ChatCompletionRequestAssistantMessageArgs::default()
+                    .content(message.content)
+                    .build()
+                    .map_err(|e| AppError::ExternalService(e.to_string()))?
+                    .into(),
+            };
+            messages.push(message);
+        }
 
         let mut req_builder = CreateChatCompletionRequestArgs::default();
         req_builder.model(&model).messages(messages);
@@ -69,31 +110,83 @@ impl super::AiProvider for OpenAIProvider {
             req_builder.max_tokens(max_tokens);
         }
 
+        // Offer tools; the model decides whether to call one
+        if !request.tools.is_empty() {
+            let mut tools = Vec::with_capacity(request.tools.len());
+            for tool in &request.tools {
+                let mut function = FunctionObjectArgs::default();
+                function
+                    .name(&tool.name)
+                    .parameters(tool.parameters.clone());
+                if let Some(description) = &tool.description {
+                    function.description(description);
+                }
+
+                tools.push(
+                    ChatCompletionToolArgs::default()
+                        .r#type(ChatCompletionToolType::Function)
+                        .function(
+                            function
+                                .build()
+                                .map_err(|e| AppError::BadRequest(e.to_string()))?,
+                        )
+                        .build()
+                        .map_err(|e| AppError::BadRequest(e.to_string()))?,
+                );
+            }
+            req_builder.tools(tools);
+        }
+
         let chat_request = req_builder
             .build()
             .map_err(|e| AppError::ExternalService(e.to_string()))?;
 
+        Ok((chat_request, model))
+    }
+}
+
+#[async_trait]
+impl super::AiProvider for OpenAIProvider {
+    async fn chat(&self, request: &ChatRequest) -> AppResult<ChatResponse> {
+        let (chat_request, model) = self.completion_request(request)?;
+
         let response = self
-            .client
-            .chat()
-            .create(chat_request)
-            .await
-            .map_err(|e| AppError::ExternalService(format!("OpenAI API error: {}", e)))?;
-
-        let content = response
-            .choices
-            .first()
-            .and_then(|c| c.message.content.clone())
-            .ok_or_else(|| AppError::ExternalService("No response from OpenAI".to_string()))?;
+            .retry
+            .run("openai", || async {
+                self.client.chat().create(chat_request.clone()).await
+            })
+            .await?;
 
-        let tokens_used = response.usage.map(|u| u.total_tokens);
+        chat_response_from(response, model)
+    }
 
-        Ok(ChatResponse {
-            response: content,
-            provider: "openai".to_string(),
-            model,
-            tokens_used,
-        })
+    async fn chat_stream(&self, request: &ChatRequest) -> AppResult<ChatStream> {
+        let (mut chat_request, _) = self.completion_request(request)?;
+        chat_request.stream = Some(true);
+        chat_request.stream_options = Some(ChatCompletionStreamOptions {
+            include_usage: true,
+        });
+
+        // Only opening the stream is retried; a stream failing midway has
+        // already sent part of the reply
+        let stream = self
+            .retry
+            .run("openai", || async {
+                self.client.chat().create_stream(chat_request.clone()).await
+            })
+            .await?;
+
+        Ok(stream
+            .filter_map(|item| async move {
+                match item {
+                    Ok(response) => stream_chunk_from(response).map(Ok),
+                    Err(e) => Some(Err(AppError::ExternalService(format!(
+                        "OpenAI API error: {}",
+                        e
+                    )))),
+                }
+            })
+            .boxed())
     }
 
     async fn generate_embedding(&self, text: &str, model: Option<String>) -> AppResult<Vec<f32>> {
@@ -106,11 +199,11 @@ impl super::AiProvider for OpenAIProvider {
             .map_err(|e| AppError::ExternalService(e.to_string()))?;
 
         let response = self
-            .client
-            .embeddings()
-            .create(request)
-            .await
-            .map_err(|e| AppError::ExternalService(format!("OpenAI API error: {}", e)))?;
+            .retry
+            .run("openai", || async {
+                self.client.embeddings().create(request.clone()).await
+            })
+            .await?;
 
         let embedding = response
             .data
@@ -121,7 +214,115 @@ impl super::AiProvider for OpenAIProvider {
         Ok(embedding)
     }
 
+    async fn generate_embeddings_batch(
+        &self,
+        texts: &[String],
+        model: Option<String>,
+    ) -> AppResult<Vec<Vec<f32>>> {
+        let model = model.unwrap_or_else(|| "text-embedding-3-small".to_string());
+
+        let request = CreateEmbeddingRequestArgs::default()
+            .model(&model)
+            .input(texts.to_vec())
+            .build()
+            .map_err(|e| AppError::ExternalService(e.to_string()))?;
+
+        let response = self
+            .retry
+            .run("openai", || async {
+                self.client.embeddings().create(request.clone()).await
+            })
+            .await?;
+
+        // Each vector carries the index of its input; don't rely on the order
+        let mut data = response.data;
+        data.sort_by_key(|d| d.index);
+
+        Ok(data.into_iter().map(|d| d.embedding).collect())
+    }
+
     fn provider_name(&self) -> &str {
         "openai"
     }
 }
+
+/// Map an OpenAI completion to a `ChatResponse`, surfacing requested tool calls
+///
+/// A reply that only calls tools has no text content, so `response` is empty.
+pub fn chat_response_from(
+    response: CreateChatCompletionResponse,
+    model: String,
+) -> AppResult<ChatResponse> {
+    let usage = response.usage.map(token_usage);
+
+    let message = response
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message)
+        .ok_or_else(|| AppError::ExternalService("No response from OpenAI".to_string()))?;
+
+    let tool_calls = message
+        .tool_calls
+        .unwrap_or_default()
+        .into_iter()
+        .map(|call| {
+            ToolCall::from_raw_arguments(call.id, call.function.name, &call.function.arguments)
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    let content = match message.content {
+        Some(content) => content,
+        None if !tool_calls.is_empty() => String::new(),
+        None => {
+            return Err(AppError::ExternalService(
+                "No response from OpenAI".to_string(),
+            ))
+        }
+    };
+
+    Ok(ChatResponse {
+        response: content,
+        provider: "openai".to_string(),
+        model,
+        tokens_used: usage.map(|u| u.total_tokens),
+        usage,
+        tool_calls,
+    })
+}
+
+/// Map one OpenAI stream event to a chunk; events carrying no text, finish
+/// reason or usage (such as the leading role-only delta) yield `None`
+///
+/// With `include_usage` set, usage arrives in a final event without choices.
+pub fn stream_chunk_from(response: CreateChatCompletionStreamResponse) -> Option<ChatStreamChunk> {
+    let usage = response.usage.map(token_usage);
+    let (delta, finish_reason) = match response.choices.into_iter().next() {
+        Some(choice) => (
+            choice.delta.content.unwrap_or_default(),
+            choice
+                .finish_reason
+                .and_then(|reason| serde_json::to_value(reason).ok())
+                .and_then(|reason| reason.as_str().map(str::to_string)),
+        ),
+        None => (String::new(), None),
+    };
+
+    if delta.is_empty() && finish_reason.is_none() && usage.is_none() {
+        return None;
+    }
+
+    Some(ChatStreamChunk {
+        delta,
+        finish_reason,
+        usage,
+    })
+}
+
+fn token_usage(usage: CompletionUsage) -> TokenUsage {
+    TokenUsage {
+        prompt_tokens: usage.prompt_tokens,
+        completion_tokens: usage.completion_tokens,
+        total_tokens: usage.total_tokens,
+    }
+}
@@ -0,0 +1,162 @@
+use async_anthropic::errors::AnthropicError;
+use async_openai::error::OpenAIError;
+use std::{fmt::Display, future::Future, time::Duration, time::Instant};
+use uuid::Uuid;
+
+use crate::config::AiConfig;
+use crate::metrics::record_external_api_call;
+use crate::utils::error::{AppError, AppResult};
+
+/// OpenAI error types and codes worth another attempt: rate limits and
+/// server-side failures
+const OPENAI_RETRYABLE: &[&str] = &[
+    "rate_limit_exceeded",
+    "rate_limit_error",
+    "requests",
+    "tokens",
+    "server_error",
+    "service_unavailable",
+];
+
+/// OpenAI codes that never succeed on a repeat, even when reported with a
+/// retryable type (quota exhaustion comes back as a 429)
+const OPENAI_FATAL: &[&str] = &[
+    "invalid_api_key",
+    "content_policy_violation",
+    "content_filter",
+    "insufficient_quota",
+];
+
+/// Anthropic error types worth another attempt
+const ANTHROPIC_RETRYABLE: &[&str] = &["rate_limit_error", "overloaded_error", "api_error"];
+
+/// Whether a failed provider call is worth repeating
+pub trait RetryableError {
+    fn is_retryable(&self) -> bool;
+}
+
+impl RetryableError for OpenAIError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            OpenAIError::Reqwest(e) => {
+                e.is_timeout()
+                    || e.is_connect()
+                    || e.status()
+                        .is_some_and(|status| status.as_u16() == 429 || status.is_server_error())
+            }
+            OpenAIError::ApiError(e) => {
+                let code = e.code.as_deref();
+                let kind = e.r#type.as_deref();
+                !code.is_some_and(|code| OPENAI_FATAL.contains(&code))
+                    && [code, kind]
+                        .into_iter()
+                        .flatten()
+                        .any(|value| OPENAI_RETRYABLE.contains(&value))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl RetryableError for AnthropicError {
+    /// The SDK only exposes the API's error type through the response body
+    fn is_retryable(&self) -> bool {
+        match self {
+            AnthropicError::NetworkError(e) => e.is_timeout() || e.is_connect(),
+            AnthropicError::StreamError(e) => ANTHROPIC_RETRYABLE.contains(&e.error_type.as_str()),
+            AnthropicError::ApiError(body) | AnthropicError::Unknown(body) => {
+                ANTHROPIC_RETRYABLE.iter().any(|kind| body.contains(kind))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Bounded exponential backoff with jitter for provider calls
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+/// Matches the `AiConfig` defaults
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(20),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &AiConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts.max(1),
+            base_delay: Duration::from_millis(config.retry_base_ms),
+            ..Self::default()
+        }
+    }
+
+    /// Half the doubled delay is fixed and half random, so clients throttled
+    /// together don't all retry at the same moment
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+        delay / 2 + jitter(delay / 2)
+    }
+
+    /// Run `call` until it succeeds, fails with a non-retryable error or
+    /// attempts run out; failures surface as `AiProviderFailed` carrying the
+    /// attempts made
+    pub async fn run<T, E, F, Fut>(&self, provider: &str, mut call: F) -> AppResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: RetryableError + Display,
+    {
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            let started = Instant::now();
+            let result = call().await;
+            let elapsed = started.elapsed().as_secs_f64();
+            record_external_api_call(provider, result.is_ok(), elapsed);
+
+            let e = match result {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+
+            let retryable = e.is_retryable();
+            if retryable && attempt >= self.max_attempts {
+                tracing::error!(provider, attempt, reason = %e, "AI provider retries exhausted");
+            }
+            if !retryable || attempt >= self.max_attempts {
+                return Err(AppError::AiProviderFailed {
+                    provider: provider.to_string(),
+                    attempts: attempt,
+                    message: e.to_string(),
+                });
+            }
+
+            tracing::warn!(provider, attempt, reason = %e, "Retrying AI provider call");
+            tokio::time::sleep(self.backoff(attempt)).await;
+        }
+    }
+}
+
+/// Random duration in `[0, limit]`; v4 UUIDs are random, which saves a
+/// dependency on `rand` for this one use
+fn jitter(limit: Duration) -> Duration {
+    let nanos = limit.as_nanos() as u64;
+    if nanos == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_nanos((Uuid::new_v4().as_u128() as u64) % (nanos + 1))
+}
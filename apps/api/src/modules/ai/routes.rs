@@ -1,74 +1,257 @@
 use axum::{
-    routing::post,
-    Router, Json, extract::State,
+    extract::{Path, Query, State},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Extension, Json, Router,
 };
+use sqlx::PgPool;
 use std::sync::Arc;
-use validator::Validate;
+use uuid::Uuid;
 
-use crate::config::AiConfig;
+use crate::config::{AiConfig, JwtConfig};
+use crate::modules::auth::{
+    jwt::Claims,
+    middleware::{auth_middleware, AuthMiddleware},
+    role_guard::{require_permission, Permission},
+};
 use crate::utils::{
-    error::AppResult,
+    error::{AppError, AppResult, ErrorResponse},
     response::ApiResponse,
     validation::validate_struct,
 };
 
-use super::model::{ChatRequest, EmbeddingRequest};
+use super::alerts::UsageAlerts;
+use super::model::{
+    ChatRequest, ChatResponse, EmbeddingInput, EmbeddingRequest, EmbeddingResponse, ModelInfo,
+};
 use super::service::AiService;
-use super::streaming::{chunk_response, create_sse_stream};
+use super::streaming::chat_sse_stream;
+use super::usage::{AiUsageLog, UsageQuery, UsageSummary};
 
 #[derive(Clone)]
 struct AiState {
     service: Arc<AiService>,
 }
 
-pub fn routes(config: AiConfig) -> Router {
-    let service = Arc::new(AiService::new(config));
-    let state = AiState { service };
+/// AI routes; all require authentication so model access can be checked per role
+/// and token usage attributed to the caller in `ai_usage` and `usage_alerts`
+pub fn routes(
+    config: AiConfig,
+    jwt_config: JwtConfig,
+    usage_alerts: UsageAlerts,
+    db_pool: PgPool,
+) -> Router {
+    service_routes(
+        AiService::new(config)
+            .with_usage_alerts(usage_alerts)
+            .with_usage_log(AiUsageLog::new(db_pool)),
+        jwt_config,
+    )
+}
+
+/// AI routes backed by an already-built `service`
+pub fn service_routes(service: AiService, jwt_config: JwtConfig) -> Router {
+    let state = AiState {
+        service: Arc::new(service),
+    };
 
     Router::new()
         .route("/ai/chat", post(chat))
         .route("/ai/chat/stream", post(chat_stream))
         .route("/ai/embeddings", post(generate_embedding))
+        .route("/ai/models", get(list_models))
+        .route("/ai/usage", get(my_usage))
+        .route(
+            "/ai/usage/users/{id}",
+            get(user_usage).layer(middleware::from_fn_with_state(
+                Permission::AiUsageRead,
+                require_permission,
+            )),
+        )
+        .layer(middleware::from_fn_with_state(
+            AuthMiddleware::new(jwt_config),
+            auth_middleware,
+        ))
         .with_state(state)
 }
 
+#[utoipa::path(
+    post,
+    path = "/ai/chat",
+    tag = "ai",
+    request_body = ChatRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The completion", body = ApiResponse<ChatResponse>),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Model not allowed for this role", body = ErrorResponse)
+    )
+)]
 async fn chat(
     State(state): State<AiState>,
+    Extension(claims): Extension<Claims>,
     Json(request): Json<ChatRequest>,
 ) -> AppResult<impl axum::response::IntoResponse> {
     validate_struct(&request)?;
 
-    let response = state.service.chat(request).await?;
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    let response = state.service.chat(request, user_id, claims.role).await?;
 
     Ok(ApiResponse::success(response))
 }
 
+/// `text/event-stream` of completion deltas, ending with `data: [DONE]`;
+/// validation and provider errors before the first delta are plain JSON errors
+#[utoipa::path(
+    post,
+    path = "/ai/chat/stream",
+    tag = "ai",
+    request_body = ChatRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Server-sent completion deltas, ending with `data: [DONE]`", content_type = "text/event-stream"),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Model not allowed for this role", body = ErrorResponse)
+    )
+)]
 async fn chat_stream(
     State(state): State<AiState>,
+    Extension(claims): Extension<Claims>,
     Json(mut request): Json<ChatRequest>,
 ) -> AppResult<impl axum::response::IntoResponse> {
     validate_struct(&request)?;
+    request.stream = true;
 
-    // Force non-streaming for the actual API call
-    request.stream = false;
-
-    // Get the full response
-    let response = state.service.chat(request).await?;
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
 
-    // Chunk the response for streaming (in production, you'd stream from the provider)
-    let chunks = chunk_response(response.response, 20);
+    let chunks = state
+        .service
+        .chat_stream(request, user_id, claims.role)
+        .await?;
 
-    // Create SSE stream
-    Ok(create_sse_stream(chunks))
+    Ok(chat_sse_stream(chunks))
 }
 
+/// `{ "text" }` returns one embedding, `{ "texts": [...] }` an ordered batch
+#[utoipa::path(
+    post,
+    path = "/ai/embeddings",
+    tag = "ai",
+    request_body = EmbeddingRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "One embedding for `text`; a `texts` batch returns `embeddings` in request order instead", body = ApiResponse<EmbeddingResponse>),
+        (status = 400, description = "Neither or both of `text` and `texts`, or too many texts", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 async fn generate_embedding(
     State(state): State<AiState>,
+    Extension(claims): Extension<Claims>,
     Json(request): Json<EmbeddingRequest>,
+) -> AppResult<Response> {
+    let service = &state.service;
+    let input = request.input(service.max_embedding_batch_size())?;
+
+    let response = match input {
+        EmbeddingInput::Single(text) => ApiResponse::success(
+            service
+                .generate_embedding(text, request.model, claims.role)
+                .await?,
+        )
+        .into_response(),
+        EmbeddingInput::Batch(texts) => ApiResponse::success(
+            service
+                .generate_embeddings_batch(texts, request.model, claims.role)
+                .await?,
+        )
+        .into_response(),
+    };
+
+    Ok(response)
+}
+
+/// Models the caller may request, from the providers configured with an API key
+#[utoipa::path(
+    get,
+    path = "/ai/models",
+    tag = "ai",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Available models by provider", body = ApiResponse<Vec<ModelInfo>>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
+async fn list_models(
+    State(state): State<AiState>,
+    Extension(claims): Extension<Claims>,
+) -> ApiResponse<Vec<ModelInfo>> {
+    ApiResponse::success(state.service.models(claims.role))
+}
+
+/// The caller's token usage per day range (`from`/`to`, inclusive)
+#[utoipa::path(
+    get,
+    path = "/ai/usage",
+    tag = "ai",
+    params(UsageQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The caller's token usage", body = ApiResponse<UsageSummary>),
+        (status = 400, description = "`from` after `to`", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
+async fn my_usage(
+    State(state): State<AiState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<UsageQuery>,
 ) -> AppResult<impl axum::response::IntoResponse> {
-    validate_struct(&request)?;
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
 
-    let response = state.service.generate_embedding(request).await?;
+    let summary = state
+        .service
+        .usage_log()?
+        .summary(user_id, query.range()?)
+        .await?;
 
-    Ok(ApiResponse::success(response))
+    Ok(ApiResponse::success(summary))
+}
+
+/// Any user's token usage; requires `ai_usage_read`
+#[utoipa::path(
+    get,
+    path = "/ai/usage/users/{id}",
+    tag = "ai",
+    params(
+        ("id" = Uuid, Path, description = "User id"),
+        UsageQuery
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The user's token usage", body = ApiResponse<UsageSummary>),
+        (status = 400, description = "`from` after `to`", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Missing `ai_usage_read`", body = ErrorResponse)
+    )
+)]
+async fn user_usage(
+    State(state): State<AiState>,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<UsageQuery>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let summary = state
+        .service
+        .usage_log()?
+        .summary(user_id, query.range()?)
+        .await?;
+
+    Ok(ApiResponse::success(summary))
 }
@@ -1,85 +1,306 @@
+use futures::StreamExt;
 use std::sync::Arc;
+use uuid::Uuid;
 
 use crate::config::AiConfig;
+use crate::modules::users::model::UserRole;
 use crate::utils::error::{AppError, AppResult};
 
-use super::model::{AiProvider as AiProviderEnum, ChatRequest, ChatResponse, EmbeddingRequest, EmbeddingResponse};
+use super::alerts::UsageAlerts;
+use super::model::{
+    normalize_model_name, AiProvider as AiProviderEnum, BatchEmbeddingResponse, ChatRequest,
+    ChatResponse, ConversationLimits, EmbeddingResponse, ModelAllowlist, ModelInfo, ProviderModels,
+    TokenUsage,
+};
 use super::providers::{
-    anthropic::AnthropicProvider,
-    openai::OpenAIProvider,
-    local::LocalProvider,
-    AiProvider,
+    anthropic::AnthropicProvider, local::LocalProvider, openai::OpenAIProvider, retry::RetryPolicy,
+    AiProvider, ChatStream,
 };
+use super::usage::{AiUsageLog, StreamUsage, UsageTracker};
+
+const ANTHROPIC_DEFAULT_MODEL: &str = "claude-3-5-sonnet-20241022";
+const LOCAL_DEFAULT_MODEL: &str = "local-model";
+const EMBEDDING_DEFAULT_MODEL: &str = "text-embedding-3-small";
 
 pub struct AiService {
-    openai: Option<Arc<OpenAIProvider>>,
-    anthropic: Option<Arc<AnthropicProvider>>,
-    local: Option<Arc<LocalProvider>>,
+    openai: Option<Arc<dyn AiProvider>>,
+    anthropic: Option<Arc<dyn AiProvider>>,
+    local: Option<Arc<dyn AiProvider>>,
+    limits: ConversationLimits,
+    allowlist: ModelAllowlist,
+    provider_models: ProviderModels,
+    normalize_model_names: bool,
+    default_model: String,
+    /// Provider `default_model` belongs to; serves requests naming none
+    default_provider: AiProviderEnum,
+    max_embedding_batch_size: usize,
+    usage: UsageTracker,
 }
 
 impl AiService {
     pub fn new(config: AiConfig) -> Self {
+        let limits = ConversationLimits {
+            max_messages: config.max_messages,
+            max_total_chars: config.max_conversation_chars,
+            max_tokens: config.max_tokens,
+        };
+
+        let allowlist = ModelAllowlist {
+            user: config.allowed_models_user.clone(),
+            moderator: config.allowed_models_moderator.clone(),
+        };
+
+        let provider_models = ProviderModels {
+            openai: config.allowed_models_openai.clone(),
+            anthropic: config.allowed_models_anthropic.clone(),
+            local: config.allowed_models_local.clone(),
+        };
+
+        let retry = RetryPolicy::from_config(&config);
+
         let openai = config.openai_api_key.map(|key| {
-            Arc::new(OpenAIProvider::new(key, config.default_model.clone()))
+            Arc::new(
+                OpenAIProvider::new(key, config.default_model.clone()).with_retry(retry.clone()),
+            ) as Arc<dyn AiProvider>
         });
 
         let anthropic = config.anthropic_api_key.map(|key| {
-            Arc::new(AnthropicProvider::new(
-                key,
-                "claude-3-5-sonnet-20241022".to_string(),
-            ))
+            Arc::new(
+                AnthropicProvider::new(key, ANTHROPIC_DEFAULT_MODEL.to_string())
+                    .with_retry(retry.clone()),
+            ) as Arc<dyn AiProvider>
         });
 
         // For local provider, you would typically load from a model path
-        let local = Some(Arc::new(LocalProvider::new(
-            "./models/local-model.gguf".to_string(),
-        )));
+        let local = Some(
+            Arc::new(LocalProvider::new("./models/local-model.gguf".to_string()))
+                as Arc<dyn AiProvider>,
+        );
+
+        let default_provider = provider_models.implied_provider(&normalize_model_name(
+            &config.default_model,
+            config.normalize_model_names,
+        ));
 
         Self {
             openai,
             anthropic,
             local,
+            limits,
+            allowlist,
+            provider_models,
+            normalize_model_names: config.normalize_model_names,
+            default_model: config.default_model,
+            default_provider,
+            max_embedding_batch_size: config.max_embedding_batch_size,
+            usage: UsageTracker::default(),
         }
     }
 
+    pub fn with_usage_alerts(mut self, usage_alerts: UsageAlerts) -> Self {
+        self.usage.alerts = usage_alerts;
+        self
+    }
+
+    /// Persist every completion's token usage to `ai_usage`
+    pub fn with_usage_log(mut self, usage_log: AiUsageLog) -> Self {
+        self.usage.log = Some(usage_log);
+        self
+    }
+
+    /// The `ai_usage` log backing the usage endpoints
+    pub fn usage_log(&self) -> AppResult<&AiUsageLog> {
+        self.usage
+            .log
+            .as_ref()
+            .ok_or_else(|| AppError::Configuration("AI usage log not configured".to_string()))
+    }
+
+    /// Serve `kind` requests from `provider` instead of the configured client
+    pub fn with_provider(mut self, kind: AiProviderEnum, provider: Arc<dyn AiProvider>) -> Self {
+        match kind {
+            AiProviderEnum::Openai => self.openai = Some(provider),
+            AiProviderEnum::Anthropic => self.anthropic = Some(provider),
+            AiProviderEnum::Local => self.local = Some(provider),
+        }
+        self
+    }
+
     fn get_provider(&self, provider: &AiProviderEnum) -> AppResult<Arc<dyn AiProvider>> {
         match provider {
-            AiProviderEnum::Openai => self
-                .openai
-                .clone()
-                .ok_or_else(|| AppError::Configuration("OpenAI API key not configured".to_string()))
-                .map(|p| p as Arc<dyn AiProvider>),
-            AiProviderEnum::Anthropic => self
-                .anthropic
-                .clone()
-                .ok_or_else(|| AppError::Configuration("Anthropic API key not configured".to_string()))
-                .map(|p| p as Arc<dyn AiProvider>),
-            AiProviderEnum::Local => self
-                .local
-                .clone()
-                .ok_or_else(|| AppError::Configuration("Local model not configured".to_string()))
-                .map(|p| p as Arc<dyn AiProvider>),
+            AiProviderEnum::Openai => self.openai.clone(),
+            AiProviderEnum::Anthropic => self.anthropic.clone(),
+            AiProviderEnum::Local => self.local.clone(),
         }
+        .ok_or_else(|| AppError::ProviderNotConfigured(provider.to_string()))
+    }
+
+    /// Models `role` may request from the configured providers; providers
+    /// without a client (no API key) are left out
+    pub fn models(&self, role: UserRole) -> Vec<ModelInfo> {
+        AiProviderEnum::ALL
+            .into_iter()
+            .filter(|provider| self.get_provider(provider).is_ok())
+            .flat_map(|provider| self.provider_models.catalog(provider))
+            .filter(|model| self.allowlist.check(role, &model.id).is_ok())
+            .collect()
+    }
+
+    /// Provider a chat request is dispatched to: the one it names, else the
+    /// one the configured default model belongs to
+    pub fn select_provider(&self, request: &ChatRequest) -> AppResult<AiProviderEnum> {
+        request.provider(self.default_provider)
     }
 
-    pub async fn chat(&self, request: ChatRequest) -> AppResult<ChatResponse> {
-        let provider = self.get_provider(&request.provider)?;
-        provider.chat(&request).await
+    /// Model a chat request will run on once provider defaults apply
+    fn chat_model<'a>(&'a self, provider: AiProviderEnum, request: &'a ChatRequest) -> &'a str {
+        request.model.as_deref().unwrap_or(match provider {
+            provider if provider == self.default_provider => &self.default_model,
+            AiProviderEnum::Openai => &self.default_model,
+            AiProviderEnum::Anthropic => ANTHROPIC_DEFAULT_MODEL,
+            AiProviderEnum::Local => LOCAL_DEFAULT_MODEL,
+        })
     }
 
-    pub async fn generate_embedding(&self, request: EmbeddingRequest) -> AppResult<EmbeddingResponse> {
-        // Default to OpenAI for embeddings
-        let provider = self
-            .openai
-            .clone()
-            .ok_or_else(|| AppError::Configuration("OpenAI API key required for embeddings".to_string()))?;
+    /// Resolve the provider and model for `request` and check them against
+    /// the model allowlists and conversation limits
+    fn prepare_chat(
+        &self,
+        mut request: ChatRequest,
+        role: UserRole,
+    ) -> AppResult<(ChatRequest, Arc<dyn AiProvider>)> {
+        let provider_kind = self.select_provider(&request)?;
+        request.provider = Some(provider_kind.to_string());
+        request.model = request
+            .model
+            .map(|model| normalize_model_name(&model, self.normalize_model_names));
+
+        let model = self.chat_model(provider_kind, &request).to_string();
+        self.provider_models.check(provider_kind, &model)?;
+        self.allowlist.check(role, &model)?;
+        request.check_limits(&self.limits)?;
+        request.model = Some(model);
 
-        let embedding = provider.generate_embedding(&request.text, request.model.clone()).await?;
+        let provider = self.get_provider(&provider_kind)?;
+        Ok((request, provider))
+    }
+
+    pub async fn chat(
+        &self,
+        request: ChatRequest,
+        user_id: Uuid,
+        role: UserRole,
+    ) -> AppResult<ChatResponse> {
+        let (request, provider) = self.prepare_chat(request, role)?;
+        let response = provider.chat(&request).await?;
+
+        let usage = response.usage.unwrap_or_else(|| {
+            TokenUsage::estimate(request.total_chars(), response.response.chars().count())
+        });
+        self.usage
+            .record(user_id, &response.provider, &response.model, usage)
+            .await;
+
+        Ok(response)
+    }
+
+    /// Stream a chat completion; its usage is recorded when the stream ends,
+    /// including when the client disconnects part way
+    pub async fn chat_stream(
+        &self,
+        request: ChatRequest,
+        user_id: Uuid,
+        role: UserRole,
+    ) -> AppResult<ChatStream> {
+        if !request.tools.is_empty() {
+            return Err(AppError::BadRequest(
+                "Tool calling is not supported when streaming".to_string(),
+            ));
+        }
+
+        let (request, provider) = self.prepare_chat(request, role)?;
+        let chunks = provider.chat_stream(&request).await?;
+
+        let mut usage = StreamUsage::new(
+            self.usage.clone(),
+            user_id,
+            provider.provider_name().to_string(),
+            request.model.clone().unwrap_or_default(),
+            request.total_chars(),
+        );
+        Ok(chunks.inspect(move |chunk| usage.observe(chunk)).boxed())
+    }
+
+    pub fn max_embedding_batch_size(&self) -> usize {
+        self.max_embedding_batch_size
+    }
+
+    /// Normalize the requested embedding model and check it is allowed
+    fn embedding_model(&self, model: Option<String>, role: UserRole) -> AppResult<String> {
+        let model = model
+            .map(|model| normalize_model_name(&model, self.normalize_model_names))
+            .unwrap_or_else(|| EMBEDDING_DEFAULT_MODEL.to_string());
+        self.provider_models.check(AiProviderEnum::Openai, &model)?;
+        self.allowlist.check(role, &model)?;
+        Ok(model)
+    }
+
+    pub async fn generate_embedding(
+        &self,
+        text: String,
+        model: Option<String>,
+        role: UserRole,
+    ) -> AppResult<EmbeddingResponse> {
+        let model = self.embedding_model(model, role)?;
+
+        // Embeddings are only offered by OpenAI
+        let provider = self.get_provider(&AiProviderEnum::Openai)?;
+
+        let embedding = provider
+            .generate_embedding(&text, Some(model.clone()))
+            .await?;
 
         Ok(EmbeddingResponse {
-            embedding: embedding.clone(),
-            model: request.model.unwrap_or_else(|| "text-embedding-3-small".to_string()),
             dimensions: embedding.len(),
+            embedding,
+            model,
+        })
+    }
+
+    /// Embed `texts` in one provider call, rejecting a reply that does not
+    /// hold one equally sized vector per text
+    pub async fn generate_embeddings_batch(
+        &self,
+        texts: Vec<String>,
+        model: Option<String>,
+        role: UserRole,
+    ) -> AppResult<BatchEmbeddingResponse> {
+        let model = self.embedding_model(model, role)?;
+        let provider = self.get_provider(&AiProviderEnum::Openai)?;
+
+        let embeddings = provider
+            .generate_embeddings_batch(&texts, Some(model.clone()))
+            .await?;
+
+        if embeddings.len() != texts.len() {
+            return Err(AppError::ExternalService(format!(
+                "Expected {} embeddings, got {}",
+                texts.len(),
+                embeddings.len()
+            )));
+        }
+
+        let dimensions = embeddings.first().map_or(0, Vec::len);
+        if embeddings.iter().any(|e| e.len() != dimensions) {
+            return Err(AppError::ExternalService(
+                "Embeddings in the batch have inconsistent dimensions".to_string(),
+            ));
+        }
+
+        Ok(BatchEmbeddingResponse {
+            embeddings,
+            model,
+            dimensions,
         })
     }
 }
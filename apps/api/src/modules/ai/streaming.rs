@@ -1,32 +1,43 @@
 use axum::response::sse::{Event, KeepAlive, Sse};
-use futures::stream::{self, Stream};
+use futures::stream::{self, Stream, StreamExt};
 use std::{convert::Infallible, time::Duration};
 
-use super::model::StreamChunk;
-
-/// Create a Server-Sent Events (SSE) stream for AI responses
-pub fn create_sse_stream(
-    chunks: Vec<String>,
-) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let stream = stream::iter(chunks)
-        .enumerate()
-        .map(|(i, chunk)| {
-            let is_last = i == chunks.len() - 1;
-            let stream_chunk = StreamChunk {
-                content: chunk,
-                done: is_last,
+use super::providers::ChatStream;
+
+/// Final `data:` frame of every chat stream
+pub const DONE_SENTINEL: &str = "[DONE]";
+
+/// Relay a provider stream as Server-Sent Events: one `data:` frame per
+/// `ChatStreamChunk`, an `error` event if the provider fails mid-stream, and
+/// a closing `data: [DONE]`
+///
+/// When the client disconnects axum drops this stream, which drops the
+/// provider stream and with it the upstream HTTP request.
+pub fn chat_sse_stream(chunks: ChatStream) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = chunks
+        .map(|chunk| {
+            let event = match chunk {
+                Ok(chunk) => {
+                    Event::default().data(serde_json::to_string(&chunk).unwrap_or_default())
+                }
+                Err(e) => {
+                    tracing::warn!("AI chat stream failed: {}", e);
+                    Event::default()
+                        .event("error")
+                        .data(serde_json::json!({ "error": e.to_string() }).to_string())
+                }
             };
+            Ok(event)
+        })
+        .chain(stream::once(async {
+            Ok(Event::default().data(DONE_SENTINEL))
+        }));
 
-            let json = serde_json::to_string(&stream_chunk).unwrap_or_default();
-
-            Ok(Event::default().data(json))
-        });
-
-    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(1)))
+    Sse::new(events).keep_alive(KeepAlive::new().interval(Duration::from_secs(1)))
 }
 
-/// Simulate streaming response by chunking text
-/// In production, you'd actually stream from the AI provider
+/// Split text into `chunk_size`-character pieces; the local provider replays
+/// its mock reply this way
 pub fn chunk_response(text: String, chunk_size: usize) -> Vec<String> {
     text.chars()
         .collect::<Vec<_>>()
@@ -0,0 +1,248 @@
+use chrono::{Days, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::utils::error::{AppError, AppResult};
+
+use super::alerts::UsageAlerts;
+use super::model::{ChatStreamChunk, TokenUsage};
+
+/// Days `GET /ai/usage` covers when `from` is omitted, including `to`
+pub const DEFAULT_USAGE_RANGE_DAYS: u64 = 30;
+
+/// Token usage per completion, persisted to `ai_usage`
+#[derive(Clone)]
+pub struct AiUsageLog {
+    db_pool: PgPool,
+}
+
+impl AiUsageLog {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    pub async fn record(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        model: &str,
+        usage: TokenUsage,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO ai_usage
+                (id, user_id, provider, model, prompt_tokens, completion_tokens, total_tokens)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(provider)
+        .bind(model)
+        .bind(usage.prompt_tokens as i32)
+        .bind(usage.completion_tokens as i32)
+        .bind(usage.total_tokens as i32)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// A user's usage within `range`, totalled and per provider/model
+    pub async fn summary(&self, user_id: Uuid, range: UsageRange) -> AppResult<UsageSummary> {
+        let (start, end) = range.bounds();
+        let by_model = sqlx::query_as::<_, ModelUsage>(
+            r#"
+            SELECT provider, model,
+                   COUNT(*) AS requests,
+                   SUM(prompt_tokens)::BIGINT AS prompt_tokens,
+                   SUM(completion_tokens)::BIGINT AS completion_tokens,
+                   SUM(total_tokens)::BIGINT AS total_tokens
+            FROM ai_usage
+            WHERE user_id = $1 AND created_at >= $2 AND created_at < $3
+            GROUP BY provider, model
+            ORDER BY provider, model
+            "#,
+        )
+        .bind(user_id)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(UsageSummary {
+            user_id,
+            from: range.from,
+            to: range.to,
+            requests: by_model.iter().map(|m| m.requests).sum(),
+            prompt_tokens: by_model.iter().map(|m| m.prompt_tokens).sum(),
+            completion_tokens: by_model.iter().map(|m| m.completion_tokens).sum(),
+            total_tokens: by_model.iter().map(|m| m.total_tokens).sum(),
+            by_model,
+        })
+    }
+}
+
+/// Inclusive range of UTC days
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsageRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl UsageRange {
+    /// Start of `from` to the start of the day after `to`
+    fn bounds(&self) -> (chrono::DateTime<Utc>, chrono::DateTime<Utc>) {
+        let start = self.from.and_time(NaiveTime::MIN).and_utc();
+        let end = self
+            .to
+            .checked_add_days(Days::new(1))
+            .unwrap_or(self.to)
+            .and_time(NaiveTime::MIN)
+            .and_utc();
+        (start, end)
+    }
+}
+
+/// Query string of the usage endpoints; dates are `YYYY-MM-DD`
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UsageQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl UsageQuery {
+    /// `to` defaults to today and `from` to `DEFAULT_USAGE_RANGE_DAYS` days
+    /// back from `to`
+    pub fn range(&self) -> AppResult<UsageRange> {
+        let to = self.to.unwrap_or_else(|| Utc::now().date_naive());
+        let from = self.from.unwrap_or_else(|| {
+            to.checked_sub_days(Days::new(DEFAULT_USAGE_RANGE_DAYS - 1))
+                .unwrap_or(to)
+        });
+
+        if from > to {
+            return Err(AppError::InvalidField {
+                field: "from".to_string(),
+                rule: "not_after_to",
+                message: "from must not be after to".to_string(),
+            });
+        }
+
+        Ok(UsageRange { from, to })
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct ModelUsage {
+    pub provider: String,
+    pub model: String,
+    pub requests: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub total_tokens: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageSummary {
+    pub user_id: Uuid,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub requests: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub total_tokens: i64,
+    pub by_model: Vec<ModelUsage>,
+}
+
+/// Where completed requests report their usage: the `ai_usage` log, when
+/// configured, and the threshold alerts
+#[derive(Clone, Default)]
+pub struct UsageTracker {
+    pub log: Option<AiUsageLog>,
+    pub alerts: UsageAlerts,
+}
+
+impl UsageTracker {
+    /// Record one completion; failures are logged, never surfaced, so a
+    /// reply the user already has is not turned into an error
+    pub async fn record(&self, user_id: Uuid, provider: &str, model: &str, usage: TokenUsage) {
+        if let Some(log) = &self.log {
+            if let Err(e) = log.record(user_id, provider, model, usage).await {
+                tracing::warn!("Failed to record AI usage for user {}: {}", user_id, e);
+            }
+        }
+
+        self.alerts.track(user_id, usage.total_tokens.into()).await;
+    }
+}
+
+/// Follows a streamed reply and records its usage when the stream is
+/// dropped, i.e. once it has finished or the client disconnected
+///
+/// The provider's reported usage wins; a stream cut off before the provider
+/// reported any is recorded with an estimate from the text sent so far.
+pub struct StreamUsage {
+    tracker: UsageTracker,
+    user_id: Uuid,
+    provider: String,
+    model: String,
+    prompt_chars: usize,
+    completion_chars: usize,
+    reported: Option<TokenUsage>,
+}
+
+impl StreamUsage {
+    pub fn new(
+        tracker: UsageTracker,
+        user_id: Uuid,
+        provider: String,
+        model: String,
+        prompt_chars: usize,
+    ) -> Self {
+        Self {
+            tracker,
+            user_id,
+            provider,
+            model,
+            prompt_chars,
+            completion_chars: 0,
+            reported: None,
+        }
+    }
+
+    pub fn observe(&mut self, chunk: &AppResult<ChatStreamChunk>) {
+        if let Ok(chunk) = chunk {
+            self.completion_chars += chunk.delta.chars().count();
+            if chunk.usage.is_some() {
+                self.reported = chunk.usage;
+            }
+        }
+    }
+
+    pub fn usage(&self) -> TokenUsage {
+        self.reported
+            .unwrap_or_else(|| TokenUsage::estimate(self.prompt_chars, self.completion_chars))
+    }
+}
+
+impl Drop for StreamUsage {
+    fn drop(&mut self) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!("No runtime to record AI stream usage on");
+            return;
+        };
+
+        let tracker = self.tracker.clone();
+        let user_id = self.user_id;
+        let provider = std::mem::take(&mut self.provider);
+        let model = std::mem::take(&mut self.model);
+        let usage = self.usage();
+        runtime.spawn(async move {
+            tracker.record(user_id, &provider, &model, usage).await;
+        });
+    }
+}
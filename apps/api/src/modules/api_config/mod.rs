@@ -1,12 +1,8 @@
-use axum::{
-    http::StatusCode,
-    response::IntoResponse,
-    routing::get,
-    Json, Router,
-};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct ConfigResponse {
     pub environment: String,
     pub features: Vec<String>,
@@ -16,6 +12,9 @@ pub struct ConfigResponse {
 }
 
 /// Config information handler (safe runtime config)
+///
+/// Serves the snapshot taken when the router was built, so it keeps answering
+/// while the database is unavailable.
 #[utoipa::path(
     get,
     path = "/api/v1/config",
@@ -24,42 +23,55 @@ pub struct ConfigResponse {
         (status = 200, description = "Runtime configuration", body = ConfigResponse)
     )
 )]
-async fn config_info() -> impl IntoResponse {
-    let environment = std::env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
-
-    let mut features = vec!["auth".to_string(), "users".to_string()];
-
-    #[cfg(feature = "ai")]
-    features.push("ai".to_string());
-
-    #[cfg(feature = "websocket")]
-    features.push("websocket".to_string());
-
-    #[cfg(feature = "jobs")]
-    features.push("jobs".to_string());
-
-    #[cfg(feature = "storage")]
-    features.push("storage".to_string());
+async fn config_info(State(config): State<Arc<ConfigResponse>>) -> impl IntoResponse {
+    (StatusCode::OK, Json(config.as_ref().clone()))
+}
 
-    let cors_origins = std::env::var("CORS_ORIGINS")
-        .unwrap_or_else(|_| "http://localhost:3000".to_string())
-        .split(',')
-        .map(|s| s.to_string())
+impl ConfigResponse {
+    /// Read the exposed settings from the environment
+    pub fn from_env() -> Self {
+        let environment =
+            std::env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
+
+        let features = [
+            Some("auth"),
+            Some("users"),
+            cfg!(feature = "ai").then_some("ai"),
+            cfg!(feature = "websocket").then_some("websocket"),
+            cfg!(feature = "jobs").then_some("jobs"),
+            cfg!(feature = "storage").then_some("storage"),
+        ]
+        .into_iter()
+        .flatten()
+        .map(str::to_string)
         .collect();
 
-    let response = ConfigResponse {
-        environment,
-        features,
-        max_upload_size_mb: 10,
-        rate_limit_per_minute: 60,
-        cors_origins,
-    };
-
-    (StatusCode::OK, Json(response))
+        // The per-client default enforced by the rate limiter
+        let rate_limit_per_second: u32 = std::env::var("RATE_LIMIT_PER_SECOND")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(100);
+
+        let cors_origins = std::env::var("CORS_ORIGINS")
+            .unwrap_or_else(|_| "http://localhost:3000".to_string())
+            .split(',')
+            .map(|s| s.to_string())
+            .collect();
+
+        Self {
+            environment,
+            features,
+            max_upload_size_mb: 10,
+            rate_limit_per_minute: rate_limit_per_second.saturating_mul(60),
+            cors_origins,
+        }
+    }
 }
 
 pub fn routes() -> Router {
-    Router::new().route("/api/v1/config", get(config_info))
+    Router::new()
+        .route("/api/v1/config", get(config_info))
+        .with_state(Arc::new(ConfigResponse::from_env()))
 }
 
 #[cfg(test)]
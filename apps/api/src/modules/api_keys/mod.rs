@@ -0,0 +1,6 @@
+pub mod model;
+pub mod routes;
+pub mod service;
+
+pub use routes::admin_routes;
+pub use service::{ApiKeyService, API_KEY_HEADER};
@@ -0,0 +1,159 @@
+use axum::{
+    extract::{Path, Query, State},
+    middleware,
+    routing::{delete, get},
+    Extension, Json, Router,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::config::JwtConfig;
+use crate::modules::auth::{
+    jwt::Claims,
+    middleware::{auth_middleware, AuthMiddleware},
+    role_guard::require_admin,
+};
+use crate::utils::{
+    error::{AppResult, ErrorResponse},
+    response::{ApiResponse, PaginatedResponse},
+};
+
+use super::model::{ApiKeyResponse, UpdateApiKeyRequest};
+use super::service::ApiKeyService;
+
+#[derive(Clone)]
+struct ApiKeyState {
+    service: Arc<ApiKeyService>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ListApiKeysQuery {
+    /// Only keys owned by this user
+    user: Option<Uuid>,
+    #[serde(default = "default_page")]
+    page: u32,
+    #[serde(default = "default_per_page")]
+    per_page: u32,
+}
+
+fn default_page() -> u32 {
+    1
+}
+
+fn default_per_page() -> u32 {
+    20
+}
+
+/// Admin-only API key oversight routes
+pub fn admin_routes(db_pool: PgPool, jwt_config: JwtConfig) -> Router {
+    let auth = AuthMiddleware::new(jwt_config).with_status_check(db_pool.clone());
+    let service = Arc::new(ApiKeyService::new(db_pool));
+    let state = ApiKeyState { service };
+
+    Router::new()
+        .route("/api/v1/admin/api-keys", get(list_api_keys))
+        .route(
+            "/api/v1/admin/api-keys/{id}",
+            delete(revoke_api_key).patch(update_api_key),
+        )
+        .layer(middleware::from_fn(require_admin))
+        .layer(middleware::from_fn_with_state(auth, auth_middleware))
+        .with_state(state)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/api-keys",
+    tag = "api_keys",
+    params(ListApiKeysQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "A page of API keys", body = PaginatedResponse<ApiKeyResponse>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse)
+    )
+)]
+async fn list_api_keys(
+    State(state): State<ApiKeyState>,
+    Query(query): Query<ListApiKeysQuery>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let per_page = query.per_page.clamp(1, 100);
+    let (keys, total) = state.service.list(query.user, query.page, per_page).await?;
+
+    Ok(PaginatedResponse::new(keys, query.page, per_page, total))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/api-keys/{id}",
+    tag = "api_keys",
+    params(("id" = Uuid, Path, description = "API key id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The revoked key", body = ApiResponse<ApiKeyResponse>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "API key not found", body = ErrorResponse)
+    )
+)]
+async fn revoke_api_key(
+    State(state): State<ApiKeyState>,
+    Extension(claims): Extension<Claims>,
+    Path(key_id): Path<Uuid>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let api_key = state.service.revoke(&key_id).await?;
+
+    tracing::info!(
+        target: "audit",
+        admin_id = %claims.sub,
+        api_key_id = %api_key.id,
+        owner_id = %api_key.user_id,
+        "API key revoked by admin"
+    );
+
+    Ok(ApiResponse::with_message(
+        api_key,
+        "API key revoked".to_string(),
+    ))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/admin/api-keys/{id}",
+    tag = "api_keys",
+    params(("id" = Uuid, Path, description = "API key id")),
+    request_body = UpdateApiKeyRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The updated key", body = ApiResponse<ApiKeyResponse>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "API key not found", body = ErrorResponse)
+    )
+)]
+async fn update_api_key(
+    State(state): State<ApiKeyState>,
+    Extension(claims): Extension<Claims>,
+    Path(key_id): Path<Uuid>,
+    Json(request): Json<UpdateApiKeyRequest>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let api_key = state
+        .service
+        .set_rate_limit_exempt(&key_id, request.rate_limit_exempt)
+        .await?;
+
+    tracing::info!(
+        target: "audit",
+        admin_id = %claims.sub,
+        api_key_id = %api_key.id,
+        owner_id = %api_key.user_id,
+        rate_limit_exempt = api_key.rate_limit_exempt,
+        "API key rate-limit exemption changed by admin"
+    );
+
+    Ok(ApiResponse::success(api_key))
+}
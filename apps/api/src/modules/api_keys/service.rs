@@ -0,0 +1,159 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::modules::auth::hash::{hash_password, verify_password};
+use crate::utils::error::{AppError, AppResult};
+
+use super::model::{ApiKey, ApiKeyResponse, CreatedApiKey};
+
+/// Prefix identifying API keys issued by this service
+const KEY_PREFIX: &str = "vk";
+
+/// Header carrying an API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+pub struct ApiKeyService {
+    db_pool: PgPool,
+}
+
+impl ApiKeyService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Issue a new API key for a user
+    ///
+    /// Keys look like `vk_<prefix>_<secret>`. Only the prefix and an Argon2
+    /// hash of the secret are stored, so the full key is returned exactly once.
+    pub async fn create(&self, user_id: &Uuid, name: &str) -> AppResult<CreatedApiKey> {
+        let key_prefix = Uuid::new_v4().simple().to_string()[..12].to_string();
+        let secret = Uuid::new_v4().simple().to_string();
+        let key_hash = hash_password(&secret)?;
+
+        let api_key = sqlx::query_as::<_, ApiKey>(
+            r#"
+            INSERT INTO api_keys (id, user_id, name, key_prefix, key_hash, created_at)
+            VALUES ($1, $2, $3, $4, $5, NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(name)
+        .bind(&key_prefix)
+        .bind(&key_hash)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(CreatedApiKey {
+            key: format!("{}_{}_{}", KEY_PREFIX, key_prefix, secret),
+            api_key: api_key.into(),
+        })
+    }
+
+    /// List API key metadata across all users, optionally filtered by owner
+    pub async fn list(
+        &self,
+        user_id: Option<Uuid>,
+        page: u32,
+        per_page: u32,
+    ) -> AppResult<(Vec<ApiKeyResponse>, u64)> {
+        let offset = (page.max(1) - 1) * per_page;
+
+        let total: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM api_keys WHERE ($1::uuid IS NULL OR user_id = $1)",
+        )
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        let keys = sqlx::query_as::<_, ApiKey>(
+            r#"
+            SELECT * FROM api_keys
+            WHERE ($1::uuid IS NULL OR user_id = $1)
+            ORDER BY created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(per_page as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let responses: Vec<ApiKeyResponse> = keys.into_iter().map(Into::into).collect();
+
+        Ok((responses, total.0 as u64))
+    }
+
+    /// Revoke an API key, returning its metadata
+    pub async fn revoke(&self, key_id: &Uuid) -> AppResult<ApiKeyResponse> {
+        let api_key = sqlx::query_as::<_, ApiKey>(
+            r#"
+            UPDATE api_keys SET revoked_at = COALESCE(revoked_at, NOW())
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(key_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("API key not found".to_string()))?;
+
+        Ok(api_key.into())
+    }
+
+    /// Flag or unflag a key as exempt from rate limiting, returning its metadata
+    pub async fn set_rate_limit_exempt(
+        &self,
+        key_id: &Uuid,
+        exempt: bool,
+    ) -> AppResult<ApiKeyResponse> {
+        let api_key = sqlx::query_as::<_, ApiKey>(
+            "UPDATE api_keys SET rate_limit_exempt = $2 WHERE id = $1 RETURNING *",
+        )
+        .bind(key_id)
+        .bind(exempt)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("API key not found".to_string()))?;
+
+        Ok(api_key.into())
+    }
+
+    /// Resolve a presented API key, rejecting unknown or revoked keys
+    pub async fn authenticate(&self, presented_key: &str) -> AppResult<ApiKey> {
+        let api_key = self.verify(presented_key).await?;
+
+        sqlx::query("UPDATE api_keys SET last_used_at = NOW() WHERE id = $1")
+            .bind(api_key.id)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(api_key)
+    }
+
+    /// Like `authenticate`, but without recording the use
+    pub async fn verify(&self, presented_key: &str) -> AppResult<ApiKey> {
+        let invalid = || AppError::Authentication("Invalid API key".to_string());
+
+        let mut parts = presented_key.splitn(3, '_');
+        let (Some(KEY_PREFIX), Some(key_prefix), Some(secret)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+
+        let api_key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE key_prefix = $1")
+            .bind(key_prefix)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(invalid)?;
+
+        if api_key.is_revoked() || !verify_password(secret, &api_key.key_hash)? {
+            return Err(invalid());
+        }
+
+        Ok(api_key)
+    }
+}
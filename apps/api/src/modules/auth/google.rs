@@ -0,0 +1,282 @@
+use axum::{
+    extract::{ConnectInfo, Query, State},
+    http::{header, HeaderMap, Request, StatusCode},
+    response::{IntoResponse, Redirect},
+    routing::get,
+    Extension, Router,
+};
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper_rustls::HttpsConnector;
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::TokioExecutor,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use sqlx::PgPool;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::config::{GoogleOAuthConfig, JwtConfig};
+use crate::events::EventBus;
+use crate::utils::{
+    error::{AppError, AppResult, ErrorResponse},
+    response::ApiResponse,
+};
+
+use super::model::{AuthResponse, ClientInfo, OAuthProfile};
+use super::routes::cookie;
+use super::service::AuthService;
+
+/// Cookie binding the consent redirect to the browser that started it
+pub const OAUTH_STATE_COOKIE: &str = "oauth_state";
+
+/// How long the consent screen may take before the state cookie expires
+const STATE_TTL: Duration = Duration::from_secs(600);
+
+/// Per-request timeout for Google's token and userinfo endpoints
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+const CALLBACK_PATH: &str = "/auth/oauth/google/callback";
+
+type HttpsClient = Client<HttpsConnector<HttpConnector>, Full<Bytes>>;
+
+/// Authorization code flow against Google's OpenID Connect endpoints
+#[derive(Clone)]
+pub struct GoogleOAuth {
+    config: Arc<GoogleOAuthConfig>,
+    client: HttpsClient,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct UserInfo {
+    sub: String,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    name: Option<String>,
+}
+
+impl GoogleOAuth {
+    pub fn new(config: GoogleOAuthConfig) -> Self {
+        let connector = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_provider_and_webpki_roots(rustls::crypto::aws_lc_rs::default_provider())
+            .expect("Default TLS provider supports the safe protocol versions")
+            .https_or_http()
+            .enable_http1()
+            .build();
+
+        Self {
+            config: Arc::new(config),
+            client: Client::builder(TokioExecutor::new()).build(connector),
+        }
+    }
+
+    /// Google's consent screen for the `openid email profile` scopes
+    pub fn authorize_url(&self, state: &str) -> String {
+        let query = serde_urlencoded::to_string([
+            ("client_id", self.config.client_id.as_str()),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", "openid email profile"),
+            ("state", state),
+            ("prompt", "select_account"),
+        ])
+        .expect("OAuth query parameters are plain strings");
+
+        format!("{}?{}", self.config.auth_url, query)
+    }
+
+    /// Exchange an authorization code for the signed-in Google account
+    pub async fn fetch_profile(&self, code: &str) -> AppResult<OAuthProfile> {
+        let form = serde_urlencoded::to_string([
+            ("code", code),
+            ("client_id", self.config.client_id.as_str()),
+            ("client_secret", self.config.client_secret.as_str()),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("grant_type", "authorization_code"),
+        ])
+        .map_err(|e| AppError::InternalServer(e.to_string()))?;
+        let request = Request::post(&self.config.token_url)
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(header::ACCEPT, "application/json")
+            .body(Full::new(Bytes::from(form)))
+            .map_err(|e| AppError::InternalServer(e.to_string()))?;
+        let token: TokenResponse = self.send(request, "token").await?;
+
+        let request = Request::get(&self.config.userinfo_url)
+            .header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", token.access_token),
+            )
+            .header(header::ACCEPT, "application/json")
+            .body(Full::new(Bytes::new()))
+            .map_err(|e| AppError::InternalServer(e.to_string()))?;
+        let info: UserInfo = self.send(request, "userinfo").await?;
+
+        let email = info.email.ok_or_else(|| {
+            AppError::Authentication("The Google account has no email address".to_string())
+        })?;
+        Ok(OAuthProfile {
+            provider: "google",
+            subject: info.sub,
+            email,
+            email_verified: info.email_verified,
+            name: info.name,
+        })
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: Request<Full<Bytes>>,
+        endpoint: &str,
+    ) -> AppResult<T> {
+        let response = tokio::time::timeout(REQUEST_TIMEOUT, self.client.request(request))
+            .await
+            .map_err(|_| {
+                AppError::ExternalService(format!("Google {} request timed out", endpoint))
+            })?
+            .map_err(|e| AppError::ExternalService(format!("Google {}: {}", endpoint, e)))?;
+
+        let status = response.status();
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("Google {}: {}", endpoint, e)))?
+            .to_bytes();
+
+        // A rejected code (expired, reused, forged) is the caller's problem
+        if status == StatusCode::BAD_REQUEST || status == StatusCode::UNAUTHORIZED {
+            return Err(AppError::Authentication(
+                "Google rejected the authorization code".to_string(),
+            ));
+        }
+        if !status.is_success() {
+            return Err(AppError::ExternalService(format!(
+                "Google {} responded with {}",
+                endpoint, status
+            )));
+        }
+
+        serde_json::from_slice(&body).map_err(|e| {
+            AppError::ExternalService(format!("Unexpected Google {} response: {}", endpoint, e))
+        })
+    }
+}
+
+#[derive(Clone)]
+struct GoogleState {
+    service: Arc<AuthService>,
+    google: GoogleOAuth,
+}
+
+/// Sign in with Google: `GET /auth/oauth/google` redirects to the consent
+/// screen, whose callback answers like `POST /auth/login`
+pub fn routes(
+    db_pool: PgPool,
+    jwt_config: JwtConfig,
+    google: GoogleOAuth,
+    current_terms_version: Option<String>,
+    events: EventBus,
+) -> Router {
+    let service = Arc::new(
+        AuthService::new(db_pool, jwt_config)
+            .with_terms_version(current_terms_version)
+            .with_events(events),
+    );
+
+    Router::new()
+        .route("/auth/oauth/google", get(start))
+        .route(CALLBACK_PATH, get(callback))
+        .with_state(GoogleState { service, google })
+}
+
+/// Only mounted when Google sign-in is configured
+#[utoipa::path(
+    get,
+    path = "/auth/oauth/google",
+    tag = "auth",
+    responses(
+        (status = 303, description = "Redirect to Google's consent screen, setting the `oauth_state` cookie")
+    )
+)]
+async fn start(State(state): State<GoogleState>) -> impl IntoResponse {
+    let csrf_state = Uuid::new_v4().simple().to_string();
+    let cookie = format!(
+        "{}={}; Path={}; Max-Age={}; HttpOnly; Secure; SameSite=Lax",
+        OAUTH_STATE_COOKIE,
+        csrf_state,
+        CALLBACK_PATH,
+        STATE_TTL.as_secs()
+    );
+
+    (
+        [(header::SET_COOKIE, cookie)],
+        Redirect::to(&state.google.authorize_url(&csrf_state)),
+    )
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/oauth/google/callback",
+    tag = "auth",
+    params(CallbackQuery),
+    responses(
+        (status = 200, description = "Signed in", body = ApiResponse<AuthResponse>),
+        (status = 400, description = "State mismatch or missing code", body = ErrorResponse),
+        (status = 401, description = "Google refused the code or the email is unverified", body = ErrorResponse)
+    )
+)]
+async fn callback(
+    State(state): State<GoogleState>,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Query(query): Query<CallbackQuery>,
+) -> AppResult<impl IntoResponse> {
+    if let Some(error) = query.error {
+        return Err(AppError::Authentication(format!(
+            "Google sign-in was not completed: {}",
+            error
+        )));
+    }
+
+    // The state must come back to the same browser that was sent away
+    let expected = cookie(&headers, OAUTH_STATE_COOKIE);
+    if expected.is_none() || expected != query.state.as_deref() {
+        return Err(AppError::BadRequest(
+            "OAuth state is missing or does not match".to_string(),
+        ));
+    }
+    let code = query
+        .code
+        .ok_or_else(|| AppError::BadRequest("Missing authorization code".to_string()))?;
+
+    let profile = state.google.fetch_profile(&code).await?;
+    let client_ip = connect_info.map(|Extension(ConnectInfo(peer))| peer.ip());
+    let client = ClientInfo::new(&headers, client_ip);
+    let response = state.service.oauth_login(profile, &client).await?;
+
+    let clear_state = format!(
+        "{}=; Path={}; Max-Age=0; HttpOnly; Secure; SameSite=Lax",
+        OAUTH_STATE_COOKIE, CALLBACK_PATH
+    );
+    Ok((
+        [(header::SET_COOKIE, clear_state)],
+        ApiResponse::success(response),
+    ))
+}
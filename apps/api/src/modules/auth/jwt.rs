@@ -1,5 +1,5 @@
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -7,15 +7,32 @@ use crate::config::JwtConfig;
 use crate::modules::users::model::UserRole;
 use crate::utils::error::{AppError, AppResult};
 
+/// Algorithm used to sign and verify every token
+pub const SIGNING_ALGORITHM: Algorithm = Algorithm::HS256;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
-    pub sub: String,        // Subject (user ID)
+    pub sub: String, // Subject (user ID)
     pub email: String,
-    pub role: UserRole,     // User role
-    pub exp: i64,           // Expiration time
-    pub iat: i64,           // Issued at
-    pub iss: String,        // Issuer
+    pub role: UserRole, // User role
+    pub exp: i64,       // Expiration time
+    pub iat: i64,       // Issued at
+    pub iss: String,    // Issuer
     pub token_type: TokenType,
+    /// When the session began (initial login); carried unchanged across refreshes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_time: Option<i64>,
+    /// Unique token id; set on refresh tokens so two issued in the same
+    /// second still differ
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+}
+
+impl Claims {
+    /// Session start, falling back to `iat` for tokens issued without `auth_time`
+    pub fn session_started_at(&self) -> i64 {
+        self.auth_time.unwrap_or(self.iat)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -39,6 +56,16 @@ pub fn generate_access_token(
     email: &str,
     role: UserRole,
     config: &JwtConfig,
+) -> AppResult<String> {
+    generate_session_access_token(user_id, email, role, Utc::now().timestamp(), config)
+}
+
+fn generate_session_access_token(
+    user_id: &Uuid,
+    email: &str,
+    role: UserRole,
+    auth_time: i64,
+    config: &JwtConfig,
 ) -> AppResult<String> {
     let expiration = Utc::now()
         .checked_add_signed(Duration::hours(config.access_token_expiry_hours))
@@ -48,15 +75,17 @@ pub fn generate_access_token(
     let claims = Claims {
         sub: user_id.to_string(),
         email: email.to_string(),
-        role,  // Include role
+        role, // Include role
         exp: expiration,
         iat: Utc::now().timestamp(),
         iss: config.issuer.clone(),
         token_type: TokenType::Access,
+        auth_time: Some(auth_time),
+        jti: None,
     };
 
     encode(
-        &Header::default(),
+        &Header::new(SIGNING_ALGORITHM),
         &claims,
         &EncodingKey::from_secret(config.secret.as_bytes()),
     )
@@ -69,39 +98,64 @@ pub fn generate_refresh_token(
     email: &str,
     role: UserRole,
     config: &JwtConfig,
+) -> AppResult<String> {
+    generate_session_refresh_token(user_id, email, role, Utc::now().timestamp(), config)
+}
+
+/// Refresh tokens never outlive the session's absolute lifetime
+fn generate_session_refresh_token(
+    user_id: &Uuid,
+    email: &str,
+    role: UserRole,
+    auth_time: i64,
+    config: &JwtConfig,
 ) -> AppResult<String> {
     let expiration = Utc::now()
         .checked_add_signed(Duration::days(config.refresh_token_expiry_days))
         .ok_or_else(|| AppError::InternalServer("Invalid expiration time".to_string()))?
-        .timestamp();
+        .timestamp()
+        .min(session_expires_at(auth_time, config));
 
     let claims = Claims {
         sub: user_id.to_string(),
         email: email.to_string(),
-        role,  // Include role
+        role, // Include role
         exp: expiration,
         iat: Utc::now().timestamp(),
         iss: config.issuer.clone(),
         token_type: TokenType::Refresh,
+        auth_time: Some(auth_time),
+        jti: Some(Uuid::new_v4().to_string()),
     };
 
     encode(
-        &Header::default(),
+        &Header::new(SIGNING_ALGORITHM),
         &claims,
         &EncodingKey::from_secret(config.secret.as_bytes()),
     )
     .map_err(|e| AppError::Authentication(format!("Failed to generate refresh token: {}", e)))
 }
 
-/// Generate both access and refresh tokens with role
+/// Generate both access and refresh tokens with role, starting a new session
 pub fn generate_token_pair(
     user_id: &Uuid,
     email: &str,
     role: UserRole,
     config: &JwtConfig,
 ) -> AppResult<TokenPair> {
-    let access_token = generate_access_token(user_id, email, role, config)?;
-    let refresh_token = generate_refresh_token(user_id, email, role, config)?;
+    generate_session_token_pair(user_id, email, role, Utc::now().timestamp(), config)
+}
+
+/// Generate a token pair continuing the session that began at `auth_time`
+pub fn generate_session_token_pair(
+    user_id: &Uuid,
+    email: &str,
+    role: UserRole,
+    auth_time: i64,
+    config: &JwtConfig,
+) -> AppResult<TokenPair> {
+    let access_token = generate_session_access_token(user_id, email, role, auth_time, config)?;
+    let refresh_token = generate_session_refresh_token(user_id, email, role, auth_time, config)?;
 
     Ok(TokenPair {
         access_token,
@@ -113,7 +167,7 @@ pub fn generate_token_pair(
 
 /// Validate and decode a JWT token
 pub fn validate_token(token: &str, config: &JwtConfig) -> AppResult<Claims> {
-    let mut validation = Validation::default();
+    let mut validation = Validation::new(SIGNING_ALGORITHM);
     validation.set_issuer(&[config.issuer.clone()]);
 
     decode::<Claims>(
@@ -122,9 +176,7 @@ pub fn validate_token(token: &str, config: &JwtConfig) -> AppResult<Claims> {
         &validation,
     )
     .map(|data| data.claims)
-    .map_err(|e| {
-        AppError::Authentication(format!("Invalid token: {}", e))
-    })
+    .map_err(|e| AppError::Authentication(format!("Invalid token: {}", e)))
 }
 
 /// Validate that the token is an access token
@@ -132,21 +184,28 @@ pub fn validate_access_token(token: &str, config: &JwtConfig) -> AppResult<Claim
     let claims = validate_token(token, config)?;
 
     if claims.token_type != TokenType::Access {
-        return Err(AppError::Authentication(
-            "Invalid token type".to_string(),
-        ));
+        return Err(AppError::Authentication("Invalid token type".to_string()));
     }
 
     Ok(claims)
 }
 
-/// Validate that the token is a refresh token
+/// Unix time at which a session that began at `auth_time` can no longer be refreshed
+pub fn session_expires_at(auth_time: i64, config: &JwtConfig) -> i64 {
+    auth_time.saturating_add(config.max_session_days.saturating_mul(86_400))
+}
+
+/// Validate that the token is a refresh token for a session still within its lifetime
 pub fn validate_refresh_token(token: &str, config: &JwtConfig) -> AppResult<Claims> {
     let claims = validate_token(token, config)?;
 
     if claims.token_type != TokenType::Refresh {
+        return Err(AppError::Authentication("Invalid token type".to_string()));
+    }
+
+    if Utc::now().timestamp() >= session_expires_at(claims.session_started_at(), config) {
         return Err(AppError::Authentication(
-            "Invalid token type".to_string(),
+            "Session expired, please log in again".to_string(),
         ));
     }
 
@@ -163,6 +222,7 @@ mod tests {
             access_token_expiry_hours: 24,
             refresh_token_expiry_days: 30,
             issuer: "vibe-api-test".to_string(),
+            max_session_days: 30,
         }
     }
 
@@ -176,8 +236,7 @@ mod tests {
         let token = generate_access_token(&user_id, email, role, &config)
             .expect("Failed to generate token");
 
-        let claims = validate_access_token(&token, &config)
-            .expect("Failed to validate token");
+        let claims = validate_access_token(&token, &config).expect("Failed to validate token");
 
         assert_eq!(claims.sub, user_id.to_string());
         assert_eq!(claims.email, email);
@@ -0,0 +1,97 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::security::{self, SecurityEvent};
+
+/// Email and client IP a run of failures is counted against
+type LockoutKey = (String, Option<IpAddr>);
+
+#[derive(Debug)]
+struct FailedLogins {
+    count: u32,
+    first_failed_at: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// Locks an email out of login from one client IP after repeated failures;
+/// failures older than the lockout duration are forgotten and a successful
+/// login clears them. Other addresses can still sign in to the account, so a
+/// third party can't lock its owner out.
+#[derive(Clone)]
+pub struct LoginLockout {
+    max_failed_attempts: u32,
+    duration: Duration,
+    failures: Arc<Mutex<HashMap<LockoutKey, FailedLogins>>>,
+}
+
+impl LoginLockout {
+    /// `max_failed_attempts` of 0 disables the lockout
+    pub fn new(max_failed_attempts: u32, duration: Duration) -> Self {
+        Self {
+            max_failed_attempts,
+            duration,
+            failures: Arc::default(),
+        }
+    }
+
+    pub fn is_locked(&self, email: &str, client_ip: Option<IpAddr>) -> bool {
+        let failures = self.failures.lock().unwrap();
+        failures
+            .get(&(email.to_string(), client_ip))
+            .and_then(|entry| entry.locked_until)
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Count a failed login, locking the email for this client and emitting a
+    /// security event when the limit is reached
+    pub fn record_failure(&self, email: &str, client_ip: Option<IpAddr>) {
+        if self.max_failed_attempts == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut failures = self.failures.lock().unwrap();
+        // Drop expired entries so guessed emails cannot grow the map forever
+        failures.retain(|_, entry| {
+            now.duration_since(entry.first_failed_at) < self.duration
+                || entry.locked_until.is_some_and(|until| now < until)
+        });
+
+        let entry = failures
+            .entry((email.to_string(), client_ip))
+            .or_insert_with(|| FailedLogins {
+                count: 0,
+                first_failed_at: now,
+                locked_until: None,
+            });
+
+        entry.count += 1;
+        if entry.count == self.max_failed_attempts {
+            entry.locked_until = Some(now + self.duration);
+            security::emit(SecurityEvent::LoginLockout {
+                email,
+                client_ip,
+                failed_attempts: entry.count,
+                locked_for_secs: self.duration.as_secs(),
+            });
+        }
+    }
+
+    pub fn record_success(&self, email: &str, client_ip: Option<IpAddr>) {
+        self.failures
+            .lock()
+            .unwrap()
+            .remove(&(email.to_string(), client_ip));
+    }
+}
+
+impl Default for LoginLockout {
+    /// Disabled
+    fn default() -> Self {
+        Self::new(0, Duration::from_secs(15 * 60))
+    }
+}
@@ -4,27 +4,41 @@ use axum::{
     middleware::Next,
     response::Response,
 };
+use sqlx::PgPool;
 use std::sync::Arc;
+use uuid::Uuid;
 
 use crate::config::JwtConfig;
+use crate::database::query_count::tracked;
 use crate::modules::auth::jwt::{validate_access_token, Claims};
+use crate::modules::users::model::UserStatus;
 use crate::utils::error::AppError;
 
+/// State for `auth_middleware`
 #[derive(Clone)]
 pub struct AuthMiddleware {
     pub jwt_config: Arc<JwtConfig>,
+    pub db_pool: Option<PgPool>,
 }
 
 impl AuthMiddleware {
     pub fn new(jwt_config: JwtConfig) -> Self {
         Self {
             jwt_config: Arc::new(jwt_config),
+            db_pool: None,
         }
     }
+
+    /// Also look up the account status on every request, so suspending a
+    /// user cuts off access tokens that were already issued
+    pub fn with_status_check(mut self, db_pool: PgPool) -> Self {
+        self.db_pool = Some(db_pool);
+        self
+    }
 }
 
 /// Extract JWT token from Authorization header
-fn extract_token(headers: &HeaderMap) -> Result<String, AppError> {
+pub(crate) fn extract_token(headers: &HeaderMap) -> Result<String, AppError> {
     let auth_header = headers
         .get("Authorization")
         .and_then(|v| v.to_str().ok())
@@ -40,13 +54,32 @@ fn extract_token(headers: &HeaderMap) -> Result<String, AppError> {
 }
 
 /// Middleware function to validate JWT token
+///
+/// With a status check configured, tokens of suspended users fail with 403
+/// `ACCOUNT_SUSPENDED`; tokens of deleted users are left to the handlers.
 pub async fn auth_middleware(
-    State(jwt_config): State<Arc<JwtConfig>>,
+    State(auth): State<AuthMiddleware>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, AppError> {
     let token = extract_token(request.headers())?;
-    let claims = validate_access_token(&token, &jwt_config)?;
+    let claims = validate_access_token(&token, &auth.jwt_config)?;
+
+    if let Some(db_pool) = &auth.db_pool {
+        let user_id = Uuid::parse_str(&claims.sub)
+            .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+        let status = tracked(
+            sqlx::query_scalar::<_, UserStatus>(
+                "SELECT status FROM users WHERE id = $1 AND deleted_at IS NULL",
+            )
+            .bind(user_id)
+            .fetch_optional(db_pool),
+        )
+        .await?;
+        if status == Some(UserStatus::Suspended) {
+            return Err(AppError::AccountSuspended);
+        }
+    }
 
     // Insert claims into request extensions so handlers can access them
     request.extensions_mut().insert(claims);
@@ -94,7 +127,10 @@ mod tests {
     #[test]
     fn test_extract_token_invalid_format() {
         let mut headers = HeaderMap::new();
-        headers.insert("Authorization", HeaderValue::from_static("InvalidFormat token"));
+        headers.insert(
+            "Authorization",
+            HeaderValue::from_static("InvalidFormat token"),
+        );
 
         let result = extract_token(&headers);
         assert!(result.is_err());
@@ -1,11 +1,20 @@
-pub mod jwt;
+pub mod google;
 pub mod hash;
-pub mod service;
-pub mod model;
-pub mod routes;
+pub mod jwt;
+pub mod lockout;
 pub mod middleware;
+pub mod model;
 pub mod role_guard;
+pub mod routes;
+pub mod service;
+pub mod signup;
+pub mod verified;
 
-pub use routes::routes;
+pub use google::GoogleOAuth;
+pub use lockout::LoginLockout;
 pub use middleware::AuthMiddleware;
-pub use role_guard::{require_admin, require_moderator, require_role};
+pub use role_guard::{
+    require_admin, require_moderator, require_permission, require_role, Permission,
+};
+pub use routes::routes;
+pub use signup::SignupGuard;
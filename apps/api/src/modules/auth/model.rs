@@ -1,12 +1,18 @@
+use axum::http::{header, HeaderMap};
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use std::net::IpAddr;
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
+use crate::config::JwtConfig;
 use crate::modules::users::model::UserRole;
+use crate::utils::validation::deserialize_email;
 
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct RegisterRequest {
     #[schema(example = "user@example.com")]
+    #[serde(deserialize_with = "deserialize_email")]
     #[validate(email(message = "Invalid email address"))]
     pub email: String,
 
@@ -14,18 +20,19 @@ pub struct RegisterRequest {
     #[validate(length(min = 8, message = "Password must be at least 8 characters"))]
     pub password: String,
 
+    /// Checked against the configured `NamePolicy`
     #[schema(example = "John Doe")]
-    #[validate(length(min = 2, max = 100, message = "Name must be between 2 and 100 characters"))]
     pub name: String,
 
     #[schema(example = "user")]
-    // Optional role (defaults to User if not provided)
-    pub role: Option<UserRole>,
+    // Optional role (defaults to User if not provided); parsed with `UserRole::parse`
+    pub role: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct LoginRequest {
     #[schema(example = "user@example.com")]
+    #[serde(deserialize_with = "deserialize_email")]
     #[validate(email(message = "Invalid email address"))]
     pub email: String,
 
@@ -34,6 +41,38 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ForgotPasswordRequest {
+    #[schema(example = "user@example.com")]
+    #[serde(deserialize_with = "deserialize_email")]
+    #[validate(email(message = "Invalid email address"))]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ResendVerificationRequest {
+    #[schema(example = "user@example.com")]
+    #[serde(deserialize_with = "deserialize_email")]
+    #[validate(email(message = "Invalid email address"))]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct VerifyEmailQuery {
+    pub token: String,
+}
+
+/// `new_password` follows the same rules as the signup password
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+
+    #[schema(example = "NewSecurePass123!")]
+    #[validate(length(min = 8, message = "Password must be at least 8 characters"))]
+    pub new_password: String,
+}
+
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct RefreshTokenRequest {
     #[schema(example = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...")]
@@ -56,3 +95,84 @@ pub struct UserInfo {
     pub name: String,
     pub role: UserRole,
 }
+
+/// Non-secret token parameters clients use to schedule refreshes
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct JwtPublicConfig {
+    #[schema(example = "vibe-api")]
+    pub issuer: String,
+    #[schema(example = "HS256")]
+    pub algorithm: String,
+    #[schema(example = 86400)]
+    pub access_token_expiry_seconds: i64,
+    #[schema(example = 2592000)]
+    pub refresh_token_expiry_seconds: i64,
+    /// Refresh is refused once this long has passed since login
+    #[schema(example = 2592000)]
+    pub max_session_seconds: i64,
+}
+
+impl From<&JwtConfig> for JwtPublicConfig {
+    fn from(config: &JwtConfig) -> Self {
+        Self {
+            issuer: config.issuer.clone(),
+            algorithm: format!("{:?}", super::jwt::SIGNING_ALGORITHM),
+            access_token_expiry_seconds: config.access_token_expiry_hours * 3600,
+            refresh_token_expiry_seconds: config.refresh_token_expiry_days * 86_400,
+            max_session_seconds: config.max_session_days * 86_400,
+        }
+    }
+}
+
+/// Longest `User-Agent` kept for a session, in characters
+pub const MAX_SESSION_USER_AGENT_CHARS: usize = 255;
+
+/// Where a login came from, recorded with its refresh tokens so users can
+/// tell their sessions apart
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    /// The client's /24 (IPv4) or /48 (IPv6), never the full address
+    pub network: Option<String>,
+    /// The full address, for the login lockout only; never stored
+    pub ip: Option<IpAddr>,
+}
+
+impl ClientInfo {
+    pub fn new(headers: &HeaderMap, ip: Option<IpAddr>) -> Self {
+        let user_agent = headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.chars().take(MAX_SESSION_USER_AGENT_CHARS).collect());
+
+        Self {
+            user_agent,
+            network: ip.map(coarse_network),
+            ip,
+        }
+    }
+}
+
+fn coarse_network(ip: IpAddr) -> String {
+    let prefix = match ip {
+        IpAddr::V4(_) => 24,
+        IpAddr::V6(_) => 48,
+    };
+    IpNet::new(ip, prefix)
+        .map(|network| network.trunc().to_string())
+        .unwrap_or_else(|_| ip.to_string())
+}
+
+/// Identity asserted by an OAuth provider after a successful code exchange
+#[derive(Debug, Clone)]
+pub struct OAuthProfile {
+    /// Provider name as stored in `oauth_connections`, e.g. `google`
+    pub provider: &'static str,
+    /// The provider's stable account id (`sub`)
+    pub subject: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub name: Option<String>,
+}
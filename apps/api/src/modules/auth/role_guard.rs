@@ -1,14 +1,84 @@
 use axum::{
-    extract::Request,
-    http::StatusCode,
+    extract::{Request, State},
     middleware::Next,
     response::Response,
 };
+use serde::Serialize;
 
 use crate::modules::auth::jwt::Claims;
 use crate::modules::users::model::UserRole;
 use crate::utils::error::AppError;
 
+/// A capability a route can require instead of a role name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    UsersList,
+    UsersRead,
+    UsersDelete,
+    UsersManageRoles,
+    /// Suspend and reinstate accounts
+    UsersSuspend,
+    StorageUpload,
+    /// Read and delete files other users uploaded
+    StorageManageAny,
+    AiUsageRead,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 8] = [
+        Permission::UsersList,
+        Permission::UsersRead,
+        Permission::UsersDelete,
+        Permission::UsersManageRoles,
+        Permission::UsersSuspend,
+        Permission::StorageUpload,
+        Permission::StorageManageAny,
+        Permission::AiUsageRead,
+    ];
+}
+
+/// Permissions each role holds; granting a role a capability only takes a
+/// change here
+pub fn role_permissions(role: UserRole) -> &'static [Permission] {
+    match role {
+        UserRole::Admin => &Permission::ALL,
+        UserRole::Moderator => &[
+            Permission::UsersList,
+            Permission::UsersRead,
+            Permission::StorageUpload,
+        ],
+        UserRole::User => &[Permission::StorageUpload],
+    }
+}
+
+pub fn has_permission(role: UserRole, permission: Permission) -> bool {
+    role_permissions(role).contains(&permission)
+}
+
+/// Permission guard middleware - checks the user's role grants `permission`
+///
+/// Use with `middleware::from_fn_with_state(Permission::UsersList, require_permission)`.
+pub async fn require_permission(
+    State(permission): State<Permission>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let claims = request
+        .extensions()
+        .get::<Claims>()
+        .ok_or_else(|| AppError::Authentication("No authentication found".to_string()))?;
+
+    if !has_permission(claims.role, permission) {
+        return Err(AppError::Authorization(format!(
+            "Insufficient permissions. Required: {:?}, Have: {:?}",
+            permission, claims.role
+        )));
+    }
+
+    Ok(next.run(request).await)
+}
+
 /// Role guard middleware - checks if user has required role
 pub async fn require_role(
     required_roles: Vec<UserRole>,
@@ -23,27 +93,22 @@ pub async fn require_role(
 
     // Check if user has one of the required roles
     if !required_roles.contains(&claims.role) {
-        return Err(AppError::Authorization(
-            format!("Insufficient permissions. Required: {:?}, Have: {:?}", required_roles, claims.role)
-        ));
+        return Err(AppError::Authorization(format!(
+            "Insufficient permissions. Required: {:?}, Have: {:?}",
+            required_roles, claims.role
+        )));
     }
 
     Ok(next.run(request).await)
 }
 
 /// Require admin role
-pub async fn require_admin(
-    request: Request,
-    next: Next,
-) -> Result<Response, AppError> {
+pub async fn require_admin(request: Request, next: Next) -> Result<Response, AppError> {
     require_role(vec![UserRole::Admin], request, next).await
 }
 
 /// Require admin or moderator role
-pub async fn require_moderator(
-    request: Request,
-    next: Next,
-) -> Result<Response, AppError> {
+pub async fn require_moderator(request: Request, next: Next) -> Result<Response, AppError> {
     require_role(vec![UserRole::Admin, UserRole::Moderator], request, next).await
 }
 
@@ -77,6 +142,8 @@ mod tests {
             iat: Utc::now().timestamp(),
             iss: "test".to_string(),
             token_type: TokenType::Access,
+            auth_time: None,
+            jti: None,
         }
     }
 
@@ -92,7 +159,9 @@ mod tests {
             .unwrap();
 
         // Add admin claims to request extensions
-        request.extensions_mut().insert(create_test_claims(UserRole::Admin));
+        request
+            .extensions_mut()
+            .insert(create_test_claims(UserRole::Admin));
 
         let response = app.oneshot(request).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
@@ -110,7 +179,9 @@ mod tests {
             .unwrap();
 
         // Add user claims to request extensions
-        request.extensions_mut().insert(create_test_claims(UserRole::User));
+        request
+            .extensions_mut()
+            .insert(create_test_claims(UserRole::User));
 
         let response = app.oneshot(request).await.unwrap();
         assert_eq!(response.status(), StatusCode::FORBIDDEN);
@@ -128,7 +199,9 @@ mod tests {
             .unwrap();
 
         // Add moderator claims to request extensions
-        request.extensions_mut().insert(create_test_claims(UserRole::Moderator));
+        request
+            .extensions_mut()
+            .insert(create_test_claims(UserRole::Moderator));
 
         let response = app.oneshot(request).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
@@ -146,12 +219,70 @@ mod tests {
             .unwrap();
 
         // Admin should also have moderator permissions
-        request.extensions_mut().insert(create_test_claims(UserRole::Admin));
+        request
+            .extensions_mut()
+            .insert(create_test_claims(UserRole::Admin));
 
         let response = app.oneshot(request).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[test]
+    fn test_admin_holds_every_permission() {
+        for permission in Permission::ALL {
+            assert!(has_permission(UserRole::Admin, permission));
+        }
+    }
+
+    #[test]
+    fn test_moderator_and_user_permissions() {
+        assert!(has_permission(UserRole::Moderator, Permission::UsersList));
+        assert!(has_permission(UserRole::Moderator, Permission::UsersRead));
+        assert!(!has_permission(
+            UserRole::Moderator,
+            Permission::UsersDelete
+        ));
+        assert!(!has_permission(
+            UserRole::Moderator,
+            Permission::UsersManageRoles
+        ));
+        assert!(!has_permission(
+            UserRole::Moderator,
+            Permission::UsersSuspend
+        ));
+        assert!(!has_permission(
+            UserRole::Moderator,
+            Permission::StorageManageAny
+        ));
+        assert_eq!(
+            role_permissions(UserRole::User),
+            &[Permission::StorageUpload]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_require_permission_checks_role_mapping() {
+        let app =
+            Router::new()
+                .route("/users", get(test_handler))
+                .layer(middleware::from_fn_with_state(
+                    Permission::UsersList,
+                    require_permission,
+                ));
+
+        let mut statuses = Vec::new();
+        for role in [UserRole::Moderator, UserRole::User] {
+            let mut request = HttpRequest::builder()
+                .uri("/users")
+                .body(Body::empty())
+                .unwrap();
+            request.extensions_mut().insert(create_test_claims(role));
+            statuses.push(app.clone().oneshot(request).await.unwrap().status());
+        }
+
+        assert_eq!(statuses, [StatusCode::OK, StatusCode::FORBIDDEN]);
+    }
+
     #[tokio::test]
     async fn test_role_guard_without_claims() {
         let app = Router::new()
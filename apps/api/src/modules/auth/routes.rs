@@ -1,69 +1,375 @@
 use axum::{
-    routing::post,
-    Router, Json, extract::State,
+    extract::{Query, State},
+    http::{header, HeaderMap},
+    middleware,
+    response::Response,
+    routing::{get, post},
+    Extension, Router,
 };
 use sqlx::PgPool;
 use std::sync::Arc;
+use utoipa::TupleUnit;
+use uuid::Uuid;
 use validator::Validate;
 
 use crate::config::JwtConfig;
+use crate::events::EventBus;
+use crate::middleware::ClientIp;
 use crate::utils::{
-    error::{AppError, AppResult},
-    response::{created, ApiResponse},
-    validation::validate_struct,
+    error::{AppError, AppResult, ErrorResponse},
+    extract::JsonOrForm,
+    response::{created, no_content, ApiResponse},
+    validation::{field_errors, validate_struct, NamePolicy},
 };
 
-use super::model::{AuthResponse, LoginRequest, RefreshTokenRequest, RegisterRequest};
+use super::jwt::Claims;
+use super::lockout::LoginLockout;
+use super::middleware::{auth_middleware, AuthMiddleware};
+use super::model::{
+    AuthResponse, ClientInfo, ForgotPasswordRequest, JwtPublicConfig, LoginRequest,
+    RefreshTokenRequest, RegisterRequest, ResendVerificationRequest, ResetPasswordRequest,
+    VerifyEmailQuery,
+};
 use super::service::AuthService;
+use super::signup::SignupGuard;
+
+/// Cookie `POST /auth/logout` reads the refresh token from when the body has none
+pub const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
 
 #[derive(Clone)]
 struct AuthState {
     service: Arc<AuthService>,
+    public_config: Arc<JwtPublicConfig>,
+    signup: SignupGuard,
+    names: NamePolicy,
 }
 
-pub fn routes(db_pool: PgPool, jwt_config: JwtConfig) -> Router {
-    let service = Arc::new(AuthService::new(db_pool, jwt_config));
-    let state = AuthState { service };
+/// Auth routes; request bodies may be JSON or form-encoded.
+/// Signing up counts as accepting `current_terms_version`.
+pub fn routes(
+    db_pool: PgPool,
+    jwt_config: JwtConfig,
+    current_terms_version: Option<String>,
+    lockout: LoginLockout,
+    signup: SignupGuard,
+    names: NamePolicy,
+    events: EventBus,
+) -> Router {
+    let public_config = Arc::new(JwtPublicConfig::from(&jwt_config));
+    let authenticated = AuthMiddleware::new(jwt_config.clone()).with_status_check(db_pool.clone());
+    let service = Arc::new(
+        AuthService::new(db_pool, jwt_config)
+            .with_terms_version(current_terms_version)
+            .with_lockout(lockout)
+            .with_events(events),
+    );
+    let state = AuthState {
+        service,
+        public_config,
+        signup,
+        names,
+    };
 
     Router::new()
         .route("/auth/register", post(register))
         .route("/auth/login", post(login))
         .route("/auth/refresh", post(refresh_token))
+        .route("/auth/forgot-password", post(forgot_password))
+        .route("/auth/reset-password", post(reset_password))
+        .route("/auth/verify-email", get(verify_email))
+        .route("/auth/resend-verification", post(resend_verification))
+        .route("/auth/config", get(token_config))
+        .route("/auth/logout", post(logout))
+        .route(
+            "/auth/logout-all",
+            post(logout_all).layer(middleware::from_fn_with_state(
+                authenticated,
+                auth_middleware,
+            )),
+        )
         .with_state(state)
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/register",
+    tag = "auth",
+    request_body(content(
+        (RegisterRequest = "application/json"),
+        (RegisterRequest = "application/x-www-form-urlencoded")
+    )),
+    responses(
+        (status = 201, description = "User registered", body = ApiResponse<AuthResponse>),
+        (status = 400, description = "Invalid fields", body = ErrorResponse),
+        (status = 409, description = "Email already registered", body = ErrorResponse),
+        (status = 429, description = "Too many signups from this address", body = ErrorResponse)
+    )
+)]
 async fn register(
     State(state): State<AuthState>,
-    Json(request): Json<RegisterRequest>,
+    headers: HeaderMap,
+    ClientIp(client_ip): ClientIp,
+    JsonOrForm(request): JsonOrForm<RegisterRequest>,
 ) -> AppResult<impl axum::response::IntoResponse> {
-    // Validate request
-    validate_struct(&request)?;
+    // Validate request, reporting every invalid field at once
+    let mut invalid = field_errors(&request);
+    invalid.collect(state.names.check(&request.name))?;
+    invalid.into_result()?;
+
+    // Spam controls; the per-IP cap counts the client the rate limiter sees
+    state.signup.check_email(&request.email)?;
+    if let Some(ip) = client_ip {
+        state.signup.check_ip(ip)?;
+    }
 
     // Register user
-    let response = state.service.register(request).await?;
+    let client = ClientInfo::new(&headers, client_ip);
+    let response = state.service.register_from(request, &client).await?;
+    if let Some(ip) = client_ip {
+        state.signup.record(ip);
+    }
 
     Ok(created(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    request_body(content(
+        (LoginRequest = "application/json"),
+        (LoginRequest = "application/x-www-form-urlencoded")
+    )),
+    responses(
+        (status = 200, description = "Signed in", body = ApiResponse<AuthResponse>),
+        (status = 401, description = "Wrong email or password", body = ErrorResponse),
+        (status = 429, description = "Account temporarily locked", body = ErrorResponse)
+    )
+)]
 async fn login(
     State(state): State<AuthState>,
-    Json(request): Json<LoginRequest>,
+    headers: HeaderMap,
+    ClientIp(client_ip): ClientIp,
+    JsonOrForm(request): JsonOrForm<LoginRequest>,
 ) -> AppResult<impl axum::response::IntoResponse> {
     // Validate request
     validate_struct(&request)?;
 
-    // Login user
-    let response = state.service.login(request).await?;
+    // Login user; like signup limits, the session label uses the client IP
+    let client = ClientInfo::new(&headers, client_ip);
+    let response = state.service.login_from(request, &client).await?;
 
     Ok(ApiResponse::success(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    tag = "auth",
+    request_body(content(
+        (RefreshTokenRequest = "application/json"),
+        (RefreshTokenRequest = "application/x-www-form-urlencoded")
+    )),
+    responses(
+        (status = 200, description = "New token pair", body = ApiResponse<AuthResponse>),
+        (status = 401, description = "Refresh token invalid, expired or revoked", body = ErrorResponse)
+    )
+)]
 async fn refresh_token(
     State(state): State<AuthState>,
-    Json(request): Json<RefreshTokenRequest>,
+    JsonOrForm(request): JsonOrForm<RefreshTokenRequest>,
 ) -> AppResult<impl axum::response::IntoResponse> {
     // Refresh token
     let response = state.service.refresh_token(request).await?;
 
     Ok(ApiResponse::success(response))
 }
+
+#[utoipa::path(
+    get,
+    path = "/auth/verify-email",
+    tag = "auth",
+    params(VerifyEmailQuery),
+    responses(
+        (status = 200, description = "Email address verified", body = ApiResponse<TupleUnit>),
+        (status = 400, description = "Token invalid or expired", body = ErrorResponse)
+    )
+)]
+async fn verify_email(
+    State(state): State<AuthState>,
+    Query(query): Query<VerifyEmailQuery>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    state.service.verify_email(&query.token).await?;
+
+    Ok(ApiResponse::with_message(
+        (),
+        "Email address verified".to_string(),
+    ))
+}
+
+/// 200 whether or not the email is registered; 429 when asked again too soon
+#[utoipa::path(
+    post,
+    path = "/auth/resend-verification",
+    tag = "auth",
+    request_body(content(
+        (ResendVerificationRequest = "application/json"),
+        (ResendVerificationRequest = "application/x-www-form-urlencoded")
+    )),
+    responses(
+        (status = 200, description = "Link sent if the address is registered and unverified", body = ApiResponse<TupleUnit>),
+        (status = 429, description = "Asked again too soon", body = ErrorResponse)
+    )
+)]
+async fn resend_verification(
+    State(state): State<AuthState>,
+    JsonOrForm(request): JsonOrForm<ResendVerificationRequest>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    validate_struct(&request)?;
+
+    state.service.resend_verification(&request.email).await?;
+
+    Ok(ApiResponse::with_message(
+        (),
+        "If that email is registered and unverified, a verification link has been sent".to_string(),
+    ))
+}
+
+/// Revoke the refresh token from the body, or else the `refresh_token` cookie
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "auth",
+    request_body(content(
+        (RefreshTokenRequest = "application/json"),
+        (RefreshTokenRequest = "application/x-www-form-urlencoded")
+    )),
+    responses(
+        (status = 204, description = "Refresh token revoked"),
+        (status = 400, description = "No refresh token in the body or cookie", body = ErrorResponse)
+    )
+)]
+async fn logout(
+    State(state): State<AuthState>,
+    headers: HeaderMap,
+    body: Result<JsonOrForm<RefreshTokenRequest>, Response>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let refresh_token = match body {
+        Ok(JsonOrForm(request)) => request.refresh_token,
+        Err(_) => cookie(&headers, REFRESH_TOKEN_COOKIE)
+            .ok_or_else(|| {
+                AppError::BadRequest(format!(
+                    "A refresh token is required in the body or the `{}` cookie",
+                    REFRESH_TOKEN_COOKIE
+                ))
+            })?
+            .to_string(),
+    };
+
+    state.service.logout(&refresh_token).await?;
+
+    Ok(no_content())
+}
+
+/// Revoke every refresh token of the authenticated user
+#[utoipa::path(
+    post,
+    path = "/auth/logout-all",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Every refresh token revoked"),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
+async fn logout_all(
+    State(state): State<AuthState>,
+    Extension(claims): Extension<Claims>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    state.service.logout_all(user_id).await?;
+
+    Ok(no_content())
+}
+
+/// Value of the request cookie called `name`, if sent
+pub(super) fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Always 200 so the response does not reveal whether the email is registered
+#[utoipa::path(
+    post,
+    path = "/auth/forgot-password",
+    tag = "auth",
+    request_body(content(
+        (ForgotPasswordRequest = "application/json"),
+        (ForgotPasswordRequest = "application/x-www-form-urlencoded")
+    )),
+    responses(
+        (status = 200, description = "Reset link sent if the address is registered", body = ApiResponse<TupleUnit>),
+        (status = 400, description = "Invalid email", body = ErrorResponse)
+    )
+)]
+async fn forgot_password(
+    State(state): State<AuthState>,
+    JsonOrForm(request): JsonOrForm<ForgotPasswordRequest>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    validate_struct(&request)?;
+
+    state.service.forgot_password(&request.email).await?;
+
+    Ok(ApiResponse::with_message(
+        (),
+        "If that email is registered, a password reset link has been sent".to_string(),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/reset-password",
+    tag = "auth",
+    request_body(content(
+        (ResetPasswordRequest = "application/json"),
+        (ResetPasswordRequest = "application/x-www-form-urlencoded")
+    )),
+    responses(
+        (status = 200, description = "Password reset", body = ApiResponse<TupleUnit>),
+        (status = 400, description = "Token invalid or password too weak", body = ErrorResponse)
+    )
+)]
+async fn reset_password(
+    State(state): State<AuthState>,
+    JsonOrForm(request): JsonOrForm<ResetPasswordRequest>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    validate_struct(&request)?;
+
+    state.service.reset_password(request).await?;
+
+    Ok(ApiResponse::with_message(
+        (),
+        "Password has been reset".to_string(),
+    ))
+}
+
+/// Token lifetimes and issuer; never includes the signing secret
+#[utoipa::path(
+    get,
+    path = "/auth/config",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Token lifetimes and issuer", body = ApiResponse<JwtPublicConfig>)
+    )
+)]
+async fn token_config(State(state): State<AuthState>) -> ApiResponse<JwtPublicConfig> {
+    ApiResponse::success(state.public_config.as_ref().clone())
+}
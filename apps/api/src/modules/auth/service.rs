@@ -1,61 +1,479 @@
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 use uuid::Uuid;
 
 use crate::config::JwtConfig;
-use crate::modules::users::model::User;
+use crate::events::{DomainEvent, EventBus};
+use crate::metrics;
+use crate::modules::users::model::{User, UserRole, UserStatus};
+use crate::modules::webhooks::{WebhookEvent, WebhookService};
+use crate::security::{self, SecurityEvent};
 use crate::utils::error::{AppError, AppResult};
+use crate::utils::validation::normalize_email;
 
 use super::hash::{hash_password, verify_password};
-use super::jwt::{generate_token_pair, validate_refresh_token};
-use super::model::{AuthResponse, LoginRequest, RefreshTokenRequest, RegisterRequest, UserInfo};
+use super::jwt::{
+    generate_session_token_pair, generate_token_pair, validate_refresh_token, validate_token,
+    TokenPair,
+};
+use super::lockout::LoginLockout;
+use super::model::{
+    AuthResponse, ClientInfo, LoginRequest, OAuthProfile, RefreshTokenRequest, RegisterRequest,
+    ResetPasswordRequest, UserInfo,
+};
+
+/// How long a password reset token stays valid
+pub const PASSWORD_RESET_TTL: Duration = Duration::from_secs(3600);
+
+/// How long an email verification token stays valid
+pub const EMAIL_VERIFICATION_TTL: Duration = Duration::from_secs(86_400);
+
+/// Minimum time between verification resends for one email address
+pub const VERIFICATION_RESEND_INTERVAL: Duration = Duration::from_secs(60);
 
 pub struct AuthService {
     db_pool: PgPool,
     jwt_config: JwtConfig,
+    webhooks: WebhookService,
+    terms_version: Option<String>,
+    lockout: LoginLockout,
+    events: EventBus,
+    password_reset_ttl: Duration,
+    email_verification_ttl: Duration,
+    resend_interval: Duration,
+    last_resends: Arc<Mutex<HashMap<String, Instant>>>,
 }
 
 impl AuthService {
     pub fn new(db_pool: PgPool, jwt_config: JwtConfig) -> Self {
-        Self { db_pool, jwt_config }
+        let webhooks = WebhookService::new(db_pool.clone());
+        Self {
+            db_pool,
+            jwt_config,
+            webhooks,
+            terms_version: None,
+            lockout: LoginLockout::default(),
+            events: EventBus::default(),
+            password_reset_ttl: PASSWORD_RESET_TTL,
+            email_verification_ttl: EMAIL_VERIFICATION_TTL,
+            resend_interval: VERIFICATION_RESEND_INTERVAL,
+            last_resends: Arc::default(),
+        }
+    }
+
+    /// Record `terms_version` as accepted for users who sign up
+    pub fn with_terms_version(mut self, terms_version: Option<String>) -> Self {
+        self.terms_version = terms_version;
+        self
+    }
+
+    /// Replace the failed-login lockout policy, which is off by default
+    pub fn with_lockout(mut self, lockout: LoginLockout) -> Self {
+        self.lockout = lockout;
+        self
+    }
+
+    /// Publish domain events on `events` instead of a private, unsubscribed bus
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    pub fn with_password_reset_ttl(mut self, ttl: Duration) -> Self {
+        self.password_reset_ttl = ttl;
+        self
+    }
+
+    pub fn with_email_verification_ttl(mut self, ttl: Duration) -> Self {
+        self.email_verification_ttl = ttl;
+        self
+    }
+
+    /// Replace the minimum time between verification resends per email
+    pub fn with_resend_interval(mut self, interval: Duration) -> Self {
+        self.resend_interval = interval;
+        self
+    }
+
+    /// Issue a password reset token for `email`
+    ///
+    /// Succeeds whether or not the account exists so callers cannot probe for
+    /// registered emails. The token is published in a `PasswordResetRequested`
+    /// event for delivery; only its hash is stored, and issuing a new one
+    /// revokes any earlier unused token.
+    pub async fn forgot_password(&self, email: &str) -> AppResult<()> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT * FROM users WHERE email = $1 AND deleted_at IS NULL",
+        )
+        .bind(email)
+        .fetch_optional(&self.db_pool)
+        .await?;
+        let Some(user) = user else {
+            return Ok(());
+        };
+
+        let token = generate_token();
+        let expires_at = expires_after(self.password_reset_ttl)?;
+
+        let mut tx = self.db_pool.begin().await?;
+        sqlx::query("DELETE FROM password_reset_tokens WHERE user_id = $1 AND used_at IS NULL")
+            .bind(user.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user.id)
+        .bind(hash_token(&token))
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.events.publish(DomainEvent::PasswordResetRequested {
+            user_id: user.id,
+            email: user.email,
+            token,
+        });
+
+        Ok(())
     }
 
-    /// Register a new user
+    /// Set a new password with a reset token, consuming the token and ending
+    /// every session
+    ///
+    /// Unknown, expired and already used tokens all fail with
+    /// `INVALID_RESET_TOKEN`.
+    pub async fn reset_password(&self, request: ResetPasswordRequest) -> AppResult<()> {
+        let password_hash = hash_password(&request.new_password)?;
+
+        let mut tx = self.db_pool.begin().await?;
+        let user_id: Uuid = sqlx::query_scalar(
+            r#"
+            UPDATE password_reset_tokens
+            SET used_at = NOW()
+            WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING user_id
+            "#,
+        )
+        .bind(hash_token(&request.token))
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::InvalidResetToken)?;
+
+        sqlx::query("UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2")
+            .bind(&password_hash)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        revoke_user_refresh_tokens(&mut *tx, user_id).await?;
+        tx.commit().await?;
+
+        self.events.publish(DomainEvent::PasswordReset { user_id });
+
+        Ok(())
+    }
+
+    /// Mark the token's account as verified, consuming the token
+    ///
+    /// Unknown, expired and already used tokens all fail with
+    /// `INVALID_VERIFICATION_TOKEN`.
+    pub async fn verify_email(&self, token: &str) -> AppResult<()> {
+        let mut tx = self.db_pool.begin().await?;
+        let user_id: Uuid = sqlx::query_scalar(
+            r#"
+            UPDATE email_verification_tokens
+            SET used_at = NOW()
+            WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING user_id
+            "#,
+        )
+        .bind(hash_token(token))
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::InvalidVerificationToken)?;
+
+        sqlx::query("UPDATE users SET email_verified = TRUE, updated_at = NOW() WHERE id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        self.events.publish(DomainEvent::EmailVerified { user_id });
+
+        Ok(())
+    }
+
+    /// Issue a fresh verification token for `email`
+    ///
+    /// Throttled per address with `RATE_LIMIT_EXCEEDED`; otherwise succeeds
+    /// whether or not the account exists or is already verified, so callers
+    /// cannot probe for registered emails.
+    pub async fn resend_verification(&self, email: &str) -> AppResult<()> {
+        {
+            let now = Instant::now();
+            let mut last_resends = self.last_resends.lock().unwrap();
+            last_resends.retain(|_, at| now.duration_since(*at) < self.resend_interval);
+            if last_resends.contains_key(email) {
+                return Err(AppError::RateLimitExceeded);
+            }
+            last_resends.insert(email.to_string(), now);
+        }
+
+        let user = sqlx::query_as::<_, User>(
+            "SELECT * FROM users WHERE email = $1 AND deleted_at IS NULL",
+        )
+        .bind(email)
+        .fetch_optional(&self.db_pool)
+        .await?;
+        match user {
+            Some(user) if !user.email_verified => {
+                self.request_verification(user.id, user.email).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Store a verification token for the user, replacing any unused one,
+    /// and publish it for delivery to `email`
+    async fn request_verification(&self, user_id: Uuid, email: String) -> AppResult<()> {
+        let token = generate_token();
+        let expires_at = expires_after(self.email_verification_ttl)?;
+
+        let mut tx = self.db_pool.begin().await?;
+        sqlx::query("DELETE FROM email_verification_tokens WHERE user_id = $1 AND used_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO email_verification_tokens (id, user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(hash_token(&token))
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.events
+            .publish(DomainEvent::EmailVerificationRequested {
+                user_id,
+                email,
+                token,
+            });
+
+        Ok(())
+    }
+
+    /// Register a new user; the account starts unverified and a verification
+    /// token is published for delivery
     pub async fn register(&self, request: RegisterRequest) -> AppResult<AuthResponse> {
+        self.register_from(request, &ClientInfo::default()).await
+    }
+
+    /// [`Self::register`], labelling the new session with `client`
+    pub async fn register_from(
+        &self,
+        request: RegisterRequest,
+        client: &ClientInfo,
+    ) -> AppResult<AuthResponse> {
+        let result = self.register_user(request, client).await;
+        metrics::record_auth_signup(outcome(&result));
+
+        if let Ok(response) = &result {
+            if let Ok(user_id) = Uuid::parse_str(&response.user.id) {
+                self.events.publish(DomainEvent::UserCreated {
+                    user_id,
+                    email: response.user.email.clone(),
+                });
+                // The account already exists, so a failure here is only
+                // logged; the user can ask for another token
+                if let Err(e) = self
+                    .request_verification(user_id, response.user.email.clone())
+                    .await
+                {
+                    tracing::warn!("Could not issue email verification for {}: {}", user_id, e);
+                }
+            }
+            self.webhooks
+                .notify(
+                    WebhookEvent::UserCreated,
+                    serde_json::json!({
+                        "id": response.user.id,
+                        "email": response.user.email,
+                        "name": response.user.name,
+                    }),
+                )
+                .await;
+        }
+
+        result
+    }
+
+    /// Login an existing user
+    pub async fn login(&self, request: LoginRequest) -> AppResult<AuthResponse> {
+        self.login_from(request, &ClientInfo::default()).await
+    }
+
+    /// [`Self::login`], labelling the new session with `client`
+    pub async fn login_from(
+        &self,
+        request: LoginRequest,
+        client: &ClientInfo,
+    ) -> AppResult<AuthResponse> {
+        let email = request.email.clone();
+        if self.lockout.is_locked(&email, client.ip) {
+            metrics::record_auth_login("locked");
+            return Err(AppError::AccountLocked);
+        }
+
+        let result = self.login_user(request, client).await;
+        metrics::record_auth_login(outcome(&result));
+
+        match &result {
+            Ok(_) => self.lockout.record_success(&email, client.ip),
+            Err(AppError::Authentication(_)) => self.lockout.record_failure(&email, client.ip),
+            Err(_) => {}
+        }
+
+        if result.is_err() {
+            self.events.publish(DomainEvent::LoginFailed {
+                email: email.clone(),
+            });
+            self.webhooks
+                .notify(
+                    WebhookEvent::LoginFailed,
+                    serde_json::json!({ "email": email }),
+                )
+                .await;
+        }
+
+        result
+    }
+
+    /// Sign in with an OAuth identity, issuing the usual token pair
+    ///
+    /// A known identity signs into the account it is linked to, matched on
+    /// the provider's subject id alone. An unknown one is linked to the
+    /// account with the same email, or gets a new password-less account,
+    /// but only if the provider has verified that email; otherwise anyone
+    /// could claim an account by registering its address with the provider.
+    pub async fn oauth_login(
+        &self,
+        profile: OAuthProfile,
+        client: &ClientInfo,
+    ) -> AppResult<AuthResponse> {
+        let result = self.oauth_login_user(profile, client).await;
+        metrics::record_auth_login(outcome(&result));
+
+        let (response, created) = result?;
+        if created {
+            if let Ok(user_id) = Uuid::parse_str(&response.user.id) {
+                self.events.publish(DomainEvent::UserCreated {
+                    user_id,
+                    email: response.user.email.clone(),
+                });
+            }
+            self.webhooks
+                .notify(
+                    WebhookEvent::UserCreated,
+                    serde_json::json!({
+                        "id": response.user.id,
+                        "email": response.user.email,
+                        "name": response.user.name,
+                    }),
+                )
+                .await;
+        }
+
+        Ok(response)
+    }
+
+    /// Exchange a refresh token for a new token pair, revoking the presented one
+    ///
+    /// Presenting an already revoked token revokes its whole family (every
+    /// token rotated from the same login) and fails with 401.
+    pub async fn refresh_token(&self, request: RefreshTokenRequest) -> AppResult<AuthResponse> {
+        let result = self.refresh_user_token(request).await;
+        metrics::record_auth_token_refresh(outcome(&result));
+        result
+    }
+
+    /// Revoke one refresh token; unknown or already revoked tokens are ignored
+    pub async fn logout(&self, refresh_token: &str) -> AppResult<()> {
+        sqlx::query("UPDATE refresh_tokens SET revoked = TRUE WHERE token_hash = $1")
+            .bind(hash_token(refresh_token))
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Revoke every refresh token of `user_id`, ending all of their sessions
+    pub async fn logout_all(&self, user_id: Uuid) -> AppResult<()> {
+        revoke_user_refresh_tokens(&self.db_pool, user_id).await?;
+        Ok(())
+    }
+
+    async fn register_user(
+        &self,
+        request: RegisterRequest,
+        client: &ClientInfo,
+    ) -> AppResult<AuthResponse> {
+        // Reject unknown roles before touching the database
+        let role = request
+            .role
+            .as_deref()
+            .map(UserRole::parse)
+            .transpose()?
+            .unwrap_or_default();
+
         // Check if user already exists
         let existing_user = sqlx::query_as::<_, User>(
-            "SELECT * FROM users WHERE email = $1"
+            "SELECT * FROM users WHERE email = $1 AND deleted_at IS NULL",
         )
         .bind(&request.email)
         .fetch_optional(&self.db_pool)
         .await?;
 
         if existing_user.is_some() {
-            return Err(AppError::Conflict("User with this email already exists".to_string()));
+            return Err(AppError::already_exists("users_email_key"));
         }
 
         // Hash password
         let password_hash = hash_password(&request.password)?;
 
-        // Create user with role (defaults to 'user' if not provided)
-        let role = request.role.unwrap_or_default();
-
         let user = sqlx::query_as::<_, User>(
             r#"
-            INSERT INTO users (id, email, password_hash, name, role, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, NOW(), NOW())
+            INSERT INTO users (id, email, password_hash, name, role, terms_version_accepted, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, NOW(), NOW())
             RETURNING *
-            "#
+            "#,
         )
         .bind(Uuid::new_v4())
         .bind(&request.email)
         .bind(&password_hash)
         .bind(&request.name)
         .bind(role)
+        .bind(&self.terms_version)
         .fetch_one(&self.db_pool)
         .await?;
 
         // Generate tokens with role
-        let token_pair = generate_token_pair(&user.id, &user.email, user.role, &self.jwt_config)?;
+        let token_pair = self.start_session(&user, client).await?;
 
         Ok(AuthResponse {
             access_token: token_pair.access_token,
@@ -71,31 +489,40 @@ impl AuthService {
         })
     }
 
-    /// Login an existing user
-    pub async fn login(&self, request: LoginRequest) -> AppResult<AuthResponse> {
+    async fn login_user(
+        &self,
+        request: LoginRequest,
+        client: &ClientInfo,
+    ) -> AppResult<AuthResponse> {
         // Find user by email
         let user = sqlx::query_as::<_, User>(
-            "SELECT * FROM users WHERE email = $1"
+            "SELECT * FROM users WHERE email = $1 AND deleted_at IS NULL",
         )
         .bind(&request.email)
         .fetch_optional(&self.db_pool)
         .await?
         .ok_or_else(|| AppError::Authentication("Invalid email or password".to_string()))?;
 
-        // Verify password
-        let is_valid = verify_password(&request.password, &user.password_hash)?;
+        // Verify password; OAuth-only accounts have none to check against
+        let is_valid = match user.password_hash.as_deref() {
+            Some(password_hash) => verify_password(&request.password, password_hash)?,
+            None => false,
+        };
         if !is_valid {
-            return Err(AppError::Authentication("Invalid email or password".to_string()));
+            return Err(AppError::Authentication(
+                "Invalid email or password".to_string(),
+            ));
         }
 
-        // Update last login
-        sqlx::query("UPDATE users SET last_login = NOW() WHERE id = $1")
-            .bind(&user.id)
-            .execute(&self.db_pool)
-            .await?;
+        // Checked after the password so the status isn't revealed to guesses
+        if user.status == UserStatus::Suspended {
+            return Err(AppError::AccountSuspended);
+        }
+
+        self.record_login(user.id).await;
 
         // Generate tokens with role
-        let token_pair = generate_token_pair(&user.id, &user.email, user.role, &self.jwt_config)?;
+        let token_pair = self.start_session(&user, client).await?;
 
         Ok(AuthResponse {
             access_token: token_pair.access_token,
@@ -111,25 +538,88 @@ impl AuthService {
         })
     }
 
-    /// Refresh access token using refresh token
-    pub async fn refresh_token(&self, request: RefreshTokenRequest) -> AppResult<AuthResponse> {
+    async fn refresh_user_token(&self, request: RefreshTokenRequest) -> AppResult<AuthResponse> {
         // Validate refresh token
         let claims = validate_refresh_token(&request.refresh_token, &self.jwt_config)?;
 
-        // Get user from database
-        let user_id = Uuid::parse_str(&claims.sub)
-            .map_err(|_| AppError::Authentication("Invalid user ID in token".to_string()))?;
+        let mut tx = self.db_pool.begin().await?;
+        let (user_id, family_id, revoked, user_agent, network) =
+            sqlx::query_as::<_, (Uuid, Uuid, bool, Option<String>, Option<String>)>(
+                r#"
+                SELECT user_id, family_id, revoked, user_agent, client_network
+                FROM refresh_tokens
+                WHERE token_hash = $1
+                FOR UPDATE
+                "#,
+            )
+            .bind(hash_token(&request.refresh_token))
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| AppError::Authentication("Unknown refresh token".to_string()))?;
 
-        let user = sqlx::query_as::<_, User>(
-            "SELECT * FROM users WHERE id = $1"
+        // A rotated token coming back means it leaked; end every session
+        // descended from the same login
+        if revoked {
+            let revoked_tokens = sqlx::query(
+                "UPDATE refresh_tokens SET revoked = TRUE WHERE family_id = $1 AND user_id = $2 AND revoked = FALSE",
+            )
+            .bind(family_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            tx.commit().await?;
+
+            security::emit(SecurityEvent::RefreshTokenReuse {
+                user_id,
+                family_id,
+                revoked_tokens,
+            });
+            return Err(AppError::Authentication(
+                "Refresh token has already been used".to_string(),
+            ));
+        }
+
+        sqlx::query("UPDATE refresh_tokens SET revoked = TRUE WHERE token_hash = $1")
+            .bind(hash_token(&request.refresh_token))
+            .execute(&mut *tx)
+            .await?;
+
+        let user =
+            sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1 AND deleted_at IS NULL")
+                .bind(user_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| AppError::Authentication("User not found".to_string()))?;
+        if user.status == UserStatus::Suspended {
+            return Err(AppError::AccountSuspended);
+        }
+
+        // Generate new token pair with role, keeping the original session start
+        let token_pair = generate_session_token_pair(
+            &user.id,
+            &user.email,
+            user.role,
+            claims.session_started_at(),
+            &self.jwt_config,
+        )?;
+        // The session keeps the client it was started from
+        let client = ClientInfo {
+            user_agent,
+            network,
+            ip: None,
+        };
+        self.store_refresh_token(
+            &mut *tx,
+            user.id,
+            family_id,
+            &token_pair.refresh_token,
+            &client,
         )
-        .bind(&user_id)
-        .fetch_optional(&self.db_pool)
-        .await?
-        .ok_or_else(|| AppError::Authentication("User not found".to_string()))?;
+        .await?;
+        tx.commit().await?;
 
-        // Generate new token pair with role
-        let token_pair = generate_token_pair(&user.id, &user.email, user.role, &self.jwt_config)?;
+        self.record_login(user.id).await;
 
         Ok(AuthResponse {
             access_token: token_pair.access_token,
@@ -144,4 +634,220 @@ impl AuthService {
             },
         })
     }
+
+    /// The account for `profile` and whether it was created for this login
+    async fn oauth_login_user(
+        &self,
+        profile: OAuthProfile,
+        client: &ClientInfo,
+    ) -> AppResult<(AuthResponse, bool)> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let linked = sqlx::query_as::<_, User>(
+            r#"
+            SELECT u.* FROM users u
+            JOIN oauth_connections c ON c.user_id = u.id
+            WHERE c.provider = $1 AND c.provider_user_id = $2 AND u.deleted_at IS NULL
+            "#,
+        )
+        .bind(profile.provider)
+        .bind(&profile.subject)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let (user, created) = match linked {
+            Some(user) => (user, false),
+            None => {
+                if !profile.email_verified {
+                    return Err(AppError::Authentication(format!(
+                        "The {} account's email address is not verified",
+                        profile.provider
+                    )));
+                }
+                let email = normalize_email(&profile.email);
+
+                let existing = sqlx::query_as::<_, User>(
+                    "SELECT * FROM users WHERE email = $1 AND deleted_at IS NULL FOR UPDATE",
+                )
+                .bind(&email)
+                .fetch_optional(&mut *tx)
+                .await?;
+                let (user, created) = match existing {
+                    // The provider vouches for the address, so it counts as verified
+                    Some(user) => {
+                        let user = sqlx::query_as::<_, User>(
+                            "UPDATE users SET email_verified = TRUE, updated_at = NOW() WHERE id = $1 RETURNING *",
+                        )
+                        .bind(user.id)
+                        .fetch_one(&mut *tx)
+                        .await?;
+                        (user, false)
+                    }
+                    None => {
+                        let name = profile
+                            .name
+                            .as_deref()
+                            .map(str::trim)
+                            .filter(|name| !name.is_empty())
+                            .unwrap_or_else(|| email.split('@').next().unwrap_or_default())
+                            .to_string();
+                        let user = sqlx::query_as::<_, User>(
+                            r#"
+                            INSERT INTO users (id, email, password_hash, name, role, email_verified, terms_version_accepted, created_at, updated_at)
+                            VALUES ($1, $2, NULL, $3, $4, TRUE, $5, NOW(), NOW())
+                            RETURNING *
+                            "#,
+                        )
+                        .bind(Uuid::new_v4())
+                        .bind(&email)
+                        .bind(&name)
+                        .bind(UserRole::default())
+                        .bind(&self.terms_version)
+                        .fetch_one(&mut *tx)
+                        .await?;
+                        (user, true)
+                    }
+                };
+
+                sqlx::query(
+                    r#"
+                    INSERT INTO oauth_connections (id, user_id, provider, provider_user_id, email)
+                    VALUES ($1, $2, $3, $4, $5)
+                    "#,
+                )
+                .bind(Uuid::new_v4())
+                .bind(user.id)
+                .bind(profile.provider)
+                .bind(&profile.subject)
+                .bind(&email)
+                .execute(&mut *tx)
+                .await?;
+
+                (user, created)
+            }
+        };
+
+        if user.status == UserStatus::Suspended {
+            return Err(AppError::AccountSuspended);
+        }
+        tx.commit().await?;
+
+        self.record_login(user.id).await;
+        let token_pair = self.start_session(&user, client).await?;
+
+        let response = AuthResponse {
+            access_token: token_pair.access_token,
+            refresh_token: token_pair.refresh_token,
+            token_type: token_pair.token_type,
+            expires_in: token_pair.expires_in,
+            user: UserInfo {
+                id: user.id.to_string(),
+                email: user.email,
+                name: user.name,
+                role: user.role,
+            },
+        };
+        Ok((response, created))
+    }
+
+    /// Stamp `last_login`; a failure is logged rather than failing the login
+    async fn record_login(&self, user_id: Uuid) {
+        if let Err(e) = sqlx::query("UPDATE users SET last_login = NOW() WHERE id = $1")
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await
+        {
+            tracing::warn!("Could not record last login for {}: {}", user_id, e);
+        }
+    }
+
+    /// Issue a token pair for a fresh login, starting a new refresh token family
+    async fn start_session(&self, user: &User, client: &ClientInfo) -> AppResult<TokenPair> {
+        let token_pair = generate_token_pair(&user.id, &user.email, user.role, &self.jwt_config)?;
+        self.store_refresh_token(
+            &self.db_pool,
+            user.id,
+            Uuid::new_v4(),
+            &token_pair.refresh_token,
+            client,
+        )
+        .await?;
+
+        Ok(token_pair)
+    }
+
+    /// Persist the hash of an issued refresh token so it can be rotated once
+    async fn store_refresh_token<'e>(
+        &self,
+        executor: impl sqlx::PgExecutor<'e>,
+        user_id: Uuid,
+        family_id: Uuid,
+        refresh_token: &str,
+        client: &ClientInfo,
+    ) -> AppResult<()> {
+        let claims = validate_token(refresh_token, &self.jwt_config)?;
+        let expires_at = chrono::DateTime::from_timestamp(claims.exp, 0)
+            .ok_or_else(|| AppError::InternalServer("Invalid expiration time".to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at, user_agent, client_network)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(family_id)
+        .bind(hash_token(refresh_token))
+        .bind(expires_at)
+        .bind(&client.user_agent)
+        .bind(&client.network)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Random single-use token for reset and verification links
+fn generate_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+fn expires_after(ttl: Duration) -> AppResult<chrono::DateTime<chrono::Utc>> {
+    Ok(chrono::Utc::now()
+        + chrono::Duration::from_std(ttl).map_err(|e| AppError::Configuration(e.to_string()))?)
+}
+
+/// Revoke every refresh token of `user_id`, returning how many were still live
+///
+/// Access tokens already issued stay valid until they expire.
+pub async fn revoke_user_refresh_tokens<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    user_id: Uuid,
+) -> AppResult<u64> {
+    let revoked = sqlx::query(
+        "UPDATE refresh_tokens SET revoked = TRUE WHERE user_id = $1 AND revoked = FALSE",
+    )
+    .bind(user_id)
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    Ok(revoked)
+}
+
+/// Hex SHA-256 of a reset or refresh token, the form both are stored and
+/// looked up in
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Metric label for the result of an auth operation
+fn outcome<T>(result: &AppResult<T>) -> &'static str {
+    if result.is_ok() {
+        "success"
+    } else {
+        "failure"
+    }
 }
@@ -0,0 +1,93 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::utils::error::{AppError, AppResult};
+
+/// Spam controls applied before an account is created: a per-IP cap on
+/// signups within a sliding window and a disposable-domain blocklist
+#[derive(Clone)]
+pub struct SignupGuard {
+    max_per_ip: u32,
+    window: Duration,
+    blocked_domains: Arc<HashSet<String>>,
+    signups: Arc<Mutex<HashMap<IpAddr, VecDeque<Instant>>>>,
+}
+
+impl SignupGuard {
+    pub fn new(max_per_ip: u32, window: Duration) -> Self {
+        Self {
+            max_per_ip,
+            window,
+            blocked_domains: Arc::default(),
+            signups: Arc::default(),
+        }
+    }
+
+    /// Domains are matched case-insensitively, including their subdomains
+    pub fn with_blocked_domains(mut self, domains: Vec<String>) -> Self {
+        self.blocked_domains = Arc::new(
+            domains
+                .into_iter()
+                .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+                .filter(|domain| !domain.is_empty())
+                .collect(),
+        );
+        self
+    }
+
+    /// Reject emails on a blocked domain with `DISPOSABLE_EMAIL`
+    pub fn check_email(&self, email: &str) -> AppResult<()> {
+        let Some((_, domain)) = email.rsplit_once('@') else {
+            return Ok(());
+        };
+        let domain = domain.to_lowercase();
+
+        // `mail.tempbox.io` is blocked by a `tempbox.io` entry
+        let blocked = std::iter::successors(Some(domain.as_str()), |d| {
+            d.split_once('.').map(|(_, parent)| parent)
+        })
+        .any(|candidate| self.blocked_domains.contains(candidate));
+
+        if blocked {
+            return Err(AppError::DisposableEmail(domain));
+        }
+        Ok(())
+    }
+
+    /// Reject the signup when `ip` already used up its signups for the window
+    pub fn check_ip(&self, ip: IpAddr) -> AppResult<()> {
+        let now = Instant::now();
+        let mut signups = self.signups.lock().unwrap();
+        signups.retain(|_, recent| {
+            recent.retain(|at| now.duration_since(*at) < self.window);
+            !recent.is_empty()
+        });
+
+        let used = signups.get(&ip.to_canonical()).map_or(0, VecDeque::len);
+        if used >= self.max_per_ip as usize {
+            return Err(AppError::RateLimitExceeded);
+        }
+        Ok(())
+    }
+
+    /// Count a completed signup against `ip`
+    pub fn record(&self, ip: IpAddr) {
+        self.signups
+            .lock()
+            .unwrap()
+            .entry(ip.to_canonical())
+            .or_default()
+            .push_back(Instant::now());
+    }
+}
+
+impl Default for SignupGuard {
+    /// Five signups per address per hour, no blocked domains
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(3600))
+    }
+}
@@ -0,0 +1,43 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::database::query_count::tracked;
+use crate::modules::auth::jwt::Claims;
+use crate::utils::error::AppError;
+
+/// Middleware rejecting users who have not verified their email with 403
+/// `EMAIL_NOT_VERIFIED`; must run after `auth_middleware`
+pub async fn require_verified(
+    State(db_pool): State<PgPool>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let claims = request
+        .extensions()
+        .get::<Claims>()
+        .ok_or_else(|| AppError::Authentication("No authentication found".to_string()))?;
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    let verified = tracked(
+        sqlx::query_scalar::<_, bool>(
+            "SELECT email_verified FROM users WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_optional(&db_pool),
+    )
+    .await?
+    .ok_or_else(|| AppError::Authentication("User no longer exists".to_string()))?;
+
+    if !verified {
+        return Err(AppError::EmailNotVerified);
+    }
+
+    Ok(next.run(request).await)
+}
@@ -0,0 +1,78 @@
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextValidation};
+use async_graphql::{ErrorExtensions, SchemaBuilder, ServerError, ValidationResult};
+use std::sync::Arc;
+
+// async-graphql reports an exceeded limit with one of these bare messages
+const TOO_DEEP: &str = "Query is nested too deep.";
+const TOO_COMPLEX: &str = "Query is too complex.";
+
+/// Maximum depth and complexity of a query; anything over either limit is
+/// rejected during validation, before a resolver runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryLimits {
+    pub max_depth: usize,
+    pub max_complexity: usize,
+}
+
+impl QueryLimits {
+    pub const DEFAULT_MAX_DEPTH: usize = 10;
+    pub const DEFAULT_MAX_COMPLEXITY: usize = 200;
+
+    pub fn new(max_depth: usize, max_complexity: usize) -> Self {
+        Self {
+            max_depth,
+            max_complexity,
+        }
+    }
+
+    /// Apply the limits to a schema, with rejections that carry a `code`
+    /// and the limit that was exceeded in their extensions
+    pub(super) fn apply<Q, M, S>(self, builder: SchemaBuilder<Q, M, S>) -> SchemaBuilder<Q, M, S> {
+        builder
+            .limit_depth(self.max_depth)
+            .limit_complexity(self.max_complexity)
+            .extension(self)
+    }
+
+    fn describe(&self, error: ServerError) -> ServerError {
+        let (code, limit, value) = match error.message.as_str() {
+            TOO_DEEP => ("QUERY_TOO_DEEP", "maxDepth", self.max_depth),
+            TOO_COMPLEX => ("QUERY_TOO_COMPLEX", "maxComplexity", self.max_complexity),
+            _ => return error,
+        };
+
+        async_graphql::Error::new(error.message)
+            .extend_with(|_, ext| {
+                ext.set("code", code);
+                ext.set(limit, value);
+            })
+            .into_server_error(Default::default())
+    }
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_DEPTH, Self::DEFAULT_MAX_COMPLEXITY)
+    }
+}
+
+impl ExtensionFactory for QueryLimits {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(LimitErrors(*self))
+    }
+}
+
+struct LimitErrors(QueryLimits);
+
+#[async_trait::async_trait]
+impl Extension for LimitErrors {
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> Result<ValidationResult, Vec<ServerError>> {
+        next.run(ctx)
+            .await
+            .map_err(|errors| errors.into_iter().map(|e| self.0.describe(e)).collect())
+    }
+}
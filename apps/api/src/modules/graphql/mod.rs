@@ -1,31 +1,94 @@
+mod limits;
 mod schema;
 
+pub use limits::QueryLimits;
 pub use schema::{build_schema, GraphQLSchema};
 
-use async_graphql::http::GraphiQLSource;
+use async_graphql::{http::GraphiQLSource, ErrorExtensions};
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::{
     extract::State,
+    http::{header, HeaderMap, StatusCode},
     response::{Html, IntoResponse},
-    routing::get,
+    routing::{get, post},
     Router,
 };
+use std::sync::Arc;
+
+use crate::config::JwtConfig;
+use crate::modules::auth::{jwt::validate_access_token, middleware::extract_token};
+
+#[derive(Clone)]
+struct GraphQLState {
+    schema: GraphQLSchema,
+    jwt_config: Arc<JwtConfig>,
+}
 
 /// GraphQL query handler
+///
+/// Requests without an `Authorization` header run anonymously, so public
+/// fields like `health` keep working; resolvers that need a user reject
+/// them. A token that is present but invalid fails the whole request.
+#[utoipa::path(
+    post,
+    path = "/graphql",
+    tag = "graphql",
+    request_body(content = Object, description = "GraphQL request: `query`, optional `variables` and `operationName`"),
+    security((), ("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "GraphQL response; errors are reported in `errors`", body = Object)
+    )
+)]
 async fn graphql_handler(
-    State(schema): State<GraphQLSchema>,
+    State(state): State<GraphQLState>,
+    headers: HeaderMap,
     req: GraphQLRequest,
 ) -> GraphQLResponse {
-    schema.execute(req.into_inner()).await.into()
+    let mut request = req.into_inner();
+
+    if headers.contains_key(header::AUTHORIZATION) {
+        match extract_token(&headers)
+            .and_then(|token| validate_access_token(&token, &state.jwt_config))
+        {
+            Ok(claims) => request = request.data(claims),
+            Err(e) => {
+                let error = async_graphql::Error::new(e.to_string())
+                    .extend_with(|_, ext| ext.set("code", "UNAUTHENTICATED"))
+                    .into_server_error(async_graphql::Pos::default());
+                return async_graphql::Response::from_errors(vec![error]).into();
+            }
+        }
+    }
+
+    state.schema.execute(request).await.into()
 }
 
 /// GraphQL Playground UI
+#[utoipa::path(
+    get,
+    path = "/graphql",
+    tag = "graphql",
+    responses(
+        (status = 200, description = "GraphiQL playground", body = String, content_type = "text/html"),
+        (status = 404, description = "Playground disabled")
+    )
+)]
 async fn graphiql() -> impl IntoResponse {
     Html(GraphiQLSource::build().endpoint("/graphql").finish())
 }
 
-pub fn routes(schema: GraphQLSchema) -> Router {
+/// GraphQL routes; the GraphiQL playground is only served when `playground_enabled`
+pub fn routes(schema: GraphQLSchema, jwt_config: JwtConfig, playground_enabled: bool) -> Router {
+    let graphql = if playground_enabled {
+        get(graphiql).post(graphql_handler)
+    } else {
+        post(graphql_handler).get(|| async { StatusCode::NOT_FOUND })
+    };
+
     Router::new()
-        .route("/graphql", get(graphiql).post(graphql_handler))
-        .with_state(schema)
+        .route("/graphql", graphql)
+        .with_state(GraphQLState {
+            schema,
+            jwt_config: Arc::new(jwt_config),
+        })
 }
@@ -1,19 +1,24 @@
-use async_graphql::{Context, EmptySubscription, Object, Result, Schema, SimpleObject};
-use sqlx::PgPool;
+use async_graphql::{
+    Context, EmptySubscription, ErrorExtensions, Object, Result, Schema, SimpleObject,
+};
+use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::modules::users::model::{User, UserRole};
+use super::limits::QueryLimits;
 use crate::modules::auth::jwt::Claims;
+use crate::modules::users::model::{
+    UpdateUserRequest, UserListOptions, UserResponse, UserRole, UserSortField,
+};
+use crate::modules::users::routes::MAX_USER_LIST_LIMIT;
+use crate::modules::users::service::UserService;
+use crate::utils::error::AppError;
+use crate::utils::validation::NamePolicy;
 
 // GraphQL Schema Type
 pub type GraphQLSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
 
-// GraphQL Context - holds shared state
-#[derive(Clone)]
-pub struct GraphQLContext {
-    pub db_pool: PgPool,
-    pub auth_claims: Option<Claims>,
-}
+/// Page size of `users` when `limit` is omitted
+const DEFAULT_USERS_LIMIT: i32 = 20;
 
 // User Type for GraphQL
 #[derive(SimpleObject)]
@@ -25,10 +30,10 @@ struct UserQL {
     created_at: String,
 }
 
-impl From<User> for UserQL {
-    fn from(user: User) -> Self {
+impl From<UserResponse> for UserQL {
+    fn from(user: UserResponse) -> Self {
         Self {
-            id: user.id.to_string(),
+            id: user.id,
             email: user.email,
             name: user.name,
             role: user.role.to_string(),
@@ -37,6 +42,55 @@ impl From<User> for UserQL {
     }
 }
 
+/// An `AppError` as a GraphQL error, with a machine-readable `code` extension
+///
+/// Internal failures keep their details out of the response.
+fn graphql_error(e: AppError) -> async_graphql::Error {
+    let code = match &e {
+        AppError::Authentication(_) => "UNAUTHENTICATED",
+        AppError::Authorization(_) | AppError::AccountSuspended => "FORBIDDEN",
+        AppError::NotFound(_) | AppError::UserNotFound => "NOT_FOUND",
+        AppError::Validation(_)
+        | AppError::InvalidField { .. }
+        | AppError::InvalidFields(_)
+        | AppError::BadRequest(_) => "BAD_USER_INPUT",
+        _ => {
+            tracing::error!("GraphQL resolver failed: {}", e);
+            return async_graphql::Error::new("An internal server error occurred")
+                .extend_with(|_, ext| ext.set("code", "INTERNAL_SERVER_ERROR"));
+        }
+    };
+
+    async_graphql::Error::new(e.to_string()).extend_with(|_, ext| ext.set("code", code))
+}
+
+/// Claims of the authenticated caller, attached to the request by the handler
+fn caller<'a>(ctx: &'a Context<'_>) -> Result<&'a Claims> {
+    ctx.data_opt::<Claims>().ok_or_else(|| {
+        graphql_error(AppError::Authentication(
+            "Authentication required".to_string(),
+        ))
+    })
+}
+
+fn caller_id(ctx: &Context<'_>) -> Result<Uuid> {
+    Uuid::parse_str(&caller(ctx)?.sub)
+        .map_err(|_| graphql_error(AppError::Authentication("Invalid user ID".to_string())))
+}
+
+fn require_admin(ctx: &Context<'_>) -> Result<()> {
+    if caller(ctx)?.role != UserRole::Admin {
+        return Err(graphql_error(AppError::Authorization(
+            "Admin access required".to_string(),
+        )));
+    }
+    Ok(())
+}
+
+fn users<'a>(ctx: &Context<'a>) -> Result<&'a Arc<UserService>> {
+    ctx.data::<Arc<UserService>>()
+}
+
 // Query Root
 pub struct QueryRoot;
 
@@ -44,76 +98,57 @@ pub struct QueryRoot;
 impl QueryRoot {
     /// Get current authenticated user
     async fn me(&self, ctx: &Context<'_>) -> Result<UserQL> {
-        let gql_ctx = ctx.data::<GraphQLContext>()?;
-
-        let claims = gql_ctx
-            .auth_claims
-            .as_ref()
-            .ok_or("Unauthorized")?;
-
-        let user_id = Uuid::parse_str(&claims.sub)
-            .map_err(|_| "Invalid user ID")?;
-
-        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
-            .bind(user_id)
-            .fetch_one(&gql_ctx.db_pool)
+        let user_id = caller_id(ctx)?;
+        let user = users(ctx)?
+            .get_by_id(&user_id)
             .await
-            .map_err(|_| "User not found")?;
+            .map_err(graphql_error)?;
 
         Ok(user.into())
     }
 
     /// Get user by ID (admin only)
     async fn user(&self, ctx: &Context<'_>, id: String) -> Result<UserQL> {
-        let gql_ctx = ctx.data::<GraphQLContext>()?;
+        require_admin(ctx)?;
 
-        let claims = gql_ctx
-            .auth_claims
-            .as_ref()
-            .ok_or("Unauthorized")?;
-
-        // Check if user is admin
-        if claims.role != UserRole::Admin {
-            return Err("Forbidden: Admin access required".into());
-        }
-
-        let user_id = Uuid::parse_str(&id)
-            .map_err(|_| "Invalid user ID format")?;
-
-        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
-            .bind(user_id)
-            .fetch_one(&gql_ctx.db_pool)
+        let user_id = Uuid::parse_str(&id).map_err(|_| {
+            graphql_error(AppError::BadRequest("Invalid user ID format".to_string()))
+        })?;
+        let user = users(ctx)?
+            .get_by_id(&user_id)
             .await
-            .map_err(|_| "User not found")?;
+            .map_err(graphql_error)?;
 
         Ok(user.into())
     }
 
-    /// List all users (admin only)
-    async fn users(&self, ctx: &Context<'_>, limit: Option<i32>, offset: Option<i32>) -> Result<Vec<UserQL>> {
-        let gql_ctx = ctx.data::<GraphQLContext>()?;
-
-        let claims = gql_ctx
-            .auth_claims
-            .as_ref()
-            .ok_or("Unauthorized")?;
+    /// List users, newest first (admin only); `limit` is capped at 100
+    async fn users(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<Vec<UserQL>> {
+        require_admin(ctx)?;
 
-        // Check if user is admin
-        if claims.role != UserRole::Admin {
-            return Err("Forbidden: Admin access required".into());
-        }
-
-        let limit = limit.unwrap_or(20).min(100);
         let offset = offset.unwrap_or(0);
+        if offset < 0 {
+            return Err(graphql_error(AppError::BadRequest(
+                "offset must not be negative".to_string(),
+            )));
+        }
 
-        let users = sqlx::query_as::<_, User>(
-            "SELECT * FROM users ORDER BY created_at DESC LIMIT $1 OFFSET $2"
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&gql_ctx.db_pool)
-        .await
-        .map_err(|_| "Failed to fetch users")?;
+        let options = UserListOptions {
+            search: None,
+            role: None,
+            offset: offset as u64,
+            limit: limit
+                .unwrap_or(DEFAULT_USERS_LIMIT)
+                .clamp(1, MAX_USER_LIST_LIMIT as i32) as u32,
+            sort_by: UserSortField::CreatedAt,
+            order: UserSortField::CreatedAt.default_order(),
+        };
+        let (users, _total) = users(ctx)?.list(&options).await.map_err(graphql_error)?;
 
         Ok(users.into_iter().map(UserQL::from).collect())
     }
@@ -131,62 +166,41 @@ pub struct MutationRoot;
 impl MutationRoot {
     /// Update current user's name
     async fn update_profile(&self, ctx: &Context<'_>, name: String) -> Result<UserQL> {
-        let gql_ctx = ctx.data::<GraphQLContext>()?;
-
-        let claims = gql_ctx
-            .auth_claims
-            .as_ref()
-            .ok_or("Unauthorized")?;
-
-        let user_id = Uuid::parse_str(&claims.sub)
-            .map_err(|_| "Invalid user ID")?;
-
-        // Validate name length
-        if name.len() < 2 || name.len() > 100 {
-            return Err("Name must be between 2 and 100 characters".into());
-        }
-
-        let user = sqlx::query_as::<_, User>(
-            "UPDATE users SET name = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
-        )
-        .bind(&name)
-        .bind(user_id)
-        .fetch_one(&gql_ctx.db_pool)
-        .await
-        .map_err(|_| "Failed to update user")?;
+        let user_id = caller_id(ctx)?;
+
+        ctx.data::<NamePolicy>()?
+            .check(&name)
+            .map_err(graphql_error)?;
+
+        let request = UpdateUserRequest {
+            name: Some(name),
+            metadata: None,
+        };
+        let user = users(ctx)?
+            .update(&user_id, request)
+            .await
+            .map_err(graphql_error)?;
 
         Ok(user.into())
     }
 
     /// Delete current user's account
     async fn delete_account(&self, ctx: &Context<'_>) -> Result<bool> {
-        let gql_ctx = ctx.data::<GraphQLContext>()?;
-
-        let claims = gql_ctx
-            .auth_claims
-            .as_ref()
-            .ok_or("Unauthorized")?;
+        let user_id = caller_id(ctx)?;
 
-        let user_id = Uuid::parse_str(&claims.sub)
-            .map_err(|_| "Invalid user ID")?;
-
-        sqlx::query("DELETE FROM users WHERE id = $1")
-            .bind(user_id)
-            .execute(&gql_ctx.db_pool)
-            .await
-            .map_err(|_| "Failed to delete account")?;
+        users(ctx)?.delete(&user_id).await.map_err(graphql_error)?;
 
         Ok(true)
     }
 }
 
-/// Build the GraphQL schema
-pub fn build_schema(db_pool: PgPool) -> GraphQLSchema {
-    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
-        .data(GraphQLContext {
-            db_pool,
-            auth_claims: None,
-        })
+/// Build the GraphQL schema; the caller's claims are attached per request
+/// by the route handler
+pub fn build_schema(users: UserService, names: NamePolicy, limits: QueryLimits) -> GraphQLSchema {
+    limits
+        .apply(Schema::build(QueryRoot, MutationRoot, EmptySubscription))
+        .data(Arc::new(users))
+        .data(names)
         .finish()
 }
 
@@ -198,9 +212,6 @@ mod tests {
     fn test_schema_builds() {
         // This is a compile-time test to ensure schema builds correctly
         // We can't test with actual DB here, but we verify the schema structure
-        assert_eq!(
-            QueryRoot.to_string(),
-            "QueryRoot"
-        );
+        assert_eq!(QueryRoot.to_string(), "QueryRoot");
     }
 }
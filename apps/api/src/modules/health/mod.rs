@@ -1,14 +1,16 @@
-use axum::{
-    extract::State,
-    http::StatusCode,
-    response::IntoResponse,
-    routing::get,
-    Json, Router,
-};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use std::time::SystemTime;
 
+use crate::database::health::{check_health, DatabaseHealth, HealthStatus};
+use crate::utils::error::AppResult;
+use crate::utils::uptime;
+
+pub mod status;
+
+pub use status::{status_routes, HealthRegistry};
+
 #[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct HealthResponse {
     pub status: String,
@@ -34,9 +36,6 @@ async fn health_check(State(pool): State<PgPool>) -> impl IntoResponse {
         Err(_) => "unhealthy",
     };
 
-    // Calculate uptime (simplified - would use actual start time in production)
-    let uptime = 0; // Placeholder
-
     let response = HealthResponse {
         status: if db_status == "healthy" {
             "healthy".to_string()
@@ -44,7 +43,7 @@ async fn health_check(State(pool): State<PgPool>) -> impl IntoResponse {
             "degraded".to_string()
         },
         database: db_status.to_string(),
-        uptime_seconds: uptime,
+        uptime_seconds: uptime::uptime_seconds(),
         timestamp: chrono::Utc::now().to_rfc3339(),
     };
 
@@ -57,12 +56,51 @@ async fn health_check(State(pool): State<PgPool>) -> impl IntoResponse {
     (status_code, Json(response))
 }
 
+/// Pool statistics, probe latency and migration version for diagnosing
+/// pool exhaustion
+#[utoipa::path(
+    get,
+    path = "/api/v1/health/database",
+    tag = "health",
+    responses(
+        (status = 200, description = "Database reachable", body = DatabaseHealth),
+        (status = 503, description = "Database probe failed", body = DatabaseHealth)
+    )
+)]
+async fn database_health(State(pool): State<PgPool>) -> AppResult<impl IntoResponse> {
+    let health = check_health(&pool).await?;
+    let status_code = if health.status == HealthStatus::Unhealthy {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+
+    Ok((status_code, Json(health)))
+}
+
 /// Liveness probe (Kubernetes-style)
+#[utoipa::path(
+    get,
+    path = "/api/v1/health/live",
+    tag = "health",
+    responses(
+        (status = 200, description = "Process is alive", body = String)
+    )
+)]
 async fn liveness() -> impl IntoResponse {
     (StatusCode::OK, "alive")
 }
 
 /// Readiness probe
+#[utoipa::path(
+    get,
+    path = "/api/v1/health/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Database reachable", body = String),
+        (status = 503, description = "Database unreachable", body = String)
+    )
+)]
 async fn readiness(State(pool): State<PgPool>) -> impl IntoResponse {
     match sqlx::query("SELECT 1").fetch_one(&pool).await {
         Ok(_) => (StatusCode::OK, "ready"),
@@ -73,6 +111,7 @@ async fn readiness(State(pool): State<PgPool>) -> impl IntoResponse {
 pub fn routes(db_pool: PgPool) -> Router {
     Router::new()
         .route("/api/v1/health", get(health_check))
+        .route("/api/v1/health/database", get(database_health))
         .route("/api/v1/health/live", get(liveness))
         .route("/api/v1/health/ready", get(readiness))
         .with_state(db_pool)
@@ -0,0 +1,287 @@
+use axum::{
+    extract::State, http::StatusCode, middleware, response::IntoResponse, routing::get, Json,
+    Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::config::JwtConfig;
+use crate::modules::auth::{
+    middleware::{auth_middleware, AuthMiddleware},
+    role_guard::require_admin,
+};
+
+pub const STATUS_PATH: &str = "/api/v1/status";
+
+/// Subsystems every status report lists; those without a registered check
+/// are reported as `disabled`
+pub const SUBSYSTEMS: [&str; 6] = ["database", "pool", "jobs", "storage", "ai", "websocket"];
+
+/// How long one subsystem check may take before it counts as unhealthy
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthState {
+    Healthy,
+    Degraded,
+    Unhealthy,
+    Disabled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SubsystemHealth {
+    pub status: HealthState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl SubsystemHealth {
+    pub fn new(status: HealthState) -> Self {
+        Self {
+            status,
+            latency_ms: None,
+            error: None,
+            details: None,
+        }
+    }
+
+    pub fn healthy() -> Self {
+        Self::new(HealthState::Healthy)
+    }
+
+    pub fn unhealthy(error: impl Into<String>) -> Self {
+        Self {
+            error: Some(error.into()),
+            ..Self::new(HealthState::Unhealthy)
+        }
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency_ms = Some(latency.as_millis() as u64);
+        self
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct StatusReport {
+    /// `unhealthy` when the database is down, `degraded` when any other
+    /// subsystem is not healthy
+    pub status: HealthState,
+    pub timestamp: String,
+    pub subsystems: BTreeMap<String, SubsystemHealth>,
+}
+
+type HealthCheck =
+    Arc<dyn Fn() -> Pin<Box<dyn Future<Output = SubsystemHealth> + Send>> + Send + Sync>;
+
+/// Named subsystem checks aggregated by `GET /api/v1/status`
+#[derive(Clone, Default)]
+pub struct HealthRegistry {
+    checks: Vec<(String, HealthCheck)>,
+}
+
+impl HealthRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or replace) the check reported under `name`
+    pub fn with_check<F, Fut>(mut self, name: &str, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = SubsystemHealth> + Send + 'static,
+    {
+        self.checks.retain(|(existing, _)| existing != name);
+        self.checks
+            .push((name.to_string(), Arc::new(move || Box::pin(check()))));
+        self
+    }
+
+    /// Register the `database`, `pool` and `jobs` checks backed by `pool`
+    pub fn with_database(self, pool: PgPool) -> Self {
+        let database = pool.clone();
+        let jobs = pool.clone();
+        self.with_check("database", move || check_database(database.clone()))
+            .with_check("pool", move || {
+                let pool = pool.clone();
+                async move { check_pool(&pool) }
+            })
+            .with_check("jobs", move || check_jobs(jobs.clone()))
+    }
+
+    /// Run every check, each bounded by `CHECK_TIMEOUT`
+    pub async fn report(&self) -> StatusReport {
+        let mut subsystems: BTreeMap<String, SubsystemHealth> = SUBSYSTEMS
+            .iter()
+            .map(|name| {
+                (
+                    name.to_string(),
+                    SubsystemHealth::new(HealthState::Disabled),
+                )
+            })
+            .collect();
+
+        for (name, check) in &self.checks {
+            let health = tokio::time::timeout(CHECK_TIMEOUT, check())
+                .await
+                .unwrap_or_else(|_| SubsystemHealth::unhealthy("Check timed out"));
+            subsystems.insert(name.clone(), health);
+        }
+
+        let database_down = subsystems
+            .get("database")
+            .is_some_and(|db| db.status == HealthState::Unhealthy);
+        let status = if database_down {
+            HealthState::Unhealthy
+        } else if subsystems
+            .values()
+            .all(|s| matches!(s.status, HealthState::Healthy | HealthState::Disabled))
+        {
+            HealthState::Healthy
+        } else {
+            HealthState::Degraded
+        };
+
+        StatusReport {
+            status,
+            timestamp: Utc::now().to_rfc3339(),
+            subsystems,
+        }
+    }
+}
+
+async fn check_database(pool: PgPool) -> SubsystemHealth {
+    let started = Instant::now();
+    match sqlx::query("SELECT 1").fetch_one(&pool).await {
+        Ok(_) => SubsystemHealth::healthy().with_latency(started.elapsed()),
+        Err(e) => {
+            tracing::warn!("Status check: database unavailable: {}", e);
+            SubsystemHealth::unhealthy("Database unavailable").with_latency(started.elapsed())
+        }
+    }
+}
+
+/// Degraded when every connection is checked out
+fn check_pool(pool: &PgPool) -> SubsystemHealth {
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+    let max = pool.options().get_max_connections();
+    let status = if size >= max && idle == 0 {
+        HealthState::Degraded
+    } else {
+        HealthState::Healthy
+    };
+
+    SubsystemHealth::new(status).with_details(serde_json::json!({
+        "size": size,
+        "idle": idle,
+        "in_use": size.saturating_sub(idle),
+        "max_connections": max,
+    }))
+}
+
+#[derive(sqlx::FromRow)]
+struct LastJobRun {
+    job_name: String,
+    status: String,
+    started_at: DateTime<Utc>,
+    finished_at: DateTime<Utc>,
+}
+
+/// Latest run of each job from `job_runs`; degraded when one last failed
+async fn check_jobs(pool: PgPool) -> SubsystemHealth {
+    let runs = sqlx::query_as::<_, LastJobRun>(
+        r#"
+        SELECT DISTINCT ON (job_name) job_name, status, started_at, finished_at
+        FROM job_runs
+        ORDER BY job_name, started_at DESC
+        "#,
+    )
+    .fetch_all(&pool)
+    .await;
+
+    let runs = match runs {
+        Ok(runs) => runs,
+        Err(e) => {
+            tracing::warn!("Status check: could not read job_runs: {}", e);
+            return SubsystemHealth::unhealthy("Job runs unavailable");
+        }
+    };
+
+    let status = if runs.iter().any(|run| run.status == "failed") {
+        HealthState::Degraded
+    } else {
+        HealthState::Healthy
+    };
+    let last_runs: serde_json::Map<String, serde_json::Value> = runs
+        .into_iter()
+        .map(|run| {
+            (
+                run.job_name,
+                serde_json::json!({
+                    "status": run.status,
+                    "started_at": run.started_at,
+                    "finished_at": run.finished_at,
+                }),
+            )
+        })
+        .collect();
+
+    SubsystemHealth::new(status).with_details(serde_json::json!({ "last_runs": last_runs }))
+}
+
+/// Per-subsystem status tree
+#[utoipa::path(
+    get,
+    path = "/api/v1/status",
+    tag = "health",
+    responses(
+        (status = 200, description = "Healthy or degraded", body = StatusReport),
+        (status = 503, description = "Database unavailable", body = StatusReport)
+    )
+)]
+async fn status(State(registry): State<Arc<HealthRegistry>>) -> impl IntoResponse {
+    let report = registry.report().await;
+    let status_code = if report.status == HealthState::Unhealthy {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+
+    (status_code, Json(report))
+}
+
+/// `GET /api/v1/status`; with `admin_only` it requires an admin bearer token
+pub fn status_routes(registry: HealthRegistry, jwt_config: JwtConfig, admin_only: bool) -> Router {
+    let router = Router::new().route(STATUS_PATH, get(status));
+    let router = if admin_only {
+        router
+            .layer(middleware::from_fn(require_admin))
+            .layer(middleware::from_fn_with_state(
+                AuthMiddleware::new(jwt_config),
+                auth_middleware,
+            ))
+    } else {
+        router
+    };
+
+    router.with_state(Arc::new(registry))
+}
@@ -1,9 +1,22 @@
+pub mod registry;
+pub mod routes;
+pub mod runs;
 pub mod scheduler;
 pub mod tasks;
 
-use sqlx::PgPool;
+pub use registry::JobRegistry;
+pub use routes::admin_routes;
+pub use scheduler::SchedulerHandle;
+
+use crate::config::SchedulerConfig;
 use crate::utils::error::AppResult;
 
-pub async fn start_scheduler(db_pool: PgPool) -> AppResult<()> {
-    scheduler::start(db_pool).await
+/// Schedule and start every job in `registry`; stop them with the returned handle
+pub async fn start_scheduler(
+    registry: JobRegistry,
+    config: &SchedulerConfig,
+) -> AppResult<SchedulerHandle> {
+    let handle = SchedulerHandle::new(registry, config).await?;
+    handle.start().await?;
+    Ok(handle)
 }
@@ -0,0 +1,149 @@
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::{collections::HashMap, future::Future, pin::Pin, sync::Arc, time::Instant};
+use tokio::sync::Mutex;
+
+use crate::config::JobsConfig;
+use crate::utils::error::{AppError, AppResult};
+
+use super::runs::{recent_job_runs, record_job_run, JobRunRecord};
+use super::tasks::{self, JobOutcome, ObjectStore, PURGE_SOFT_DELETED_JOB};
+
+/// Job name of `tasks::cleanup_old_data`
+pub const CLEANUP_JOB: &str = "cleanup_old_data";
+
+/// Job name of `tasks::aggregate_metrics`
+pub const METRICS_JOB: &str = "aggregate_metrics";
+
+type Task = Arc<
+    dyn Fn(PgPool) -> Pin<Box<dyn Future<Output = AppResult<JobOutcome>> + Send>> + Send + Sync,
+>;
+
+struct RegisteredJob {
+    task: Task,
+    /// Held while the job runs, so a manual trigger and the scheduled run
+    /// never overlap
+    running: Arc<Mutex<()>>,
+}
+
+/// One completed run of a named job
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct JobRun {
+    pub job: String,
+    #[serde(flatten)]
+    pub outcome: JobOutcome,
+    pub duration_ms: u64,
+}
+
+/// The background jobs by name, shared by the scheduler and the admin trigger
+#[derive(Clone)]
+pub struct JobRegistry {
+    db_pool: PgPool,
+    jobs: Arc<HashMap<&'static str, RegisteredJob>>,
+}
+
+impl JobRegistry {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            jobs: Arc::default(),
+        }
+    }
+
+    /// Registry of every built-in job; `objects` is where purged users'
+    /// files are stored, if anywhere
+    pub fn with_default_jobs(
+        db_pool: PgPool,
+        config: &JobsConfig,
+        objects: Option<Arc<dyn ObjectStore>>,
+    ) -> Self {
+        let retention_days = config.soft_delete_retention_days;
+
+        Self::new(db_pool)
+            .with_job(CLEANUP_JOB, |pool| async move {
+                tasks::cleanup_old_data(&pool).await
+            })
+            .with_job(METRICS_JOB, |pool| async move {
+                tasks::aggregate_metrics(&pool).await
+            })
+            .with_job(PURGE_SOFT_DELETED_JOB, move |pool| {
+                let objects = objects.clone();
+                async move {
+                    let summary =
+                        tasks::purge_soft_deleted(pool, retention_days, objects.as_deref()).await?;
+                    Ok(JobOutcome {
+                        rows: summary.users_purged,
+                        details: serde_json::to_value(&summary).unwrap_or_default(),
+                    })
+                }
+            })
+    }
+
+    /// Register (or replace) the job run under `name`
+    pub fn with_job<F, Fut>(mut self, name: &'static str, task: F) -> Self
+    where
+        F: Fn(PgPool) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = AppResult<JobOutcome>> + Send + 'static,
+    {
+        let job = RegisteredJob {
+            task: Arc::new(move |pool| Box::pin(task(pool))),
+            running: Arc::default(),
+        };
+        // Only called while building, before the map is shared
+        Arc::get_mut(&mut self.jobs)
+            .expect("jobs are registered before the registry is cloned")
+            .insert(name, job);
+        self
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.jobs.keys().copied().collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Run `name` now; 404 for unknown jobs, 409 while it is already running
+    ///
+    /// Every run is recorded in `job_runs`, failures included. The task runs
+    /// on its own task, so a panic is recorded as a failure rather than
+    /// taking the caller (the scheduler) down with it.
+    pub async fn run(&self, name: &str) -> AppResult<JobRun> {
+        let job = self
+            .jobs
+            .get(name)
+            .ok_or_else(|| AppError::NotFound(format!("No job named '{}'", name)))?;
+
+        let _running = job
+            .running
+            .clone()
+            .try_lock_owned()
+            .map_err(|_| AppError::Conflict(format!("Job '{}' is already running", name)))?;
+
+        let started_at = Utc::now();
+        let started = Instant::now();
+        let result = match tokio::spawn((job.task)(self.db_pool.clone())).await {
+            Ok(result) => result,
+            Err(e) => Err(AppError::InternalServer(format!(
+                "Job '{}' panicked: {}",
+                name, e
+            ))),
+        };
+
+        if let Err(e) = record_job_run(&self.db_pool, name, started_at, &result).await {
+            tracing::warn!("Failed to record run of job '{}': {}", name, e);
+        }
+        let outcome = result?;
+
+        Ok(JobRun {
+            job: name.to_string(),
+            outcome,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Latest recorded runs, of `name` or of every job
+    pub async fn history(&self, name: Option<&str>, limit: u32) -> AppResult<Vec<JobRunRecord>> {
+        recent_job_runs(&self.db_pool, name, limit).await
+    }
+}
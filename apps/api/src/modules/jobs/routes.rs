@@ -0,0 +1,105 @@
+use axum::{
+    extract::{Path, Query, State},
+    middleware,
+    routing::{get, post},
+    Extension, Router,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::config::JwtConfig;
+use crate::modules::auth::{
+    jwt::Claims,
+    middleware::{auth_middleware, AuthMiddleware},
+    role_guard::require_admin,
+};
+use crate::utils::{
+    error::{AppResult, ErrorResponse},
+    response::ApiResponse,
+};
+
+use super::registry::{JobRegistry, JobRun};
+use super::runs::JobRunRecord;
+
+/// Most runs `GET /api/v1/admin/jobs/history` returns
+pub const MAX_JOB_HISTORY_LIMIT: u32 = 100;
+
+const DEFAULT_JOB_HISTORY_LIMIT: u32 = 20;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct HistoryQuery {
+    /// Only runs of this job
+    name: Option<String>,
+    /// Default 20, at most 100
+    limit: Option<u32>,
+}
+
+/// Admin-only manual job triggers and run history
+pub fn admin_routes(registry: JobRegistry, jwt_config: JwtConfig) -> Router {
+    Router::new()
+        .route("/api/v1/admin/jobs/history", get(job_history))
+        .route("/api/v1/admin/jobs/{name}/run", post(run_job))
+        .layer(middleware::from_fn(require_admin))
+        .layer(middleware::from_fn_with_state(
+            AuthMiddleware::new(jwt_config),
+            auth_middleware,
+        ))
+        .with_state(registry)
+}
+
+/// Run a job now and wait for it; 409 while the same job is already running
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/jobs/{name}/run",
+    tag = "jobs",
+    params(("name" = String, Path, description = "Job name, e.g. `cleanup_old_data`")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The finished run", body = ApiResponse<JobRun>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Unknown job", body = ErrorResponse),
+        (status = 409, description = "Job already running", body = ErrorResponse)
+    )
+)]
+async fn run_job(
+    State(registry): State<JobRegistry>,
+    Extension(claims): Extension<Claims>,
+    Path(name): Path<String>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    tracing::info!(
+        target: "audit",
+        admin_id = %claims.sub,
+        job = %name,
+        "Job triggered manually by admin"
+    );
+
+    let run = registry.run(&name).await?;
+    Ok(ApiResponse::success(run))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/jobs/history",
+    tag = "jobs",
+    params(HistoryQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Most recent runs first", body = ApiResponse<Vec<JobRunRecord>>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse)
+    )
+)]
+async fn job_history(
+    State(registry): State<JobRegistry>,
+    Query(query): Query<HistoryQuery>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_JOB_HISTORY_LIMIT)
+        .clamp(1, MAX_JOB_HISTORY_LIMIT);
+
+    let runs = registry.history(query.name.as_deref(), limit).await?;
+    Ok(ApiResponse::success(runs))
+}
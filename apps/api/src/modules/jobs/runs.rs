@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::utils::error::AppResult;
+
+use super::tasks::JobOutcome;
+
+/// One execution of a background job, as stored in `job_runs`
+#[derive(Debug, Serialize, sqlx::FromRow, utoipa::ToSchema)]
+pub struct JobRunRecord {
+    pub id: Uuid,
+    pub job_name: String,
+    /// `succeeded` or `failed`
+    pub status: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub rows_affected: Option<i64>,
+    pub error_message: Option<String>,
+    #[schema(value_type = Object)]
+    pub summary: serde_json::Value,
+}
+
+/// Record one execution of a background job in `job_runs`
+pub async fn record_job_run(
+    pool: &PgPool,
+    job_name: &str,
+    started_at: DateTime<Utc>,
+    result: &AppResult<JobOutcome>,
+) -> AppResult<()> {
+    let (status, rows_affected, error_message, summary) = match result {
+        Ok(outcome) => (
+            "succeeded",
+            Some(outcome.rows as i64),
+            None,
+            outcome.details.clone(),
+        ),
+        Err(e) => ("failed", None, Some(e.to_string()), serde_json::Value::Null),
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO job_runs
+            (id, job_name, status, summary, started_at, finished_at, rows_affected, error_message)
+        VALUES ($1, $2, $3, COALESCE($4, '{}'::jsonb), $5, NOW(), $6, $7)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(job_name)
+    .bind(status)
+    .bind((!summary.is_null()).then_some(summary))
+    .bind(started_at)
+    .bind(rows_affected)
+    .bind(error_message)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Latest runs first, of one job or of all of them
+pub async fn recent_job_runs(
+    pool: &PgPool,
+    job_name: Option<&str>,
+    limit: u32,
+) -> AppResult<Vec<JobRunRecord>> {
+    let runs = sqlx::query_as::<_, JobRunRecord>(
+        r#"
+        SELECT id, job_name, status, started_at, finished_at, rows_affected, error_message, summary
+        FROM job_runs
+        WHERE $1::TEXT IS NULL OR job_name = $1
+        ORDER BY started_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(job_name)
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(runs)
+}
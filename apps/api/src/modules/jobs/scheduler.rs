@@ -1,68 +1,86 @@
-use sqlx::PgPool;
 use tokio_cron_scheduler::{Job, JobScheduler};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
+use crate::config::SchedulerConfig;
 use crate::utils::error::{AppError, AppResult};
 
-use super::tasks;
+use super::registry::{JobRegistry, CLEANUP_JOB, METRICS_JOB};
+use super::tasks::PURGE_SOFT_DELETED_JOB;
 
-pub async fn start(db_pool: PgPool) -> AppResult<()> {
-    let scheduler = JobScheduler::new()
-        .await
-        .map_err(|e| AppError::InternalServer(format!("Failed to create scheduler: {}", e)))?;
+/// The background jobs, scheduled but not running until `start`
+pub struct SchedulerHandle {
+    scheduler: JobScheduler,
+}
 
-    // Example: Run cleanup task every day at midnight
-    let cleanup_job = Job::new_async("0 0 0 * * *", move |_uuid, _lock| {
-        let pool = db_pool.clone();
-        Box::pin(async move {
-            info!("Running daily cleanup task");
-            match tasks::cleanup_old_data(pool).await {
-                Ok(_) => info!("Cleanup task completed successfully"),
-                Err(e) => error!("Cleanup task failed: {}", e),
-            }
-        })
-    })
-    .map_err(|e| AppError::InternalServer(format!("Failed to create cleanup job: {}", e)))?;
+impl SchedulerHandle {
+    /// Schedule the registry's jobs per `config`; fails on an invalid cron
+    /// expression
+    pub async fn new(registry: JobRegistry, config: &SchedulerConfig) -> AppResult<Self> {
+        let scheduler = JobScheduler::new()
+            .await
+            .map_err(|e| AppError::InternalServer(format!("Failed to create scheduler: {}", e)))?;
+        let handle = Self { scheduler };
 
-    scheduler
-        .add(cleanup_job)
-        .await
-        .map_err(|e| AppError::InternalServer(format!("Failed to add cleanup job: {}", e)))?;
+        handle
+            .add(CLEANUP_JOB, &config.cleanup_cron, registry.clone())
+            .await?;
+        handle
+            .add(METRICS_JOB, &config.metrics_cron, registry.clone())
+            .await?;
+        handle
+            .add(PURGE_SOFT_DELETED_JOB, &config.purge_cron, registry)
+            .await?;
 
-    // Example: Run metrics aggregation every hour
-    let db_pool_clone = db_pool.clone();
-    let metrics_job = Job::new_async("0 0 * * * *", move |_uuid, _lock| {
-        let pool = db_pool_clone.clone();
-        Box::pin(async move {
-            info!("Running hourly metrics aggregation");
-            match tasks::aggregate_metrics(pool).await {
-                Ok(_) => info!("Metrics aggregation completed successfully"),
-                Err(e) => error!("Metrics aggregation failed: {}", e),
-            }
+        Ok(handle)
+    }
+
+    /// Schedule the job registered as `name` to run on `cron`, logging its outcome
+    async fn add(&self, name: &'static str, cron: &str, registry: JobRegistry) -> AppResult<()> {
+        let job = Job::new_async(cron, move |_uuid, _lock| {
+            let registry = registry.clone();
+            Box::pin(async move {
+                info!("Running {} job", name);
+                match registry.run(name).await {
+                    Ok(run) => info!("{} job completed: {:?}", name, run),
+                    // Triggered manually and still going
+                    Err(AppError::Conflict(_)) => warn!("Skipping {} job: already running", name),
+                    Err(e) => error!("{} job failed: {}", name, e),
+                }
+            })
         })
-    })
-    .map_err(|e| AppError::InternalServer(format!("Failed to create metrics job: {}", e)))?;
+        .map_err(|e| {
+            AppError::InternalServer(format!(
+                "Invalid schedule '{}' for {} job: {}",
+                cron, name, e
+            ))
+        })?;
+
+        self.scheduler
+            .add(job)
+            .await
+            .map_err(|e| AppError::InternalServer(format!("Failed to add {} job: {}", name, e)))?;
 
-    scheduler
-        .add(metrics_job)
-        .await
-        .map_err(|e| AppError::InternalServer(format!("Failed to add metrics job: {}", e)))?;
+        Ok(())
+    }
 
-    // Start the scheduler
-    scheduler
-        .start()
-        .await
-        .map_err(|e| AppError::InternalServer(format!("Failed to start scheduler: {}", e)))?;
+    pub async fn start(&self) -> AppResult<()> {
+        self.scheduler
+            .start()
+            .await
+            .map_err(|e| AppError::InternalServer(format!("Failed to start scheduler: {}", e)))?;
 
-    info!("Job scheduler started successfully");
+        info!("Job scheduler started successfully");
+        Ok(())
+    }
 
-    // Keep scheduler alive by spawning it in a background task
-    tokio::spawn(async move {
-        // Scheduler will run in the background
-        loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(3600)).await;
-        }
-    });
+    /// Stop scheduling; a job already running is not interrupted
+    pub async fn stop(mut self) -> AppResult<()> {
+        self.scheduler
+            .shutdown()
+            .await
+            .map_err(|e| AppError::InternalServer(format!("Failed to stop scheduler: {}", e)))?;
 
-    Ok(())
+        info!("Job scheduler stopped");
+        Ok(())
+    }
 }
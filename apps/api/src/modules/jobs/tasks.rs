@@ -1,10 +1,50 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::Serialize;
 use sqlx::PgPool;
-use tracing::info;
+use tracing::{info, warn};
+use uuid::Uuid;
 
 use crate::utils::error::AppResult;
 
-/// Example task: Clean up old data
-pub async fn cleanup_old_data(pool: PgPool) -> AppResult<()> {
+/// Job name used for soft-delete purges in `job_runs`
+pub const PURGE_SOFT_DELETED_JOB: &str = "purge_soft_deleted";
+
+/// What one run of a task did
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct JobOutcome {
+    /// Rows the task deleted or counted
+    pub rows: u64,
+    /// Task-specific summary, stored with the run in `job_runs`
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    #[schema(value_type = Object)]
+    pub details: serde_json::Value,
+}
+
+/// Rows permanently removed by a soft-delete purge
+#[derive(Debug, Default, Serialize)]
+pub struct PurgeSummary {
+    pub retention_days: i64,
+    pub users_purged: u64,
+    pub files_purged: u64,
+    /// Files kept because their object could not be deleted; their owners
+    /// are kept with them and retried on the next run
+    pub files_failed: u64,
+}
+
+/// Where uploaded files' objects are stored; `StorageService` (S3) when the
+/// `storage` feature is on
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    async fn delete_object(&self, key: &str) -> AppResult<()>;
+}
+
+/// Delete users inactive for two years; `rows` is the number deleted
+///
+/// Users who never logged in count as inactive from their signup. Expired
+/// idempotency keys go too (reported in `details`), since they are otherwise
+/// only reclaimed when a client reuses the same key.
+pub async fn cleanup_old_data(pool: &PgPool) -> AppResult<JobOutcome> {
     info!("Starting cleanup of old data...");
 
     // Example: Delete users who haven't logged in for 2 years
@@ -13,18 +53,103 @@ pub async fn cleanup_old_data(pool: PgPool) -> AppResult<()> {
         DELETE FROM users
         WHERE last_login < NOW() - INTERVAL '2 years'
         OR (last_login IS NULL AND created_at < NOW() - INTERVAL '2 years')
-        "#
+        "#,
     )
-    .execute(&pool)
+    .execute(pool)
     .await?;
 
     info!("Cleaned up {} old user records", result.rows_affected());
 
-    Ok(())
+    let idempotency_keys = sqlx::query("DELETE FROM idempotency_keys WHERE expires_at <= NOW()")
+        .execute(pool)
+        .await?;
+
+    Ok(JobOutcome {
+        rows: result.rows_affected(),
+        details: serde_json::json!({
+            "idempotency_keys_deleted": idempotency_keys.rows_affected(),
+        }),
+    })
 }
 
-/// Example task: Aggregate metrics
-pub async fn aggregate_metrics(pool: PgPool) -> AppResult<()> {
+/// Permanently delete users soft-deleted more than `retention_days` ago,
+/// along with their files and, given `objects`, the files' stored objects
+///
+/// Only rows with `deleted_at` set are considered; live accounts are never
+/// touched. A file's row is only removed once its object is, so a failed
+/// object delete leaves the file and its owner for the next run.
+pub async fn purge_soft_deleted(
+    pool: PgPool,
+    retention_days: i64,
+    objects: Option<&dyn ObjectStore>,
+) -> AppResult<PurgeSummary> {
+    let cutoff = Utc::now() - chrono::Duration::days(retention_days);
+
+    let files: Vec<(Uuid, String, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT f.id, f.storage_key, f.thumbnail_key FROM files f
+        JOIN users u ON u.id = f.owner_id
+        WHERE u.deleted_at IS NOT NULL AND u.deleted_at < $1
+        "#,
+    )
+    .bind(cutoff)
+    .fetch_all(&pool)
+    .await?;
+
+    let mut removed = Vec::with_capacity(files.len());
+    let mut files_failed = 0;
+    for (id, storage_key, thumbnail_key) in files {
+        if let Some(objects) = objects {
+            if let Err(e) = objects.delete_object(&storage_key).await {
+                warn!(key = %storage_key, "Failed to delete purged file: {}", e);
+                files_failed += 1;
+                continue;
+            }
+            // A leftover thumbnail is harmless, as in `StorageService::delete_file`
+            if let Some(thumbnail_key) = thumbnail_key {
+                if let Err(e) = objects.delete_object(&thumbnail_key).await {
+                    warn!(key = %thumbnail_key, "Failed to delete thumbnail: {}", e);
+                }
+            }
+        }
+        removed.push(id);
+    }
+
+    let mut tx = pool.begin().await?;
+    let files_purged = sqlx::query("DELETE FROM files WHERE id = ANY($1)")
+        .bind(&removed)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    // Owners of files still on storage stay until those are gone
+    let users_purged = sqlx::query(
+        r#"
+        DELETE FROM users u
+        WHERE u.deleted_at IS NOT NULL AND u.deleted_at < $1
+        AND NOT EXISTS (SELECT 1 FROM files f WHERE f.owner_id = u.id)
+        "#,
+    )
+    .bind(cutoff)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    tx.commit().await?;
+
+    let summary = PurgeSummary {
+        retention_days,
+        users_purged,
+        files_purged,
+        files_failed,
+    };
+    info!(
+        "Purged {} soft-deleted users and {} of their files",
+        summary.users_purged, summary.files_purged
+    );
+    Ok(summary)
+}
+
+/// Count users active in the last 24 hours; `rows` is that count
+pub async fn aggregate_metrics(pool: &PgPool) -> AppResult<JobOutcome> {
     info!("Starting metrics aggregation...");
 
     // Example: Calculate daily active users
@@ -33,9 +158,9 @@ pub async fn aggregate_metrics(pool: PgPool) -> AppResult<()> {
         SELECT COUNT(DISTINCT id)
         FROM users
         WHERE last_login >= NOW() - INTERVAL '24 hours'
-        "#
+        "#,
     )
-    .fetch_one(&pool)
+    .fetch_one(pool)
     .await?;
 
     info!("Daily active users: {}", result.0);
@@ -44,10 +169,13 @@ pub async fn aggregate_metrics(pool: PgPool) -> AppResult<()> {
     // sqlx::query("INSERT INTO metrics (metric_name, value, recorded_at) VALUES ($1, $2, NOW())")
     //     .bind("daily_active_users")
     //     .bind(result.0)
-    //     .execute(&pool)
+    //     .execute(pool)
     //     .await?;
 
-    Ok(())
+    Ok(JobOutcome {
+        rows: result.0 as u64,
+        ..Default::default()
+    })
 }
 
 /// Example task: Send notification emails
@@ -0,0 +1,81 @@
+use axum::{extract::State, middleware, routing::get, Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+
+use crate::config::JwtConfig;
+use crate::middleware::read_only::{ReadOnlyMode, READ_ONLY_ADMIN_PATH};
+use crate::modules::auth::{
+    jwt::Claims,
+    middleware::{auth_middleware, AuthMiddleware},
+    role_guard::require_admin,
+};
+use crate::utils::{
+    error::{AppResult, ErrorResponse},
+    response::ApiResponse,
+};
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ReadOnlyStatus {
+    pub enabled: bool,
+}
+
+/// Admin-only maintenance switches
+pub fn admin_routes(read_only: ReadOnlyMode, jwt_config: JwtConfig) -> Router {
+    Router::new()
+        .route(READ_ONLY_ADMIN_PATH, get(get_read_only).put(set_read_only))
+        .layer(middleware::from_fn(require_admin))
+        .layer(middleware::from_fn_with_state(
+            AuthMiddleware::new(jwt_config),
+            auth_middleware,
+        ))
+        .with_state(read_only)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/read-only",
+    tag = "maintenance",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Whether writes are rejected", body = ApiResponse<ReadOnlyStatus>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse)
+    )
+)]
+async fn get_read_only(
+    State(read_only): State<ReadOnlyMode>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    Ok(ApiResponse::success(ReadOnlyStatus {
+        enabled: read_only.is_enabled(),
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/read-only",
+    tag = "maintenance",
+    request_body = ReadOnlyStatus,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The new setting", body = ApiResponse<ReadOnlyStatus>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse)
+    )
+)]
+async fn set_read_only(
+    State(read_only): State<ReadOnlyMode>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<ReadOnlyStatus>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    read_only.set_enabled(request.enabled);
+
+    tracing::info!(
+        target: "audit",
+        admin_id = %claims.sub,
+        read_only = request.enabled,
+        "Read-only mode changed by admin"
+    );
+
+    Ok(ApiResponse::success(ReadOnlyStatus {
+        enabled: read_only.is_enabled(),
+    }))
+}
@@ -1,9 +1,13 @@
+pub mod api_config;
+pub mod api_keys;
 pub mod auth;
-pub mod users;
+pub mod graphql;
 pub mod health;
+pub mod maintenance;
+pub mod ping;
+pub mod users;
 pub mod version;
-pub mod api_config;
-pub mod graphql;
+pub mod webhooks;
 
 #[cfg(feature = "ai")]
 pub mod ai;
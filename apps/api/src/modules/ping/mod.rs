@@ -0,0 +1,22 @@
+use axum::{routing::get, Router};
+
+/// Liveness endpoint for high-frequency external monitors
+pub const PING_PATH: &str = "/api/v1/ping";
+
+/// Answers without touching the database; use `/health` or `/ready` for dependency checks
+#[utoipa::path(
+    get,
+    path = "/api/v1/ping",
+    tag = "health",
+    responses(
+        (status = 200, description = "Server is up", body = String)
+    )
+)]
+async fn ping() -> &'static str {
+    "pong"
+}
+
+/// Merged outside the rate limiter so monitors polling it are never throttled
+pub fn routes() -> Router {
+    Router::new().route(PING_PATH, get(ping))
+}
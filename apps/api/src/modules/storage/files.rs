@@ -0,0 +1,211 @@
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::future::Future;
+use uuid::Uuid;
+
+use crate::utils::error::{AppError, AppResult};
+
+use super::model::StoredFile;
+
+/// Hex-encoded SHA-256 of an upload's bytes
+pub fn content_hash(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Bucket key of an upload
+pub fn storage_key(file_id: Uuid, file_name: &str) -> String {
+    format!("uploads/{}/{}", file_id, file_name)
+}
+
+/// Columns of a `files` row about to be inserted
+pub struct NewFile<'a> {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub file_name: &'a str,
+    pub content_type: &'a str,
+    pub file_size: u64,
+    pub content_hash: &'a str,
+    pub storage_key: &'a str,
+}
+
+/// Records uploaded objects in the `files` table
+#[derive(Clone)]
+pub struct FileStore {
+    db_pool: PgPool,
+    dedupe: bool,
+}
+
+impl FileStore {
+    pub fn new(db_pool: PgPool, dedupe: bool) -> Self {
+        Self { db_pool, dedupe }
+    }
+
+    /// Store an upload through `put`, unless dedupe is on and the owner already
+    /// uploaded identical content; returns the file and whether it was reused
+    pub async fn store<F, Fut>(
+        &self,
+        owner_id: Uuid,
+        file_name: &str,
+        content_type: &str,
+        data: &[u8],
+        put: F,
+    ) -> AppResult<(StoredFile, bool)>
+    where
+        F: FnOnce(String) -> Fut,
+        Fut: Future<Output = AppResult<()>>,
+    {
+        let hash = content_hash(data);
+
+        if self.dedupe {
+            if let Some(existing) = self.find_by_hash(owner_id, &hash).await? {
+                return Ok((existing, true));
+            }
+        }
+
+        let file_id = Uuid::new_v4();
+        let key = storage_key(file_id, file_name);
+
+        put(key.clone()).await?;
+
+        let file = self
+            .record(NewFile {
+                id: file_id,
+                owner_id,
+                file_name,
+                content_type,
+                file_size: data.len() as u64,
+                content_hash: &hash,
+                storage_key: &key,
+            })
+            .await?;
+
+        Ok((file, false))
+    }
+
+    /// Whether an owner's identical uploads should share one object
+    pub fn dedupe(&self) -> bool {
+        self.dedupe
+    }
+
+    /// Insert the row for an object already written to the bucket
+    pub async fn record(&self, file: NewFile<'_>) -> AppResult<StoredFile> {
+        let file = sqlx::query_as::<_, StoredFile>(
+            r#"
+            INSERT INTO files (id, owner_id, file_name, content_type, file_size, content_hash, storage_key)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+        )
+        .bind(file.id)
+        .bind(file.owner_id)
+        .bind(file.file_name)
+        .bind(file.content_type)
+        .bind(file.file_size as i64)
+        .bind(file.content_hash)
+        .bind(file.storage_key)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(file)
+    }
+
+    /// Insert the row for a presigned upload the client has yet to send
+    pub async fn record_pending(
+        &self,
+        id: Uuid,
+        owner_id: Uuid,
+        file_name: &str,
+        content_type: &str,
+        storage_key: &str,
+    ) -> AppResult<StoredFile> {
+        let file = sqlx::query_as::<_, StoredFile>(
+            r#"
+            INSERT INTO files (id, owner_id, file_name, content_type, file_size, storage_key, pending)
+            VALUES ($1, $2, $3, $4, 0, $5, TRUE)
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(owner_id)
+        .bind(file_name)
+        .bind(content_type)
+        .bind(storage_key)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(file)
+    }
+
+    /// Mark a presigned upload as present in the bucket with `file_size` bytes
+    pub async fn confirm(&self, id: Uuid, file_size: u64) -> AppResult<StoredFile> {
+        let file = sqlx::query_as::<_, StoredFile>(
+            "UPDATE files SET pending = FALSE, file_size = $2 WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(file_size as i64)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("File not found".to_string()))?;
+
+        Ok(file)
+    }
+
+    pub async fn set_thumbnail(&self, id: Uuid, thumbnail_key: &str) -> AppResult<StoredFile> {
+        let file = sqlx::query_as::<_, StoredFile>(
+            "UPDATE files SET thumbnail_key = $2 WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(thumbnail_key)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("File not found".to_string()))?;
+
+        Ok(file)
+    }
+
+    pub async fn find(&self, id: Uuid) -> AppResult<Option<StoredFile>> {
+        let file = sqlx::query_as::<_, StoredFile>("SELECT * FROM files WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.db_pool)
+            .await?;
+
+        Ok(file)
+    }
+
+    /// The owner's earliest upload with this content hash
+    pub async fn find_by_hash(&self, owner_id: Uuid, hash: &str) -> AppResult<Option<StoredFile>> {
+        let file = sqlx::query_as::<_, StoredFile>(
+            "SELECT * FROM files WHERE owner_id = $1 AND content_hash = $2 ORDER BY created_at LIMIT 1",
+        )
+        .bind(owner_id)
+        .bind(hash)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(file)
+    }
+
+    /// Delete a file's row together with its object: the row is removed in
+    /// a transaction that only commits once `delete_object` succeeded, so a
+    /// failed S3 delete leaves the row in place
+    pub async fn delete<F, Fut>(&self, file_id: Uuid, delete_object: F) -> AppResult<()>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AppResult<()>>,
+    {
+        let mut tx = self.db_pool.begin().await?;
+
+        let result = sqlx::query("DELETE FROM files WHERE id = $1")
+            .bind(file_id)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("File not found".to_string()));
+        }
+
+        delete_object().await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
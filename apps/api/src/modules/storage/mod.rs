@@ -1,5 +1,8 @@
+pub mod files;
 pub mod model;
-pub mod service;
+pub mod retry;
 pub mod routes;
+pub mod service;
+pub mod thumbnail;
 
 pub use routes::routes;
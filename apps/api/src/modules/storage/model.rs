@@ -1,26 +1,48 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UploadResponse {
     pub file_id: String,
     pub file_name: String,
     pub file_size: u64,
     pub content_type: String,
     pub url: String,
+    /// True when identical content from the same owner was already stored
+    pub deduplicated: bool,
+    /// Image uploads get one; see `GET /storage/{id}/thumbnail`
+    pub has_thumbnail: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PresignedUrlResponse {
     pub url: String,
     pub expires_in_seconds: u64,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PresignedUploadRequest {
+    pub filename: String,
+    pub content_type: String,
+}
+
+/// Where to `PUT` the object, and the file to confirm once it is there
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PresignedUploadResponse {
+    pub file_id: String,
+    pub url: String,
+    pub expires_in_seconds: u64,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GetFileRequest {
     pub file_id: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FileMetadata {
     pub file_id: String,
     pub file_name: String,
@@ -28,3 +50,21 @@ pub struct FileMetadata {
     pub content_type: String,
     pub uploaded_at: String,
 }
+
+/// Row in the `files` table
+#[derive(Debug, Clone, FromRow)]
+pub struct StoredFile {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub file_size: i64,
+    /// `None` for presigned uploads, whose bytes never pass through the API
+    pub content_hash: Option<String>,
+    pub storage_key: String,
+    /// Presigned upload the client has not confirmed yet
+    pub pending: bool,
+    /// Set for image uploads once their thumbnail is stored
+    pub thumbnail_key: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
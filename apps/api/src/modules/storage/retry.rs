@@ -0,0 +1,111 @@
+use aws_sdk_s3::{config::http::HttpResponse, error::ProvideErrorMetadata, error::SdkError};
+use std::{fmt::Display, future::Future, time::Duration, time::Instant};
+
+use crate::config::StorageConfig;
+use crate::metrics::record_external_api_call;
+use crate::utils::error::{AppError, AppResult};
+
+/// Error codes S3 (and compatible stores) use for throttling
+const THROTTLING_CODES: &[&str] = &[
+    "SlowDown",
+    "Throttling",
+    "ThrottlingException",
+    "RequestLimitExceeded",
+    "RequestTimeout",
+];
+
+/// Whether a failed call is worth repeating
+pub trait RetryableError {
+    fn is_retryable(&self) -> bool;
+}
+
+impl<E> RetryableError for SdkError<E, HttpResponse>
+where
+    E: ProvideErrorMetadata,
+{
+    fn is_retryable(&self) -> bool {
+        match self {
+            SdkError::TimeoutError(_) | SdkError::DispatchFailure(_) => true,
+            SdkError::ResponseError(_) => true,
+            SdkError::ServiceError(context) => {
+                let status = context.raw().status().as_u16();
+                status == 429
+                    || status >= 500
+                    || context
+                        .err()
+                        .code()
+                        .is_some_and(|code| THROTTLING_CODES.contains(&code))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Per-attempt timeout plus bounded exponential backoff for S3 calls
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub timeout: Duration,
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &StorageConfig) -> Self {
+        Self {
+            timeout: Duration::from_secs(config.s3_timeout_secs),
+            max_attempts: config.s3_max_attempts.max(1),
+            base_delay: Duration::from_millis(config.s3_retry_base_ms),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay)
+    }
+
+    /// Run `call` until it succeeds, fails with a non-retryable error
+    /// (mapped through `map_err`) or attempts run out (`StorageUnavailable`)
+    pub async fn run<T, E, F, Fut, M>(
+        &self,
+        operation: &str,
+        mut call: F,
+        map_err: M,
+    ) -> AppResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: RetryableError + Display,
+        M: FnOnce(E) -> AppError,
+    {
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            let started = Instant::now();
+            let result = tokio::time::timeout(self.timeout, call()).await;
+            let elapsed = started.elapsed().as_secs_f64();
+            record_external_api_call("s3", matches!(result, Ok(Ok(_))), elapsed);
+
+            let reason = match result {
+                Ok(Ok(value)) => return Ok(value),
+                Ok(Err(e)) if !e.is_retryable() => return Err(map_err(e)),
+                Ok(Err(e)) => e.to_string(),
+                Err(_) => format!("timed out after {:?}", self.timeout),
+            };
+
+            if attempt >= self.max_attempts {
+                tracing::error!(operation, attempt, %reason, "S3 retries exhausted");
+                return Err(AppError::StorageUnavailable(format!(
+                    "S3 {} failed after {} attempts",
+                    operation, attempt
+                )));
+            }
+
+            tracing::warn!(operation, attempt, %reason, "Retrying S3 call");
+            tokio::time::sleep(self.backoff(attempt)).await;
+        }
+    }
+}
@@ -1,143 +1,348 @@
 use axum::{
-    extract::{Multipart, Path, Query, State},
+    extract::{
+        multipart::{Field, MultipartError},
+        DefaultBodyLimit, Multipart, Path, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    middleware,
     routing::{delete, get, post},
-    Json, Router,
+    Extension, Json, Router,
 };
-use serde::Deserialize;
-use std::sync::Arc;
+use sqlx::PgPool;
+use std::{future::Future, sync::Arc, time::Duration};
+use uuid::Uuid;
 
-use crate::config::StorageConfig;
+use crate::config::{JwtConfig, StorageConfig};
+use crate::middleware::concurrency::{concurrency_limit_middleware, ConcurrencyLimit};
+use crate::modules::auth::{
+    jwt::Claims,
+    middleware::{auth_middleware, AuthMiddleware},
+};
 use crate::utils::{
-    error::{AppError, AppResult},
+    error::{AppError, AppResult, ErrorResponse},
     response::{no_content, ApiResponse},
 };
 
-use super::service::StorageService;
-
-#[derive(Clone)]
-struct StorageState {
-    service: Arc<StorageService>,
-}
+use super::model::{
+    FileMetadata, PresignedUploadRequest, PresignedUploadResponse, PresignedUrlResponse,
+    UploadResponse,
+};
+use super::service::{PendingUpload, StorageService};
 
-#[derive(Deserialize)]
-struct PresignedUrlQuery {
-    file_name: String,
-    content_type: String,
-    #[serde(default = "default_expires_in")]
-    expires_in: u64,
-}
+/// Room for multipart boundaries and part headers on top of the file itself
+const MULTIPART_OVERHEAD_BYTES: u64 = 64 * 1024;
 
-#[derive(Deserialize)]
-struct DownloadQuery {
-    file_name: String,
-    #[serde(default = "default_expires_in")]
-    expires_in: u64,
+/// Largest request body accepted on the upload route: the file size cap
+/// plus multipart framing
+pub fn upload_body_limit(config: &StorageConfig) -> usize {
+    (config.max_file_size_mb * 1024 * 1024 + MULTIPART_OVERHEAD_BYTES) as usize
 }
 
-fn default_expires_in() -> u64 {
-    3600 // 1 hour
+#[derive(Clone)]
+struct StorageState {
+    service: Arc<StorageService>,
+    read_timeout: Duration,
 }
 
-pub fn routes(config: StorageConfig) -> Router {
-    // Create service asynchronously - we'll need to handle this in main.rs
-    // For now, create a placeholder router that will be initialized properly
-    let rt = tokio::runtime::Handle::current();
-    let service = rt.block_on(async {
-        Arc::new(StorageService::new(config).await.expect("Failed to create storage service"))
-    });
+/// Storage routes; all require authentication so uploads have an owner
+pub async fn routes(db_pool: PgPool, jwt_config: JwtConfig, config: StorageConfig) -> Router {
+    // Uploads beyond the cap are shed immediately rather than queued
+    let upload_limit = ConcurrencyLimit::new(config.max_concurrent_uploads, Duration::ZERO);
+    let read_timeout = Duration::from_secs(config.upload_read_timeout_secs);
+    let body_limit = upload_body_limit(&config);
 
-    let state = StorageState { service };
+    let auth = AuthMiddleware::new(jwt_config).with_status_check(db_pool.clone());
+    let service = Arc::new(
+        StorageService::new(db_pool, config)
+            .await
+            .expect("Failed to create storage service"),
+    );
+    let state = StorageState {
+        service,
+        read_timeout,
+    };
 
     Router::new()
-        .route("/storage/upload", post(upload_file))
-        .route("/storage/presigned-upload", get(get_presigned_upload_url))
-        .route("/storage/presigned-download/:file_id", get(get_presigned_download_url))
-        .route("/storage/:file_id", get(get_file_metadata))
-        .route("/storage/:file_id", delete(delete_file))
+        .route(
+            "/storage/upload",
+            post(upload_file)
+                .layer(DefaultBodyLimit::max(body_limit))
+                .layer(middleware::from_fn_with_state(
+                    upload_limit,
+                    concurrency_limit_middleware,
+                )),
+        )
+        .route("/storage/presigned-upload", post(presigned_upload))
+        .route("/storage/{file_id}/confirm", post(confirm_upload))
+        .route(
+            "/storage/{file_id}/presigned-download",
+            get(presigned_download),
+        )
+        .route("/storage/{file_id}/metadata", get(get_file_metadata))
+        .route("/storage/{file_id}/thumbnail", get(get_thumbnail))
+        .route("/storage/{file_id}", delete(delete_file))
+        .layer(middleware::from_fn_with_state(auth, auth_middleware))
         .with_state(state)
 }
 
+/// Stream the `file` field of a multipart form to S3; the body is never
+/// held in memory beyond one part
+#[utoipa::path(
+    post,
+    path = "/storage/upload",
+    tag = "storage",
+    request_body(content_type = "multipart/form-data", description = "The file in a field named `file`"),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "File stored", body = ApiResponse<UploadResponse>),
+        (status = 400, description = "No `file` field or file name", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 408, description = "Client stalled while sending the file", body = ErrorResponse),
+        (status = 413, description = "File too large", body = ErrorResponse)
+    )
+)]
 async fn upload_file(
     State(state): State<StorageState>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
     mut multipart: Multipart,
 ) -> AppResult<impl axum::response::IntoResponse> {
-    let mut file_name: Option<String> = None;
-    let mut content_type: Option<String> = None;
-    let mut file_data: Option<Vec<u8>> = None;
+    let owner_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
 
-    while let Some(field) = multipart
-        .next_field()
-        .await
-        .map_err(|e| AppError::BadRequest(format!("Invalid multipart data: {}", e)))?
-    {
-        let field_name = field.name().unwrap_or("").to_string();
+    // A declared length over the limit is refused before reading any of it
+    let declared_length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if declared_length.is_some_and(|length| {
+        length > state.service.max_file_size_bytes() + MULTIPART_OVERHEAD_BYTES
+    }) {
+        return Err(AppError::FileTooLarge);
+    }
 
-        match field_name.as_str() {
-            "file" => {
-                file_name = field.file_name().map(|s| s.to_string());
-                content_type = field.content_type().map(|s| s.to_string());
+    while let Some(field) = within(state.read_timeout, multipart.next_field())
+        .await?
+        .map_err(multipart_error)?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
 
-                let data = field
-                    .bytes()
-                    .await
-                    .map_err(|e| AppError::BadRequest(format!("Failed to read file: {}", e)))?;
+        let file_name = field
+            .file_name()
+            .map(|s| s.to_string())
+            .ok_or_else(|| AppError::BadRequest("File name is required".to_string()))?;
+        let content_type = field
+            .content_type()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "application/octet-stream".to_string());
 
-                file_data = Some(data.to_vec());
-            }
-            _ => {}
+        let mut upload = state
+            .service
+            .begin_upload(owner_id, file_name, content_type);
+        if let Err(e) = stream_field(field, &mut upload, state.read_timeout).await {
+            upload.abort().await;
+            return Err(e);
         }
+        let response = upload.finish().await?;
+
+        return Ok(ApiResponse::success(response));
     }
 
-    let file_name = file_name.ok_or_else(|| AppError::BadRequest("File name is required".to_string()))?;
-    let content_type = content_type.unwrap_or_else(|| "application/octet-stream".to_string());
-    let file_data = file_data.ok_or_else(|| AppError::BadRequest("File data is required".to_string()))?;
+    Err(AppError::BadRequest("File data is required".to_string()))
+}
 
-    let response = state.service.upload_file(file_name, content_type, file_data).await?;
+/// Feed a multipart field to `upload` chunk by chunk, failing if the client
+/// stalls
+async fn stream_field(
+    mut field: Field<'_>,
+    upload: &mut PendingUpload<'_>,
+    read_timeout: Duration,
+) -> AppResult<()> {
+    while let Some(chunk) = within(read_timeout, field.chunk())
+        .await?
+        .map_err(multipart_error)?
+    {
+        upload.write(&chunk).await?;
+    }
 
-    Ok(ApiResponse::success(response))
+    Ok(())
 }
 
-async fn get_presigned_upload_url(
+/// A body over the route's limit surfaces as a multipart error; keep it a 413
+fn multipart_error(e: MultipartError) -> AppError {
+    if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        AppError::FileTooLarge
+    } else {
+        AppError::BadRequest(format!("Invalid multipart data: {}", e))
+    }
+}
+
+/// Await a body read, mapping a stall longer than `read_timeout` to 408
+async fn within<T>(read_timeout: Duration, read: impl Future<Output = T>) -> AppResult<T> {
+    tokio::time::timeout(read_timeout, read)
+        .await
+        .map_err(|_| AppError::RequestTimeout)
+}
+
+#[utoipa::path(
+    post,
+    path = "/storage/presigned-upload",
+    tag = "storage",
+    request_body = PresignedUploadRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Where to `PUT` the object", body = ApiResponse<PresignedUploadResponse>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
+async fn presigned_upload(
     State(state): State<StorageState>,
-    Query(query): Query<PresignedUrlQuery>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<PresignedUploadRequest>,
 ) -> AppResult<impl axum::response::IntoResponse> {
+    let owner_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
     let response = state
         .service
-        .generate_presigned_upload_url(query.file_name, query.content_type, query.expires_in)
+        .presign_upload(owner_id, request.filename, request.content_type)
         .await?;
 
     Ok(ApiResponse::success(response))
 }
 
-async fn get_presigned_download_url(
+#[utoipa::path(
+    post,
+    path = "/storage/{file_id}/confirm",
+    tag = "storage",
+    params(("file_id" = Uuid, Path, description = "File id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Upload confirmed", body = ApiResponse<UploadResponse>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "File not found", body = ErrorResponse)
+    )
+)]
+async fn confirm_upload(
     State(state): State<StorageState>,
-    Path(file_id): Path<String>,
-    Query(query): Query<DownloadQuery>,
+    Extension(claims): Extension<Claims>,
+    Path(file_id): Path<Uuid>,
 ) -> AppResult<impl axum::response::IntoResponse> {
-    let response = state
-        .service
-        .generate_presigned_download_url(file_id, query.file_name, query.expires_in)
-        .await?;
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    let response = state.service.confirm_upload(file_id, user_id).await?;
+
+    Ok(ApiResponse::success(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/storage/{file_id}/presigned-download",
+    tag = "storage",
+    params(("file_id" = Uuid, Path, description = "File id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Time-limited download URL", body = ApiResponse<PresignedUrlResponse>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "File not found", body = ErrorResponse)
+    )
+)]
+async fn presigned_download(
+    State(state): State<StorageState>,
+    Extension(claims): Extension<Claims>,
+    Path(file_id): Path<Uuid>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    let response = state.service.presign_download(file_id, user_id).await?;
 
     Ok(ApiResponse::success(response))
 }
 
+#[utoipa::path(
+    get,
+    path = "/storage/{file_id}/metadata",
+    tag = "storage",
+    params(("file_id" = Uuid, Path, description = "File id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "File metadata", body = ApiResponse<FileMetadata>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "File not found", body = ErrorResponse)
+    )
+)]
 async fn get_file_metadata(
     State(state): State<StorageState>,
-    Path(file_id): Path<String>,
-    Query(query): Query<DownloadQuery>,
+    Extension(claims): Extension<Claims>,
+    Path(file_id): Path<Uuid>,
 ) -> AppResult<impl axum::response::IntoResponse> {
-    let metadata = state.service.get_file_metadata(file_id, query.file_name).await?;
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    let metadata = state
+        .service
+        .get_file_metadata(file_id, user_id, claims.role)
+        .await?;
 
     Ok(ApiResponse::success(metadata))
 }
 
+#[utoipa::path(
+    get,
+    path = "/storage/{file_id}/thumbnail",
+    tag = "storage",
+    params(("file_id" = Uuid, Path, description = "File id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Time-limited thumbnail URL", body = ApiResponse<PresignedUrlResponse>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "File or thumbnail not found", body = ErrorResponse)
+    )
+)]
+async fn get_thumbnail(
+    State(state): State<StorageState>,
+    Extension(claims): Extension<Claims>,
+    Path(file_id): Path<Uuid>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    let response = state
+        .service
+        .presign_thumbnail(file_id, user_id, claims.role)
+        .await?;
+
+    Ok(ApiResponse::success(response))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/storage/{file_id}",
+    tag = "storage",
+    params(("file_id" = Uuid, Path, description = "File id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "File deleted"),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "File not found", body = ErrorResponse)
+    )
+)]
 async fn delete_file(
     State(state): State<StorageState>,
-    Path(file_id): Path<String>,
-    Query(query): Query<DownloadQuery>,
+    Extension(claims): Extension<Claims>,
+    Path(file_id): Path<Uuid>,
 ) -> AppResult<impl axum::response::IntoResponse> {
-    state.service.delete_file(file_id, query.file_name).await?;
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    state
+        .service
+        .delete_file(file_id, user_id, claims.role)
+        .await?;
 
     Ok(no_content())
 }
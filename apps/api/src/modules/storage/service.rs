@@ -1,28 +1,57 @@
 use aws_config::BehaviorVersion;
 use aws_sdk_s3::{
-    config::Region,
+    config::{Credentials, Region},
     presigning::PresigningConfig,
     primitives::ByteStream,
+    types::{CompletedMultipartUpload, CompletedPart},
     Client,
 };
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
 use std::time::Duration;
 use uuid::Uuid;
 
 use crate::config::StorageConfig;
+use crate::modules::auth::role_guard::{has_permission, Permission};
+use crate::modules::users::model::UserRole;
 use crate::utils::error::{AppError, AppResult};
 
-use super::model::{FileMetadata, PresignedUrlResponse, UploadResponse};
+use super::files::{storage_key, FileStore, NewFile};
+use super::model::{
+    FileMetadata, PresignedUploadResponse, PresignedUrlResponse, StoredFile, UploadResponse,
+};
+use super::retry::RetryPolicy;
+use super::thumbnail;
+
+/// Bytes buffered per multipart part; S3's minimum for every part but the last
+const PART_SIZE: usize = 5 * 1024 * 1024;
 
 pub struct StorageService {
     client: Client,
     bucket: String,
     max_file_size_bytes: u64,
+    retry: RetryPolicy,
+    files: FileStore,
+    presigned_url_expiry: Duration,
 }
 
 impl StorageService {
-    pub async fn new(config: StorageConfig) -> AppResult<Self> {
+    pub async fn new(db_pool: PgPool, config: StorageConfig) -> AppResult<Self> {
+        let retry = RetryPolicy::from_config(&config);
+        let files = FileStore::new(db_pool, config.dedupe_uploads);
+        let credentials = Credentials::new(
+            config.s3_access_key.clone(),
+            config.s3_secret_key.clone(),
+            None,
+            None,
+            "vibe-api",
+        );
+        // MinIO, LocalStack and friends don't serve bucket subdomains
+        let path_style = config.s3_endpoint.is_some();
+
         let mut aws_config_builder = aws_config::defaults(BehaviorVersion::latest())
-            .region(Region::new(config.s3_region.clone()));
+            .region(Region::new(config.s3_region.clone()))
+            .credentials_provider(credentials);
 
         // If custom endpoint is provided (for MinIO, LocalStack, etc.)
         if let Some(endpoint) = config.s3_endpoint {
@@ -30,7 +59,10 @@ impl StorageService {
         }
 
         let aws_config = aws_config_builder.load().await;
-        let client = Client::new(&aws_config);
+        let s3_config = aws_sdk_s3::config::Builder::from(&aws_config)
+            .force_path_style(path_style)
+            .build();
+        let client = Client::from_conf(s3_config);
 
         let max_file_size_bytes = config.max_file_size_mb * 1024 * 1024;
 
@@ -38,147 +70,633 @@ impl StorageService {
             client,
             bucket: config.s3_bucket,
             max_file_size_bytes,
+            retry,
+            files,
+            presigned_url_expiry: Duration::from_secs(config.presigned_url_expiry_secs),
         })
     }
 
-    /// Upload a file to S3
-    pub async fn upload_file(
-        &self,
-        file_name: String,
-        content_type: String,
-        data: Vec<u8>,
-    ) -> AppResult<UploadResponse> {
-        // Validate file size
-        if data.len() as u64 > self.max_file_size_bytes {
-            return Err(AppError::FileTooLarge);
-        }
-
-        let file_id = Uuid::new_v4().to_string();
-        let key = format!("uploads/{}/{}", file_id, file_name);
+    pub fn max_file_size_bytes(&self) -> u64 {
+        self.max_file_size_bytes
+    }
 
-        // Upload to S3
+    /// Whether the bucket answers at all; a single attempt, for readiness probes
+    pub async fn check_bucket(&self) -> AppResult<()> {
         self.client
-            .put_object()
+            .head_bucket()
             .bucket(&self.bucket)
-            .key(&key)
-            .body(ByteStream::from(data.clone()))
-            .content_type(&content_type)
             .send()
             .await
-            .map_err(|e| AppError::ExternalService(format!("S3 upload error: {}", e)))?;
+            .map_err(|e| AppError::StorageUnavailable(format!("S3 bucket check failed: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Start streaming an upload to S3; feed it with `PendingUpload::write`
+    /// and store it with `PendingUpload::finish`
+    pub fn begin_upload(
+        &self,
+        owner_id: Uuid,
+        file_name: String,
+        content_type: String,
+    ) -> PendingUpload<'_> {
+        let file_id = Uuid::new_v4();
+
+        PendingUpload {
+            service: self,
+            owner_id,
+            file_id,
+            key: storage_key(file_id, &file_name),
+            file_name,
+            content_type,
+            hasher: Sha256::new(),
+            size: 0,
+            buffer: Vec::new(),
+            multipart: None,
+        }
+    }
 
+    fn upload_response(&self, file: StoredFile, deduplicated: bool) -> UploadResponse {
         // Generate public URL (adjust based on your S3 configuration)
         let url = format!(
             "https://{}.s3.amazonaws.com/{}",
-            self.bucket, key
+            self.bucket, file.storage_key
         );
 
-        Ok(UploadResponse {
-            file_id,
-            file_name,
-            file_size: data.len() as u64,
-            content_type,
+        UploadResponse {
+            file_id: file.id.to_string(),
+            file_name: file.file_name,
+            file_size: file.file_size as u64,
+            content_type: file.content_type,
             url,
-        })
+            deduplicated,
+            has_thumbnail: file.thumbnail_key.is_some(),
+        }
     }
 
-    /// Generate a presigned URL for direct upload
-    pub async fn generate_presigned_upload_url(
+    async fn put_object(&self, key: &str, body: &[u8], content_type: &str) -> AppResult<()> {
+        self.retry
+            .run(
+                "upload",
+                || {
+                    self.client
+                        .put_object()
+                        .bucket(&self.bucket)
+                        .key(key)
+                        .body(ByteStream::from(body.to_vec()))
+                        .content_type(content_type)
+                        .send()
+                },
+                |e| AppError::ExternalService(format!("S3 upload error: {}", e)),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_object(&self, key: &str) -> AppResult<Vec<u8>> {
+        let output = self
+            .retry
+            .run(
+                "download",
+                || {
+                    self.client
+                        .get_object()
+                        .bucket(&self.bucket)
+                        .key(key)
+                        .send()
+                },
+                |e| AppError::ExternalService(format!("S3 download error: {}", e)),
+            )
+            .await?;
+
+        let body = output
+            .body
+            .collect()
+            .await
+            .map_err(|e| AppError::ExternalService(format!("S3 download error: {}", e)))?;
+
+        Ok(body.into_bytes().to_vec())
+    }
+
+    /// Generate and store a thumbnail for an image upload, from `body` when
+    /// the caller still holds it and from the bucket otherwise; best-effort,
+    /// so a failure is logged and leaves the file without one
+    async fn attach_thumbnail(&self, file: StoredFile, body: Option<Vec<u8>>) -> StoredFile {
+        if !thumbnail::supports(&file.content_type) {
+            return file;
+        }
+
+        match self.store_thumbnail(&file, body).await {
+            Ok(file) => file,
+            Err(e) => {
+                tracing::warn!(file_id = %file.id, "Failed to generate thumbnail: {}", e);
+                file
+            }
+        }
+    }
+
+    async fn store_thumbnail(
         &self,
+        file: &StoredFile,
+        body: Option<Vec<u8>>,
+    ) -> AppResult<StoredFile> {
+        let body = match body {
+            Some(body) => body,
+            None => self.get_object(&file.storage_key).await?,
+        };
+
+        let png = tokio::task::spawn_blocking(move || thumbnail::render(&body))
+            .await
+            .map_err(|e| AppError::InternalServer(format!("Thumbnail task failed: {}", e)))?
+            .map_err(|e| AppError::BadRequest(format!("Unreadable image: {}", e)))?;
+
+        let key = thumbnail::key(file.id);
+        self.put_object(&key, &png, thumbnail::CONTENT_TYPE).await?;
+        self.files.set_thumbnail(file.id, &key).await
+    }
+
+    async fn delete_object(&self, key: &str) -> AppResult<()> {
+        self.retry
+            .run(
+                "delete",
+                || {
+                    self.client
+                        .delete_object()
+                        .bucket(&self.bucket)
+                        .key(key)
+                        .send()
+                },
+                |e| AppError::ExternalService(format!("S3 delete error: {}", e)),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// The file `file_id`, provided `user_id` uploaded it
+    pub async fn owned_file(&self, file_id: Uuid, user_id: Uuid) -> AppResult<StoredFile> {
+        let file = self.find_file(file_id).await?;
+
+        if file.owner_id != user_id {
+            return Err(no_access());
+        }
+
+        Ok(file)
+    }
+
+    /// The file `file_id`, provided `user_id` uploaded it or `role` may
+    /// manage every user's files
+    pub async fn accessible_file(
+        &self,
+        file_id: Uuid,
+        user_id: Uuid,
+        role: UserRole,
+    ) -> AppResult<StoredFile> {
+        let file = self.find_file(file_id).await?;
+
+        if file.owner_id != user_id && !has_permission(role, Permission::StorageManageAny) {
+            return Err(no_access());
+        }
+
+        Ok(file)
+    }
+
+    async fn find_file(&self, file_id: Uuid) -> AppResult<StoredFile> {
+        self.files
+            .find(file_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("File not found".to_string()))
+    }
+
+    /// Record a pending file and presign a `PUT` of its object, so the client
+    /// uploads straight to S3; it confirms the upload afterwards
+    pub async fn presign_upload(
+        &self,
+        owner_id: Uuid,
         file_name: String,
         content_type: String,
-        expires_in_seconds: u64,
-    ) -> AppResult<PresignedUrlResponse> {
-        let file_id = Uuid::new_v4().to_string();
-        let key = format!("uploads/{}/{}", file_id, file_name);
+    ) -> AppResult<PresignedUploadResponse> {
+        if file_name.trim().is_empty() {
+            return Err(AppError::InvalidField {
+                field: "filename".to_string(),
+                rule: "required",
+                message: "filename must not be empty".to_string(),
+            });
+        }
 
-        let presigning_config = PresigningConfig::expires_in(Duration::from_secs(expires_in_seconds))
-            .map_err(|e| AppError::InternalServer(format!("Presigning config error: {}", e)))?;
+        let file_id = Uuid::new_v4();
+        let key = storage_key(file_id, &file_name);
+        let presigning_config = self.presigning_config()?;
 
         let presigned_request = self
-            .client
-            .put_object()
-            .bucket(&self.bucket)
-            .key(&key)
-            .content_type(&content_type)
-            .presigned(presigning_config)
-            .await
-            .map_err(|e| AppError::ExternalService(format!("Presigning error: {}", e)))?;
+            .retry
+            .run(
+                "presign upload",
+                || {
+                    self.client
+                        .put_object()
+                        .bucket(&self.bucket)
+                        .key(&key)
+                        .content_type(&content_type)
+                        .presigned(presigning_config.clone())
+                },
+                |e| AppError::ExternalService(format!("Presigning error: {}", e)),
+            )
+            .await?;
 
-        Ok(PresignedUrlResponse {
+        self.files
+            .record_pending(file_id, owner_id, &file_name, &content_type, &key)
+            .await?;
+
+        Ok(PresignedUploadResponse {
+            file_id: file_id.to_string(),
             url: presigned_request.uri().to_string(),
-            expires_in_seconds,
+            expires_in_seconds: self.presigned_url_expiry.as_secs(),
         })
     }
 
-    /// Generate a presigned URL for download
-    pub async fn generate_presigned_download_url(
+    /// Confirm a presigned upload once its object is in the bucket, taking
+    /// the size from S3
+    pub async fn confirm_upload(&self, file_id: Uuid, user_id: Uuid) -> AppResult<UploadResponse> {
+        let file = self.owned_file(file_id, user_id).await?;
+        if !file.pending {
+            return Ok(self.upload_response(file, false));
+        }
+
+        let head_object = self
+            .retry
+            .run(
+                "head",
+                || {
+                    self.client
+                        .head_object()
+                        .bucket(&self.bucket)
+                        .key(&file.storage_key)
+                        .send()
+                },
+                |_| AppError::Conflict("The file has not been uploaded yet".to_string()),
+            )
+            .await?;
+
+        let file_size = head_object.content_length().unwrap_or(0).max(0) as u64;
+        if file_size > self.max_file_size_bytes {
+            return Err(AppError::FileTooLarge);
+        }
+
+        let file = self.files.confirm(file_id, file_size).await?;
+        let file = self.attach_thumbnail(file, None).await;
+
+        Ok(self.upload_response(file, false))
+    }
+
+    /// Presign a `GET` of a confirmed file the caller owns
+    pub async fn presign_download(
         &self,
-        file_id: String,
-        file_name: String,
-        expires_in_seconds: u64,
+        file_id: Uuid,
+        user_id: Uuid,
     ) -> AppResult<PresignedUrlResponse> {
-        let key = format!("uploads/{}/{}", file_id, file_name);
+        let file = self.owned_file(file_id, user_id).await?;
+        if file.pending {
+            return Err(AppError::Conflict(
+                "The file upload has not been confirmed".to_string(),
+            ));
+        }
+
+        self.presign_get(&file.storage_key).await
+    }
 
-        let presigning_config = PresigningConfig::expires_in(Duration::from_secs(expires_in_seconds))
-            .map_err(|e| AppError::InternalServer(format!("Presigning config error: {}", e)))?;
+    /// Presign a `GET` of a file's thumbnail; 404 for files without one
+    pub async fn presign_thumbnail(
+        &self,
+        file_id: Uuid,
+        user_id: Uuid,
+        role: UserRole,
+    ) -> AppResult<PresignedUrlResponse> {
+        let file = self.accessible_file(file_id, user_id, role).await?;
+        let key = file
+            .thumbnail_key
+            .ok_or_else(|| AppError::NotFound("File has no thumbnail".to_string()))?;
+
+        self.presign_get(&key).await
+    }
+
+    async fn presign_get(&self, key: &str) -> AppResult<PresignedUrlResponse> {
+        let presigning_config = self.presigning_config()?;
 
         let presigned_request = self
-            .client
-            .get_object()
-            .bucket(&self.bucket)
-            .key(&key)
-            .presigned(presigning_config)
-            .await
-            .map_err(|e| AppError::ExternalService(format!("Presigning error: {}", e)))?;
+            .retry
+            .run(
+                "presign download",
+                || {
+                    self.client
+                        .get_object()
+                        .bucket(&self.bucket)
+                        .key(key)
+                        .presigned(presigning_config.clone())
+                },
+                |e| AppError::ExternalService(format!("Presigning error: {}", e)),
+            )
+            .await?;
 
         Ok(PresignedUrlResponse {
             url: presigned_request.uri().to_string(),
-            expires_in_seconds,
+            expires_in_seconds: self.presigned_url_expiry.as_secs(),
         })
     }
 
-    /// Delete a file from S3
-    pub async fn delete_file(&self, file_id: String, file_name: String) -> AppResult<()> {
-        let key = format!("uploads/{}/{}", file_id, file_name);
+    fn presigning_config(&self) -> AppResult<PresigningConfig> {
+        PresigningConfig::expires_in(self.presigned_url_expiry)
+            .map_err(|e| AppError::InternalServer(format!("Presigning config error: {}", e)))
+    }
 
-        self.client
-            .delete_object()
-            .bucket(&self.bucket)
-            .key(&key)
-            .send()
-            .await
-            .map_err(|e| AppError::ExternalService(format!("S3 delete error: {}", e)))?;
+    /// Delete a file's object and row; the row stays if the S3 delete fails
+    pub async fn delete_file(&self, file_id: Uuid, user_id: Uuid, role: UserRole) -> AppResult<()> {
+        let file = self.accessible_file(file_id, user_id, role).await?;
 
-        Ok(())
+        self.files
+            .delete(file.id, || async {
+                self.delete_object(&file.storage_key).await?;
+                // A leftover thumbnail is harmless, so it doesn't block the delete
+                if let Some(thumbnail_key) = &file.thumbnail_key {
+                    if let Err(e) = self.delete_object(thumbnail_key).await {
+                        tracing::warn!(key = %thumbnail_key, "Failed to delete thumbnail: {}", e);
+                    }
+                }
+                Ok::<_, AppError>(())
+            })
+            .await
     }
 
-    /// Get file metadata
+    /// Metadata of a file, as recorded at upload
     pub async fn get_file_metadata(
         &self,
-        file_id: String,
-        file_name: String,
+        file_id: Uuid,
+        user_id: Uuid,
+        role: UserRole,
     ) -> AppResult<FileMetadata> {
-        let key = format!("uploads/{}/{}", file_id, file_name);
+        let file = self.accessible_file(file_id, user_id, role).await?;
 
-        let head_object = self
+        Ok(FileMetadata {
+            file_id: file.id.to_string(),
+            file_name: file.file_name,
+            file_size: file.file_size as u64,
+            content_type: file.content_type,
+            uploaded_at: file.created_at.to_rfc3339(),
+        })
+    }
+}
+
+/// Lets the soft-delete purge remove purged users' objects
+#[cfg(feature = "jobs")]
+#[async_trait::async_trait]
+impl crate::modules::jobs::tasks::ObjectStore for StorageService {
+    async fn delete_object(&self, key: &str) -> AppResult<()> {
+        StorageService::delete_object(self, key).await
+    }
+}
+
+fn no_access() -> AppError {
+    AppError::Authorization("You do not have access to this file".to_string())
+}
+
+/// Multipart upload in progress, begun once a body outgrows one part
+struct MultipartUpload {
+    upload_id: String,
+    parts: Vec<CompletedPart>,
+}
+
+/// An upload being streamed to S3; at most one part is held in memory
+///
+/// A body that fits in one part is sent with a single `PutObject`, anything
+/// larger as a multipart upload. Callers that give up part way must `abort`
+/// so S3 drops the parts sent so far.
+pub struct PendingUpload<'a> {
+    service: &'a StorageService,
+    owner_id: Uuid,
+    file_id: Uuid,
+    file_name: String,
+    content_type: String,
+    key: String,
+    hasher: Sha256,
+    size: u64,
+    buffer: Vec<u8>,
+    multipart: Option<MultipartUpload>,
+}
+
+impl PendingUpload<'_> {
+    /// Append a chunk of the body; fails with `FileTooLarge` as soon as the
+    /// body passes the configured maximum
+    pub async fn write(&mut self, chunk: &[u8]) -> AppResult<()> {
+        self.size += chunk.len() as u64;
+        if self.size > self.service.max_file_size_bytes {
+            self.abort().await;
+            return Err(AppError::FileTooLarge);
+        }
+
+        self.hasher.update(chunk);
+        self.buffer.extend_from_slice(chunk);
+
+        if self.buffer.len() >= PART_SIZE {
+            if let Err(e) = self.send_part().await {
+                self.abort().await;
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Complete the upload and record it, reusing the owner's existing copy
+    /// of identical content when dedupe is on
+    pub async fn finish(mut self) -> AppResult<UploadResponse> {
+        let result = self.complete().await;
+        if result.is_err() {
+            self.abort().await;
+        }
+        result
+    }
+
+    /// Drop whatever was sent so far; failures are logged, as S3's lifecycle
+    /// rules clean up abandoned multipart uploads eventually
+    pub async fn abort(&mut self) {
+        let Some(multipart) = self.multipart.take() else {
+            return;
+        };
+
+        let service = self.service;
+        let result = service
             .client
-            .head_object()
-            .bucket(&self.bucket)
-            .key(&key)
+            .abort_multipart_upload()
+            .bucket(&service.bucket)
+            .key(&self.key)
+            .upload_id(&multipart.upload_id)
             .send()
-            .await
-            .map_err(|e| AppError::NotFound(format!("File not found: {}", e)))?;
+            .await;
 
-        Ok(FileMetadata {
-            file_id,
-            file_name,
-            file_size: head_object.content_length().unwrap_or(0) as u64,
-            content_type: head_object.content_type().unwrap_or("").to_string(),
-            uploaded_at: head_object
-                .last_modified()
-                .map(|t| t.to_string())
-                .unwrap_or_default(),
-        })
+        if let Err(e) = result {
+            tracing::warn!(key = %self.key, "Failed to abort multipart upload: {}", e);
+        }
+    }
+
+    async fn complete(&mut self) -> AppResult<UploadResponse> {
+        let service = self.service;
+        let hash = hex::encode(self.hasher.clone().finalize());
+        let single_part = self.multipart.is_none();
+
+        if single_part {
+            // The whole body is still in memory, so a duplicate is never sent
+            if let Some(existing) = self.existing_copy(&hash).await? {
+                return Ok(service.upload_response(existing, true));
+            }
+
+            service
+                .put_object(&self.key, &self.buffer, &self.content_type)
+                .await?;
+        } else {
+            if !self.buffer.is_empty() {
+                self.send_part().await?;
+            }
+            self.complete_multipart().await?;
+
+            // Content is only known once it has streamed through, so a
+            // duplicate is uploaded and then removed
+            if let Some(existing) = self.existing_copy(&hash).await? {
+                if let Err(e) = service.delete_object(&self.key).await {
+                    tracing::warn!(key = %self.key, "Failed to remove duplicate upload: {}", e);
+                }
+                return Ok(service.upload_response(existing, true));
+            }
+        }
+
+        let file = service
+            .files
+            .record(NewFile {
+                id: self.file_id,
+                owner_id: self.owner_id,
+                file_name: &self.file_name,
+                content_type: &self.content_type,
+                file_size: self.size,
+                content_hash: &hash,
+                storage_key: &self.key,
+            })
+            .await?;
+
+        // A single-part body is at hand; a larger one is read back from S3
+        let body = single_part.then(|| std::mem::take(&mut self.buffer));
+        let file = service.attach_thumbnail(file, body).await;
+
+        Ok(service.upload_response(file, false))
+    }
+
+    async fn existing_copy(&self, hash: &str) -> AppResult<Option<StoredFile>> {
+        if !self.service.files.dedupe() {
+            return Ok(None);
+        }
+        self.service.files.find_by_hash(self.owner_id, hash).await
+    }
+
+    /// Send the buffered bytes as the next part, starting the multipart
+    /// upload on the first one
+    async fn send_part(&mut self) -> AppResult<()> {
+        let service = self.service;
+        let (key, mime) = (&self.key, &self.content_type);
+
+        if self.multipart.is_none() {
+            let output = service
+                .retry
+                .run(
+                    "start multipart upload",
+                    || {
+                        service
+                            .client
+                            .create_multipart_upload()
+                            .bucket(&service.bucket)
+                            .key(key)
+                            .content_type(mime)
+                            .send()
+                    },
+                    |e| AppError::ExternalService(format!("S3 upload error: {}", e)),
+                )
+                .await?;
+            let upload_id = output
+                .upload_id()
+                .ok_or_else(|| AppError::ExternalService("S3 returned no upload id".to_string()))?
+                .to_string();
+            self.multipart = Some(MultipartUpload {
+                upload_id,
+                parts: Vec::new(),
+            });
+        }
+
+        let Some(multipart) = self.multipart.as_mut() else {
+            return Ok(());
+        };
+        let part = std::mem::take(&mut self.buffer);
+        let part_number = multipart.parts.len() as i32 + 1;
+        let upload_id = &multipart.upload_id;
+
+        let output = service
+            .retry
+            .run(
+                "upload part",
+                || {
+                    service
+                        .client
+                        .upload_part()
+                        .bucket(&service.bucket)
+                        .key(key)
+                        .upload_id(upload_id)
+                        .part_number(part_number)
+                        .body(ByteStream::from(part.clone()))
+                        .send()
+                },
+                |e| AppError::ExternalService(format!("S3 upload error: {}", e)),
+            )
+            .await?;
+
+        multipart.parts.push(
+            CompletedPart::builder()
+                .set_e_tag(output.e_tag().map(str::to_string))
+                .part_number(part_number)
+                .build(),
+        );
+
+        Ok(())
+    }
+
+    async fn complete_multipart(&mut self) -> AppResult<()> {
+        let service = self.service;
+        let Some(multipart) = self.multipart.take() else {
+            return Ok(());
+        };
+        let (key, upload_id) = (&self.key, &multipart.upload_id);
+        let parts = CompletedMultipartUpload::builder()
+            .set_parts(Some(multipart.parts.clone()))
+            .build();
+
+        let result = service
+            .retry
+            .run(
+                "complete multipart upload",
+                || {
+                    service
+                        .client
+                        .complete_multipart_upload()
+                        .bucket(&service.bucket)
+                        .key(key)
+                        .upload_id(upload_id)
+                        .multipart_upload(parts.clone())
+                        .send()
+                },
+                |e| AppError::ExternalService(format!("S3 upload error: {}", e)),
+            )
+            .await;
+
+        if result.is_err() {
+            // Put it back so `finish` aborts it
+            self.multipart = Some(multipart);
+        }
+        result.map(|_| ())
     }
 }
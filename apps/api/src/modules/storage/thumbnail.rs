@@ -0,0 +1,38 @@
+use image::{ImageFormat, ImageResult};
+use std::io::Cursor;
+use uuid::Uuid;
+
+/// Longest edge of a thumbnail, in pixels
+pub const MAX_EDGE: u32 = 256;
+
+/// Thumbnails are always PNG, whatever the source format
+pub const CONTENT_TYPE: &str = "image/png";
+
+/// Upload content types a thumbnail is generated for
+const SOURCE_CONTENT_TYPES: &[&str] = &["image/png", "image/jpeg", "image/webp"];
+
+pub fn supports(content_type: &str) -> bool {
+    SOURCE_CONTENT_TYPES.contains(&content_type)
+}
+
+/// Bucket key of a file's thumbnail
+pub fn key(file_id: Uuid) -> String {
+    format!("thumbnails/{}.png", file_id)
+}
+
+/// Decode an image and scale it to fit `MAX_EDGE`, keeping its aspect
+/// ratio; images already that small are only re-encoded
+///
+/// CPU-bound, so run it off the async runtime.
+pub fn render(data: &[u8]) -> ImageResult<Vec<u8>> {
+    let image = image::load_from_memory(data)?;
+    let thumbnail = if image.width() > MAX_EDGE || image.height() > MAX_EDGE {
+        image.thumbnail(MAX_EDGE, MAX_EDGE)
+    } else {
+        image
+    };
+
+    let mut png = Cursor::new(Vec::new());
+    thumbnail.write_to(&mut png, ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
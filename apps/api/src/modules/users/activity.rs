@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use sqlx::PgPool;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+use crate::database::query_count::tracked;
+use crate::modules::auth::jwt::Claims;
+use crate::utils::error::AppResult;
+
+/// Default minimum interval between `last_active_at` writes for one user
+pub const ACTIVITY_WRITE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Records `last_active_at` for authenticated users, writing at most once per interval
+#[derive(Clone)]
+pub struct ActivityTracker {
+    db_pool: PgPool,
+    interval: Duration,
+    last_written: Arc<Mutex<HashMap<Uuid, Instant>>>,
+}
+
+impl ActivityTracker {
+    pub fn new(db_pool: PgPool, interval: Duration) -> Self {
+        Self {
+            db_pool,
+            interval,
+            last_written: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Mark a user as active, returning whether the timestamp was written
+    pub async fn touch(&self, user_id: &Uuid) -> AppResult<bool> {
+        {
+            let mut last_written = self.last_written.lock().unwrap();
+            let now = Instant::now();
+
+            if let Some(at) = last_written.get(user_id) {
+                if now.duration_since(*at) < self.interval {
+                    return Ok(false);
+                }
+            }
+
+            // Drop stale entries so the map only holds recently active users
+            last_written.retain(|_, at| now.duration_since(*at) < self.interval);
+            last_written.insert(*user_id, now);
+        }
+
+        tracked(
+            sqlx::query("UPDATE users SET last_active_at = NOW() WHERE id = $1")
+                .bind(user_id)
+                .execute(&self.db_pool),
+        )
+        .await?;
+
+        Ok(true)
+    }
+}
+
+/// Middleware recording activity for requests that passed `auth_middleware`
+pub async fn track_activity(
+    State(tracker): State<ActivityTracker>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let user_id = request
+        .extensions()
+        .get::<Claims>()
+        .and_then(|claims| Uuid::parse_str(&claims.sub).ok());
+
+    if let Some(user_id) = user_id {
+        if let Err(e) = tracker.touch(&user_id).await {
+            tracing::warn!("Failed to record activity for {}: {}", user_id, e);
+        }
+    }
+
+    next.run(request).await
+}
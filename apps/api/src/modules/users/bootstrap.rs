@@ -0,0 +1,65 @@
+use sqlx::{Acquire, Postgres};
+use uuid::Uuid;
+
+use crate::config::BootstrapAdminConfig;
+use crate::modules::auth::hash::hash_password;
+use crate::utils::error::{AppError, AppResult};
+use crate::utils::validation::validate_password_strength;
+
+/// Advisory lock key serializing bootstrap across replicas booting together
+const BOOTSTRAP_LOCK_KEY: i64 = 0x7669_6265_6164_6d6e;
+
+/// Create the configured admin if the database has no admin yet
+///
+/// Returns `true` when an admin was created and `false` when one already
+/// existed, so running this on every boot is safe.
+pub async fn ensure_bootstrap_admin<'a, A>(conn: A, admin: &BootstrapAdminConfig) -> AppResult<bool>
+where
+    A: Acquire<'a, Database = Postgres>,
+{
+    validate_password_strength(&admin.password)
+        .map_err(|_| AppError::Configuration("BOOTSTRAP_ADMIN_PASSWORD is too weak".to_string()))?;
+
+    let mut tx = conn.begin().await?;
+
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(BOOTSTRAP_LOCK_KEY)
+        .execute(&mut *tx)
+        .await?;
+
+    let admin_exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM users WHERE role = 'admin' AND deleted_at IS NULL)",
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if admin_exists {
+        return Ok(false);
+    }
+
+    let email_taken: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM users WHERE email = $1 AND deleted_at IS NULL)",
+    )
+    .bind(&admin.email)
+    .fetch_one(&mut *tx)
+    .await?;
+    if email_taken {
+        return Err(AppError::Conflict(format!(
+            "{} is already registered as a non-admin user",
+            admin.email
+        )));
+    }
+
+    sqlx::query(
+        "INSERT INTO users (id, email, password_hash, name, role) VALUES ($1, $2, $3, $4, 'admin')",
+    )
+    .bind(Uuid::new_v4())
+    .bind(&admin.email)
+    .bind(hash_password(&admin.password)?)
+    .bind("Administrator")
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(true)
+}
@@ -1,5 +1,8 @@
+pub mod activity;
+pub mod bootstrap;
 pub mod model;
-pub mod service;
 pub mod routes;
+pub mod service;
+pub mod terms;
 
 pub use routes::routes;
@@ -3,10 +3,26 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use utoipa::ToSchema;
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
+
+use crate::utils::error::{AppError, AppResult};
+
+/// Profile metadata keys users may set on themselves
+pub const ALLOWED_METADATA_KEYS: &[&str] = &[
+    "avatar_url",
+    "bio",
+    "company",
+    "locale",
+    "location",
+    "timezone",
+    "website",
+];
+
+/// Maximum serialized size of a user's metadata object
+pub const MAX_METADATA_BYTES: usize = 4096;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, sqlx::Type, ToSchema)]
-#[sqlx(type_name = "text")]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum UserRole {
     User,
@@ -14,6 +30,31 @@ pub enum UserRole {
     Moderator,
 }
 
+impl UserRole {
+    pub const ALL: [UserRole; 3] = [UserRole::User, UserRole::Admin, UserRole::Moderator];
+
+    /// Parse a role from request input, case-insensitively; unknown roles
+    /// fail with `INVALID_ROLE` listing the accepted values
+    pub fn parse(role: &str) -> AppResult<Self> {
+        let normalized = role.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|candidate| candidate.to_string() == normalized)
+            .ok_or_else(|| AppError::InvalidRole {
+                role: role.to_string(),
+                valid_roles: Self::ALL.iter().map(ToString::to_string).collect(),
+            })
+    }
+}
+
+impl std::str::FromStr for UserRole {
+    type Err = AppError;
+
+    fn from_str(role: &str) -> AppResult<Self> {
+        Self::parse(role)
+    }
+}
+
 impl Default for UserRole {
     fn default() -> Self {
         Self::User
@@ -30,17 +71,127 @@ impl std::fmt::Display for UserRole {
     }
 }
 
+/// Whether an account may sign in; suspension is reversible, unlike deletion
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, sqlx::Type, ToSchema,
+)]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum UserStatus {
+    #[default]
+    Active,
+    Suspended,
+}
+
+/// Columns the admin user list can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UserSortField {
+    Email,
+    Name,
+    #[default]
+    CreatedAt,
+    LastLogin,
+}
+
+impl UserSortField {
+    pub const ALL: [UserSortField; 4] = [
+        UserSortField::Email,
+        UserSortField::Name,
+        UserSortField::CreatedAt,
+        UserSortField::LastLogin,
+    ];
+
+    /// Parse a `sort_by` query value; anything outside `ALL` is rejected
+    pub fn parse(value: &str) -> AppResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|field| field.column() == value)
+            .ok_or_else(|| AppError::InvalidField {
+                field: "sort_by".to_string(),
+                rule: "one_of",
+                message: format!(
+                    "sort_by must be one of: {}",
+                    Self::ALL.map(Self::column).join(", ")
+                ),
+            })
+    }
+
+    /// The column name, the only form of `sort_by` that reaches SQL
+    pub fn column(self) -> &'static str {
+        match self {
+            UserSortField::Email => "email",
+            UserSortField::Name => "name",
+            UserSortField::CreatedAt => "created_at",
+            UserSortField::LastLogin => "last_login",
+        }
+    }
+
+    /// Newest first for timestamps, alphabetical for text
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            UserSortField::Email | UserSortField::Name => SortOrder::Asc,
+            UserSortField::CreatedAt | UserSortField::LastLogin => SortOrder::Desc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    /// Parse an `order` query value, case-insensitively
+    pub fn parse(value: &str) -> AppResult<Self> {
+        match value.to_lowercase().as_str() {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(AppError::InvalidField {
+                field: "order".to_string(),
+                rule: "one_of",
+                message: "order must be one of: asc, desc".to_string(),
+            }),
+        }
+    }
+
+    pub fn keyword(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// Filters, page and ordering of the admin user list
+#[derive(Debug, Clone)]
+pub struct UserListOptions {
+    /// Case-insensitive substring of the email or name; `None` matches all
+    pub search: Option<String>,
+    pub role: Option<UserRole>,
+    pub offset: u64,
+    pub limit: u32,
+    pub sort_by: UserSortField,
+    pub order: SortOrder,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
     pub id: Uuid,
     pub email: String,
+    /// `None` for accounts that only sign in through an OAuth provider
     #[serde(skip_serializing)]
-    pub password_hash: String,
+    pub password_hash: Option<String>,
     pub name: String,
     pub role: UserRole,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_login: Option<DateTime<Utc>>,
+    pub last_active_at: Option<DateTime<Utc>>,
+    pub metadata: serde_json::Value,
+    pub terms_version_accepted: Option<String>,
+    pub email_verified: bool,
+    pub status: UserStatus,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -52,6 +203,12 @@ pub struct UserResponse {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_login: Option<DateTime<Utc>>,
+    pub last_active_at: Option<DateTime<Utc>>,
+    #[schema(value_type = Object)]
+    pub metadata: serde_json::Value,
+    pub terms_version_accepted: Option<String>,
+    pub email_verified: bool,
+    pub status: UserStatus,
 }
 
 impl From<User> for UserResponse {
@@ -64,21 +221,153 @@ impl From<User> for UserResponse {
             created_at: user.created_at,
             updated_at: user.updated_at,
             last_login: user.last_login,
+            last_active_at: user.last_active_at,
+            metadata: user.metadata,
+            terms_version_accepted: user.terms_version_accepted,
+            email_verified: user.email_verified,
+            status: user.status,
         }
     }
 }
 
-#[derive(Debug, Deserialize, Validate)]
+/// An OAuth provider account linked to a user
+#[derive(Debug, Clone, FromRow)]
+pub struct OAuthConnection {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub provider: String,
+    pub provider_user_id: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConnectionResponse {
+    pub provider: String,
+    pub email: Option<String>,
+    pub connected_at: DateTime<Utc>,
+}
+
+impl From<OAuthConnection> for ConnectionResponse {
+    fn from(connection: OAuthConnection) -> Self {
+        Self {
+            provider: connection.provider,
+            email: connection.email,
+            connected_at: connection.created_at,
+        }
+    }
+}
+
+/// A signed-in device: one refresh token family, from login until it is
+/// revoked or expires
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    /// When the session last refreshed its tokens
+    pub last_used_at: DateTime<Utc>,
+    /// `User-Agent` sent at login
+    pub user_agent: Option<String>,
+    /// Coarse client network at login, e.g. `203.0.113.0/24`
+    pub ip_network: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateUserRequest {
-    #[validate(length(min = 2, max = 100))]
+    /// Checked against the configured `NamePolicy`
     pub name: Option<String>,
+
+    /// Replaces the stored metadata object
+    #[validate(custom(function = "validate_metadata"))]
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Reject metadata with keys outside the allowlist or exceeding the size cap
+fn validate_metadata(
+    metadata: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(), ValidationError> {
+    if let Some(key) = metadata
+        .keys()
+        .find(|key| !ALLOWED_METADATA_KEYS.contains(&key.as_str()))
+    {
+        let mut error = ValidationError::new("metadata_key_not_allowed");
+        error.message = Some(format!("Metadata key '{}' is not allowed", key).into());
+        return Err(error);
+    }
+
+    let size = serde_json::to_vec(metadata)
+        .map(|bytes| bytes.len())
+        .unwrap_or(usize::MAX);
+    if size > MAX_METADATA_BYTES {
+        let mut error = ValidationError::new("metadata_too_large");
+        error.message =
+            Some(format!("Metadata must not exceed {} bytes", MAX_METADATA_BYTES).into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct AcceptTermsRequest {
+    #[validate(length(min = 1, max = 50))]
+    pub version: String,
+}
+
+/// Admin request to change another user's role
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateRoleRequest {
+    pub role: String,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct ChangePasswordRequest {
-    #[validate(length(min = 1))]
+    #[validate(length(min = 1, message = "Current password is required"))]
     pub current_password: String,
 
-    #[validate(length(min = 8))]
+    #[validate(length(min = 8, message = "Password must be at least 8 characters"))]
     pub new_password: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn update_with_metadata(metadata: serde_json::Value) -> UpdateUserRequest {
+        UpdateUserRequest {
+            name: None,
+            metadata: metadata.as_object().cloned(),
+        }
+    }
+
+    #[test]
+    fn test_metadata_with_allowed_keys_is_valid() {
+        let request = update_with_metadata(json!({
+            "bio": "Rustacean",
+            "timezone": "Europe/Amsterdam"
+        }));
+
+        assert!(request.validate().is_ok());
+    }
+
+    #[test]
+    fn test_metadata_with_disallowed_key_is_rejected() {
+        let request = update_with_metadata(json!({ "role": "admin" }));
+
+        let errors = request.validate().unwrap_err();
+        assert!(errors.field_errors().contains_key("metadata"));
+    }
+
+    #[test]
+    fn test_oversized_metadata_is_rejected() {
+        let request = update_with_metadata(json!({ "bio": "x".repeat(MAX_METADATA_BYTES) }));
+
+        let errors = request.validate().unwrap_err();
+        assert_eq!(
+            errors.field_errors()["metadata"][0].code,
+            "metadata_too_large"
+        );
+    }
+}
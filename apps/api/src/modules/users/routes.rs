@@ -1,80 +1,237 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Path, State},
     middleware,
-    routing::{delete, get, patch, put},
+    response::IntoResponse,
+    routing::{delete, get, patch, post, put},
     Extension, Json, Router,
 };
 use serde::Deserialize;
 use sqlx::PgPool;
 use std::sync::Arc;
+use utoipa::{IntoParams, TupleUnit};
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::config::JwtConfig;
+use crate::events::EventBus;
+use crate::middleware::concurrency::{concurrency_limit_middleware, ConcurrencyLimit};
 use crate::modules::auth::{
     jwt::Claims,
-    middleware::auth_middleware,
-    role_guard::require_admin,
+    middleware::{auth_middleware, AuthMiddleware},
+    role_guard::{require_permission, Permission},
 };
 use crate::utils::{
-    error::{AppError, AppResult},
-    response::{no_content, ApiResponse, PaginatedResponse},
-    validation::validate_struct,
+    cursor::Cursor,
+    error::{AppError, AppResult, ErrorResponse},
+    extract::{QueryFields, StrictQuery},
+    response::{no_content, ApiResponse, CursorPaginatedResponse, PaginatedResponse},
+    validation::{field_errors, validate_struct, NamePolicy},
 };
 
-use super::model::{ChangePasswordRequest, UpdateUserRequest, UserResponse};
+use super::activity::{track_activity, ActivityTracker, ACTIVITY_WRITE_INTERVAL};
+use super::model::{
+    AcceptTermsRequest, ChangePasswordRequest, ConnectionResponse, SessionResponse, SortOrder,
+    UpdateRoleRequest, UpdateUserRequest, UserListOptions, UserResponse, UserRole, UserSortField,
+    UserStatus,
+};
 use super::service::UserService;
+use super::terms::{require_terms_accepted, TermsGate};
 
 #[derive(Clone)]
 struct UserState {
     service: Arc<UserService>,
     jwt_config: Arc<JwtConfig>,
+    terms: TermsGate,
+    names: NamePolicy,
 }
 
-#[derive(Deserialize)]
-struct PaginationQuery {
-    #[serde(default = "default_page")]
-    page: u32,
-    #[serde(default = "default_per_page")]
-    per_page: u32,
+/// Largest page `GET /users` returns
+pub const MAX_USER_LIST_LIMIT: u32 = 100;
+
+const DEFAULT_USER_LIST_LIMIT: u32 = 20;
+
+/// `limit`/`offset` or `page`/`per_page` (the offset form wins when both are
+/// given), plus optional `q` search and `role` filters. An `after` cursor
+/// replaces both offset forms.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ListUsersQuery {
+    /// `next_cursor` of the previous page
+    after: Option<String>,
+    page: Option<u32>,
+    per_page: Option<u32>,
+    /// Page size, default 20, at most 100
+    limit: Option<u32>,
+    offset: Option<u64>,
+    /// `email`, `name`, `created_at` or `last_login`
+    sort_by: Option<String>,
+    /// `asc` or `desc`
+    order: Option<String>,
+    /// Case-insensitive email or name substring
+    q: Option<String>,
+    role: Option<String>,
 }
 
-fn default_page() -> u32 {
-    1
+impl QueryFields for ListUsersQuery {
+    const FIELDS: &'static [&'static str] = &[
+        "after", "page", "per_page", "limit", "offset", "sort_by", "order", "q", "role",
+    ];
 }
 
-fn default_per_page() -> u32 {
-    20
+impl ListUsersQuery {
+    fn options(&self) -> AppResult<UserListOptions> {
+        let limit = self
+            .limit
+            .or(self.per_page)
+            .unwrap_or(DEFAULT_USER_LIST_LIMIT)
+            .clamp(1, MAX_USER_LIST_LIMIT);
+        let offset = self.offset.unwrap_or_else(|| {
+            u64::from(self.page.unwrap_or(1).saturating_sub(1)) * u64::from(limit)
+        });
+        let sort_by = self
+            .sort_by
+            .as_deref()
+            .map(UserSortField::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let order = self
+            .order
+            .as_deref()
+            .map(SortOrder::parse)
+            .transpose()?
+            .unwrap_or_else(|| sort_by.default_order());
+
+        let search = self
+            .q
+            .as_deref()
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .map(str::to_string);
+        let role = self.role.as_deref().map(UserRole::parse).transpose()?;
+
+        Ok(UserListOptions {
+            search,
+            role,
+            offset,
+            limit,
+            sort_by,
+            order,
+        })
+    }
 }
 
-pub fn routes(db_pool: PgPool) -> Router {
-    // Note: JWT config will be provided at runtime via layer
-    // For now, we create a placeholder that will be replaced in main.rs
-    let jwt_config = Arc::new(JwtConfig {
-        secret: String::new(),
-        access_token_expiry_hours: 24,
-        refresh_token_expiry_days: 30,
-        issuer: String::new(),
-    });
+/// User routes; `list_limit` bounds concurrent calls to the DB-heavy user listing.
+/// When `current_terms_version` is set, users must accept it before using their account.
+pub fn routes(
+    db_pool: PgPool,
+    jwt_config: JwtConfig,
+    list_limit: ConcurrencyLimit,
+    current_terms_version: Option<String>,
+    names: NamePolicy,
+    events: EventBus,
+) -> Router {
+    let auth = AuthMiddleware::new(jwt_config).with_status_check(db_pool.clone());
 
-    let service = Arc::new(UserService::new(db_pool));
-    let state = UserState { service, jwt_config: jwt_config.clone() };
+    let activity = ActivityTracker::new(db_pool.clone(), ACTIVITY_WRITE_INTERVAL);
+    let terms = TermsGate::new(db_pool.clone(), current_terms_version);
+    let service = Arc::new(UserService::new(db_pool).with_events(events));
+    let state = UserState {
+        service,
+        jwt_config: auth.jwt_config.clone(),
+        terms: terms.clone(),
+        names,
+    };
 
-    // Public/authenticated routes (any authenticated user)
-    let authenticated_routes = Router::new()
+    // Routes that stay reachable before the current terms are accepted
+    let ungated_routes = Router::new()
         .route("/users/me", get(get_current_user))
-        .route("/users/me", patch(update_current_user))
         .route("/users/me", delete(delete_current_user))
+        .route("/users/me/accept-terms", post(accept_terms));
+
+    // Routes that require the current terms to be accepted
+    let gated_routes = Router::new()
+        .route("/users/me", patch(update_current_user))
         .route("/users/me/password", put(change_password))
-        .layer(middleware::from_fn_with_state(jwt_config.clone(), auth_middleware));
+        .route("/users/me/connections", get(list_connections))
+        .route(
+            "/users/me/connections/{provider}",
+            delete(unlink_connection),
+        )
+        .route("/users/me/sessions", get(list_sessions))
+        .route("/users/me/sessions/{id}", delete(revoke_session))
+        .layer(middleware::from_fn_with_state(
+            terms.clone(),
+            require_terms_accepted,
+        ));
+
+    // Public/authenticated routes (any authenticated user)
+    let authenticated_routes = Router::new()
+        .merge(ungated_routes)
+        .merge(gated_routes)
+        .layer(middleware::from_fn_with_state(
+            activity.clone(),
+            track_activity,
+        ))
+        .layer(middleware::from_fn_with_state(
+            auth.clone(),
+            auth_middleware,
+        ));
 
-    // Admin-only routes
+    // User management routes, each guarded by its own permission
     let admin_routes = Router::new()
-        .route("/users", get(list_users))
-        .route("/users/:id", get(get_user_by_id))
-        .route("/users/:id", delete(delete_user_by_id))
-        .layer(middleware::from_fn(require_admin))
-        .layer(middleware::from_fn_with_state(jwt_config, auth_middleware));
+        .route(
+            "/users",
+            get(list_users)
+                .layer(middleware::from_fn_with_state(
+                    list_limit,
+                    concurrency_limit_middleware,
+                ))
+                .layer(middleware::from_fn_with_state(
+                    Permission::UsersList,
+                    require_permission,
+                )),
+        )
+        .route(
+            "/users/{id}",
+            get(get_user_by_id).layer(middleware::from_fn_with_state(
+                Permission::UsersRead,
+                require_permission,
+            )),
+        )
+        .route(
+            "/users/{id}",
+            delete(delete_user_by_id).layer(middleware::from_fn_with_state(
+                Permission::UsersDelete,
+                require_permission,
+            )),
+        )
+        .route(
+            "/users/{id}/role",
+            patch(update_user_role).layer(middleware::from_fn_with_state(
+                Permission::UsersManageRoles,
+                require_permission,
+            )),
+        )
+        .route(
+            "/users/{id}/suspend",
+            post(suspend_user).layer(middleware::from_fn_with_state(
+                Permission::UsersSuspend,
+                require_permission,
+            )),
+        )
+        .route(
+            "/users/{id}/unsuspend",
+            post(unsuspend_user).layer(middleware::from_fn_with_state(
+                Permission::UsersSuspend,
+                require_permission,
+            )),
+        )
+        .layer(middleware::from_fn_with_state(
+            terms,
+            require_terms_accepted,
+        ))
+        .layer(middleware::from_fn_with_state(activity, track_activity))
+        .layer(middleware::from_fn_with_state(auth, auth_middleware));
 
     Router::new()
         .merge(authenticated_routes)
@@ -82,6 +239,16 @@ pub fn routes(db_pool: PgPool) -> Router {
         .with_state(state)
 }
 
+#[utoipa::path(
+    get,
+    path = "/users/me",
+    tag = "users",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The signed-in user", body = ApiResponse<UserResponse>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 async fn get_current_user(
     State(state): State<UserState>,
     Extension(claims): Extension<Claims>,
@@ -94,6 +261,19 @@ async fn get_current_user(
     Ok(ApiResponse::success(user))
 }
 
+#[utoipa::path(
+    get,
+    path = "/users/{id}",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "User id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The user", body = ApiResponse<UserResponse>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Missing permission", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    )
+)]
 async fn get_user_by_id(
     State(state): State<UserState>,
     Path(user_id): Path<Uuid>,
@@ -102,12 +282,28 @@ async fn get_user_by_id(
     Ok(ApiResponse::success(user))
 }
 
+#[utoipa::path(
+    patch,
+    path = "/users/me",
+    tag = "users",
+    request_body = UpdateUserRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Updated user", body = ApiResponse<UserResponse>),
+        (status = 400, description = "Invalid fields", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 async fn update_current_user(
     State(state): State<UserState>,
     Extension(claims): Extension<Claims>,
     Json(update_request): Json<UpdateUserRequest>,
 ) -> AppResult<impl axum::response::IntoResponse> {
-    validate_struct(&update_request)?;
+    let mut invalid = field_errors(&update_request);
+    if let Some(name) = &update_request.name {
+        invalid.collect(state.names.check(name))?;
+    }
+    invalid.into_result()?;
 
     let user_id = Uuid::parse_str(&claims.sub)
         .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
@@ -117,6 +313,16 @@ async fn update_current_user(
     Ok(ApiResponse::success(user))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/users/me",
+    tag = "users",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Account deleted"),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 async fn delete_current_user(
     State(state): State<UserState>,
     Extension(claims): Extension<Claims>,
@@ -129,6 +335,18 @@ async fn delete_current_user(
     Ok(no_content())
 }
 
+#[utoipa::path(
+    put,
+    path = "/users/me/password",
+    tag = "users",
+    request_body = ChangePasswordRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Password changed", body = ApiResponse<TupleUnit>),
+        (status = 400, description = "Invalid new password", body = ErrorResponse),
+        (status = 401, description = "Not authenticated or wrong current password", body = ErrorResponse)
+    )
+)]
 async fn change_password(
     State(state): State<UserState>,
     Extension(claims): Extension<Claims>,
@@ -139,7 +357,10 @@ async fn change_password(
     let user_id = Uuid::parse_str(&claims.sub)
         .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
 
-    state.service.change_password(&user_id, password_request).await?;
+    state
+        .service
+        .change_password(&user_id, password_request)
+        .await?;
 
     Ok(ApiResponse::with_message(
         (),
@@ -147,20 +368,217 @@ async fn change_password(
     ))
 }
 
-async fn list_users(
+#[utoipa::path(
+    post,
+    path = "/users/me/accept-terms",
+    tag = "users",
+    request_body = AcceptTermsRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Terms accepted", body = ApiResponse<UserResponse>),
+        (status = 400, description = "Not the current terms version", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
+async fn accept_terms(
     State(state): State<UserState>,
-    Query(pagination): Query<PaginationQuery>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<AcceptTermsRequest>,
 ) -> AppResult<impl axum::response::IntoResponse> {
-    let (users, total) = state.service.list(pagination.page, pagination.per_page).await?;
+    validate_struct(&request)?;
 
-    Ok(PaginatedResponse::new(
-        users,
-        pagination.page,
-        pagination.per_page,
-        total,
-    ))
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    match state.terms.current_version() {
+        Some(current) if current == request.version => {}
+        Some(current) => {
+            return Err(AppError::BadRequest(format!(
+                "Terms version {} is not current; accept version {}",
+                request.version, current
+            )))
+        }
+        None => {
+            return Err(AppError::BadRequest(
+                "No terms version currently requires acceptance".to_string(),
+            ))
+        }
+    }
+
+    let user = state
+        .service
+        .accept_terms(&user_id, &request.version)
+        .await?;
+
+    Ok(ApiResponse::success(user))
 }
 
+#[utoipa::path(
+    get,
+    path = "/users/me/connections",
+    tag = "users",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Linked OAuth providers", body = ApiResponse<Vec<ConnectionResponse>>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
+async fn list_connections(
+    State(state): State<UserState>,
+    Extension(claims): Extension<Claims>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    let connections = state.service.list_connections(&user_id).await?;
+
+    Ok(ApiResponse::success(connections))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/me/connections/{provider}",
+    tag = "users",
+    params(("provider" = String, Path, description = "Provider name, e.g. `google`")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Provider unlinked"),
+        (status = 409, description = "Only way left to sign in", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "Provider not linked", body = ErrorResponse)
+    )
+)]
+async fn unlink_connection(
+    State(state): State<UserState>,
+    Extension(claims): Extension<Claims>,
+    Path(provider): Path<String>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    state.service.unlink_connection(&user_id, &provider).await?;
+
+    Ok(no_content())
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/me/sessions",
+    tag = "users",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Active sessions", body = ApiResponse<Vec<SessionResponse>>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
+async fn list_sessions(
+    State(state): State<UserState>,
+    Extension(claims): Extension<Claims>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    let sessions = state.service.list_sessions(&user_id).await?;
+
+    Ok(ApiResponse::success(sessions))
+}
+
+/// 404 for sessions that are unknown, already revoked or someone else's
+#[utoipa::path(
+    delete,
+    path = "/users/me/sessions/{id}",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "Session id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "Session not found", body = ErrorResponse)
+    )
+)]
+async fn revoke_session(
+    State(state): State<UserState>,
+    Extension(claims): Extension<Claims>,
+    Path(session_id): Path<Uuid>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    state.service.revoke_session(&user_id, &session_id).await?;
+
+    Ok(no_content())
+}
+
+/// Offset pages sorted by `created_at` also return a `next_cursor`, so a
+/// client can switch to cursors after the first page
+#[utoipa::path(
+    get,
+    path = "/users",
+    tag = "users",
+    params(ListUsersQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "A page of users; with `after`, `pagination` holds only `limit` and `next_cursor`", body = PaginatedResponse<UserResponse>),
+        (status = 400, description = "Invalid query or cursor", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Missing permission", body = ErrorResponse)
+    )
+)]
+async fn list_users(
+    State(state): State<UserState>,
+    StrictQuery(query): StrictQuery<ListUsersQuery>,
+) -> AppResult<axum::response::Response> {
+    let options = query.options()?;
+    let cursor_key = state.jwt_config.secret.as_bytes();
+    let cursor_sorted = options.sort_by == UserSortField::CreatedAt;
+
+    if let Some(after) = &query.after {
+        if !cursor_sorted {
+            return Err(AppError::InvalidField {
+                field: "sort_by".to_string(),
+                rule: "cursor_sort",
+                message: "after can only be combined with sort_by=created_at".to_string(),
+            });
+        }
+        let after = Cursor::decode(after, cursor_key)?;
+        let (users, next) = state.service.list_after(&options, &after).await?;
+        let next_cursor = next.map(|cursor| cursor.encode(cursor_key));
+        return Ok(CursorPaginatedResponse::new(users, options.limit, next_cursor).into_response());
+    }
+
+    let (users, total) = state.service.list(&options).await?;
+    let more = options.offset + (users.len() as u64) < total;
+    let next_cursor = users
+        .last()
+        .filter(|_| cursor_sorted && more)
+        .and_then(|last| {
+            Some(Cursor::new(
+                last.created_at,
+                Uuid::parse_str(&last.id).ok()?,
+            ))
+        })
+        .map(|cursor| cursor.encode(cursor_key));
+
+    Ok(
+        PaginatedResponse::from_offset(users, options.offset, options.limit, total)
+            .with_next_cursor(next_cursor)
+            .into_response(),
+    )
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/{id}",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "User id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "User deleted"),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Missing permission", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    )
+)]
 async fn delete_user_by_id(
     State(state): State<UserState>,
     Path(user_id): Path<Uuid>,
@@ -168,3 +586,90 @@ async fn delete_user_by_id(
     state.service.delete(&user_id).await?;
     Ok(no_content())
 }
+
+/// Admins can't change their own role, so a stray request can't demote them
+#[utoipa::path(
+    patch,
+    path = "/users/{id}/role",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "User id")),
+    request_body = UpdateRoleRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Updated user", body = ApiResponse<UserResponse>),
+        (status = 400, description = "Unknown role", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Missing permission", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    )
+)]
+async fn update_user_role(
+    State(state): State<UserState>,
+    Path(user_id): Path<Uuid>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<UpdateRoleRequest>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let role = UserRole::parse(&request.role)?;
+    if claims.sub == user_id.to_string() {
+        return Err(AppError::Authorization(
+            "You cannot change your own role".to_string(),
+        ));
+    }
+    let user = state.service.update_role(&user_id, role).await?;
+    Ok(ApiResponse::success(user))
+}
+
+/// Admins can't suspend themselves, which would lock them out mid-request
+#[utoipa::path(
+    post,
+    path = "/users/{id}/suspend",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "User id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Suspended user", body = ApiResponse<UserResponse>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Missing permission", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    )
+)]
+async fn suspend_user(
+    State(state): State<UserState>,
+    Path(user_id): Path<Uuid>,
+    Extension(claims): Extension<Claims>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    if claims.sub == user_id.to_string() {
+        return Err(AppError::Authorization(
+            "You cannot suspend your own account".to_string(),
+        ));
+    }
+    let user = state
+        .service
+        .set_status(&user_id, UserStatus::Suspended)
+        .await?;
+    Ok(ApiResponse::success(user))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/{id}/unsuspend",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "User id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Reactivated user", body = ApiResponse<UserResponse>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Missing permission", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    )
+)]
+async fn unsuspend_user(
+    State(state): State<UserState>,
+    Path(user_id): Path<Uuid>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let user = state
+        .service
+        .set_status(&user_id, UserStatus::Active)
+        .await?;
+    Ok(ApiResponse::success(user))
+}
@@ -1,42 +1,72 @@
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::database::query_count::tracked;
+use crate::events::{DomainEvent, EventBus};
 use crate::modules::auth::hash::{hash_password, verify_password};
+use crate::modules::auth::service::revoke_user_refresh_tokens;
+use crate::modules::webhooks::{WebhookEvent, WebhookService};
+use crate::utils::cursor::Cursor;
 use crate::utils::error::{AppError, AppResult};
+use crate::utils::validation::normalize_email;
 
-use super::model::{ChangePasswordRequest, UpdateUserRequest, User, UserResponse};
+use super::model::{
+    ChangePasswordRequest, ConnectionResponse, OAuthConnection, SessionResponse, SortOrder,
+    UpdateUserRequest, User, UserListOptions, UserResponse, UserRole, UserStatus,
+};
+
+/// `WHERE` clause of the user list: `$1` search pattern, `$2` role; soft-deleted
+/// users are never listed
+const LIST_FILTER: &str = "deleted_at IS NULL \
+     AND ($1::text IS NULL OR lower(email) LIKE $1 OR name ILIKE $1) \
+     AND ($2::varchar IS NULL OR role = $2)";
 
 pub struct UserService {
     db_pool: PgPool,
+    webhooks: WebhookService,
+    events: EventBus,
 }
 
 impl UserService {
     pub fn new(db_pool: PgPool) -> Self {
-        Self { db_pool }
+        let webhooks = WebhookService::new(db_pool.clone());
+        Self {
+            db_pool,
+            webhooks,
+            events: EventBus::default(),
+        }
+    }
+
+    /// Publish domain events on `events` instead of a private, unsubscribed bus
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
     }
 
     /// Get user by ID
     pub async fn get_by_id(&self, user_id: &Uuid) -> AppResult<UserResponse> {
-        let user = sqlx::query_as::<_, User>(
-            "SELECT * FROM users WHERE id = $1"
+        let user = tracked(
+            sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1 AND deleted_at IS NULL")
+                .bind(user_id)
+                .fetch_optional(&self.db_pool),
         )
-        .bind(user_id)
-        .fetch_optional(&self.db_pool)
         .await?
-        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        .ok_or(AppError::UserNotFound)?;
 
         Ok(user.into())
     }
 
     /// Get user by email
     pub async fn get_by_email(&self, email: &str) -> AppResult<UserResponse> {
-        let user = sqlx::query_as::<_, User>(
-            "SELECT * FROM users WHERE email = $1"
+        let user = tracked(
+            sqlx::query_as::<_, User>(
+                "SELECT * FROM users WHERE email = $1 AND deleted_at IS NULL",
+            )
+            .bind(normalize_email(email))
+            .fetch_optional(&self.db_pool),
         )
-        .bind(email)
-        .fetch_optional(&self.db_pool)
         .await?
-        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        .ok_or(AppError::UserNotFound)?;
 
         Ok(user.into())
     }
@@ -49,18 +79,23 @@ impl UserService {
     ) -> AppResult<UserResponse> {
         // Build dynamic query based on provided fields
         let mut query = String::from("UPDATE users SET updated_at = NOW()");
-        let mut has_updates = false;
+        let mut param_index = 1;
 
         if request.name.is_some() {
-            query.push_str(", name = $2");
-            has_updates = true;
+            param_index += 1;
+            query.push_str(&format!(", name = ${}", param_index));
+        }
+
+        if request.metadata.is_some() {
+            param_index += 1;
+            query.push_str(&format!(", metadata = ${}", param_index));
         }
 
-        if !has_updates {
+        if param_index == 1 {
             return Err(AppError::BadRequest("No fields to update".to_string()));
         }
 
-        query.push_str(" WHERE id = $1 RETURNING *");
+        query.push_str(" WHERE id = $1 AND deleted_at IS NULL RETURNING *");
 
         let mut query_builder = sqlx::query_as::<_, User>(&query).bind(user_id);
 
@@ -68,84 +103,365 @@ impl UserService {
             query_builder = query_builder.bind(name);
         }
 
+        if let Some(metadata) = request.metadata {
+            query_builder = query_builder.bind(serde_json::Value::Object(metadata));
+        }
+
         let user = query_builder
             .fetch_optional(&self.db_pool)
             .await?
-            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+            .ok_or(AppError::UserNotFound)?;
 
         Ok(user.into())
     }
 
-    /// Change user password
+    /// Change user password, revoking all of the user's refresh tokens
     pub async fn change_password(
         &self,
         user_id: &Uuid,
         request: ChangePasswordRequest,
     ) -> AppResult<()> {
         // Get current user
+        let user =
+            sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1 AND deleted_at IS NULL")
+                .bind(user_id)
+                .fetch_optional(&self.db_pool)
+                .await?
+                .ok_or(AppError::UserNotFound)?;
+
+        // Verify current password
+        let password_hash = user.password_hash.as_deref().ok_or_else(|| {
+            AppError::BadRequest("No password is set for this account".to_string())
+        })?;
+        let is_valid = verify_password(&request.current_password, password_hash)?;
+        if !is_valid {
+            return Err(AppError::Authentication(
+                "Current password is incorrect".to_string(),
+            ));
+        }
+
+        // Hash new password
+        let new_password_hash = hash_password(&request.new_password)?;
+
+        // Update password and end every existing session
+        let mut tx = self.db_pool.begin().await?;
+        sqlx::query("UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2")
+            .bind(&new_password_hash)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        revoke_user_refresh_tokens(&mut *tx, *user_id).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Record that a user accepted the given terms version
+    pub async fn accept_terms(&self, user_id: &Uuid, version: &str) -> AppResult<UserResponse> {
         let user = sqlx::query_as::<_, User>(
-            "SELECT * FROM users WHERE id = $1"
+            r#"
+            UPDATE users SET terms_version_accepted = $2, updated_at = NOW()
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING *
+            "#,
         )
         .bind(user_id)
+        .bind(version)
         .fetch_optional(&self.db_pool)
         .await?
-        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        .ok_or(AppError::UserNotFound)?;
 
-        // Verify current password
-        let is_valid = verify_password(&request.current_password, &user.password_hash)?;
-        if !is_valid {
-            return Err(AppError::Authentication("Current password is incorrect".to_string()));
+        Ok(user.into())
+    }
+
+    /// Change a user's role, refusing to demote the last remaining admin
+    pub async fn update_role(&self, user_id: &Uuid, role: UserRole) -> AppResult<UserResponse> {
+        let mut tx = self.db_pool.begin().await?;
+
+        // Lock every admin row, in a fixed order, so concurrent demotions of
+        // two different admins can't both see the other one still in place
+        let admins: Vec<Uuid> =
+            sqlx::query_scalar("SELECT id FROM users WHERE role = 'admin' AND deleted_at IS NULL ORDER BY id FOR UPDATE")
+                .fetch_all(&mut *tx)
+                .await?;
+
+        if role != UserRole::Admin && admins.len() == 1 && admins[0] == *user_id {
+            return Err(AppError::Conflict(
+                "Cannot demote the last admin; promote another user first".to_string(),
+            ));
         }
 
-        // Hash new password
-        let new_password_hash = hash_password(&request.new_password)?;
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users SET role = $2, updated_at = NOW()
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(role)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::UserNotFound)?;
+
+        tx.commit().await?;
 
-        // Update password
-        sqlx::query(
-            "UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2"
+        self.events.publish(DomainEvent::UserRoleChanged {
+            user_id: user.id,
+            role: user.role,
+        });
+
+        Ok(user.into())
+    }
+
+    /// Suspend or reinstate an account; suspending also revokes every refresh
+    /// token, and `auth_middleware` rejects access tokens already issued
+    pub async fn set_status(&self, user_id: &Uuid, status: UserStatus) -> AppResult<UserResponse> {
+        let mut tx = self.db_pool.begin().await?;
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users SET status = $2, updated_at = NOW()
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING *
+            "#,
         )
-        .bind(&new_password_hash)
         .bind(user_id)
-        .execute(&self.db_pool)
+        .bind(status)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::UserNotFound)?;
+
+        if status == UserStatus::Suspended {
+            revoke_user_refresh_tokens(&mut *tx, user.id).await?;
+        }
+        tx.commit().await?;
+
+        Ok(user.into())
+    }
+
+    /// List the OAuth providers linked to a user
+    pub async fn list_connections(&self, user_id: &Uuid) -> AppResult<Vec<ConnectionResponse>> {
+        let connections = tracked(
+            sqlx::query_as::<_, OAuthConnection>(
+                "SELECT * FROM oauth_connections WHERE user_id = $1 ORDER BY created_at",
+            )
+            .bind(user_id)
+            .fetch_all(&self.db_pool),
+        )
+        .await?;
+
+        Ok(connections.into_iter().map(Into::into).collect())
+    }
+
+    /// Active sessions of a user, most recently used first
+    pub async fn list_sessions(&self, user_id: &Uuid) -> AppResult<Vec<SessionResponse>> {
+        let sessions = tracked(
+            sqlx::query_as::<_, SessionResponse>(
+                r#"
+                SELECT family_id AS id,
+                       MIN(created_at) AS created_at,
+                       MAX(created_at) AS last_used_at,
+                       (ARRAY_AGG(user_agent ORDER BY created_at))[1] AS user_agent,
+                       (ARRAY_AGG(client_network ORDER BY created_at))[1] AS ip_network
+                FROM refresh_tokens
+                WHERE user_id = $1
+                GROUP BY family_id
+                HAVING BOOL_OR(NOT revoked AND expires_at > NOW())
+                ORDER BY last_used_at DESC
+                "#,
+            )
+            .bind(user_id)
+            .fetch_all(&self.db_pool),
+        )
         .await?;
 
+        Ok(sessions)
+    }
+
+    /// Revoke one of the user's sessions; its refresh token stops working,
+    /// access tokens already issued stay valid until they expire
+    pub async fn revoke_session(&self, user_id: &Uuid, session_id: &Uuid) -> AppResult<()> {
+        let revoked = sqlx::query(
+            "UPDATE refresh_tokens SET revoked = TRUE WHERE user_id = $1 AND family_id = $2 AND revoked = FALSE",
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .execute(&self.db_pool)
+        .await?
+        .rows_affected();
+
+        if revoked == 0 {
+            return Err(AppError::NotFound("Session not found".to_string()));
+        }
+
         Ok(())
     }
 
-    /// Delete user
-    pub async fn delete(&self, user_id: &Uuid) -> AppResult<()> {
-        let result = sqlx::query("DELETE FROM users WHERE id = $1")
+    /// Unlink an OAuth provider, refusing to remove the user's last way to sign in
+    pub async fn unlink_connection(&self, user_id: &Uuid, provider: &str) -> AppResult<()> {
+        let mut tx = self.db_pool.begin().await?;
+
+        // Lock the user row so concurrent unlinks can't both pass the check
+        let has_password: bool = sqlx::query_scalar(
+            "SELECT password_hash IS NOT NULL FROM users WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::UserNotFound)?;
+
+        let providers: Vec<String> =
+            sqlx::query_scalar("SELECT provider FROM oauth_connections WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        if !providers.iter().any(|linked| linked == provider) {
+            return Err(AppError::NotFound("Connection not found".to_string()));
+        }
+
+        if !has_password && providers.len() == 1 {
+            return Err(AppError::Conflict(
+                "Cannot unlink the only sign-in method; set a password first".to_string(),
+            ));
+        }
+
+        sqlx::query("DELETE FROM oauth_connections WHERE user_id = $1 AND provider = $2")
             .bind(user_id)
-            .execute(&self.db_pool)
+            .bind(provider)
+            .execute(&mut *tx)
             .await?;
 
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Soft-delete a user: the account disappears at once and every refresh
+    /// token is revoked, while the row (and the user's files) stay until the
+    /// soft-delete purge job removes them after the retention period
+    pub async fn delete(&self, user_id: &Uuid) -> AppResult<()> {
+        let mut tx = self.db_pool.begin().await?;
+        let result = sqlx::query(
+            "UPDATE users SET deleted_at = NOW(), updated_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
         if result.rows_affected() == 0 {
-            return Err(AppError::NotFound("User not found".to_string()));
+            return Err(AppError::UserNotFound);
         }
 
+        revoke_user_refresh_tokens(&mut *tx, *user_id).await?;
+        // Frees the provider accounts to sign up again
+        sqlx::query("DELETE FROM oauth_connections WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        self.events
+            .publish(DomainEvent::UserDeleted { user_id: *user_id });
+        self.webhooks
+            .notify(
+                WebhookEvent::UserDeleted,
+                serde_json::json!({ "id": user_id }),
+            )
+            .await;
+
         Ok(())
     }
 
-    /// List all users (with pagination)
-    pub async fn list(&self, page: u32, per_page: u32) -> AppResult<(Vec<UserResponse>, u64)> {
-        let offset = (page - 1) * per_page;
+    /// List users page by page, optionally filtered by a search term and
+    /// role; `options.sort_by` only ever injects one of the fixed column
+    /// names into `ORDER BY`, everything else is bound
+    pub async fn list(&self, options: &UserListOptions) -> AppResult<(Vec<UserResponse>, u64)> {
+        let pattern = options.search.as_deref().map(contains_pattern);
 
         // Get total count
-        let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
-            .fetch_one(&self.db_pool)
-            .await?;
+        let total: (i64,) = tracked(
+            sqlx::query_as(&format!("SELECT COUNT(*) FROM users WHERE {}", LIST_FILTER))
+                .bind(&pattern)
+                .bind(options.role)
+                .fetch_one(&self.db_pool),
+        )
+        .await?;
 
-        // Get paginated users
-        let users = sqlx::query_as::<_, User>(
-            "SELECT * FROM users ORDER BY created_at DESC LIMIT $1 OFFSET $2"
+        // Get paginated users; `id` keeps pages stable when sort values tie
+        let sql = format!(
+            "SELECT * FROM users WHERE {} ORDER BY {} {} NULLS LAST, id LIMIT $3 OFFSET $4",
+            LIST_FILTER,
+            options.sort_by.column(),
+            options.order.keyword()
+        );
+        let users = tracked(
+            sqlx::query_as::<_, User>(&sql)
+                .bind(&pattern)
+                .bind(options.role)
+                .bind(options.limit as i64)
+                .bind(options.offset as i64)
+                .fetch_all(&self.db_pool),
         )
-        .bind(per_page as i64)
-        .bind(offset as i64)
-        .fetch_all(&self.db_pool)
         .await?;
 
         let user_responses: Vec<UserResponse> = users.into_iter().map(Into::into).collect();
 
         Ok((user_responses, total.0 as u64))
     }
+
+    /// The users following `after` in `created_at` order (ties broken by
+    /// `id`, like `list`), plus the cursor of the last one when more follow.
+    /// Keyset pagination: no offset to skip and no total to count.
+    pub async fn list_after(
+        &self,
+        options: &UserListOptions,
+        after: &Cursor,
+    ) -> AppResult<(Vec<UserResponse>, Option<Cursor>)> {
+        let pattern = options.search.as_deref().map(contains_pattern);
+        let keyset = match options.order {
+            SortOrder::Asc => "(created_at, id) > ($3, $4)",
+            SortOrder::Desc => "(created_at < $3 OR (created_at = $3 AND id > $4))",
+        };
+
+        // One extra row tells whether another page follows
+        let sql = format!(
+            "SELECT * FROM users WHERE {} AND {} ORDER BY created_at {}, id LIMIT $5",
+            LIST_FILTER,
+            keyset,
+            options.order.keyword()
+        );
+        let mut users = tracked(
+            sqlx::query_as::<_, User>(&sql)
+                .bind(&pattern)
+                .bind(options.role)
+                .bind(after.created_at)
+                .bind(after.id)
+                .bind(i64::from(options.limit) + 1)
+                .fetch_all(&self.db_pool),
+        )
+        .await?;
+
+        let next = if users.len() > options.limit as usize {
+            users.truncate(options.limit as usize);
+            users
+                .last()
+                .map(|user| Cursor::new(user.created_at, user.id))
+        } else {
+            None
+        };
+
+        Ok((users.into_iter().map(Into::into).collect(), next))
+    }
+}
+
+/// Lowercased `LIKE` pattern matching `term` anywhere, with `LIKE`
+/// wildcards in the term escaped
+fn contains_pattern(term: &str) -> String {
+    let escaped = term
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
 }
@@ -0,0 +1,78 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::database::query_count::tracked;
+use crate::modules::auth::jwt::Claims;
+use crate::utils::error::{AppError, AppResult};
+
+/// Requires authenticated users to have accepted the current terms version
+#[derive(Clone)]
+pub struct TermsGate {
+    db_pool: PgPool,
+    current_version: Option<Arc<str>>,
+}
+
+impl TermsGate {
+    /// A gate for `current_version`; `None` lets every user through
+    pub fn new(db_pool: PgPool, current_version: Option<String>) -> Self {
+        Self {
+            db_pool,
+            current_version: current_version.map(Arc::from),
+        }
+    }
+
+    pub fn current_version(&self) -> Option<&str> {
+        self.current_version.as_deref()
+    }
+
+    /// Fail with `TermsNotAccepted` unless the user accepted the current version
+    pub async fn check(&self, user_id: &Uuid) -> AppResult<()> {
+        let Some(current_version) = self.current_version() else {
+            return Ok(());
+        };
+
+        let accepted = tracked(
+            sqlx::query_scalar::<_, Option<String>>(
+                "SELECT terms_version_accepted FROM users WHERE id = $1 AND deleted_at IS NULL",
+            )
+            .bind(user_id)
+            .fetch_optional(&self.db_pool),
+        )
+        .await?
+        .ok_or_else(|| AppError::Authentication("User no longer exists".to_string()))?;
+
+        if accepted.as_deref() == Some(current_version) {
+            Ok(())
+        } else {
+            Err(AppError::TermsNotAccepted(format!(
+                "Terms version {} must be accepted via POST /users/me/accept-terms",
+                current_version
+            )))
+        }
+    }
+}
+
+/// Middleware enforcing the terms gate for requests that passed `auth_middleware`
+pub async fn require_terms_accepted(
+    State(gate): State<TermsGate>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let claims = request
+        .extensions()
+        .get::<Claims>()
+        .ok_or_else(|| AppError::Authentication("No authentication found".to_string()))?;
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Authentication("Invalid user ID".to_string()))?;
+
+    gate.check(&user_id).await?;
+
+    Ok(next.run(request).await)
+}
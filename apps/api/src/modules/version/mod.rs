@@ -1,11 +1,7 @@
-use axum::{
-    http::StatusCode,
-    response::IntoResponse,
-    routing::get,
-    Json, Router,
-};
+use axum::{http::StatusCode, response::IntoResponse, routing::get, Json, Router};
 use serde::{Deserialize, Serialize};
-use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::utils::uptime;
 
 #[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct VersionResponse {
@@ -17,6 +13,8 @@ pub struct VersionResponse {
 }
 
 /// Version information handler
+///
+/// Built from compile-time values only; never touches the database.
 #[utoipa::path(
     get,
     path = "/api/v1/version",
@@ -31,18 +29,11 @@ async fn version_info() -> impl IntoResponse {
     let build_timestamp = option_env!("BUILD_TIMESTAMP").unwrap_or("unknown");
     let rust_version = env!("CARGO_PKG_RUST_VERSION");
 
-    // Calculate uptime (simplified - would use actual start time in production)
-    let uptime = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
-        % 86400; // Placeholder: reset daily
-
     let response = VersionResponse {
         version: version.to_string(),
         commit_hash: commit_hash.to_string(),
         build_timestamp: build_timestamp.to_string(),
-        uptime_seconds: uptime,
+        uptime_seconds: uptime::uptime_seconds(),
         rust_version: rust_version.to_string(),
     };
 
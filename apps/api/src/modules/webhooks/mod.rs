@@ -0,0 +1,10 @@
+pub mod model;
+pub mod routes;
+pub mod service;
+pub mod signature;
+pub mod worker;
+
+pub use model::WebhookEvent;
+pub use routes::admin_routes;
+pub use service::WebhookService;
+pub use worker::WebhookWorker;
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// Account events that can be delivered to webhooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    UserCreated,
+    UserDeleted,
+    LoginFailed,
+    AiUsageThresholdCrossed,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 4] = [
+        WebhookEvent::UserCreated,
+        WebhookEvent::UserDeleted,
+        WebhookEvent::LoginFailed,
+        WebhookEvent::AiUsageThresholdCrossed,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::UserCreated => "user.created",
+            WebhookEvent::UserDeleted => "user.deleted",
+            WebhookEvent::LoginFailed => "login.failed",
+            WebhookEvent::AiUsageThresholdCrossed => "ai.usage_threshold_crossed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Webhook configuration - never includes the signing secret
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookResponse {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Webhook> for WebhookResponse {
+    fn from(webhook: Webhook) -> Self {
+        Self {
+            id: webhook.id.to_string(),
+            url: webhook.url,
+            events: webhook.events,
+            created_at: webhook.created_at,
+            updated_at: webhook.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateWebhookRequest {
+    #[schema(example = "https://example.com/hooks/vibe")]
+    #[validate(url(message = "Invalid webhook URL"))]
+    pub url: String,
+
+    #[validate(length(min = 16, message = "Secret must be at least 16 characters"))]
+    pub secret: String,
+
+    #[schema(example = json!(["user.created", "user.deleted"]))]
+    #[validate(custom(function = "validate_events"))]
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UpdateWebhookRequest {
+    #[validate(url(message = "Invalid webhook URL"))]
+    pub url: Option<String>,
+
+    #[validate(length(min = 16, message = "Secret must be at least 16 characters"))]
+    pub secret: Option<String>,
+
+    #[validate(custom(function = "validate_events"))]
+    pub events: Option<Vec<String>>,
+}
+
+/// Events must be non-empty and limited to known event names
+fn validate_events(events: &[String]) -> Result<(), ValidationError> {
+    if events.is_empty() {
+        return Err(ValidationError::new("events_required"));
+    }
+
+    let known = |event: &String| WebhookEvent::ALL.iter().any(|e| e.as_str() == event);
+    if !events.iter().all(known) {
+        return Err(ValidationError::new("unknown_event"));
+    }
+
+    Ok(())
+}
+
+/// A single event queued for one webhook, with its delivery history
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
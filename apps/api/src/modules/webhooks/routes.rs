@@ -0,0 +1,212 @@
+use axum::{
+    extract::{Path, State},
+    middleware,
+    routing::get,
+    Extension, Json, Router,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::config::JwtConfig;
+use crate::modules::auth::{
+    jwt::Claims,
+    middleware::{auth_middleware, AuthMiddleware},
+    role_guard::require_admin,
+};
+use crate::utils::{
+    error::{AppResult, ErrorResponse},
+    response::{created, no_content, ApiResponse},
+    validation::validate_struct,
+};
+
+use super::model::{CreateWebhookRequest, UpdateWebhookRequest, WebhookDelivery, WebhookResponse};
+use super::service::WebhookService;
+
+#[derive(Clone)]
+struct WebhookState {
+    service: Arc<WebhookService>,
+}
+
+/// Admin-only webhook management routes
+pub fn admin_routes(db_pool: PgPool, jwt_config: JwtConfig) -> Router {
+    let auth = AuthMiddleware::new(jwt_config).with_status_check(db_pool.clone());
+    let service = Arc::new(WebhookService::new(db_pool));
+    let state = WebhookState { service };
+
+    Router::new()
+        .route(
+            "/api/v1/admin/webhooks",
+            get(list_webhooks).post(create_webhook),
+        )
+        .route(
+            "/api/v1/admin/webhooks/{id}",
+            get(get_webhook)
+                .patch(update_webhook)
+                .delete(delete_webhook),
+        )
+        .route(
+            "/api/v1/admin/webhooks/{id}/deliveries",
+            get(list_deliveries),
+        )
+        .layer(middleware::from_fn(require_admin))
+        .layer(middleware::from_fn_with_state(auth, auth_middleware))
+        .with_state(state)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/webhooks",
+    tag = "webhooks",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Every webhook", body = ApiResponse<Vec<WebhookResponse>>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse)
+    )
+)]
+async fn list_webhooks(
+    State(state): State<WebhookState>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let webhooks = state.service.list().await?;
+    Ok(ApiResponse::success(webhooks))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/webhooks",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Webhook created", body = ApiResponse<WebhookResponse>),
+        (status = 400, description = "Invalid fields", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse)
+    )
+)]
+async fn create_webhook(
+    State(state): State<WebhookState>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateWebhookRequest>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    validate_struct(&request)?;
+
+    let webhook = state.service.create(request).await?;
+
+    tracing::info!(
+        target: "audit",
+        admin_id = %claims.sub,
+        webhook_id = %webhook.id,
+        "Webhook created by admin"
+    );
+
+    Ok(created(webhook))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The webhook", body = ApiResponse<WebhookResponse>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse)
+    )
+)]
+async fn get_webhook(
+    State(state): State<WebhookState>,
+    Path(webhook_id): Path<Uuid>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let webhook = state.service.get(&webhook_id).await?;
+    Ok(ApiResponse::success(webhook))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/v1/admin/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id")),
+    request_body = UpdateWebhookRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Updated webhook", body = ApiResponse<WebhookResponse>),
+        (status = 400, description = "Invalid fields", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse)
+    )
+)]
+async fn update_webhook(
+    State(state): State<WebhookState>,
+    Extension(claims): Extension<Claims>,
+    Path(webhook_id): Path<Uuid>,
+    Json(request): Json<UpdateWebhookRequest>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    validate_struct(&request)?;
+
+    let webhook = state.service.update(&webhook_id, request).await?;
+
+    tracing::info!(
+        target: "audit",
+        admin_id = %claims.sub,
+        webhook_id = %webhook.id,
+        "Webhook updated by admin"
+    );
+
+    Ok(ApiResponse::success(webhook))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 204, description = "Webhook deleted"),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse)
+    )
+)]
+async fn delete_webhook(
+    State(state): State<WebhookState>,
+    Extension(claims): Extension<Claims>,
+    Path(webhook_id): Path<Uuid>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    state.service.delete(&webhook_id).await?;
+
+    tracing::info!(
+        target: "audit",
+        admin_id = %claims.sub,
+        webhook_id = %webhook_id,
+        "Webhook deleted by admin"
+    );
+
+    Ok(no_content())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(("id" = Uuid, Path, description = "Webhook id")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Recent deliveries", body = ApiResponse<Vec<WebhookDelivery>>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not an admin", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse)
+    )
+)]
+async fn list_deliveries(
+    State(state): State<WebhookState>,
+    Path(webhook_id): Path<Uuid>,
+) -> AppResult<impl axum::response::IntoResponse> {
+    let deliveries = state.service.list_deliveries(&webhook_id).await?;
+    Ok(ApiResponse::success(deliveries))
+}
@@ -0,0 +1,152 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::utils::error::{AppError, AppResult};
+
+use super::model::{
+    CreateWebhookRequest, UpdateWebhookRequest, Webhook, WebhookDelivery, WebhookEvent,
+    WebhookResponse,
+};
+
+#[derive(Clone)]
+pub struct WebhookService {
+    db_pool: PgPool,
+}
+
+impl WebhookService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Register a webhook endpoint
+    pub async fn create(&self, request: CreateWebhookRequest) -> AppResult<WebhookResponse> {
+        let webhook = sqlx::query_as::<_, Webhook>(
+            r#"
+            INSERT INTO webhooks (id, url, secret, events, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, NOW(), NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&request.url)
+        .bind(&request.secret)
+        .bind(&request.events)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(webhook.into())
+    }
+
+    /// List all registered webhooks
+    pub async fn list(&self) -> AppResult<Vec<WebhookResponse>> {
+        let webhooks =
+            sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks ORDER BY created_at DESC")
+                .fetch_all(&self.db_pool)
+                .await?;
+
+        Ok(webhooks.into_iter().map(Into::into).collect())
+    }
+
+    /// Get a webhook by ID
+    pub async fn get(&self, webhook_id: &Uuid) -> AppResult<WebhookResponse> {
+        let webhook = sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks WHERE id = $1")
+            .bind(webhook_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))?;
+
+        Ok(webhook.into())
+    }
+
+    /// Update a webhook; omitted fields are left unchanged
+    pub async fn update(
+        &self,
+        webhook_id: &Uuid,
+        request: UpdateWebhookRequest,
+    ) -> AppResult<WebhookResponse> {
+        let webhook = sqlx::query_as::<_, Webhook>(
+            r#"
+            UPDATE webhooks
+            SET url = COALESCE($2, url),
+                secret = COALESCE($3, secret),
+                events = COALESCE($4, events)
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(webhook_id)
+        .bind(&request.url)
+        .bind(&request.secret)
+        .bind(&request.events)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))?;
+
+        Ok(webhook.into())
+    }
+
+    /// Delete a webhook along with its delivery history
+    pub async fn delete(&self, webhook_id: &Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM webhooks WHERE id = $1")
+            .bind(webhook_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Webhook not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Recent delivery attempts for a webhook, newest first
+    pub async fn list_deliveries(&self, webhook_id: &Uuid) -> AppResult<Vec<WebhookDelivery>> {
+        self.get(webhook_id).await?;
+
+        let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+            r#"
+            SELECT * FROM webhook_deliveries
+            WHERE webhook_id = $1
+            ORDER BY created_at DESC
+            LIMIT 100
+            "#,
+        )
+        .bind(webhook_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    /// Queue an event for every webhook subscribed to it, returning the number queued
+    pub async fn enqueue(&self, event: WebhookEvent, data: serde_json::Value) -> AppResult<u64> {
+        let payload = serde_json::json!({
+            "event": event.as_str(),
+            "occurred_at": Utc::now(),
+            "data": data,
+        });
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries (id, webhook_id, event, payload)
+            SELECT gen_random_uuid(), id, $1, $2
+            FROM webhooks
+            WHERE $1 = ANY(events)
+            "#,
+        )
+        .bind(event.as_str())
+        .bind(&payload)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Queue an event without failing the caller; errors are only logged
+    pub async fn notify(&self, event: WebhookEvent, data: serde_json::Value) {
+        if let Err(e) = self.enqueue(event, data).await {
+            tracing::warn!("Failed to enqueue {} webhook: {}", event.as_str(), e);
+        }
+    }
+}
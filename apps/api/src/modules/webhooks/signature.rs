@@ -0,0 +1,58 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Header carrying the payload signature
+pub const SIGNATURE_HEADER: &str = "x-webhook-signature";
+
+/// Sign a payload as `sha256=<hex HMAC-SHA256(secret, body)>`
+///
+/// Receivers recompute the HMAC over the raw request body with their copy of
+/// the secret and compare it to the header in constant time.
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Verify a signature produced by `sign_payload`
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(digest) = signature
+        .strip_prefix("sha256=")
+        .and_then(|hex| hex::decode(hex).ok())
+    else {
+        return false;
+    };
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.verify_slice(&digest).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_roundtrip() {
+        let signature = sign_payload("top-secret-value", b"{\"event\":\"user.created\"}");
+
+        assert!(signature.starts_with("sha256="));
+        assert!(verify_signature(
+            "top-secret-value",
+            b"{\"event\":\"user.created\"}",
+            &signature
+        ));
+        assert!(!verify_signature(
+            "other-secret-value",
+            b"{\"event\":\"user.created\"}",
+            &signature
+        ));
+        assert!(!verify_signature(
+            "top-secret-value",
+            b"tampered",
+            &signature
+        ));
+    }
+}
@@ -0,0 +1,216 @@
+use axum::http::{header, Request};
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper_rustls::HttpsConnector;
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::TokioExecutor,
+};
+use sqlx::{FromRow, PgPool};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::utils::error::AppResult;
+
+use super::signature::{sign_payload, SIGNATURE_HEADER};
+
+/// Deliveries claimed per polling round
+const BATCH_SIZE: i64 = 20;
+
+/// Per-request timeout for webhook endpoints
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Upper bound for the exponential retry delay
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(3600);
+
+type HttpsClient = Client<HttpsConnector<HttpConnector>, Full<Bytes>>;
+
+#[derive(FromRow)]
+struct DueDelivery {
+    id: Uuid,
+    event: String,
+    payload: serde_json::Value,
+    attempts: i32,
+    url: String,
+    secret: String,
+}
+
+/// Background worker POSTing queued webhook deliveries
+///
+/// Each attempt is recorded on the delivery row. Failures are retried with
+/// exponential backoff (`retry_base * 2^(attempt - 1)`) until `max_attempts`,
+/// after which the delivery is marked `failed`.
+#[derive(Clone)]
+pub struct WebhookWorker {
+    db_pool: PgPool,
+    client: HttpsClient,
+    retry_base: Duration,
+    max_attempts: u32,
+}
+
+impl WebhookWorker {
+    pub fn new(db_pool: PgPool) -> Self {
+        let connector = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_provider_and_webpki_roots(rustls::crypto::aws_lc_rs::default_provider())
+            .expect("Default TLS provider supports the safe protocol versions")
+            .https_or_http()
+            .enable_http1()
+            .build();
+
+        Self {
+            db_pool,
+            client: Client::builder(TokioExecutor::new()).build(connector),
+            retry_base: Duration::from_secs(30),
+            max_attempts: 5,
+        }
+    }
+
+    pub fn with_retry_base(mut self, retry_base: Duration) -> Self {
+        self.retry_base = retry_base;
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Poll for due deliveries every `poll_interval` until the task is aborted
+    pub fn spawn(self, poll_interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.deliver_due().await {
+                    tracing::error!("Webhook delivery round failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Attempt every delivery that is currently due, returning how many were attempted
+    pub async fn deliver_due(&self) -> AppResult<usize> {
+        // Claim a batch by pushing it into the future, so concurrent workers
+        // skip it and a crashed worker's batch becomes due again later
+        let due = sqlx::query_as::<_, DueDelivery>(
+            r#"
+            UPDATE webhook_deliveries d
+            SET next_attempt_at = NOW() + INTERVAL '5 minutes'
+            FROM webhooks w
+            WHERE w.id = d.webhook_id
+              AND d.id IN (
+                  SELECT id FROM webhook_deliveries
+                  WHERE status = 'pending' AND next_attempt_at <= NOW()
+                  ORDER BY next_attempt_at
+                  LIMIT $1
+                  FOR UPDATE SKIP LOCKED
+              )
+            RETURNING d.id, d.event, d.payload, d.attempts, w.url, w.secret
+            "#,
+        )
+        .bind(BATCH_SIZE)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        for delivery in &due {
+            match self.send(delivery).await {
+                Ok(status_code) => self.record_success(delivery, status_code).await?,
+                Err((status_code, error)) => {
+                    tracing::warn!(
+                        delivery_id = %delivery.id,
+                        event = %delivery.event,
+                        "Webhook delivery failed: {}",
+                        error
+                    );
+                    self.record_failure(delivery, status_code, &error).await?
+                }
+            }
+        }
+
+        Ok(due.len())
+    }
+
+    async fn send(&self, delivery: &DueDelivery) -> Result<u16, (Option<u16>, String)> {
+        let body = serde_json::to_vec(&delivery.payload).map_err(|e| (None, e.to_string()))?;
+        let signature = sign_payload(&delivery.secret, &body);
+
+        let request = Request::post(&delivery.url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("x-webhook-event", &delivery.event)
+            .header("x-webhook-delivery", delivery.id.to_string())
+            .header(SIGNATURE_HEADER, signature)
+            .body(Full::new(Bytes::from(body)))
+            .map_err(|e| (None, e.to_string()))?;
+
+        let response = tokio::time::timeout(REQUEST_TIMEOUT, self.client.request(request))
+            .await
+            .map_err(|_| (None, "Request timed out".to_string()))?
+            .map_err(|e| (None, e.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            Ok(status.as_u16())
+        } else {
+            Err((
+                Some(status.as_u16()),
+                format!("Endpoint responded with {}", status),
+            ))
+        }
+    }
+
+    async fn record_success(&self, delivery: &DueDelivery, status_code: u16) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'delivered', attempts = attempts + 1, last_status_code = $2,
+                last_error = NULL, delivered_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(delivery.id)
+        .bind(status_code as i32)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_failure(
+        &self,
+        delivery: &DueDelivery,
+        status_code: Option<u16>,
+        error: &str,
+    ) -> AppResult<()> {
+        let attempt = delivery.attempts as u32 + 1;
+        let status = if attempt >= self.max_attempts {
+            "failed"
+        } else {
+            "pending"
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = $2, attempts = attempts + 1, last_status_code = $3, last_error = $4,
+                next_attempt_at = NOW() + make_interval(secs => $5)
+            WHERE id = $1
+            "#,
+        )
+        .bind(delivery.id)
+        .bind(status)
+        .bind(status_code.map(i32::from))
+        .bind(error)
+        .bind(self.retry_delay(attempt).as_secs_f64())
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn retry_delay(&self, attempt: u32) -> Duration {
+        self.retry_base
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(MAX_RETRY_DELAY)
+    }
+}
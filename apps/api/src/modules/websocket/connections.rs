@@ -1,33 +1,270 @@
-use std::collections::HashMap;
+use axum::extract::ws::Message;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, RwLock};
-use axum::extract::ws::Message;
+use uuid::Uuid;
+
+use super::model::{Connection, WebSocketMessage};
+use crate::config::WebSocketConfig;
+use crate::metrics;
+use crate::modules::health::status::SubsystemHealth;
+use crate::modules::users::model::UserRole;
+
+/// Minimum interval between repeated typing events from one connection
+pub const TYPING_THROTTLE: Duration = Duration::from_millis(500);
+
+/// How long a dropped connection can be resumed with its resume token
+pub const RESUME_WINDOW: Duration = Duration::from_secs(60);
+
+/// Messages buffered per dropped connection; the oldest are discarded beyond this
+pub const RESUME_BACKLOG_LIMIT: usize = 100;
 
-use super::model::Connection;
+/// Default number of rooms one connection may be in at once
+pub const MAX_ROOMS_PER_CONNECTION: usize = 50;
 
-pub type Tx = mpsc::UnboundedSender<Message>;
+/// Default number of outbound messages queued per connection; must stay above
+/// `RESUME_BACKLOG_LIMIT` so a full backlog can be replayed
+pub const OUTBOUND_QUEUE_CAPACITY: usize = 256;
+
+/// Default interval between server pings
+pub const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default time a client may send nothing before it is disconnected
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Bounded outbound queue of one connection, drained in order by its socket
+pub type Tx = mpsc::Sender<Message>;
 pub type ConnectionMap = Arc<RwLock<HashMap<String, (Connection, Tx)>>>;
 
+/// Queue a non-critical message without waiting
+///
+/// When the connection's queue is full the message is dropped and counted
+/// rather than buffered. Returns whether it was queued.
+pub fn try_deliver(tx: &Tx, message: Message) -> bool {
+    match tx.try_send(message) {
+        Ok(()) => true,
+        Err(mpsc::error::TrySendError::Full(_)) => {
+            metrics::record_ws_message_dropped();
+            false
+        }
+        Err(mpsc::error::TrySendError::Closed(_)) => false,
+    }
+}
+
+/// State of a dropped connection, kept until its resume token expires
+#[derive(Debug)]
+pub struct ResumableSession {
+    pub user_id: Option<String>,
+    pub role: Option<UserRole>,
+    pub rooms: Vec<String>,
+    /// Room and direct messages sent while the client was away
+    pub backlog: VecDeque<Message>,
+    expires_at: Instant,
+}
+
+impl ResumableSession {
+    fn is_expired(&self, now: Instant) -> bool {
+        now >= self.expires_at
+    }
+
+    fn buffer(&mut self, message: Message) {
+        if self.backlog.len() == RESUME_BACKLOG_LIMIT {
+            self.backlog.pop_front();
+        }
+        self.backlog.push_back(message);
+    }
+}
+
 #[derive(Clone)]
 pub struct ConnectionManager {
     connections: ConnectionMap,
+    /// Resume token currently issued to each live connection
+    resume_tokens: Arc<RwLock<HashMap<String, String>>>,
+    /// Dropped connections awaiting resume, keyed by resume token
+    parked: Arc<RwLock<HashMap<String, ResumableSession>>>,
+    resume_window: Duration,
+    max_rooms_per_connection: usize,
+    outbound_capacity: usize,
+    ping_interval: Duration,
+    idle_timeout: Duration,
 }
 
 impl ConnectionManager {
     pub fn new() -> Self {
+        Self::with_resume_window(RESUME_WINDOW)
+    }
+
+    pub fn with_resume_window(resume_window: Duration) -> Self {
         Self {
             connections: Arc::new(RwLock::new(HashMap::new())),
+            resume_tokens: Arc::new(RwLock::new(HashMap::new())),
+            parked: Arc::new(RwLock::new(HashMap::new())),
+            resume_window,
+            max_rooms_per_connection: MAX_ROOMS_PER_CONNECTION,
+            outbound_capacity: OUTBOUND_QUEUE_CAPACITY,
+            ping_interval: PING_INTERVAL,
+            idle_timeout: IDLE_TIMEOUT,
         }
     }
 
+    pub fn from_config(config: &WebSocketConfig) -> Self {
+        Self::new()
+            .with_max_rooms_per_connection(config.max_rooms_per_connection)
+            .with_outbound_capacity(config.outbound_queue_capacity)
+            .with_heartbeat(
+                Duration::from_secs(config.ping_interval_secs),
+                Duration::from_secs(config.idle_timeout_secs),
+            )
+    }
+
+    pub fn with_max_rooms_per_connection(mut self, max_rooms: usize) -> Self {
+        self.max_rooms_per_connection = max_rooms;
+        self
+    }
+
+    pub fn max_rooms_per_connection(&self) -> usize {
+        self.max_rooms_per_connection
+    }
+
+    pub fn with_outbound_capacity(mut self, capacity: usize) -> Self {
+        self.outbound_capacity = capacity.max(1);
+        self
+    }
+
+    /// Capacity to create each connection's `Tx` with
+    pub fn outbound_capacity(&self) -> usize {
+        self.outbound_capacity
+    }
+
+    pub fn with_heartbeat(mut self, ping_interval: Duration, idle_timeout: Duration) -> Self {
+        self.ping_interval = ping_interval.max(Duration::from_millis(1));
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    pub fn ping_interval(&self) -> Duration {
+        self.ping_interval
+    }
+
+    pub fn idle_timeout(&self) -> Duration {
+        self.idle_timeout
+    }
+
     pub async fn add_connection(&self, connection: Connection, tx: Tx) {
         let mut connections = self.connections.write().await;
         connections.insert(connection.id.clone(), (connection, tx));
+        metrics::set_ws_active_connections(connections.len());
     }
 
     pub async fn remove_connection(&self, connection_id: &str) {
         let mut connections = self.connections.write().await;
         connections.remove(connection_id);
+        metrics::set_ws_active_connections(connections.len());
+        self.resume_tokens.write().await.remove(connection_id);
+    }
+
+    /// Record that the client sent a frame
+    pub async fn touch(&self, connection_id: &str) {
+        if let Some((connection, _)) = self.connections.write().await.get_mut(connection_id) {
+            connection.last_seen = Instant::now();
+        }
+    }
+
+    /// Queue a ping for the client to answer, unless its queue is full; the
+    /// idle timeout deals with clients that never answer
+    pub async fn ping(&self, connection_id: &str) -> bool {
+        match self.get_connection(connection_id).await {
+            Some((_, tx)) => try_deliver(&tx, Message::Ping(Default::default())),
+            None => false,
+        }
+    }
+
+    /// Whether a live connection has sent nothing for `idle_timeout`; false
+    /// once it is gone
+    pub async fn is_idle(&self, connection_id: &str) -> bool {
+        self.connections
+            .read()
+            .await
+            .get(connection_id)
+            .is_some_and(|(connection, _)| connection.last_seen.elapsed() >= self.idle_timeout)
+    }
+
+    /// Issue a fresh resume token for a live connection, replacing any previous one
+    pub async fn issue_resume_token(&self, connection_id: &str) -> String {
+        let token = Uuid::new_v4().simple().to_string();
+        self.resume_tokens
+            .write()
+            .await
+            .insert(connection_id.to_string(), token.clone());
+        token
+    }
+
+    /// Handle a dropped connection, keeping its rooms and buffering messages
+    /// for `resume_window` if it was issued a resume token
+    pub async fn disconnect(&self, connection_id: &str) {
+        let mut connections = self.connections.write().await;
+        let Some((connection, _)) = connections.remove(connection_id) else {
+            return;
+        };
+        metrics::set_ws_active_connections(connections.len());
+        let Some(token) = self.resume_tokens.write().await.remove(connection_id) else {
+            return;
+        };
+
+        let now = Instant::now();
+        let mut parked = self.parked.write().await;
+        parked.retain(|_, session| !session.is_expired(now));
+        parked.insert(
+            token,
+            ResumableSession {
+                user_id: connection.user_id,
+                role: connection.role,
+                rooms: connection.rooms,
+                backlog: VecDeque::new(),
+                expires_at: now + self.resume_window,
+            },
+        );
+    }
+
+    /// Claim the session behind a resume token
+    ///
+    /// Tokens are single use. A token still held by a live connection (the
+    /// server has not noticed the drop yet) takes that connection over.
+    /// Returns `None` for unknown or expired tokens.
+    pub async fn take_resumable(&self, token: &str) -> Option<ResumableSession> {
+        let live_id = self
+            .resume_tokens
+            .read()
+            .await
+            .iter()
+            .find(|(_, issued)| issued.as_str() == token)
+            .map(|(connection_id, _)| connection_id.clone());
+        if let Some(connection_id) = live_id {
+            self.disconnect(&connection_id).await;
+        }
+
+        let now = Instant::now();
+        let mut parked = self.parked.write().await;
+        parked.retain(|_, session| !session.is_expired(now));
+        parked.remove(token)
+    }
+
+    /// Register a connection that resumes `session`, replaying its backlog first
+    pub async fn restore(&self, connection_id: &str, session: ResumableSession, tx: Tx) {
+        for message in session.backlog {
+            try_deliver(&tx, message);
+        }
+
+        let connection = Connection {
+            id: connection_id.to_string(),
+            user_id: session.user_id,
+            role: session.role,
+            rooms: session.rooms,
+            last_typing: None,
+            last_seen: Instant::now(),
+        };
+        self.add_connection(connection, tx).await;
     }
 
     pub async fn get_connection(&self, connection_id: &str) -> Option<(Connection, Tx)> {
@@ -35,14 +272,86 @@ impl ConnectionManager {
         connections.get(connection_id).cloned()
     }
 
+    /// Send a reply the client asked for (pong, error, echo)
+    ///
+    /// Replies are never dropped on their own: a client too slow to take one
+    /// is shed instead. Its queue is closed, so the socket drains what was
+    /// already queued and then closes; the session stays resumable.
+    /// Returns whether the reply was queued.
+    pub async fn reply(&self, connection_id: &str, message: Message) -> bool {
+        let Some((_, tx)) = self.get_connection(connection_id).await else {
+            return false;
+        };
+
+        match tx.try_send(message) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                tracing::warn!(
+                    "Shedding WebSocket connection {}: outbound queue full",
+                    connection_id
+                );
+                metrics::record_ws_connection_shed();
+                self.disconnect(connection_id).await;
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        }
+    }
+
     pub async fn broadcast_to_room(&self, room: &str, message: Message) {
         let connections = self.connections.read().await;
 
         for (connection, tx) in connections.values() {
             if connection.rooms.contains(&room.to_string()) {
-                let _ = tx.send(message.clone());
+                try_deliver(tx, message.clone());
             }
         }
+
+        let now = Instant::now();
+        for session in self.parked.write().await.values_mut() {
+            if !session.is_expired(now) && session.rooms.iter().any(|r| r == room) {
+                session.buffer(message.clone());
+            }
+        }
+    }
+
+    /// Fan out a typing indicator to the other members of a room
+    ///
+    /// Typing events are never persisted. Repeating the same state within
+    /// `TYPING_THROTTLE` is dropped, as is typing in a room the connection
+    /// has not joined. Returns whether the event was delivered.
+    pub async fn broadcast_typing(&self, connection_id: &str, room: &str, is_typing: bool) -> bool {
+        let mut connections = self.connections.write().await;
+
+        let Some((connection, _)) = connections.get_mut(connection_id) else {
+            return false;
+        };
+
+        if !connection.rooms.iter().any(|r| r == room) {
+            return false;
+        }
+
+        if let Some((last_state, last_at)) = connection.last_typing {
+            if last_state == is_typing && last_at.elapsed() < TYPING_THROTTLE {
+                return false;
+            }
+        }
+        connection.last_typing = Some((is_typing, Instant::now()));
+
+        let event = WebSocketMessage::Typing {
+            room: room.to_string(),
+            user_id: connection.user_id.clone(),
+            is_typing,
+        };
+        let message = Message::Text(serde_json::to_string(&event).unwrap().into());
+
+        for (id, (member, tx)) in connections.iter() {
+            if id != connection_id && member.rooms.iter().any(|r| r == room) {
+                try_deliver(tx, message.clone());
+            }
+        }
+
+        true
     }
 
     pub async fn send_to_user(&self, user_id: &str, message: Message) {
@@ -51,20 +360,33 @@ impl ConnectionManager {
         for (connection, tx) in connections.values() {
             if let Some(uid) = &connection.user_id {
                 if uid == user_id {
-                    let _ = tx.send(message.clone());
+                    try_deliver(tx, message.clone());
                 }
             }
         }
+
+        let now = Instant::now();
+        for session in self.parked.write().await.values_mut() {
+            if !session.is_expired(now) && session.user_id.as_deref() == Some(user_id) {
+                session.buffer(message.clone());
+            }
+        }
     }
 
-    pub async fn add_to_room(&self, connection_id: &str, room: String) {
+    /// Join a room; returns false when the connection is already at its room
+    /// cap (rejoining a room it is in always succeeds)
+    pub async fn add_to_room(&self, connection_id: &str, room: String) -> bool {
         let mut connections = self.connections.write().await;
 
         if let Some((connection, tx)) = connections.get_mut(connection_id) {
             if !connection.rooms.contains(&room) {
+                if connection.rooms.len() >= self.max_rooms_per_connection {
+                    return false;
+                }
                 connection.rooms.push(room);
             }
         }
+        true
     }
 
     pub async fn remove_from_room(&self, connection_id: &str, room: &str) {
@@ -75,6 +397,14 @@ impl ConnectionManager {
         }
     }
 
+    /// Entry for the `websocket` subsystem of `GET /api/v1/status`
+    pub async fn health(&self) -> SubsystemHealth {
+        SubsystemHealth::healthy().with_details(serde_json::json!({
+            "connections": self.connection_count().await,
+            "resumable_sessions": self.parked.read().await.len(),
+        }))
+    }
+
     pub async fn connection_count(&self) -> usize {
         let connections = self.connections.read().await;
         connections.len()
@@ -1,36 +1,72 @@
-use axum::extract::ws::{Message, WebSocket};
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket};
 use futures::{SinkExt, StreamExt};
-use tokio::sync::mpsc;
-use tracing::{error, info, warn};
+use std::time::Instant;
+use tokio::{sync::mpsc, task::AbortHandle};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use super::connections::ConnectionManager;
+use super::connections::{try_deliver, ConnectionManager, ResumableSession};
 use super::model::{Connection, WebSocketMessage};
+use crate::metrics;
+use crate::modules::users::model::UserRole;
 
-pub async fn handle_socket(socket: WebSocket, manager: ConnectionManager, user_id: Option<String>) {
+pub async fn handle_socket(
+    socket: WebSocket,
+    manager: ConnectionManager,
+    user_id: Option<String>,
+    role: Option<UserRole>,
+    resumed: Option<ResumableSession>,
+) {
     let connection_id = Uuid::new_v4().to_string();
     info!("New WebSocket connection: {}", connection_id);
 
     let (mut sender, mut receiver) = socket.split();
-    let (tx, mut rx) = mpsc::unbounded_channel::<Message>();
+    let (tx, mut rx) = mpsc::channel::<Message>(manager.outbound_capacity());
 
-    // Create connection
-    let connection = Connection {
-        id: connection_id.clone(),
+    // Hand out the token the client can resume this connection with
+    let session = WebSocketMessage::Session {
+        connection_id: connection_id.clone(),
+        resume_token: manager.issue_resume_token(&connection_id).await,
+        resumed: resumed.is_some(),
         user_id: user_id.clone(),
-        rooms: vec![],
     };
+    try_deliver(
+        &tx,
+        Message::Text(serde_json::to_string(&session).unwrap().into()),
+    );
 
-    // Register connection
-    manager.add_connection(connection, tx).await;
+    // Register connection, restoring rooms and missed messages when resuming
+    match resumed {
+        Some(session) => manager.restore(&connection_id, session, tx).await,
+        None => {
+            let connection = Connection {
+                id: connection_id.clone(),
+                user_id,
+                role,
+                rooms: vec![],
+                last_typing: None,
+                last_seen: Instant::now(),
+            };
+            manager.add_connection(connection, tx).await;
+        }
+    }
 
     // Spawn task to handle outgoing messages
     let mut send_task = tokio::spawn(async move {
         while let Some(msg) = rx.recv().await {
             if sender.send(msg).await.is_err() {
-                break;
+                return;
             }
         }
+
+        // The manager closed our queue: this client was shed for falling
+        // behind, or its session was resumed on another connection
+        let _ = sender
+            .send(Message::Close(Some(CloseFrame {
+                code: close_code::AGAIN,
+                reason: "Connection dropped by server; reconnect with your resume token".into(),
+            })))
+            .await;
     });
 
     // Handle incoming messages
@@ -39,25 +75,110 @@ pub async fn handle_socket(socket: WebSocket, manager: ConnectionManager, user_i
 
     let mut recv_task = tokio::spawn(async move {
         while let Some(Ok(msg)) = receiver.next().await {
+            manager_clone.touch(&connection_id_clone).await;
             if let Err(e) = process_message(msg, &manager_clone, &connection_id_clone).await {
                 error!("Error processing message: {}", e);
             }
         }
     });
 
-    // Wait for either task to finish
+    let mut heartbeat_task = tokio::spawn(heartbeat(manager.clone(), connection_id.clone()));
+
+    let guard = ConnectionGuard {
+        manager,
+        connection_id,
+        tasks: [
+            send_task.abort_handle(),
+            recv_task.abort_handle(),
+            heartbeat_task.abort_handle(),
+        ],
+        closed: false,
+    };
+
+    // Wait for any task to finish
     tokio::select! {
-        _ = (&mut send_task) => {
-            recv_task.abort();
+        _ = (&mut send_task) => {}
+        _ = (&mut recv_task) => {}
+        _ = (&mut heartbeat_task) => {}
+    }
+
+    guard.close().await;
+}
+
+/// Ping the client every `ping_interval` and return once it has been idle
+/// for `idle_timeout`, or is gone
+///
+/// Idleness is checked on each ping, so a silent client is dropped within
+/// one interval after its timeout. Half-open sockets never error on their
+/// own; this is what reclaims them.
+async fn heartbeat(manager: ConnectionManager, connection_id: String) {
+    let mut ticker = tokio::time::interval(manager.ping_interval());
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+
+        if manager.is_idle(&connection_id).await {
+            warn!(
+                "Closing WebSocket connection {}: idle for {:?}",
+                connection_id,
+                manager.idle_timeout()
+            );
+            metrics::record_ws_idle_timeout();
+            return;
         }
-        _ = (&mut recv_task) => {
-            send_task.abort();
+        if manager.get_connection(&connection_id).await.is_none() {
+            return;
         }
+        manager.ping(&connection_id).await;
+    }
+}
+
+/// Tears a connection down however its handler ends
+///
+/// The socket halves live in spawned tasks, which would keep the connection
+/// (and its room memberships) alive if the handler were dropped mid-await,
+/// e.g. on server shutdown. Dropping the guard aborts both tasks and
+/// disconnects the connection.
+struct ConnectionGuard {
+    manager: ConnectionManager,
+    connection_id: String,
+    tasks: [AbortHandle; 3],
+    closed: bool,
+}
+
+impl ConnectionGuard {
+    /// Clean up connection; its state stays resumable for a short window
+    async fn close(mut self) {
+        self.closed = true;
+        self.tasks.iter().for_each(AbortHandle::abort);
+        self.manager.disconnect(&self.connection_id).await;
+        info!("WebSocket connection closed: {}", self.connection_id);
     }
+}
 
-    // Clean up connection
-    manager.remove_connection(&connection_id).await;
-    info!("WebSocket connection closed: {}", connection_id);
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        if self.closed {
+            return;
+        }
+        self.tasks.iter().for_each(AbortHandle::abort);
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            warn!(
+                "No runtime to disconnect WebSocket {} on",
+                self.connection_id
+            );
+            return;
+        };
+        let manager = self.manager.clone();
+        let connection_id = std::mem::take(&mut self.connection_id);
+        runtime.spawn(async move {
+            manager.disconnect(&connection_id).await;
+            info!("WebSocket connection dropped: {}", connection_id);
+        });
+    }
 }
 
 async fn process_message(
@@ -80,7 +201,7 @@ async fn process_message(
             // Axum handles pong automatically
         }
         Message::Pong(_) => {
-            // Received pong
+            // Answer to our heartbeat; already counted as activity
         }
         Message::Close(_) => {
             info!("Received close message");
@@ -90,7 +211,8 @@ async fn process_message(
     Ok(())
 }
 
-async fn handle_ws_message(
+/// Apply one parsed client message on behalf of `connection_id`
+pub async fn handle_ws_message(
     message: WebSocketMessage,
     manager: &ConnectionManager,
     connection_id: &str,
@@ -98,25 +220,41 @@ async fn handle_ws_message(
     match message {
         WebSocketMessage::Ping => {
             // Send pong
-            if let Some((_, tx)) = manager.get_connection(connection_id).await {
-                let pong = WebSocketMessage::Pong;
-                let json = serde_json::to_string(&pong).unwrap();
-                let _ = tx.send(Message::Text(json));
-            }
+            let pong = WebSocketMessage::Pong;
+            let json = serde_json::to_string(&pong).unwrap();
+            manager
+                .reply(connection_id, Message::Text(json.into()))
+                .await;
         }
         WebSocketMessage::Text { content } => {
             info!("Received text: {}", content);
             // Echo back or handle as needed
-            if let Some((_, tx)) = manager.get_connection(connection_id).await {
-                let response = WebSocketMessage::Text {
-                    content: format!("Echo: {}", content),
-                };
-                let json = serde_json::to_string(&response).unwrap();
-                let _ = tx.send(Message::Text(json));
-            }
+            let response = WebSocketMessage::Text {
+                content: format!("Echo: {}", content),
+            };
+            let json = serde_json::to_string(&response).unwrap();
+            manager
+                .reply(connection_id, Message::Text(json.into()))
+                .await;
         }
         WebSocketMessage::Join { room } => {
-            manager.add_to_room(connection_id, room.clone()).await;
+            if !manager.add_to_room(connection_id, room.clone()).await {
+                debug!(
+                    "Connection {} at room cap, not joining {}",
+                    connection_id, room
+                );
+                let error = WebSocketMessage::Error {
+                    message: format!(
+                        "Room limit reached: a connection may join at most {} rooms",
+                        manager.max_rooms_per_connection()
+                    ),
+                };
+                let json = serde_json::to_string(&error).unwrap();
+                manager
+                    .reply(connection_id, Message::Text(json.into()))
+                    .await;
+                return Ok(());
+            }
             info!("Connection {} joined room {}", connection_id, room);
 
             // Notify room
@@ -124,7 +262,9 @@ async fn handle_ws_message(
                 content: format!("User joined room: {}", room),
             };
             let json = serde_json::to_string(&notification).unwrap();
-            manager.broadcast_to_room(&room, Message::Text(json)).await;
+            manager
+                .broadcast_to_room(&room, Message::Text(json.into()))
+                .await;
         }
         WebSocketMessage::Leave { room } => {
             manager.remove_from_room(connection_id, &room).await;
@@ -133,7 +273,22 @@ async fn handle_ws_message(
         WebSocketMessage::Broadcast { room, content } => {
             let broadcast_msg = WebSocketMessage::Text { content };
             let json = serde_json::to_string(&broadcast_msg).unwrap();
-            manager.broadcast_to_room(&room, Message::Text(json)).await;
+            manager
+                .broadcast_to_room(&room, Message::Text(json.into()))
+                .await;
+        }
+        WebSocketMessage::Typing {
+            room, is_typing, ..
+        } => {
+            if !manager
+                .broadcast_typing(connection_id, &room, is_typing)
+                .await
+            {
+                debug!(
+                    "Dropped typing event from {} in room {}",
+                    connection_id, room
+                );
+            }
         }
         WebSocketMessage::Error { message } => {
             error!("Error message: {}", message);
@@ -1,5 +1,5 @@
-pub mod handler;
 pub mod connections;
+pub mod handler;
 pub mod model;
 pub mod routes;
 
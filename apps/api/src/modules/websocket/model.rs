@@ -1,20 +1,55 @@
 use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+use crate::modules::users::model::UserRole;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum WebSocketMessage {
     Ping,
     Pong,
-    Text { content: String },
-    Join { room: String },
-    Leave { room: String },
-    Broadcast { room: String, content: String },
-    Error { message: String },
+    Text {
+        content: String,
+    },
+    Join {
+        room: String,
+    },
+    Leave {
+        room: String,
+    },
+    Broadcast {
+        room: String,
+        content: String,
+    },
+    /// Ephemeral typing indicator; `user_id` is filled in by the server
+    Typing {
+        room: String,
+        #[serde(default)]
+        user_id: Option<String>,
+        is_typing: bool,
+    },
+    Error {
+        message: String,
+    },
+    /// Sent by the server on connect; reconnect with `?resume_token=` to resume
+    Session {
+        connection_id: String,
+        resume_token: String,
+        resumed: bool,
+        /// Authenticated user; `None` on anonymous connections
+        #[serde(default)]
+        user_id: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone)]
 pub struct Connection {
     pub id: String,
     pub user_id: Option<String>,
+    pub role: Option<UserRole>,
     pub rooms: Vec<String>,
+    /// Last typing state sent by this connection, used for throttling
+    pub last_typing: Option<(bool, Instant)>,
+    /// When the client last sent any frame, pongs included
+    pub last_seen: Instant,
 }
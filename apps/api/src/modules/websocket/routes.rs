@@ -1,11 +1,17 @@
 use axum::{
     extract::{ws::WebSocketUpgrade, Query, State},
-    response::Response,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     routing::get,
     Router,
 };
 use serde::Deserialize;
 use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::config::JwtConfig;
+use crate::modules::auth::jwt::{validate_access_token, Claims};
+use crate::utils::error::{AppError, AppResult, ErrorResponse};
 
 use super::connections::ConnectionManager;
 use super::handler::handle_socket;
@@ -13,26 +19,96 @@ use super::handler::handle_socket;
 #[derive(Clone)]
 struct WebSocketState {
     manager: Arc<ConnectionManager>,
+    jwt_config: Arc<JwtConfig>,
+    allow_anonymous: bool,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 struct WebSocketQuery {
-    user_id: Option<String>,
+    /// Access token, for clients that cannot set headers on the upgrade
+    token: Option<String>,
+    /// Token from a previous connection's `session` message
+    resume_token: Option<String>,
 }
 
-pub fn routes() -> Router {
-    let manager = Arc::new(ConnectionManager::new());
-    let state = WebSocketState { manager };
+/// `GET /ws`, serving connections registered with `manager`
+///
+/// Upgrades need an access token unless `allow_anonymous` is set.
+pub fn routes(manager: ConnectionManager, jwt_config: JwtConfig, allow_anonymous: bool) -> Router {
+    let state = WebSocketState {
+        manager: Arc::new(manager),
+        jwt_config: Arc::new(jwt_config),
+        allow_anonymous,
+    };
 
     Router::new()
         .route("/ws", get(websocket_handler))
         .with_state(state)
 }
 
+/// The caller's claims, from `Authorization: Bearer` or else `?token=`
+///
+/// A token that is present but invalid is always rejected; a missing one
+/// only when anonymous connections are not allowed.
+fn authenticate(
+    headers: &HeaderMap,
+    query: &WebSocketQuery,
+    state: &WebSocketState,
+) -> AppResult<Option<Claims>> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    match bearer.or(query.token.as_deref()) {
+        Some(token) => validate_access_token(token, &state.jwt_config).map(Some),
+        None if state.allow_anonymous => Ok(None),
+        None => Err(AppError::Authentication("Missing access token".to_string())),
+    }
+}
+
+/// Upgrade to a WebSocket; the access token may also be sent as `?token=`
+#[utoipa::path(
+    get,
+    path = "/ws",
+    tag = "websocket",
+    params(WebSocketQuery),
+    security((), ("bearer_auth" = [])),
+    responses(
+        (status = 101, description = "Switching to the WebSocket protocol"),
+        (status = 401, description = "Missing or invalid access token", body = ErrorResponse),
+        (status = 403, description = "Resume token belongs to another user", body = ErrorResponse),
+        (status = 410, description = "Resume token expired", body = String)
+    )
+)]
 async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(state): State<WebSocketState>,
     Query(query): Query<WebSocketQuery>,
-) -> Response {
-    ws.on_upgrade(move |socket| handle_socket(socket, (*state.manager).clone(), query.user_id))
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    // Rejected before switching protocols, so clients see a plain 401
+    let claims = authenticate(&headers, &query, &state)?;
+    let user_id = claims.as_ref().map(|c| c.sub.clone());
+
+    let resumed = match &query.resume_token {
+        Some(token) => match state.manager.take_resumable(token).await {
+            // A session only resumes for the user it belonged to
+            Some(session) if session.user_id != user_id => {
+                return Err(AppError::Authorization(
+                    "Resume token belongs to another user".to_string(),
+                ));
+            }
+            Some(session) => Some(session),
+            // Expired or unknown: the client must open a fresh connection
+            None => return Ok((StatusCode::GONE, "Resume token expired").into_response()),
+        },
+        None => None,
+    };
+
+    let role = claims.map(|c| c.role);
+    Ok(ws.on_upgrade(move |socket| {
+        handle_socket(socket, (*state.manager).clone(), user_id, role, resumed)
+    }))
 }
@@ -0,0 +1,192 @@
+use axum::Router;
+use utoipa::{
+    openapi::{
+        security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+        OpenApi as OpenApiDocument,
+    },
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::metrics;
+use crate::modules::{
+    api_config, api_keys, auth, graphql, health, maintenance, ping, users, version, webhooks,
+};
+use crate::utils::error::ErrorResponse;
+
+/// Where the generated document is served
+pub const OPENAPI_JSON_PATH: &str = "/api-docs/openapi.json";
+
+/// Swagger UI, reading `OPENAPI_JSON_PATH`
+pub const SWAGGER_UI_PATH: &str = "/swagger-ui";
+
+/// Security scheme name operations behind `auth_middleware` refer to
+pub const BEARER_AUTH: &str = "bearer_auth";
+
+/// Registers the access token scheme, so Swagger UI's "Authorize" sends
+/// `Authorization: Bearer <token>`
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut OpenApiDocument) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            BEARER_AUTH,
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("Access token from `POST /auth/login`"))
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Routes mounted in every build; feature-gated modules are merged in by
+/// `openapi`
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        auth::routes::register,
+        auth::routes::login,
+        auth::routes::refresh_token,
+        auth::routes::forgot_password,
+        auth::routes::reset_password,
+        auth::routes::verify_email,
+        auth::routes::resend_verification,
+        auth::routes::token_config,
+        auth::routes::logout,
+        auth::routes::logout_all,
+        auth::google::start,
+        auth::google::callback,
+        users::routes::get_current_user,
+        users::routes::update_current_user,
+        users::routes::delete_current_user,
+        users::routes::accept_terms,
+        users::routes::change_password,
+        users::routes::list_connections,
+        users::routes::unlink_connection,
+        users::routes::list_sessions,
+        users::routes::revoke_session,
+        users::routes::list_users,
+        users::routes::get_user_by_id,
+        users::routes::delete_user_by_id,
+        users::routes::update_user_role,
+        users::routes::suspend_user,
+        users::routes::unsuspend_user,
+        webhooks::routes::list_webhooks,
+        webhooks::routes::create_webhook,
+        webhooks::routes::get_webhook,
+        webhooks::routes::update_webhook,
+        webhooks::routes::delete_webhook,
+        webhooks::routes::list_deliveries,
+        api_keys::routes::list_api_keys,
+        api_keys::routes::revoke_api_key,
+        api_keys::routes::update_api_key,
+        maintenance::get_read_only,
+        maintenance::set_read_only,
+        health::health_check,
+        health::database_health,
+        health::liveness,
+        health::readiness,
+        health::status::status,
+        metrics::health_handler,
+        metrics::readiness_handler,
+        metrics::metrics_handler,
+        ping::ping,
+        version::version_info,
+        api_config::config_info,
+        graphql::graphql_handler,
+        graphql::graphiql,
+    ),
+    components(schemas(ErrorResponse)),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "auth", description = "Registration, sign-in and tokens"),
+        (name = "users", description = "Profiles, sessions and user management"),
+        (name = "webhooks", description = "Admin webhook subscriptions"),
+        (name = "api_keys", description = "Admin API key oversight"),
+        (name = "maintenance", description = "Admin maintenance switches"),
+        (name = "health", description = "Liveness, readiness and status"),
+        (name = "metrics", description = "Prometheus metrics"),
+        (name = "version", description = "Build information"),
+        (name = "config", description = "Public runtime configuration"),
+        (name = "graphql", description = "GraphQL endpoint"),
+    )
+)]
+pub struct ApiDoc;
+
+#[cfg(feature = "ai")]
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::modules::ai::routes::chat,
+        crate::modules::ai::routes::chat_stream,
+        crate::modules::ai::routes::generate_embedding,
+        crate::modules::ai::routes::list_models,
+        crate::modules::ai::routes::my_usage,
+        crate::modules::ai::routes::user_usage,
+    ),
+    components(schemas(crate::modules::ai::model::BatchEmbeddingResponse)),
+    tags((name = "ai", description = "Chat completions, embeddings and token usage"))
+)]
+struct AiApiDoc;
+
+#[cfg(feature = "storage")]
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::modules::storage::routes::upload_file,
+        crate::modules::storage::routes::presigned_upload,
+        crate::modules::storage::routes::confirm_upload,
+        crate::modules::storage::routes::presigned_download,
+        crate::modules::storage::routes::get_file_metadata,
+        crate::modules::storage::routes::get_thumbnail,
+        crate::modules::storage::routes::delete_file,
+    ),
+    tags((name = "storage", description = "File uploads and downloads"))
+)]
+struct StorageApiDoc;
+
+#[cfg(feature = "jobs")]
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::modules::jobs::routes::run_job,
+        crate::modules::jobs::routes::job_history,
+    ),
+    tags((name = "jobs", description = "Admin background job triggers"))
+)]
+struct JobsApiDoc;
+
+#[cfg(feature = "websocket")]
+#[derive(OpenApi)]
+#[openapi(
+    paths(crate::modules::websocket::routes::websocket_handler),
+    tags((name = "websocket", description = "Real-time messaging"))
+)]
+struct WebSocketApiDoc;
+
+/// The document for this build: `ApiDoc` plus the routes of every enabled
+/// feature
+pub fn openapi() -> OpenApiDocument {
+    #[allow(unused_mut)]
+    let mut doc = ApiDoc::openapi();
+    #[cfg(feature = "ai")]
+    doc.merge(AiApiDoc::openapi());
+    #[cfg(feature = "storage")]
+    doc.merge(StorageApiDoc::openapi());
+    #[cfg(feature = "jobs")]
+    doc.merge(JobsApiDoc::openapi());
+    #[cfg(feature = "websocket")]
+    doc.merge(WebSocketApiDoc::openapi());
+    doc
+}
+
+/// Swagger UI at `SWAGGER_UI_PATH`, serving `doc` at `OPENAPI_JSON_PATH`
+pub fn routes(doc: OpenApiDocument) -> Router {
+    SwaggerUi::new(SWAGGER_UI_PATH)
+        .url(OPENAPI_JSON_PATH, doc)
+        .into()
+}
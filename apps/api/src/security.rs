@@ -0,0 +1,135 @@
+// Security events for SIEM ingestion
+// Emitted on the `security` tracing target so they can be routed or filtered
+// (e.g. `RUST_LOG=security=warn`) independently of application logs.
+
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// Tracing target every security event is logged under
+pub const SECURITY_TARGET: &str = "security";
+
+#[derive(Debug, Clone)]
+pub enum SecurityEvent<'a> {
+    /// An account hit the failed-login limit and is temporarily locked
+    LoginLockout {
+        email: &'a str,
+        client_ip: Option<IpAddr>,
+        failed_attempts: u32,
+        locked_for_secs: u64,
+    },
+    /// A request was rejected by the global rate limiter
+    RateLimitExceeded {
+        client_ip: Option<IpAddr>,
+        method: &'a str,
+        path: &'a str,
+    },
+    /// One address collected many 401/403 responses within the window
+    RepeatedAuthFailures {
+        client_ip: IpAddr,
+        failures: u32,
+        window_secs: u64,
+    },
+    /// An already rotated refresh token was presented again, so the token
+    /// family it belongs to was revoked
+    RefreshTokenReuse {
+        user_id: Uuid,
+        family_id: Uuid,
+        revoked_tokens: u64,
+    },
+}
+
+impl SecurityEvent<'_> {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SecurityEvent::LoginLockout { .. } => "login_lockout",
+            SecurityEvent::RateLimitExceeded { .. } => "rate_limit_exceeded",
+            SecurityEvent::RepeatedAuthFailures { .. } => "repeated_auth_failures",
+            SecurityEvent::RefreshTokenReuse { .. } => "refresh_token_reuse",
+        }
+    }
+}
+
+/// Log a security event; identifiers such as emails are redacted first
+pub fn emit(event: SecurityEvent<'_>) {
+    let name = event.name();
+
+    match event {
+        SecurityEvent::LoginLockout {
+            email,
+            client_ip,
+            failed_attempts,
+            locked_for_secs,
+        } => tracing::warn!(
+            target: SECURITY_TARGET,
+            event = name,
+            email = %redact_email(email),
+            client_ip = %display_ip(client_ip),
+            failed_attempts,
+            locked_for_secs,
+            "Account locked after repeated failed logins"
+        ),
+        SecurityEvent::RateLimitExceeded {
+            client_ip,
+            method,
+            path,
+        } => tracing::warn!(
+            target: SECURITY_TARGET,
+            event = name,
+            client_ip = %display_ip(client_ip),
+            method,
+            path,
+            "Request rejected by rate limiter"
+        ),
+        SecurityEvent::RepeatedAuthFailures {
+            client_ip,
+            failures,
+            window_secs,
+        } => tracing::warn!(
+            target: SECURITY_TARGET,
+            event = name,
+            client_ip = %client_ip,
+            failures,
+            window_secs,
+            "Repeated 401/403 responses from one address"
+        ),
+        SecurityEvent::RefreshTokenReuse {
+            user_id,
+            family_id,
+            revoked_tokens,
+        } => tracing::warn!(
+            target: SECURITY_TARGET,
+            event = name,
+            user_id = %user_id,
+            family_id = %family_id,
+            revoked_tokens,
+            "Refresh token reused; token family revoked"
+        ),
+    }
+}
+
+/// Keep the first character and the domain: `alice@example.com` -> `a***@example.com`
+pub fn redact_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            let first: String = local.chars().take(1).collect();
+            format!("{}***@{}", first, domain)
+        }
+        None => "***".to_string(),
+    }
+}
+
+fn display_ip(ip: Option<IpAddr>) -> String {
+    ip.map(|ip| ip.to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_email_keeps_domain_only() {
+        assert_eq!(redact_email("alice@example.com"), "a***@example.com");
+        assert_eq!(redact_email("not-an-email"), "***");
+    }
+}
@@ -0,0 +1,107 @@
+use axum::Router;
+use std::{future::Future, io, net::SocketAddr, time::Duration};
+use tokio::{net::TcpListener, sync::oneshot};
+
+use crate::metrics::record_shutdown_drain;
+use crate::middleware::InFlightTracker;
+
+/// Resolves on Ctrl+C, or SIGTERM on Unix
+pub async fn signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Outcome of waiting for in-flight requests at shutdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrainReport {
+    pub drained: usize,
+    pub abandoned: usize,
+}
+
+/// Wait up to `grace` for in-flight requests to finish, then record and log
+/// how many completed and how many are abandoned when the server stops
+pub async fn drain(tracker: &InFlightTracker, grace: Duration) -> DrainReport {
+    let pending = tracker.in_flight();
+
+    let _ = tokio::time::timeout(grace, tracker.wait_idle()).await;
+
+    let abandoned = tracker.in_flight().min(pending);
+    let report = DrainReport {
+        drained: pending - abandoned,
+        abandoned,
+    };
+
+    record_shutdown_drain(report.drained as u64, report.abandoned as u64);
+    tracing::info!(
+        drained = report.drained,
+        abandoned = report.abandoned,
+        grace_secs = grace.as_secs_f64(),
+        "Shutdown drain complete"
+    );
+
+    report
+}
+
+/// Serve `app` until `signal` resolves, then stop accepting connections and
+/// give in-flight requests up to `grace` to finish.
+///
+/// Returns once the server has stopped, so the caller can stop background
+/// work and close the database pool; requests still running after the grace
+/// period are abandoned.
+pub async fn serve<F>(
+    listener: TcpListener,
+    app: Router,
+    signal: F,
+    in_flight: &InFlightTracker,
+    grace: Duration,
+) -> io::Result<DrainReport>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (stop_tx, stop_rx) = oneshot::channel();
+    let server = tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move {
+            signal.await;
+            let _ = stop_tx.send(());
+        })
+        .await
+    });
+
+    if stop_rx.await.is_err() {
+        // The server exited on its own; surface why
+        server.await.map_err(io::Error::other)??;
+        return Err(io::Error::other("Server stopped before a shutdown signal"));
+    }
+
+    let report = drain(in_flight, grace).await;
+    if report.abandoned > 0 {
+        // Graceful shutdown would otherwise wait for them forever
+        server.abort();
+    }
+    let _ = server.await;
+
+    Ok(report)
+}
@@ -0,0 +1,119 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::utils::error::{AppError, AppResult};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Length of the HMAC-SHA256 tag appended to every cursor
+const TAG_LEN: usize = 32;
+
+/// Opaque keyset pagination cursor over `(created_at, id)`
+///
+/// Encoded as URL-safe base64 of the keyset followed by an HMAC tag, so
+/// clients can pass cursors back but cannot forge or alter them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn new(created_at: DateTime<Utc>, id: Uuid) -> Self {
+        Self { created_at, id }
+    }
+
+    /// Encode the cursor, signing it with `key`
+    pub fn encode(&self, key: &[u8]) -> String {
+        let mut bytes = self.keyset_bytes();
+        let tag = Self::mac(key, &bytes).finalize().into_bytes();
+        bytes.extend_from_slice(&tag);
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Decode a cursor produced by `encode`, rejecting malformed or forged input
+    pub fn decode(encoded: &str, key: &[u8]) -> AppResult<Self> {
+        let invalid = || AppError::BadRequest("Invalid pagination cursor".to_string());
+
+        let bytes = URL_SAFE_NO_PAD.decode(encoded).map_err(|_| invalid())?;
+        if bytes.len() <= TAG_LEN {
+            return Err(invalid());
+        }
+
+        let (keyset, tag) = bytes.split_at(bytes.len() - TAG_LEN);
+        Self::mac(key, keyset)
+            .verify_slice(tag)
+            .map_err(|_| invalid())?;
+
+        let keyset = std::str::from_utf8(keyset).map_err(|_| invalid())?;
+        let (micros, id) = keyset.split_once(':').ok_or_else(invalid)?;
+        let created_at = micros
+            .parse()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(invalid)?;
+        let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+
+        Ok(Self { created_at, id })
+    }
+
+    fn keyset_bytes(&self) -> Vec<u8> {
+        format!("{}:{}", self.created_at.timestamp_micros(), self.id).into_bytes()
+    }
+
+    fn mac(key: &[u8], data: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+        mac.update(data);
+        mac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"cursor-test-key";
+
+    fn sample_cursor() -> Cursor {
+        let created_at = DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap();
+        Cursor::new(created_at, Uuid::new_v4())
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = sample_cursor();
+
+        let decoded = Cursor::decode(&cursor.encode(KEY), KEY).unwrap();
+
+        assert_eq!(decoded, cursor);
+    }
+
+    #[test]
+    fn test_tampered_cursor_is_rejected() {
+        let encoded = sample_cursor().encode(KEY);
+        let mut bytes = URL_SAFE_NO_PAD.decode(&encoded).unwrap();
+        bytes[0] ^= 0x01;
+        let tampered = URL_SAFE_NO_PAD.encode(bytes);
+
+        assert!(matches!(
+            Cursor::decode(&tampered, KEY),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_cursor_signed_with_other_key_is_rejected() {
+        let encoded = sample_cursor().encode(b"some-other-key");
+
+        assert!(Cursor::decode(&encoded, KEY).is_err());
+    }
+
+    #[test]
+    fn test_garbage_cursor_is_rejected() {
+        assert!(Cursor::decode("not a cursor", KEY).is_err());
+        assert!(Cursor::decode("", KEY).is_err());
+    }
+}
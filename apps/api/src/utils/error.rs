@@ -1,14 +1,144 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde::Serialize;
+use std::borrow::Cow;
 use std::fmt;
 use thiserror::Error;
+use utoipa::ToSchema;
 
 pub type AppResult<T> = Result<T, AppError>;
 
+/// Content type of RFC 7807 problem details
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Prefix of the problem `type` URI; the error code follows in kebab case
+pub const PROBLEM_TYPE_PREFIX: &str = "urn:vibe-api:error:";
+
+/// Unique constraints and the request field each one protects, used to tell
+/// clients which field conflicted on a unique violation
+pub const UNIQUE_CONSTRAINT_FIELDS: &[(&str, &str)] = &[
+    ("users_email_key", "email"),
+    ("api_keys_user_id_name_key", "name"),
+    ("api_keys_key_prefix_key", "key_prefix"),
+    ("oauth_connections_user_provider", "provider"),
+    ("oauth_connections_provider_account", "provider_user_id"),
+];
+
+/// Unique constraints reported with a specific error code instead of
+/// `ALREADY_EXISTS`, so clients can branch on the conflict without parsing
+/// the message
+pub const UNIQUE_CONSTRAINT_CODES: &[(&str, &str)] = &[("users_email_key", "DUPLICATE_EMAIL")];
+
+/// One request field that failed validation
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub rule: &'static str,
+    pub message: String,
+}
+
+/// Every invalid field of a request, so one response can report them all
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationError {
+    pub errors: Vec<FieldError>,
+}
+
+impl ValidationError {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_field(mut self, field: &str, rule: &'static str, message: &str) -> Self {
+        self.errors.push(FieldError {
+            field: field.to_string(),
+            rule,
+            message: message.to_string(),
+        });
+        self
+    }
+
+    /// Add the outcome of another check: field errors are collected, any
+    /// other error is returned as-is
+    pub fn collect(&mut self, result: AppResult<()>) -> AppResult<()> {
+        match result {
+            Ok(()) => Ok(()),
+            Err(AppError::InvalidField {
+                field,
+                rule,
+                message,
+            }) => {
+                self.errors.push(FieldError {
+                    field,
+                    rule,
+                    message,
+                });
+                Ok(())
+            }
+            Err(AppError::InvalidFields(other)) => {
+                self.errors.extend(other.errors);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// `Ok` when nothing was collected; a single failure stays an
+    /// `InvalidField` so its `details` keep naming the field and rule
+    pub fn into_result(mut self) -> AppResult<()> {
+        match self.errors.len() {
+            0 => Ok(()),
+            1 => {
+                let error = self.errors.remove(0);
+                Err(AppError::InvalidField {
+                    field: error.field,
+                    rule: error.rule,
+                    message: error.message,
+                })
+            }
+            _ => Err(AppError::InvalidFields(self)),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<&str> = self.errors.iter().map(|e| e.message.as_str()).collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+impl From<validator::ValidationErrors> for ValidationError {
+    /// Field errors sorted by field name; a field without a message gets a
+    /// generic one naming it
+    fn from(errors: validator::ValidationErrors) -> Self {
+        let mut fields: Vec<_> = errors.field_errors().into_iter().collect();
+        fields.sort_by_key(|(field, _)| *field);
+
+        let errors = fields
+            .into_iter()
+            .flat_map(|(field, errors)| {
+                errors.iter().map(move |error| FieldError {
+                    field: field.to_string(),
+                    rule: match &error.code {
+                        Cow::Borrowed(code) => code,
+                        Cow::Owned(_) => "invalid",
+                    },
+                    message: error
+                        .message
+                        .as_ref()
+                        .map(|message| message.to_string())
+                        .unwrap_or_else(|| format!("{} is invalid", field)),
+                })
+            })
+            .collect();
+
+        Self { errors }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Database error: {0}")]
@@ -23,12 +153,37 @@ pub enum AppError {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    /// A single request field broke `rule`; reported as `VALIDATION_ERROR`
+    #[error("Validation error: {message}")]
+    InvalidField {
+        field: String,
+        rule: &'static str,
+        message: String,
+    },
+
+    /// Several request fields failed validation; reported as `VALIDATION_ERROR`
+    #[error("Validation error: {0}")]
+    InvalidFields(ValidationError),
+
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("User not found")]
+    UserNotFound,
+
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    /// A unique constraint rejected the write; `field` is `None` for
+    /// constraints missing from `UNIQUE_CONSTRAINT_FIELDS`, and `code` is
+    /// `ALREADY_EXISTS` for those missing from `UNIQUE_CONSTRAINT_CODES`
+    #[error("{} already exists", .field.as_deref().unwrap_or("Resource"))]
+    AlreadyExists {
+        field: Option<String>,
+        constraint: String,
+        code: &'static str,
+    },
+
     #[error("Bad request: {0}")]
     BadRequest(String),
 
@@ -44,24 +199,177 @@ pub enum AppError {
     #[error("Rate limit exceeded")]
     RateLimitExceeded,
 
+    #[error("Account temporarily locked")]
+    AccountLocked,
+
+    #[error("Account suspended")]
+    AccountSuspended,
+
+    #[error("Service is in read-only mode")]
+    ReadOnlyMode,
+
+    #[error("Request timed out")]
+    RequestTimeout,
+
     #[error("File too large")]
     FileTooLarge,
 
+    #[error("Request body exceeds the {limit_bytes} byte limit")]
+    PayloadTooLarge { limit_bytes: usize },
+
     #[error("Unsupported media type")]
     UnsupportedMediaType,
+
+    #[error("Conversation too large: {0}")]
+    ConversationTooLarge(String),
+
+    #[error("Storage unavailable: {0}")]
+    StorageUnavailable(String),
+
+    #[error("Model not allowed: {0}")]
+    ModelNotAllowed(String),
+
+    #[error("Terms not accepted: {0}")]
+    TermsNotAccepted(String),
+
+    #[error("Signups from disposable email domains are not allowed: {0}")]
+    DisposableEmail(String),
+
+    #[error("Invalid role: {role}")]
+    InvalidRole {
+        role: String,
+        valid_roles: Vec<String>,
+    },
+
+    #[error(
+        "Unknown AI provider '{provider}'; expected one of: {}",
+        .valid_providers.join(", ")
+    )]
+    InvalidProvider {
+        provider: String,
+        valid_providers: Vec<String>,
+    },
+
+    #[error("AI provider '{0}' is not configured")]
+    ProviderNotConfigured(String),
+
+    #[error("{provider} request failed after {attempts} attempt(s): {message}")]
+    AiProviderFailed {
+        provider: String,
+        attempts: u32,
+        message: String,
+    },
+
+    #[error(
+        "Model '{model}' is not offered by {provider}; expected one of: {}",
+        .valid_models.join(", ")
+    )]
+    InvalidModel {
+        model: String,
+        provider: String,
+        valid_models: Vec<String>,
+    },
+
+    #[error("Password reset token is invalid or expired")]
+    InvalidResetToken,
+
+    #[error("Email verification token is invalid or expired")]
+    InvalidVerificationToken,
+
+    #[error("Email address has not been verified")]
+    EmailNotVerified,
+
+    #[error("Unknown query parameters: {}", .0.join(", "))]
+    UnknownQueryParameters(Vec<String>),
+
+    #[error("Idempotency-Key was already used for a different request")]
+    IdempotencyKeyReused,
+}
+
+impl AppError {
+    /// Unique violation of `constraint`, naming its field when known
+    pub fn already_exists(constraint: &str) -> Self {
+        let field = UNIQUE_CONSTRAINT_FIELDS
+            .iter()
+            .find(|(name, _)| *name == constraint)
+            .map(|(_, field)| field.to_string());
+        let code = UNIQUE_CONSTRAINT_CODES
+            .iter()
+            .find(|(name, _)| *name == constraint)
+            .map_or("ALREADY_EXISTS", |(_, code)| code);
+
+        AppError::AlreadyExists {
+            field,
+            constraint: constraint.to_string(),
+            code,
+        }
+    }
+}
+
+/// An error response as `AppError::into_response` built it, kept in the
+/// response extensions so middleware can render it in another format
+#[derive(Debug, Clone)]
+pub struct ErrorInfo {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+    /// Each invalid field of a validation error
+    pub errors: Vec<FieldError>,
+}
+
+impl ErrorInfo {
+    /// Stable problem `type`, e.g. `urn:vibe-api:error:user-not-found`
+    pub fn problem_type(&self) -> String {
+        format!(
+            "{}{}",
+            PROBLEM_TYPE_PREFIX,
+            self.code.to_ascii_lowercase().replace('_', "-")
+        )
+    }
+
+    /// The error as `application/problem+json` for a request to `instance`;
+    /// `code`, `details` and `errors` are kept as extension members
+    pub fn problem_response(&self, instance: &str) -> Response {
+        let mut problem = serde_json::json!({
+            "type": self.problem_type(),
+            "title": self.status.canonical_reason().unwrap_or("Error"),
+            "status": self.status.as_u16(),
+            "detail": self.message,
+            "instance": instance,
+            "code": self.code,
+        });
+        if let Some(details) = &self.details {
+            problem["details"] = details.clone();
+        }
+        if !self.errors.is_empty() {
+            problem["errors"] = serde_json::json!(self.errors);
+        }
+
+        (
+            self.status,
+            [(header::CONTENT_TYPE, PROBLEM_JSON)],
+            problem.to_string(),
+        )
+            .into_response()
+    }
 }
 
-#[derive(Serialize)]
-struct ErrorResponse {
+/// JSON body of every error response
+#[derive(Serialize, ToSchema)]
+pub struct ErrorResponse {
     error: ErrorDetail,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct ErrorDetail {
+    /// Stable machine-readable code, e.g. `VALIDATION_ERROR`
     code: String,
     message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     details: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<FieldError>,
 }
 
 impl IntoResponse for AppError {
@@ -87,21 +395,16 @@ impl IntoResponse for AppError {
                 "VALIDATION_ERROR",
                 self.to_string(),
             ),
-            AppError::NotFound(_) => (
-                StatusCode::NOT_FOUND,
-                "NOT_FOUND",
-                self.to_string(),
-            ),
-            AppError::Conflict(_) => (
-                StatusCode::CONFLICT,
-                "CONFLICT",
-                self.to_string(),
-            ),
-            AppError::BadRequest(_) => (
+            AppError::InvalidField { .. } | AppError::InvalidFields(_) => (
                 StatusCode::BAD_REQUEST,
-                "BAD_REQUEST",
+                "VALIDATION_ERROR",
                 self.to_string(),
             ),
+            AppError::NotFound(_) => (StatusCode::NOT_FOUND, "NOT_FOUND", self.to_string()),
+            AppError::UserNotFound => (StatusCode::NOT_FOUND, "USER_NOT_FOUND", self.to_string()),
+            AppError::Conflict(_) => (StatusCode::CONFLICT, "CONFLICT", self.to_string()),
+            AppError::AlreadyExists { code, .. } => (StatusCode::CONFLICT, *code, self.to_string()),
+            AppError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BAD_REQUEST", self.to_string()),
             AppError::InternalServer(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "INTERNAL_SERVER_ERROR",
@@ -122,16 +425,111 @@ impl IntoResponse for AppError {
                 "RATE_LIMIT_EXCEEDED",
                 "Rate limit exceeded. Please try again later.".to_string(),
             ),
+            AppError::AccountLocked => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "ACCOUNT_LOCKED",
+                "Too many failed login attempts. Please try again later.".to_string(),
+            ),
+            AppError::AccountSuspended => (
+                StatusCode::FORBIDDEN,
+                "ACCOUNT_SUSPENDED",
+                "This account has been suspended".to_string(),
+            ),
+            AppError::ReadOnlyMode => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "READ_ONLY_MODE",
+                "The service is in read-only maintenance mode; writes are temporarily disabled"
+                    .to_string(),
+            ),
+            AppError::RequestTimeout => (
+                StatusCode::REQUEST_TIMEOUT,
+                "REQUEST_TIMEOUT",
+                "The request body was not received in time".to_string(),
+            ),
             AppError::FileTooLarge => (
                 StatusCode::PAYLOAD_TOO_LARGE,
                 "FILE_TOO_LARGE",
                 "File size exceeds maximum allowed size".to_string(),
             ),
+            AppError::PayloadTooLarge { .. } => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "PAYLOAD_TOO_LARGE",
+                self.to_string(),
+            ),
             AppError::UnsupportedMediaType => (
                 StatusCode::UNSUPPORTED_MEDIA_TYPE,
                 "UNSUPPORTED_MEDIA_TYPE",
                 "Unsupported media type".to_string(),
             ),
+            AppError::ConversationTooLarge(_) => (
+                StatusCode::BAD_REQUEST,
+                "CONVERSATION_TOO_LARGE",
+                self.to_string(),
+            ),
+            AppError::StorageUnavailable(_) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "STORAGE_UNAVAILABLE",
+                "Storage is temporarily unavailable. Please try again later.".to_string(),
+            ),
+            AppError::ModelNotAllowed(_) => {
+                (StatusCode::FORBIDDEN, "MODEL_NOT_ALLOWED", self.to_string())
+            }
+            AppError::TermsNotAccepted(_) => (
+                StatusCode::FORBIDDEN,
+                "TERMS_NOT_ACCEPTED",
+                self.to_string(),
+            ),
+            AppError::DisposableEmail(_) => (
+                StatusCode::BAD_REQUEST,
+                "DISPOSABLE_EMAIL",
+                self.to_string(),
+            ),
+            AppError::InvalidRole { .. } => {
+                (StatusCode::BAD_REQUEST, "INVALID_ROLE", self.to_string())
+            }
+            AppError::InvalidProvider { .. } => (
+                StatusCode::BAD_REQUEST,
+                "INVALID_PROVIDER",
+                self.to_string(),
+            ),
+            AppError::ProviderNotConfigured(_) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "PROVIDER_NOT_CONFIGURED",
+                self.to_string(),
+            ),
+            AppError::AiProviderFailed { .. } => (
+                StatusCode::BAD_GATEWAY,
+                "AI_PROVIDER_ERROR",
+                self.to_string(),
+            ),
+            AppError::InvalidModel { .. } => {
+                (StatusCode::BAD_REQUEST, "INVALID_MODEL", self.to_string())
+            }
+            AppError::InvalidResetToken => (
+                StatusCode::BAD_REQUEST,
+                "INVALID_RESET_TOKEN",
+                self.to_string(),
+            ),
+            AppError::InvalidVerificationToken => (
+                StatusCode::BAD_REQUEST,
+                "INVALID_VERIFICATION_TOKEN",
+                self.to_string(),
+            ),
+            AppError::EmailNotVerified => (
+                StatusCode::FORBIDDEN,
+                "EMAIL_NOT_VERIFIED",
+                self.to_string(),
+            ),
+            AppError::UnknownQueryParameters(_) => (
+                StatusCode::BAD_REQUEST,
+                "UNKNOWN_QUERY_PARAMETERS",
+                self.to_string(),
+            ),
+            AppError::IdempotencyKeyReused => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "IDEMPOTENCY_KEY_REUSED",
+                self.to_string(),
+            ),
         };
 
         // Log internal errors
@@ -142,15 +540,67 @@ impl IntoResponse for AppError {
             tracing::error!("Internal error: {:?}", self);
         }
 
+        let details = match &self {
+            AppError::UnknownQueryParameters(keys) => {
+                Some(serde_json::json!({ "unknown_parameters": keys }))
+            }
+            AppError::AlreadyExists {
+                field, constraint, ..
+            } => Some(serde_json::json!({ "field": field, "constraint": constraint })),
+            AppError::InvalidField { field, rule, .. } => {
+                Some(serde_json::json!({ "field": field, "rule": rule }))
+            }
+            AppError::PayloadTooLarge { limit_bytes } => {
+                Some(serde_json::json!({ "limit_bytes": limit_bytes }))
+            }
+            AppError::InvalidRole { valid_roles, .. } => {
+                Some(serde_json::json!({ "valid_roles": valid_roles }))
+            }
+            AppError::InvalidProvider {
+                valid_providers, ..
+            } => Some(serde_json::json!({ "valid_providers": valid_providers })),
+            AppError::InvalidModel { valid_models, .. } => {
+                Some(serde_json::json!({ "valid_models": valid_models }))
+            }
+            AppError::AiProviderFailed {
+                provider, attempts, ..
+            } => Some(serde_json::json!({ "provider": provider, "attempts": attempts })),
+            _ => None,
+        };
+
+        let errors = match self {
+            AppError::InvalidField {
+                field,
+                rule,
+                message,
+            } => vec![FieldError {
+                field,
+                rule,
+                message,
+            }],
+            AppError::InvalidFields(invalid) => invalid.errors,
+            _ => Vec::new(),
+        };
+
+        let info = ErrorInfo {
+            status,
+            code,
+            message,
+            details,
+            errors,
+        };
         let body = Json(ErrorResponse {
             error: ErrorDetail {
                 code: code.to_string(),
-                message,
-                details: None,
+                message: info.message.clone(),
+                details: info.details.clone(),
+                errors: info.errors.clone(),
             },
         });
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        response.extensions_mut().insert(info);
+        response
     }
 }
 
@@ -158,17 +608,20 @@ impl IntoResponse for AppError {
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
         match err {
-            sqlx::Error::RowNotFound => {
-                AppError::NotFound("Resource not found".to_string())
+            sqlx::Error::RowNotFound => AppError::NotFound("Resource not found".to_string()),
+            sqlx::Error::PoolTimedOut => {
+                crate::metrics::record_db_pool_acquire_timeout();
+                AppError::Database(err.to_string())
             }
             sqlx::Error::Database(db_err) => {
                 // Check for unique constraint violations
                 if let Some(code) = db_err.code() {
                     if code == "23505" {
                         // PostgreSQL unique violation
-                        return AppError::Conflict(
-                            "Resource already exists".to_string()
-                        );
+                        return match db_err.constraint() {
+                            Some(constraint) => AppError::already_exists(constraint),
+                            None => AppError::Conflict("Resource already exists".to_string()),
+                        };
                     }
                 }
                 AppError::Database(db_err.to_string())
@@ -181,7 +634,10 @@ impl From<sqlx::Error> for AppError {
 // Conversion from validation errors
 impl From<validator::ValidationErrors> for AppError {
     fn from(err: validator::ValidationErrors) -> Self {
-        AppError::Validation(err.to_string())
+        match ValidationError::from(err).into_result() {
+            Err(e) => e,
+            Ok(()) => AppError::Validation("Invalid request".to_string()),
+        }
     }
 }
 
@@ -0,0 +1,82 @@
+use axum::{
+    extract::{FromRequest, FromRequestParts, Query, Request},
+    http::{header, request::Parts},
+    response::{IntoResponse, Response},
+    Form, Json,
+};
+use serde::de::DeserializeOwned;
+
+use super::error::AppError;
+
+/// Body extractor accepting either JSON or `application/x-www-form-urlencoded`
+///
+/// Form bodies are detected from `Content-Type`; everything else goes through
+/// `Json`, so JSON stays the default and its rejections are unchanged.
+pub struct JsonOrForm<T>(pub T);
+
+impl<T, S> FromRequest<S> for JsonOrForm<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_form = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+
+        if is_form {
+            let Form(value) = Form::<T>::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            Ok(Self(value))
+        } else {
+            let Json(value) = Json::<T>::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            Ok(Self(value))
+        }
+    }
+}
+
+/// Query parameter names a [`StrictQuery`] target accepts
+pub trait QueryFields {
+    const FIELDS: &'static [&'static str];
+}
+
+/// Query extractor that rejects parameters `T` does not know about
+///
+/// Opt-in replacement for `Query` on endpoints where a typo such as
+/// `?limt=10` should fail loudly instead of being ignored. Unknown keys
+/// are reported together in a single `UNKNOWN_QUERY_PARAMETERS` error.
+pub struct StrictQuery<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for StrictQuery<T>
+where
+    T: DeserializeOwned + QueryFields,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Query(pairs) = Query::<Vec<(String, String)>>::try_from_uri(&parts.uri)
+            .map_err(IntoResponse::into_response)?;
+
+        let mut unknown: Vec<String> = Vec::new();
+        for (key, _) in pairs {
+            if !T::FIELDS.contains(&key.as_str()) && !unknown.contains(&key) {
+                unknown.push(key);
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(AppError::UnknownQueryParameters(unknown).into_response());
+        }
+
+        let Query(value) =
+            Query::<T>::try_from_uri(&parts.uri).map_err(IntoResponse::into_response)?;
+        Ok(Self(value))
+    }
+}
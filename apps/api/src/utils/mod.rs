@@ -1,3 +1,6 @@
+pub mod cursor;
 pub mod error;
+pub mod extract;
 pub mod response;
+pub mod uptime;
 pub mod validation;
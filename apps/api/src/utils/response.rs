@@ -1,7 +1,8 @@
 use axum::{http::StatusCode, response::IntoResponse, Json};
 use serde::Serialize;
+use utoipa::ToSchema;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ApiResponse<T: Serialize> {
     pub success: bool,
     pub data: T,
@@ -9,19 +10,42 @@ pub struct ApiResponse<T: Serialize> {
     pub message: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct PaginatedResponse<T: Serialize> {
     pub success: bool,
     pub data: Vec<T>,
     pub pagination: PaginationMetadata,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct PaginationMetadata {
     pub page: u32,
     pub per_page: u32,
     pub total: u64,
     pub total_pages: u32,
+    /// `per_page` and the number of items skipped, for offset-based clients
+    pub limit: u32,
+    pub offset: u64,
+    /// Continue from here with `after` instead of `offset`, on lists that
+    /// support cursors; absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// A page fetched with an `after` cursor; there is no total or page number,
+/// which keyset pagination never computes
+#[derive(Serialize, ToSchema)]
+pub struct CursorPaginatedResponse<T: Serialize> {
+    pub success: bool,
+    pub data: Vec<T>,
+    pub pagination: CursorMetadata,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CursorMetadata {
+    pub limit: u32,
+    /// `None` on the last page
+    pub next_cursor: Option<String>,
 }
 
 impl<T: Serialize> ApiResponse<T> {
@@ -50,6 +74,18 @@ impl<T: Serialize> IntoResponse for ApiResponse<T> {
 
 impl<T: Serialize> PaginatedResponse<T> {
     pub fn new(data: Vec<T>, page: u32, per_page: u32, total: u64) -> Self {
+        let offset = u64::from(page.saturating_sub(1)) * u64::from(per_page);
+        Self::with_metadata(data, page, per_page, offset, total)
+    }
+
+    /// Page of `limit` items starting after `offset`; `page` is the one that
+    /// offset falls in
+    pub fn from_offset(data: Vec<T>, offset: u64, limit: u32, total: u64) -> Self {
+        let page = (offset / u64::from(limit.max(1)) + 1).min(u64::from(u32::MAX)) as u32;
+        Self::with_metadata(data, page, limit, offset, total)
+    }
+
+    fn with_metadata(data: Vec<T>, page: u32, per_page: u32, offset: u64, total: u64) -> Self {
         let total_pages = ((total as f64) / (per_page as f64)).ceil() as u32;
 
         Self {
@@ -60,9 +96,17 @@ impl<T: Serialize> PaginatedResponse<T> {
                 per_page,
                 total,
                 total_pages,
+                limit: per_page,
+                offset,
+                next_cursor: None,
             },
         }
     }
+
+    pub fn with_next_cursor(mut self, next_cursor: Option<String>) -> Self {
+        self.pagination.next_cursor = next_cursor;
+        self
+    }
 }
 
 impl<T: Serialize> IntoResponse for PaginatedResponse<T> {
@@ -71,6 +115,22 @@ impl<T: Serialize> IntoResponse for PaginatedResponse<T> {
     }
 }
 
+impl<T: Serialize> CursorPaginatedResponse<T> {
+    pub fn new(data: Vec<T>, limit: u32, next_cursor: Option<String>) -> Self {
+        Self {
+            success: true,
+            data,
+            pagination: CursorMetadata { limit, next_cursor },
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for CursorPaginatedResponse<T> {
+    fn into_response(self) -> axum::response::Response {
+        (StatusCode::OK, Json(self)).into_response()
+    }
+}
+
 pub fn created<T: Serialize>(data: T) -> impl IntoResponse {
     (StatusCode::CREATED, Json(ApiResponse::success(data)))
 }
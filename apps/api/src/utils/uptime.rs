@@ -0,0 +1,20 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+static STARTED_AT: OnceLock<Instant> = OnceLock::new();
+
+/// Record the process start; call first thing in `main`. Later calls keep
+/// the original instant.
+pub fn init() {
+    STARTED_AT.get_or_init(Instant::now);
+}
+
+/// Time since `init`, or since the first uptime query if it was never called
+pub fn uptime() -> Duration {
+    STARTED_AT.get_or_init(Instant::now).elapsed()
+}
+
+/// Whole seconds of `uptime`, as reported by the health and version endpoints
+pub fn uptime_seconds() -> u64 {
+    uptime().as_secs()
+}
@@ -1,10 +1,19 @@
+use crate::utils::error::{self, AppError, AppResult};
+use serde::{Deserialize, Deserializer};
 use validator::{Validate, ValidationError};
-use crate::utils::error::{AppError, AppResult};
 
 /// Validate a struct and convert validation errors to AppError
 pub fn validate_struct<T: Validate>(data: &T) -> AppResult<()> {
+    data.validate().map_err(AppError::from)
+}
+
+/// Field errors of a struct, to be combined with checks the derive can't
+/// express (such as `NamePolicy`) before reporting them together
+pub fn field_errors<T: Validate>(data: &T) -> error::ValidationError {
     data.validate()
-        .map_err(|e| AppError::Validation(format!("{}", e)))
+        .err()
+        .map(error::ValidationError::from)
+        .unwrap_or_default()
 }
 
 /// Custom email validator (can be used with validator crate)
@@ -20,6 +29,75 @@ pub fn validate_email(email: &str) -> Result<(), ValidationError> {
     }
 }
 
+/// Canonicalize an email address (trim + lowercase) for storage and lookup
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Serde helper applying `normalize_email` to request fields at deserialization,
+/// so validation and lookups always see the canonical form
+pub fn deserialize_email<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer).map(|email| normalize_email(&email))
+}
+
+/// Length bounds and character rules for display names
+#[derive(Debug, Clone, Copy)]
+pub struct NamePolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+}
+
+impl NamePolicy {
+    pub fn new(min_length: usize, max_length: usize) -> Self {
+        Self {
+            min_length,
+            max_length,
+        }
+    }
+
+    /// Reject names outside the length bounds (counted in characters) or
+    /// containing control characters such as newlines or NUL
+    pub fn check(&self, name: &str) -> AppResult<()> {
+        let invalid = |rule: &'static str, message: String| AppError::InvalidField {
+            field: "name".to_string(),
+            rule,
+            message,
+        };
+
+        let length = name.chars().count();
+        if length < self.min_length {
+            return Err(invalid(
+                "min_length",
+                format!("Name must be at least {} characters", self.min_length),
+            ));
+        }
+        if length > self.max_length {
+            return Err(invalid(
+                "max_length",
+                format!("Name must be at most {} characters", self.max_length),
+            ));
+        }
+        if name.chars().any(char::is_control) {
+            return Err(invalid(
+                "control_characters",
+                "Name must not contain control characters".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for NamePolicy {
+    /// 2 to 100 characters
+    fn default() -> Self {
+        Self::new(2, 100)
+    }
+}
+
 /// Custom password strength validator
 pub fn validate_password_strength(password: &str) -> Result<(), ValidationError> {
     if password.len() < 8 {
@@ -51,6 +129,12 @@ mod tests {
         assert!(validate_email("test@").is_err());
     }
 
+    #[test]
+    fn test_normalize_email() {
+        assert_eq!(normalize_email("  USER@Example.com "), "user@example.com");
+        assert_eq!(normalize_email("user@example.com"), "user@example.com");
+    }
+
     #[test]
     fn test_validate_password_strength() {
         assert!(validate_password_strength("StrongP@ss123").is_ok());
@@ -4,12 +4,10 @@
 mod common;
 
 use common::{
-    create_test_db_pool, run_migrations, clean_test_db,
-    MockAiProvider, MockAiResponse, MockStreamChunk, MockEmbedding,
-    mock_openai_response, mock_anthropic_response, mock_stream_chunks,
-    mock_embedding_vector, mock_openai_embedding_response,
-    mock_rate_limit_error, mock_invalid_key_error, mock_content_filter_error,
-    mock_model_not_found_error,
+    mock_anthropic_response, mock_content_filter_error, mock_embedding_vector,
+    mock_invalid_key_error, mock_model_not_found_error, mock_openai_embedding_response,
+    mock_openai_response, mock_rate_limit_error, mock_stream_chunks, MockAiProvider,
+    MockAiResponse, MockEmbedding, MockStreamChunk,
 };
 use serde_json::Value;
 
@@ -100,12 +98,30 @@ async fn test_ai_streaming_response() {
     let provider = MockAiProvider::new();
 
     let chunks = vec![
-        MockStreamChunk { delta: "Hello".to_string(), finish: false },
-        MockStreamChunk { delta: " there!".to_string(), finish: false },
-        MockStreamChunk { delta: " How".to_string(), finish: false },
-        MockStreamChunk { delta: " are".to_string(), finish: false },
-        MockStreamChunk { delta: " you?".to_string(), finish: false },
-        MockStreamChunk { delta: String::new(), finish: true },
+        MockStreamChunk {
+            delta: "Hello".to_string(),
+            finish: false,
+        },
+        MockStreamChunk {
+            delta: " there!".to_string(),
+            finish: false,
+        },
+        MockStreamChunk {
+            delta: " How".to_string(),
+            finish: false,
+        },
+        MockStreamChunk {
+            delta: " are".to_string(),
+            finish: false,
+        },
+        MockStreamChunk {
+            delta: " you?".to_string(),
+            finish: false,
+        },
+        MockStreamChunk {
+            delta: String::new(),
+            finish: true,
+        },
     ];
 
     provider.set_stream_chunks(chunks);
@@ -128,9 +144,10 @@ async fn test_ai_streaming_empty_response() {
     // Arrange
     let provider = MockAiProvider::new();
 
-    let chunks = vec![
-        MockStreamChunk { delta: String::new(), finish: true },
-    ];
+    let chunks = vec![MockStreamChunk {
+        delta: String::new(),
+        finish: true,
+    }];
 
     provider.set_stream_chunks(chunks);
 
@@ -211,10 +228,10 @@ async fn test_ai_embeddings_different_dimensions() {
     let provider = MockAiProvider::new();
 
     let dimensions = vec![
-        384,   // MiniLM
-        768,   // BERT
-        1536,  // OpenAI ada-002
-        3072,  // OpenAI large
+        384,  // MiniLM
+        768,  // BERT
+        1536, // OpenAI ada-002
+        3072, // OpenAI large
     ];
 
     // Act & Assert
@@ -308,12 +325,15 @@ async fn test_ai_provider_clear() {
     // Arrange
     let provider = MockAiProvider::new();
 
-    provider.set_response("test", MockAiResponse {
-        content: "test".to_string(),
-        model: "gpt-4".to_string(),
-        tokens_used: 10,
-        finish_reason: "stop".to_string(),
-    });
+    provider.set_response(
+        "test",
+        MockAiResponse {
+            content: "test".to_string(),
+            model: "gpt-4".to_string(),
+            tokens_used: 10,
+            finish_reason: "stop".to_string(),
+        },
+    );
 
     provider.chat("test", "gpt-4").ok();
     assert_eq!(provider.request_count(), 1);
@@ -384,7 +404,10 @@ async fn test_rate_limit_error_format() {
 
     // Assert
     assert_eq!(error["error"]["type"], "rate_limit_error");
-    assert!(error["error"]["message"].as_str().unwrap().contains("Rate limit"));
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Rate limit"));
 }
 
 #[tokio::test]
@@ -394,7 +417,10 @@ async fn test_invalid_key_error_format() {
 
     // Assert
     assert_eq!(error["error"]["type"], "invalid_request_error");
-    assert!(error["error"]["message"].as_str().unwrap().contains("Invalid API key"));
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Invalid API key"));
 }
 
 #[tokio::test]
@@ -404,7 +430,10 @@ async fn test_content_filter_error_format() {
 
     // Assert
     assert_eq!(error["error"]["type"], "content_policy_violation");
-    assert!(error["error"]["message"].as_str().unwrap().contains("content policy"));
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("content policy"));
 }
 
 #[tokio::test]
@@ -414,7 +443,10 @@ async fn test_model_not_found_error_format() {
 
     // Assert
     assert_eq!(error["error"]["type"], "invalid_request_error");
-    assert!(error["error"]["message"].as_str().unwrap().contains("does not exist"));
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("does not exist"));
 }
 
 #[tokio::test]
@@ -426,9 +458,8 @@ async fn test_concurrent_ai_requests() {
     let mut handles = vec![];
     for i in 0..10 {
         let provider_clone = provider.clone();
-        let handle = tokio::spawn(async move {
-            provider_clone.chat(&format!("prompt {}", i), "gpt-4")
-        });
+        let handle =
+            tokio::spawn(async move { provider_clone.chat(&format!("prompt {}", i), "gpt-4") });
         handles.push(handle);
     }
 
@@ -478,194 +509,2754 @@ async fn test_streaming_chunks_helper() {
     assert_eq!(reconstructed, text);
 }
 
-// The following tests demonstrate patterns for testing actual AI endpoints
-// They would need the full AI module setup to run
+// End-to-end tests of the AI routes. The OpenAI and Anthropic providers run
+// unmodified against a mock upstream, so request and response mapping is
+// exercised through the real SDKs.
+
+#[cfg(feature = "ai")]
+mod endpoints {
+    use async_anthropic::Client as AnthropicClient;
+    use async_openai::config::OpenAIConfig;
+    use axum::{
+        body::Body,
+        extract::State,
+        http::{Request, StatusCode},
+        response::{
+            sse::{Event, Sse},
+            IntoResponse, Response,
+        },
+        routing::post,
+        Json, Router,
+    };
+    use futures::{future, stream, StreamExt};
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use sqlx::PgPool;
+    use std::{
+        convert::Infallible,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use vibe_api::{
+        config::AiConfig,
+        middleware::rate_limit::{
+            create_rate_limiter, create_rate_limiter_per_minute, rate_limit_middleware, RateLimit,
+        },
+        modules::{
+            ai::{
+                self,
+                model::AiProvider as AiProviderKind,
+                providers::{anthropic::AnthropicProvider, openai::OpenAIProvider},
+                service::AiService,
+                AiUsageLog,
+            },
+            auth::jwt::generate_access_token,
+            users::model::UserRole,
+        },
+    };
 
-#[tokio::test]
-#[ignore] // Requires AI feature and route setup
-async fn test_ai_chat_endpoint() {
-    // This test would:
-    // 1. Create app with AI routes
-    // 2. POST to /ai/chat with { "prompt": "...", "model": "gpt-4" }
-    // 3. Assert 200 OK
-    // 4. Assert response contains AI-generated content
-    // 5. Verify token usage is tracked
+    use crate::common::{app::create_test_jwt_config, create_test_db_pool, run_migrations};
 
-    let pool = create_test_db_pool().await;
-    run_migrations(&pool).await;
-    clean_test_db(&pool).await;
+    /// Request bodies the mock upstream received, in order
+    #[derive(Clone, Default)]
+    struct Upstream {
+        requests: Arc<Mutex<Vec<Value>>>,
+    }
 
-    // Pattern established for future implementation
-    assert!(true);
-}
+    impl Upstream {
+        fn record(&self, body: &Value) {
+            self.requests.lock().unwrap().push(body.clone());
+        }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_chat_requires_authentication() {
-    // This test would:
-    // 1. POST to /ai/chat without JWT
-    // 2. Assert 401 UNAUTHORIZED
+        fn requests(&self) -> Vec<Value> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
 
-    assert!(true);
-}
+    /// Text of the last user turn of an OpenAI or Anthropic request; the
+    /// latter sends content as a list of blocks
+    fn last_user_message(body: &Value) -> String {
+        let content = body["messages"]
+            .as_array()
+            .and_then(|messages| messages.iter().rev().find(|m| m["role"] == "user"))
+            .map_or(&Value::Null, |message| &message["content"]);
+
+        match content {
+            Value::Array(blocks) => blocks
+                .iter()
+                .filter_map(|block| block["text"].as_str())
+                .collect(),
+            content => content.as_str().unwrap_or_default().to_string(),
+        }
+    }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_chat_stream_endpoint() {
-    // This test would:
-    // 1. POST to /ai/chat/stream
-    // 2. Assert 200 OK
-    // 3. Assert Content-Type: text/event-stream
-    // 4. Assert SSE format with data: chunks
-    // 5. Verify final [DONE] message
+    async fn openai_completion(
+        State(upstream): State<Upstream>,
+        Json(body): Json<Value>,
+    ) -> Json<Value> {
+        upstream.record(&body);
+        Json(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1700000000,
+            "model": body["model"],
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": format!("Echo: {}", last_user_message(&body))
+                },
+                "finish_reason": "stop",
+                "logprobs": null
+            }],
+            "usage": { "prompt_tokens": 9, "completion_tokens": 3, "total_tokens": 12 }
+        }))
+    }
 
-    assert!(true);
-}
+    /// Four-dimensional vectors whose first component is the input's index
+    async fn openai_embeddings(
+        State(upstream): State<Upstream>,
+        Json(body): Json<Value>,
+    ) -> Json<Value> {
+        upstream.record(&body);
+        let inputs = match &body["input"] {
+            Value::Array(inputs) => inputs.len(),
+            _ => 1,
+        };
+        let data: Vec<Value> = (0..inputs)
+            .map(|index| {
+                json!({
+                    "object": "embedding",
+                    "index": index,
+                    "embedding": [index as f32, 0.5, 0.25, 0.125]
+                })
+            })
+            .collect();
+
+        Json(json!({
+            "object": "list",
+            "data": data,
+            "model": body["model"],
+            "usage": { "prompt_tokens": 4, "total_tokens": 4 }
+        }))
+    }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_chat_with_openai_provider() {
-    // This test would:
-    // 1. POST with { "provider": "openai", "model": "gpt-4", "prompt": "..." }
-    // 2. Assert response format matches OpenAI structure
-    // 3. Verify usage tokens are returned
+    fn anthropic_event(event: Value) -> Result<Event, Infallible> {
+        let name = event["type"].as_str().unwrap().to_string();
+        Ok(Event::default().event(name).data(event.to_string()))
+    }
 
-    assert!(true);
-}
+    /// Messages API; a streamed reply to `hang` stops after its first delta
+    /// and never finishes, like an upstream still generating
+    async fn anthropic_messages(
+        State(upstream): State<Upstream>,
+        Json(body): Json<Value>,
+    ) -> Response {
+        upstream.record(&body);
+        let message = last_user_message(&body);
+
+        if body["stream"] != true {
+            return Json(json!({
+                "id": "msg_1",
+                "type": "message",
+                "role": "assistant",
+                "model": body["model"],
+                "content": [{ "type": "text", "text": format!("Echo: {}", message) }],
+                "stop_reason": "end_turn",
+                "stop_sequence": null,
+                "usage": { "input_tokens": 11, "output_tokens": 4 }
+            }))
+            .into_response();
+        }
+
+        let start = json!({
+            "type": "message_start",
+            "message": {
+                "id": "msg_1",
+                "model": body["model"],
+                "role": "assistant",
+                "content": [],
+                "usage": { "input_tokens": 11, "output_tokens": 1 }
+            }
+        });
+        let delta = |text: &str| {
+            json!({
+                "type": "content_block_delta",
+                "index": 0,
+                "delta": { "type": "text_delta", "text": text }
+            })
+        };
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_chat_with_anthropic_provider() {
-    // This test would:
-    // 1. POST with { "provider": "anthropic", "model": "claude-3-opus", "prompt": "..." }
-    // 2. Assert response format matches Anthropic structure
-    // 3. Verify usage tokens are returned
+        if message == "hang" {
+            let events = stream::iter([start, delta("Hel")].map(anthropic_event));
+            return Sse::new(events.chain(stream::pending())).into_response();
+        }
+
+        let events = [
+            start,
+            json!({
+                "type": "content_block_start",
+                "index": 0,
+                "content_block": { "type": "text", "text": "" }
+            }),
+            delta("Hello"),
+            delta(" there"),
+            json!({ "type": "content_block_stop", "index": 0 }),
+            json!({
+                "type": "message_delta",
+                "delta": { "stop_reason": "end_turn", "stop_sequence": null },
+                "usage": { "output_tokens": 2 }
+            }),
+            json!({ "type": "message_stop" }),
+        ];
+        Sse::new(stream::iter(events.map(anthropic_event))).into_response()
+    }
 
-    assert!(true);
-}
+    /// Mock OpenAI and Anthropic APIs; returns their base URL
+    async fn spawn_upstream(upstream: Upstream) -> String {
+        let app = Router::new()
+            .route("/chat/completions", post(openai_completion))
+            .route("/embeddings", post(openai_embeddings))
+            .route("/v1/messages", post(anthropic_messages))
+            .with_state(upstream);
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_chat_with_local_model() {
-    // This test would:
-    // 1. POST with { "provider": "local", "model": "llama-2", "prompt": "..." }
-    // 2. Assert 200 OK
-    // 3. Verify local model inference works
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
 
-    assert!(true);
-}
+        url
+    }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_chat_invalid_provider() {
-    // This test would:
-    // 1. POST with { "provider": "invalid", ... }
-    // 2. Assert 400 BAD_REQUEST
-    // 3. Assert error message about invalid provider
+    fn test_ai_config() -> AiConfig {
+        AiConfig {
+            openai_api_key: None,
+            anthropic_api_key: None,
+            default_model: "gpt-4".to_string(),
+            max_tokens: 2000,
+            temperature: 0.7,
+            max_messages: 20,
+            max_conversation_chars: 1_000,
+            max_embedding_batch_size: 100,
+            max_attempts: 1,
+            retry_base_ms: 1,
+            allowed_models_user: None,
+            allowed_models_moderator: None,
+            allowed_models_openai: None,
+            allowed_models_anthropic: None,
+            allowed_models_local: None,
+            normalize_model_names: true,
+            usage_alert_user_tokens: vec![],
+            usage_alert_global_tokens: vec![],
+            usage_alert_period_secs: 86400,
+        }
+    }
 
-    assert!(true);
+    /// Service whose OpenAI and Anthropic providers talk to `url`; the local
+    /// provider is the built-in one
+    fn service(config: AiConfig, url: &str) -> AiService {
+        let openai = OpenAIProvider::with_config(
+            OpenAIConfig::new()
+                .with_api_key("sk-test")
+                .with_api_base(url),
+            config.default_model.clone(),
+        );
+        let anthropic = AnthropicProvider::with_client(
+            AnthropicClient::builder()
+                .base_url(url)
+                .api_key("sk-ant-test")
+                .version("2023-06-01")
+                .build()
+                .unwrap(),
+            "claude-3-5-sonnet-20241022".to_string(),
+        );
+
+        AiService::new(config)
+            .with_provider(AiProviderKind::Openai, Arc::new(openai))
+            .with_provider(AiProviderKind::Anthropic, Arc::new(anthropic))
+    }
+
+    /// AI routes logging usage to `pool`, backed by a fresh mock upstream
+    async fn app_with(config: AiConfig, pool: &PgPool) -> (Router, Upstream) {
+        let upstream = Upstream::default();
+        let url = spawn_upstream(upstream.clone()).await;
+        let service = service(config, &url).with_usage_log(AiUsageLog::new(pool.clone()));
+
+        (
+            ai::service_routes(service, create_test_jwt_config()),
+            upstream,
+        )
+    }
+
+    async fn app(pool: &PgPool) -> (Router, Upstream) {
+        app_with(test_ai_config(), pool).await
+    }
+
+    async fn test_pool() -> PgPool {
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        pool
+    }
+
+    async fn insert_user(pool: &PgPool) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query("INSERT INTO users (id, email, password_hash, name) VALUES ($1, $2, $3, $4)")
+            .bind(id)
+            .bind(format!("ai_{}@example.com", id.simple()))
+            .bind("$argon2id$v=19$m=19456,t=2,p=1$test")
+            .bind("AI User")
+            .execute(pool)
+            .await
+            .expect("Failed to insert user");
+        id
+    }
+
+    fn request(method: &str, uri: &str, user_id: Uuid, body: Option<Value>) -> Request<Body> {
+        let token = generate_access_token(
+            &user_id,
+            "ai@example.com",
+            UserRole::User,
+            &create_test_jwt_config(),
+        )
+        .unwrap();
+
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {}", token))
+            .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+            .unwrap()
+    }
+
+    async fn send(app: &Router, uri: &str, user_id: Uuid, body: Value) -> Response {
+        app.clone()
+            .oneshot(request("POST", uri, user_id, Some(body)))
+            .await
+            .unwrap()
+    }
+
+    async fn json_body(response: Response) -> Value {
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// `data:` payloads of each SSE frame
+    fn sse_data(body: &str) -> Vec<String> {
+        body.split("\n\n")
+            .filter(|frame| !frame.is_empty() && !frame.starts_with(':'))
+            .map(|frame| frame.strip_prefix("data: ").unwrap().to_string())
+            .collect()
+    }
+
+    /// Usage rows of `user_id`, waiting briefly for background recording
+    async fn usage_rows(pool: &PgPool, user_id: Uuid) -> Vec<(String, String, i32, i32, i32)> {
+        for _ in 0..50 {
+            let rows = sqlx::query_as(
+                "SELECT provider, model, prompt_tokens, completion_tokens, total_tokens
+                 FROM ai_usage WHERE user_id = $1 ORDER BY created_at",
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await
+            .unwrap();
+            if !rows.is_empty() {
+                return rows;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        Vec::new()
+    }
+
+    #[tokio::test]
+    async fn test_ai_chat_endpoint() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, upstream) = app(&pool).await;
+        let user_id = insert_user(&pool).await;
+
+        // Act
+        let response = send(
+            &app,
+            "/ai/chat",
+            user_id,
+            json!({ "prompt": "Hello there" }),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["response"], "Echo: Hello there");
+        assert_eq!(body["data"]["provider"], "openai");
+        assert_eq!(body["data"]["model"], "gpt-4");
+        assert_eq!(body["data"]["tokens_used"], 12);
+        assert_eq!(upstream.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ai_chat_requires_authentication() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, upstream) = app(&pool).await;
+
+        // Act
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/ai/chat")
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({ "prompt": "hi" }).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(upstream.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ai_chat_stream_endpoint() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, _upstream) = app(&pool).await;
+        let user_id = insert_user(&pool).await;
+
+        // Act
+        let response = send(
+            &app,
+            "/ai/chat/stream",
+            user_id,
+            json!({ "provider": "anthropic", "prompt": "Say hello" }),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let frames = sse_data(std::str::from_utf8(&body).unwrap());
+
+        assert_eq!(frames.last().unwrap(), "[DONE]");
+        let chunks: Vec<Value> = frames[..frames.len() - 1]
+            .iter()
+            .map(|frame| serde_json::from_str(frame).unwrap())
+            .collect();
+        let text: String = chunks
+            .iter()
+            .map(|chunk| chunk["delta"].as_str().unwrap())
+            .collect();
+        assert_eq!(text, "Hello there");
+
+        let last = chunks.last().unwrap();
+        assert_eq!(last["finish_reason"], "end_turn");
+        assert_eq!(
+            last["usage"],
+            json!({ "prompt_tokens": 11, "completion_tokens": 2, "total_tokens": 13 })
+        );
+        assert_eq!(
+            usage_rows(&pool, user_id).await,
+            vec![(
+                "anthropic".to_string(),
+                "claude-3-5-sonnet-20241022".to_string(),
+                11,
+                2,
+                13
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ai_chat_with_openai_provider() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, upstream) = app(&pool).await;
+        let user_id = insert_user(&pool).await;
+
+        // Act
+        let response = send(
+            &app,
+            "/ai/chat",
+            user_id,
+            json!({
+                "provider": "openai",
+                "model": "gpt-3.5-turbo",
+                "system_prompt": "Be brief",
+                "prompt": "Hi",
+                "max_tokens": 50
+            }),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["data"]["response"], "Echo: Hi");
+        assert_eq!(body["data"]["provider"], "openai");
+        assert_eq!(body["data"]["model"], "gpt-3.5-turbo");
+        assert_eq!(
+            body["data"]["usage"],
+            json!({ "prompt_tokens": 9, "completion_tokens": 3, "total_tokens": 12 })
+        );
+
+        let sent = &upstream.requests()[0];
+        assert_eq!(sent["model"], "gpt-3.5-turbo");
+        assert_eq!(sent["max_tokens"], 50);
+        assert_eq!(
+            sent["messages"],
+            json!([
+                { "role": "system", "content": "Be brief" },
+                { "role": "user", "content": "Hi" }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ai_chat_with_anthropic_provider() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, upstream) = app(&pool).await;
+        let user_id = insert_user(&pool).await;
+
+        // Act
+        let response = send(
+            &app,
+            "/ai/chat",
+            user_id,
+            json!({
+                "provider": "anthropic",
+                "system_prompt": "Be brief",
+                "messages": [
+                    { "role": "system", "content": "Answer in English" },
+                    { "role": "user", "content": "Hi" },
+                    { "role": "assistant", "content": "Hello!" }
+                ],
+                "prompt": "How are you?"
+            }),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["data"]["response"], "Echo: How are you?");
+        assert_eq!(body["data"]["provider"], "anthropic");
+        assert_eq!(body["data"]["model"], "claude-3-5-sonnet-20241022");
+        assert_eq!(
+            body["data"]["usage"],
+            json!({ "prompt_tokens": 11, "completion_tokens": 4, "total_tokens": 15 })
+        );
+
+        // System turns move to `system`; Anthropic requires `max_tokens`
+        let sent = &upstream.requests()[0];
+        assert_eq!(sent["system"], "Be brief\n\nAnswer in English");
+        assert_eq!(sent["max_tokens"], 2048);
+        assert_eq!(
+            sent["messages"],
+            json!([
+                { "role": "user", "content": [{ "type": "text", "text": "Hi" }] },
+                { "role": "assistant", "content": [{ "type": "text", "text": "Hello!" }] },
+                { "role": "user", "content": [{ "type": "text", "text": "How are you?" }] }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ai_chat_with_local_model() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, upstream) = app(&pool).await;
+        let user_id = insert_user(&pool).await;
+
+        // Act
+        let response = send(
+            &app,
+            "/ai/chat",
+            user_id,
+            json!({ "provider": "local", "prompt": "Hi there" }),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["data"]["provider"], "local");
+        assert_eq!(body["data"]["model"], "local-model");
+        assert!(body["data"]["response"]
+            .as_str()
+            .unwrap()
+            .ends_with("Received message: Hi there"));
+        assert!(upstream.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ai_chat_invalid_provider() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, upstream) = app(&pool).await;
+
+        // Act
+        let response = send(
+            &app,
+            "/ai/chat",
+            Uuid::new_v4(),
+            json!({ "provider": "gemini", "prompt": "Hi" }),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = json_body(response).await;
+        assert_eq!(body["error"]["code"], "INVALID_PROVIDER");
+        assert!(upstream.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ai_chat_invalid_model() {
+        // Arrange
+        let pool = test_pool().await;
+        let config = AiConfig {
+            allowed_models_openai: Some(vec!["gpt-4".to_string(), "gpt-3.5-turbo".to_string()]),
+            ..test_ai_config()
+        };
+        let (app, upstream) = app_with(config, &pool).await;
+
+        // Act
+        let response = send(
+            &app,
+            "/ai/chat",
+            Uuid::new_v4(),
+            json!({ "provider": "openai", "model": "gpt-99", "prompt": "Hi" }),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = json_body(response).await;
+        assert_eq!(body["error"]["code"], "INVALID_MODEL");
+        assert!(upstream.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ai_embeddings_endpoint() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, upstream) = app(&pool).await;
+
+        // Act
+        let response = send(
+            &app,
+            "/ai/embeddings",
+            Uuid::new_v4(),
+            json!({ "text": "Embed me" }),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["data"]["embedding"], json!([0.0, 0.5, 0.25, 0.125]));
+        assert_eq!(body["data"]["dimensions"], 4);
+        assert_eq!(body["data"]["model"], "text-embedding-3-small");
+        assert_eq!(upstream.requests()[0]["input"], "Embed me");
+    }
+
+    #[tokio::test]
+    async fn test_ai_embeddings_batch() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, upstream) = app(&pool).await;
+
+        // Act
+        let response = send(
+            &app,
+            "/ai/embeddings",
+            Uuid::new_v4(),
+            json!({ "texts": ["first", "second", "third"] }),
+        )
+        .await;
+
+        // Assert: one upstream call, vectors in request order
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        let embeddings = body["data"]["embeddings"].as_array().unwrap();
+        assert_eq!(embeddings.len(), 3);
+        for (index, embedding) in embeddings.iter().enumerate() {
+            assert_eq!(embedding[0], index as f64);
+            assert_eq!(embedding.as_array().unwrap().len(), 4);
+        }
+        assert_eq!(body["data"]["dimensions"], 4);
+        let sent = upstream.requests();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0]["input"], json!(["first", "second", "third"]));
+    }
+
+    #[tokio::test]
+    async fn test_ai_rate_limiting() {
+        // Arrange: the AI routes behind a route limit of two a minute
+        let pool = test_pool().await;
+        let (app, upstream) = app(&pool).await;
+        let limit = RateLimit::new(create_rate_limiter(100))
+            .with_route_limit("/ai", create_rate_limiter_per_minute(2));
+        let app = app.layer(axum::middleware::from_fn_with_state(
+            limit,
+            rate_limit_middleware,
+        ));
+        let user_id = Uuid::new_v4();
+
+        // Act
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            let response = send(&app, "/ai/chat", user_id, json!({ "prompt": "Hi" })).await;
+            statuses.push(response.status());
+        }
+
+        // Assert: the limited request never reaches the provider
+        assert_eq!(
+            statuses,
+            [
+                StatusCode::OK,
+                StatusCode::OK,
+                StatusCode::TOO_MANY_REQUESTS
+            ]
+        );
+        assert_eq!(upstream.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_ai_token_usage_tracking() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, _upstream) = app(&pool).await;
+        let user_id = insert_user(&pool).await;
+
+        // Act
+        let response = send(&app, "/ai/chat", user_id, json!({ "prompt": "Hi" })).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let rows = usage_rows(&pool, user_id).await;
+        let usage = app
+            .clone()
+            .oneshot(request("GET", "/ai/usage", user_id, None))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(
+            rows,
+            vec![("openai".to_string(), "gpt-4".to_string(), 9, 3, 12)]
+        );
+        assert_eq!(usage.status(), StatusCode::OK);
+        let summary = json_body(usage).await;
+        assert_eq!(summary["data"]["requests"], 1);
+        assert_eq!(summary["data"]["prompt_tokens"], 9);
+        assert_eq!(summary["data"]["completion_tokens"], 3);
+        assert_eq!(summary["data"]["total_tokens"], 12);
+    }
+
+    #[tokio::test]
+    async fn test_ai_usage_is_broken_down_per_model() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, _upstream) = app(&pool).await;
+        let user_id = insert_user(&pool).await;
+
+        // Act
+        for model in ["gpt-4", "gpt-4", "gpt-3.5-turbo"] {
+            let response = send(
+                &app,
+                "/ai/chat",
+                user_id,
+                json!({ "model": model, "prompt": "Hi" }),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let usage = app
+            .clone()
+            .oneshot(request("GET", "/ai/usage", user_id, None))
+            .await
+            .unwrap();
+
+        // Assert
+        let summary = json_body(usage).await;
+        assert_eq!(summary["data"]["requests"], 3);
+        assert_eq!(summary["data"]["total_tokens"], 36);
+        assert_eq!(
+            summary["data"]["by_model"],
+            json!([
+                {
+                    "provider": "openai",
+                    "model": "gpt-3.5-turbo",
+                    "requests": 1,
+                    "prompt_tokens": 9,
+                    "completion_tokens": 3,
+                    "total_tokens": 12
+                },
+                {
+                    "provider": "openai",
+                    "model": "gpt-4",
+                    "requests": 2,
+                    "prompt_tokens": 18,
+                    "completion_tokens": 6,
+                    "total_tokens": 24
+                }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ai_prompt_validation() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, upstream) = app(&pool).await;
+        let user_id = Uuid::new_v4();
+
+        // Act
+        let empty = send(&app, "/ai/chat", user_id, json!({ "prompt": "" })).await;
+        let too_long = send(
+            &app,
+            "/ai/chat",
+            user_id,
+            json!({ "prompt": "a".repeat(1_001) }),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(empty.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(json_body(empty).await["error"]["code"], "VALIDATION_ERROR");
+        assert_eq!(too_long.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            json_body(too_long).await["error"]["code"],
+            "CONVERSATION_TOO_LARGE"
+        );
+        assert!(upstream.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ai_concurrent_requests_different_users() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, upstream) = app(&pool).await;
+        let mut users = Vec::new();
+        for _ in 0..3 {
+            users.push(insert_user(&pool).await);
+        }
+
+        // Act
+        let responses = future::join_all(users.iter().enumerate().map(|(i, &user_id)| {
+            let app = app.clone();
+            async move {
+                let prompt = format!("Question {}", i);
+                json_body(send(&app, "/ai/chat", user_id, json!({ "prompt": prompt })).await).await
+            }
+        }))
+        .await;
+
+        // Assert: every user got their own answer and is billed once
+        for (i, body) in responses.iter().enumerate() {
+            assert_eq!(body["data"]["response"], format!("Echo: Question {}", i));
+        }
+        for &user_id in &users {
+            assert_eq!(
+                usage_rows(&pool, user_id).await,
+                vec![("openai".to_string(), "gpt-4".to_string(), 9, 3, 12)]
+            );
+        }
+        assert_eq!(upstream.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_ai_streaming_cancellation() {
+        // Arrange
+        let pool = test_pool().await;
+        let (app, _upstream) = app(&pool).await;
+        let user_id = insert_user(&pool).await;
+        let response = send(
+            &app,
+            "/ai/chat/stream",
+            user_id,
+            json!({ "provider": "anthropic", "prompt": "hang" }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Act: read the first delta, then hang up while upstream is still open
+        let mut body = response.into_body();
+        let frame = tokio::time::timeout(Duration::from_secs(5), body.frame())
+            .await
+            .expect("first delta")
+            .unwrap()
+            .unwrap();
+        let frame = frame.into_data().unwrap();
+        assert!(std::str::from_utf8(&frame).unwrap().contains("\"Hel\""));
+        drop(body);
+
+        // Assert: upstream never reported usage, so the partial reply is
+        // recorded with an estimate
+        assert_eq!(
+            usage_rows(&pool, user_id).await,
+            vec![(
+                "anthropic".to_string(),
+                "claude-3-5-sonnet-20241022".to_string(),
+                1,
+                1,
+                2
+            )]
+        );
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_chat_invalid_model() {
-    // This test would:
-    // 1. POST with { "model": "gpt-99", ... }
-    // 2. Assert 400 BAD_REQUEST or 404 NOT_FOUND
-    // 3. Assert error message about model not found
+#[cfg(feature = "ai")]
+mod conversation_limits {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use vibe_api::{
+        config::AiConfig,
+        modules::{ai, auth::jwt::generate_access_token, users::model::UserRole},
+    };
+
+    use crate::common::app::create_test_jwt_config;
+
+    fn test_ai_config() -> AiConfig {
+        AiConfig {
+            openai_api_key: None,
+            anthropic_api_key: None,
+            default_model: "gpt-4".to_string(),
+            max_tokens: 2000,
+            temperature: 0.7,
+            max_messages: 4,
+            max_conversation_chars: 100,
+            max_embedding_batch_size: 100,
+            max_attempts: 3,
+            retry_base_ms: 500,
+            allowed_models_user: Some(vec!["local-model".to_string(), "local-small".to_string()]),
+            allowed_models_moderator: None,
+            allowed_models_openai: None,
+            allowed_models_anthropic: None,
+            allowed_models_local: Some(vec![
+                "local-model".to_string(),
+                "local-small".to_string(),
+                "local-large".to_string(),
+            ]),
+            normalize_model_names: true,
+            usage_alert_user_tokens: vec![],
+            usage_alert_global_tokens: vec![],
+            usage_alert_period_secs: 86400,
+        }
+    }
+
+    async fn post_chat_as(role: UserRole, body: Value) -> (StatusCode, Value) {
+        let jwt_config = create_test_jwt_config();
+        let token =
+            generate_access_token(&Uuid::new_v4(), "ai@example.com", role, &jwt_config).unwrap();
+
+        let response =
+            ai::service_routes(ai::service::AiService::new(test_ai_config()), jwt_config)
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/ai/chat")
+                        .header("content-type", "application/json")
+                        .header("authorization", format!("Bearer {}", token))
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn post_chat(body: Value) -> (StatusCode, Value) {
+        post_chat_as(UserRole::User, body).await
+    }
+
+    #[tokio::test]
+    async fn test_chat_accepts_conversation_within_limits() {
+        let (status, _) = post_chat(json!({
+            "provider": "local",
+            "message": "And tomorrow?",
+            "messages": [
+                { "role": "user", "content": "Weather today?" },
+                { "role": "assistant", "content": "Sunny." }
+            ]
+        }))
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_too_many_messages() {
+        let history: Vec<Value> = (0..4)
+            .map(|_| json!({ "role": "user", "content": "hi" }))
+            .collect();
+
+        let (status, body) = post_chat(json!({
+            "provider": "local",
+            "message": "hi",
+            "messages": history
+        }))
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "CONVERSATION_TOO_LARGE");
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_excessive_total_size() {
+        let (status, body) = post_chat(json!({
+            "provider": "local",
+            "system_prompt": "x".repeat(60),
+            "message": "y".repeat(60)
+        }))
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "CONVERSATION_TOO_LARGE");
+    }
+
+    #[tokio::test]
+    async fn test_chat_allows_allowlisted_model() {
+        let (status, body) = post_chat(json!({
+            "provider": "local",
+            "model": "local-small",
+            "message": "hi"
+        }))
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["model"], "local-small");
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_model_outside_role_allowlist() {
+        let (status, body) = post_chat(json!({
+            "provider": "local",
+            "model": "local-large",
+            "message": "hi"
+        }))
+        .await;
+
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"]["code"], "MODEL_NOT_ALLOWED");
+    }
+
+    #[tokio::test]
+    async fn test_chat_normalizes_provider_and_model_names() {
+        let (status, body) = post_chat(json!({
+            "provider": "  Local ",
+            "model": " Local-Small ",
+            "message": "hi"
+        }))
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["provider"], "local");
+        assert_eq!(body["data"]["model"], "local-small");
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_unknown_provider_listing_valid_options() {
+        let (status, body) = post_chat(json!({
+            "provider": "gemini",
+            "message": "hi"
+        }))
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "INVALID_PROVIDER");
+        assert_eq!(
+            body["error"]["details"]["valid_providers"],
+            json!(["openai", "anthropic", "local"])
+        );
+        let message = body["error"]["message"].as_str().unwrap();
+        assert!(message.contains("openai, anthropic, local"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_model_the_provider_does_not_offer() {
+        let (status, body) = post_chat_as(
+            UserRole::Admin,
+            json!({
+                "provider": "local",
+                "model": "gpt-4",
+                "message": "hi"
+            }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "INVALID_MODEL");
+        assert!(body["error"]["details"]["valid_models"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("local-small")));
+    }
 
-    assert!(true);
+    #[test]
+    fn test_provider_names_are_trimmed_and_case_insensitive() {
+        use vibe_api::modules::ai::model::AiProvider;
+
+        assert_eq!(AiProvider::parse("  OpenAI ").unwrap(), AiProvider::Openai);
+        assert_eq!(
+            AiProvider::parse("ANTHROPIC").unwrap(),
+            AiProvider::Anthropic
+        );
+        assert!(AiProvider::parse("open ai").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_chat_admin_bypasses_model_allowlist() {
+        let (status, _) = post_chat_as(
+            UserRole::Admin,
+            json!({
+                "provider": "local",
+                "model": "local-large",
+                "message": "hi"
+            }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[test]
+    fn test_prompt_desugars_into_a_single_user_message() {
+        use vibe_api::modules::ai::model::{ChatRequest, Message, Role};
+
+        let request: ChatRequest = serde_json::from_value(json!({ "prompt": "hi" })).unwrap();
+
+        assert_eq!(
+            request.conversation(),
+            vec![Message {
+                role: Role::User,
+                content: "hi".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_message_follows_the_messages_it_is_sent_with() {
+        use vibe_api::modules::ai::model::{ChatRequest, Role};
+
+        let request: ChatRequest = serde_json::from_value(json!({
+            "messages": [
+                { "role": "system", "content": "Be brief." },
+                { "role": "user", "content": "Weather today?" },
+                { "role": "assistant", "content": "Sunny." }
+            ],
+            "message": "And tomorrow?"
+        }))
+        .unwrap();
+
+        let roles: Vec<Role> = request.conversation().iter().map(|m| m.role).collect();
+        assert_eq!(
+            roles,
+            vec![Role::System, Role::User, Role::Assistant, Role::User]
+        );
+        assert_eq!(request.conversation()[3].content, "And tomorrow?");
+    }
+
+    #[tokio::test]
+    async fn test_chat_accepts_messages_without_a_prompt() {
+        let (status, body) = post_chat(json!({
+            "provider": "local",
+            "messages": [
+                { "role": "system", "content": "Be brief." },
+                { "role": "user", "content": "Weather today?" }
+            ]
+        }))
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["response"]
+            .as_str()
+            .unwrap()
+            .ends_with("Weather today?"));
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_an_empty_conversation() {
+        let (status, body) = post_chat(json!({ "provider": "local" })).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+        assert_eq!(body["error"]["details"]["field"], "messages");
+        assert_eq!(body["error"]["details"]["rule"], "required");
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_temperature_outside_zero_to_two() {
+        for temperature in [-0.1, 2.1] {
+            let (status, body) = post_chat(json!({
+                "provider": "local",
+                "message": "hi",
+                "temperature": temperature
+            }))
+            .await;
+
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", temperature);
+            assert_eq!(body["error"]["details"]["field"], "temperature");
+        }
+
+        let (status, _) = post_chat(json!({
+            "provider": "local",
+            "message": "hi",
+            "temperature": 2.0
+        }))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_max_tokens_over_the_configured_limit() {
+        let (status, body) = post_chat(json!({
+            "provider": "local",
+            "message": "hi",
+            "max_tokens": 2001
+        }))
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["details"]["field"], "max_tokens");
+        assert_eq!(body["error"]["details"]["rule"], "range");
+
+        let (status, _) = post_chat(json!({
+            "provider": "local",
+            "message": "hi",
+            "max_tokens": 2000
+        }))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_embeddings_endpoint() {
-    // This test would:
-    // 1. POST to /ai/embeddings with { "text": "..." }
-    // 2. Assert 200 OK
-    // 3. Assert response contains embedding vector
-    // 4. Verify vector dimensions match expected
+#[cfg(feature = "ai")]
+mod tool_calling {
+    use async_openai::types::CreateChatCompletionResponse;
+    use serde_json::json;
+    use vibe_api::modules::ai::{model::ChatRequest, providers::openai::chat_response_from};
+
+    fn openai_payload(message: serde_json::Value) -> CreateChatCompletionResponse {
+        serde_json::from_value(json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "created": 1700000000,
+            "model": "gpt-4",
+            "choices": [{
+                "index": 0,
+                "message": message,
+                "finish_reason": "tool_calls",
+                "logprobs": null
+            }],
+            "usage": { "prompt_tokens": 40, "completion_tokens": 12, "total_tokens": 52 }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_chat_request_accepts_tool_definitions() {
+        let request: ChatRequest = serde_json::from_value(json!({
+            "message": "What's the weather in Amsterdam?",
+            "tools": [{
+                "name": "get_weather",
+                "description": "Current weather for a city",
+                "parameters": {
+                    "type": "object",
+                    "properties": { "city": { "type": "string" } },
+                    "required": ["city"]
+                }
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(request.tools.len(), 1);
+        assert_eq!(request.tools[0].name, "get_weather");
+        assert_eq!(request.tools[0].parameters["required"][0], "city");
+    }
+
+    #[test]
+    fn test_chat_request_accepts_functions_alias() {
+        let request: ChatRequest = serde_json::from_value(json!({
+            "message": "hi",
+            "functions": [{ "name": "lookup" }]
+        }))
+        .unwrap();
+
+        assert_eq!(request.tools[0].name, "lookup");
+        assert_eq!(request.tools[0].parameters["type"], "object");
+    }
+
+    #[test]
+    fn test_openai_tool_call_is_surfaced() {
+        // Arrange
+        let payload = openai_payload(json!({
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{
+                "id": "call_abc",
+                "type": "function",
+                "function": {
+                    "name": "get_weather",
+                    "arguments": "{\"city\":\"Amsterdam\"}"
+                }
+            }]
+        }));
+
+        // Act
+        let response = chat_response_from(payload, "gpt-4".to_string()).unwrap();
+
+        // Assert
+        assert_eq!(response.response, "");
+        assert_eq!(response.tokens_used, Some(52));
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].id, "call_abc");
+        assert_eq!(response.tool_calls[0].name, "get_weather");
+        assert_eq!(
+            response.tool_calls[0].arguments,
+            json!({ "city": "Amsterdam" })
+        );
+        let serialized = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            serialized["tool_calls"][0]["arguments"]["city"],
+            "Amsterdam"
+        );
+    }
 
-    assert!(true);
+    #[test]
+    fn test_plain_reply_has_no_tool_calls() {
+        let payload = openai_payload(json!({ "role": "assistant", "content": "Hello!" }));
+
+        let response = chat_response_from(payload, "gpt-4".to_string()).unwrap();
+
+        assert_eq!(response.response, "Hello!");
+        let serialized = serde_json::to_value(&response).unwrap();
+        assert!(serialized.get("tool_calls").is_none());
+    }
+
+    #[test]
+    fn test_malformed_tool_arguments_are_rejected() {
+        let payload = openai_payload(json!({
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{
+                "id": "call_bad",
+                "type": "function",
+                "function": { "name": "get_weather", "arguments": "{\"city\":" }
+            }]
+        }));
+
+        assert!(chat_response_from(payload, "gpt-4".to_string()).is_err());
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_embeddings_batch() {
-    // This test would:
-    // 1. POST with { "texts": ["text1", "text2", "text3"] }
-    // 2. Assert 200 OK
-    // 3. Assert response contains array of embeddings
-    // 4. Verify all have same dimensions
+#[cfg(feature = "ai")]
+mod usage_alerts {
+    use serde_json::json;
+    use std::time::Duration;
+    use uuid::Uuid;
+    use vibe_api::modules::{ai::UsageAlerts, webhooks::WebhookService};
+
+    use crate::common::{create_test_db_pool, run_migrations};
+
+    fn daily(per_user: Vec<u64>, global: Vec<u64>) -> UsageAlerts {
+        UsageAlerts::new(per_user, global, Duration::from_secs(86_400))
+    }
 
-    assert!(true);
+    #[test]
+    fn test_crossing_user_threshold_fires_exactly_one_alert() {
+        // Arrange
+        let alerts = daily(vec![1_000], vec![]);
+        let user_id = Uuid::new_v4();
+
+        // Act
+        let below = alerts.record(user_id, 600);
+        let crossing = alerts.record(user_id, 600);
+        let beyond = alerts.record(user_id, 600);
+
+        // Assert
+        assert!(below.is_empty());
+        assert_eq!(crossing.len(), 1);
+        assert_eq!(crossing[0].user_id, Some(user_id));
+        assert_eq!(crossing[0].threshold_tokens, 1_000);
+        assert_eq!(crossing[0].tokens_used, 1_200);
+        assert!(beyond.is_empty());
+    }
+
+    #[test]
+    fn test_usage_under_thresholds_fires_no_alert() {
+        // Arrange
+        let alerts = daily(vec![1_000], vec![5_000]);
+
+        // Act
+        let fired: Vec<_> = (0..4)
+            .flat_map(|_| alerts.record(Uuid::new_v4(), 999))
+            .collect();
+
+        // Assert: each user stays under 1000 and the total under 5000
+        assert!(fired.is_empty());
+    }
+
+    #[test]
+    fn test_global_threshold_counts_all_users() {
+        // Arrange
+        let alerts = daily(vec![], vec![1_000]);
+
+        // Act
+        let first = alerts.record(Uuid::new_v4(), 700);
+        let second = alerts.record(Uuid::new_v4(), 700);
+
+        // Assert
+        assert!(first.is_empty());
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].user_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_crossed_threshold_queues_one_webhook() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let webhooks = WebhookService::new(pool.clone());
+        let webhook = webhooks
+            .create(
+                serde_json::from_value(json!({
+                    "url": "http://127.0.0.1:9/hook",
+                    "secret": "whsec_test_0123456789",
+                    "events": ["ai.usage_threshold_crossed"]
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        let alerts = daily(vec![100], vec![]).with_webhooks(webhooks);
+        let user_id = Uuid::new_v4();
+
+        // Act
+        for _ in 0..3 {
+            alerts.track(user_id, 60).await;
+        }
+
+        // Assert
+        let queued: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM webhook_deliveries WHERE webhook_id = $1::uuid",
+        )
+        .bind(&webhook.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(queued, 1);
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_rate_limiting() {
-    // This test would:
-    // 1. Make 100 rapid requests to /ai/chat
-    // 2. Assert some return 429 TOO_MANY_REQUESTS
-    // 3. Verify rate limit headers
+#[cfg(feature = "ai")]
+mod chat_streaming {
+    use async_trait::async_trait;
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        response::Response,
+    };
+    use futures::{stream, StreamExt};
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use vibe_api::{
+        config::AiConfig,
+        modules::{
+            ai::{
+                self,
+                model::{AiProvider as AiProviderKind, ChatRequest, ChatResponse, ChatStreamChunk},
+                providers::{AiProvider, ChatStream},
+                service::AiService,
+            },
+            auth::jwt::generate_access_token,
+            users::model::UserRole,
+        },
+        utils::error::{AppError, AppResult},
+    };
 
-    assert!(true);
+    use crate::common::{app::create_test_jwt_config, mock_stream_chunks, MockAiProvider};
+
+    /// Serves the mock provider's stream chunks through the real provider trait
+    struct StreamingMock(MockAiProvider);
+
+    #[async_trait]
+    impl AiProvider for StreamingMock {
+        async fn chat(&self, _request: &ChatRequest) -> AppResult<ChatResponse> {
+            Err(AppError::ExternalService("chat is not mocked".to_string()))
+        }
+
+        async fn chat_stream(&self, request: &ChatRequest) -> AppResult<ChatStream> {
+            let chunks = self
+                .0
+                .stream(request.message.as_deref().unwrap_or_default())
+                .map_err(AppError::ExternalService)?;
+
+            Ok(stream::iter(chunks.into_iter().map(|chunk| {
+                Ok(ChatStreamChunk {
+                    delta: chunk.delta,
+                    finish_reason: chunk.finish.then(|| "stop".to_string()),
+                    usage: None,
+                })
+            }))
+            .boxed())
+        }
+
+        async fn generate_embedding(
+            &self,
+            _text: &str,
+            _model: Option<String>,
+        ) -> AppResult<Vec<f32>> {
+            Err(AppError::ExternalService(
+                "embeddings are not mocked".to_string(),
+            ))
+        }
+
+        fn provider_name(&self) -> &str {
+            "local"
+        }
+    }
+
+    fn test_ai_config() -> AiConfig {
+        AiConfig {
+            openai_api_key: None,
+            anthropic_api_key: None,
+            default_model: "gpt-4".to_string(),
+            max_tokens: 2000,
+            temperature: 0.7,
+            max_messages: 20,
+            max_conversation_chars: 10_000,
+            max_embedding_batch_size: 100,
+            max_attempts: 3,
+            retry_base_ms: 500,
+            allowed_models_user: None,
+            allowed_models_moderator: None,
+            allowed_models_openai: None,
+            allowed_models_anthropic: None,
+            allowed_models_local: None,
+            normalize_model_names: true,
+            usage_alert_user_tokens: vec![],
+            usage_alert_global_tokens: vec![],
+            usage_alert_period_secs: 86400,
+        }
+    }
+
+    async fn post_stream(mock: MockAiProvider, body: Value) -> Response {
+        let jwt_config = create_test_jwt_config();
+        let token = generate_access_token(
+            &Uuid::new_v4(),
+            "ai@example.com",
+            UserRole::User,
+            &jwt_config,
+        )
+        .unwrap();
+        let service = AiService::new(test_ai_config())
+            .with_provider(AiProviderKind::Local, Arc::new(StreamingMock(mock)));
+
+        ai::service_routes(service, jwt_config)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/ai/chat/stream")
+                    .header("content-type", "application/json")
+                    .header("authorization", format!("Bearer {}", token))
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    /// `data:` payloads of each SSE frame, skipping keep-alive comments
+    fn sse_data(body: &str) -> Vec<String> {
+        body.split("\n\n")
+            .filter(|frame| !frame.is_empty() && !frame.starts_with(':'))
+            .map(|frame| {
+                frame
+                    .strip_prefix("data: ")
+                    .unwrap_or_else(|| panic!("frame without data: {:?}", frame))
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_emits_a_frame_per_delta_then_done() {
+        // Arrange
+        let mock = MockAiProvider::new();
+        mock.set_stream_chunks(mock_stream_chunks("Streaming works fine"));
+
+        // Act
+        let response = post_stream(
+            mock,
+            json!({ "provider": "local", "message": "Does streaming work?" }),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let frames = sse_data(std::str::from_utf8(&body).unwrap());
+
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames.last().unwrap(), "[DONE]");
+        let chunks: Vec<Value> = frames[..4]
+            .iter()
+            .map(|frame| serde_json::from_str(frame).unwrap())
+            .collect();
+        let text: String = chunks
+            .iter()
+            .map(|chunk| chunk["delta"].as_str().unwrap())
+            .collect();
+        assert_eq!(text, "Streaming works fine");
+        assert!(chunks[0].get("finish_reason").is_none());
+        assert_eq!(chunks[3]["finish_reason"], "stop");
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_provider_failure_is_a_json_error() {
+        // Arrange
+        let mock = MockAiProvider::new();
+        mock.set_error_mode(Some("upstream unavailable".to_string()));
+
+        // Act
+        let response = post_stream(mock, json!({ "provider": "local", "message": "hi" })).await;
+
+        // Assert: nothing was streamed, so the client gets a normal error body
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "EXTERNAL_SERVICE_ERROR");
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_rejects_tools() {
+        let response = post_stream(
+            MockAiProvider::new(),
+            json!({
+                "provider": "local",
+                "message": "hi",
+                "tools": [{ "name": "lookup" }]
+            }),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_token_usage_tracking() {
-    // This test would:
-    // 1. Make AI chat request
-    // 2. Query database for token usage record
-    // 3. Verify prompt_tokens, completion_tokens, total_tokens
+#[cfg(feature = "ai")]
+mod provider_selection {
+    use async_trait::async_trait;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use futures::{stream, StreamExt};
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use vibe_api::{
+        config::AiConfig,
+        modules::{
+            ai::{
+                self,
+                model::{AiProvider as AiProviderKind, ChatRequest, ChatResponse, ProviderModels},
+                providers::{AiProvider, ChatStream},
+                service::AiService,
+            },
+            auth::jwt::generate_access_token,
+            users::model::UserRole,
+        },
+        utils::error::{AppError, AppResult},
+    };
+
+    use crate::common::app::create_test_jwt_config;
+
+    /// Answers every chat with its own name so tests can see where a request went
+    struct NamedProvider(&'static str);
+
+    #[async_trait]
+    impl AiProvider for NamedProvider {
+        async fn chat(&self, request: &ChatRequest) -> AppResult<ChatResponse> {
+            Ok(ChatResponse {
+                response: format!("{} replied", self.0),
+                provider: self.0.to_string(),
+                model: request.model.clone().unwrap_or_default(),
+                tokens_used: None,
+                usage: None,
+                tool_calls: vec![],
+            })
+        }
+
+        async fn chat_stream(&self, _request: &ChatRequest) -> AppResult<ChatStream> {
+            Ok(stream::empty().boxed())
+        }
+
+        async fn generate_embedding(
+            &self,
+            _text: &str,
+            _model: Option<String>,
+        ) -> AppResult<Vec<f32>> {
+            Err(AppError::ExternalService(
+                "embeddings are not mocked".to_string(),
+            ))
+        }
+
+        fn provider_name(&self) -> &str {
+            self.0
+        }
+    }
+
+    fn test_ai_config(default_model: &str) -> AiConfig {
+        AiConfig {
+            openai_api_key: None,
+            anthropic_api_key: None,
+            default_model: default_model.to_string(),
+            max_tokens: 2000,
+            temperature: 0.7,
+            max_messages: 20,
+            max_conversation_chars: 10_000,
+            max_embedding_batch_size: 100,
+            max_attempts: 3,
+            retry_base_ms: 500,
+            allowed_models_user: None,
+            allowed_models_moderator: None,
+            allowed_models_openai: None,
+            allowed_models_anthropic: None,
+            allowed_models_local: None,
+            normalize_model_names: true,
+            usage_alert_user_tokens: vec![],
+            usage_alert_global_tokens: vec![],
+            usage_alert_period_secs: 86400,
+        }
+    }
+
+    /// Service with a named mock behind every provider
+    fn all_providers(default_model: &str) -> AiService {
+        AiService::new(test_ai_config(default_model))
+            .with_provider(AiProviderKind::Openai, Arc::new(NamedProvider("openai")))
+            .with_provider(
+                AiProviderKind::Anthropic,
+                Arc::new(NamedProvider("anthropic")),
+            )
+            .with_provider(AiProviderKind::Local, Arc::new(NamedProvider("local")))
+    }
+
+    async fn post_chat(service: AiService, body: Value) -> (StatusCode, Value) {
+        let jwt_config = create_test_jwt_config();
+        let token = generate_access_token(
+            &Uuid::new_v4(),
+            "ai@example.com",
+            UserRole::User,
+            &jwt_config,
+        )
+        .unwrap();
+
+        let response = ai::service_routes(service, jwt_config)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/ai/chat")
+                    .header("content-type", "application/json")
+                    .header("authorization", format!("Bearer {}", token))
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_chat_dispatches_to_the_named_provider() {
+        for provider in ["openai", "anthropic", "local"] {
+            let (status, body) = post_chat(
+                all_providers("gpt-4"),
+                json!({ "provider": provider, "message": "hi" }),
+            )
+            .await;
+
+            assert_eq!(status, StatusCode::OK, "{}", provider);
+            assert_eq!(body["data"]["provider"], provider);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_without_provider_follows_default_model() {
+        for (default_model, provider) in [
+            ("gpt-4", "openai"),
+            ("claude-3-5-haiku-20241022", "anthropic"),
+            ("local-model", "local"),
+        ] {
+            let (status, body) =
+                post_chat(all_providers(default_model), json!({ "message": "hi" })).await;
+
+            assert_eq!(status, StatusCode::OK, "{}", default_model);
+            assert_eq!(body["data"]["provider"], provider);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_unknown_provider() {
+        let (status, body) = post_chat(
+            all_providers("gpt-4"),
+            json!({ "provider": "mistral", "message": "hi" }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["code"], "INVALID_PROVIDER");
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_unconfigured_provider_is_unprocessable() {
+        // No API keys are configured, so only the local provider exists
+        let service = AiService::new(test_ai_config("gpt-4"));
+
+        let (status, body) =
+            post_chat(service, json!({ "provider": "anthropic", "message": "hi" })).await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"]["code"], "PROVIDER_NOT_CONFIGURED");
+        assert!(body["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("anthropic"));
+    }
+
+    #[tokio::test]
+    async fn test_chat_default_provider_without_key_is_unprocessable() {
+        let (status, body) = post_chat(
+            AiService::new(test_ai_config("gpt-4")),
+            json!({ "message": "hi" }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"]["code"], "PROVIDER_NOT_CONFIGURED");
+    }
+
+    #[test]
+    fn test_provider_allowlists_take_precedence_over_model_name() {
+        let models = ProviderModels {
+            anthropic: Some(vec!["house-model".to_string()]),
+            ..Default::default()
+        };
 
-    assert!(true);
+        assert_eq!(
+            models.implied_provider("house-model"),
+            AiProviderKind::Anthropic
+        );
+        assert_eq!(
+            models.implied_provider("claude-3-opus"),
+            AiProviderKind::Anthropic
+        );
+        assert_eq!(
+            models.implied_provider("local-small"),
+            AiProviderKind::Local
+        );
+        assert_eq!(models.implied_provider("gpt-4o"), AiProviderKind::Openai);
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_cost_calculation() {
-    // This test would:
-    // 1. Make requests with different models (gpt-4 vs gpt-3.5)
-    // 2. Verify cost calculation based on token usage
-    // 3. Assert gpt-4 costs more per token
+#[cfg(feature = "ai")]
+mod usage_tracking {
+    use async_trait::async_trait;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        response::Response,
+        Router,
+    };
+    use chrono::Utc;
+    use futures::{stream, StreamExt};
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use sqlx::PgPool;
+    use std::{sync::Arc, time::Duration};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use vibe_api::{
+        config::AiConfig,
+        modules::{
+            ai::{
+                self,
+                model::{
+                    AiProvider as AiProviderKind, ChatRequest, ChatResponse, ChatStreamChunk,
+                    TokenUsage,
+                },
+                providers::{AiProvider, ChatStream},
+                service::AiService,
+                AiUsageLog,
+            },
+            auth::jwt::generate_access_token,
+            users::model::UserRole,
+        },
+        utils::error::{AppError, AppResult},
+    };
+
+    use crate::common::{app::create_test_jwt_config, create_test_db_pool, run_migrations};
+
+    /// Reports fixed usage for chats; streams a few deltas and then stalls,
+    /// like an upstream still generating when the client walks away
+    struct MeteredProvider;
+
+    #[async_trait]
+    impl AiProvider for MeteredProvider {
+        async fn chat(&self, request: &ChatRequest) -> AppResult<ChatResponse> {
+            let usage = TokenUsage::new(12, 30);
+            Ok(ChatResponse {
+                response: "metered reply".to_string(),
+                provider: "local".to_string(),
+                model: request.model.clone().unwrap_or_default(),
+                tokens_used: Some(usage.total_tokens),
+                usage: Some(usage),
+                tool_calls: vec![],
+            })
+        }
+
+        async fn chat_stream(&self, _request: &ChatRequest) -> AppResult<ChatStream> {
+            let deltas = ["abcd", "efgh", "ijkl"].map(|delta| {
+                Ok(ChatStreamChunk {
+                    delta: delta.to_string(),
+                    ..Default::default()
+                })
+            });
+
+            Ok(stream::iter(deltas).chain(stream::pending()).boxed())
+        }
+
+        async fn generate_embedding(
+            &self,
+            _text: &str,
+            _model: Option<String>,
+        ) -> AppResult<Vec<f32>> {
+            Err(AppError::ExternalService(
+                "embeddings are not mocked".to_string(),
+            ))
+        }
+
+        fn provider_name(&self) -> &str {
+            "local"
+        }
+    }
+
+    fn test_ai_config() -> AiConfig {
+        AiConfig {
+            openai_api_key: None,
+            anthropic_api_key: None,
+            default_model: "local-model".to_string(),
+            max_tokens: 2000,
+            temperature: 0.7,
+            max_messages: 20,
+            max_conversation_chars: 10_000,
+            max_embedding_batch_size: 100,
+            max_attempts: 3,
+            retry_base_ms: 500,
+            allowed_models_user: None,
+            allowed_models_moderator: None,
+            allowed_models_openai: None,
+            allowed_models_anthropic: None,
+            allowed_models_local: None,
+            normalize_model_names: true,
+            usage_alert_user_tokens: vec![],
+            usage_alert_global_tokens: vec![],
+            usage_alert_period_secs: 86400,
+        }
+    }
+
+    fn app(pool: &PgPool) -> Router {
+        let service = AiService::new(test_ai_config())
+            .with_provider(AiProviderKind::Local, Arc::new(MeteredProvider))
+            .with_usage_log(AiUsageLog::new(pool.clone()));
+        ai::service_routes(service, create_test_jwt_config())
+    }
+
+    async fn insert_user(pool: &PgPool) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query("INSERT INTO users (id, email, password_hash, name) VALUES ($1, $2, $3, $4)")
+            .bind(id)
+            .bind(format!("ai_{}@example.com", id.simple()))
+            .bind("$argon2id$v=19$m=19456,t=2,p=1$test")
+            .bind("AI User")
+            .execute(pool)
+            .await
+            .expect("Failed to insert user");
+        id
+    }
+
+    async fn send(
+        app: Router,
+        method: &str,
+        uri: &str,
+        user_id: Uuid,
+        role: UserRole,
+        body: Option<Value>,
+    ) -> Response {
+        let token =
+            generate_access_token(&user_id, "ai@example.com", role, &create_test_jwt_config())
+                .unwrap();
+
+        app.oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", token))
+                .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn json_body(response: Response) -> Value {
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Usage rows of `user_id`, waiting briefly for background recording
+    async fn usage_rows(pool: &PgPool, user_id: Uuid) -> Vec<(String, String, i32, i32, i32)> {
+        for _ in 0..50 {
+            let rows = sqlx::query_as(
+                "SELECT provider, model, prompt_tokens, completion_tokens, total_tokens
+                 FROM ai_usage WHERE user_id = $1",
+            )
+            .bind(user_id)
+            .fetch_all(pool)
+            .await
+            .unwrap();
+            if !rows.is_empty() {
+                return rows;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        Vec::new()
+    }
 
-    assert!(true);
+    #[tokio::test]
+    async fn test_chat_records_reported_usage() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let user_id = insert_user(&pool).await;
+
+        // Act
+        let response = send(
+            app(&pool),
+            "POST",
+            "/ai/chat",
+            user_id,
+            UserRole::User,
+            Some(json!({ "message": "hi" })),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            usage_rows(&pool, user_id).await,
+            vec![("local".to_string(), "local-model".to_string(), 12, 30, 42)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_records_partial_usage_when_client_disconnects() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let user_id = insert_user(&pool).await;
+        let response = send(
+            app(&pool),
+            "POST",
+            "/ai/chat/stream",
+            user_id,
+            UserRole::User,
+            Some(json!({ "message": "hi" })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Act: read the three deltas, then hang up while upstream is still open
+        let mut body = response.into_body();
+        let mut received = String::new();
+        while received.matches("data: ").count() < 3 {
+            let frame = body.frame().await.unwrap().unwrap();
+            if let Ok(data) = frame.into_data() {
+                received.push_str(std::str::from_utf8(&data).unwrap());
+            }
+        }
+        drop(body);
+
+        // Assert: no usage was reported, so 12 streamed chars are estimated
+        // as 3 tokens and the 2-char prompt as 1
+        assert_eq!(
+            usage_rows(&pool, user_id).await,
+            vec![("local".to_string(), "local-model".to_string(), 1, 3, 4)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_usage_endpoint_sums_callers_usage_in_range() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let user_id = insert_user(&pool).await;
+        let other_user = insert_user(&pool).await;
+        let log = AiUsageLog::new(pool.clone());
+        log.record(user_id, "openai", "gpt-4", TokenUsage::new(10, 20))
+            .await
+            .unwrap();
+        log.record(user_id, "openai", "gpt-4", TokenUsage::new(5, 5))
+            .await
+            .unwrap();
+        log.record(
+            user_id,
+            "anthropic",
+            "claude-3-haiku",
+            TokenUsage::new(1, 2),
+        )
+        .await
+        .unwrap();
+        log.record(other_user, "openai", "gpt-4", TokenUsage::new(100, 100))
+            .await
+            .unwrap();
+        // Outside the requested range
+        log.record(user_id, "openai", "gpt-4", TokenUsage::new(50, 50))
+            .await
+            .unwrap();
+        sqlx::query(
+            "UPDATE ai_usage SET created_at = NOW() - INTERVAL '40 days'
+             WHERE user_id = $1 AND prompt_tokens = 50",
+        )
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let today = Utc::now().date_naive();
+
+        // Act
+        let response = send(
+            app(&pool),
+            "GET",
+            &format!("/ai/usage?from={}&to={}", today, today),
+            user_id,
+            UserRole::User,
+            None,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+        let summary = json_body(response).await["data"].clone();
+        assert_eq!(summary["user_id"], user_id.to_string());
+        assert_eq!(summary["requests"], 3);
+        assert_eq!(summary["prompt_tokens"], 16);
+        assert_eq!(summary["completion_tokens"], 27);
+        assert_eq!(summary["total_tokens"], 43);
+        assert_eq!(
+            summary["by_model"],
+            json!([
+                {
+                    "provider": "anthropic", "model": "claude-3-haiku", "requests": 1,
+                    "prompt_tokens": 1, "completion_tokens": 2, "total_tokens": 3
+                },
+                {
+                    "provider": "openai", "model": "gpt-4", "requests": 2,
+                    "prompt_tokens": 15, "completion_tokens": 25, "total_tokens": 40
+                }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_usage_range_must_not_be_inverted() {
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+
+        let response = send(
+            app(&pool),
+            "GET",
+            "/ai/usage?from=2026-02-01&to=2026-01-01",
+            Uuid::new_v4(),
+            UserRole::User,
+            None,
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            json_body(response).await["error"]["details"]["field"],
+            "from"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_admins_read_other_users_usage() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let user_id = insert_user(&pool).await;
+        AiUsageLog::new(pool.clone())
+            .record(user_id, "openai", "gpt-4", TokenUsage::new(3, 4))
+            .await
+            .unwrap();
+        let uri = format!("/ai/usage/users/{}", user_id);
+
+        // Act
+        let as_admin = send(
+            app(&pool),
+            "GET",
+            &uri,
+            Uuid::new_v4(),
+            UserRole::Admin,
+            None,
+        )
+        .await;
+        let as_user = send(
+            app(&pool),
+            "GET",
+            &uri,
+            Uuid::new_v4(),
+            UserRole::User,
+            None,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(as_admin.status(), StatusCode::OK);
+        let summary = json_body(as_admin).await["data"].clone();
+        assert_eq!(summary["user_id"], user_id.to_string());
+        assert_eq!(summary["total_tokens"], 7);
+        assert_eq!(as_user.status(), StatusCode::FORBIDDEN);
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_prompt_validation() {
-    // This test would:
-    // 1. POST with empty prompt
-    // 2. Assert 400 BAD_REQUEST
-    // 3. POST with prompt > max length
-    // 4. Assert 400 BAD_REQUEST with validation error
+#[cfg(feature = "ai")]
+mod batch_embeddings {
+    use async_trait::async_trait;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use vibe_api::{
+        config::AiConfig,
+        modules::{
+            ai::{
+                self,
+                model::{AiProvider as AiProviderKind, ChatRequest, ChatResponse},
+                providers::{AiProvider, ChatStream},
+                service::AiService,
+            },
+            auth::jwt::generate_access_token,
+            users::model::UserRole,
+        },
+        utils::error::{AppError, AppResult},
+    };
+
+    use crate::common::{app::create_test_jwt_config, mock_embedding_vector};
+
+    /// Embeds a text as `[len, first byte, ...]` so each vector identifies
+    /// its input; counts upstream calls
+    #[derive(Default)]
+    struct EmbeddingMock {
+        calls: AtomicUsize,
+        /// Return a short last vector from batches
+        ragged: bool,
+    }
+
+    fn embed(text: &str) -> Vec<f32> {
+        let mut vector = mock_embedding_vector(8);
+        vector[0] = text.len() as f32;
+        vector[1] = text.as_bytes()[0] as f32;
+        vector
+    }
+
+    #[async_trait]
+    impl AiProvider for EmbeddingMock {
+        async fn chat(&self, _request: &ChatRequest) -> AppResult<ChatResponse> {
+            Err(AppError::ExternalService("chat is not mocked".to_string()))
+        }
+
+        async fn chat_stream(&self, _request: &ChatRequest) -> AppResult<ChatStream> {
+            Err(AppError::ExternalService("chat is not mocked".to_string()))
+        }
+
+        async fn generate_embedding(
+            &self,
+            text: &str,
+            _model: Option<String>,
+        ) -> AppResult<Vec<f32>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(embed(text))
+        }
+
+        async fn generate_embeddings_batch(
+            &self,
+            texts: &[String],
+            _model: Option<String>,
+        ) -> AppResult<Vec<Vec<f32>>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let mut embeddings: Vec<Vec<f32>> = texts.iter().map(|text| embed(text)).collect();
+            if self.ragged {
+                embeddings.last_mut().unwrap().pop();
+            }
+            Ok(embeddings)
+        }
+
+        fn provider_name(&self) -> &str {
+            "openai"
+        }
+    }
+
+    fn test_ai_config() -> AiConfig {
+        AiConfig {
+            openai_api_key: None,
+            anthropic_api_key: None,
+            default_model: "gpt-4".to_string(),
+            max_tokens: 2000,
+            temperature: 0.7,
+            max_messages: 20,
+            max_conversation_chars: 10_000,
+            max_embedding_batch_size: 100,
+            max_attempts: 3,
+            retry_base_ms: 500,
+            allowed_models_user: None,
+            allowed_models_moderator: None,
+            allowed_models_openai: None,
+            allowed_models_anthropic: None,
+            allowed_models_local: None,
+            normalize_model_names: true,
+            usage_alert_user_tokens: vec![],
+            usage_alert_global_tokens: vec![],
+            usage_alert_period_secs: 86400,
+        }
+    }
+
+    async fn post_embeddings(provider: Arc<EmbeddingMock>, body: Value) -> (StatusCode, Value) {
+        let jwt_config = create_test_jwt_config();
+        let token = generate_access_token(
+            &Uuid::new_v4(),
+            "ai@example.com",
+            UserRole::User,
+            &jwt_config,
+        )
+        .unwrap();
+        let service =
+            AiService::new(test_ai_config()).with_provider(AiProviderKind::Openai, provider);
+
+        let response = ai::service_routes(service, jwt_config)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/ai/embeddings")
+                    .header("content-type", "application/json")
+                    .header("authorization", format!("Bearer {}", token))
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_batch_embeddings_keep_input_order_in_one_call() {
+        // Arrange
+        let provider = Arc::new(EmbeddingMock::default());
+        let texts = ["alpha", "be", "gamma-ray", "delta"];
+
+        // Act
+        let (status, body) = post_embeddings(provider.clone(), json!({ "texts": texts })).await;
+
+        // Assert
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+        let embeddings = body["data"]["embeddings"].as_array().unwrap();
+        assert_eq!(embeddings.len(), texts.len());
+        for (embedding, text) in embeddings.iter().zip(texts) {
+            assert_eq!(embedding[0], text.len() as f64);
+            assert_eq!(embedding[1], text.as_bytes()[0] as f64);
+            assert_eq!(embedding.as_array().unwrap().len(), 8);
+        }
+        assert_eq!(body["data"]["dimensions"], 8);
+        assert_eq!(body["data"]["model"], "text-embedding-3-small");
+    }
+
+    #[tokio::test]
+    async fn test_single_text_returns_one_embedding() {
+        let (status, body) = post_embeddings(
+            Arc::new(EmbeddingMock::default()),
+            json!({ "text": "hello" }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["embedding"][0], 5.0);
+        assert_eq!(body["data"]["dimensions"], 8);
+    }
+
+    #[tokio::test]
+    async fn test_batch_with_inconsistent_dimensions_is_rejected() {
+        let provider = Arc::new(EmbeddingMock {
+            ragged: true,
+            ..Default::default()
+        });
+
+        let (status, body) = post_embeddings(provider, json!({ "texts": ["a", "b"] })).await;
+
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert_eq!(body["error"]["code"], "EXTERNAL_SERVICE_ERROR");
+    }
+
+    #[tokio::test]
+    async fn test_batch_over_max_size_is_rejected() {
+        // Arrange
+        let provider = Arc::new(EmbeddingMock::default());
+        let texts: Vec<String> = (0..101).map(|i| format!("text {}", i)).collect();
+
+        // Act
+        let (status, body) = post_embeddings(provider.clone(), json!({ "texts": texts })).await;
+
+        // Assert
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["details"]["field"], "texts");
+        assert_eq!(body["error"]["details"]["rule"], "max_items");
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 0);
+    }
 
-    assert!(true);
+    #[tokio::test]
+    async fn test_text_and_texts_together_are_rejected() {
+        let (status, body) = post_embeddings(
+            Arc::new(EmbeddingMock::default()),
+            json!({ "text": "a", "texts": ["b"] }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"]["details"]["rule"], "exactly_one");
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_concurrent_requests_different_users() {
-    // This test would:
-    // 1. Create multiple test users
-    // 2. Make concurrent AI requests from different users
-    // 3. Verify all succeed without interference
-    // 4. Verify token usage tracked per user
+#[cfg(feature = "ai")]
+mod provider_retry {
+    use async_openai::config::OpenAIConfig;
+    use axum::{http::StatusCode, response::IntoResponse, routing::post, Json, Router};
+    use serde_json::{json, Value};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+    use vibe_api::{
+        modules::ai::{
+            model::ChatRequest,
+            providers::{openai::OpenAIProvider, retry::RetryPolicy, AiProvider},
+        },
+        utils::error::AppError,
+    };
 
-    assert!(true);
+    fn completion() -> Value {
+        json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1700000000,
+            "model": "gpt-4",
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": "Hello!" },
+                "finish_reason": "stop",
+                "logprobs": null
+            }],
+            "usage": { "prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7 }
+        })
+    }
+
+    /// Mock OpenAI API failing the first `failures` completions with
+    /// `status` and `error`, then answering normally; returns its base URL
+    /// and the number of completions requested so far
+    async fn spawn_upstream(
+        failures: usize,
+        status: StatusCode,
+        error: Value,
+    ) -> (String, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let app = Router::new().route(
+            "/chat/completions",
+            post(move || {
+                let counter = counter.clone();
+                let error = error.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                        (status, Json(json!({ "error": error }))).into_response()
+                    } else {
+                        Json(completion()).into_response()
+                    }
+                }
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (url, calls)
+    }
+
+    fn provider(api_base: String) -> OpenAIProvider {
+        let config = OpenAIConfig::new()
+            .with_api_key("sk-test")
+            .with_api_base(api_base);
+        OpenAIProvider::with_config(config, "gpt-4".to_string()).with_retry(RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+        })
+    }
+
+    fn request() -> ChatRequest {
+        serde_json::from_value(json!({ "message": "hi" })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_call_succeeds_after_retries() {
+        // Arrange
+        let (url, calls) = spawn_upstream(
+            2,
+            StatusCode::TOO_MANY_REQUESTS,
+            json!({
+                "message": "Rate limit reached for gpt-4",
+                "type": "requests",
+                "param": null,
+                "code": "rate_limit_exceeded"
+            }),
+        )
+        .await;
+
+        // Act
+        let response = provider(url).chat(&request()).await.unwrap();
+
+        // Assert: two retries after the first attempt
+        assert_eq!(response.response, "Hello!");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_api_key_fails_without_retry() {
+        // Arrange
+        let (url, calls) = spawn_upstream(
+            usize::MAX,
+            StatusCode::UNAUTHORIZED,
+            json!({
+                "message": "Incorrect API key provided",
+                "type": "invalid_request_error",
+                "param": null,
+                "code": "invalid_api_key"
+            }),
+        )
+        .await;
+
+        // Act
+        let error = provider(url).chat(&request()).await.unwrap_err();
+
+        // Assert
+        assert!(matches!(
+            error,
+            AppError::AiProviderFailed { ref provider, attempts: 1, .. } if provider == "openai"
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_exhaust_attempts() {
+        let (url, calls) = spawn_upstream(
+            usize::MAX,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            json!({
+                "message": "The server had an error while processing your request",
+                "type": "server_error",
+                "param": null,
+                "code": null
+            }),
+        )
+        .await;
+
+        let error = provider(url).chat(&request()).await.unwrap_err();
+
+        assert!(matches!(
+            error,
+            AppError::AiProviderFailed { attempts: 3, .. }
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_ai_streaming_cancellation() {
-    // This test would:
-    // 1. Start streaming request
-    // 2. Cancel connection mid-stream
-    // 3. Verify resources cleaned up properly
-    // 4. Verify partial token usage tracked
-
-    assert!(true);
+#[cfg(feature = "ai")]
+mod model_catalog {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use serde_json::Value;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use vibe_api::{
+        config::AiConfig,
+        modules::{
+            ai::{self, service::AiService},
+            auth::jwt::generate_access_token,
+            users::model::UserRole,
+        },
+    };
+
+    use crate::common::app::create_test_jwt_config;
+
+    /// Only the OpenAI key is set
+    fn openai_only_config() -> AiConfig {
+        AiConfig {
+            openai_api_key: Some("sk-test".to_string()),
+            anthropic_api_key: None,
+            default_model: "gpt-4".to_string(),
+            max_tokens: 2000,
+            temperature: 0.7,
+            max_messages: 20,
+            max_conversation_chars: 10_000,
+            max_embedding_batch_size: 100,
+            max_attempts: 3,
+            retry_base_ms: 500,
+            allowed_models_user: None,
+            allowed_models_moderator: None,
+            allowed_models_openai: None,
+            allowed_models_anthropic: None,
+            allowed_models_local: None,
+            normalize_model_names: true,
+            usage_alert_user_tokens: vec![],
+            usage_alert_global_tokens: vec![],
+            usage_alert_period_secs: 86400,
+        }
+    }
+
+    async fn get_models(config: AiConfig, role: UserRole) -> Vec<Value> {
+        let jwt_config = create_test_jwt_config();
+        let token =
+            generate_access_token(&Uuid::new_v4(), "ai@example.com", role, &jwt_config).unwrap();
+
+        let response = ai::service_routes(AiService::new(config), jwt_config)
+            .oneshot(
+                Request::builder()
+                    .uri("/ai/models")
+                    .header("authorization", format!("Bearer {}", token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        body["data"].as_array().unwrap().clone()
+    }
+
+    fn find<'a>(models: &'a [Value], id: &str) -> Option<&'a Value> {
+        models.iter().find(|model| model["id"] == id)
+    }
+
+    #[tokio::test]
+    async fn test_models_hide_providers_without_an_api_key() {
+        // Act
+        let models = get_models(openai_only_config(), UserRole::User).await;
+
+        // Assert
+        assert!(models.iter().all(|model| model["provider"] != "anthropic"));
+        assert!(models
+            .iter()
+            .all(|model| !model["id"].as_str().unwrap().starts_with("claude")));
+
+        let gpt4 = find(&models, "gpt-4").expect("gpt-4 is listed");
+        assert_eq!(gpt4["provider"], "openai");
+        assert_eq!(gpt4["context_window"], 8192);
+        assert_eq!(gpt4["supports_embeddings"], false);
+
+        let embeddings = find(&models, "text-embedding-3-small").unwrap();
+        assert_eq!(embeddings["supports_embeddings"], true);
+    }
+
+    #[tokio::test]
+    async fn test_models_follow_provider_and_role_allowlists() {
+        // Arrange
+        let config = AiConfig {
+            allowed_models_openai: Some(vec![
+                "gpt-4o".to_string(),
+                "gpt-4o-mini".to_string(),
+                "ft:gpt-4o:acme".to_string(),
+            ]),
+            allowed_models_user: Some(vec![
+                "gpt-4o-mini".to_string(),
+                "ft:gpt-4o:acme".to_string(),
+            ]),
+            ..openai_only_config()
+        };
+
+        // Act
+        let user = get_models(config.clone(), UserRole::User).await;
+        let admin = get_models(config, UserRole::Admin).await;
+
+        // Assert: unknown models are listed without a context window
+        let openai = |models: &[Value]| -> Vec<String> {
+            models
+                .iter()
+                .filter(|model| model["provider"] == "openai")
+                .map(|model| model["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(openai(&user), vec!["gpt-4o-mini", "ft:gpt-4o:acme"]);
+        assert_eq!(
+            openai(&admin),
+            vec!["gpt-4o", "gpt-4o-mini", "ft:gpt-4o:acme"]
+        );
+        assert!(find(&user, "ft:gpt-4o:acme").unwrap()["context_window"].is_null());
+    }
 }
@@ -0,0 +1,404 @@
+// Admin API key integration tests
+// Validates listing, revoking and rate-limit exemption of API keys across users
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use http_body_util::BodyExt;
+use sqlx::PgPool;
+use tower::ServiceExt;
+use uuid::Uuid;
+use vibe_api::modules::{
+    api_keys::{self, ApiKeyService},
+    auth::jwt::generate_access_token,
+    users::model::UserRole,
+};
+
+use common::{app, create_test_db_pool, run_migrations};
+
+async fn setup() -> (PgPool, Router) {
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+
+    let app = api_keys::admin_routes(pool.clone(), app::create_test_jwt_config());
+    (pool, app)
+}
+
+async fn insert_user(pool: &PgPool, role: UserRole) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO users (id, email, password_hash, name, role) VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(id)
+    .bind(format!("user_{}@example.com", id.simple()))
+    .bind("$argon2id$v=19$m=19456,t=2,p=1$test")
+    .bind("Test User")
+    .bind(role)
+    .execute(pool)
+    .await
+    .expect("Failed to insert user");
+    id
+}
+
+fn bearer_for(user_id: &Uuid, role: UserRole) -> String {
+    let token = generate_access_token(
+        user_id,
+        "admin@example.com",
+        role,
+        &app::create_test_jwt_config(),
+    )
+    .expect("Failed to generate token");
+    format!("Bearer {}", token)
+}
+
+#[tokio::test]
+async fn test_admin_can_list_user_api_keys() {
+    // Arrange
+    let (pool, app) = setup().await;
+    let admin_id = insert_user(&pool, UserRole::Admin).await;
+    let owner_id = insert_user(&pool, UserRole::User).await;
+    let service = ApiKeyService::new(pool.clone());
+    service.create(&owner_id, "ci").await.unwrap();
+    service.create(&owner_id, "deploy").await.unwrap();
+
+    // Act
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v1/admin/api-keys?user={}", owner_id))
+                .header("authorization", bearer_for(&admin_id, UserRole::Admin))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(json["pagination"]["total"], 2);
+    let keys = json["data"].as_array().unwrap();
+    assert_eq!(keys.len(), 2);
+    for key in keys {
+        assert_eq!(key["user_id"], owner_id.to_string());
+        assert!(key.get("key_hash").is_none(), "Hash must never be exposed");
+        assert!(key.get("key").is_none(), "Secret must never be exposed");
+    }
+}
+
+#[tokio::test]
+async fn test_admin_revoke_blocks_subsequent_auth() {
+    // Arrange
+    let (pool, app) = setup().await;
+    let admin_id = insert_user(&pool, UserRole::Admin).await;
+    let owner_id = insert_user(&pool, UserRole::User).await;
+    let service = ApiKeyService::new(pool.clone());
+    let created = service.create(&owner_id, "ci").await.unwrap();
+    assert!(service.authenticate(&created.key).await.is_ok());
+
+    // Act
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/api/v1/admin/api-keys/{}", created.api_key.id))
+                .header("authorization", bearer_for(&admin_id, UserRole::Admin))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        service.authenticate(&created.key).await.is_err(),
+        "Revoked key must no longer authenticate"
+    );
+}
+
+#[tokio::test]
+async fn test_revoke_unknown_api_key_returns_404() {
+    let (pool, app) = setup().await;
+    let admin_id = insert_user(&pool, UserRole::Admin).await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/api/v1/admin/api-keys/{}", Uuid::new_v4()))
+                .header("authorization", bearer_for(&admin_id, UserRole::Admin))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_non_admin_is_forbidden() {
+    // Arrange
+    let (pool, app) = setup().await;
+    let user_id = insert_user(&pool, UserRole::User).await;
+    let created = ApiKeyService::new(pool.clone())
+        .create(&user_id, "mine")
+        .await
+        .unwrap();
+
+    // Act
+    let list_response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/admin/api-keys")
+                .header("authorization", bearer_for(&user_id, UserRole::User))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let revoke_response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/api/v1/admin/api-keys/{}", created.api_key.id))
+                .header("authorization", bearer_for(&user_id, UserRole::User))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(list_response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(revoke_response.status(), StatusCode::FORBIDDEN);
+}
+
+// ============================================================================
+// Rate-limit exempt keys
+// ============================================================================
+
+/// App limited to one request per second, honouring exempt API keys
+fn rate_limited_app(pool: &PgPool) -> Router {
+    use axum::routing::get;
+    use vibe_api::middleware::{
+        rate_limit::{create_rate_limiter, rate_limit_middleware},
+        RateLimit,
+    };
+
+    let limit = RateLimit::new(create_rate_limiter(1))
+        .with_exempt_api_keys(ApiKeyService::new(pool.clone()));
+
+    Router::new()
+        .route("/api", get(|| async { "OK" }))
+        .layer(axum::middleware::from_fn_with_state(
+            limit,
+            rate_limit_middleware,
+        ))
+}
+
+/// Status codes of `count` consecutive requests carrying `header`
+async fn statuses(app: &Router, header: (&str, &str), count: usize) -> Vec<StatusCode> {
+    let mut statuses = Vec::new();
+    for _ in 0..count {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api")
+                    .header(header.0, header.1)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        statuses.push(response.status());
+    }
+    statuses
+}
+
+#[tokio::test]
+async fn test_admin_flags_key_as_rate_limit_exempt() {
+    // Arrange
+    let (pool, app) = setup().await;
+    let admin_id = insert_user(&pool, UserRole::Admin).await;
+    let owner_id = insert_user(&pool, UserRole::User).await;
+    let created = ApiKeyService::new(pool.clone())
+        .create(&owner_id, "billing-service")
+        .await
+        .unwrap();
+    assert!(!created.api_key.rate_limit_exempt);
+
+    // Act
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/api/v1/admin/api-keys/{}", created.api_key.id))
+                .header("authorization", bearer_for(&admin_id, UserRole::Admin))
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"rate_limit_exempt":true}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["data"]["rate_limit_exempt"], true);
+}
+
+#[tokio::test]
+async fn test_flagged_key_bypasses_rate_limit() {
+    // Arrange
+    let (pool, _) = setup().await;
+    let owner_id = insert_user(&pool, UserRole::User).await;
+    let service = ApiKeyService::new(pool.clone());
+    let created = service.create(&owner_id, "billing-service").await.unwrap();
+    let key_id: Uuid = created.api_key.id.parse().unwrap();
+    service.set_rate_limit_exempt(&key_id, true).await.unwrap();
+    let app = rate_limited_app(&pool);
+
+    // Act
+    let statuses = statuses(&app, (api_keys::API_KEY_HEADER, &created.key), 4).await;
+
+    // Assert
+    assert_eq!(statuses, vec![StatusCode::OK; 4]);
+}
+
+#[tokio::test]
+async fn test_rate_limiter_does_not_record_key_use() {
+    // Arrange
+    let (pool, _) = setup().await;
+    let owner_id = insert_user(&pool, UserRole::User).await;
+    let service = ApiKeyService::new(pool.clone());
+    let created = service.create(&owner_id, "billing-service").await.unwrap();
+    let key_id: Uuid = created.api_key.id.parse().unwrap();
+    service.set_rate_limit_exempt(&key_id, true).await.unwrap();
+    let app = rate_limited_app(&pool);
+
+    // Act: the first request is within the limit, the rest need the exemption
+    let statuses = statuses(&app, (api_keys::API_KEY_HEADER, &created.key), 3).await;
+
+    // Assert
+    assert_eq!(statuses, vec![StatusCode::OK; 3]);
+    let last_used_at: Option<chrono::DateTime<chrono::Utc>> =
+        sqlx::query_scalar("SELECT last_used_at FROM api_keys WHERE id = $1")
+            .bind(key_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(last_used_at.is_none());
+}
+
+#[tokio::test]
+async fn test_normal_key_is_rate_limited() {
+    let (pool, _) = setup().await;
+    let owner_id = insert_user(&pool, UserRole::User).await;
+    let created = ApiKeyService::new(pool.clone())
+        .create(&owner_id, "public-client")
+        .await
+        .unwrap();
+    let app = rate_limited_app(&pool);
+
+    let statuses = statuses(&app, (api_keys::API_KEY_HEADER, &created.key), 2).await;
+
+    assert_eq!(
+        statuses,
+        vec![StatusCode::OK, StatusCode::TOO_MANY_REQUESTS]
+    );
+}
+
+#[tokio::test]
+async fn test_jwt_and_forged_exempt_key_are_rate_limited() {
+    // Arrange
+    let (pool, _) = setup().await;
+    let owner_id = insert_user(&pool, UserRole::Admin).await;
+    let service = ApiKeyService::new(pool.clone());
+    let created = service.create(&owner_id, "billing-service").await.unwrap();
+    let key_id: Uuid = created.api_key.id.parse().unwrap();
+    service.set_rate_limit_exempt(&key_id, true).await.unwrap();
+    // Right prefix, wrong secret
+    let forged = format!(
+        "{}_{}_{}",
+        "vk",
+        created.api_key.key_prefix,
+        Uuid::new_v4().simple()
+    );
+    let bearer = bearer_for(&owner_id, UserRole::Admin);
+
+    // Act
+    let jwt_statuses = statuses(&rate_limited_app(&pool), ("authorization", &bearer), 2).await;
+    let forged_statuses = statuses(
+        &rate_limited_app(&pool),
+        (api_keys::API_KEY_HEADER, &forged),
+        2,
+    )
+    .await;
+
+    // Assert
+    assert_eq!(
+        jwt_statuses,
+        vec![StatusCode::OK, StatusCode::TOO_MANY_REQUESTS]
+    );
+    assert_eq!(
+        forged_statuses,
+        vec![StatusCode::OK, StatusCode::TOO_MANY_REQUESTS]
+    );
+}
+
+#[tokio::test]
+async fn test_duplicate_api_key_name_returns_structured_conflict() {
+    use axum::response::IntoResponse;
+
+    // Arrange
+    let (pool, _) = setup().await;
+    let owner_id = insert_user(&pool, UserRole::User).await;
+    let service = ApiKeyService::new(pool);
+    service.create(&owner_id, "ci").await.unwrap();
+
+    // Act
+    let error = service.create(&owner_id, "ci").await.unwrap_err();
+
+    // Assert
+    let response = error.into_response();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"]["code"], "ALREADY_EXISTS");
+    assert_eq!(json["error"]["details"]["field"], "name");
+    assert_eq!(
+        json["error"]["details"]["constraint"],
+        "api_keys_user_id_name_key"
+    );
+}
+
+#[tokio::test]
+async fn test_revoked_api_key_name_can_be_reused() {
+    // Arrange
+    let (pool, _) = setup().await;
+    let owner_id = insert_user(&pool, UserRole::User).await;
+    let service = ApiKeyService::new(pool);
+    let first = service.create(&owner_id, "ci").await.unwrap();
+    service
+        .revoke(&Uuid::parse_str(&first.api_key.id).unwrap())
+        .await
+        .unwrap();
+
+    // Act
+    let second = service.create(&owner_id, "ci").await;
+
+    // Assert
+    assert!(second.is_ok());
+}
@@ -7,6 +7,7 @@ use axum::{
     body::Body,
     http::{Request, StatusCode},
 };
+use http_body_util::BodyExt;
 use serde_json::json;
 use tower::ServiceExt;
 
@@ -38,7 +39,7 @@ async fn test_user_registration_success() {
 
     assert_eq!(response.status(), StatusCode::CREATED);
 
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
     let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
 
     assert!(json["success"].as_bool().unwrap());
@@ -197,7 +198,7 @@ async fn test_user_login_success() {
 
     assert_eq!(response.status(), StatusCode::OK);
 
-    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
     let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
 
     assert!(json["success"].as_bool().unwrap());
@@ -277,3 +278,1788 @@ async fn test_user_login_nonexistent_user() {
 
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 }
+
+#[tokio::test]
+async fn test_user_login_normalizes_email() {
+    let db_pool = create_test_db().await;
+    let app = common::create_test_app(db_pool).await;
+    let local_part = format!("user_{}", uuid::Uuid::new_v4().simple());
+
+    // Register with the canonical address
+    let register_response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/auth/register")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({
+                        "email": format!("{}@example.com", local_part),
+                        "password": TEST_PASSWORD,
+                        "name": TEST_NAME
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(register_response.status(), StatusCode::CREATED);
+
+    // Login with surrounding whitespace and mixed case
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/auth/login")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({
+                        "email": format!("  {}@Example.com ", local_part.to_uppercase()),
+                        "password": TEST_PASSWORD
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(
+        json["data"]["user"]["email"],
+        format!("{}@example.com", local_part)
+    );
+}
+
+async fn register_with(
+    app: axum::Router,
+    content_type: &str,
+    body: String,
+) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/auth/register")
+                .header("content-type", content_type)
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_user_registration_accepts_json_and_form_bodies() {
+    let db_pool = create_test_db().await;
+    let app = common::create_test_app(db_pool).await;
+    let json_local = format!("json_{}", uuid::Uuid::new_v4().simple());
+    let form_local = format!("form_{}", uuid::Uuid::new_v4().simple());
+
+    let (json_status, json_body) = register_with(
+        app.clone(),
+        "application/json",
+        json!({
+            "email": format!("{}@example.com", json_local),
+            "password": TEST_PASSWORD,
+            "name": TEST_NAME,
+            "role": "user"
+        })
+        .to_string(),
+    )
+    .await;
+    let (form_status, form_body) = register_with(
+        app,
+        "application/x-www-form-urlencoded",
+        format!(
+            "email={}%40example.com&password=TestPassword123%21&name=Test+User&role=user",
+            form_local
+        ),
+    )
+    .await;
+
+    assert_eq!(json_status, StatusCode::CREATED);
+    assert_eq!(form_status, json_status);
+    assert_eq!(
+        form_body["data"]["user"]["email"],
+        format!("{}@example.com", form_local)
+    );
+    for field in ["name", "role"] {
+        assert_eq!(
+            form_body["data"]["user"][field],
+            json_body["data"]["user"][field]
+        );
+    }
+    assert_eq!(
+        form_body["data"]["token_type"],
+        json_body["data"]["token_type"]
+    );
+    assert!(form_body["data"]["access_token"].is_string());
+}
+
+#[tokio::test]
+async fn test_user_registration_rejects_unsupported_content_type() {
+    let db_pool = create_test_db().await;
+    let app = common::create_test_app(db_pool).await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/auth/register")
+                .header("content-type", "text/plain")
+                .body(Body::from("email=a@example.com"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn test_user_registration_rejects_invalid_role() {
+    let db_pool = create_test_db().await;
+    let app = common::create_test_app(db_pool).await;
+
+    let (status, body) = register_with(
+        app,
+        "application/json",
+        json!({
+            "email": format!("role_{}@example.com", uuid::Uuid::new_v4().simple()),
+            "password": TEST_PASSWORD,
+            "name": TEST_NAME,
+            "role": "superuser"
+        })
+        .to_string(),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "INVALID_ROLE");
+    assert_eq!(
+        body["error"]["details"]["valid_roles"],
+        json!(["user", "admin", "moderator"])
+    );
+}
+
+/// A refresh token for a session that began `session_age_days` ago; the token itself
+/// was issued yesterday and is valid for another day
+fn refresh_token_for_session(user: &serde_json::Value, session_age_days: i64) -> String {
+    use vibe_api::modules::auth::jwt::{Claims, TokenType};
+
+    let config = common::app::create_test_jwt_config();
+    let now = chrono::Utc::now().timestamp();
+    let claims = Claims {
+        sub: user["id"].as_str().unwrap().to_string(),
+        email: user["email"].as_str().unwrap().to_string(),
+        role: vibe_api::modules::users::model::UserRole::User,
+        exp: now + 86_400,
+        iat: now - 86_400,
+        iss: config.issuer.clone(),
+        token_type: TokenType::Refresh,
+        auth_time: Some(now - session_age_days * 86_400),
+        jti: Some(uuid::Uuid::new_v4().to_string()),
+    };
+
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(config.secret.as_bytes()),
+    )
+    .unwrap()
+}
+
+async fn post_refresh(app: axum::Router, refresh_token: &str) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/auth/refresh")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({ "refresh_token": refresh_token }).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_refresh_keeps_original_session_start() {
+    use vibe_api::modules::auth::jwt::validate_refresh_token;
+
+    // Arrange
+    let db_pool = create_test_db().await;
+    let app = common::create_test_app(db_pool).await;
+    let email = format!("session_{}@example.com", uuid::Uuid::new_v4().simple());
+    let (status, registered) = register_with(
+        app.clone(),
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let config = common::app::create_test_jwt_config();
+    let original = validate_refresh_token(
+        registered["data"]["refresh_token"].as_str().unwrap(),
+        &config,
+    )
+    .unwrap();
+
+    // Act
+    let (status, refreshed) =
+        post_refresh(app, registered["data"]["refresh_token"].as_str().unwrap()).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK);
+    let rotated = validate_refresh_token(
+        refreshed["data"]["refresh_token"].as_str().unwrap(),
+        &config,
+    )
+    .unwrap();
+    assert!(original.auth_time.is_some());
+    assert_eq!(rotated.auth_time, original.auth_time);
+}
+
+/// Record `token` as issued, as login does, so refresh accepts it
+async fn store_issued_refresh_token(pool: &sqlx::PgPool, user: &serde_json::Value, token: &str) {
+    use sha2::{Digest, Sha256};
+
+    sqlx::query(
+        r#"
+        INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4, NOW() + INTERVAL '1 day')
+        "#,
+    )
+    .bind(uuid::Uuid::new_v4())
+    .bind(uuid::Uuid::parse_str(user["id"].as_str().unwrap()).unwrap())
+    .bind(uuid::Uuid::new_v4())
+    .bind(hex::encode(Sha256::digest(token.as_bytes())))
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_refresh_within_session_lifetime_succeeds() {
+    let db_pool = create_test_db().await;
+    let app = common::create_test_app(db_pool.clone()).await;
+    let email = format!("session_{}@example.com", uuid::Uuid::new_v4().simple());
+    let (_, registered) = register_with(
+        app.clone(),
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+
+    let token = refresh_token_for_session(&registered["data"]["user"], 29);
+    store_issued_refresh_token(&db_pool, &registered["data"]["user"], &token).await;
+    let (status, _) = post_refresh(app, &token).await;
+
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_refresh_rejected_after_session_lifetime() {
+    // Arrange: the refresh token is unexpired but the session is 31 days old
+    let db_pool = create_test_db().await;
+    let app = common::create_test_app(db_pool).await;
+    let email = format!("session_{}@example.com", uuid::Uuid::new_v4().simple());
+    let (_, registered) = register_with(
+        app.clone(),
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    let token = refresh_token_for_session(&registered["data"]["user"], 31);
+
+    // Act
+    let (status, body) = post_refresh(app, &token).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Session expired"));
+}
+
+#[tokio::test]
+async fn test_auth_config_reports_configured_token_parameters() {
+    // Arrange
+    let config = vibe_api::config::JwtConfig {
+        secret: "auth-config-test-secret-never-exposed".to_string(),
+        access_token_expiry_hours: 2,
+        refresh_token_expiry_days: 7,
+        issuer: "auth-config-test".to_string(),
+        max_session_days: 14,
+    };
+    let app = vibe_api::modules::auth::routes(
+        create_test_db().await,
+        config.clone(),
+        None,
+        vibe_api::modules::auth::LoginLockout::default(),
+        vibe_api::modules::auth::SignupGuard::default(),
+        vibe_api::utils::validation::NamePolicy::default(),
+        vibe_api::events::EventBus::default(),
+    );
+
+    // Act
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/auth/config")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(
+        !String::from_utf8_lossy(&body).contains(&config.secret),
+        "Signing secret must never be exposed"
+    );
+
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        json["data"],
+        json!({
+            "issuer": "auth-config-test",
+            "algorithm": "HS256",
+            "access_token_expiry_seconds": 2 * 3600,
+            "refresh_token_expiry_seconds": 7 * 86_400,
+            "max_session_seconds": 14 * 86_400,
+        })
+    );
+}
+
+async fn login_with(app: axum::Router, email: &str, password: &str) -> StatusCode {
+    login_from(app, "203.0.113.70:1000", email, password).await
+}
+
+/// Attempt a login from the `peer` address
+async fn login_from(app: axum::Router, peer: &str, email: &str, password: &str) -> StatusCode {
+    let peer: std::net::SocketAddr = peer.parse().unwrap();
+    app.oneshot(
+        Request::builder()
+            .method("POST")
+            .uri("/auth/login")
+            .header("content-type", "application/json")
+            .extension(axum::extract::ConnectInfo(peer))
+            .body(Body::from(
+                json!({ "email": email, "password": password }).to_string(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+    .status()
+}
+
+#[tokio::test]
+async fn test_repeated_failed_logins_lock_account_and_emit_security_event() {
+    use vibe_api::{modules::auth, security::SECURITY_TARGET};
+
+    // Arrange
+    let (events, _guard) = common::capture_events(SECURITY_TARGET);
+    let app = auth::routes(
+        create_test_db().await,
+        common::app::create_test_jwt_config(),
+        None,
+        auth::LoginLockout::new(3, std::time::Duration::from_secs(60)),
+        auth::SignupGuard::default(),
+        vibe_api::utils::validation::NamePolicy::default(),
+        vibe_api::events::EventBus::default(),
+    );
+    let email = format!("lockout-{}@example.com", uuid::Uuid::new_v4().simple());
+    let (status, _) = register_with(
+        app.clone(),
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // Act
+    for _ in 0..3 {
+        let status = login_with(app.clone(), &email, "WrongPassword1!").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+    let locked = login_with(app.clone(), &email, TEST_PASSWORD).await;
+    let elsewhere = login_from(app, "198.51.100.70:1000", &email, TEST_PASSWORD).await;
+
+    // Assert: even the correct password is refused while locked, but only
+    // from the address that failed
+    assert_eq!(locked, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(elsewhere, StatusCode::OK);
+
+    let lockouts = events.named("login_lockout");
+    assert_eq!(lockouts.len(), 1);
+    let event = &lockouts[0];
+    assert_eq!(event["email"], "l***@example.com");
+    assert_eq!(event["client_ip"], "203.0.113.70");
+    assert_eq!(event["failed_attempts"], "3");
+    assert_eq!(event["locked_for_secs"], "60");
+    assert!(
+        event
+            .values()
+            .all(|value| !value.contains(&email) && !value.contains("WrongPassword1!")),
+        "Security events must not carry the raw email or password"
+    );
+}
+
+fn signup_app(signup: vibe_api::modules::auth::SignupGuard, pool: sqlx::PgPool) -> axum::Router {
+    use vibe_api::modules::auth;
+
+    auth::routes(
+        pool,
+        common::app::create_test_jwt_config(),
+        None,
+        auth::LoginLockout::default(),
+        signup,
+        vibe_api::utils::validation::NamePolicy::default(),
+        vibe_api::events::EventBus::default(),
+    )
+}
+
+/// Register a fresh account at `domain` from `peer`
+async fn signup_from(
+    app: axum::Router,
+    peer: &str,
+    domain: &str,
+) -> (StatusCode, serde_json::Value) {
+    signup_forwarded(app, peer, None, domain).await
+}
+
+/// Register a fresh account at `domain` from `peer`, optionally relaying for
+/// the `X-Forwarded-For` client
+async fn signup_forwarded(
+    app: axum::Router,
+    peer: &str,
+    forwarded_for: Option<&str>,
+    domain: &str,
+) -> (StatusCode, serde_json::Value) {
+    let peer: std::net::SocketAddr = peer.parse().unwrap();
+    let email = format!("signup-{}@{}", uuid::Uuid::new_v4().simple(), domain);
+
+    let mut request = Request::builder()
+        .method("POST")
+        .uri("/auth/register")
+        .header("content-type", "application/json")
+        .extension(axum::extract::ConnectInfo(peer));
+    if let Some(client) = forwarded_for {
+        request = request.header("x-forwarded-for", client);
+    }
+    let response = app
+        .oneshot(
+            request
+                .body(Body::from(
+                    json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME })
+                        .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_signups_beyond_per_ip_cap_return_429() {
+    // Arrange
+    let signup = vibe_api::modules::auth::SignupGuard::new(2, std::time::Duration::from_secs(60));
+    let app = signup_app(signup, create_test_db().await);
+
+    // Act
+    let first = signup_from(app.clone(), "203.0.113.50:1000", "example.com").await;
+    let second = signup_from(app.clone(), "203.0.113.50:1001", "example.com").await;
+    let third = signup_from(app.clone(), "203.0.113.50:1002", "example.com").await;
+    let other_ip = signup_from(app, "203.0.113.51:1000", "example.com").await;
+
+    // Assert
+    assert_eq!(first.0, StatusCode::CREATED);
+    assert_eq!(second.0, StatusCode::CREATED);
+    assert_eq!(third.0, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(third.1["error"]["code"], "RATE_LIMIT_EXCEEDED");
+    assert_eq!(other_ip.0, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_signup_cap_counts_clients_behind_trusted_proxy() {
+    // Arrange
+    let signup = vibe_api::modules::auth::SignupGuard::new(1, std::time::Duration::from_secs(60));
+    let app = signup_app(signup, create_test_db().await).layer(axum::Extension(
+        vibe_api::middleware::TrustedProxies::new(vec!["10.0.0.0/8".parse().unwrap()]),
+    ));
+
+    // Act
+    let first = signup_forwarded(
+        app.clone(),
+        "10.0.0.2:1000",
+        Some("203.0.113.60"),
+        "example.com",
+    )
+    .await;
+    let other_client = signup_forwarded(
+        app.clone(),
+        "10.0.0.2:1001",
+        Some("203.0.113.61"),
+        "example.com",
+    )
+    .await;
+    let repeat = signup_forwarded(
+        app.clone(),
+        "10.0.0.2:1002",
+        Some("203.0.113.60"),
+        "example.com",
+    )
+    .await;
+    // Anyone else's header is ignored, so a spoofed client shares the peer's cap
+    let untrusted = signup_forwarded(
+        app.clone(),
+        "198.51.100.60:1000",
+        Some("203.0.113.62"),
+        "example.com",
+    )
+    .await;
+    let spoofed = signup_forwarded(
+        app,
+        "198.51.100.60:1001",
+        Some("203.0.113.63"),
+        "example.com",
+    )
+    .await;
+
+    // Assert
+    assert_eq!(first.0, StatusCode::CREATED);
+    assert_eq!(other_client.0, StatusCode::CREATED);
+    assert_eq!(repeat.0, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(untrusted.0, StatusCode::CREATED);
+    assert_eq!(spoofed.0, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_disposable_email_domain_is_rejected() {
+    // Arrange
+    let signup = vibe_api::modules::auth::SignupGuard::default()
+        .with_blocked_domains(vec!["mailinator.com".to_string(), "TempBox.io".to_string()]);
+    let app = signup_app(signup, create_test_db().await);
+
+    // Act
+    let blocked = signup_from(app.clone(), "198.51.100.1:1000", "mailinator.com").await;
+    let blocked_subdomain = signup_from(app.clone(), "198.51.100.2:1000", "in.tempbox.io").await;
+    let allowed = signup_from(app, "198.51.100.3:1000", "example.com").await;
+
+    // Assert
+    assert_eq!(blocked.0, StatusCode::BAD_REQUEST);
+    assert_eq!(blocked.1["error"]["code"], "DISPOSABLE_EMAIL");
+    assert_eq!(blocked_subdomain.0, StatusCode::BAD_REQUEST);
+    assert_eq!(blocked_subdomain.1["error"]["code"], "DISPOSABLE_EMAIL");
+    assert_eq!(allowed.0, StatusCode::CREATED);
+}
+
+fn register_body(name: &str) -> String {
+    json!({
+        "email": format!("name_{}@example.com", uuid::Uuid::new_v4().simple()),
+        "password": TEST_PASSWORD,
+        "name": name
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_signup_rejects_overlong_name() {
+    let app = common::create_test_app(create_test_db().await).await;
+
+    let (status, body) =
+        register_with(app, "application/json", register_body(&"A".repeat(101))).await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+    assert_eq!(body["error"]["details"]["field"], "name");
+    assert_eq!(body["error"]["details"]["rule"], "max_length");
+}
+
+/// `(field, rule)` of each entry in a validation error's `errors` array
+fn invalid_fields(body: &serde_json::Value) -> Vec<(String, String)> {
+    body["error"]["errors"]
+        .as_array()
+        .expect("errors array")
+        .iter()
+        .map(|error| {
+            assert!(error["message"].as_str().is_some_and(|m| !m.is_empty()));
+            (
+                error["field"].as_str().unwrap().to_string(),
+                error["rule"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_signup_reports_every_invalid_field() {
+    let app = common::create_test_app(create_test_db().await).await;
+    let body = json!({ "email": "not-an-email", "password": "weak", "name": "" }).to_string();
+
+    let (status, body) = register_with(app, "application/json", body).await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+    assert_eq!(
+        invalid_fields(&body),
+        vec![
+            ("email".to_string(), "email".to_string()),
+            ("password".to_string(), "length".to_string()),
+            ("name".to_string(), "min_length".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_signup_reports_long_name_with_other_fields() {
+    let app = common::create_test_app(create_test_db().await).await;
+    let body = json!({
+        "email": "not-an-email",
+        "password": TEST_PASSWORD,
+        "name": "A".repeat(101)
+    })
+    .to_string();
+
+    let (status, body) = register_with(app, "application/json", body).await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        invalid_fields(&body),
+        vec![
+            ("email".to_string(), "email".to_string()),
+            ("name".to_string(), "max_length".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_single_invalid_field_keeps_details() {
+    let app = common::create_test_app(create_test_db().await).await;
+
+    let (_, body) = register_with(app, "application/json", register_body("")).await;
+
+    assert_eq!(body["error"]["details"]["field"], "name");
+    assert_eq!(
+        invalid_fields(&body),
+        vec![("name".to_string(), "min_length".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn test_login_reports_every_invalid_field() {
+    let app = common::create_test_app(create_test_db().await).await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/auth/login")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({ "email": "nope", "password": "" }).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        invalid_fields(&body),
+        vec![
+            ("email".to_string(), "email".to_string()),
+            ("password".to_string(), "length".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_signup_rejects_name_with_control_characters() {
+    let app = common::create_test_app(create_test_db().await).await;
+
+    let (status, body) =
+        register_with(app, "application/json", register_body("Eve\u{0007}\nAdmin")).await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["details"]["field"], "name");
+    assert_eq!(body["error"]["details"]["rule"], "control_characters");
+}
+
+#[tokio::test]
+async fn test_signup_name_limits_are_configurable() {
+    use vibe_api::{modules::auth, utils::validation::NamePolicy};
+
+    let app = auth::routes(
+        create_test_db().await,
+        common::app::create_test_jwt_config(),
+        None,
+        auth::LoginLockout::default(),
+        auth::SignupGuard::default(),
+        NamePolicy::new(2, 10),
+        vibe_api::events::EventBus::default(),
+    );
+
+    let (valid, _) =
+        register_with(app.clone(), "application/json", register_body("Zoë Müller")).await;
+    let (too_long, body) =
+        register_with(app, "application/json", register_body("Zoë Müllers")).await;
+
+    assert_eq!(valid, StatusCode::CREATED);
+    assert_eq!(too_long, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["details"]["rule"], "max_length");
+}
+
+#[tokio::test]
+async fn test_duplicate_email_signup_names_conflicting_field() {
+    let app = common::create_test_app(create_test_db().await).await;
+    let body = register_body(TEST_NAME);
+
+    let (first, _) = register_with(app.clone(), "application/json", body.clone()).await;
+    let (second, json) = register_with(app, "application/json", body).await;
+
+    assert_eq!(first, StatusCode::CREATED);
+    assert_eq!(second, StatusCode::CONFLICT);
+    assert_eq!(json["error"]["code"], "DUPLICATE_EMAIL");
+    assert_eq!(json["error"]["details"]["field"], "email");
+    assert_eq!(json["error"]["details"]["constraint"], "users_email_key");
+}
+
+fn password_reset_app(pool: sqlx::PgPool, events: vibe_api::events::EventBus) -> axum::Router {
+    use vibe_api::modules::auth;
+
+    auth::routes(
+        pool,
+        common::app::create_test_jwt_config(),
+        None,
+        auth::LoginLockout::default(),
+        auth::SignupGuard::default(),
+        vibe_api::utils::validation::NamePolicy::default(),
+        events,
+    )
+}
+
+async fn post_auth(
+    app: axum::Router,
+    uri: &str,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+/// Register a fresh account and request a reset, returning its email and the
+/// token published for delivery
+async fn issue_reset_token(
+    app: axum::Router,
+    events: &mut tokio::sync::broadcast::Receiver<vibe_api::events::DomainEvent>,
+) -> (String, String) {
+    use vibe_api::events::DomainEvent;
+
+    let email = format!("reset-{}@example.com", uuid::Uuid::new_v4().simple());
+    let (status, _) = register_with(
+        app.clone(),
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, _) = post_auth(app, "/auth/forgot-password", json!({ "email": email })).await;
+    assert_eq!(status, StatusCode::OK);
+
+    loop {
+        if let DomainEvent::PasswordResetRequested { token, .. } = events.recv().await.unwrap() {
+            return (email, token);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_password_reset_replaces_password() {
+    // Arrange
+    let events = vibe_api::events::EventBus::default();
+    let mut received = events.subscribe();
+    let app = password_reset_app(create_test_db().await, events);
+    let (email, token) = issue_reset_token(app.clone(), &mut received).await;
+
+    // Act
+    let (status, _) = post_auth(
+        app.clone(),
+        "/auth/reset-password",
+        json!({ "token": token, "new_password": "BrandNewPass456!" }),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        login_with(app.clone(), &email, "BrandNewPass456!").await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        login_with(app, &email, TEST_PASSWORD).await,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn test_forgot_password_does_not_reveal_unknown_email() {
+    // Arrange
+    let events = vibe_api::events::EventBus::default();
+    let mut received = events.subscribe();
+    let app = password_reset_app(create_test_db().await, events);
+    let (registered, _) = issue_reset_token(app.clone(), &mut received).await;
+    let (known_status, known) = post_auth(
+        app.clone(),
+        "/auth/forgot-password",
+        json!({ "email": registered }),
+    )
+    .await;
+
+    // Act
+    let (status, body) = post_auth(
+        app,
+        "/auth/forgot-password",
+        json!({ "email": format!("nobody-{}@example.com", uuid::Uuid::new_v4().simple()) }),
+    )
+    .await;
+
+    // Assert: same answer as for any other address, and nothing to deliver
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(status, known_status);
+    assert_eq!(body["message"], known["message"]);
+    while let Ok(event) = received.try_recv() {
+        assert!(
+            !matches!(
+                &event,
+                vibe_api::events::DomainEvent::PasswordResetRequested { email, .. }
+                    if email.starts_with("nobody-")
+            ),
+            "No token may be issued for an unknown email"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_reset_token_cannot_be_reused() {
+    // Arrange
+    let events = vibe_api::events::EventBus::default();
+    let mut received = events.subscribe();
+    let app = password_reset_app(create_test_db().await, events);
+    let (_, token) = issue_reset_token(app.clone(), &mut received).await;
+    let (first, _) = post_auth(
+        app.clone(),
+        "/auth/reset-password",
+        json!({ "token": token, "new_password": "BrandNewPass456!" }),
+    )
+    .await;
+
+    // Act
+    let (second, body) = post_auth(
+        app,
+        "/auth/reset-password",
+        json!({ "token": token, "new_password": "AnotherPass789!" }),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(first, StatusCode::OK);
+    assert_eq!(second, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "INVALID_RESET_TOKEN");
+}
+
+#[tokio::test]
+async fn test_expired_reset_token_is_rejected() {
+    // Arrange
+    let pool = create_test_db().await;
+    let events = vibe_api::events::EventBus::default();
+    let mut received = events.subscribe();
+    let app = password_reset_app(pool.clone(), events);
+    let (email, token) = issue_reset_token(app.clone(), &mut received).await;
+    sqlx::query(
+        "UPDATE password_reset_tokens SET expires_at = NOW() - INTERVAL '1 minute' \
+         WHERE user_id = (SELECT id FROM users WHERE email = $1)",
+    )
+    .bind(&email)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Act
+    let (status, body) = post_auth(
+        app.clone(),
+        "/auth/reset-password",
+        json!({ "token": token, "new_password": "BrandNewPass456!" }),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "INVALID_RESET_TOKEN");
+    assert_eq!(login_with(app, &email, TEST_PASSWORD).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_reset_rejects_weak_new_password() {
+    // Arrange
+    let events = vibe_api::events::EventBus::default();
+    let mut received = events.subscribe();
+    let app = password_reset_app(create_test_db().await, events);
+    let (_, token) = issue_reset_token(app.clone(), &mut received).await;
+
+    // Act
+    let (weak, body) = post_auth(
+        app.clone(),
+        "/auth/reset-password",
+        json!({ "token": token, "new_password": "short" }),
+    )
+    .await;
+
+    // Assert: rejected without consuming the token
+    assert_eq!(weak, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+    let (retry, _) = post_auth(
+        app,
+        "/auth/reset-password",
+        json!({ "token": token, "new_password": "BrandNewPass456!" }),
+    )
+    .await;
+    assert_eq!(retry, StatusCode::OK);
+}
+
+/// Register a fresh account and return its first refresh token
+async fn register_for_refresh(app: axum::Router) -> String {
+    let email = format!("rotate-{}@example.com", uuid::Uuid::new_v4().simple());
+    let (status, registered) = register_with(
+        app,
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    registered["data"]["refresh_token"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_refresh_rotates_refresh_token() {
+    // Arrange
+    let app = common::create_test_app(create_test_db().await).await;
+    let original = register_for_refresh(app.clone()).await;
+
+    // Act
+    let (status, first) = post_refresh(app.clone(), &original).await;
+    let rotated = first["data"]["refresh_token"].as_str().unwrap();
+    let (next_status, _) = post_refresh(app, rotated).await;
+
+    // Assert: every refresh hands out a new token that can itself be rotated
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(rotated, original);
+    assert!(first["data"]["access_token"].as_str().is_some());
+    assert_eq!(next_status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_reused_refresh_token_revokes_token_family() {
+    use vibe_api::security::SECURITY_TARGET;
+
+    // Arrange: one rotation, plus an unrelated session for the same user
+    let (events, _guard) = common::capture_events(SECURITY_TARGET);
+    let app = common::create_test_app(create_test_db().await).await;
+    let email = format!("reuse-{}@example.com", uuid::Uuid::new_v4().simple());
+    let (_, registered) = register_with(
+        app.clone(),
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    let stolen = registered["data"]["refresh_token"].as_str().unwrap();
+    let (_, rotated) = post_refresh(app.clone(), stolen).await;
+    let rotated = rotated["data"]["refresh_token"].as_str().unwrap();
+    let (_, other_login) = post_auth(
+        app.clone(),
+        "/auth/login",
+        json!({ "email": email, "password": TEST_PASSWORD }),
+    )
+    .await;
+    let other_session = other_login["data"]["refresh_token"].as_str().unwrap();
+
+    // Act
+    let (reuse_status, _) = post_refresh(app.clone(), stolen).await;
+    let (rotated_status, _) = post_refresh(app.clone(), rotated).await;
+    let (other_status, _) = post_refresh(app, other_session).await;
+
+    // Assert: the whole family is dead, other logins are untouched
+    assert_eq!(reuse_status, StatusCode::UNAUTHORIZED);
+    assert_eq!(rotated_status, StatusCode::UNAUTHORIZED);
+    assert_eq!(other_status, StatusCode::OK);
+
+    // Each revoked token presented is reported; the first revoked the family
+    let reuses = events.named("refresh_token_reuse");
+    assert_eq!(reuses.len(), 2);
+    assert_eq!(
+        reuses[0]["user_id"],
+        registered["data"]["user"]["id"].as_str().unwrap()
+    );
+    assert_eq!(reuses[0]["revoked_tokens"], "1");
+    assert_eq!(reuses[1]["revoked_tokens"], "0");
+}
+
+#[tokio::test]
+async fn test_unissued_refresh_token_is_rejected() {
+    // Arrange: validly signed, but never handed out by the service
+    let app = common::create_test_app(create_test_db().await).await;
+    let email = format!("unissued-{}@example.com", uuid::Uuid::new_v4().simple());
+    let (_, registered) = register_with(
+        app.clone(),
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    let token = refresh_token_for_session(&registered["data"]["user"], 1);
+
+    // Act
+    let (status, _) = post_refresh(app, &token).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+async fn post_with_headers(
+    app: axum::Router,
+    uri: &str,
+    headers: &[(&str, &str)],
+    body: Body,
+) -> StatusCode {
+    let mut request = Request::builder().method("POST").uri(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+
+    app.oneshot(request.body(body).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn test_refresh_after_logout_returns_401() {
+    // Arrange
+    let app = common::create_test_app(create_test_db().await).await;
+    let mine = register_for_refresh(app.clone()).await;
+    let someone_else = register_for_refresh(app.clone()).await;
+
+    // Act
+    let status = post_with_headers(
+        app.clone(),
+        "/auth/logout",
+        &[("content-type", "application/json")],
+        Body::from(json!({ "refresh_token": mine }).to_string()),
+    )
+    .await;
+    let (mine_status, _) = post_refresh(app.clone(), &mine).await;
+    let (other_status, _) = post_refresh(app, &someone_else).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(mine_status, StatusCode::UNAUTHORIZED);
+    assert_eq!(other_status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_logout_reads_refresh_token_cookie() {
+    // Arrange
+    let app = common::create_test_app(create_test_db().await).await;
+    let token = register_for_refresh(app.clone()).await;
+    let cookie = format!("theme=dark; refresh_token={}", token);
+
+    // Act
+    let status = post_with_headers(
+        app.clone(),
+        "/auth/logout",
+        &[("cookie", &cookie)],
+        Body::empty(),
+    )
+    .await;
+    let (refresh_status, _) = post_refresh(app.clone(), &token).await;
+    let missing = post_with_headers(app, "/auth/logout", &[], Body::empty()).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(refresh_status, StatusCode::UNAUTHORIZED);
+    assert_eq!(missing, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_logout_all_revokes_every_session_of_the_user_only() {
+    // Arrange: two logins for one user, one for another
+    let app = common::create_test_app(create_test_db().await).await;
+    let email = format!("logout-all-{}@example.com", uuid::Uuid::new_v4().simple());
+    let (_, registered) = register_with(
+        app.clone(),
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    let (_, second_login) = post_auth(
+        app.clone(),
+        "/auth/login",
+        json!({ "email": email, "password": TEST_PASSWORD }),
+    )
+    .await;
+    let someone_else = register_for_refresh(app.clone()).await;
+    let bearer = format!(
+        "Bearer {}",
+        registered["data"]["access_token"].as_str().unwrap()
+    );
+
+    // Act
+    let status = post_with_headers(
+        app.clone(),
+        "/auth/logout-all",
+        &[("authorization", &bearer)],
+        Body::empty(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    for session in [&registered, &second_login] {
+        let token = session["data"]["refresh_token"].as_str().unwrap();
+        let (status, _) = post_refresh(app.clone(), token).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+    let (other_status, _) = post_refresh(app.clone(), &someone_else).await;
+    assert_eq!(other_status, StatusCode::OK);
+
+    let anonymous = post_with_headers(app, "/auth/logout-all", &[], Body::empty()).await;
+    assert_eq!(anonymous, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_change_password_revokes_existing_sessions() {
+    // Arrange
+    let app = common::create_test_app(create_test_db().await).await;
+    let email = format!("change-pw-{}@example.com", uuid::Uuid::new_v4().simple());
+    let (_, registered) = register_with(
+        app.clone(),
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    let bearer = format!(
+        "Bearer {}",
+        registered["data"]["access_token"].as_str().unwrap()
+    );
+
+    // Act
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri("/users/me/password")
+                .header("content-type", "application/json")
+                .header("authorization", &bearer)
+                .body(Body::from(
+                    json!({
+                        "current_password": TEST_PASSWORD,
+                        "new_password": "AnotherPass456!"
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let (status, _) =
+        post_refresh(app, registered["data"]["refresh_token"].as_str().unwrap()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+/// Register a fresh account, returning its email and the verification token
+/// published for delivery
+async fn register_unverified(
+    app: axum::Router,
+    events: &mut tokio::sync::broadcast::Receiver<vibe_api::events::DomainEvent>,
+) -> (String, String) {
+    let email = format!("verify-{}@example.com", uuid::Uuid::new_v4().simple());
+    let (status, _) = register_with(
+        app,
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    (email, next_verification_token(events).await)
+}
+
+async fn next_verification_token(
+    events: &mut tokio::sync::broadcast::Receiver<vibe_api::events::DomainEvent>,
+) -> String {
+    use vibe_api::events::DomainEvent;
+
+    loop {
+        if let DomainEvent::EmailVerificationRequested { token, .. } = events.recv().await.unwrap()
+        {
+            return token;
+        }
+    }
+}
+
+async fn get_verify_email(app: axum::Router, token: &str) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/auth/verify-email?token={}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+async fn is_verified(pool: &sqlx::PgPool, email: &str) -> bool {
+    sqlx::query_scalar("SELECT email_verified FROM users WHERE email = $1")
+        .bind(email)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_verify_email_marks_account_verified() {
+    // Arrange
+    let pool = create_test_db().await;
+    let events = vibe_api::events::EventBus::default();
+    let mut received = events.subscribe();
+    let app = password_reset_app(pool.clone(), events);
+    let (email, token) = register_unverified(app.clone(), &mut received).await;
+    assert!(!is_verified(&pool, &email).await);
+
+    // Act
+    let (status, _) = get_verify_email(app, &token).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK);
+    assert!(is_verified(&pool, &email).await);
+}
+
+#[tokio::test]
+async fn test_verification_token_cannot_be_used_twice() {
+    // Arrange
+    let pool = create_test_db().await;
+    let events = vibe_api::events::EventBus::default();
+    let mut received = events.subscribe();
+    let app = password_reset_app(pool, events);
+    let (_, token) = register_unverified(app.clone(), &mut received).await;
+    let (first, _) = get_verify_email(app.clone(), &token).await;
+
+    // Act
+    let (status, body) = get_verify_email(app, &token).await;
+
+    // Assert
+    assert_eq!(first, StatusCode::OK);
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "INVALID_VERIFICATION_TOKEN");
+}
+
+#[tokio::test]
+async fn test_expired_verification_token_is_rejected() {
+    // Arrange
+    let pool = create_test_db().await;
+    let events = vibe_api::events::EventBus::default();
+    let mut received = events.subscribe();
+    let app = password_reset_app(pool.clone(), events);
+    let (email, token) = register_unverified(app.clone(), &mut received).await;
+    sqlx::query(
+        "UPDATE email_verification_tokens SET expires_at = NOW() - INTERVAL '1 minute' \
+         WHERE user_id = (SELECT id FROM users WHERE email = $1)",
+    )
+    .bind(&email)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Act
+    let (status, body) = get_verify_email(app, &token).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "INVALID_VERIFICATION_TOKEN");
+    assert!(!is_verified(&pool, &email).await);
+}
+
+#[tokio::test]
+async fn test_resend_verification_replaces_token_and_is_throttled() {
+    // Arrange
+    let pool = create_test_db().await;
+    let events = vibe_api::events::EventBus::default();
+    let mut received = events.subscribe();
+    let app = password_reset_app(pool.clone(), events);
+    let (email, original) = register_unverified(app.clone(), &mut received).await;
+
+    // Act
+    let (status, _) = post_auth(
+        app.clone(),
+        "/auth/resend-verification",
+        json!({ "email": email }),
+    )
+    .await;
+    let resent = next_verification_token(&mut received).await;
+    let (again, _) = post_auth(
+        app.clone(),
+        "/auth/resend-verification",
+        json!({ "email": email }),
+    )
+    .await;
+
+    // Assert: only the newest token works, and an immediate retry is refused
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(again, StatusCode::TOO_MANY_REQUESTS);
+    let (original_status, _) = get_verify_email(app.clone(), &original).await;
+    assert_eq!(original_status, StatusCode::BAD_REQUEST);
+    let (resent_status, _) = get_verify_email(app, &resent).await;
+    assert_eq!(resent_status, StatusCode::OK);
+    assert!(is_verified(&pool, &email).await);
+}
+
+#[tokio::test]
+async fn test_resend_verification_does_not_reveal_unknown_email() {
+    // Arrange
+    let app = password_reset_app(
+        create_test_db().await,
+        vibe_api::events::EventBus::default(),
+    );
+    let email = format!("nobody-{}@example.com", uuid::Uuid::new_v4().simple());
+
+    // Act
+    let (status, body) =
+        post_auth(app, "/auth/resend-verification", json!({ "email": email })).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["success"], true);
+}
+
+#[tokio::test]
+async fn test_require_verified_blocks_unverified_users() {
+    use axum::{middleware, routing::get};
+    use vibe_api::modules::auth::{
+        middleware::{auth_middleware, AuthMiddleware},
+        verified::require_verified,
+    };
+
+    // Arrange
+    let pool = create_test_db().await;
+    let events = vibe_api::events::EventBus::default();
+    let mut received = events.subscribe();
+    let auth_app = password_reset_app(pool.clone(), events);
+    let (email, token) = register_unverified(auth_app.clone(), &mut received).await;
+    let (_, login) = post_auth(
+        auth_app.clone(),
+        "/auth/login",
+        json!({ "email": email, "password": TEST_PASSWORD }),
+    )
+    .await;
+    let bearer = format!("Bearer {}", login["data"]["access_token"].as_str().unwrap());
+    let guarded = axum::Router::new()
+        .route("/guarded", get(|| async { "ok" }))
+        .layer(middleware::from_fn_with_state(pool, require_verified))
+        .layer(middleware::from_fn_with_state(
+            AuthMiddleware::new(common::app::create_test_jwt_config()),
+            auth_middleware,
+        ));
+    let request = || {
+        Request::builder()
+            .uri("/guarded")
+            .header("authorization", &bearer)
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    // Act
+    let before = guarded.clone().oneshot(request()).await.unwrap();
+    get_verify_email(auth_app, &token).await;
+    let after = guarded.oneshot(request()).await.unwrap();
+
+    // Assert
+    assert_eq!(before.status(), StatusCode::FORBIDDEN);
+    let body = before.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "EMAIL_NOT_VERIFIED");
+    assert_eq!(after.status(), StatusCode::OK);
+}
+
+/// Auth routes behind a small body limit, as main.rs layers them
+async fn body_limited_app(db_pool: sqlx::PgPool, limit_bytes: usize) -> axum::Router {
+    common::create_test_app(db_pool)
+        .await
+        .layer(axum::middleware::from_fn_with_state(
+            vibe_api::middleware::BodyLimit::new(limit_bytes),
+            vibe_api::middleware::body_limit::limit_request_body,
+        ))
+}
+
+fn oversized_signup() -> String {
+    json!({
+        "email": format!("big_{}@example.com", uuid::Uuid::new_v4().simple()),
+        "password": TEST_PASSWORD,
+        "name": "x".repeat(4096)
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_signup_with_oversized_declared_body_is_413() {
+    // Arrange
+    let app = body_limited_app(create_test_db().await, 1024).await;
+    let body = oversized_signup();
+
+    // Act
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/auth/register")
+                .header("content-type", "application/json")
+                .header("content-length", body.len())
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "PAYLOAD_TOO_LARGE");
+    assert_eq!(body["error"]["details"]["limit_bytes"], 1024);
+}
+
+#[tokio::test]
+async fn test_signup_with_oversized_body_without_length_is_413() {
+    // Arrange: no Content-Length, so the limit is enforced while reading
+    let app = body_limited_app(create_test_db().await, 1024).await;
+
+    // Act
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/auth/register")
+                .header("content-type", "application/json")
+                .body(Body::from(oversized_signup()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "PAYLOAD_TOO_LARGE");
+}
+
+#[tokio::test]
+async fn test_signup_within_body_limit_succeeds() {
+    // Arrange
+    let app = body_limited_app(create_test_db().await, 1024).await;
+
+    // Act
+    let (status, body) = post_auth(
+        app,
+        "/auth/register",
+        json!({
+            "email": format!("small_{}@example.com", uuid::Uuid::new_v4().simple()),
+            "password": TEST_PASSWORD,
+            "name": TEST_NAME
+        }),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(body["data"]["access_token"].is_string());
+}
+
+async fn send_as_admin(app: axum::Router, method: &str, uri: &str) -> StatusCode {
+    let token = vibe_api::modules::auth::jwt::generate_access_token(
+        &uuid::Uuid::new_v4(),
+        "admin@example.com",
+        vibe_api::modules::users::model::UserRole::Admin,
+        &common::app::create_test_jwt_config(),
+    )
+    .unwrap();
+
+    app.oneshot(
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+    .status()
+}
+
+#[tokio::test]
+async fn test_suspended_account_is_locked_out_until_unsuspended() {
+    // Arrange: a signed-in user with a live access and refresh token
+    let app = common::create_test_app(create_test_db().await).await;
+    let email = format!("suspend-{}@example.com", uuid::Uuid::new_v4().simple());
+    let credentials = json!({ "email": email, "password": TEST_PASSWORD });
+    let (_, registered) = register_with(
+        app.clone(),
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    let user_id = registered["data"]["user"]["id"].as_str().unwrap();
+    let access_token = registered["data"]["access_token"].as_str().unwrap();
+    let refresh_token = registered["data"]["refresh_token"].as_str().unwrap();
+    let me = |app: axum::Router| async move {
+        app.oneshot(
+            Request::builder()
+                .uri("/users/me")
+                .header("authorization", format!("Bearer {}", access_token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    };
+
+    // Act
+    let suspend_status =
+        send_as_admin(app.clone(), "POST", &format!("/users/{}/suspend", user_id)).await;
+    let (login_status, login) = post_auth(app.clone(), "/auth/login", credentials.clone()).await;
+    let me_response = me(app.clone()).await;
+    let (refresh_status, _) = post_refresh(app.clone(), refresh_token).await;
+
+    // Assert: every way back in is closed
+    assert_eq!(suspend_status, StatusCode::OK);
+    assert_eq!(login_status, StatusCode::FORBIDDEN);
+    assert_eq!(login["error"]["code"], "ACCOUNT_SUSPENDED");
+    assert_eq!(me_response.status(), StatusCode::FORBIDDEN);
+    let body = me_response.into_body().collect().await.unwrap().to_bytes();
+    let me_json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(me_json["error"]["code"], "ACCOUNT_SUSPENDED");
+    assert_ne!(refresh_status, StatusCode::OK);
+
+    // Act: reinstate
+    let unsuspend_status = send_as_admin(
+        app.clone(),
+        "POST",
+        &format!("/users/{}/unsuspend", user_id),
+    )
+    .await;
+    let (relogin_status, _) = post_auth(app.clone(), "/auth/login", credentials).await;
+
+    // Assert
+    assert_eq!(unsuspend_status, StatusCode::OK);
+    assert_eq!(relogin_status, StatusCode::OK);
+    assert_eq!(me(app).await.status(), StatusCode::OK);
+}
+
+async fn last_login_of(pool: &sqlx::PgPool, email: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    sqlx::query_scalar("SELECT last_login FROM users WHERE email = $1")
+        .bind(email)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_login_and_refresh_record_last_login() {
+    // Arrange
+    let db_pool = create_test_db().await;
+    let app = common::create_test_app(db_pool.clone()).await;
+    let email = format!("last-login-{}@example.com", uuid::Uuid::new_v4().simple());
+    register_with(
+        app.clone(),
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    assert_eq!(last_login_of(&db_pool, &email).await, None);
+
+    // Act: log in
+    let (login_status, login) = post_auth(
+        app.clone(),
+        "/auth/login",
+        json!({ "email": email, "password": TEST_PASSWORD }),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(login_status, StatusCode::OK);
+    let after_login = last_login_of(&db_pool, &email).await.unwrap();
+    assert!(chrono::Utc::now() - after_login < chrono::Duration::seconds(30));
+
+    // Act: refresh a day later
+    sqlx::query("UPDATE users SET last_login = NOW() - INTERVAL '1 day' WHERE email = $1")
+        .bind(&email)
+        .execute(&db_pool)
+        .await
+        .unwrap();
+    let (refresh_status, _) =
+        post_refresh(app, login["data"]["refresh_token"].as_str().unwrap()).await;
+
+    // Assert
+    assert_eq!(refresh_status, StatusCode::OK);
+    let after_refresh = last_login_of(&db_pool, &email).await.unwrap();
+    assert!(chrono::Utc::now() - after_refresh < chrono::Duration::seconds(30));
+}
+
+async fn send_bearer(
+    app: axum::Router,
+    method: &str,
+    uri: &str,
+    access_token: &str,
+) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("authorization", format!("Bearer {}", access_token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_sessions_are_listed_and_revocable() {
+    // Arrange: a signup session plus a login from a named client
+    let app = common::create_test_app(create_test_db().await).await;
+    let email = format!("sessions-{}@example.com", uuid::Uuid::new_v4().simple());
+    let (_, registered) = register_with(
+        app.clone(),
+        "application/json",
+        json!({ "email": email, "password": TEST_PASSWORD, "name": TEST_NAME }).to_string(),
+    )
+    .await;
+    let access_token = registered["data"]["access_token"].as_str().unwrap();
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/auth/login")
+                .header("content-type", "application/json")
+                .header("user-agent", "SessionTest/1.0")
+                .body(Body::from(
+                    json!({ "email": email, "password": TEST_PASSWORD }).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let login: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let login_refresh = login["data"]["refresh_token"].as_str().unwrap();
+
+    // Act
+    let (list_status, listed) =
+        send_bearer(app.clone(), "GET", "/users/me/sessions", access_token).await;
+
+    // Assert: newest first, labelled with the login's client
+    assert_eq!(list_status, StatusCode::OK);
+    let sessions = listed["data"].as_array().unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[0]["user_agent"], "SessionTest/1.0");
+    assert!(sessions[0]["created_at"].is_string());
+    assert!(sessions[0]["last_used_at"].is_string());
+    let session_id = sessions[0]["id"].as_str().unwrap().to_string();
+
+    // Act: revoke the login session
+    let (revoke_status, _) = send_bearer(
+        app.clone(),
+        "DELETE",
+        &format!("/users/me/sessions/{}", session_id),
+        access_token,
+    )
+    .await;
+    let (refresh_status, _) = post_refresh(app.clone(), login_refresh).await;
+    let (_, relisted) = send_bearer(app.clone(), "GET", "/users/me/sessions", access_token).await;
+    let (again_status, again) = send_bearer(
+        app,
+        "DELETE",
+        &format!("/users/me/sessions/{}", session_id),
+        access_token,
+    )
+    .await;
+
+    // Assert
+    assert_eq!(revoke_status, StatusCode::NO_CONTENT);
+    assert_eq!(refresh_status, StatusCode::UNAUTHORIZED);
+    let remaining = relisted["data"].as_array().unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_ne!(remaining[0]["id"], session_id.as_str());
+    assert_eq!(again_status, StatusCode::NOT_FOUND);
+    assert_eq!(again["error"]["code"], "NOT_FOUND");
+}
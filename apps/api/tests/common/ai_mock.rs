@@ -333,9 +333,18 @@ mod tests {
         let provider = MockAiProvider::new();
 
         let chunks = vec![
-            MockStreamChunk { delta: "Hello".to_string(), finish: false },
-            MockStreamChunk { delta: " World".to_string(), finish: false },
-            MockStreamChunk { delta: String::new(), finish: true },
+            MockStreamChunk {
+                delta: "Hello".to_string(),
+                finish: false,
+            },
+            MockStreamChunk {
+                delta: " World".to_string(),
+                finish: false,
+            },
+            MockStreamChunk {
+                delta: String::new(),
+                finish: true,
+            },
         ];
 
         provider.set_stream_chunks(chunks);
@@ -410,12 +419,15 @@ mod tests {
     fn test_mock_provider_clear() {
         let provider = MockAiProvider::new();
 
-        provider.set_response("test", MockAiResponse {
-            content: "test".to_string(),
-            model: "gpt-4".to_string(),
-            tokens_used: 10,
-            finish_reason: "stop".to_string(),
-        });
+        provider.set_response(
+            "test",
+            MockAiResponse {
+                content: "test".to_string(),
+                model: "gpt-4".to_string(),
+                tokens_used: 10,
+                finish_reason: "stop".to_string(),
+            },
+        );
 
         provider.chat("test", "gpt-4").unwrap();
         assert_eq!(provider.request_count(), 1);
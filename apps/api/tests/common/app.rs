@@ -3,9 +3,12 @@
 
 use axum::Router;
 use sqlx::{Pool, Postgres};
+use std::time::Duration;
 use vibe_api::{
     config::{DatabaseConfig, JwtConfig, ServerConfig},
-    modules::{auth, users},
+    middleware::ConcurrencyLimit,
+    modules::{api_keys, auth, users},
+    utils::validation::NamePolicy,
 };
 
 use super::fixtures::TEST_JWT_SECRET;
@@ -17,8 +20,24 @@ pub async fn create_test_app(db_pool: Pool<Postgres>) -> Router {
     Router::new()
         // Note: In real implementation, we'd need to adapt routes to work with SQLite
         // For now, this is a placeholder structure
-        .merge(auth::routes(db_pool.clone().into(), jwt_config.clone()))
-        .merge(users::routes(db_pool.into()))
+        .merge(auth::routes(
+            db_pool.clone().into(),
+            jwt_config.clone(),
+            None,
+            auth::LoginLockout::default(),
+            auth::SignupGuard::default(),
+            NamePolicy::default(),
+            vibe_api::events::EventBus::default(),
+        ))
+        .merge(users::routes(
+            db_pool.clone().into(),
+            jwt_config.clone(),
+            ConcurrencyLimit::new(4, Duration::from_secs(2)),
+            None,
+            NamePolicy::default(),
+            vibe_api::events::EventBus::default(),
+        ))
+        .merge(api_keys::admin_routes(db_pool, jwt_config))
 }
 
 /// Create test JWT configuration
@@ -28,6 +47,7 @@ pub fn create_test_jwt_config() -> JwtConfig {
         access_token_expiry_hours: 24,
         refresh_token_expiry_days: 30,
         issuer: "vibe-api-test".to_string(),
+        max_session_days: 30,
     }
 }
 
@@ -39,6 +59,8 @@ pub fn create_test_db_config() -> DatabaseConfig {
         min_connections: 1,
         acquire_timeout_secs: 5,
         idle_timeout_secs: 60,
+        connect_max_attempts: 1,
+        connect_backoff_cap_secs: 0,
     }
 }
 
@@ -49,6 +71,38 @@ pub fn create_test_server_config() -> ServerConfig {
         host: "127.0.0.1".to_string(),
         cors_origins: vec!["*".to_string()],
         environment: vibe_api::config::Environment::Test,
+        heavy_route_concurrency: 4,
+        heavy_route_queue_timeout_ms: 2000,
+        graphiql_enabled: true,
+        graphql_max_depth: 10,
+        graphql_max_complexity: 200,
+        pretty_json_enabled: true,
+        problem_json_default: false,
+        hsts_max_age_secs: 31536000,
+        content_security_policy: Some("default-src 'none'; frame-ancestors 'none'".to_string()),
+        csp_exempt_paths: vec!["/swagger-ui".to_string(), "/graphql".to_string()],
+        max_request_body_bytes: 1024 * 1024,
+        compression_min_bytes: 1024,
+        rate_limit_per_second: 100,
+        rate_limit_exempt_paths: vec!["/health".to_string()],
+        rate_limit_trusted_networks: vec![],
+        rate_limit_trusted_proxies: vec![],
+        rate_limit_routes: vec![],
+        shutdown_grace_secs: 30,
+        current_terms_version: None,
+        max_queries_per_request: None,
+        login_max_failed_attempts: 5,
+        login_lockout_secs: 900,
+        auth_failure_alert_threshold: 20,
+        auth_failure_window_secs: 60,
+        signup_max_per_ip: 5,
+        signup_window_secs: 3600,
+        disposable_email_domains: vec![],
+        name_min_length: 2,
+        name_max_length: 100,
+        read_only_mode: false,
+        status_admin_only: true,
+        idempotency_ttl_hours: 24,
     }
 }
 
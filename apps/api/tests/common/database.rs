@@ -78,16 +78,14 @@ mod tests {
         let pool = create_test_db().await;
 
         // Insert test data
-        sqlx::query(
-            "INSERT INTO users (id, email, password_hash, name) VALUES (?, ?, ?, ?)"
-        )
-        .bind("test-id")
-        .bind("test@example.com")
-        .bind("hash")
-        .bind("Test User")
-        .execute(&pool)
-        .await
-        .unwrap();
+        sqlx::query("INSERT INTO users (id, email, password_hash, name) VALUES (?, ?, ?, ?)")
+            .bind("test-id")
+            .bind("test@example.com")
+            .bind("hash")
+            .bind("Test User")
+            .execute(&pool)
+            .await
+            .unwrap();
 
         // Verify data exists
         let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
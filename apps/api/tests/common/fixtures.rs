@@ -3,7 +3,7 @@
 
 use chrono::Utc;
 use uuid::Uuid;
-use vibe_api::modules::users::model::{User, UserRole};
+use vibe_api::modules::users::model::{User, UserRole, UserStatus};
 
 /// Generate a test user with random data
 pub fn create_test_user() -> User {
@@ -11,12 +11,17 @@ pub fn create_test_user() -> User {
     User {
         id,
         email: format!("user_{}@example.com", id.simple()),
-        password_hash: "$argon2id$v=19$m=19456,t=2,p=1$test".to_string(),
+        password_hash: Some("$argon2id$v=19$m=19456,t=2,p=1$test".to_string()),
         name: format!("Test User {}", &id.to_string()[..8]),
         role: UserRole::User,
         created_at: Utc::now(),
         updated_at: Utc::now(),
         last_login: Some(Utc::now()),
+        last_active_at: None,
+        metadata: serde_json::json!({}),
+        terms_version_accepted: None,
+        email_verified: false,
+        status: UserStatus::Active,
     }
 }
 
@@ -25,12 +30,17 @@ pub fn create_test_user_with(email: &str, name: &str) -> User {
     User {
         id: Uuid::new_v4(),
         email: email.to_string(),
-        password_hash: "$argon2id$v=19$m=19456,t=2,p=1$test".to_string(),
+        password_hash: Some("$argon2id$v=19$m=19456,t=2,p=1$test".to_string()),
         name: name.to_string(),
         role: UserRole::User,
         created_at: Utc::now(),
         updated_at: Utc::now(),
         last_login: Some(Utc::now()),
+        last_active_at: None,
+        metadata: serde_json::json!({}),
+        terms_version_accepted: None,
+        email_verified: false,
+        status: UserStatus::Active,
     }
 }
 
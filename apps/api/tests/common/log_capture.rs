@@ -0,0 +1,106 @@
+// Log capture for tests
+// Records tracing events of one target so tests can assert on structured fields,
+// including the fields of every span the event was logged in
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    subscriber::DefaultGuard,
+    Event, Subscriber,
+};
+use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+/// Fields of one captured event and its spans, plus its `level`, values
+/// rendered as strings
+pub type CapturedFields = HashMap<String, String>;
+
+#[derive(Clone, Default)]
+pub struct CapturedEvents {
+    events: Arc<Mutex<Vec<CapturedFields>>>,
+}
+
+impl CapturedEvents {
+    pub fn all(&self) -> Vec<CapturedFields> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Events whose `event` field equals `name`
+    pub fn named(&self, name: &str) -> Vec<CapturedFields> {
+        self.all()
+            .into_iter()
+            .filter(|fields| fields.get("event").map(String::as_str) == Some(name))
+            .collect()
+    }
+}
+
+struct CaptureLayer {
+    target: &'static str,
+    captured: CapturedEvents,
+}
+
+struct FieldVisitor<'a>(&'a mut CapturedFields);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for CaptureLayer {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = CapturedFields::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(fields) = span.extensions_mut().get_mut::<CapturedFields>() {
+                values.record(&mut FieldVisitor(fields));
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if event.metadata().target() != self.target {
+            return;
+        }
+
+        // Outermost span first, so inner spans and the event itself win
+        let mut fields = CapturedFields::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(span_fields) = span.extensions().get::<CapturedFields>() {
+                    fields.extend(span_fields.clone());
+                }
+            }
+        }
+        fields.insert("level".to_string(), event.metadata().level().to_string());
+        event.record(&mut FieldVisitor(&mut fields));
+        self.captured.events.lock().unwrap().push(fields);
+    }
+}
+
+/// Capture events logged under `target` on the current thread until the guard
+/// drops; use with the default current-thread `#[tokio::test]` runtime
+pub fn capture_events(target: &'static str) -> (CapturedEvents, DefaultGuard) {
+    let captured = CapturedEvents::default();
+    let subscriber = tracing_subscriber::registry().with(CaptureLayer {
+        target,
+        captured: captured.clone(),
+    });
+
+    (captured, tracing::subscriber::set_default(subscriber))
+}
@@ -1,20 +1,22 @@
 // Common test utilities module
 // Provides shared testing infrastructure for integration tests
 
+pub mod ai_mock;
+pub mod app;
 pub mod database;
 pub mod fixtures;
+pub mod log_capture;
 pub mod mocks;
-pub mod app;
-pub mod test_app;
 pub mod s3_mock;
-pub mod ai_mock;
+pub mod test_app;
 pub mod ws_mock;
 
+pub use ai_mock::*;
+pub use app::*;
 pub use database::*;
 pub use fixtures::*;
+pub use log_capture::*;
 pub use mocks::*;
-pub use app::*;
-pub use test_app::*;
 pub use s3_mock::*;
-pub use ai_mock::*;
+pub use test_app::*;
 pub use ws_mock::*;
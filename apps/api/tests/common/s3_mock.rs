@@ -1,9 +1,9 @@
 // S3 Mock Infrastructure for Storage Tests
 // Provides in-memory S3 simulation for testing file operations
 
+use bytes::Bytes;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use bytes::Bytes;
 use uuid::Uuid;
 
 /// Mock S3 file entry
@@ -140,20 +140,6 @@ pub struct MockS3FileMetadata {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-/// Generate a mock presigned URL
-pub fn generate_presigned_url(
-    bucket: &str,
-    file_id: &str,
-    operation: &str,
-    expiry_secs: u64,
-) -> String {
-    let expiry = chrono::Utc::now().timestamp() + expiry_secs as i64;
-    format!(
-        "https://s3.mock.amazonaws.com/{}/{}?X-Amz-Algorithm=AWS4-HMAC-SHA256&X-Amz-Expires={}&operation={}",
-        bucket, file_id, expiry, operation
-    )
-}
-
 /// Create a test file with random content
 pub fn create_test_file(size_kb: usize) -> Bytes {
     let size_bytes = size_kb * 1024;
@@ -227,15 +213,6 @@ mod tests {
         assert_eq!(metadata.size, data.len());
     }
 
-    #[test]
-    fn test_generate_presigned_url() {
-        let url = generate_presigned_url("test-bucket", "file123", "getObject", 3600);
-        assert!(url.contains("test-bucket"));
-        assert!(url.contains("file123"));
-        assert!(url.contains("X-Amz-Algorithm"));
-        assert!(url.contains("operation=getObject"));
-    }
-
     #[test]
     fn test_create_test_files() {
         let file = create_test_file(10); // 10 KB
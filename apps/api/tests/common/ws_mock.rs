@@ -12,13 +12,31 @@ use uuid::Uuid;
 pub enum WsMessage {
     Ping,
     Pong,
-    Text { content: String },
-    Join { room: String, user_id: String },
-    Leave { room: String, user_id: String },
-    Broadcast { room: String, content: String, from_user: String },
-    Error { message: String },
-    Connected { connection_id: String },
-    Disconnected { connection_id: String },
+    Text {
+        content: String,
+    },
+    Join {
+        room: String,
+        user_id: String,
+    },
+    Leave {
+        room: String,
+        user_id: String,
+    },
+    Broadcast {
+        room: String,
+        content: String,
+        from_user: String,
+    },
+    Error {
+        message: String,
+    },
+    Connected {
+        connection_id: String,
+    },
+    Disconnected {
+        connection_id: String,
+    },
 }
 
 /// Mock WebSocket connection
@@ -256,11 +274,7 @@ impl MockWsServer {
 
     /// Get connection
     pub fn get_connection(&self, connection_id: &str) -> Option<MockWsConnection> {
-        self.connections
-            .lock()
-            .unwrap()
-            .get(connection_id)
-            .cloned()
+        self.connections.lock().unwrap().get(connection_id).cloned()
     }
 
     /// Get room members
@@ -310,7 +324,10 @@ impl Default for MockWsServer {
 /// Helper to create a JSON WebSocket message
 pub fn create_ws_json_message(msg_type: &str, data: serde_json::Value) -> String {
     let mut obj = serde_json::Map::new();
-    obj.insert("type".to_string(), serde_json::Value::String(msg_type.to_string()));
+    obj.insert(
+        "type".to_string(),
+        serde_json::Value::String(msg_type.to_string()),
+    );
 
     for (key, value) in data.as_object().unwrap() {
         obj.insert(key.clone(), value.clone());
@@ -381,7 +398,9 @@ mod tests {
         server.join_room(&conn2, "room1").unwrap();
         // conn3 not in room1
 
-        server.broadcast_to_room("room1", "Hello room!", "user1").unwrap();
+        server
+            .broadcast_to_room("room1", "Hello room!", "user1")
+            .unwrap();
 
         // Check messages received
         let c1 = server.get_connection(&conn1).unwrap();
@@ -410,9 +429,14 @@ mod tests {
         let server = MockWsServer::new();
         let conn_id = server.connect(Some("user1".to_string()));
 
-        server.send_message(&conn_id, WsMessage::Text {
-            content: "Hello".to_string()
-        }).unwrap();
+        server
+            .send_message(
+                &conn_id,
+                WsMessage::Text {
+                    content: "Hello".to_string(),
+                },
+            )
+            .unwrap();
 
         let history = server.message_history();
         assert_eq!(history.len(), 1);
@@ -8,10 +8,7 @@ use common::TEST_CONFIG;
 #[test]
 fn test_database_url_is_set() {
     let config = &*TEST_CONFIG;
-    assert!(
-        !config.database_url.is_empty(),
-        "DATABASE_URL must be set"
-    );
+    assert!(!config.database_url.is_empty(), "DATABASE_URL must be set");
     assert!(
         config.database_url.starts_with("postgres://"),
         "DATABASE_URL must be a PostgreSQL connection string"
@@ -21,10 +18,7 @@ fn test_database_url_is_set() {
 #[test]
 fn test_jwt_secret_is_set() {
     let config = &*TEST_CONFIG;
-    assert!(
-        !config.jwt_secret.is_empty(),
-        "JWT_SECRET must be set"
-    );
+    assert!(!config.jwt_secret.is_empty(), "JWT_SECRET must be set");
     assert!(
         config.jwt_secret.len() >= 16,
         "JWT_SECRET should be at least 16 characters for security"
@@ -34,10 +28,7 @@ fn test_jwt_secret_is_set() {
 #[test]
 fn test_server_port_is_valid() {
     let config = &*TEST_CONFIG;
-    assert!(
-        config.server_port > 0,
-        "SERVER_PORT must be greater than 0"
-    );
+    assert!(config.server_port > 0, "SERVER_PORT must be greater than 0");
     assert!(
         config.server_port < 65536,
         "SERVER_PORT must be less than 65536"
@@ -0,0 +1,116 @@
+// Event bus integration tests
+// Validates domain event delivery to subscribed handlers
+
+mod common;
+
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use vibe_api::{
+    events::{DomainEvent, EventBus},
+    modules::auth::service::AuthService,
+};
+
+use common::{app, create_test_db_pool, run_migrations};
+
+fn user_created() -> DomainEvent {
+    DomainEvent::UserCreated {
+        user_id: Uuid::new_v4(),
+        email: "events@example.com".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_user_created_reaches_subscribed_handler() {
+    // Arrange
+    let bus = EventBus::default();
+    let (tx, mut received) = mpsc::unbounded_channel();
+    bus.spawn_handler("test", move |event| {
+        let tx = tx.clone();
+        async move {
+            let _ = tx.send(event);
+        }
+    });
+    let event = user_created();
+
+    // Act
+    bus.publish(event.clone());
+
+    // Assert
+    let delivered = tokio::time::timeout(Duration::from_secs(1), received.recv())
+        .await
+        .expect("handler should receive the event")
+        .unwrap();
+    assert_eq!(delivered, event);
+    assert_eq!(delivered.name(), "user.created");
+}
+
+#[tokio::test]
+async fn test_slow_subscriber_does_not_block_publishers() {
+    // Arrange: one handler never finishes its first event
+    let bus = EventBus::new(4);
+    bus.spawn_handler("stuck", |_| std::future::pending::<()>());
+    let mut fast = bus.subscribe();
+
+    // Act
+    let published = tokio::time::timeout(Duration::from_secs(1), async {
+        for _ in 0..100 {
+            bus.publish(user_created());
+        }
+    })
+    .await;
+
+    // Assert: publishing completed and other subscribers still get events
+    assert!(published.is_ok());
+    assert!(matches!(
+        fast.recv().await,
+        Err(tokio::sync::broadcast::error::RecvError::Lagged(_))
+    ));
+    assert!(fast.recv().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_registration_publishes_user_created() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let bus = EventBus::default();
+    let mut events = bus.subscribe();
+    let service = AuthService::new(pool, app::create_test_jwt_config()).with_events(bus);
+    let email = format!("events_{}@example.com", Uuid::new_v4().simple());
+
+    // Act
+    let response = service
+        .register(
+            serde_json::from_value(json!({
+                "email": email,
+                "password": "SecurePass123!",
+                "name": "Event User"
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    let expected = DomainEvent::UserCreated {
+        user_id: Uuid::parse_str(&response.user.id).unwrap(),
+        email,
+    };
+    assert_eq!(events.recv().await.unwrap(), expected);
+}
+
+#[test]
+fn test_audit_handler_logs_event_with_redacted_email() {
+    // Arrange
+    let (captured, _guard) = common::capture_events("audit");
+
+    // Act
+    vibe_api::events::handlers::audit(&user_created());
+
+    // Assert
+    let logged = captured.named("user.created");
+    assert_eq!(logged.len(), 1);
+    assert_eq!(logged[0]["email"], "e***@example.com");
+}
@@ -3,7 +3,7 @@
 
 mod common;
 
-use common::{create_test_db_pool, run_migrations, clean_test_db};
+use common::{clean_test_db, create_test_db_pool, run_migrations};
 use sqlx::Row;
 use std::time::Duration;
 
@@ -21,7 +21,7 @@ async fn test_cleanup_old_data_task_deletes_inactive_users() {
     // 1. Active user (last_login recent)
     sqlx::query(
         "INSERT INTO users (id, email, password_hash, name, role, last_login)
-         VALUES (gen_random_uuid(), 'active@test.com', 'hash', 'Active User', 'user', NOW())"
+         VALUES (gen_random_uuid(), 'active@test.com', 'hash', 'Active User', 'user', NOW())",
     )
     .execute(&pool)
     .await
@@ -39,19 +39,17 @@ async fn test_cleanup_old_data_task_deletes_inactive_users() {
     // 3. User with NULL last_login (never logged in)
     sqlx::query(
         "INSERT INTO users (id, email, password_hash, name, role, last_login)
-         VALUES (gen_random_uuid(), 'never@test.com', 'hash', 'Never Logged In', 'user', NULL)"
+         VALUES (gen_random_uuid(), 'never@test.com', 'hash', 'Never Logged In', 'user', NULL)",
     )
     .execute(&pool)
     .await
     .expect("Failed to insert never-logged-in user");
 
     // Act - Simulate the cleanup task
-    let result = sqlx::query(
-        "DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'"
-    )
-    .execute(&pool)
-    .await
-    .expect("Cleanup query failed");
+    let result = sqlx::query("DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'")
+        .execute(&pool)
+        .await
+        .expect("Cleanup query failed");
 
     // Assert
     let rows_affected = result.rows_affected();
@@ -63,7 +61,10 @@ async fn test_cleanup_old_data_task_deletes_inactive_users() {
         .await
         .expect("Failed to count users");
 
-    assert_eq!(count.0, 2, "Should have 2 users remaining (active + never logged in)");
+    assert_eq!(
+        count.0, 2,
+        "Should have 2 users remaining (active + never logged in)"
+    );
 }
 
 #[tokio::test]
@@ -76,7 +77,7 @@ async fn test_cleanup_old_data_task_handles_no_inactive_users() {
     // Insert only active users
     sqlx::query(
         "INSERT INTO users (id, email, password_hash, name, role, last_login)
-         VALUES (gen_random_uuid(), 'active1@test.com', 'hash', 'Active User 1', 'user', NOW())"
+         VALUES (gen_random_uuid(), 'active1@test.com', 'hash', 'Active User 1', 'user', NOW())",
     )
     .execute(&pool)
     .await
@@ -84,19 +85,17 @@ async fn test_cleanup_old_data_task_handles_no_inactive_users() {
 
     sqlx::query(
         "INSERT INTO users (id, email, password_hash, name, role, last_login)
-         VALUES (gen_random_uuid(), 'active2@test.com', 'hash', 'Active User 2', 'user', NOW())"
+         VALUES (gen_random_uuid(), 'active2@test.com', 'hash', 'Active User 2', 'user', NOW())",
     )
     .execute(&pool)
     .await
     .expect("Failed to insert user");
 
     // Act
-    let result = sqlx::query(
-        "DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'"
-    )
-    .execute(&pool)
-    .await
-    .expect("Cleanup query failed");
+    let result = sqlx::query("DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'")
+        .execute(&pool)
+        .await
+        .expect("Cleanup query failed");
 
     // Assert
     assert_eq!(result.rows_affected(), 0, "Should delete 0 users");
@@ -117,15 +116,17 @@ async fn test_cleanup_old_data_task_handles_empty_table() {
     clean_test_db(&pool).await;
 
     // Act - Run cleanup on empty table
-    let result = sqlx::query(
-        "DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'"
-    )
-    .execute(&pool)
-    .await
-    .expect("Cleanup query failed");
+    let result = sqlx::query("DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'")
+        .execute(&pool)
+        .await
+        .expect("Cleanup query failed");
 
     // Assert
-    assert_eq!(result.rows_affected(), 0, "Should delete 0 users from empty table");
+    assert_eq!(
+        result.rows_affected(),
+        0,
+        "Should delete 0 users from empty table"
+    );
 }
 
 #[tokio::test]
@@ -140,7 +141,7 @@ async fn test_aggregate_metrics_task_calculates_correct_dau() {
     for i in 1..=3 {
         sqlx::query(
             "INSERT INTO users (id, email, password_hash, name, role, last_login)
-             VALUES (gen_random_uuid(), $1, 'hash', 'User', 'user', NOW())"
+             VALUES (gen_random_uuid(), $1, 'hash', 'User', 'user', NOW())",
         )
         .bind(format!("today{}@test.com", i))
         .execute(&pool)
@@ -152,7 +153,7 @@ async fn test_aggregate_metrics_task_calculates_correct_dau() {
     for i in 1..=2 {
         sqlx::query(
             "INSERT INTO users (id, email, password_hash, name, role, last_login)
-             VALUES (gen_random_uuid(), $1, 'hash', 'User', 'user', NOW() - INTERVAL '2 days')"
+             VALUES (gen_random_uuid(), $1, 'hash', 'User', 'user', NOW() - INTERVAL '2 days')",
         )
         .bind(format!("yesterday{}@test.com", i))
         .execute(&pool)
@@ -163,7 +164,7 @@ async fn test_aggregate_metrics_task_calculates_correct_dau() {
     // 1 user never logged in
     sqlx::query(
         "INSERT INTO users (id, email, password_hash, name, role, last_login)
-         VALUES (gen_random_uuid(), 'never@test.com', 'hash', 'Never', 'user', NULL)"
+         VALUES (gen_random_uuid(), 'never@test.com', 'hash', 'Never', 'user', NULL)",
     )
     .execute(&pool)
     .await
@@ -171,14 +172,17 @@ async fn test_aggregate_metrics_task_calculates_correct_dau() {
 
     // Act - Simulate the metrics aggregation query
     let result: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'"
+        "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'",
     )
     .fetch_one(&pool)
     .await
     .expect("Metrics query failed");
 
     // Assert
-    assert_eq!(result.0, 3, "DAU should be 3 (users active in last 24 hours)");
+    assert_eq!(
+        result.0, 3,
+        "DAU should be 3 (users active in last 24 hours)"
+    );
 }
 
 #[tokio::test]
@@ -199,7 +203,7 @@ async fn test_aggregate_metrics_task_handles_zero_active_users() {
 
     // Act
     let result: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'"
+        "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'",
     )
     .fetch_one(&pool)
     .await
@@ -218,7 +222,7 @@ async fn test_aggregate_metrics_task_handles_empty_table() {
 
     // Act
     let result: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'"
+        "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'",
     )
     .fetch_one(&pool)
     .await
@@ -245,8 +249,8 @@ async fn test_cleanup_task_with_database_error() {
     // Verify error is the correct type
     if let Err(e) = result {
         assert!(
-            e.to_string().contains("non_existent_table") ||
-            e.to_string().contains("does not exist"),
+            e.to_string().contains("non_existent_table")
+                || e.to_string().contains("does not exist"),
             "Error should mention the non-existent table"
         );
     }
@@ -263,7 +267,7 @@ async fn test_metrics_query_performance() {
     for i in 0..100 {
         sqlx::query(
             "INSERT INTO users (id, email, password_hash, name, role, last_login)
-             VALUES (gen_random_uuid(), $1, 'hash', 'User', 'user', NOW())"
+             VALUES (gen_random_uuid(), $1, 'hash', 'User', 'user', NOW())",
         )
         .bind(format!("user{}@test.com", i))
         .execute(&pool)
@@ -275,7 +279,7 @@ async fn test_metrics_query_performance() {
     let start = std::time::Instant::now();
 
     let _result: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'"
+        "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'",
     )
     .fetch_one(&pool)
     .await
@@ -312,12 +316,10 @@ async fn test_cleanup_query_performance() {
     // Act & Assert - Deletion should be fast
     let start = std::time::Instant::now();
 
-    let _result = sqlx::query(
-        "DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'"
-    )
-    .execute(&pool)
-    .await
-    .expect("Cleanup query failed");
+    let _result = sqlx::query("DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'")
+        .execute(&pool)
+        .await
+        .expect("Cleanup query failed");
 
     let duration = start.elapsed();
 
@@ -339,7 +341,7 @@ async fn test_concurrent_metric_calculations() {
     for i in 0..10 {
         sqlx::query(
             "INSERT INTO users (id, email, password_hash, name, role, last_login)
-             VALUES (gen_random_uuid(), $1, 'hash', 'User', 'user', NOW())"
+             VALUES (gen_random_uuid(), $1, 'hash', 'User', 'user', NOW())",
         )
         .bind(format!("user{}@test.com", i))
         .execute(&pool)
@@ -353,7 +355,7 @@ async fn test_concurrent_metric_calculations() {
         let pool_clone = pool.clone();
         let handle = tokio::spawn(async move {
             sqlx::query_as::<_, (i64,)>(
-                "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'"
+                "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'",
             )
             .fetch_one(&pool_clone)
             .await
@@ -369,7 +371,11 @@ async fn test_concurrent_metric_calculations() {
         assert!(result.is_ok(), "Concurrent query failed");
         let query_result = result.unwrap();
         assert!(query_result.is_ok(), "Query execution failed");
-        assert_eq!(query_result.unwrap().0, 10, "All queries should return same count");
+        assert_eq!(
+            query_result.unwrap().0,
+            10,
+            "All queries should return same count"
+        );
     }
 }
 
@@ -407,34 +413,28 @@ async fn test_cleanup_preserves_recent_users() {
     }
 
     // Act - Run cleanup
-    let _result = sqlx::query(
-        "DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'"
-    )
-    .execute(&pool)
-    .await
-    .expect("Cleanup failed");
+    let _result = sqlx::query("DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'")
+        .execute(&pool)
+        .await
+        .expect("Cleanup failed");
 
     // Assert - Verify only recent users remain
     for email in &recent_emails {
-        let exists: (bool,) = sqlx::query_as(
-            "SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)"
-        )
-        .bind(email)
-        .fetch_one(&pool)
-        .await
-        .expect("Failed to check user existence");
+        let exists: (bool,) = sqlx::query_as("SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)")
+            .bind(email)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to check user existence");
 
         assert!(exists.0, "Recent user {} should still exist", email);
     }
 
     for email in &old_emails {
-        let exists: (bool,) = sqlx::query_as(
-            "SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)"
-        )
-        .bind(email)
-        .fetch_one(&pool)
-        .await
-        .expect("Failed to check user existence");
+        let exists: (bool,) = sqlx::query_as("SELECT EXISTS(SELECT 1 FROM users WHERE email = $1)")
+            .bind(email)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to check user existence");
 
         assert!(!exists.0, "Old user {} should be deleted", email);
     }
@@ -451,7 +451,7 @@ async fn test_metrics_with_null_last_login() {
     for i in 0..5 {
         sqlx::query(
             "INSERT INTO users (id, email, password_hash, name, role, last_login)
-             VALUES (gen_random_uuid(), $1, 'hash', 'Never Logged In', 'user', NULL)"
+             VALUES (gen_random_uuid(), $1, 'hash', 'Never Logged In', 'user', NULL)",
         )
         .bind(format!("never{}@test.com", i))
         .execute(&pool)
@@ -461,14 +461,17 @@ async fn test_metrics_with_null_last_login() {
 
     // Act
     let result: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'"
+        "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'",
     )
     .fetch_one(&pool)
     .await
     .expect("Metrics query failed");
 
     // Assert
-    assert_eq!(result.0, 0, "Users with NULL last_login should not count as active");
+    assert_eq!(
+        result.0, 0,
+        "Users with NULL last_login should not count as active"
+    );
 }
 
 #[tokio::test]
@@ -482,7 +485,7 @@ async fn test_cleanup_does_not_delete_null_last_login() {
     for i in 0..3 {
         sqlx::query(
             "INSERT INTO users (id, email, password_hash, name, role, last_login)
-             VALUES (gen_random_uuid(), $1, 'hash', 'Never Logged In', 'user', NULL)"
+             VALUES (gen_random_uuid(), $1, 'hash', 'Never Logged In', 'user', NULL)",
         )
         .bind(format!("never{}@test.com", i))
         .execute(&pool)
@@ -491,15 +494,17 @@ async fn test_cleanup_does_not_delete_null_last_login() {
     }
 
     // Act
-    let result = sqlx::query(
-        "DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'"
-    )
-    .execute(&pool)
-    .await
-    .expect("Cleanup failed");
+    let result = sqlx::query("DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'")
+        .execute(&pool)
+        .await
+        .expect("Cleanup failed");
 
     // Assert
-    assert_eq!(result.rows_affected(), 0, "Should not delete users with NULL last_login");
+    assert_eq!(
+        result.rows_affected(),
+        0,
+        "Should not delete users with NULL last_login"
+    );
 
     let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
         .fetch_one(&pool)
@@ -508,3 +513,555 @@ async fn test_cleanup_does_not_delete_null_last_login() {
 
     assert_eq!(count.0, 3, "All NULL last_login users should remain");
 }
+
+#[cfg(feature = "jobs")]
+mod soft_delete_purge {
+    use super::common::{create_test_db_pool, run_migrations};
+    use async_trait::async_trait;
+    use sqlx::PgPool;
+    use std::sync::Mutex;
+    use uuid::Uuid;
+    use vibe_api::config::{JobsConfig, SchedulerConfig};
+    use vibe_api::modules::jobs::{
+        tasks::{purge_soft_deleted, ObjectStore, PURGE_SOFT_DELETED_JOB},
+        JobRegistry,
+    };
+    use vibe_api::utils::error::{AppError, AppResult};
+
+    /// Records deleted keys; keys starting with `fail` cannot be deleted
+    #[derive(Default)]
+    struct FakeObjects {
+        deleted: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ObjectStore for FakeObjects {
+        async fn delete_object(&self, key: &str) -> AppResult<()> {
+            if key.starts_with("fail") {
+                return Err(AppError::ExternalService("S3 delete error".to_string()));
+            }
+            self.deleted.lock().unwrap().push(key.to_string());
+            Ok(())
+        }
+    }
+
+    async fn insert_user(pool: &PgPool, deleted_days_ago: Option<i32>) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, email, password_hash, name, created_at, deleted_at)
+             VALUES ($1, $2, 'hash', 'Purge User', NOW() - INTERVAL '5 years',
+                     NOW() - make_interval(days => $3))",
+        )
+        .bind(id)
+        .bind(format!("purge_{}@test.com", id.simple()))
+        .bind(deleted_days_ago)
+        .execute(pool)
+        .await
+        .expect("Failed to insert user");
+        id
+    }
+
+    async fn exists(pool: &PgPool, id: &Uuid) -> bool {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    /// Insert a file of `owner` stored under `storage_key`, with a thumbnail
+    async fn insert_file(pool: &PgPool, owner: &Uuid, storage_key: &str) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO files (id, owner_id, file_name, content_type, file_size, content_hash, storage_key, thumbnail_key)
+             VALUES ($1, $2, 'photo.png', 'image/png', 3, $3, $4, $5)",
+        )
+        .bind(id)
+        .bind(owner)
+        .bind("0".repeat(64))
+        .bind(storage_key)
+        .bind(format!("thumb-{}", storage_key))
+        .execute(pool)
+        .await
+        .expect("Failed to insert file");
+        id
+    }
+
+    async fn file_exists(pool: &PgPool, id: &Uuid) -> bool {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM files WHERE id = $1)")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_only_rows_past_retention() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let expired = insert_user(&pool, Some(45)).await;
+        let recent = insert_user(&pool, Some(2)).await;
+        let live = insert_user(&pool, None).await;
+
+        // Act
+        purge_soft_deleted(pool.clone(), 30, None).await.unwrap();
+
+        // Assert
+        assert!(!exists(&pool, &expired).await);
+        assert!(exists(&pool, &recent).await, "Still within retention");
+        assert!(exists(&pool, &live).await, "Never soft-deleted");
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_files_and_objects_past_retention() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let expired = insert_user(&pool, Some(45)).await;
+        let recent = insert_user(&pool, Some(2)).await;
+        let expired_key = format!("uploads/{}", Uuid::new_v4());
+        let recent_key = format!("uploads/{}", Uuid::new_v4());
+        let expired_file = insert_file(&pool, &expired, &expired_key).await;
+        let recent_file = insert_file(&pool, &recent, &recent_key).await;
+        let objects = FakeObjects::default();
+
+        // Act
+        let summary = purge_soft_deleted(pool.clone(), 30, Some(&objects))
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(!exists(&pool, &expired).await);
+        assert!(!file_exists(&pool, &expired_file).await);
+        assert!(exists(&pool, &recent).await, "Still within retention");
+        assert!(file_exists(&pool, &recent_file).await);
+        let deleted = objects.deleted.lock().unwrap().clone();
+        assert!(deleted.contains(&expired_key));
+        assert!(deleted.contains(&format!("thumb-{}", expired_key)));
+        assert!(!deleted.iter().any(|key| key.ends_with(&recent_key)));
+        assert!(summary.files_purged >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_purge_keeps_file_and_owner_when_object_delete_fails() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let owner = insert_user(&pool, Some(45)).await;
+        let file = insert_file(&pool, &owner, &format!("fail/{}", Uuid::new_v4())).await;
+
+        // Act
+        let summary = purge_soft_deleted(pool.clone(), 30, Some(&FakeObjects::default()))
+            .await
+            .unwrap();
+
+        // Assert - both are retried on the next run
+        assert!(exists(&pool, &owner).await);
+        assert!(file_exists(&pool, &file).await);
+        assert!(summary.files_failed >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_purge_records_job_run() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let before = chrono::Utc::now();
+
+        let config = JobsConfig {
+            soft_delete_retention_days: 30,
+            scheduler: SchedulerConfig {
+                cleanup_cron: "0 0 0 * * *".to_string(),
+                metrics_cron: "0 0 * * * *".to_string(),
+                purge_cron: "0 0 3 * * *".to_string(),
+            },
+        };
+        let registry = JobRegistry::with_default_jobs(pool.clone(), &config, None);
+
+        // Act
+        let run = registry.run(PURGE_SOFT_DELETED_JOB).await.unwrap();
+
+        // Assert
+        let runs: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM job_runs
+             WHERE job_name = $1 AND status = 'succeeded' AND started_at >= $2
+               AND (summary->>'users_purged')::BIGINT = $3
+               AND (summary->>'retention_days')::BIGINT = 30",
+        )
+        .bind(PURGE_SOFT_DELETED_JOB)
+        .bind(before)
+        .bind(run.outcome.rows as i64)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(runs >= 1);
+    }
+}
+
+#[cfg(feature = "jobs")]
+mod tasks {
+    use super::common::{create_test_db_pool, run_migrations};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+    use vibe_api::modules::jobs::tasks::{aggregate_metrics, cleanup_old_data};
+
+    /// Insert a user created `created_days_ago` and last seen `login_days_ago`
+    async fn insert_user(
+        pool: &PgPool,
+        created_days_ago: i32,
+        login_days_ago: Option<i32>,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, email, password_hash, name, created_at, last_login)
+             VALUES ($1, $2, 'hash', 'Task User', NOW() - make_interval(days => $3),
+                     NOW() - make_interval(days => $4))",
+        )
+        .bind(id)
+        .bind(format!("task_{}@test.com", id.simple()))
+        .bind(created_days_ago)
+        .bind(login_days_ago)
+        .execute(pool)
+        .await
+        .expect("Failed to insert user");
+        id
+    }
+
+    async fn exists(pool: &PgPool, id: &Uuid) -> bool {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_deletes_users_inactive_for_two_years() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let stale = insert_user(&pool, 1000, Some(800)).await;
+        let never_logged_in = insert_user(&pool, 1000, None).await;
+        let recent = insert_user(&pool, 1000, Some(400)).await;
+        let new_signup = insert_user(&pool, 10, None).await;
+
+        // Act
+        let outcome = cleanup_old_data(&pool).await.unwrap();
+
+        // Assert: other tests may add stale users concurrently
+        assert!(outcome.rows >= 2);
+        assert!(!exists(&pool, &stale).await);
+        assert!(!exists(&pool, &never_logged_in).await);
+        assert!(exists(&pool, &recent).await);
+        assert!(exists(&pool, &new_signup).await);
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_metrics_counts_daily_active_users() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let before = aggregate_metrics(&pool).await.unwrap();
+        insert_user(&pool, 30, Some(0)).await;
+        insert_user(&pool, 30, Some(0)).await;
+        insert_user(&pool, 30, Some(3)).await;
+
+        // Act
+        let after = aggregate_metrics(&pool).await.unwrap();
+
+        // Assert: the two logins within 24 hours count; other tests may log
+        // users in concurrently
+        assert!(after.rows >= before.rows + 2);
+    }
+}
+
+#[cfg(feature = "jobs")]
+mod manual_trigger {
+    use super::common::{self, create_test_db_pool, run_migrations};
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        Router,
+    };
+    use http_body_util::BodyExt;
+    use serde_json::Value;
+    use std::sync::Arc;
+    use tokio::sync::{Notify, Semaphore};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use vibe_api::modules::{
+        auth::jwt::generate_access_token,
+        jobs::{self, registry::METRICS_JOB, tasks::JobOutcome, JobRegistry},
+        users::model::UserRole,
+    };
+
+    fn token(role: UserRole) -> String {
+        generate_access_token(
+            &Uuid::new_v4(),
+            "jobs@example.com",
+            role,
+            &common::app::create_test_jwt_config(),
+        )
+        .unwrap()
+    }
+
+    fn app(registry: JobRegistry) -> Router {
+        jobs::admin_routes(registry, common::app::create_test_jwt_config())
+    }
+
+    async fn trigger(app: Router, name: &str, role: UserRole) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/admin/jobs/{}/run", name))
+            .header(header::AUTHORIZATION, format!("Bearer {}", token(role)))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_admin_triggers_known_job() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let registry = JobRegistry::new(pool).with_job(METRICS_JOB, |pool| async move {
+            jobs::tasks::aggregate_metrics(&pool).await
+        });
+
+        // Act
+        let (status, body) = trigger(app(registry), METRICS_JOB, UserRole::Admin).await;
+
+        // Assert
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["job"], METRICS_JOB);
+        assert!(body["data"]["rows"].is_u64());
+        assert!(body["data"]["duration_ms"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_job_is_not_found() {
+        let pool = create_test_db_pool().await;
+
+        let (status, _) = trigger(app(JobRegistry::new(pool)), "reindex", UserRole::Admin).await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_non_admin_cannot_trigger_jobs() {
+        let pool = create_test_db_pool().await;
+        let registry =
+            JobRegistry::new(pool).with_job("noop", |_| async { Ok(JobOutcome::default()) });
+
+        let (status, _) = trigger(app(registry), "noop", UserRole::Moderator).await;
+
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_job_cannot_run_twice_at_once() {
+        // Arrange: a job that blocks until released
+        let pool = create_test_db_pool().await;
+        let started = Arc::new(Notify::new());
+        let release = Arc::new(Semaphore::new(0));
+        let registry = JobRegistry::new(pool).with_job("slow", {
+            let started = started.clone();
+            let release = release.clone();
+            move |_| {
+                let started = started.clone();
+                let release = release.clone();
+                async move {
+                    started.notify_one();
+                    release.acquire().await.unwrap().forget();
+                    Ok(JobOutcome {
+                        rows: 7,
+                        ..Default::default()
+                    })
+                }
+            }
+        });
+        let first = tokio::spawn(trigger(app(registry.clone()), "slow", UserRole::Admin));
+        started.notified().await;
+
+        // Act
+        let (concurrent, _) = trigger(app(registry.clone()), "slow", UserRole::Admin).await;
+        release.add_permits(1);
+        let (finished, body) = first.await.unwrap();
+
+        // Assert
+        assert_eq!(concurrent, StatusCode::CONFLICT);
+        assert_eq!(finished, StatusCode::OK);
+        assert_eq!(body["data"]["rows"], 7);
+
+        // The lock is released once the run ends
+        release.add_permits(1);
+        let (again, _) = trigger(app(registry), "slow", UserRole::Admin).await;
+        assert_eq!(again, StatusCode::OK);
+    }
+}
+
+#[cfg(feature = "jobs")]
+mod job_history {
+    use super::common::{self, create_test_db_pool, run_migrations};
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use serde_json::Value;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use vibe_api::modules::{
+        auth::jwt::generate_access_token,
+        jobs::{self, registry::CLEANUP_JOB, runs::JobRunRecord, tasks::JobOutcome, JobRegistry},
+        users::model::UserRole,
+    };
+    use vibe_api::utils::error::AppError;
+
+    async fn latest_run(pool: &PgPool, job_name: &str) -> JobRunRecord {
+        sqlx::query_as(
+            "SELECT id, job_name, status, started_at, finished_at, rows_affected, error_message, summary
+             FROM job_runs WHERE job_name = $1 ORDER BY started_at DESC LIMIT 1",
+        )
+        .bind(job_name)
+        .fetch_one(pool)
+        .await
+        .expect("Run was recorded")
+    }
+
+    /// Job name no other test uses
+    fn unique_name(prefix: &str) -> &'static str {
+        Box::leak(format!("{}_{}", prefix, Uuid::new_v4().simple()).into_boxed_str())
+    }
+
+    /// Drop the runs of a job that failed on purpose, so its last failure
+    /// doesn't leave `/api/v1/status` degraded for other tests
+    async fn forget_runs(pool: &PgPool, job_name: &str) {
+        sqlx::query("DELETE FROM job_runs WHERE job_name = $1")
+            .bind(job_name)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_successful_run_is_recorded() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let registry = JobRegistry::new(pool.clone()).with_job(CLEANUP_JOB, |pool| async move {
+            jobs::tasks::cleanup_old_data(&pool).await
+        });
+        let before = chrono::Utc::now();
+
+        // Act
+        let run = registry.run(CLEANUP_JOB).await.unwrap();
+
+        // Assert
+        let record = latest_run(&pool, CLEANUP_JOB).await;
+        assert_eq!(record.status, "succeeded");
+        assert!(record.started_at >= before - chrono::Duration::seconds(1));
+        assert!(record.finished_at >= record.started_at);
+        assert_eq!(record.rows_affected, Some(run.outcome.rows as i64));
+        assert_eq!(record.error_message, None);
+    }
+
+    #[tokio::test]
+    async fn test_failed_run_records_error() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let name = unique_name("failing");
+        let registry = JobRegistry::new(pool.clone()).with_job(name, |_| async {
+            Err::<JobOutcome, _>(AppError::InternalServer("disk full".to_string()))
+        });
+
+        // Act
+        let result = registry.run(name).await;
+
+        // Assert
+        assert!(result.is_err());
+        let record = latest_run(&pool, name).await;
+        assert_eq!(record.status, "failed");
+        assert_eq!(record.rows_affected, None);
+        assert!(record.error_message.unwrap().contains("disk full"));
+        forget_runs(&pool, name).await;
+    }
+
+    #[tokio::test]
+    async fn test_panicking_run_is_recorded_as_failure() {
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let name = unique_name("panicking");
+        let registry = JobRegistry::new(pool.clone()).with_job(name, |_| async {
+            panic!("task bug");
+            #[allow(unreachable_code)]
+            Ok(JobOutcome::default())
+        });
+
+        let result = registry.run(name).await;
+
+        assert!(result.is_err());
+        let record = latest_run(&pool, name).await;
+        assert_eq!(record.status, "failed");
+        assert!(record.error_message.unwrap().contains("panicked"));
+        // The lock was released despite the panic: the job runs again
+        // rather than reporting a conflict
+        assert!(matches!(
+            registry.run(name).await,
+            Err(AppError::InternalServer(_))
+        ));
+        forget_runs(&pool, name).await;
+    }
+
+    #[tokio::test]
+    async fn test_history_endpoint_lists_latest_runs_of_a_job() {
+        // Arrange
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let name = unique_name("history");
+        let registry = JobRegistry::new(pool.clone()).with_job(name, |_| async {
+            Ok(JobOutcome {
+                rows: 3,
+                ..Default::default()
+            })
+        });
+        for _ in 0..3 {
+            registry.run(name).await.unwrap();
+        }
+        let token = generate_access_token(
+            &Uuid::new_v4(),
+            "jobs@example.com",
+            UserRole::Admin,
+            &common::app::create_test_jwt_config(),
+        )
+        .unwrap();
+
+        // Act
+        let request = Request::builder()
+            .uri(format!("/api/v1/admin/jobs/history?name={}&limit=2", name))
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        let response = jobs::admin_routes(registry, common::app::create_test_jwt_config())
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        let runs = body["data"].as_array().unwrap();
+        assert_eq!(runs.len(), 2);
+        assert!(runs.iter().all(|run| run["job_name"] == name));
+        assert!(runs.iter().all(|run| run["rows_affected"] == 3));
+        assert!(runs[0]["started_at"].as_str() >= runs[1]["started_at"].as_str());
+    }
+}
@@ -3,7 +3,10 @@
 
 mod common;
 
-use std::sync::{Arc, atomic::{AtomicU32, Ordering}};
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -47,7 +50,7 @@ async fn test_async_job_execution() {
 
 #[tokio::test]
 async fn test_job_with_database_operation() {
-    use common::{create_test_db_pool, run_migrations, clean_test_db};
+    use common::{clean_test_db, create_test_db_pool, run_migrations};
 
     // Arrange
     let pool = create_test_db_pool().await;
@@ -65,11 +68,10 @@ async fn test_job_with_database_operation() {
 
     // Act - Simulate job execution
     let job_result = async {
-        let result = sqlx::query(
-            "DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'"
-        )
-        .execute(&pool)
-        .await;
+        let result =
+            sqlx::query("DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'")
+                .execute(&pool)
+                .await;
 
         match result {
             Ok(res) => {
@@ -81,7 +83,8 @@ async fn test_job_with_database_operation() {
                 Err(e)
             }
         }
-    }.await;
+    }
+    .await;
 
     // Assert
     assert!(job_result.is_ok(), "Job should complete successfully");
@@ -118,7 +121,8 @@ async fn test_job_handles_database_error_gracefully() {
                 Err(e)
             }
         }
-    }.await;
+    }
+    .await;
 
     // Assert - Job fails but doesn't panic
     assert!(job_result.is_err(), "Job should return error for bad query");
@@ -126,7 +130,7 @@ async fn test_job_handles_database_error_gracefully() {
 
 #[tokio::test]
 async fn test_multiple_jobs_can_run_concurrently() {
-    use common::{create_test_db_pool, run_migrations, clean_test_db};
+    use common::{clean_test_db, create_test_db_pool, run_migrations};
 
     // Arrange
     let pool = create_test_db_pool().await;
@@ -137,7 +141,7 @@ async fn test_multiple_jobs_can_run_concurrently() {
     for i in 0..10 {
         sqlx::query(
             "INSERT INTO users (id, email, password_hash, name, role, last_login)
-             VALUES (gen_random_uuid(), $1, 'hash', 'User', 'user', NOW())"
+             VALUES (gen_random_uuid(), $1, 'hash', 'User', 'user', NOW())",
         )
         .bind(format!("concurrent{}@test.com", i))
         .execute(&pool)
@@ -152,7 +156,7 @@ async fn test_multiple_jobs_can_run_concurrently() {
     let job1 = tokio::spawn(async move {
         // Metrics job
         let result: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'"
+            "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'",
         )
         .fetch_one(&pool1)
         .await
@@ -177,7 +181,11 @@ async fn test_multiple_jobs_can_run_concurrently() {
     // Assert
     assert!(metrics_result.is_ok(), "Metrics job should succeed");
     assert!(count_result.is_ok(), "Count job should succeed");
-    assert_eq!(metrics_result.unwrap(), 10, "Metrics should count all recent users");
+    assert_eq!(
+        metrics_result.unwrap(),
+        10,
+        "Metrics should count all recent users"
+    );
     assert_eq!(count_result.unwrap(), 10, "Count should match total users");
 }
 
@@ -202,7 +210,11 @@ async fn test_job_execution_timing() {
     let duration = start.elapsed();
 
     // Assert
-    assert_eq!(execution_count.load(Ordering::SeqCst), 3, "Job should execute 3 times");
+    assert_eq!(
+        execution_count.load(Ordering::SeqCst),
+        3,
+        "Job should execute 3 times"
+    );
     assert!(
         duration >= Duration::from_millis(300),
         "Total duration should be at least 300ms"
@@ -215,7 +227,7 @@ async fn test_job_execution_timing() {
 
 #[tokio::test]
 async fn test_job_cleanup_logs_result() {
-    use common::{create_test_db_pool, run_migrations, clean_test_db};
+    use common::{clean_test_db, create_test_db_pool, run_migrations};
 
     // Arrange
     let pool = create_test_db_pool().await;
@@ -236,18 +248,18 @@ async fn test_job_cleanup_logs_result() {
 
     // Act - Job with logging
     let deleted_count = async {
-        let result = sqlx::query(
-            "DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'"
-        )
-        .execute(&pool)
-        .await
-        .expect("Cleanup failed");
+        let result =
+            sqlx::query("DELETE FROM users WHERE last_login < NOW() - INTERVAL '365 days'")
+                .execute(&pool)
+                .await
+                .expect("Cleanup failed");
 
         let count = result.rows_affected();
         println!("[Cleanup Job] Deleted {} inactive users", count);
 
         count
-    }.await;
+    }
+    .await;
 
     // Assert
     assert_eq!(deleted_count, 5, "Should delete all 5 old users");
@@ -255,7 +267,7 @@ async fn test_job_cleanup_logs_result() {
 
 #[tokio::test]
 async fn test_metrics_job_logs_result() {
-    use common::{create_test_db_pool, run_migrations, clean_test_db};
+    use common::{clean_test_db, create_test_db_pool, run_migrations};
 
     // Arrange
     let pool = create_test_db_pool().await;
@@ -266,7 +278,7 @@ async fn test_metrics_job_logs_result() {
     for i in 0..7 {
         sqlx::query(
             "INSERT INTO users (id, email, password_hash, name, role, last_login)
-             VALUES (gen_random_uuid(), $1, 'hash', 'Active User', 'user', NOW())"
+             VALUES (gen_random_uuid(), $1, 'hash', 'Active User', 'user', NOW())",
         )
         .bind(format!("active{}@test.com", i))
         .execute(&pool)
@@ -277,7 +289,7 @@ async fn test_metrics_job_logs_result() {
     // Act - Metrics job with logging
     let dau_count = async {
         let result: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'"
+            "SELECT COUNT(*) FROM users WHERE last_login >= NOW() - INTERVAL '24 hours'",
         )
         .fetch_one(&pool)
         .await
@@ -287,7 +299,8 @@ async fn test_metrics_job_logs_result() {
         println!("[Metrics Job] Daily Active Users (DAU): {}", count);
 
         count
-    }.await;
+    }
+    .await;
 
     // Assert
     assert_eq!(dau_count, 7, "DAU should be 7");
@@ -309,7 +322,8 @@ async fn test_job_error_does_not_panic_scheduler() {
                 count.fetch_add(1, Ordering::SeqCst);
                 Ok(())
             }
-        }.await;
+        }
+        .await;
 
         // Scheduler should log error and continue
         if let Err(e) = job_result {
@@ -319,5 +333,47 @@ async fn test_job_error_does_not_panic_scheduler() {
     }
 
     // Assert - Successful jobs still ran
-    assert_eq!(error_count.load(Ordering::SeqCst), 2, "2 jobs should succeed despite 1 failure");
+    assert_eq!(
+        error_count.load(Ordering::SeqCst),
+        2,
+        "2 jobs should succeed despite 1 failure"
+    );
+}
+
+#[cfg(feature = "jobs")]
+mod scheduler_handle {
+    use super::common::{create_test_db_pool, run_migrations};
+    use vibe_api::config::SchedulerConfig;
+    use vibe_api::modules::jobs::{JobRegistry, SchedulerHandle};
+
+    fn config(cleanup_cron: &str) -> SchedulerConfig {
+        SchedulerConfig {
+            cleanup_cron: cleanup_cron.to_string(),
+            metrics_cron: "0 0 * * * *".to_string(),
+            purge_cron: "0 0 3 * * *".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_starts_and_stops() {
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+
+        let handle = SchedulerHandle::new(JobRegistry::new(pool), &config("0 0 0 * * *"))
+            .await
+            .unwrap();
+
+        handle.start().await.unwrap();
+        handle.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_cron_expression_is_rejected() {
+        let pool = create_test_db_pool().await;
+
+        let result = SchedulerHandle::new(JobRegistry::new(pool), &config("every midnight")).await;
+
+        let error = result.err().expect("Invalid cron must not schedule");
+        assert!(error.to_string().contains("every midnight"));
+    }
 }
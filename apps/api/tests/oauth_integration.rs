@@ -0,0 +1,305 @@
+// Google OAuth integration tests
+// Runs the authorization code flow against local token/userinfo endpoints
+
+mod common;
+
+use axum::{
+    body::Body,
+    extract::{Form, State},
+    http::{header, HeaderMap, Request, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use http_body_util::BodyExt;
+use serde_json::json;
+use sqlx::PgPool;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tower::ServiceExt;
+use uuid::Uuid;
+use vibe_api::{
+    config::GoogleOAuthConfig,
+    events::EventBus,
+    modules::auth::{google, GoogleOAuth},
+};
+
+use common::{app, create_test_db_pool, run_migrations};
+
+const VALID_CODE: &str = "valid-code";
+const ACCESS_TOKEN: &str = "google-access-token";
+
+/// Stand-in for Google's token and userinfo endpoints
+#[derive(Clone)]
+struct MockGoogle {
+    userinfo: Arc<Mutex<serde_json::Value>>,
+}
+
+async fn token_endpoint(
+    Form(form): Form<HashMap<String, String>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let valid = form.get("code").map(String::as_str) == Some(VALID_CODE)
+        && form.get("grant_type").map(String::as_str) == Some("authorization_code")
+        && form.get("client_secret").map(String::as_str) == Some("test-client-secret");
+    if valid {
+        (
+            StatusCode::OK,
+            Json(json!({ "access_token": ACCESS_TOKEN, "token_type": "Bearer" })),
+        )
+    } else {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "invalid_grant" })),
+        )
+    }
+}
+
+async fn userinfo_endpoint(
+    State(mock): State<MockGoogle>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let authorized = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        == Some(&format!("Bearer {}", ACCESS_TOKEN));
+    if authorized {
+        (StatusCode::OK, Json(mock.userinfo.lock().unwrap().clone()))
+    } else {
+        (StatusCode::UNAUTHORIZED, Json(json!({})))
+    }
+}
+
+/// Google sign-in routes wired to a local mock answering with `userinfo`
+async fn google_app(pool: PgPool, userinfo: serde_json::Value) -> (Router, MockGoogle) {
+    let mock = MockGoogle {
+        userinfo: Arc::new(Mutex::new(userinfo)),
+    };
+    let mock_app = Router::new()
+        .route("/token", post(token_endpoint))
+        .route("/userinfo", get(userinfo_endpoint))
+        .with_state(mock.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, mock_app).await.unwrap() });
+
+    let config = GoogleOAuthConfig {
+        client_id: "test-client-id".to_string(),
+        client_secret: "test-client-secret".to_string(),
+        redirect_uri: "http://localhost:3000/auth/oauth/google/callback".to_string(),
+        auth_url: format!("{}/auth", base),
+        token_url: format!("{}/token", base),
+        userinfo_url: format!("{}/userinfo", base),
+    };
+    let router = google::routes(
+        pool,
+        app::create_test_jwt_config(),
+        GoogleOAuth::new(config),
+        None,
+        EventBus::default(),
+    );
+
+    (router, mock)
+}
+
+fn google_profile(subject: &str, email: &str, verified: bool) -> serde_json::Value {
+    json!({
+        "sub": subject,
+        "email": email,
+        "email_verified": verified,
+        "name": "Google User",
+    })
+}
+
+async fn callback(app: Router, code: &str) -> (StatusCode, HeaderMap, serde_json::Value) {
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!(
+                    "/auth/oauth/google/callback?code={}&state=csrf-123",
+                    code
+                ))
+                .header(header::COOKIE, "oauth_state=csrf-123")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, headers, serde_json::from_slice(&body).unwrap())
+}
+
+fn unique_email(prefix: &str) -> String {
+    format!("{}-{}@example.com", prefix, Uuid::new_v4().simple())
+}
+
+async fn connection_owner(pool: &PgPool, subject: &str) -> Option<Uuid> {
+    sqlx::query_scalar(
+        "SELECT user_id FROM oauth_connections WHERE provider = 'google' AND provider_user_id = $1",
+    )
+    .bind(subject)
+    .fetch_optional(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_google_start_redirects_to_consent_screen_with_state_cookie() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let (app, _) = google_app(pool, json!({})).await;
+
+    // Act
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/auth/oauth/google")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let location = response.headers()[header::LOCATION].to_str().unwrap();
+    assert!(location.contains("/auth?client_id=test-client-id"));
+    assert!(location.contains("scope=openid+email+profile"));
+    let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+    let state = cookie
+        .strip_prefix("oauth_state=")
+        .and_then(|rest| rest.split(';').next())
+        .unwrap();
+    assert!(location.contains(&format!("state={}", state)));
+    assert!(cookie.contains("HttpOnly"));
+}
+
+#[tokio::test]
+async fn test_google_callback_creates_new_user() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let subject = Uuid::new_v4().to_string();
+    let email = unique_email("google-new");
+    let (app, _) = google_app(pool.clone(), google_profile(&subject, &email, true)).await;
+
+    // Act
+    let (status, headers, json) = callback(app, VALID_CODE).await;
+
+    // Assert: the normal token pair for a fresh, verified, password-less account
+    assert_eq!(status, StatusCode::OK);
+    assert!(json["data"]["access_token"].is_string());
+    assert!(json["data"]["refresh_token"].is_string());
+    assert_eq!(json["data"]["user"]["email"], email.as_str());
+    assert_eq!(json["data"]["user"]["name"], "Google User");
+    assert!(headers[header::SET_COOKIE]
+        .to_str()
+        .unwrap()
+        .starts_with("oauth_state=;"));
+    let user_id = Uuid::parse_str(json["data"]["user"]["id"].as_str().unwrap()).unwrap();
+    let (password_hash, email_verified): (Option<String>, bool) =
+        sqlx::query_as("SELECT password_hash, email_verified FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(password_hash, None);
+    assert!(email_verified);
+    assert_eq!(connection_owner(&pool, &subject).await, Some(user_id));
+}
+
+#[tokio::test]
+async fn test_google_callback_links_existing_user_by_verified_email() {
+    // Arrange: a password account with the same address
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let email = unique_email("google-link");
+    let existing = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id, email, password_hash, name) VALUES ($1, $2, $3, $4)")
+        .bind(existing)
+        .bind(&email)
+        .bind("$argon2id$v=19$m=19456,t=2,p=1$test")
+        .bind("Existing User")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let subject = Uuid::new_v4().to_string();
+    let (app, mock) = google_app(pool.clone(), google_profile(&subject, &email, true)).await;
+
+    // Act
+    let (status, _, json) = callback(app.clone(), VALID_CODE).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["user"]["id"], existing.to_string().as_str());
+    assert_eq!(json["data"]["user"]["name"], "Existing User");
+    assert_eq!(connection_owner(&pool, &subject).await, Some(existing));
+
+    // Act: the Google account later changes its address
+    *mock.userinfo.lock().unwrap() =
+        google_profile(&subject, &unique_email("google-renamed"), true);
+    let (status, _, json) = callback(app, VALID_CODE).await;
+
+    // Assert: still the linked account, matched on the subject id
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"]["user"]["id"], existing.to_string().as_str());
+}
+
+#[tokio::test]
+async fn test_google_callback_refuses_unverified_email() {
+    // Arrange: an unverified Google address must not claim an existing account
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let email = unique_email("google-unverified");
+    sqlx::query("INSERT INTO users (id, email, password_hash, name) VALUES ($1, $2, $3, $4)")
+        .bind(Uuid::new_v4())
+        .bind(&email)
+        .bind("$argon2id$v=19$m=19456,t=2,p=1$test")
+        .bind("Victim")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let subject = Uuid::new_v4().to_string();
+    let (app, _) = google_app(pool.clone(), google_profile(&subject, &email, false)).await;
+
+    // Act
+    let (status, _, json) = callback(app, VALID_CODE).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(json["error"]["code"], "AUTHENTICATION_ERROR");
+    assert_eq!(connection_owner(&pool, &subject).await, None);
+}
+
+#[tokio::test]
+async fn test_google_callback_rejects_state_mismatch_and_bad_code() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let subject = Uuid::new_v4().to_string();
+    let profile = google_profile(&subject, &unique_email("google-csrf"), true);
+    let (app, _) = google_app(pool.clone(), profile).await;
+
+    // Act
+    let mismatched = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/auth/oauth/google/callback?code=valid-code&state=forged")
+                .header(header::COOKIE, "oauth_state=csrf-123")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let (bad_code_status, _, _) = callback(app, "expired-code").await;
+
+    // Assert
+    assert_eq!(mismatched.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(bad_code_status, StatusCode::UNAUTHORIZED);
+    assert_eq!(connection_owner(&pool, &subject).await, None);
+}
@@ -0,0 +1,280 @@
+// OpenAPI document tests
+// Validates that /api-docs/openapi.json documents every mounted route
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tower::ServiceExt;
+use vibe_api::openapi::{self, OPENAPI_JSON_PATH};
+
+/// Every route mounted in a build without optional features, by method
+const CORE_OPERATIONS: &[(&str, &str)] = &[
+    ("post", "/auth/register"),
+    ("post", "/auth/login"),
+    ("post", "/auth/refresh"),
+    ("post", "/auth/forgot-password"),
+    ("post", "/auth/reset-password"),
+    ("get", "/auth/verify-email"),
+    ("post", "/auth/resend-verification"),
+    ("get", "/auth/config"),
+    ("post", "/auth/logout"),
+    ("post", "/auth/logout-all"),
+    ("get", "/auth/oauth/google"),
+    ("get", "/auth/oauth/google/callback"),
+    ("get", "/users/me"),
+    ("patch", "/users/me"),
+    ("delete", "/users/me"),
+    ("post", "/users/me/accept-terms"),
+    ("put", "/users/me/password"),
+    ("get", "/users/me/connections"),
+    ("delete", "/users/me/connections/{provider}"),
+    ("get", "/users/me/sessions"),
+    ("delete", "/users/me/sessions/{id}"),
+    ("get", "/users"),
+    ("get", "/users/{id}"),
+    ("delete", "/users/{id}"),
+    ("patch", "/users/{id}/role"),
+    ("post", "/users/{id}/suspend"),
+    ("post", "/users/{id}/unsuspend"),
+    ("get", "/api/v1/admin/webhooks"),
+    ("post", "/api/v1/admin/webhooks"),
+    ("get", "/api/v1/admin/webhooks/{id}"),
+    ("patch", "/api/v1/admin/webhooks/{id}"),
+    ("delete", "/api/v1/admin/webhooks/{id}"),
+    ("get", "/api/v1/admin/webhooks/{id}/deliveries"),
+    ("get", "/api/v1/admin/api-keys"),
+    ("patch", "/api/v1/admin/api-keys/{id}"),
+    ("delete", "/api/v1/admin/api-keys/{id}"),
+    ("get", "/api/v1/admin/read-only"),
+    ("put", "/api/v1/admin/read-only"),
+    ("get", "/api/v1/health"),
+    ("get", "/api/v1/health/database"),
+    ("get", "/api/v1/health/live"),
+    ("get", "/api/v1/health/ready"),
+    ("get", "/api/v1/status"),
+    ("get", "/health"),
+    ("get", "/ready"),
+    ("get", "/metrics"),
+    ("get", "/api/v1/ping"),
+    ("get", "/api/v1/version"),
+    ("get", "/api/v1/config"),
+    ("get", "/graphql"),
+    ("post", "/graphql"),
+];
+
+/// Routes the `ai` feature mounts
+#[cfg(feature = "ai")]
+const AI_OPERATIONS: &[(&str, &str)] = &[
+    ("post", "/ai/chat"),
+    ("post", "/ai/chat/stream"),
+    ("post", "/ai/embeddings"),
+    ("get", "/ai/models"),
+    ("get", "/ai/usage"),
+    ("get", "/ai/usage/users/{id}"),
+];
+
+/// Routes the `storage` feature mounts
+#[cfg(feature = "storage")]
+const STORAGE_OPERATIONS: &[(&str, &str)] = &[
+    ("post", "/storage/upload"),
+    ("post", "/storage/presigned-upload"),
+    ("post", "/storage/{file_id}/confirm"),
+    ("get", "/storage/{file_id}/presigned-download"),
+    ("get", "/storage/{file_id}/metadata"),
+    ("get", "/storage/{file_id}/thumbnail"),
+    ("delete", "/storage/{file_id}"),
+];
+
+/// Routes the `jobs` feature mounts
+#[cfg(feature = "jobs")]
+const JOBS_OPERATIONS: &[(&str, &str)] = &[
+    ("post", "/api/v1/admin/jobs/{name}/run"),
+    ("get", "/api/v1/admin/jobs/history"),
+];
+
+const METHODS: &[&str] = &["get", "post", "put", "patch", "delete"];
+
+/// Every route mounted in this build
+fn mounted_operations() -> Vec<(&'static str, &'static str)> {
+    #[allow(unused_mut)]
+    let mut operations = CORE_OPERATIONS.to_vec();
+    #[cfg(feature = "ai")]
+    operations.extend_from_slice(AI_OPERATIONS);
+    #[cfg(feature = "storage")]
+    operations.extend_from_slice(STORAGE_OPERATIONS);
+    #[cfg(feature = "jobs")]
+    operations.extend_from_slice(JOBS_OPERATIONS);
+    #[cfg(feature = "websocket")]
+    operations.push(("get", "/ws"));
+    operations
+}
+
+/// Fetch the document the way Swagger UI does
+async fn served_document() -> Value {
+    let response = openapi::routes(openapi::openapi())
+        .oneshot(
+            Request::builder()
+                .uri(OPENAPI_JSON_PATH)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).expect("OpenAPI document should be JSON")
+}
+
+/// Every `$ref` anywhere below `value`
+fn collect_refs<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get("$ref") {
+                refs.push(reference);
+            }
+            map.values().for_each(|v| collect_refs(v, refs));
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+        _ => {}
+    }
+}
+
+#[tokio::test]
+async fn test_openapi_json_lists_key_paths() {
+    // Act
+    let doc = served_document().await;
+
+    // Assert
+    assert!(doc["openapi"].as_str().unwrap().starts_with("3."));
+    assert!(doc["info"]["title"].is_string());
+    assert!(doc["paths"]["/auth/login"]["post"].is_object());
+    assert!(doc["paths"]["/users/me"]["get"].is_object());
+    assert_eq!(
+        doc["paths"]["/auth/login"]["post"]["responses"]["200"]["content"]["application/json"]
+            ["schema"]["$ref"],
+        "#/components/schemas/ApiResponse_AuthResponse"
+    );
+    let data = &doc["components"]["schemas"]["ApiResponse_AuthResponse"]["properties"]["data"];
+    assert!(data["properties"]["access_token"].is_object());
+    assert_eq!(
+        data["properties"]["user"]["$ref"],
+        "#/components/schemas/UserInfo"
+    );
+    assert!(doc["components"]["schemas"]["LoginRequest"].is_object());
+    assert!(doc["components"]["schemas"]["UserResponse"].is_object());
+    assert!(doc["components"]["schemas"]["ErrorResponse"].is_object());
+}
+
+#[tokio::test]
+async fn test_openapi_documents_every_mounted_route() {
+    // Act
+    let doc = served_document().await;
+
+    // Assert: each route is present, with its path parameters declared
+    let mounted = mounted_operations();
+    for (method, path) in &mounted {
+        let operation = &doc["paths"][*path][*method];
+        assert!(
+            operation.is_object(),
+            "{} {} is not documented",
+            method,
+            path
+        );
+        assert!(
+            operation["responses"]
+                .as_object()
+                .is_some_and(|r| !r.is_empty()),
+            "{} {} has no responses",
+            method,
+            path
+        );
+
+        let declared: Vec<&str> = operation["parameters"]
+            .as_array()
+            .map(|params| {
+                params
+                    .iter()
+                    .filter(|param| param["in"] == "path")
+                    .filter_map(|param| param["name"].as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let templated: Vec<&str> = path
+            .split('/')
+            .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+            .collect();
+        assert_eq!(declared, templated, "{} {} path parameters", method, path);
+    }
+
+    // Assert: nothing is documented that is not mounted
+    let unmounted: Vec<(&str, &str)> = doc["paths"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .flat_map(|(path, item)| {
+            METHODS
+                .iter()
+                .filter(|method| item.get(**method).is_some())
+                .map(move |method| (*method, path.as_str()))
+        })
+        .filter(|operation| !mounted.contains(operation))
+        .collect();
+    assert!(
+        unmounted.is_empty(),
+        "documented but not mounted: {:?}",
+        unmounted
+    );
+}
+
+#[tokio::test]
+async fn test_openapi_schema_references_resolve() {
+    // Act
+    let doc = served_document().await;
+
+    // Assert
+    let mut refs = Vec::new();
+    collect_refs(&doc, &mut refs);
+    assert!(!refs.is_empty());
+    for reference in refs {
+        let name = reference
+            .strip_prefix("#/components/schemas/")
+            .unwrap_or_else(|| panic!("Unexpected reference {}", reference));
+        assert!(
+            doc["components"]["schemas"][name].is_object(),
+            "Missing schema {}",
+            name
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_openapi_defines_bearer_auth_for_protected_routes() {
+    // Act
+    let doc = served_document().await;
+
+    // Assert: Swagger UI can authorize with an access token
+    let scheme = &doc["components"]["securitySchemes"]["bearer_auth"];
+    assert_eq!(scheme["type"], "http");
+    assert_eq!(scheme["scheme"], "bearer");
+    assert_eq!(scheme["bearerFormat"], "JWT");
+
+    let requires_token = |method: &str, path: &str| {
+        doc["paths"][path][method]["security"]
+            .as_array()
+            .is_some_and(|requirements| {
+                requirements
+                    .iter()
+                    .any(|requirement| requirement.get("bearer_auth").is_some())
+            })
+    };
+    assert!(requires_token("get", "/users/me"));
+    assert!(requires_token("get", "/users"));
+    assert!(requires_token("post", "/auth/logout-all"));
+    assert!(requires_token("get", "/api/v1/admin/webhooks"));
+    assert!(!requires_token("post", "/auth/login"));
+    assert!(!requires_token("get", "/api/v1/health"));
+}
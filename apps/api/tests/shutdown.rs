@@ -0,0 +1,161 @@
+// Graceful shutdown tests
+// Serves over a real listener, triggers the shutdown signal and checks draining
+
+mod common;
+
+use axum::{extract::State, routing::get, Router};
+use sqlx::PgPool;
+use std::{net::SocketAddr, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+    task::JoinHandle,
+};
+use vibe_api::{
+    database,
+    middleware::{in_flight::track_in_flight, InFlightTracker},
+    shutdown::{self, DrainReport},
+};
+
+use common::create_test_db_pool;
+
+fn create_app(pool: PgPool, tracker: InFlightTracker) -> Router {
+    Router::new()
+        .route(
+            "/db",
+            get(|State(pool): State<PgPool>| async move {
+                let one: i32 = sqlx::query_scalar("SELECT 1")
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+                one.to_string()
+            }),
+        )
+        .route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                "done"
+            }),
+        )
+        .route("/stuck", get(std::future::pending::<&'static str>))
+        .with_state(pool)
+        .layer(axum::middleware::from_fn_with_state(
+            tracker,
+            track_in_flight,
+        ))
+}
+
+/// Serve `app` until the returned sender fires
+async fn start_server(
+    app: Router,
+    tracker: InFlightTracker,
+    grace: Duration,
+) -> (
+    SocketAddr,
+    oneshot::Sender<()>,
+    JoinHandle<std::io::Result<DrainReport>>,
+) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (signal_tx, signal_rx) = oneshot::channel::<()>();
+    let server = tokio::spawn(async move {
+        let signal = async {
+            let _ = signal_rx.await;
+        };
+        shutdown::serve(listener, app, signal, &tracker, grace).await
+    });
+
+    (addr, signal_tx, server)
+}
+
+/// Raw HTTP/1.1 GET, returning the whole response
+async fn get_raw(addr: SocketAddr, path: &str) -> std::io::Result<String> {
+    let mut stream = TcpStream::connect(addr).await?;
+    stream
+        .write_all(
+            format!(
+                "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+                path
+            )
+            .as_bytes(),
+        )
+        .await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response)
+}
+
+async fn wait_for_in_flight(tracker: &InFlightTracker, expected: usize) {
+    while tracker.in_flight() < expected {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown_drains_requests_then_stops_serving_and_closes_pool() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    let tracker = InFlightTracker::new();
+    let app = create_app(pool.clone(), tracker.clone());
+    let (addr, signal, server) = start_server(app, tracker.clone(), Duration::from_secs(5)).await;
+    assert!(get_raw(addr, "/db")
+        .await
+        .unwrap()
+        .starts_with("HTTP/1.1 200"));
+    let slow = tokio::spawn(get_raw(addr, "/slow"));
+    wait_for_in_flight(&tracker, 1).await;
+
+    // Act: the sequence main runs on SIGTERM
+    signal.send(()).unwrap();
+    let report = server.await.unwrap().unwrap();
+    database::close_pool(pool.clone()).await;
+
+    // Assert: the in-flight request finished, nothing new is accepted
+    assert_eq!(
+        report,
+        DrainReport {
+            drained: 1,
+            abandoned: 0
+        }
+    );
+    let slow = slow.await.unwrap().unwrap();
+    assert!(slow.starts_with("HTTP/1.1 200"));
+    assert!(slow.ends_with("done"));
+    assert!(TcpStream::connect(addr).await.is_err());
+    assert!(pool.is_closed());
+    assert!(sqlx::query("SELECT 1").execute(&pool).await.is_err());
+}
+
+#[tokio::test]
+async fn test_shutdown_abandons_requests_still_running_after_grace() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    let tracker = InFlightTracker::new();
+    let app = create_app(pool, tracker.clone());
+    let (addr, signal, server) =
+        start_server(app, tracker.clone(), Duration::from_millis(200)).await;
+    let stuck = tokio::spawn(get_raw(addr, "/stuck"));
+    wait_for_in_flight(&tracker, 1).await;
+
+    // Act
+    signal.send(()).unwrap();
+    let report = tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("Shutdown must not wait for a stuck request beyond the grace period")
+        .unwrap()
+        .unwrap();
+
+    // Assert: shutdown went ahead without an answer to the stuck request
+    assert_eq!(
+        report,
+        DrainReport {
+            drained: 0,
+            abandoned: 1
+        }
+    );
+    assert!(!stuck.is_finished());
+    assert!(TcpStream::connect(addr).await.is_err());
+    stuck.abort();
+}
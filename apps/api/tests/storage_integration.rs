@@ -9,9 +9,7 @@ use axum::{
 };
 use bytes::Bytes;
 use common::{
-    create_test_app, create_test_db_pool, run_migrations, clean_test_db,
-    MockS3Storage, create_test_file, create_test_image, create_test_pdf,
-    generate_presigned_url,
+    create_test_app, create_test_file, create_test_image, create_test_pdf, MockS3Storage,
 };
 use http_body_util::BodyExt;
 use serde_json::{json, Value};
@@ -282,8 +280,12 @@ async fn test_storage_list_files() {
     let storage = MockS3Storage::new("test-bucket");
     let data = Bytes::from("content");
 
-    storage.upload("file1.txt", "text/plain", data.clone()).unwrap();
-    storage.upload("file2.txt", "text/plain", data.clone()).unwrap();
+    storage
+        .upload("file1.txt", "text/plain", data.clone())
+        .unwrap();
+    storage
+        .upload("file2.txt", "text/plain", data.clone())
+        .unwrap();
     storage.upload("file3.txt", "text/plain", data).unwrap();
 
     // Act
@@ -311,7 +313,9 @@ async fn test_storage_clear_all_files() {
     let storage = MockS3Storage::new("test-bucket");
     let data = Bytes::from("content");
 
-    storage.upload("file1.txt", "text/plain", data.clone()).unwrap();
+    storage
+        .upload("file1.txt", "text/plain", data.clone())
+        .unwrap();
     storage.upload("file2.txt", "text/plain", data).unwrap();
 
     assert_eq!(storage.list_files().len(), 2);
@@ -329,8 +333,12 @@ async fn test_storage_total_size_calculation() {
     // Arrange
     let storage = MockS3Storage::new("test-bucket");
 
-    storage.upload("file1.txt", "text/plain", Bytes::from("12345")).unwrap();
-    storage.upload("file2.txt", "text/plain", Bytes::from("678")).unwrap();
+    storage
+        .upload("file1.txt", "text/plain", Bytes::from("12345"))
+        .unwrap();
+    storage
+        .upload("file2.txt", "text/plain", Bytes::from("678"))
+        .unwrap();
 
     // Act
     let total = storage.total_size();
@@ -339,56 +347,6 @@ async fn test_storage_total_size_calculation() {
     assert_eq!(total, 8); // 5 + 3 bytes
 }
 
-#[tokio::test]
-async fn test_presigned_url_upload_generation() {
-    // Arrange
-    let bucket = "test-bucket";
-    let file_id = "test-file-123";
-    let expiry = 3600;
-
-    // Act
-    let url = generate_presigned_url(bucket, file_id, "putObject", expiry);
-
-    // Assert
-    assert!(url.contains(bucket));
-    assert!(url.contains(file_id));
-    assert!(url.contains("X-Amz-Algorithm=AWS4-HMAC-SHA256"));
-    assert!(url.contains("X-Amz-Expires"));
-    assert!(url.contains("operation=putObject"));
-}
-
-#[tokio::test]
-async fn test_presigned_url_download_generation() {
-    // Arrange
-    let bucket = "test-bucket";
-    let file_id = "test-file-456";
-    let expiry = 1800;
-
-    // Act
-    let url = generate_presigned_url(bucket, file_id, "getObject", expiry);
-
-    // Assert
-    assert!(url.contains(bucket));
-    assert!(url.contains(file_id));
-    assert!(url.contains("operation=getObject"));
-}
-
-#[tokio::test]
-async fn test_presigned_url_different_expiry_times() {
-    // Arrange
-    let bucket = "test-bucket";
-    let file_id = "test-file";
-
-    // Act
-    let url_1h = generate_presigned_url(bucket, file_id, "getObject", 3600);
-    let url_24h = generate_presigned_url(bucket, file_id, "getObject", 86400);
-
-    // Assert
-    assert_ne!(url_1h, url_24h);
-    assert!(url_1h.contains("X-Amz-Expires="));
-    assert!(url_24h.contains("X-Amz-Expires="));
-}
-
 #[tokio::test]
 async fn test_concurrent_file_uploads() {
     // Arrange
@@ -400,11 +358,7 @@ async fn test_concurrent_file_uploads() {
         let storage_clone = storage.clone();
         let handle = tokio::spawn(async move {
             let data = Bytes::from(format!("content {}", i));
-            storage_clone.upload(
-                &format!("file{}.txt", i),
-                "text/plain",
-                data
-            )
+            storage_clone.upload(&format!("file{}.txt", i), "text/plain", data)
         });
         handles.push(handle);
     }
@@ -429,7 +383,9 @@ async fn test_concurrent_file_downloads() {
     let mut file_ids = vec![];
     for i in 0..5 {
         let data = Bytes::from(format!("content {}", i));
-        let id = storage.upload(&format!("file{}.txt", i), "text/plain", data).unwrap();
+        let id = storage
+            .upload(&format!("file{}.txt", i), "text/plain", data)
+            .unwrap();
         file_ids.push(id);
     }
 
@@ -437,9 +393,7 @@ async fn test_concurrent_file_downloads() {
     let mut handles = vec![];
     for file_id in file_ids {
         let storage_clone = storage.clone();
-        let handle = tokio::spawn(async move {
-            storage_clone.download(&file_id)
-        });
+        let handle = tokio::spawn(async move { storage_clone.download(&file_id) });
         handles.push(handle);
     }
 
@@ -492,116 +446,1336 @@ async fn test_storage_with_different_content_types() {
     }
 }
 
-// The following tests demonstrate patterns for testing actual storage endpoints
-// They would need the full storage module setup to run
+// ============================================================================
+// Upload limits (require the storage feature)
+// ============================================================================
+
+#[cfg(feature = "storage")]
+mod upload_limits {
+    use super::*;
+    use crate::common::create_test_db_pool;
+    use futures::StreamExt;
+    use std::time::Duration;
+    use uuid::Uuid;
+    use vibe_api::{
+        config::StorageConfig,
+        modules::{auth::jwt::generate_access_token, storage, users::model::UserRole},
+    };
+
+    const BOUNDARY: &str = "vibe-test-boundary";
+
+    fn test_storage_config(max_concurrent_uploads: usize) -> StorageConfig {
+        StorageConfig {
+            s3_bucket: "test-bucket".to_string(),
+            s3_region: "us-east-1".to_string(),
+            s3_endpoint: Some("http://127.0.0.1:9".to_string()),
+            s3_access_key: "test".to_string(),
+            s3_secret_key: "test".to_string(),
+            max_file_size_mb: 1,
+            max_concurrent_uploads,
+            upload_read_timeout_secs: 1,
+            s3_timeout_secs: 1,
+            s3_max_attempts: 1,
+            s3_retry_base_ms: 1,
+            dedupe_uploads: true,
+            presigned_url_expiry_secs: 3600,
+        }
+    }
 
-#[tokio::test]
-#[ignore] // Requires storage feature and route setup
-async fn test_storage_upload_endpoint() {
-    // This test would:
-    // 1. Create app with storage routes
-    // 2. Create multipart form with file
-    // 3. POST to /storage/upload
-    // 4. Assert 200 OK and file_id returned
-    // 5. Verify file in storage
-
-    let pool = create_test_db_pool().await;
-    run_migrations(&pool).await;
-    clean_test_db(&pool).await;
-
-    // Pattern established for future implementation
-    assert!(true);
-}
+    async fn storage_app(max_concurrent_uploads: usize) -> axum::Router {
+        storage::routes(
+            create_test_db_pool().await,
+            common::app::create_test_jwt_config(),
+            test_storage_config(max_concurrent_uploads),
+        )
+        .await
+    }
 
-#[tokio::test]
-#[ignore]
-async fn test_storage_upload_exceeds_max_size() {
-    // This test would:
-    // 1. Create file larger than MAX_FILE_SIZE
-    // 2. POST to /storage/upload
-    // 3. Assert 400 BAD_REQUEST
-    // 4. Assert error message about file size
-
-    assert!(true);
-}
+    /// Multipart upload that sends its first part header and then stalls forever
+    fn stalled_upload() -> Request<Body> {
+        let head = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"slow.txt\"\r\n\r\npartial",
+            BOUNDARY
+        );
+        let stream = futures::stream::iter(vec![Ok::<_, std::io::Error>(Bytes::from(head))])
+            .chain(futures::stream::pending());
+        let token = generate_access_token(
+            &Uuid::new_v4(),
+            "uploader@example.com",
+            UserRole::User,
+            &common::app::create_test_jwt_config(),
+        )
+        .unwrap();
+
+        Request::builder()
+            .method("POST")
+            .uri("/storage/upload")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            )
+            .body(Body::from_stream(stream))
+            .unwrap()
+    }
 
-#[tokio::test]
-#[ignore]
-async fn test_storage_presigned_upload_url_endpoint() {
-    // This test would:
-    // 1. GET /storage/presigned-upload?filename=test.pdf&content_type=application/pdf
-    // 2. Assert 200 OK
-    // 3. Assert response contains presigned URL
-    // 4. Assert URL is valid format
-
-    assert!(true);
+    #[tokio::test]
+    async fn test_stalled_upload_returns_408() {
+        let app = storage_app(4).await;
+
+        let response = app.oneshot(stalled_upload()).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn test_upload_beyond_concurrency_cap_is_rejected() {
+        // Arrange
+        let app = storage_app(1).await;
+        let in_flight = tokio::spawn(app.clone().oneshot(stalled_upload()));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Act
+        let rejected = app.oneshot(stalled_upload()).await.unwrap();
+
+        // Assert
+        assert_eq!(rejected.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(rejected.headers().contains_key(header::RETRY_AFTER));
+        assert_eq!(
+            in_flight.await.unwrap().unwrap().status(),
+            StatusCode::REQUEST_TIMEOUT
+        );
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_storage_presigned_download_url_endpoint() {
-    // This test would:
-    // 1. Upload a file
-    // 2. GET /storage/presigned-download/:file_id
-    // 3. Assert 200 OK
-    // 4. Assert response contains presigned URL
-
-    assert!(true);
+#[cfg(feature = "storage")]
+mod upload_endpoint {
+    use super::*;
+    use crate::common::{create_test_db_pool, run_migrations};
+    use axum::{extract::Request as UpstreamRequest, response::IntoResponse, Router};
+    use sqlx::PgPool;
+    use std::sync::{Arc, Mutex};
+    use uuid::Uuid;
+    use vibe_api::{
+        config::StorageConfig,
+        modules::{auth::jwt::generate_access_token, storage, users::model::UserRole},
+    };
+
+    const BOUNDARY: &str = "vibe-upload-boundary";
+
+    /// Requests the fake bucket received: method, path and body length
+    type Received = Arc<Mutex<Vec<(String, String, usize)>>>;
+
+    /// Minimal S3 stand-in accepting every call; returns its endpoint URL
+    async fn spawn_fake_s3() -> (String, Received) {
+        let received: Received = Arc::default();
+        let log = received.clone();
+        let app = Router::new().fallback(move |request: UpstreamRequest| {
+            let log = log.clone();
+            async move {
+                let method = request.method().to_string();
+                let path = request.uri().path().to_string();
+                let body = request.into_body().collect().await.unwrap().to_bytes();
+                log.lock().unwrap().push((method, path, body.len()));
+                ([(header::ETAG, "\"fake-etag\"")], "").into_response()
+            }
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (url, received)
+    }
+
+    fn test_storage_config(endpoint: String) -> StorageConfig {
+        StorageConfig {
+            s3_bucket: "test-bucket".to_string(),
+            s3_region: "us-east-1".to_string(),
+            s3_endpoint: Some(endpoint),
+            s3_access_key: "test".to_string(),
+            s3_secret_key: "test".to_string(),
+            max_file_size_mb: 1,
+            max_concurrent_uploads: 4,
+            upload_read_timeout_secs: 5,
+            s3_timeout_secs: 5,
+            s3_max_attempts: 1,
+            s3_retry_base_ms: 1,
+            dedupe_uploads: true,
+            presigned_url_expiry_secs: 3600,
+        }
+    }
+
+    async fn insert_owner(pool: &PgPool) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, email, password_hash, name, role) VALUES ($1, $2, 'hash', 'Uploader', 'user')",
+        )
+        .bind(id)
+        .bind(format!("uploader-{}@example.com", id))
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    fn upload_request(
+        owner_id: Uuid,
+        file_name: &str,
+        content_type: &str,
+        data: &[u8],
+    ) -> Request<Body> {
+        let token = generate_access_token(
+            &owner_id,
+            "uploader@example.com",
+            UserRole::User,
+            &common::app::create_test_jwt_config(),
+        )
+        .unwrap();
+
+        let mut body = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            BOUNDARY, file_name, content_type
+        )
+        .into_bytes();
+        body.extend_from_slice(data);
+        body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+
+        Request::builder()
+            .method("POST")
+            .uri("/storage/upload")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            )
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    async fn setup() -> (PgPool, axum::Router, Received, Uuid) {
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let owner_id = insert_owner(&pool).await;
+        let (endpoint, received) = spawn_fake_s3().await;
+        let app = storage::routes(
+            pool.clone(),
+            common::app::create_test_jwt_config(),
+            test_storage_config(endpoint),
+        )
+        .await;
+        (pool, app, received, owner_id)
+    }
+
+    #[tokio::test]
+    async fn test_upload_stores_object_and_records_metadata() {
+        // Arrange
+        let (pool, app, received, owner_id) = setup().await;
+
+        // Act
+        let response = app
+            .oneshot(upload_request(
+                owner_id,
+                "hello.txt",
+                "text/plain",
+                b"hello storage",
+            ))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        let file_id = Uuid::parse_str(body["data"]["file_id"].as_str().unwrap()).unwrap();
+
+        let (row_owner, file_name, content_type, file_size, key): (Uuid, String, String, i64, String) =
+            sqlx::query_as(
+                "SELECT owner_id, file_name, content_type, file_size, storage_key FROM files WHERE id = $1",
+            )
+            .bind(file_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(row_owner, owner_id);
+        assert_eq!(file_name, "hello.txt");
+        assert_eq!(content_type, "text/plain");
+        assert_eq!(file_size, 13);
+        assert_eq!(key, format!("uploads/{}/hello.txt", file_id));
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(
+            received[0],
+            ("PUT".to_string(), format!("/test-bucket/{}", key), 13)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_without_token_is_rejected() {
+        // Arrange
+        let (pool, app, received, owner_id) = setup().await;
+        let mut request = upload_request(owner_id, "hello.txt", "text/plain", b"hello storage");
+        request.headers_mut().remove(header::AUTHORIZATION);
+
+        // Act
+        let response = app.oneshot(request).await.unwrap();
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM files WHERE owner_id = $1")
+            .bind(owner_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 0);
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_oversize_upload_is_rejected_with_413() {
+        // Arrange: one byte over MAX_FILE_SIZE_MB
+        let (pool, app, received, owner_id) = setup().await;
+        let data = vec![b'x'; 1024 * 1024 + 1];
+
+        // Act
+        let response = app
+            .oneshot(upload_request(
+                owner_id,
+                "big.bin",
+                "application/octet-stream",
+                &data,
+            ))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "FILE_TOO_LARGE");
+
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM files WHERE owner_id = $1")
+            .bind(owner_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 0);
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_declared_oversize_body_is_rejected_before_reading() {
+        let (_pool, app, received, owner_id) = setup().await;
+        let mut request = upload_request(owner_id, "big.bin", "application/octet-stream", b"x");
+        request
+            .headers_mut()
+            .insert(header::CONTENT_LENGTH, (10 * 1024 * 1024).into());
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(received.lock().unwrap().is_empty());
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_storage_delete_endpoint() {
-    // This test would:
-    // 1. Upload a file as authenticated user
-    // 2. DELETE /storage/:file_id with JWT
-    // 3. Assert 200 OK
-    // 4. Verify file no longer exists
-
-    assert!(true);
+#[cfg(feature = "storage")]
+mod presigned_urls {
+    use super::*;
+    use crate::common::{create_test_db_pool, run_migrations};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+    use vibe_api::{
+        config::StorageConfig,
+        modules::{auth::jwt::generate_access_token, storage, users::model::UserRole},
+    };
+
+    /// Presigning is local, so the endpoint is never contacted
+    fn test_storage_config() -> StorageConfig {
+        StorageConfig {
+            s3_bucket: "test-bucket".to_string(),
+            s3_region: "us-east-1".to_string(),
+            s3_endpoint: Some("http://127.0.0.1:9".to_string()),
+            s3_access_key: "test".to_string(),
+            s3_secret_key: "test".to_string(),
+            max_file_size_mb: 1,
+            max_concurrent_uploads: 4,
+            upload_read_timeout_secs: 1,
+            s3_timeout_secs: 1,
+            s3_max_attempts: 1,
+            s3_retry_base_ms: 1,
+            dedupe_uploads: true,
+            presigned_url_expiry_secs: 900,
+        }
+    }
+
+    async fn insert_user(pool: &PgPool) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, email, password_hash, name, role) VALUES ($1, $2, 'hash', 'Uploader', 'user')",
+        )
+        .bind(id)
+        .bind(format!("uploader-{}@example.com", id))
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    /// A confirmed file owned by `owner_id`; returns its id and key
+    async fn insert_file(pool: &PgPool, owner_id: Uuid) -> (Uuid, String) {
+        let id = Uuid::new_v4();
+        let key = format!("uploads/{}/report.pdf", id);
+        sqlx::query(
+            "INSERT INTO files (id, owner_id, file_name, content_type, file_size, storage_key) VALUES ($1, $2, 'report.pdf', 'application/pdf', 42, $3)",
+        )
+        .bind(id)
+        .bind(owner_id)
+        .bind(&key)
+        .execute(pool)
+        .await
+        .unwrap();
+        (id, key)
+    }
+
+    async fn setup() -> (PgPool, axum::Router, Uuid) {
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let owner_id = insert_user(&pool).await;
+        let app = storage::routes(
+            pool.clone(),
+            common::app::create_test_jwt_config(),
+            test_storage_config(),
+        )
+        .await;
+        (pool, app, owner_id)
+    }
+
+    async fn send(
+        app: axum::Router,
+        method: &str,
+        uri: &str,
+        user_id: Uuid,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let token = generate_access_token(
+            &user_id,
+            "uploader@example.com",
+            UserRole::User,
+            &common::app::create_test_jwt_config(),
+        )
+        .unwrap();
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(header::CONTENT_TYPE, "application/json");
+        let request = match body {
+            Some(body) => request.body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_owner_mints_presigned_upload_url() {
+        // Arrange
+        let (pool, app, owner_id) = setup().await;
+
+        // Act
+        let (status, body) = send(
+            app,
+            "POST",
+            "/storage/presigned-upload",
+            owner_id,
+            Some(json!({ "filename": "report.pdf", "content_type": "application/pdf" })),
+        )
+        .await;
+
+        // Assert: a signed PUT of the file's key, expiring per config
+        assert_eq!(status, StatusCode::OK);
+        let file_id = Uuid::parse_str(body["data"]["file_id"].as_str().unwrap()).unwrap();
+        let url = body["data"]["url"].as_str().unwrap();
+        assert!(url.contains(&format!("/test-bucket/uploads/{}/report.pdf", file_id)));
+        assert!(url.contains("X-Amz-Signature="));
+        assert!(url.contains("X-Amz-Expires=900"));
+        assert_eq!(body["data"]["expires_in_seconds"], 900);
+
+        let (row_owner, pending): (Uuid, bool) =
+            sqlx::query_as("SELECT owner_id, pending FROM files WHERE id = $1")
+                .bind(file_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(row_owner, owner_id);
+        assert!(pending);
+    }
+
+    #[tokio::test]
+    async fn test_owner_mints_presigned_download_url() {
+        let (pool, app, owner_id) = setup().await;
+        let (file_id, key) = insert_file(&pool, owner_id).await;
+
+        let (status, body) = send(
+            app,
+            "GET",
+            &format!("/storage/{}/presigned-download", file_id),
+            owner_id,
+            None,
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        let url = body["data"]["url"].as_str().unwrap();
+        assert!(url.contains(&format!("/test-bucket/{}", key)));
+        assert!(url.contains("X-Amz-Signature="));
+        assert!(url.contains("X-Amz-Expires=900"));
+    }
+
+    #[tokio::test]
+    async fn test_non_owner_cannot_mint_download_url() {
+        // Arrange
+        let (pool, app, owner_id) = setup().await;
+        let (file_id, _) = insert_file(&pool, owner_id).await;
+        let other_user = insert_user(&pool).await;
+
+        // Act
+        let (status, body) = send(
+            app,
+            "GET",
+            &format!("/storage/{}/presigned-download", file_id),
+            other_user,
+            None,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body["data"]["url"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_download_url_for_unknown_file_is_404() {
+        let (_pool, app, owner_id) = setup().await;
+
+        let (status, _) = send(
+            app,
+            "GET",
+            &format!("/storage/{}/presigned-download", Uuid::new_v4()),
+            owner_id,
+            None,
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_unconfirmed_upload_cannot_be_downloaded() {
+        // Arrange
+        let (_pool, app, owner_id) = setup().await;
+        let (_, body) = send(
+            app.clone(),
+            "POST",
+            "/storage/presigned-upload",
+            owner_id,
+            Some(json!({ "filename": "later.txt", "content_type": "text/plain" })),
+        )
+        .await;
+        let file_id = body["data"]["file_id"].as_str().unwrap().to_string();
+
+        // Act
+        let (status, _) = send(
+            app,
+            "GET",
+            &format!("/storage/{}/presigned-download", file_id),
+            owner_id,
+            None,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_storage_delete_unauthorized() {
-    // This test would:
-    // 1. Upload file as user A
-    // 2. Try to DELETE with user B's JWT
-    // 3. Assert 403 FORBIDDEN
-
-    assert!(true);
+#[cfg(feature = "storage")]
+mod file_access {
+    use super::*;
+    use crate::common::{create_test_db_pool, run_migrations};
+    use axum::{extract::Request as UpstreamRequest, response::IntoResponse, Router};
+    use sqlx::PgPool;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    };
+    use uuid::Uuid;
+    use vibe_api::{
+        config::StorageConfig,
+        modules::{auth::jwt::generate_access_token, storage, users::model::UserRole},
+    };
+
+    /// Fake bucket: records `METHOD path` of each call and, while `failing`
+    /// is set, denies them the way S3 does
+    #[derive(Clone, Default)]
+    struct FakeS3 {
+        calls: Arc<Mutex<Vec<String>>>,
+        failing: Arc<AtomicBool>,
+    }
+
+    impl FakeS3 {
+        async fn spawn(&self) -> String {
+            let fake = self.clone();
+            let app = Router::new().fallback(move |request: UpstreamRequest| {
+                let fake = fake.clone();
+                async move {
+                    fake.calls
+                        .lock()
+                        .unwrap()
+                        .push(format!("{} {}", request.method(), request.uri().path()));
+                    if fake.failing.load(Ordering::SeqCst) {
+                        let body = "<Error><Code>AccessDenied</Code><Message>Access Denied</Message></Error>";
+                        return (StatusCode::FORBIDDEN, body).into_response();
+                    }
+                    StatusCode::NO_CONTENT.into_response()
+                }
+            });
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+            url
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    fn test_storage_config(endpoint: String) -> StorageConfig {
+        StorageConfig {
+            s3_bucket: "test-bucket".to_string(),
+            s3_region: "us-east-1".to_string(),
+            s3_endpoint: Some(endpoint),
+            s3_access_key: "test".to_string(),
+            s3_secret_key: "test".to_string(),
+            max_file_size_mb: 1,
+            max_concurrent_uploads: 4,
+            upload_read_timeout_secs: 5,
+            s3_timeout_secs: 5,
+            s3_max_attempts: 1,
+            s3_retry_base_ms: 1,
+            dedupe_uploads: true,
+            presigned_url_expiry_secs: 3600,
+        }
+    }
+
+    async fn insert_user(pool: &PgPool, role: UserRole) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, email, password_hash, name, role) VALUES ($1, $2, 'hash', 'Uploader', $3)",
+        )
+        .bind(id)
+        .bind(format!("uploader-{}@example.com", id))
+        .bind(role)
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    /// A stored file owned by `owner_id`; returns its id and key
+    async fn insert_file(pool: &PgPool, owner_id: Uuid) -> (Uuid, String) {
+        let id = Uuid::new_v4();
+        let key = format!("uploads/{}/notes.txt", id);
+        sqlx::query(
+            "INSERT INTO files (id, owner_id, file_name, content_type, file_size, storage_key) VALUES ($1, $2, 'notes.txt', 'text/plain', 5, $3)",
+        )
+        .bind(id)
+        .bind(owner_id)
+        .bind(&key)
+        .execute(pool)
+        .await
+        .unwrap();
+        (id, key)
+    }
+
+    async fn file_exists(pool: &PgPool, file_id: Uuid) -> bool {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM files WHERE id = $1)")
+            .bind(file_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn setup() -> (PgPool, axum::Router, FakeS3, Uuid) {
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let owner_id = insert_user(&pool, UserRole::User).await;
+        let s3 = FakeS3::default();
+        let app = storage::routes(
+            pool.clone(),
+            common::app::create_test_jwt_config(),
+            test_storage_config(s3.spawn().await),
+        )
+        .await;
+        (pool, app, s3, owner_id)
+    }
+
+    async fn send(
+        app: axum::Router,
+        method: &str,
+        uri: &str,
+        user_id: Uuid,
+        role: UserRole,
+    ) -> (StatusCode, Value) {
+        let token = generate_access_token(
+            &user_id,
+            "uploader@example.com",
+            role,
+            &common::app::create_test_jwt_config(),
+        )
+        .unwrap();
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_owner_deletes_object_and_row() {
+        // Arrange
+        let (pool, app, s3, owner_id) = setup().await;
+        let (file_id, key) = insert_file(&pool, owner_id).await;
+
+        // Act
+        let (status, _) = send(
+            app,
+            "DELETE",
+            &format!("/storage/{}", file_id),
+            owner_id,
+            UserRole::User,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(!file_exists(&pool, file_id).await);
+        assert_eq!(s3.calls(), vec![format!("DELETE /test-bucket/{}", key)]);
+    }
+
+    #[tokio::test]
+    async fn test_other_user_cannot_delete_file() {
+        // Arrange
+        let (pool, app, s3, owner_id) = setup().await;
+        let (file_id, _) = insert_file(&pool, owner_id).await;
+        let other_user = insert_user(&pool, UserRole::User).await;
+
+        // Act
+        let (status, _) = send(
+            app,
+            "DELETE",
+            &format!("/storage/{}", file_id),
+            other_user,
+            UserRole::User,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(file_exists(&pool, file_id).await);
+        assert!(s3.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_admin_deletes_any_users_file() {
+        let (pool, app, _s3, owner_id) = setup().await;
+        let (file_id, _) = insert_file(&pool, owner_id).await;
+        let admin = insert_user(&pool, UserRole::Admin).await;
+
+        let (status, _) = send(
+            app,
+            "DELETE",
+            &format!("/storage/{}", file_id),
+            admin,
+            UserRole::Admin,
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(!file_exists(&pool, file_id).await);
+    }
+
+    #[tokio::test]
+    async fn test_failed_s3_delete_keeps_row() {
+        // Arrange
+        let (pool, app, s3, owner_id) = setup().await;
+        let (file_id, _) = insert_file(&pool, owner_id).await;
+        s3.failing.store(true, Ordering::SeqCst);
+
+        // Act
+        let (status, _) = send(
+            app,
+            "DELETE",
+            &format!("/storage/{}", file_id),
+            owner_id,
+            UserRole::User,
+        )
+        .await;
+
+        // Assert: the delete was attempted, and the row survived its failure
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert_eq!(s3.calls().len(), 1);
+        assert!(file_exists(&pool, file_id).await);
+    }
+
+    #[tokio::test]
+    async fn test_delete_unknown_file_is_404() {
+        let (_pool, app, s3, owner_id) = setup().await;
+
+        let (status, _) = send(
+            app,
+            "DELETE",
+            &format!("/storage/{}", Uuid::new_v4()),
+            owner_id,
+            UserRole::User,
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(s3.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_metadata_is_limited_to_owner_and_admin() {
+        // Arrange
+        let (pool, app, _s3, owner_id) = setup().await;
+        let (file_id, _) = insert_file(&pool, owner_id).await;
+        let other_user = insert_user(&pool, UserRole::User).await;
+        let admin = insert_user(&pool, UserRole::Admin).await;
+        let uri = format!("/storage/{}/metadata", file_id);
+
+        // Act
+        let (owner_status, body) = send(app.clone(), "GET", &uri, owner_id, UserRole::User).await;
+        let (other_status, _) = send(app.clone(), "GET", &uri, other_user, UserRole::User).await;
+        let (admin_status, _) = send(app.clone(), "GET", &uri, admin, UserRole::Admin).await;
+        let (missing_status, _) = send(
+            app,
+            "GET",
+            &format!("/storage/{}/metadata", Uuid::new_v4()),
+            owner_id,
+            UserRole::User,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(owner_status, StatusCode::OK);
+        assert_eq!(body["data"]["file_id"], file_id.to_string());
+        assert_eq!(body["data"]["file_name"], "notes.txt");
+        assert_eq!(body["data"]["file_size"], 5);
+        assert_eq!(body["data"]["content_type"], "text/plain");
+        assert_eq!(other_status, StatusCode::FORBIDDEN);
+        assert_eq!(admin_status, StatusCode::OK);
+        assert_eq!(missing_status, StatusCode::NOT_FOUND);
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_storage_metadata_endpoint() {
-    // This test would:
-    // 1. Upload a file
-    // 2. GET /storage/:file_id/metadata
-    // 3. Assert 200 OK
-    // 4. Assert metadata matches (filename, size, content_type)
-
-    assert!(true);
+#[cfg(feature = "storage")]
+mod thumbnails {
+    use super::*;
+    use crate::common::{create_test_db_pool, run_migrations};
+    use axum::{extract::Request as UpstreamRequest, response::IntoResponse, Router};
+    use image::{DynamicImage, GenericImageView, ImageFormat, Rgb, RgbImage};
+    use sqlx::PgPool;
+    use std::{
+        collections::HashMap,
+        io::Cursor,
+        sync::{Arc, Mutex},
+    };
+    use uuid::Uuid;
+    use vibe_api::{
+        config::StorageConfig,
+        modules::{auth::jwt::generate_access_token, storage, users::model::UserRole},
+    };
+
+    const BOUNDARY: &str = "vibe-thumbnail-boundary";
+
+    /// Objects the fake bucket holds, by request path
+    type Bucket = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// S3 stand-in that keeps `PUT` bodies and serves them back on `GET`
+    async fn spawn_fake_s3() -> (String, Bucket) {
+        let bucket: Bucket = Arc::default();
+        let objects = bucket.clone();
+        let app = Router::new().fallback(move |request: UpstreamRequest| {
+            let objects = objects.clone();
+            async move {
+                let path = request.uri().path().to_string();
+                if request.method() == "GET" {
+                    return match objects.lock().unwrap().get(&path) {
+                        Some(body) => body.clone().into_response(),
+                        None => StatusCode::NOT_FOUND.into_response(),
+                    };
+                }
+                let body = request.into_body().collect().await.unwrap().to_bytes();
+                objects.lock().unwrap().insert(path, body.to_vec());
+                ([(header::ETAG, "\"fake-etag\"")], "").into_response()
+            }
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (url, bucket)
+    }
+
+    fn test_storage_config(endpoint: String) -> StorageConfig {
+        StorageConfig {
+            s3_bucket: "test-bucket".to_string(),
+            s3_region: "us-east-1".to_string(),
+            s3_endpoint: Some(endpoint),
+            s3_access_key: "test".to_string(),
+            s3_secret_key: "test".to_string(),
+            max_file_size_mb: 1,
+            max_concurrent_uploads: 4,
+            upload_read_timeout_secs: 5,
+            s3_timeout_secs: 5,
+            s3_max_attempts: 1,
+            s3_retry_base_ms: 1,
+            dedupe_uploads: true,
+            presigned_url_expiry_secs: 3600,
+        }
+    }
+
+    /// A solid-colour image encoded as `format`
+    fn fixture(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let image = RgbImage::from_pixel(width, height, Rgb([200, 40, 40]));
+        let mut encoded = Cursor::new(Vec::new());
+        DynamicImage::ImageRgb8(image)
+            .write_to(&mut encoded, format)
+            .unwrap();
+        encoded.into_inner()
+    }
+
+    async fn insert_owner(pool: &PgPool) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, email, password_hash, name, role) VALUES ($1, $2, 'hash', 'Uploader', 'user')",
+        )
+        .bind(id)
+        .bind(format!("uploader-{}@example.com", id))
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    fn token(owner_id: Uuid) -> String {
+        generate_access_token(
+            &owner_id,
+            "uploader@example.com",
+            UserRole::User,
+            &common::app::create_test_jwt_config(),
+        )
+        .unwrap()
+    }
+
+    async fn setup() -> (PgPool, axum::Router, Bucket, Uuid) {
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let owner_id = insert_owner(&pool).await;
+        let (endpoint, bucket) = spawn_fake_s3().await;
+        let app = storage::routes(
+            pool.clone(),
+            common::app::create_test_jwt_config(),
+            test_storage_config(endpoint),
+        )
+        .await;
+        (pool, app, bucket, owner_id)
+    }
+
+    /// Upload through the route; returns the new file's id
+    async fn upload(
+        app: axum::Router,
+        owner_id: Uuid,
+        file_name: &str,
+        content_type: &str,
+        data: &[u8],
+    ) -> Uuid {
+        let mut body = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            BOUNDARY, file_name, content_type
+        )
+        .into_bytes();
+        body.extend_from_slice(data);
+        body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/storage/upload")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token(owner_id)))
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            )
+            .body(Body::from(body))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        Uuid::parse_str(body["data"]["file_id"].as_str().unwrap()).unwrap()
+    }
+
+    async fn thumbnail_key(pool: &PgPool, file_id: Uuid) -> Option<String> {
+        sqlx::query_scalar("SELECT thumbnail_key FROM files WHERE id = $1")
+            .bind(file_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn get_thumbnail(
+        app: axum::Router,
+        owner_id: Uuid,
+        file_id: Uuid,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .uri(format!("/storage/{}/thumbnail", file_id))
+            .header(header::AUTHORIZATION, format!("Bearer {}", token(owner_id)))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_png_upload_gets_thumbnail() {
+        // Arrange
+        let (pool, app, bucket, owner_id) = setup().await;
+        let png = fixture(600, 300, ImageFormat::Png);
+
+        // Act
+        let file_id = upload(app, owner_id, "wide.png", "image/png", &png).await;
+
+        // Assert: recorded, stored, and scaled to a 256px longest edge
+        let key = thumbnail_key(&pool, file_id)
+            .await
+            .expect("thumbnail recorded");
+        assert_eq!(key, format!("thumbnails/{}.png", file_id));
+
+        let stored = bucket
+            .lock()
+            .unwrap()
+            .get(&format!("/test-bucket/{}", key))
+            .cloned()
+            .expect("thumbnail stored");
+        let thumbnail = image::load_from_memory(&stored).unwrap();
+        assert_eq!(thumbnail.dimensions(), (256, 128));
+    }
+
+    #[tokio::test]
+    async fn test_jpeg_upload_gets_thumbnail() {
+        let (pool, app, _bucket, owner_id) = setup().await;
+        let jpeg = fixture(300, 600, ImageFormat::Jpeg);
+
+        let file_id = upload(app, owner_id, "tall.jpg", "image/jpeg", &jpeg).await;
+
+        assert_eq!(
+            thumbnail_key(&pool, file_id).await,
+            Some(format!("thumbnails/{}.png", file_id))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_image_still_uploads_without_thumbnail() {
+        let (pool, app, _bucket, owner_id) = setup().await;
+
+        let file_id = upload(app, owner_id, "broken.png", "image/png", b"not a png").await;
+
+        assert_eq!(thumbnail_key(&pool, file_id).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_thumbnail_url_for_image() {
+        // Arrange
+        let (_pool, app, _bucket, owner_id) = setup().await;
+        let png = fixture(64, 64, ImageFormat::Png);
+        let file_id = upload(app.clone(), owner_id, "icon.png", "image/png", &png).await;
+
+        // Act
+        let (status, body) = get_thumbnail(app, owner_id, file_id).await;
+
+        // Assert
+        assert_eq!(status, StatusCode::OK);
+        let url = body["data"]["url"].as_str().unwrap();
+        assert!(url.contains(&format!("/test-bucket/thumbnails/{}.png", file_id)));
+        assert!(url.contains("X-Amz-Signature="));
+    }
+
+    #[tokio::test]
+    async fn test_non_image_has_no_thumbnail() {
+        let (pool, app, _bucket, owner_id) = setup().await;
+        let file_id = upload(app.clone(), owner_id, "notes.txt", "text/plain", b"hello").await;
+
+        let (status, _) = get_thumbnail(app, owner_id, file_id).await;
+
+        assert_eq!(thumbnail_key(&pool, file_id).await, None);
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_storage_upload_with_authentication() {
-    // This test would:
-    // 1. Create user and get JWT
-    // 2. POST to /storage/upload with Authorization header
-    // 3. Assert 200 OK
-    // 4. Verify file ownership in database
-
-    assert!(true);
+#[cfg(feature = "storage")]
+mod upload_dedupe {
+    use crate::common::{create_test_db_pool, run_migrations};
+    use sqlx::PgPool;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use uuid::Uuid;
+    use vibe_api::{
+        modules::storage::{files::FileStore, model::StoredFile},
+        utils::error::AppResult,
+    };
+
+    async fn insert_owner(pool: &PgPool) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, email, password_hash, name, role) VALUES ($1, $2, 'hash', 'Uploader', 'user')",
+        )
+        .bind(id)
+        .bind(format!("uploader-{}@example.com", id))
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    /// Store through `files`, counting objects actually written to the bucket
+    async fn upload(
+        files: &FileStore,
+        bucket: &AtomicU32,
+        owner_id: Uuid,
+        data: &[u8],
+    ) -> AppResult<(StoredFile, bool)> {
+        files
+            .store(owner_id, "report.txt", "text/plain", data, |_key| async {
+                bucket.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .await
+    }
+
+    async fn setup() -> (PgPool, Uuid) {
+        let pool = create_test_db_pool().await;
+        run_migrations(&pool).await;
+        let owner_id = insert_owner(&pool).await;
+        (pool, owner_id)
+    }
+
+    #[tokio::test]
+    async fn test_identical_upload_returns_existing_file() {
+        // Arrange
+        let (pool, owner_id) = setup().await;
+        let files = FileStore::new(pool.clone(), true);
+        let bucket = AtomicU32::new(0);
+
+        // Act
+        let (first, first_reused) = upload(&files, &bucket, owner_id, b"same bytes")
+            .await
+            .unwrap();
+        let (second, second_reused) = upload(&files, &bucket, owner_id, b"same bytes")
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(!first_reused);
+        assert!(second_reused);
+        assert_eq!(first.id, second.id);
+        assert_eq!(bucket.load(Ordering::SeqCst), 1);
+
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM files WHERE owner_id = $1")
+            .bind(owner_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+
+    #[tokio::test]
+    async fn test_different_content_is_stored_separately() {
+        let (pool, owner_id) = setup().await;
+        let files = FileStore::new(pool, true);
+        let bucket = AtomicU32::new(0);
+
+        let (first, _) = upload(&files, &bucket, owner_id, b"first").await.unwrap();
+        let (second, reused) = upload(&files, &bucket, owner_id, b"second").await.unwrap();
+
+        assert!(!reused);
+        assert_ne!(first.id, second.id);
+        assert_ne!(first.content_hash, second.content_hash);
+        assert_eq!(bucket.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_identical_content_from_another_owner_is_stored() {
+        let (pool, owner_id) = setup().await;
+        let other_owner = insert_owner(&pool).await;
+        let files = FileStore::new(pool, true);
+        let bucket = AtomicU32::new(0);
+
+        upload(&files, &bucket, owner_id, b"shared").await.unwrap();
+        let (_, reused) = upload(&files, &bucket, other_owner, b"shared")
+            .await
+            .unwrap();
+
+        assert!(!reused);
+        assert_eq!(bucket.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dedupe_disabled_stores_every_upload() {
+        let (pool, owner_id) = setup().await;
+        let files = FileStore::new(pool, false);
+        let bucket = AtomicU32::new(0);
+
+        upload(&files, &bucket, owner_id, b"again").await.unwrap();
+        let (_, reused) = upload(&files, &bucket, owner_id, b"again").await.unwrap();
+
+        assert!(!reused);
+        assert_eq!(bucket.load(Ordering::SeqCst), 2);
+    }
 }
 
-#[tokio::test]
-#[ignore]
-async fn test_storage_upload_without_authentication() {
-    // This test would:
-    // 1. POST to /storage/upload without JWT
-    // 2. Assert 401 UNAUTHORIZED
+#[cfg(feature = "storage")]
+mod s3_retry {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+    use vibe_api::{
+        modules::storage::retry::{RetryPolicy, RetryableError},
+        utils::error::AppError,
+    };
+
+    /// Stand-in for an S3 SDK error: throttling/5xx are retryable, 4xx are not
+    #[derive(Debug)]
+    struct MockS3Error {
+        status: u16,
+    }
+
+    impl std::fmt::Display for MockS3Error {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "mock S3 status {}", self.status)
+        }
+    }
+
+    impl RetryableError for MockS3Error {
+        fn is_retryable(&self) -> bool {
+            self.status == 429 || self.status >= 500
+        }
+    }
+
+    fn test_policy() -> RetryPolicy {
+        RetryPolicy {
+            timeout: Duration::from_millis(200),
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_failure_is_retried() {
+        let attempts = AtomicU32::new(0);
+
+        let result = test_policy()
+            .run(
+                "upload",
+                || async {
+                    match attempts.fetch_add(1, Ordering::SeqCst) {
+                        0 => Err(MockS3Error { status: 503 }),
+                        1 => Err(MockS3Error { status: 429 }),
+                        _ => Ok("etag"),
+                    }
+                },
+                |e| AppError::ExternalService(e.to_string()),
+            )
+            .await;
+
+        assert_eq!(result.unwrap(), "etag");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
 
-    assert!(true);
+    #[tokio::test]
+    async fn test_non_retryable_error_fails_fast() {
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<(), _> = test_policy()
+            .run(
+                "delete",
+                || async {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err(MockS3Error { status: 403 })
+                },
+                |e| AppError::ExternalService(e.to_string()),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::ExternalService(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_map_to_unavailable() {
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<(), _> = test_policy()
+            .run(
+                "upload",
+                || async {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err(MockS3Error { status: 500 })
+                },
+                |e| AppError::ExternalService(e.to_string()),
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::StorageUnavailable(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_hung_call_times_out_and_retries() {
+        let attempts = AtomicU32::new(0);
+
+        let result = test_policy()
+            .run(
+                "head",
+                || async {
+                    if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                        std::future::pending::<()>().await;
+                    }
+                    Ok::<_, MockS3Error>(())
+                },
+                |e| AppError::ExternalService(e.to_string()),
+            )
+            .await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
 }
@@ -0,0 +1,1385 @@
+// User management integration tests
+// Validates profile updates against the real users table
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    response::IntoResponse,
+    Router,
+};
+use chrono::{DateTime, Utc};
+use http_body_util::BodyExt;
+use serde_json::json;
+use sqlx::PgPool;
+use std::time::Duration;
+use tower::ServiceExt;
+use uuid::Uuid;
+use vibe_api::{
+    config::BootstrapAdminConfig,
+    middleware::ConcurrencyLimit,
+    modules::{
+        auth::{self, jwt::generate_access_token},
+        users::{
+            self,
+            activity::ActivityTracker,
+            bootstrap::ensure_bootstrap_admin,
+            model::{UpdateUserRequest, UserRole, MAX_METADATA_BYTES},
+            service::UserService,
+        },
+    },
+    utils::{
+        error::AppError,
+        validation::{validate_struct, NamePolicy},
+    },
+};
+
+use common::{app, create_test_app, create_test_db_pool, run_migrations};
+
+async fn insert_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id, email, password_hash, name) VALUES ($1, $2, $3, $4)")
+        .bind(id)
+        .bind(format!("user_{}@example.com", id.simple()))
+        .bind("$argon2id$v=19$m=19456,t=2,p=1$test")
+        .bind("Test User")
+        .execute(pool)
+        .await
+        .expect("Failed to insert user");
+    id
+}
+
+fn metadata_update(metadata: serde_json::Value) -> UpdateUserRequest {
+    serde_json::from_value(json!({ "metadata": metadata })).unwrap()
+}
+
+#[tokio::test]
+async fn test_set_allowed_metadata() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    let service = UserService::new(pool);
+    let request = metadata_update(json!({ "bio": "Rustacean", "location": "Utrecht" }));
+    validate_struct(&request).expect("Allowed metadata should validate");
+
+    // Act
+    let updated = service.update(&user_id, request).await.unwrap();
+    let fetched = service.get_by_id(&user_id).await.unwrap();
+
+    // Assert
+    assert_eq!(updated.metadata["bio"], "Rustacean");
+    assert_eq!(
+        fetched.metadata,
+        json!({ "bio": "Rustacean", "location": "Utrecht" })
+    );
+}
+
+#[tokio::test]
+async fn test_new_user_has_empty_metadata() {
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+
+    let user = UserService::new(pool).get_by_id(&user_id).await.unwrap();
+
+    assert_eq!(user.metadata, json!({}));
+}
+
+#[test]
+fn test_oversized_metadata_returns_400() {
+    let request = metadata_update(json!({ "bio": "x".repeat(MAX_METADATA_BYTES + 1) }));
+
+    let error = validate_struct(&request).unwrap_err();
+
+    assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn test_disallowed_metadata_key_returns_400() {
+    let request = metadata_update(json!({ "is_admin": true }));
+
+    let error = validate_struct(&request).unwrap_err();
+
+    assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+}
+
+async fn last_active_at(pool: &PgPool, user_id: &Uuid) -> Option<DateTime<Utc>> {
+    sqlx::query_scalar("SELECT last_active_at FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_authenticated_request_records_last_active() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    let token = generate_access_token(
+        &user_id,
+        "user@example.com",
+        UserRole::User,
+        &app::create_test_jwt_config(),
+    )
+    .unwrap();
+    let app = create_test_app(pool.clone()).await;
+
+    // Act
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/users/me")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(json["data"]["last_active_at"].is_string());
+    assert!(last_active_at(&pool, &user_id).await.is_some());
+}
+
+#[tokio::test]
+async fn test_last_active_write_is_throttled() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    let tracker = ActivityTracker::new(pool.clone(), Duration::from_secs(60));
+
+    // Act
+    let first = tracker.touch(&user_id).await.unwrap();
+    let recorded = last_active_at(&pool, &user_id).await;
+    let second = tracker.touch(&user_id).await.unwrap();
+
+    // Assert
+    assert!(first);
+    assert!(!second, "Second touch within the interval must not write");
+    assert_eq!(last_active_at(&pool, &user_id).await, recorded);
+}
+
+#[tokio::test]
+async fn test_last_active_advances_after_interval() {
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    let tracker = ActivityTracker::new(pool.clone(), Duration::ZERO);
+
+    tracker.touch(&user_id).await.unwrap();
+    let first = last_active_at(&pool, &user_id).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    tracker.touch(&user_id).await.unwrap();
+    let second = last_active_at(&pool, &user_id).await.unwrap();
+
+    assert!(second > first);
+}
+
+async fn insert_oauth_only_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id, email, name) VALUES ($1, $2, $3)")
+        .bind(id)
+        .bind(format!("oauth_{}@example.com", id.simple()))
+        .bind("OAuth User")
+        .execute(pool)
+        .await
+        .expect("Failed to insert user");
+    id
+}
+
+async fn link_provider(pool: &PgPool, user_id: &Uuid, provider: &str) {
+    sqlx::query(
+        "INSERT INTO oauth_connections (id, user_id, provider, provider_user_id, email)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(provider)
+    .bind(Uuid::new_v4().to_string())
+    .bind(format!("{}@{}.example.com", user_id.simple(), provider))
+    .execute(pool)
+    .await
+    .expect("Failed to link provider");
+}
+
+fn bearer_request(method: &str, uri: &str, user_id: &Uuid) -> Request<Body> {
+    let token = generate_access_token(
+        user_id,
+        "user@example.com",
+        UserRole::User,
+        &app::create_test_jwt_config(),
+    )
+    .unwrap();
+
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_list_connections() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    link_provider(&pool, &user_id, "google").await;
+    link_provider(&pool, &user_id, "github").await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let response = app
+        .oneshot(bearer_request("GET", "/users/me/connections", &user_id))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let mut providers: Vec<&str> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|connection| connection["provider"].as_str().unwrap())
+        .collect();
+    providers.sort();
+    assert_eq!(providers, vec!["github", "google"]);
+}
+
+#[tokio::test]
+async fn test_unlink_secondary_provider() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_oauth_only_user(&pool).await;
+    link_provider(&pool, &user_id, "google").await;
+    link_provider(&pool, &user_id, "github").await;
+    let app = create_test_app(pool.clone()).await;
+
+    // Act
+    let response = app
+        .oneshot(bearer_request(
+            "DELETE",
+            "/users/me/connections/github",
+            &user_id,
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let remaining = UserService::new(pool)
+        .list_connections(&user_id)
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].provider, "google");
+}
+
+#[tokio::test]
+async fn test_unlink_refuses_sole_login_method() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_oauth_only_user(&pool).await;
+    link_provider(&pool, &user_id, "google").await;
+    let app = create_test_app(pool.clone()).await;
+
+    // Act
+    let response = app
+        .oneshot(bearer_request(
+            "DELETE",
+            "/users/me/connections/google",
+            &user_id,
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let remaining = UserService::new(pool)
+        .list_connections(&user_id)
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 1, "Sole connection must stay linked");
+}
+
+#[tokio::test]
+async fn test_unlink_sole_provider_when_password_set() {
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    link_provider(&pool, &user_id, "google").await;
+    let service = UserService::new(pool);
+
+    service.unlink_connection(&user_id, "google").await.unwrap();
+
+    assert!(service.list_connections(&user_id).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_unlink_unknown_provider_not_found() {
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    let service = UserService::new(pool);
+
+    let result = service.unlink_connection(&user_id, "gitlab").await;
+
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_get_unknown_user_is_user_not_found() {
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let service = UserService::new(pool);
+
+    let result = service.get_by_id(&Uuid::new_v4()).await;
+
+    assert!(matches!(result, Err(AppError::UserNotFound)));
+}
+
+#[tokio::test]
+async fn test_deleted_user_gets_user_not_found_code() {
+    // Arrange - a valid token whose user no longer exists
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let response = app
+        .oneshot(bearer_request("GET", "/users/me", &Uuid::new_v4()))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"]["code"], "USER_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_delete_soft_deletes_and_frees_email() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    let service = UserService::new(pool.clone());
+
+    // Act
+    service.delete(&user_id).await.unwrap();
+
+    // Assert - the row stays for the purge job but the account is gone
+    let deleted_at: Option<DateTime<Utc>> =
+        sqlx::query_scalar("SELECT deleted_at FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(deleted_at.is_some());
+    assert!(matches!(
+        service.get_by_id(&user_id).await,
+        Err(AppError::UserNotFound)
+    ));
+    assert!(matches!(
+        service.delete(&user_id).await,
+        Err(AppError::UserNotFound)
+    ));
+
+    // The email can be registered again
+    let email = format!("user_{}@example.com", user_id.simple());
+    sqlx::query("INSERT INTO users (id, email, password_hash, name) VALUES ($1, $2, $3, $4)")
+        .bind(Uuid::new_v4())
+        .bind(&email)
+        .bind("$argon2id$v=19$m=19456,t=2,p=1$test")
+        .bind("New User")
+        .execute(&pool)
+        .await
+        .expect("A soft-deleted user's email should be free");
+}
+
+fn bootstrap_admin_config(email: &str) -> BootstrapAdminConfig {
+    BootstrapAdminConfig {
+        email: email.to_string(),
+        password: "Bootstrap-Pass-123!".to_string(),
+    }
+}
+
+async fn admin_count(conn: &mut sqlx::PgConnection) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE role = 'admin'")
+        .fetch_one(conn)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_bootstrap_admin_created_when_none_exist() {
+    // Arrange: hide existing admins inside a transaction that is rolled back
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let mut tx = pool.begin().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE role = 'admin'")
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    let email = format!("admin_{}@example.com", Uuid::new_v4().simple());
+
+    // Act
+    let created = ensure_bootstrap_admin(&mut tx, &bootstrap_admin_config(&email))
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(created);
+    let role: String = sqlx::query_scalar("SELECT role FROM users WHERE email = $1")
+        .bind(&email)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+    assert_eq!(role, "admin");
+    tx.rollback().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_bootstrap_admin_is_noop_on_subsequent_boot() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let mut tx = pool.begin().await.unwrap();
+    sqlx::query("DELETE FROM users WHERE role = 'admin'")
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    let config = bootstrap_admin_config(&format!("admin_{}@example.com", Uuid::new_v4().simple()));
+    assert!(ensure_bootstrap_admin(&mut tx, &config).await.unwrap());
+
+    // Act
+    let second = ensure_bootstrap_admin(&mut tx, &config).await.unwrap();
+    let other = bootstrap_admin_config(&format!("other_{}@example.com", Uuid::new_v4().simple()));
+    let third = ensure_bootstrap_admin(&mut tx, &other).await.unwrap();
+
+    // Assert
+    assert!(!second);
+    assert!(!third, "Any existing admin makes bootstrap a no-op");
+    assert_eq!(admin_count(&mut tx).await, 1);
+    tx.rollback().await.unwrap();
+}
+
+#[test]
+fn test_bootstrap_admin_debug_redacts_password() {
+    let config = bootstrap_admin_config("admin@example.com");
+
+    let debug = format!("{:?}", config);
+
+    assert!(debug.contains("admin@example.com"));
+    assert!(!debug.contains("Bootstrap-Pass-123!"));
+}
+
+const CURRENT_TERMS: &str = "2026-01";
+
+fn terms_gated_app(pool: PgPool) -> Router {
+    let jwt_config = app::create_test_jwt_config();
+
+    Router::new()
+        .merge(auth::routes(
+            pool.clone(),
+            jwt_config.clone(),
+            Some(CURRENT_TERMS.to_string()),
+            auth::LoginLockout::default(),
+            auth::SignupGuard::default(),
+            NamePolicy::default(),
+            vibe_api::events::EventBus::default(),
+        ))
+        .merge(users::routes(
+            pool,
+            jwt_config,
+            ConcurrencyLimit::new(4, Duration::from_secs(2)),
+            Some(CURRENT_TERMS.to_string()),
+            NamePolicy::default(),
+            vibe_api::events::EventBus::default(),
+        ))
+}
+
+async fn set_accepted_terms(pool: &PgPool, user_id: &Uuid, version: &str) {
+    sqlx::query("UPDATE users SET terms_version_accepted = $2 WHERE id = $1")
+        .bind(user_id)
+        .bind(version)
+        .execute(pool)
+        .await
+        .expect("Failed to set accepted terms");
+}
+
+fn accept_terms_request(user_id: &Uuid, version: &str) -> Request<Body> {
+    let mut request = bearer_request("POST", "/users/me/accept-terms", user_id);
+    request
+        .headers_mut()
+        .insert("content-type", "application/json".parse().unwrap());
+    *request.body_mut() = Body::from(json!({ "version": version }).to_string());
+    request
+}
+
+#[tokio::test]
+async fn test_user_on_old_terms_is_gated() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    set_accepted_terms(&pool, &user_id, "2025-01").await;
+    let app = terms_gated_app(pool);
+
+    // Act
+    let gated = app
+        .clone()
+        .oneshot(bearer_request("GET", "/users/me/connections", &user_id))
+        .await
+        .unwrap();
+    let profile = app
+        .oneshot(bearer_request("GET", "/users/me", &user_id))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(gated.status(), StatusCode::FORBIDDEN);
+    let body = gated.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"]["code"], "TERMS_NOT_ACCEPTED");
+    assert_eq!(
+        profile.status(),
+        StatusCode::OK,
+        "Profile stays readable so clients can prompt for acceptance"
+    );
+}
+
+#[tokio::test]
+async fn test_accepting_current_terms_unblocks_user() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    let app = terms_gated_app(pool);
+
+    // Act
+    let stale = app
+        .clone()
+        .oneshot(accept_terms_request(&user_id, "2025-01"))
+        .await
+        .unwrap();
+    let accepted = app
+        .clone()
+        .oneshot(accept_terms_request(&user_id, CURRENT_TERMS))
+        .await
+        .unwrap();
+    let after = app
+        .oneshot(bearer_request("GET", "/users/me/connections", &user_id))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(stale.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(accepted.status(), StatusCode::OK);
+    let body = accepted.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["data"]["terms_version_accepted"], CURRENT_TERMS);
+    assert_eq!(after.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_new_signup_is_not_gated() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let app = terms_gated_app(pool);
+    let register = Request::builder()
+        .method("POST")
+        .uri("/auth/register")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({
+                "email": format!("terms_{}@example.com", Uuid::new_v4().simple()),
+                "password": "SecurePass123!",
+                "name": "Terms User"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+
+    // Act
+    let response = app.clone().oneshot(register).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let token = json["data"]["access_token"].as_str().unwrap();
+    let connections = app
+        .oneshot(
+            Request::builder()
+                .uri("/users/me/connections")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(connections.status(), StatusCode::OK);
+}
+
+fn admin_request(uri: &str) -> Request<Body> {
+    let token = generate_access_token(
+        &Uuid::new_v4(),
+        "admin@example.com",
+        UserRole::Admin,
+        &app::create_test_jwt_config(),
+    )
+    .unwrap();
+
+    Request::builder()
+        .uri(uri)
+        .header("authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_user_list_accepts_known_query_params() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let response = app
+        .oneshot(admin_request("/users?page=1&per_page=5"))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["pagination"]["per_page"], 5);
+}
+
+#[tokio::test]
+async fn test_user_list_rejects_unknown_query_params() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let response = app
+        .oneshot(admin_request("/users?page=1&limt=10&sort=name&limt=20"))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"]["code"], "UNKNOWN_QUERY_PARAMETERS");
+    assert_eq!(
+        json["error"]["details"]["unknown_parameters"],
+        json!(["limt", "sort"])
+    );
+}
+
+async fn list_users_json(app: Router, uri: &str) -> (StatusCode, serde_json::Value) {
+    let response = app.oneshot(admin_request(uri)).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_user_list_defaults_to_first_20_users() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    for _ in 0..21 {
+        insert_user(&pool).await;
+    }
+    let app = create_test_app(pool).await;
+
+    // Act
+    let (status, json) = list_users_json(app, "/users").await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["data"].as_array().unwrap().len(), 20);
+    assert_eq!(json["pagination"]["limit"], 20);
+    assert_eq!(json["pagination"]["offset"], 0);
+    assert!(json["pagination"]["total"].as_u64().unwrap() >= 21);
+}
+
+#[tokio::test]
+async fn test_user_list_caps_limit_at_100() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let (status, json) = list_users_json(app, "/users?limit=500").await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["pagination"]["limit"], 100);
+    assert!(json["data"].as_array().unwrap().len() <= 100);
+}
+
+#[tokio::test]
+async fn test_user_list_sorts_and_offsets_by_requested_column() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    for _ in 0..3 {
+        insert_user(&pool).await;
+    }
+    let app = create_test_app(pool).await;
+
+    // Act
+    let (status, page) =
+        list_users_json(app.clone(), "/users?sort_by=created_at&order=asc&limit=3").await;
+    let (_, shifted) =
+        list_users_json(app, "/users?sort_by=created_at&order=asc&limit=1&offset=1").await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK);
+    let created: Vec<DateTime<Utc>> = page["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|user| user["created_at"].as_str().unwrap().parse().unwrap())
+        .collect();
+    assert_eq!(created.len(), 3);
+    assert!(created.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(shifted["data"][0]["id"], page["data"][1]["id"]);
+    assert_eq!(shifted["pagination"]["offset"], 1);
+}
+
+#[tokio::test]
+async fn test_user_list_rejects_unknown_sort_column() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let (status, json) = list_users_json(app, "/users?sort_by=password_hash").await;
+
+    // Assert
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"]["code"], "VALIDATION_ERROR");
+    assert_eq!(json["error"]["details"]["field"], "sort_by");
+}
+
+#[tokio::test]
+async fn test_profile_update_reports_every_invalid_field() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    let app = create_test_app(pool).await;
+    let mut request = bearer_request("PATCH", "/users/me", &user_id);
+    request
+        .headers_mut()
+        .insert("content-type", "application/json".parse().unwrap());
+    *request.body_mut() =
+        Body::from(json!({ "name": "A".repeat(101), "metadata": { "role": "admin" } }).to_string());
+
+    // Act
+    let response = app.oneshot(request).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"]["code"], "VALIDATION_ERROR");
+    let fields: Vec<&str> = json["error"]["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|error| error["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, vec!["metadata", "name"]);
+}
+
+/// Insert a user with a recognizable email and name, returning its id
+async fn insert_named_user(pool: &PgPool, email: &str, name: &str, role: UserRole) -> Uuid {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO users (id, email, password_hash, name, role) VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(id)
+    .bind(email)
+    .bind("$argon2id$v=19$m=19456,t=2,p=1$test")
+    .bind(name)
+    .bind(role)
+    .execute(pool)
+    .await
+    .expect("Failed to insert user");
+    id
+}
+
+fn listed_ids(json: &serde_json::Value) -> Vec<String> {
+    json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|user| user["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_user_list_search_matches_email_or_name_case_insensitively() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let tag = Uuid::new_v4().simple().to_string();
+    let by_email = insert_named_user(
+        &pool,
+        &format!("Search.{}@Example.com", tag),
+        "Plain Name",
+        UserRole::User,
+    )
+    .await;
+    let by_name = insert_named_user(
+        &pool,
+        &format!("other_{}@example.com", Uuid::new_v4().simple()),
+        &format!("Named {}", tag.to_uppercase()),
+        UserRole::User,
+    )
+    .await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let (status, json) =
+        list_users_json(app, &format!("/users?q={}&limit=100", tag.to_uppercase())).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK);
+    let ids = listed_ids(&json);
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains(&by_email.to_string()));
+    assert!(ids.contains(&by_name.to_string()));
+    assert_eq!(json["pagination"]["total"], 2);
+}
+
+#[tokio::test]
+async fn test_user_list_combines_search_and_role_filters() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let tag = Uuid::new_v4().simple().to_string();
+    let moderator = insert_named_user(
+        &pool,
+        &format!("mod_{}@example.com", tag),
+        "Moderator",
+        UserRole::Moderator,
+    )
+    .await;
+    insert_named_user(
+        &pool,
+        &format!("user_{}@example.com", tag),
+        "Regular",
+        UserRole::User,
+    )
+    .await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let (status, json) = list_users_json(
+        app.clone(),
+        &format!("/users?q={}&role=moderator&page=1", tag),
+    )
+    .await;
+    let (_, everyone) = list_users_json(app, "/users?q=&limit=1").await;
+
+    // Assert: an empty `q` does not filter
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed_ids(&json), vec![moderator.to_string()]);
+    assert!(everyone["pagination"]["total"].as_u64().unwrap() >= 2);
+}
+
+#[tokio::test]
+async fn test_user_list_search_treats_wildcards_literally() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    insert_user(&pool).await;
+    let app = create_test_app(pool).await;
+
+    // Act: `%` would match every user if it reached LIKE unescaped
+    let (status, json) = list_users_json(app, "/users?q=%25").await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["pagination"]["total"], 0);
+}
+
+#[tokio::test]
+async fn test_user_list_rejects_unknown_role_filter() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let (status, json) = list_users_json(app, "/users?role=superuser").await;
+
+    // Assert
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"]["code"], "INVALID_ROLE");
+}
+
+fn request_as(role: UserRole, method: &str, uri: &str) -> Request<Body> {
+    let token = generate_access_token(
+        &Uuid::new_v4(),
+        "someone@example.com",
+        role,
+        &app::create_test_jwt_config(),
+    )
+    .unwrap();
+
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_user_routes_check_permissions_not_roles() {
+    // Arrange: moderators hold `users_list` but not `users_delete`
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let target = insert_user(&pool).await;
+    let app = create_test_app(pool).await;
+    let delete_uri = format!("/users/{}", target);
+
+    // Act
+    let moderator_list = app
+        .clone()
+        .oneshot(request_as(UserRole::Moderator, "GET", "/users"))
+        .await
+        .unwrap();
+    let user_list = app
+        .clone()
+        .oneshot(request_as(UserRole::User, "GET", "/users"))
+        .await
+        .unwrap();
+    let moderator_delete = app
+        .oneshot(request_as(UserRole::Moderator, "DELETE", &delete_uri))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(moderator_list.status(), StatusCode::OK);
+    assert_eq!(user_list.status(), StatusCode::FORBIDDEN);
+    assert_eq!(moderator_delete.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_strict_query_is_opt_in_per_route() {
+    // Arrange: the admin API key listing still uses the lenient extractor
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let response = app
+        .oneshot(admin_request("/api/v1/admin/api-keys?limt=10"))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+fn role_change_request(user_id: Uuid, role: &str) -> Request<Body> {
+    let mut request = admin_request(&format!("/users/{}/role", user_id));
+    *request.method_mut() = axum::http::Method::PATCH;
+    request.headers_mut().insert(
+        "content-type",
+        axum::http::HeaderValue::from_static("application/json"),
+    );
+    *request.body_mut() = Body::from(json!({ "role": role }).to_string());
+    request
+}
+
+#[tokio::test]
+async fn test_admin_role_change_rejects_invalid_role() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    let app = create_test_app(pool.clone()).await;
+
+    // Act
+    let response = app
+        .oneshot(role_change_request(user_id, "root"))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"]["code"], "INVALID_ROLE");
+    assert_eq!(
+        json["error"]["details"]["valid_roles"],
+        json!(["user", "admin", "moderator"])
+    );
+    let role: String = sqlx::query_scalar("SELECT role FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(role, "user");
+}
+
+#[tokio::test]
+async fn test_admin_role_change_accepts_known_role_case_insensitively() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let response = app
+        .oneshot(role_change_request(user_id, "Moderator"))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["data"]["role"], "moderator");
+}
+
+async fn role_of(pool: &PgPool, user_id: Uuid) -> String {
+    sqlx::query_scalar("SELECT role FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_admin_role_change_keeps_last_admin() {
+    // Arrange: a schema of its own, so this admin really is the only one
+    let pool = common::create_isolated_test_db_pool().await;
+    let admin = insert_named_user(&pool, "only-admin@example.com", "Admin", UserRole::Admin).await;
+    let app = create_test_app(pool.clone()).await;
+
+    // Act
+    let response = app
+        .oneshot(role_change_request(admin, "user"))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"]["code"], "CONFLICT");
+    assert_eq!(role_of(&pool, admin).await, "admin");
+    common::drop_isolated_schema(pool).await;
+}
+
+#[tokio::test]
+async fn test_admin_role_change_demotes_admin_while_another_remains() {
+    // Arrange
+    let pool = common::create_isolated_test_db_pool().await;
+    let first = insert_named_user(&pool, "first@example.com", "First", UserRole::Admin).await;
+    insert_named_user(&pool, "second@example.com", "Second", UserRole::Admin).await;
+    let app = create_test_app(pool.clone()).await;
+
+    // Act
+    let response = app
+        .oneshot(role_change_request(first, "moderator"))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(role_of(&pool, first).await, "moderator");
+    common::drop_isolated_schema(pool).await;
+}
+
+#[tokio::test]
+async fn test_admin_cannot_change_own_role() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let admin = insert_named_user(
+        &pool,
+        &format!("self_{}@example.com", Uuid::new_v4().simple()),
+        "Admin",
+        UserRole::Admin,
+    )
+    .await;
+    let app = create_test_app(pool.clone()).await;
+    let token = generate_access_token(
+        &admin,
+        "self@example.com",
+        UserRole::Admin,
+        &app::create_test_jwt_config(),
+    )
+    .unwrap();
+    let mut request = role_change_request(admin, "user");
+    request.headers_mut().insert(
+        "authorization",
+        format!("Bearer {}", token).parse().unwrap(),
+    );
+
+    // Act
+    let response = app.oneshot(request).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(role_of(&pool, admin).await, "admin");
+}
+
+#[test]
+fn test_user_role_parse_lists_valid_roles() {
+    assert_eq!(UserRole::parse(" ADMIN ").unwrap(), UserRole::Admin);
+
+    match UserRole::parse("owner") {
+        Err(AppError::InvalidRole { role, valid_roles }) => {
+            assert_eq!(role, "owner");
+            assert_eq!(valid_roles, vec!["user", "admin", "moderator"]);
+        }
+        other => panic!("expected InvalidRole, got {:?}", other),
+    }
+}
+
+async fn patch_own_name(app: Router, user_id: Uuid, name: &str) -> (StatusCode, serde_json::Value) {
+    let token = generate_access_token(
+        &user_id,
+        "user@example.com",
+        UserRole::User,
+        &app::create_test_jwt_config(),
+    )
+    .unwrap();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri("/users/me")
+                .header("authorization", format!("Bearer {}", token))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "name": name }).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_profile_update_enforces_name_policy() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let user_id = insert_user(&pool).await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let (too_long, too_long_body) = patch_own_name(app.clone(), user_id, &"B".repeat(101)).await;
+    let (control, control_body) = patch_own_name(app.clone(), user_id, "Bob\u{0000}").await;
+    let (valid, valid_body) = patch_own_name(app, user_id, "Bob O'Brien").await;
+
+    // Assert
+    assert_eq!(too_long, StatusCode::BAD_REQUEST);
+    assert_eq!(too_long_body["error"]["details"]["rule"], "max_length");
+    assert_eq!(control, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        control_body["error"]["details"]["rule"],
+        "control_characters"
+    );
+    assert_eq!(valid, StatusCode::OK);
+    assert_eq!(valid_body["data"]["name"], "Bob O'Brien");
+}
+
+#[tokio::test]
+async fn test_admin_cannot_suspend_self() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let admin = insert_named_user(
+        &pool,
+        &format!("self_{}@example.com", Uuid::new_v4().simple()),
+        "Admin",
+        UserRole::Admin,
+    )
+    .await;
+    let app = create_test_app(pool.clone()).await;
+    let token = generate_access_token(
+        &admin,
+        "self@example.com",
+        UserRole::Admin,
+        &app::create_test_jwt_config(),
+    )
+    .unwrap();
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/users/{}/suspend", admin))
+        .header("authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+
+    // Act
+    let response = app.oneshot(request).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let status: String = sqlx::query_scalar("SELECT status FROM users WHERE id = $1")
+        .bind(admin)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "active");
+}
+
+#[tokio::test]
+async fn test_moderator_cannot_suspend_users() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let target = insert_user(&pool).await;
+    let app = create_test_app(pool).await;
+
+    // Act
+    let response = app
+        .oneshot(request_as(
+            UserRole::Moderator,
+            "POST",
+            &format!("/users/{}/suspend", target),
+        ))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+/// Follow `next_cursor` from the first offset page until it runs out
+async fn walk_user_pages(app: Router, query: &str) -> Vec<String> {
+    let (status, first) = list_users_json(app.clone(), &format!("/users?{}", query)).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut ids = listed_ids(&first);
+    let mut cursor = first["pagination"]["next_cursor"].clone();
+
+    while let Some(after) = cursor.as_str() {
+        let (status, page) =
+            list_users_json(app.clone(), &format!("/users?{}&after={}", query, after)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(page["pagination"].get("offset").is_none());
+        ids.extend(listed_ids(&page));
+        cursor = page["pagination"]["next_cursor"].clone();
+    }
+
+    ids
+}
+
+#[tokio::test]
+async fn test_user_list_cursor_pages_have_no_duplicates_or_gaps() {
+    // Arrange: seven matching users, three of them created at the same instant
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let tag = Uuid::new_v4().simple().to_string();
+    let mut inserted = Vec::new();
+    for i in 0..7 {
+        let email = format!("cursor{}_{}@example.com", i, tag);
+        inserted.push(insert_named_user(&pool, &email, "Cursor", UserRole::User).await);
+    }
+    sqlx::query("UPDATE users SET created_at = NOW() - INTERVAL '1 hour' WHERE id = ANY($1)")
+        .bind(&inserted[2..5])
+        .execute(&pool)
+        .await
+        .unwrap();
+    let app = create_test_app(pool).await;
+    let mut expected: Vec<String> = inserted.iter().map(Uuid::to_string).collect();
+    expected.sort();
+
+    for order in ["asc", "desc"] {
+        // Act
+        let mut ids = walk_user_pages(
+            app.clone(),
+            &format!("q={}&sort_by=created_at&order={}&limit=3", tag, order),
+        )
+        .await;
+
+        // Assert: every user exactly once across the pages
+        assert_eq!(ids.len(), 7, "order={}", order);
+        ids.sort();
+        assert_eq!(ids, expected, "order={}", order);
+    }
+}
+
+#[tokio::test]
+async fn test_user_list_rejects_malformed_or_tampered_cursor() {
+    // Arrange
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let tag = Uuid::new_v4().simple().to_string();
+    for i in 0..2 {
+        let email = format!("tamper{}_{}@example.com", i, tag);
+        insert_named_user(&pool, &email, "Cursor", UserRole::User).await;
+    }
+    let app = create_test_app(pool).await;
+    let (_, first) = list_users_json(app.clone(), &format!("/users?q={}&limit=1", tag)).await;
+    let cursor = first["pagination"]["next_cursor"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let mut tampered = cursor.clone().into_bytes();
+    tampered[0] = if tampered[0] == b'A' { b'B' } else { b'A' };
+    let tampered = String::from_utf8(tampered).unwrap();
+
+    for after in ["not-a-cursor", tampered.as_str()] {
+        // Act
+        let (status, json) = list_users_json(
+            app.clone(),
+            &format!("/users?q={}&limit=1&after={}", tag, after),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(status, StatusCode::BAD_REQUEST, "after={}", after);
+        assert_eq!(json["error"]["code"], "BAD_REQUEST");
+    }
+
+    // Act: a cursor only orders by creation time
+    let (status, json) = list_users_json(
+        app,
+        &format!("/users?q={}&sort_by=email&after={}", tag, cursor),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["error"]["details"]["field"], "sort_by");
+}
@@ -0,0 +1,248 @@
+// Webhook integration tests
+// Validates event enqueueing, HMAC signing, and retrying deliveries
+
+mod common;
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Router,
+};
+use serde_json::json;
+use sqlx::PgPool;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use uuid::Uuid;
+use vibe_api::modules::{
+    auth::service::AuthService,
+    users::service::UserService,
+    webhooks::{
+        model::WebhookDelivery,
+        signature::{verify_signature, SIGNATURE_HEADER},
+        WebhookService, WebhookWorker,
+    },
+};
+
+use common::{app, create_test_db_pool, run_migrations};
+
+const SECRET: &str = "whsec_test_0123456789";
+
+/// Local endpoint recording webhook requests, failing the first `failures` of them
+#[derive(Clone, Default)]
+struct Receiver {
+    requests: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+    failures_left: Arc<AtomicUsize>,
+}
+
+async fn receive(State(receiver): State<Receiver>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    receiver.requests.lock().unwrap().push((headers, body));
+
+    let should_fail = receiver
+        .failures_left
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+        .is_ok();
+
+    if should_fail {
+        StatusCode::INTERNAL_SERVER_ERROR
+    } else {
+        StatusCode::OK
+    }
+}
+
+async fn spawn_receiver(failures: usize) -> (String, Receiver) {
+    let receiver = Receiver::default();
+    receiver.failures_left.store(failures, Ordering::SeqCst);
+
+    let app = Router::new()
+        .route("/hook", post(receive))
+        .with_state(receiver.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    (url, receiver)
+}
+
+async fn create_webhook(service: &WebhookService, url: &str, event: &str) -> Uuid {
+    let webhook = service
+        .create(
+            serde_json::from_value(json!({ "url": url, "secret": SECRET, "events": [event] }))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    Uuid::parse_str(&webhook.id).unwrap()
+}
+
+/// Run delivery rounds until none of the webhook's deliveries are pending
+async fn deliver_until_settled(
+    worker: &WebhookWorker,
+    service: &WebhookService,
+    webhook_id: &Uuid,
+) -> Vec<WebhookDelivery> {
+    for _ in 0..100 {
+        worker.deliver_due().await.unwrap();
+        let deliveries = service.list_deliveries(webhook_id).await.unwrap();
+        if deliveries.iter().all(|d| d.status != "pending") {
+            return deliveries;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("Webhook deliveries did not settle");
+}
+
+async fn setup() -> (PgPool, WebhookService) {
+    let pool = create_test_db_pool().await;
+    run_migrations(&pool).await;
+    let service = WebhookService::new(pool.clone());
+    (pool, service)
+}
+
+#[tokio::test]
+async fn test_user_created_enqueues_delivery() {
+    // Arrange
+    let (pool, service) = setup().await;
+    let webhook_id = create_webhook(&service, "http://127.0.0.1:9/hook", "user.created").await;
+    let email = format!("webhook_{}@example.com", Uuid::new_v4().simple());
+
+    // Act
+    AuthService::new(pool, app::create_test_jwt_config())
+        .register(
+            serde_json::from_value(json!({
+                "email": email,
+                "password": "SecurePass123!",
+                "name": "Webhook User"
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    let deliveries = service.list_deliveries(&webhook_id).await.unwrap();
+    let delivery = deliveries
+        .iter()
+        .find(|d| d.payload["data"]["email"] == email)
+        .expect("user.created delivery should be queued");
+    assert_eq!(delivery.event, "user.created");
+    assert_eq!(delivery.payload["event"], "user.created");
+    assert_eq!(delivery.attempts, 0);
+
+    service.delete(&webhook_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_delivery_is_hmac_signed() {
+    // Arrange
+    let (pool, service) = setup().await;
+    let (url, receiver) = spawn_receiver(0).await;
+    let webhook_id = create_webhook(&service, &url, "login.failed").await;
+    let worker = WebhookWorker::new(pool.clone()).with_retry_base(Duration::ZERO);
+    let email = format!("nobody_{}@example.com", Uuid::new_v4().simple());
+
+    let login = AuthService::new(pool, app::create_test_jwt_config())
+        .login(
+            serde_json::from_value(json!({ "email": email, "password": "WrongPass123!" })).unwrap(),
+        )
+        .await;
+    assert!(login.is_err());
+
+    // Act
+    let deliveries = deliver_until_settled(&worker, &service, &webhook_id).await;
+
+    // Assert
+    assert!(deliveries.iter().all(|d| d.status == "delivered"));
+    let requests = receiver.requests.lock().unwrap().clone();
+    let (headers, body) = requests
+        .iter()
+        .find(|(_, body)| {
+            serde_json::from_slice::<serde_json::Value>(body).unwrap()["data"]["email"] == email
+        })
+        .expect("login.failed event should be delivered");
+
+    let signature = headers.get(SIGNATURE_HEADER).unwrap().to_str().unwrap();
+    assert!(verify_signature(SECRET, body, signature));
+    assert!(!verify_signature(
+        "some-other-secret-value",
+        body,
+        signature
+    ));
+    assert_eq!(headers.get("x-webhook-event").unwrap(), "login.failed");
+
+    service.delete(&webhook_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_failing_endpoint_is_retried() {
+    // Arrange
+    let (pool, service) = setup().await;
+    let (url, receiver) = spawn_receiver(2).await;
+    let webhook_id = create_webhook(&service, &url, "user.deleted").await;
+    let worker = WebhookWorker::new(pool.clone()).with_retry_base(Duration::ZERO);
+
+    let user_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id, email, password_hash, name) VALUES ($1, $2, $3, $4)")
+        .bind(user_id)
+        .bind(format!("user_{}@example.com", user_id.simple()))
+        .bind("$argon2id$v=19$m=19456,t=2,p=1$test")
+        .bind("Doomed User")
+        .execute(&pool)
+        .await
+        .unwrap();
+    UserService::new(pool).delete(&user_id).await.unwrap();
+
+    // Act
+    let deliveries = deliver_until_settled(&worker, &service, &webhook_id).await;
+
+    // Assert
+    let delivery = deliveries
+        .iter()
+        .find(|d| d.payload["data"]["id"] == user_id.to_string())
+        .expect("user.deleted delivery should exist");
+    assert_eq!(delivery.status, "delivered");
+    assert_eq!(delivery.attempts, 3, "Two failures followed by a success");
+    assert_eq!(delivery.last_status_code, Some(200));
+    assert_eq!(receiver.requests.lock().unwrap().len(), 3);
+
+    service.delete(&webhook_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_delivery_gives_up_after_max_attempts() {
+    // Arrange
+    let (pool, service) = setup().await;
+    let (url, _receiver) = spawn_receiver(usize::MAX).await;
+    let webhook_id = create_webhook(&service, &url, "user.deleted").await;
+    let worker = WebhookWorker::new(pool.clone())
+        .with_retry_base(Duration::ZERO)
+        .with_max_attempts(2);
+
+    sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (id, webhook_id, event, payload)
+        VALUES ($1, $2, 'user.deleted', '{}')
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(webhook_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Act
+    let deliveries = deliver_until_settled(&worker, &service, &webhook_id).await;
+
+    // Assert
+    assert_eq!(deliveries[0].status, "failed");
+    assert_eq!(deliveries[0].attempts, 2);
+    assert_eq!(deliveries[0].last_status_code, Some(500));
+
+    service.delete(&webhook_id).await.unwrap();
+}
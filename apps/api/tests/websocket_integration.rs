@@ -3,10 +3,7 @@
 
 mod common;
 
-use common::{
-    create_test_db_pool, run_migrations, clean_test_db,
-    MockWsServer, MockWsConnection, WsMessage, create_ws_json_message,
-};
+use common::{create_ws_json_message, MockWsConnection, MockWsServer, WsMessage};
 use serde_json::json;
 
 // Note: These tests validate WebSocket patterns
@@ -107,7 +104,9 @@ async fn test_ws_ping_pong() {
     let connection_id = server.connect(None);
 
     // Act
-    server.send_message(&connection_id, WsMessage::Ping).unwrap();
+    server
+        .send_message(&connection_id, WsMessage::Ping)
+        .unwrap();
 
     // Assert
     let connection = server.get_connection(&connection_id).unwrap();
@@ -215,7 +214,10 @@ async fn test_ws_leave_room() {
     let connection_id = server.connect(Some("user1".to_string()));
     server.join_room(&connection_id, "general").unwrap();
 
-    assert!(server.get_connection(&connection_id).unwrap().is_in_room("general"));
+    assert!(server
+        .get_connection(&connection_id)
+        .unwrap()
+        .is_in_room("general"));
 
     // Act
     server.leave_room(&connection_id, "general").unwrap();
@@ -381,9 +383,7 @@ async fn test_ws_concurrent_connections() {
     let mut handles = Vec::new();
     for i in 0..20 {
         let server_clone = server.clone();
-        let handle = tokio::spawn(async move {
-            server_clone.connect(Some(format!("user{}", i)))
-        });
+        let handle = tokio::spawn(async move { server_clone.connect(Some(format!("user{}", i))) });
         handles.push(handle);
     }
 
@@ -416,9 +416,8 @@ async fn test_ws_concurrent_room_joins() {
     for conn_id in &connections {
         let server_clone = server.clone();
         let conn_id_clone = conn_id.clone();
-        let handle = tokio::spawn(async move {
-            server_clone.join_room(&conn_id_clone, "concurrent_room")
-        });
+        let handle =
+            tokio::spawn(async move { server_clone.join_room(&conn_id_clone, "concurrent_room") });
         handles.push(handle);
     }
 
@@ -564,54 +563,6 @@ async fn test_ws_broadcast_sender_also_receives() {
 // The following tests demonstrate patterns for testing actual WebSocket endpoints
 // They would need the full WebSocket module setup to run
 
-#[tokio::test]
-#[ignore] // Requires websocket feature and route setup
-async fn test_ws_connection_upgrade() {
-    // This test would:
-    // 1. GET /ws with Upgrade: websocket headers
-    // 2. Assert 101 Switching Protocols
-    // 3. Verify WebSocket handshake completion
-
-    let pool = create_test_db_pool().await;
-    run_migrations(&pool).await;
-    clean_test_db(&pool).await;
-
-    // Pattern established for future implementation
-    assert!(true);
-}
-
-#[tokio::test]
-#[ignore]
-async fn test_ws_authenticated_connection() {
-    // This test would:
-    // 1. Create user and get JWT
-    // 2. Connect to /ws with Authorization header
-    // 3. Verify user_id associated with connection
-
-    assert!(true);
-}
-
-#[tokio::test]
-#[ignore]
-async fn test_ws_unauthenticated_connection() {
-    // This test would:
-    // 1. Try to connect to /ws without JWT
-    // 2. Assert 401 UNAUTHORIZED or connection rejection
-
-    assert!(true);
-}
-
-#[tokio::test]
-#[ignore]
-async fn test_ws_heartbeat_timeout() {
-    // This test would:
-    // 1. Establish WebSocket connection
-    // 2. Stop sending ping responses
-    // 3. Verify connection closed after timeout (e.g., 60s)
-
-    assert!(true);
-}
-
 #[tokio::test]
 #[ignore]
 async fn test_ws_max_connections_per_user() {
@@ -678,3 +629,758 @@ async fn test_ws_persistent_rooms_after_disconnect() {
 
     assert!(true);
 }
+
+// ============================================================================
+// Real server tests (require the websocket feature)
+// ============================================================================
+
+#[cfg(feature = "websocket")]
+mod typing_indicator {
+    use axum::extract::ws::Message;
+    use std::time::Instant;
+    use tokio::sync::mpsc::{channel, Receiver};
+    use vibe_api::modules::websocket::{
+        connections::{ConnectionManager, OUTBOUND_QUEUE_CAPACITY, TYPING_THROTTLE},
+        model::{Connection, WebSocketMessage},
+    };
+
+    async fn join(manager: &ConnectionManager, id: &str, room: &str) -> Receiver<Message> {
+        let (tx, rx) = channel(OUTBOUND_QUEUE_CAPACITY);
+        let connection = Connection {
+            id: id.to_string(),
+            user_id: Some(format!("user-{}", id)),
+            role: None,
+            rooms: vec![],
+            last_typing: None,
+            last_seen: Instant::now(),
+        };
+        manager.add_connection(connection, tx).await;
+        manager.add_to_room(id, room.to_string()).await;
+        rx
+    }
+
+    fn typing_events(rx: &mut Receiver<Message>) -> Vec<WebSocketMessage> {
+        let mut events = Vec::new();
+        while let Ok(Message::Text(text)) = rx.try_recv() {
+            events.push(serde_json::from_str(&text).unwrap());
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn test_typing_event_reaches_other_room_members() {
+        // Arrange
+        let manager = ConnectionManager::new();
+        let _alice = join(&manager, "alice", "general").await;
+        let mut bob = join(&manager, "bob", "general").await;
+
+        // Act
+        let delivered = manager.broadcast_typing("alice", "general", true).await;
+
+        // Assert
+        assert!(delivered);
+        let events = typing_events(&mut bob);
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            WebSocketMessage::Typing {
+                room,
+                user_id,
+                is_typing,
+            } => {
+                assert_eq!(room, "general");
+                assert_eq!(user_id.as_deref(), Some("user-alice"));
+                assert!(is_typing);
+            }
+            other => panic!("Expected typing event, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_typing_event_not_echoed_to_sender() {
+        let manager = ConnectionManager::new();
+        let mut alice = join(&manager, "alice", "general").await;
+        let _bob = join(&manager, "bob", "general").await;
+
+        manager.broadcast_typing("alice", "general", true).await;
+
+        assert!(typing_events(&mut alice).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rapid_typing_events_are_throttled() {
+        // Arrange
+        let manager = ConnectionManager::new();
+        let _alice = join(&manager, "alice", "general").await;
+        let mut bob = join(&manager, "bob", "general").await;
+
+        // Act
+        for _ in 0..5 {
+            manager.broadcast_typing("alice", "general", true).await;
+        }
+        let stopped = manager.broadcast_typing("alice", "general", false).await;
+        tokio::time::sleep(TYPING_THROTTLE).await;
+        let resumed = manager.broadcast_typing("alice", "general", false).await;
+
+        // Assert
+        assert!(stopped, "A state change is never throttled");
+        assert!(
+            resumed,
+            "Events are allowed again after the throttle window"
+        );
+        assert_eq!(typing_events(&mut bob).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_typing_requires_room_membership() {
+        let manager = ConnectionManager::new();
+        let _alice = join(&manager, "alice", "general").await;
+        let mut bob = join(&manager, "bob", "random").await;
+
+        let delivered = manager.broadcast_typing("bob", "general", true).await;
+
+        assert!(!delivered);
+        assert!(typing_events(&mut bob).is_empty());
+    }
+}
+
+#[cfg(feature = "websocket")]
+mod resume {
+    use axum::extract::ws::Message;
+    use std::time::{Duration, Instant};
+    use tokio::sync::mpsc::{channel, Receiver};
+    use vibe_api::modules::websocket::{
+        connections::{ConnectionManager, OUTBOUND_QUEUE_CAPACITY},
+        model::Connection,
+    };
+
+    async fn connect(
+        manager: &ConnectionManager,
+        id: &str,
+        rooms: &[&str],
+    ) -> (String, Receiver<Message>) {
+        let (tx, rx) = channel(OUTBOUND_QUEUE_CAPACITY);
+        let connection = Connection {
+            id: id.to_string(),
+            user_id: Some(format!("user-{}", id)),
+            role: None,
+            rooms: vec![],
+            last_typing: None,
+            last_seen: Instant::now(),
+        };
+        manager.add_connection(connection, tx).await;
+        for room in rooms {
+            manager.add_to_room(id, room.to_string()).await;
+        }
+        let token = manager.issue_resume_token(id).await;
+        (token, rx)
+    }
+
+    fn text(content: &str) -> Message {
+        Message::Text(content.to_string().into())
+    }
+
+    fn texts(rx: &mut Receiver<Message>) -> Vec<String> {
+        let mut received = Vec::new();
+        while let Ok(Message::Text(text)) = rx.try_recv() {
+            received.push(text.to_string());
+        }
+        received
+    }
+
+    #[tokio::test]
+    async fn test_resume_restores_rooms_and_missed_messages() {
+        // Arrange
+        let manager = ConnectionManager::new();
+        let (token, _rx) = connect(&manager, "mobile", &["general", "random"]).await;
+        manager.disconnect("mobile").await;
+        manager.broadcast_to_room("general", text("missed 1")).await;
+        manager
+            .broadcast_to_room("other", text("not a member"))
+            .await;
+        manager.send_to_user("user-mobile", text("missed 2")).await;
+
+        // Act
+        let session = manager
+            .take_resumable(&token)
+            .await
+            .expect("Token is valid within the resume window");
+        let (tx, mut rx) = channel(OUTBOUND_QUEUE_CAPACITY);
+        manager.restore("mobile-2", session, tx).await;
+        manager.broadcast_to_room("random", text("live")).await;
+
+        // Assert
+        let (connection, _) = manager.get_connection("mobile-2").await.unwrap();
+        assert_eq!(connection.user_id.as_deref(), Some("user-mobile"));
+        assert_eq!(connection.rooms, vec!["general", "random"]);
+        assert_eq!(texts(&mut rx), vec!["missed 1", "missed 2", "live"]);
+    }
+
+    #[tokio::test]
+    async fn test_resume_token_is_single_use() {
+        let manager = ConnectionManager::new();
+        let (token, _rx) = connect(&manager, "mobile", &["general"]).await;
+        manager.disconnect("mobile").await;
+
+        assert!(manager.take_resumable(&token).await.is_some());
+        assert!(manager.take_resumable(&token).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_resume_token_requires_fresh_connection() {
+        // Arrange
+        let manager = ConnectionManager::with_resume_window(Duration::from_millis(20));
+        let (token, _rx) = connect(&manager, "mobile", &["general"]).await;
+        manager.disconnect("mobile").await;
+
+        // Act
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let session = manager.take_resumable(&token).await;
+
+        // Assert
+        assert!(session.is_none());
+        assert_eq!(manager.room_member_count("general").await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_resume_takes_over_connection_not_yet_dropped() {
+        // Arrange
+        let manager = ConnectionManager::new();
+        let (token, _rx) = connect(&manager, "mobile", &["general"]).await;
+
+        // Act
+        let session = manager.take_resumable(&token).await;
+
+        // Assert
+        assert_eq!(session.unwrap().rooms, vec!["general"]);
+        assert!(manager.get_connection("mobile").await.is_none());
+    }
+}
+
+#[cfg(feature = "websocket")]
+mod room_cap {
+    use axum::extract::ws::Message;
+    use std::time::Instant;
+    use tokio::sync::mpsc::{channel, Receiver};
+    use vibe_api::modules::websocket::{
+        connections::{ConnectionManager, OUTBOUND_QUEUE_CAPACITY},
+        handler::handle_ws_message,
+        model::{Connection, WebSocketMessage},
+    };
+
+    async fn connect(manager: &ConnectionManager, id: &str) -> Receiver<Message> {
+        let (tx, rx) = channel(OUTBOUND_QUEUE_CAPACITY);
+        let connection = Connection {
+            id: id.to_string(),
+            user_id: None,
+            role: None,
+            rooms: vec![],
+            last_typing: None,
+            last_seen: Instant::now(),
+        };
+        manager.add_connection(connection, tx).await;
+        rx
+    }
+
+    async fn send(manager: &ConnectionManager, id: &str, message: WebSocketMessage) {
+        handle_ws_message(message, manager, id).await.unwrap();
+    }
+
+    fn join(room: &str) -> WebSocketMessage {
+        WebSocketMessage::Join {
+            room: room.to_string(),
+        }
+    }
+
+    fn errors(rx: &mut Receiver<Message>) -> Vec<String> {
+        let mut errors = Vec::new();
+        while let Ok(Message::Text(text)) = rx.try_recv() {
+            if let Ok(WebSocketMessage::Error { message }) = serde_json::from_str(&text) {
+                errors.push(message);
+            }
+        }
+        errors
+    }
+
+    async fn rooms(manager: &ConnectionManager, id: &str) -> Vec<String> {
+        manager.get_connection(id).await.unwrap().0.rooms
+    }
+
+    #[tokio::test]
+    async fn test_joins_up_to_cap_are_allowed() {
+        // Arrange
+        let manager = ConnectionManager::new().with_max_rooms_per_connection(3);
+        let mut rx = connect(&manager, "client").await;
+
+        // Act
+        for room in ["a", "b", "c"] {
+            send(&manager, "client", join(room)).await;
+        }
+
+        // Assert
+        assert_eq!(rooms(&manager, "client").await, vec!["a", "b", "c"]);
+        assert!(errors(&mut rx).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_join_beyond_cap_is_rejected_with_error() {
+        // Arrange
+        let manager = ConnectionManager::new().with_max_rooms_per_connection(2);
+        let mut rx = connect(&manager, "client").await;
+        send(&manager, "client", join("a")).await;
+        send(&manager, "client", join("b")).await;
+        errors(&mut rx);
+
+        // Act
+        send(&manager, "client", join("c")).await;
+
+        // Assert
+        assert_eq!(rooms(&manager, "client").await, vec!["a", "b"]);
+        assert_eq!(manager.room_member_count("c").await, 0);
+        let rejections = errors(&mut rx);
+        assert_eq!(rejections.len(), 1);
+        assert!(
+            rejections[0].contains("at most 2 rooms"),
+            "{}",
+            rejections[0]
+        );
+
+        // Rejoining a room already held never counts against the cap
+        send(&manager, "client", join("a")).await;
+        assert!(errors(&mut rx).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_leaving_a_room_frees_a_slot() {
+        // Arrange
+        let manager = ConnectionManager::new().with_max_rooms_per_connection(2);
+        let mut rx = connect(&manager, "client").await;
+        send(&manager, "client", join("a")).await;
+        send(&manager, "client", join("b")).await;
+
+        // Act
+        send(
+            &manager,
+            "client",
+            WebSocketMessage::Leave {
+                room: "a".to_string(),
+            },
+        )
+        .await;
+        send(&manager, "client", join("c")).await;
+
+        // Assert
+        assert_eq!(rooms(&manager, "client").await, vec!["b", "c"]);
+        assert!(errors(&mut rx).is_empty());
+    }
+}
+
+#[cfg(feature = "websocket")]
+mod backpressure {
+    use axum::extract::ws::Message;
+    use std::time::{Duration, Instant};
+    use tokio::sync::mpsc::{channel, error::TryRecvError, Receiver};
+    use vibe_api::modules::websocket::{
+        connections::ConnectionManager,
+        handler::handle_ws_message,
+        model::{Connection, WebSocketMessage},
+    };
+
+    /// A client that joined `room` and never reads its socket
+    async fn slow_consumer(manager: &ConnectionManager, id: &str, room: &str) -> Receiver<Message> {
+        let (tx, rx) = channel(manager.outbound_capacity());
+        let connection = Connection {
+            id: id.to_string(),
+            user_id: Some(format!("user-{}", id)),
+            role: None,
+            rooms: vec![],
+            last_typing: None,
+            last_seen: Instant::now(),
+        };
+        manager.add_connection(connection, tx).await;
+        manager.add_to_room(id, room.to_string()).await;
+        manager.issue_resume_token(id).await;
+        rx
+    }
+
+    fn text(content: String) -> Message {
+        Message::Text(content.into())
+    }
+
+    #[tokio::test]
+    async fn test_slow_consumer_sheds_broadcasts_instead_of_buffering() {
+        // Arrange
+        let manager = ConnectionManager::new().with_outbound_capacity(4);
+        let mut rx = slow_consumer(&manager, "slow", "general").await;
+
+        // Act: publishing never waits on the stalled client
+        tokio::time::timeout(Duration::from_secs(1), async {
+            for i in 0..1000 {
+                manager
+                    .broadcast_to_room("general", text(format!("message {}", i)))
+                    .await;
+            }
+        })
+        .await
+        .expect("Broadcasting must not block on a slow consumer");
+
+        // Assert: only the queue's worth is held, oldest first
+        let mut queued = Vec::new();
+        while let Ok(Message::Text(message)) = rx.try_recv() {
+            queued.push(message.to_string());
+        }
+        assert_eq!(
+            queued,
+            vec!["message 0", "message 1", "message 2", "message 3"]
+        );
+        assert!(manager.get_connection("slow").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_slow_consumer_is_closed_when_a_reply_does_not_fit() {
+        // Arrange
+        let manager = ConnectionManager::new().with_outbound_capacity(2);
+        let mut rx = slow_consumer(&manager, "slow", "general").await;
+        for i in 0..2 {
+            manager
+                .broadcast_to_room("general", text(format!("message {}", i)))
+                .await;
+        }
+
+        // Act
+        handle_ws_message(WebSocketMessage::Ping, &manager, "slow")
+            .await
+            .unwrap();
+
+        // Assert: queued messages drain, then the queue reports closed
+        assert!(manager.get_connection("slow").await.is_none());
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_ok());
+        assert_eq!(rx.try_recv().unwrap_err(), TryRecvError::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn test_other_room_members_keep_receiving_while_one_lags() {
+        // Arrange
+        let manager = ConnectionManager::new().with_outbound_capacity(2);
+        let _slow = slow_consumer(&manager, "slow", "general").await;
+        let mut fast = slow_consumer(&manager, "fast", "general").await;
+
+        // Act
+        for i in 0..10 {
+            manager
+                .broadcast_to_room("general", text(format!("message {}", i)))
+                .await;
+            while fast.try_recv().is_ok() {}
+        }
+        manager
+            .broadcast_to_room("general", text("last".to_string()))
+            .await;
+
+        // Assert
+        match fast.try_recv() {
+            Ok(Message::Text(message)) => assert_eq!(message.as_str(), "last"),
+            other => panic!("Expected the latest broadcast, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(feature = "websocket")]
+mod connection_upgrade {
+    use super::common;
+    use serde_json::Value;
+    use std::{net::SocketAddr, time::Duration};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+    };
+    use uuid::Uuid;
+    use vibe_api::modules::{
+        auth::jwt::generate_access_token,
+        users::model::UserRole,
+        websocket::{self, connections::ConnectionManager},
+    };
+
+    /// Key and matching accept value from RFC 6455 section 1.3
+    const KEY: &str = "dGhlIHNhbXBsZSBub25jZQ==";
+    const ACCEPT: &str = "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=";
+
+    async fn spawn_server(manager: ConnectionManager, allow_anonymous: bool) -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = websocket::routes(
+            manager,
+            common::app::create_test_jwt_config(),
+            allow_anonymous,
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        addr
+    }
+
+    fn access_token(user_id: Uuid) -> String {
+        generate_access_token(
+            &user_id,
+            "ws@example.com",
+            UserRole::User,
+            &common::app::create_test_jwt_config(),
+        )
+        .unwrap()
+    }
+
+    /// Send the upgrade request for `target` with any `extra_headers`;
+    /// returns the socket and the response head
+    async fn handshake(addr: SocketAddr, target: &str, extra_headers: &str) -> (TcpStream, String) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n{}\r\n",
+            target, addr, KEY, extra_headers
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        (stream, String::from_utf8(head).unwrap())
+    }
+
+    const TEXT: u8 = 0x1;
+    const PING: u8 = 0x9;
+    const PONG: u8 = 0xa;
+
+    /// Read one unfragmented frame sent by the server: opcode and payload
+    async fn read_frame(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let opcode = stream.read_u8().await.unwrap() & 0x0f;
+        let len = match stream.read_u8().await.unwrap() & 0x7f {
+            126 => stream.read_u16().await.unwrap() as usize,
+            127 => stream.read_u64().await.unwrap() as usize,
+            len => len as usize,
+        };
+        let mut payload = vec![0; len];
+        stream.read_exact(&mut payload).await.unwrap();
+        (opcode, payload)
+    }
+
+    async fn read_text(stream: &mut TcpStream) -> Value {
+        let (opcode, payload) = read_frame(stream).await;
+        assert_eq!(opcode, TEXT, "Expected a text frame");
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    /// Write a masked frame, as clients must
+    async fn write_frame(stream: &mut TcpStream, opcode: u8, payload: &[u8]) {
+        assert!(payload.len() < 126);
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![0x80 | opcode, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        stream.write_all(&frame).await.unwrap();
+    }
+
+    async fn write_text(stream: &mut TcpStream, message: Value) {
+        write_frame(stream, TEXT, message.to_string().as_bytes()).await;
+    }
+
+    async fn wait_for(manager: &ConnectionManager, connections: usize) {
+        tokio::time::timeout(Duration::from_secs(2), async {
+            while manager.connection_count().await != connections {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Connection count never settled");
+    }
+
+    #[tokio::test]
+    async fn test_ws_connection_upgrade() {
+        // Arrange
+        let manager = ConnectionManager::new();
+        let addr = spawn_server(manager.clone(), false).await;
+        let user_id = Uuid::new_v4();
+        let auth = format!("Authorization: Bearer {}\r\n", access_token(user_id));
+
+        // Act
+        let (mut stream, head) = handshake(addr, "/ws", &auth).await;
+
+        // Assert
+        assert!(
+            head.starts_with("HTTP/1.1 101"),
+            "Unexpected response: {}",
+            head
+        );
+        assert!(head.to_ascii_lowercase().contains(&format!(
+            "sec-websocket-accept: {}",
+            ACCEPT.to_ascii_lowercase()
+        )));
+
+        let session = read_text(&mut stream).await;
+        assert_eq!(session["type"], "session");
+        assert_eq!(session["resumed"], false);
+        assert_eq!(session["user_id"], user_id.to_string());
+        wait_for(&manager, 1).await;
+    }
+
+    #[tokio::test]
+    async fn test_ws_query_token_identifies_user() {
+        let manager = ConnectionManager::new();
+        let addr = spawn_server(manager.clone(), false).await;
+        let user_id = Uuid::new_v4();
+
+        let target = format!("/ws?token={}", access_token(user_id));
+        let (mut stream, head) = handshake(addr, &target, "").await;
+
+        assert!(
+            head.starts_with("HTTP/1.1 101"),
+            "Unexpected response: {}",
+            head
+        );
+        assert_eq!(read_text(&mut stream).await["user_id"], user_id.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_ws_invalid_token_rejected_before_upgrade() {
+        // Arrange: an invalid token is refused even when anonymous is allowed
+        let manager = ConnectionManager::new();
+        let addr = spawn_server(manager.clone(), true).await;
+
+        // Act
+        let (_stream, head) = handshake(addr, "/ws?token=not-a-jwt", "").await;
+
+        // Assert
+        assert!(
+            head.starts_with("HTTP/1.1 401"),
+            "Unexpected response: {}",
+            head
+        );
+        assert_eq!(manager.connection_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ws_missing_token_rejected_unless_anonymous_allowed() {
+        let closed = spawn_server(ConnectionManager::new(), false).await;
+        let (_stream, head) = handshake(closed, "/ws", "").await;
+        assert!(
+            head.starts_with("HTTP/1.1 401"),
+            "Unexpected response: {}",
+            head
+        );
+
+        let open = spawn_server(ConnectionManager::new(), true).await;
+        let (mut stream, head) = handshake(open, "/ws", "").await;
+        assert!(
+            head.starts_with("HTTP/1.1 101"),
+            "Unexpected response: {}",
+            head
+        );
+        assert_eq!(read_text(&mut stream).await["user_id"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_ws_real_socket_ping_and_rooms() {
+        // Arrange
+        let manager = ConnectionManager::new();
+        let addr = spawn_server(manager.clone(), false).await;
+        let target = format!("/ws?token={}", access_token(Uuid::new_v4()));
+        let (mut stream, _) = handshake(addr, &target, "").await;
+        read_text(&mut stream).await;
+
+        // Act & Assert: ping is answered
+        write_text(&mut stream, serde_json::json!({ "type": "ping" })).await;
+        assert_eq!(read_text(&mut stream).await["type"], "pong");
+
+        // Joining notifies the room, including the new member
+        write_text(
+            &mut stream,
+            serde_json::json!({ "type": "join", "room": "general" }),
+        )
+        .await;
+        let joined = read_text(&mut stream).await;
+        assert_eq!(joined["content"], "User joined room: general");
+        assert_eq!(manager.room_member_count("general").await, 1);
+
+        write_text(
+            &mut stream,
+            serde_json::json!({ "type": "broadcast", "room": "general", "content": "hi" }),
+        )
+        .await;
+        assert_eq!(read_text(&mut stream).await["content"], "hi");
+    }
+
+    #[tokio::test]
+    async fn test_ws_dropped_socket_leaves_rooms() {
+        // Arrange
+        let manager = ConnectionManager::new();
+        let addr = spawn_server(manager.clone(), false).await;
+        let target = format!("/ws?token={}", access_token(Uuid::new_v4()));
+        let (mut stream, _) = handshake(addr, &target, "").await;
+        read_text(&mut stream).await;
+        write_text(
+            &mut stream,
+            serde_json::json!({ "type": "join", "room": "general" }),
+        )
+        .await;
+        read_text(&mut stream).await;
+
+        // Act
+        drop(stream);
+
+        // Assert
+        wait_for(&manager, 0).await;
+        assert_eq!(manager.room_member_count("general").await, 0);
+    }
+
+    /// Pings every 20ms, disconnects after 100ms of silence
+    fn heartbeat_manager() -> ConnectionManager {
+        ConnectionManager::new()
+            .with_heartbeat(Duration::from_millis(20), Duration::from_millis(100))
+    }
+
+    #[tokio::test]
+    async fn test_ws_missed_heartbeat_disconnects() {
+        // Arrange
+        let manager = heartbeat_manager();
+        let addr = spawn_server(manager.clone(), false).await;
+        let target = format!("/ws?token={}", access_token(Uuid::new_v4()));
+        let (mut stream, _) = handshake(addr, &target, "").await;
+        read_text(&mut stream).await;
+        write_text(
+            &mut stream,
+            serde_json::json!({ "type": "join", "room": "general" }),
+        )
+        .await;
+        read_text(&mut stream).await;
+
+        // Act: the server pings, the client never answers
+        assert_eq!(read_frame(&mut stream).await.0, PING);
+
+        // Assert
+        wait_for(&manager, 0).await;
+        assert_eq!(manager.room_member_count("general").await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ws_pongs_keep_connection_alive() {
+        // Arrange
+        let manager = heartbeat_manager();
+        let addr = spawn_server(manager.clone(), false).await;
+        let target = format!("/ws?token={}", access_token(Uuid::new_v4()));
+        let (mut stream, _) = handshake(addr, &target, "").await;
+        read_text(&mut stream).await;
+
+        // Act: answer every ping for several idle timeouts
+        let mut pongs = 0;
+        let _ = tokio::time::timeout(Duration::from_millis(400), async {
+            loop {
+                let (opcode, payload) = read_frame(&mut stream).await;
+                if opcode == PING {
+                    write_frame(&mut stream, PONG, &payload).await;
+                    pongs += 1;
+                }
+            }
+        })
+        .await;
+
+        // Assert
+        assert!(pongs > 5, "Expected regular pings, got {}", pongs);
+        assert_eq!(manager.connection_count().await, 1);
+    }
+}